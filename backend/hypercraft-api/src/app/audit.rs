@@ -0,0 +1,217 @@
+//! 管理操作审计日志：每条记录一行 JSON，追加写入 `<data_dir>/audit.jsonl`。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::io::ErrorKind;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use tokio::fs::OpenOptions;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+use super::middleware::{current_request_id, AuthInfo};
+
+/// 审计日志文件名（位于数据目录下）
+pub const AUDIT_FILE: &str = "audit.jsonl";
+
+/// 单条审计记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub ts: DateTime<Utc>,
+    /// 操作者的用户 ID（JWT sub）
+    pub actor_sub: String,
+    /// 操作类型，如 `user.create`、`service.delete`
+    pub action: String,
+    /// 操作对象（用户 ID、服务 ID 等）
+    pub target: String,
+    pub ip: String,
+    /// 成功为 `ok`，失败为错误信息
+    pub result: String,
+    /// DevToken 会话通过 `X-Admin-Identity` 声明的操作者
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_identity: Option<String>,
+    /// 触发该操作的请求 ID，可与访问日志对应
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+/// 只追加的审计日志写入器
+#[derive(Debug)]
+pub struct AuditLog {
+    path: PathBuf,
+    /// 串行化写入，避免并发追加交错成半行
+    lock: Mutex<()>,
+}
+
+impl AuditLog {
+    pub fn new(data_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            path: data_dir.into().join(AUDIT_FILE),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// 记录一次操作及其结果。
+    pub async fn record<T, E: Display>(
+        &self,
+        auth: &AuthInfo,
+        addr: SocketAddr,
+        action: &str,
+        target: &str,
+        result: &Result<T, E>,
+    ) {
+        let entry = AuditEntry {
+            ts: Utc::now(),
+            actor_sub: auth.claims.sub.clone(),
+            action: action.to_string(),
+            target: target.to_string(),
+            ip: addr.ip().to_string(),
+            result: match result {
+                Ok(_) => "ok".to_string(),
+                Err(e) => e.to_string(),
+            },
+            admin_identity: auth.admin_identity.clone(),
+            request_id: current_request_id(),
+        };
+        self.write(entry).await;
+    }
+
+    /// 记录未经认证的安全事件（如 refresh token 重放），`actor_sub` 为受影响的用户。
+    pub async fn record_security_event(
+        &self,
+        actor_sub: &str,
+        addr: SocketAddr,
+        action: &str,
+        target: &str,
+        result: &str,
+    ) {
+        let entry = AuditEntry {
+            ts: Utc::now(),
+            actor_sub: actor_sub.to_string(),
+            action: action.to_string(),
+            target: target.to_string(),
+            ip: addr.ip().to_string(),
+            result: result.to_string(),
+            admin_identity: None,
+            request_id: current_request_id(),
+        };
+        self.write(entry).await;
+    }
+
+    /// 写入失败只打 error 日志，不影响已经完成的业务操作。
+    async fn write(&self, entry: AuditEntry) {
+        if let Err(e) = self.append(&entry).await {
+            tracing::error!(
+                action = %entry.action,
+                target = %entry.target,
+                actor = %entry.actor_sub,
+                error = %e,
+                "写入审计日志失败"
+            );
+        }
+    }
+
+    async fn append(&self, entry: &AuditEntry) -> std::io::Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        let _guard = self.lock.lock().await;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(&line).await?;
+        file.flush().await
+    }
+
+    /// 读取 `since` 之后（含）的最近 `limit` 条记录，按时间正序返回。
+    ///
+    /// 无法解析的行（如写入中断留下的半行）直接跳过。
+    pub async fn read(
+        &self,
+        since: Option<DateTime<Utc>>,
+        limit: usize,
+    ) -> std::io::Result<Vec<AuditEntry>> {
+        let content = {
+            let _guard = self.lock.lock().await;
+            match tokio::fs::read_to_string(&self.path).await {
+                Ok(content) => content,
+                Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+                Err(e) => return Err(e),
+            }
+        };
+        let mut entries: Vec<AuditEntry> = content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .filter(|entry: &AuditEntry| since.is_none_or(|since| entry.ts >= since))
+            .collect();
+        let skip = entries.len().saturating_sub(limit);
+        Ok(entries.split_off(skip))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hypercraft_core::{TokenClaims, TokenType};
+
+    fn admin() -> AuthInfo {
+        AuthInfo::from_claims(TokenClaims {
+            sub: "admin-1".into(),
+            username: "root".into(),
+            iss: None,
+            aud: None,
+            token_type: TokenType::User,
+            service_ids: Vec::new(),
+            is_admin: true,
+            token_version: 0,
+            refresh_nonce: None,
+            service_id: None,
+            session_id: None,
+            exp: 0,
+            iat: 0,
+        })
+    }
+
+    #[tokio::test]
+    async fn record_appends_and_read_returns_latest_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path());
+        let auth = admin();
+        let addr: SocketAddr = "10.0.0.7:4242".parse().unwrap();
+
+        let ok: Result<(), String> = Ok(());
+        let failed: Result<(), String> = Err("u2 not found".into());
+        log.record(&auth, addr, "user.create", "alice", &ok).await;
+        log.record(&auth, addr, "user.delete", "u2", &failed).await;
+        log.record(&auth, addr, "service.delete", "mc", &ok).await;
+
+        let mut shared = AuthInfo::from_claims(auth.claims.clone());
+        shared.admin_identity = Some("alice".into());
+        log.record(&shared, addr, "service.start", "mc", &ok).await;
+
+        let all = log.read(None, 100).await.unwrap();
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[0].admin_identity, None);
+        assert_eq!(all[3].admin_identity.as_deref(), Some("alice"));
+        assert_eq!(all[0].action, "user.create");
+        assert_eq!(all[0].actor_sub, "admin-1");
+        assert_eq!(all[0].ip, "10.0.0.7");
+        assert_eq!(all[1].result, "u2 not found");
+
+        let latest = log.read(None, 2).await.unwrap();
+        let actions: Vec<_> = latest.iter().map(|e| e.action.as_str()).collect();
+        assert_eq!(actions, ["service.delete", "service.start"]);
+
+        let future = Utc::now() + chrono::Duration::hours(1);
+        assert!(log.read(Some(future), 100).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn read_without_log_file_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path());
+        assert!(log.read(None, 10).await.unwrap().is_empty());
+    }
+}
@@ -1,44 +1,198 @@
-use axum::http::StatusCode;
+use axum::http::{header, HeaderValue, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::Json;
-use hypercraft_core::ServiceError;
+use hypercraft_core::{PolicyReason, ServiceError};
+use serde::{Serialize, Serializer};
 use serde_json::json;
+use std::fmt;
+use std::time::Duration;
+
+use super::middleware::current_request_id;
+use super::rate_limit::{RATE_LIMIT_REMAINING_HEADER, RATE_LIMIT_RESET_HEADER};
+
+/// 错误响应体中的机器可读错误码（`code` 字段）
+///
+/// 取值是对外契约：脚本与客户端按它分支，只能新增，不能改名。
+/// 完整列表与对应的 HTTP 状态见 README「错误码」一节。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    BadRequest,
+    /// 请求参数或字段取值不合法（名称为空、scope 未知、manifest 版本过新等）
+    InvalidInput,
+    InvalidId,
+    InvalidSchedule,
+    InvalidPattern,
+    InvalidGroup,
+    InvalidPath,
+    /// 请求体不是合法 JSON 或字段类型不符
+    SerdeError,
+    PolicyViolation,
+    UnsupportedSignal,
+    Unauthorized,
+    /// 需要双因素认证码（历史取值 `2FA_REQUIRED`）
+    TwoFactorRequired,
+    RefreshTokenReused,
+    Forbidden,
+    CommandNotAllowed,
+    PathNotAllowed,
+    NotFound,
+    MethodNotAllowed,
+    StartTimeout,
+    /// 与当前状态冲突（如已撤销的 API Key 不能再修改）
+    Conflict,
+    AlreadyExists,
+    AlreadyRunning,
+    NotRunning,
+    NotOwned,
+    PayloadTooLarge,
+    UnsupportedMediaType,
+    NoTimestampedLogs,
+    TooManyRequests,
+    Internal,
+    SpawnFailed,
+    IoError,
+    ServiceUnavailable,
+    StorageUnavailable,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::BadRequest => "BadRequest",
+            ErrorCode::InvalidInput => "InvalidInput",
+            ErrorCode::InvalidId => "InvalidId",
+            ErrorCode::InvalidSchedule => "InvalidSchedule",
+            ErrorCode::InvalidPattern => "InvalidPattern",
+            ErrorCode::InvalidGroup => "InvalidGroup",
+            ErrorCode::InvalidPath => "InvalidPath",
+            ErrorCode::SerdeError => "SerdeError",
+            ErrorCode::PolicyViolation => "PolicyViolation",
+            ErrorCode::UnsupportedSignal => "UnsupportedSignal",
+            ErrorCode::Unauthorized => "Unauthorized",
+            ErrorCode::TwoFactorRequired => "2FA_REQUIRED",
+            ErrorCode::RefreshTokenReused => "RefreshTokenReused",
+            ErrorCode::Forbidden => "Forbidden",
+            ErrorCode::CommandNotAllowed => "CommandNotAllowed",
+            ErrorCode::PathNotAllowed => "PathNotAllowed",
+            ErrorCode::NotFound => "NotFound",
+            ErrorCode::MethodNotAllowed => "MethodNotAllowed",
+            ErrorCode::StartTimeout => "StartTimeout",
+            ErrorCode::Conflict => "Conflict",
+            ErrorCode::AlreadyExists => "AlreadyExists",
+            ErrorCode::AlreadyRunning => "AlreadyRunning",
+            ErrorCode::NotRunning => "NotRunning",
+            ErrorCode::NotOwned => "NotOwned",
+            ErrorCode::PayloadTooLarge => "PayloadTooLarge",
+            ErrorCode::UnsupportedMediaType => "UnsupportedMediaType",
+            ErrorCode::NoTimestampedLogs => "NoTimestampedLogs",
+            ErrorCode::TooManyRequests => "TooManyRequests",
+            ErrorCode::Internal => "Internal",
+            ErrorCode::SpawnFailed => "SpawnFailed",
+            ErrorCode::IoError => "IoError",
+            ErrorCode::ServiceUnavailable => "ServiceUnavailable",
+            ErrorCode::StorageUnavailable => "StorageUnavailable",
+        }
+    }
+
+    /// 按 HTTP 状态选取通用错误码，用于框架层拒绝（请求体解析失败、方法不允许等）
+    pub fn from_status(status: StatusCode) -> Self {
+        match status {
+            StatusCode::UNAUTHORIZED => ErrorCode::Unauthorized,
+            StatusCode::FORBIDDEN => ErrorCode::Forbidden,
+            StatusCode::NOT_FOUND => ErrorCode::NotFound,
+            StatusCode::METHOD_NOT_ALLOWED => ErrorCode::MethodNotAllowed,
+            StatusCode::CONFLICT => ErrorCode::Conflict,
+            StatusCode::PAYLOAD_TOO_LARGE => ErrorCode::PayloadTooLarge,
+            StatusCode::UNSUPPORTED_MEDIA_TYPE => ErrorCode::UnsupportedMediaType,
+            // axum 的 Json 提取器在字段类型不符时返回 422
+            StatusCode::UNPROCESSABLE_ENTITY => ErrorCode::SerdeError,
+            StatusCode::TOO_MANY_REQUESTS => ErrorCode::TooManyRequests,
+            StatusCode::SERVICE_UNAVAILABLE => ErrorCode::ServiceUnavailable,
+            status if status.is_server_error() => ErrorCode::Internal,
+            _ => ErrorCode::BadRequest,
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for ErrorCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
 
 #[derive(Debug)]
 pub struct ApiError {
-    code: &'static str,
+    code: ErrorCode,
     message: String,
     status: StatusCode,
+    /// 附加的结构化信息（如策略拒绝时允许的命令/目录），序列化为 `details`
+    details: Option<serde_json::Value>,
+    /// 限流时距离下一次可用的等待时间，转为 `Retry-After` 等响应头
+    retry_after: Option<Duration>,
 }
 
 impl ApiError {
-    pub fn new(code: &'static str, status: StatusCode, message: impl Into<String>) -> Self {
+    pub fn new(code: ErrorCode, status: StatusCode, message: impl Into<String>) -> Self {
         Self {
             code,
             status,
             message: message.into(),
+            details: None,
+            retry_after: None,
         }
     }
 
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    pub fn with_retry_after(mut self, wait: Duration) -> Self {
+        self.retry_after = Some(wait);
+        self
+    }
+
     pub fn unauthorized() -> Self {
-        Self::new("Unauthorized", StatusCode::UNAUTHORIZED, "unauthorized")
+        Self::new(
+            ErrorCode::Unauthorized,
+            StatusCode::UNAUTHORIZED,
+            "unauthorized",
+        )
     }
 
     pub fn unauthorized_with_message(message: impl Into<String>) -> Self {
-        Self::new("Unauthorized", StatusCode::UNAUTHORIZED, message)
+        Self::new(ErrorCode::Unauthorized, StatusCode::UNAUTHORIZED, message)
     }
 
     pub fn forbidden(message: impl Into<String>) -> Self {
-        Self::new("Forbidden", StatusCode::FORBIDDEN, message)
+        Self::new(ErrorCode::Forbidden, StatusCode::FORBIDDEN, message)
     }
 
     pub fn bad_request(message: impl Into<String>) -> Self {
-        Self::new("BadRequest", StatusCode::BAD_REQUEST, message)
+        Self::new(ErrorCode::BadRequest, StatusCode::BAD_REQUEST, message)
     }
 
     pub fn too_many_requests(message: impl Into<String>) -> Self {
         Self::new(
-            "TooManyRequests",
+            ErrorCode::TooManyRequests,
             StatusCode::TOO_MANY_REQUESTS,
             message,
         )
@@ -49,60 +203,298 @@ impl From<ServiceError> for ApiError {
     fn from(err: ServiceError) -> Self {
         match err {
             ServiceError::NotFound(id) => {
-                ApiError::new("NotFound", StatusCode::NOT_FOUND, format!("{id} not found"))
+                ApiError::new(ErrorCode::NotFound, StatusCode::NOT_FOUND, format!("{id} not found"))
             }
             ServiceError::AlreadyExists(id) => ApiError::new(
-                "AlreadyExists",
+                ErrorCode::AlreadyExists,
                 StatusCode::CONFLICT,
                 format!("{id} already exists"),
             ),
             ServiceError::AlreadyRunning(id) => ApiError::new(
-                "AlreadyRunning",
+                ErrorCode::AlreadyRunning,
                 StatusCode::CONFLICT,
                 format!("service {id} already running"),
             ),
             ServiceError::NotRunning(id) => ApiError::new(
-                "NotRunning",
+                ErrorCode::NotRunning,
                 StatusCode::CONFLICT,
                 format!("service {id} not running"),
             ),
+            ServiceError::StartTimeout(id, secs) => ApiError::new(
+                ErrorCode::StartTimeout,
+                StatusCode::REQUEST_TIMEOUT,
+                format!("service {id} did not start within {secs}s"),
+            ),
+            ServiceError::NotOwned(id) => ApiError::new(
+                ErrorCode::NotOwned,
+                StatusCode::CONFLICT,
+                format!(
+                    "service {id} is running but was not started by this API instance; \
+                     console input is unavailable until it is restarted"
+                ),
+            ),
             ServiceError::InvalidId => {
-                ApiError::new("InvalidId", StatusCode::BAD_REQUEST, "invalid id")
-            }
-            ServiceError::PolicyViolation(msg) => {
-                ApiError::new("PolicyViolation", StatusCode::BAD_REQUEST, msg)
+                ApiError::new(ErrorCode::InvalidId, StatusCode::BAD_REQUEST, "invalid id")
             }
+            ServiceError::PolicyViolation(reason) => policy_error(reason),
             ServiceError::InvalidSchedule(msg) => {
-                ApiError::new("InvalidSchedule", StatusCode::BAD_REQUEST, msg)
+                ApiError::new(ErrorCode::InvalidSchedule, StatusCode::BAD_REQUEST, msg)
+            }
+            ServiceError::InvalidPattern(msg) => {
+                ApiError::new(ErrorCode::InvalidPattern, StatusCode::BAD_REQUEST, msg)
+            }
+            ServiceError::InvalidGroup(msg) => {
+                ApiError::new(ErrorCode::InvalidGroup, StatusCode::BAD_REQUEST, msg)
+            }
+            ServiceError::InvalidInput(msg) => {
+                ApiError::new(ErrorCode::InvalidInput, StatusCode::BAD_REQUEST, msg)
+            }
+            ServiceError::Conflict(msg) => {
+                ApiError::new(ErrorCode::Conflict, StatusCode::CONFLICT, msg)
             }
+            ServiceError::NoTimestampedLogs(id) => ApiError::new(
+                ErrorCode::NoTimestampedLogs,
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("service {id} has no timestamped logs; since/until need lines that start with a timestamp"),
+            ),
+            ServiceError::UnsupportedSignal(signal) => ApiError::new(
+                ErrorCode::UnsupportedSignal,
+                StatusCode::BAD_REQUEST,
+                format!("signal {signal} is not supported on this platform"),
+            ),
             ServiceError::SpawnFailed(msg) => {
-                ApiError::new("SpawnFailed", StatusCode::INTERNAL_SERVER_ERROR, msg)
+                ApiError::new(ErrorCode::SpawnFailed, StatusCode::INTERNAL_SERVER_ERROR, msg)
+            }
+            ServiceError::StorageUnavailable(msg) => {
+                ApiError::new(ErrorCode::StorageUnavailable, StatusCode::INSUFFICIENT_STORAGE, msg)
             }
             ServiceError::Io(e) => {
-                ApiError::new("IoError", StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+                ApiError::new(ErrorCode::IoError, StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
             }
             ServiceError::Serde(e) => {
-                ApiError::new("SerdeError", StatusCode::BAD_REQUEST, e.to_string())
+                ApiError::new(ErrorCode::SerdeError, StatusCode::BAD_REQUEST, e.to_string())
             }
             ServiceError::Unauthorized(msg) => {
-                ApiError::new("Unauthorized", StatusCode::UNAUTHORIZED, msg)
+                ApiError::new(ErrorCode::Unauthorized, StatusCode::UNAUTHORIZED, msg)
             }
             ServiceError::TwoFactorRequired(msg) => {
-                ApiError::new("2FA_REQUIRED", StatusCode::UNAUTHORIZED, msg)
+                ApiError::new(ErrorCode::TwoFactorRequired, StatusCode::UNAUTHORIZED, msg)
             }
+            // 不回显用户 ID：调用方只需知道会话已全部失效
+            ServiceError::RefreshTokenReused(_) => ApiError::new(
+                ErrorCode::RefreshTokenReused,
+                StatusCode::UNAUTHORIZED,
+                "refresh token 已被使用过，该账号的全部会话已撤销，请重新登录",
+            ),
             ServiceError::Other(msg) => {
-                ApiError::new("Error", StatusCode::INTERNAL_SERVER_ERROR, msg)
+                ApiError::new(ErrorCode::Internal, StatusCode::INTERNAL_SERVER_ERROR, msg)
             }
         }
     }
 }
 
+/// 策略拒绝：命令/路径不在白名单时返回 403，并列出允许项便于修正
+fn policy_error(reason: PolicyReason) -> ApiError {
+    let message = reason.to_string();
+    match reason {
+        PolicyReason::Command { command, allowed } => {
+            ApiError::new(ErrorCode::CommandNotAllowed, StatusCode::FORBIDDEN, message)
+                .with_details(json!({
+                    "kind": "command",
+                    "command": command,
+                    "allowed": allowed,
+                }))
+        }
+        PolicyReason::PathRoot {
+            field,
+            path,
+            allowed_roots,
+        } => ApiError::new(ErrorCode::PathNotAllowed, StatusCode::FORBIDDEN, message).with_details(
+            json!({
+                "kind": field,
+                "path": path,
+                "allowed_roots": allowed_roots,
+            }),
+        ),
+        PolicyReason::InvalidPath {
+            field,
+            path,
+            problem,
+        } => ApiError::new(ErrorCode::InvalidPath, StatusCode::BAD_REQUEST, message).with_details(
+            json!({
+                "kind": field,
+                "path": path,
+                "problem": problem,
+            }),
+        ),
+        PolicyReason::Other(_) => {
+            ApiError::new(ErrorCode::PolicyViolation, StatusCode::BAD_REQUEST, message)
+        }
+    }
+}
+
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
-        let body = Json(json!({
+        let mut body = json!({
             "code": self.code,
             "message": self.message,
-        }));
-        (self.status, body).into_response()
+        });
+        if let Some(details) = self.details {
+            body["details"] = details;
+        }
+        // 便于用户报错时提供，与服务端日志中的 request_id 对应
+        if let Some(request_id) = current_request_id() {
+            body["request_id"] = json!(request_id);
+        }
+        let mut response = (self.status, Json(body)).into_response();
+        if let Some(wait) = self.retry_after {
+            // 向上取整到秒，至少 1 秒，避免客户端立即重试
+            let secs = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+            let secs = secs.max(1);
+            let reset = chrono::Utc::now().timestamp().max(0) as u64 + secs;
+            let headers = response.headers_mut();
+            headers.insert(header::RETRY_AFTER, HeaderValue::from(secs));
+            headers.insert(RATE_LIMIT_RESET_HEADER, HeaderValue::from(reset));
+            headers.insert(RATE_LIMIT_REMAINING_HEADER, HeaderValue::from(0u64));
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 由同一份列表生成数组与穷尽 match：`ErrorCode` 新增变体而这里漏列时无法编译
+    macro_rules! all_codes {
+        ($($code:ident),* $(,)?) => {{
+            fn _exhaustive(code: ErrorCode) {
+                match code {
+                    $(ErrorCode::$code)|* => {}
+                }
+            }
+            [$(ErrorCode::$code),*]
+        }};
+    }
+
+    fn all_codes() -> Vec<ErrorCode> {
+        all_codes![
+            BadRequest,
+            InvalidInput,
+            InvalidId,
+            InvalidSchedule,
+            InvalidPattern,
+            InvalidGroup,
+            InvalidPath,
+            SerdeError,
+            PolicyViolation,
+            UnsupportedSignal,
+            Unauthorized,
+            TwoFactorRequired,
+            RefreshTokenReused,
+            Forbidden,
+            CommandNotAllowed,
+            PathNotAllowed,
+            NotFound,
+            MethodNotAllowed,
+            StartTimeout,
+            Conflict,
+            AlreadyExists,
+            AlreadyRunning,
+            NotRunning,
+            NotOwned,
+            PayloadTooLarge,
+            UnsupportedMediaType,
+            NoTimestampedLogs,
+            TooManyRequests,
+            Internal,
+            SpawnFailed,
+            IoError,
+            ServiceUnavailable,
+            StorageUnavailable,
+        ]
+        .to_vec()
+    }
+
+    async fn body(err: ApiError) -> (StatusCode, serde_json::Value) {
+        let resp = err.into_response();
+        let status = resp.status();
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn command_rejection_lists_allowed_commands() {
+        let err = ServiceError::PolicyViolation(PolicyReason::Command {
+            command: "/tmp/java".into(),
+            allowed: vec!["java".into(), "node".into()],
+        });
+        let (status, json) = body(err.into()).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(json["code"], "CommandNotAllowed");
+        assert_eq!(json["details"]["command"], "/tmp/java");
+        assert_eq!(json["details"]["allowed"], json!(["java", "node"]));
+    }
+
+    #[tokio::test]
+    async fn rate_limited_response_carries_retry_headers() {
+        let err =
+            ApiError::too_many_requests("slow down").with_retry_after(Duration::from_millis(1500));
+        let resp = err.into_response();
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+        let headers = resp.headers();
+        assert_eq!(headers[header::RETRY_AFTER], "2");
+        assert_eq!(headers[RATE_LIMIT_REMAINING_HEADER], "0");
+        let reset: i64 = headers[RATE_LIMIT_RESET_HEADER]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(reset > chrono::Utc::now().timestamp());
+    }
+
+    #[tokio::test]
+    async fn full_disk_maps_to_insufficient_storage() {
+        let err = ServiceError::from(std::io::Error::from(std::io::ErrorKind::StorageFull));
+        let (status, json) = body(err.into()).await;
+        assert_eq!(status, StatusCode::INSUFFICIENT_STORAGE);
+        assert_eq!(json["code"], "StorageUnavailable");
+    }
+
+    #[test]
+    fn error_codes_are_unique_and_documented() {
+        let readme = include_str!("../../../../README.md");
+        let mut seen = std::collections::HashSet::new();
+        for code in all_codes() {
+            assert!(seen.insert(code.as_str()), "duplicate code {code}");
+            assert!(
+                readme.contains(&format!("`{code}`")),
+                "code {code} missing from README"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn client_input_errors_are_not_internal() {
+        let err = ServiceError::InvalidInput("scopes must not be empty".into());
+        let (status, json) = body(err.into()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(json["code"], "InvalidInput");
+
+        let err = ServiceError::Conflict("api key already revoked".into());
+        let (status, json) = body(err.into()).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(json["code"], "Conflict");
+    }
+
+    #[tokio::test]
+    async fn other_policy_violation_has_no_details() {
+        let err = ServiceError::PolicyViolation("dependency cycle: a -> a".into());
+        let (status, json) = body(err.into()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(json["code"], "PolicyViolation");
+        assert!(json.get("details").is_none());
     }
 }
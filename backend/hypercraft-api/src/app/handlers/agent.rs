@@ -1,24 +1,26 @@
 //! Agent 薄封装：复用 service / 分组运维能力，默认文本日志
 
-use axum::extract::{Path, Query, State};
+use axum::extract::{ConnectInfo, Path, Query, State};
 use axum::http::StatusCode;
 use axum::response::Response;
 use axum::Extension;
 use axum::Json;
-use hypercraft_core::{ServiceGroup, ServiceManifest, ServiceStatus, ServiceSummary};
+use hypercraft_core::{ServiceGroup, ServiceListQuery, ServiceManifest, ServiceStatus};
 use serde::Serialize;
 use serde_json::json;
+use std::net::SocketAddr;
 use tracing::instrument;
 
-use super::attach::attach_service;
+use super::attach::{attach_service, AttachQuery};
 use super::groups::{
     create_group, delete_group, list_groups, reorder_groups, update_group, CreateGroupRequest,
-    ReorderGroupsRequest, UpdateGroupRequest,
+    DeleteGroupQuery, ReorderGroupsRequest, UpdateGroupRequest,
 };
 use super::logs::{get_logs, LogQuery};
 use super::services::{
     create_service, delete_service, get_service, get_status, kill_service, list_services,
     restart_service, shutdown_service, start_service, stop_service, update_service,
+    DeleteServiceQuery, ServiceListResponse,
 };
 use super::super::error::ApiError;
 use super::super::middleware::{AuthInfo, ServicePermission};
@@ -66,7 +68,7 @@ pub async fn agent_help() -> Json<AgentHelp> {
                 method: "GET",
                 path: "/agent/services",
                 scope: Some("read"),
-                note: "可见服务列表",
+                note: "可见服务列表（?group=&state=&tag=&q=&sort=&limit=&offset=，总数见 X-Total-Count）",
             },
             AgentEndpoint {
                 method: "POST",
@@ -84,13 +86,13 @@ pub async fn agent_help() -> Json<AgentHelp> {
                 method: "PUT",
                 path: "/agent/services/:id",
                 scope: Some("manage"),
-                note: "更新服务定义（body=ServiceManifest）",
+                note: "创建或更新服务定义（body=ServiceManifest；新建返回 201，更新返回 200）",
             },
             AgentEndpoint {
                 method: "DELETE",
-                path: "/agent/services/:id",
+                path: "/agent/services/:id?purge=false&keep_logs=false",
                 scope: Some("manage"),
-                note: "删除服务",
+                note: "删除服务：默认归档到 data_dir/archive 可恢复；purge=true 永久删除（管理员），keep_logs=true 时保留日志",
             },
             AgentEndpoint {
                 method: "GET",
@@ -193,17 +195,19 @@ pub struct AgentEndpoint {
 pub async fn agent_list_services(
     state: State<AppState>,
     auth: Extension<AuthInfo>,
-) -> Result<Json<Vec<ServiceSummary>>, ApiError> {
-    list_services(state, auth).await
+    query: Query<ServiceListQuery>,
+) -> Result<ServiceListResponse, ApiError> {
+    list_services(state, auth, query).await
 }
 
 /// POST /agent/services — 创建服务
 pub async fn agent_create_service(
     state: State<AppState>,
+    addr: ConnectInfo<SocketAddr>,
     auth: Extension<AuthInfo>,
     body: Json<ServiceManifest>,
 ) -> Result<Json<ServiceManifest>, ApiError> {
-    create_service(state, auth, body).await
+    create_service(state, addr, auth, body).await
 }
 
 /// GET /agent/services/:id
@@ -214,23 +218,26 @@ pub async fn agent_get_service(
     get_service(state, perm).await
 }
 
-/// PUT /agent/services/:id — 更新服务定义
+/// PUT /agent/services/:id — 创建或更新服务定义（upsert）
 pub async fn agent_update_service(
     state: State<AppState>,
+    addr: ConnectInfo<SocketAddr>,
     auth: Extension<AuthInfo>,
     Path(id): Path<String>,
     body: Json<ServiceManifest>,
-) -> Result<StatusCode, ApiError> {
-    update_service(state, auth, Path(id), body).await
+) -> Result<(StatusCode, Json<ServiceManifest>), ApiError> {
+    update_service(state, addr, auth, Path(id), body).await
 }
 
-/// DELETE /agent/services/:id — 删除服务
+/// DELETE /agent/services/:id — 删除服务（默认归档，`purge=true` 永久删除）
 pub async fn agent_delete_service(
     state: State<AppState>,
+    addr: ConnectInfo<SocketAddr>,
     auth: Extension<AuthInfo>,
     Path(id): Path<String>,
+    query: Query<DeleteServiceQuery>,
 ) -> Result<StatusCode, ApiError> {
-    delete_service(state, auth, Path(id)).await
+    delete_service(state, addr, auth, Path(id), query).await
 }
 
 /// GET /agent/services/:id/status
@@ -304,9 +311,10 @@ pub async fn agent_attach(
     state: State<AppState>,
     auth: Extension<AuthInfo>,
     Path(id): Path<String>,
+    query: Query<AttachQuery>,
     ws: axum::extract::ws::WebSocketUpgrade,
-) -> Result<Response, ApiError> {
-    attach_service(state, auth, Path(id), ws).await
+) -> Response {
+    attach_service(state, auth, Path(id), query, ws).await
 }
 
 /// GET /agent/groups — 分组列表
@@ -341,8 +349,9 @@ pub async fn agent_delete_group(
     state: State<AppState>,
     auth: Extension<AuthInfo>,
     Path(id): Path<String>,
+    query: Query<DeleteGroupQuery>,
 ) -> Result<StatusCode, ApiError> {
-    delete_group(state, auth, Path(id)).await
+    delete_group(state, auth, Path(id), query).await
 }
 
 /// POST /agent/groups/reorder — 重排分组
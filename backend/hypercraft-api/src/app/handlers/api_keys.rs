@@ -1,16 +1,18 @@
-//! API Key 管理（仅超级管理员）
-//! API Key 可访问全部服务，因此管理端点仅向超级管理员开放。
+//! API Key 管理
+//! 全局 API Key 可访问全部服务，因此管理端点仅向超级管理员开放；
+//! 个人 API Key（`/users/:id/api-keys`）以所属用户身份鉴权，本人或管理员可管理。
 
-use axum::extract::{Path, State};
+use axum::extract::{ConnectInfo, Path, State};
 use axum::http::StatusCode;
-use axum::Json;
+use axum::{Extension, Json};
 use hypercraft_core::{
     ApiKeySecretResponse, ApiKeySummary, CreateApiKeyRequest, CreateApiKeyResponse,
-    UpdateApiKeyRequest,
+    CreateUserApiKeyRequest, UpdateApiKeyRequest,
 };
+use std::net::SocketAddr;
 
 use super::super::error::ApiError;
-use super::super::middleware::RequireSuperAdmin;
+use super::super::middleware::{AuthInfo, RequireSuperAdmin};
 use super::super::state::AppState;
 
 /// GET /api-keys - 列出当前管理员可见的 API Key
@@ -83,9 +85,76 @@ pub async fn rotate_api_key(
 /// DELETE /api-keys/:id - 撤销 API Key
 pub async fn revoke_api_key(
     State(state): State<AppState>,
-    RequireSuperAdmin(_): RequireSuperAdmin,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    RequireSuperAdmin(auth): RequireSuperAdmin,
     Path(id): Path<String>,
 ) -> Result<Json<ApiKeySummary>, ApiError> {
-    let key = state.user_manager.revoke_api_key(&id).await?;
+    let result = state.user_manager.revoke_api_key(&id).await;
+    state
+        .audit
+        .record(&auth, addr, "api_key.revoke", &id, &result)
+        .await;
+    let key = result?;
+    Ok(Json(key.into()))
+}
+
+/// 个人 API Key 仅本人或管理员可管理；API Key 身份不能再签发密钥
+fn ensure_can_manage_user_keys(auth: &AuthInfo, user_id: &str) -> Result<(), ApiError> {
+    if auth.via_api_key() {
+        return Err(ApiError::forbidden("API Key 不能管理 API Key"));
+    }
+    if user_id == "__devtoken__" {
+        return Err(ApiError::forbidden("不能为系统管理员账号创建个人密钥"));
+    }
+    if auth.claims.sub != user_id && !auth.is_admin() {
+        return Err(ApiError::forbidden("只能管理自己的 API Key"));
+    }
+    Ok(())
+}
+
+/// GET /users/:id/api-keys - 列出用户的个人 API Key
+pub async fn list_user_api_keys(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthInfo>,
+    Path(user_id): Path<String>,
+) -> Result<Json<Vec<ApiKeySummary>>, ApiError> {
+    ensure_can_manage_user_keys(&auth, &user_id)?;
+    let keys = state.user_manager.list_user_api_keys(&user_id).await?;
+    Ok(Json(keys.into_iter().map(Into::into).collect()))
+}
+
+/// POST /users/:id/api-keys - 创建个人 API Key（明文仅返回一次）
+pub async fn create_user_api_key(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthInfo>,
+    Path(user_id): Path<String>,
+    Json(req): Json<CreateUserApiKeyRequest>,
+) -> Result<(StatusCode, Json<CreateApiKeyResponse>), ApiError> {
+    ensure_can_manage_user_keys(&auth, &user_id)?;
+    let resp = state
+        .user_manager
+        .create_user_api_key(&user_id, req, &auth.claims.sub)
+        .await?;
+    Ok((StatusCode::CREATED, Json(resp)))
+}
+
+/// DELETE /users/:id/api-keys/:key_id - 撤销个人 API Key
+pub async fn revoke_user_api_key(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Extension(auth): Extension<AuthInfo>,
+    Path((user_id, key_id)): Path<(String, String)>,
+) -> Result<Json<ApiKeySummary>, ApiError> {
+    ensure_can_manage_user_keys(&auth, &user_id)?;
+    let result = state
+        .user_manager
+        .revoke_user_api_key(&user_id, &key_id)
+        .await;
+    let target = format!("{user_id}/{key_id}");
+    state
+        .audit
+        .record(&auth, addr, "api_key.revoke", &target, &result)
+        .await;
+    let key = result?;
     Ok(Json(key.into()))
 }
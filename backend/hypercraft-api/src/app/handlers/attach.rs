@@ -4,21 +4,40 @@
 //! - Binary 消息：双向传输原始终端数据
 //! - Text 消息：JSON 控制命令
 //!   - 客户端 -> 服务端: {"signal": "INT|TERM|KILL"}
+//!   - 客户端 -> 服务端: {"type": "resize", "rows": R, "cols": C}
 //!   - 服务端 -> 客户端: {"type": "notice|error", "message": "..."}
+//!   - 服务端 -> 客户端: {"type": "stderr", "data": "..."}（仅 `split_stderr` 的服务，
+//!     单独捕获的 stderr 以文本帧下发，与 Binary 的 stdout 区分）
+//!
+//! 管理员可通过 `?record=true` 把本次会话的 PTY 输出录制为 asciinema v2 文件。
+//!
+//! `?mode=readonly` 以只读观察者身份接入：照常接收输出，但所有 Binary 输入、
+//! signal 与 resize 控制命令都会被忽略，并回复 `{"type":"error"}`。
+//! 只读会话不会改变 PTY 尺寸，也无法向进程发送信号。
+//! `tty: false` 的服务没有 PTY，attach 总是按只读处理。
+//!
+//! `?wait_secs=N`（最多 60）在服务尚未运行时等待它启动再接入，便于“先启动再 attach”；
+//! 超时则按下文方式以 4408 关闭。
+//!
+//! 权限不足、并发超限、服务未运行等情况仍会完成握手：先发送
+//! `{"type":"error","code":"...","message":"..."}`，再以 `4000 + HTTP 状态码`
+//! （如 4403、4409、4429）关闭连接，浏览器端也能拿到原因而不是一次静默断开。
 
-use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
-use axum::extract::{Path, State};
+use axum::extract::ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade};
+use axum::body::Body;
+use axum::extract::{Path, Query, State};
+use axum::http::header;
 use axum::response::Response;
-use axum::Extension;
+use axum::{Extension, Json};
 use futures::stream::StreamExt;
 use futures::SinkExt;
-use hypercraft_core::ServiceManager;
+use hypercraft_core::{parse_signal, RecordingInfo, ServiceError, ServiceManager, SessionRecorder};
 use serde::Deserialize;
 use std::sync::Arc;
 use std::time::Duration;
 use sysinfo::Signal;
 
-use crate::app::middleware::AuthInfo;
+use crate::app::middleware::{AuthInfo, ServicePermission};
 use crate::app::rate_limit::StreamPermit;
 use crate::app::{ApiError, AppState};
 use hypercraft_core::api_key_scopes;
@@ -28,64 +47,196 @@ const DEFAULT_PTY_COLS: u16 = 155;
 const ATTACH_IDLE_TIMEOUT: Duration = Duration::from_secs(15 * 60);
 /// attach 回放日志的最大字节数
 const ATTACH_REPLAY_BYTES: usize = 64 * 1024;
+/// `wait_secs` 上限
+const MAX_ATTACH_WAIT_SECS: u64 = 60;
+/// resync 时最多丢弃的积压消息数，防止输出持续涌入时一直卡在清空循环里
+const RESYNC_DRAIN_LIMIT: usize = 1024;
+/// 终端完全重置（RIS），resync 重发日志前先清掉客户端已有的屏幕内容
+const TERMINAL_RESET: &[u8] = b"\x1bc";
+
+/// attach 会话模式
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttachMode {
+    /// 可输入、可发送控制命令
+    #[default]
+    Interactive,
+    /// 只读观察，拒绝任何输入
+    Readonly,
+}
+
+/// attach 查询参数
+#[derive(Debug, Default, Deserialize)]
+pub struct AttachQuery {
+    /// 录制本次会话（仅管理员）
+    #[serde(default)]
+    pub record: bool,
+    /// 会话模式，默认可交互
+    #[serde(default)]
+    pub mode: AttachMode,
+    /// 服务未运行时等待其启动的秒数，默认不等待
+    #[serde(default)]
+    pub wait_secs: u64,
+    /// 输出落后被丢弃时，用日志末尾重新同步终端而不只是提示丢弃条数
+    #[serde(default)]
+    pub resync: bool,
+}
+
+const READONLY_INPUT_ERROR: &str =
+    r#"{"type":"error","message":"read-only attach session does not accept input"}"#;
+
+/// 握手前准备好的 attach 会话
+struct AttachSession {
+    manager: Arc<ServiceManager>,
+    handle: hypercraft_core::AttachHandle,
+    replay_logs: bool,
+    readonly: bool,
+    resync: bool,
+    recorder: Option<SessionRecorder>,
+    permit: StreamPermit,
+}
 
 /// GET /services/:id/attach - WebSocket 连接到服务终端
 pub async fn attach_service(
     State(state): State<AppState>,
     Extension(auth): Extension<AuthInfo>,
     Path(id): Path<String>,
+    Query(query): Query<AttachQuery>,
     ws: WebSocketUpgrade,
-) -> Result<Response, ApiError> {
+) -> Response {
+    match prepare_attach(&state, &auth, &id, &query).await {
+        Ok(session) => ws.on_upgrade(move |socket| {
+            handle_socket(
+                socket,
+                session.manager,
+                id,
+                session.handle,
+                session.replay_logs,
+                session.readonly,
+                session.resync,
+                session.recorder,
+                session.permit,
+            )
+        }),
+        Err(err) => ws.on_upgrade(move |socket| reject_socket(socket, err)),
+    }
+}
+
+/// 鉴权、限流并接入 PTY；失败时由调用方通过 WebSocket 告知原因
+async fn prepare_attach(
+    state: &AppState,
+    auth: &AuthInfo,
+    id: &str,
+    query: &AttachQuery,
+) -> Result<AttachSession, ApiError> {
     auth.require_scope(api_key_scopes::ATTACH)?;
-    if !auth.can_access_service(&id) {
+    // 只读观察只需读权限；可输入的会话等同控制权
+    let allowed = match query.mode {
+        AttachMode::Readonly => auth.can_read(id),
+        AttachMode::Interactive => auth.can_control(id),
+    };
+    if !allowed {
         return Err(ApiError::forbidden(format!(
             "没有权限访问服务: {}",
             id
         )));
     }
+    if query.record && !auth.is_admin() {
+        return Err(ApiError::forbidden("仅管理员可以录制 attach 会话"));
+    }
 
     let stream_key = format!("ws:{}:{}", auth.claims.sub, id);
     let permit = state.stream_limiter.try_acquire(stream_key).ok_or_else(|| {
         ApiError::too_many_requests("too many concurrent attach sessions for this service")
     })?;
 
-    let handle = match state.manager.attach(&id).await {
-        Ok(handle) => handle,
-        Err(err) => {
-            drop(permit);
-            return Err(ApiError::from(err));
-        }
-    };
-    let manifest = state.manager.load_manifest(&id).await.ok();
+    let wait = Duration::from_secs(query.wait_secs.min(MAX_ATTACH_WAIT_SECS));
+    let handle = state.manager.attach_or_wait(id, wait).await?;
+    let manifest = state.manager.load_manifest(id).await.ok();
     let replay_logs = manifest
         .as_ref()
         .map(|manifest| !manifest.terminal_tui)
         .unwrap_or(true);
-    if let Some(manifest) = manifest.as_ref().filter(|manifest| manifest.terminal_tui) {
+    // 管道模式（tty: false）没有终端可交互，attach 一律只读
+    let readonly = query.mode == AttachMode::Readonly
+        || manifest.as_ref().is_some_and(|manifest| !manifest.tty);
+    // 重新 attach 时沿用上次尺寸；TUI 服务即使尺寸未变也借此触发重绘
+    if readonly {
+        // 只读观察者不触碰 PTY 尺寸，避免干扰正在操作的会话
+    } else if let Some((rows, cols)) = state.manager.last_pty_size(id) {
+        let _ = state.manager.resize_pty(id, rows, cols).await;
+    } else if let Some(manifest) = manifest.as_ref().filter(|manifest| manifest.terminal_tui) {
         let _ = state
             .manager
-            .resize_pty(&id, manifest.pty_rows.clamp(5, 500), DEFAULT_PTY_COLS)
+            .resize_pty(id, manifest.pty_rows.clamp(5, 500), DEFAULT_PTY_COLS)
             .await;
     }
-    let manager = state.manager.clone();
+    let recorder = if query.record {
+        let (rows, cols) = state
+            .manager
+            .last_pty_size(id)
+            .unwrap_or((manifest.as_ref().map_or(24, |m| m.pty_rows), DEFAULT_PTY_COLS));
+        Some(state.manager.start_recording(id, rows, cols).await?)
+    } else {
+        None
+    };
+
+    Ok(AttachSession {
+        manager: state.manager.clone(),
+        handle,
+        replay_logs,
+        readonly,
+        // TUI 服务靠重绘同步画面，回放日志只会把屏幕搅乱
+        resync: query.resync && replay_logs,
+        recorder,
+        permit,
+    })
+}
 
-    Ok(ws.on_upgrade(move |socket| {
-        handle_socket(socket, manager, id, handle, replay_logs, permit)
-    }))
+/// 握手后发送错误消息并关闭连接
+async fn reject_socket(mut socket: WebSocket, err: ApiError) {
+    let (message, close) = rejection_frames(&err);
+    let _ = socket.send(Message::Text(message)).await;
+    let _ = socket.send(Message::Close(Some(close))).await;
+}
+
+/// 拒绝 attach 时的错误消息与关闭帧（关闭码 4000 + HTTP 状态码）
+fn rejection_frames(err: &ApiError) -> (String, CloseFrame<'static>) {
+    let message = serde_json::json!({
+        "type": "error",
+        "code": err.code(),
+        "message": err.message(),
+    })
+    .to_string();
+    // 关闭原因最长 123 字节，按字符边界截断
+    let mut reason = err.message().to_string();
+    while reason.len() > 123 {
+        reason.pop();
+    }
+    let close = CloseFrame {
+        code: 4000 + err.status().as_u16(),
+        reason: reason.into(),
+    };
+    (message, close)
 }
 
 /// 处理 WebSocket 连接
+#[allow(clippy::too_many_arguments)]
 async fn handle_socket(
     socket: WebSocket,
     manager: Arc<ServiceManager>,
     id: String,
     handle: hypercraft_core::AttachHandle,
     replay_logs: bool,
+    readonly: bool,
+    resync: bool,
+    mut recorder: Option<SessionRecorder>,
     _permit: StreamPermit,
 ) {
     let (mut ws_tx, mut ws_rx) = socket.split();
     let pty_tx = handle.input;
     let mut pty_rx = handle.output;
+    let mut stderr_rx = handle.stderr;
 
     // 发送最近的原始日志（保留所有控制序列，确保 xterm 状态同步）
     if replay_logs {
@@ -102,14 +253,26 @@ async fn handle_socket(
             // 客户端 -> PTY
             msg = ws_rx.next() => {
                 match msg {
+                    Some(Ok(Message::Binary(_) | Message::Text(_))) if readonly => {
+                        let _ = ws_tx
+                            .send(Message::Text(READONLY_INPUT_ERROR.to_string()))
+                            .await;
+                    }
                     Some(Ok(Message::Binary(data))) => {
                         // 原始终端输入
                         let _ = pty_tx.send(data).await;
                     }
                     Some(Ok(Message::Text(text))) => {
                         // JSON 控制命令
-                        if let Some(sig) = parse_signal_command(&text) {
-                            let _ = manager.send_signal(&id, sig).await;
+                        if let Some((rows, cols)) = parse_resize_command(&text) {
+                            let _ = manager.resize_pty(&id, rows, cols).await;
+                        } else if let Some(sig) = parse_signal_command(&text) {
+                            // 非 Unix 无法投递 INT/TERM，沿用直接终止进程的行为
+                            if let Err(ServiceError::UnsupportedSignal(_)) =
+                                manager.send_signal(&id, sig).await
+                            {
+                                let _ = manager.send_signal(&id, Signal::Kill).await;
+                            }
                         }
                     }
                     Some(Ok(Message::Ping(data))) => {
@@ -123,10 +286,27 @@ async fn handle_socket(
             data = pty_rx.recv() => {
                 match data {
                     Ok(bytes) => {
+                        if let Some(rec) = recorder.as_mut() {
+                            if let Err(e) = rec.write_output(&bytes).await {
+                                tracing::warn!(service_id = %id, error = %e, "attach recording failed");
+                                recorder = None;
+                            }
+                        }
                         if ws_tx.send(Message::Binary(bytes)).await.is_err() {
                             break;
                         }
                     }
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) if resync => {
+                        // 丢掉积压的旧输出，重置终端后改发日志末尾让客户端整体重绘
+                        drain_backlog(&mut pty_rx);
+                        let msg = format!(r#"{{"type":"resync","dropped":{}}}"#, n);
+                        let _ = ws_tx.send(Message::Text(msg)).await;
+                        if let Ok(logs) = manager.tail_logs_raw(&id, ATTACH_REPLAY_BYTES) {
+                            if ws_tx.send(Message::Binary(resync_frame(logs))).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
                     Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
                         // 客户端太慢，丢弃了一些输出
                         let msg = format!(r#"{{"type":"notice","message":"dropped {} messages"}}"#, n);
@@ -135,6 +315,28 @@ async fn handle_socket(
                     Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
                 }
             }
+            // 单独捕获的 stderr -> 客户端（文本帧）
+            data = recv_stderr(&mut stderr_rx) => {
+                match data {
+                    Ok(bytes) => {
+                        if let Some(rec) = recorder.as_mut() {
+                            if let Err(e) = rec.write_output(&bytes).await {
+                                tracing::warn!(service_id = %id, error = %e, "attach recording failed");
+                                recorder = None;
+                            }
+                        }
+                        if ws_tx.send(Message::Text(stderr_frame(&bytes))).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                        let msg = format!(r#"{{"type":"notice","message":"dropped {} stderr messages"}}"#, n);
+                        let _ = ws_tx.send(Message::Text(msg)).await;
+                    }
+                    // stdout 关闭时循环自会结束，这里只停止监听 stderr
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => stderr_rx = None,
+                }
+            }
             _ = tokio::time::sleep(ATTACH_IDLE_TIMEOUT) => {
                 let _ = ws_tx
                     .send(Message::Text(
@@ -147,6 +349,73 @@ async fn handle_socket(
     }
 }
 
+/// 丢弃接收端积压的消息，最多 [`RESYNC_DRAIN_LIMIT`] 条
+fn drain_backlog(rx: &mut tokio::sync::broadcast::Receiver<Vec<u8>>) {
+    use tokio::sync::broadcast::error::TryRecvError;
+    for _ in 0..RESYNC_DRAIN_LIMIT {
+        if let Err(TryRecvError::Empty | TryRecvError::Closed) = rx.try_recv() {
+            break;
+        }
+    }
+}
+
+/// resync 重发的日志帧：先重置终端，避免与屏幕上残留的旧内容叠加
+fn resync_frame(logs: Vec<u8>) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(TERMINAL_RESET.len() + logs.len());
+    frame.extend_from_slice(TERMINAL_RESET);
+    frame.extend(logs);
+    frame
+}
+
+/// 接收单独捕获的 stderr；未分离 stderr 时永远挂起
+async fn recv_stderr(
+    rx: &mut Option<tokio::sync::broadcast::Receiver<Vec<u8>>>,
+) -> Result<Vec<u8>, tokio::sync::broadcast::error::RecvError> {
+    match rx {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// stderr 文本帧：`{"type":"stderr","data":"..."}`，非 UTF-8 字节按替换字符处理
+fn stderr_frame(bytes: &[u8]) -> String {
+    serde_json::json!({ "type": "stderr", "data": String::from_utf8_lossy(bytes) }).to_string()
+}
+
+/// GET /services/:id/recordings - 列出 attach 录制文件（仅管理员）
+pub async fn list_recordings(
+    State(state): State<AppState>,
+    ServicePermission { auth, service_id }: ServicePermission,
+) -> Result<Json<Vec<RecordingInfo>>, ApiError> {
+    if !auth.is_admin() {
+        return Err(ApiError::forbidden("仅管理员可以查看 attach 录制"));
+    }
+    Ok(Json(state.manager.list_recordings(&service_id)?))
+}
+
+/// GET /services/:id/recordings/:name - 下载 .cast 录制文件（仅管理员）
+pub async fn download_recording(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthInfo>,
+    Path((id, name)): Path<(String, String)>,
+) -> Result<Response, ApiError> {
+    if !auth.is_admin() || !auth.can_read(&id) {
+        return Err(ApiError::forbidden("仅管理员可以下载 attach 录制"));
+    }
+    let path = state.manager.recording_path(&id, &name)?;
+    let content = tokio::fs::read(path)
+        .await
+        .map_err(hypercraft_core::ServiceError::from)?;
+    Ok(Response::builder()
+        .header(header::CONTENT_TYPE, "application/x-asciicast")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", name),
+        )
+        .body(Body::from(content))
+        .unwrap())
+}
+
 /// 解析信号命令: {"signal": "INT"}
 fn parse_signal_command(text: &str) -> Option<Signal> {
     #[derive(Deserialize)]
@@ -156,18 +425,92 @@ fn parse_signal_command(text: &str) -> Option<Signal> {
 
     serde_json::from_str::<SignalCmd>(text)
         .ok()
-        .and_then(|cmd| match cmd.signal.to_uppercase().as_str() {
-            "INT" | "SIGINT" => Some(Signal::Interrupt),
-            "TERM" | "SIGTERM" => Some(Signal::Term),
-            "KILL" | "SIGKILL" => Some(Signal::Kill),
-            _ => None,
-        })
+        .and_then(|cmd| parse_signal(&cmd.signal))
+}
+
+/// 解析尺寸命令: {"type": "resize", "rows": 40, "cols": 120}，尺寸限制在合理范围内
+fn parse_resize_command(text: &str) -> Option<(u16, u16)> {
+    #[derive(Deserialize)]
+    struct ResizeCmd {
+        #[serde(rename = "type")]
+        kind: String,
+        rows: u16,
+        cols: u16,
+    }
+
+    serde_json::from_str::<ResizeCmd>(text)
+        .ok()
+        .filter(|cmd| cmd.kind == "resize")
+        .map(|cmd| (cmd.rows.clamp(5, 500), cmd.cols.clamp(20, 1000)))
 }
 
 #[cfg(test)]
 mod tests {
+    use super::{
+        drain_backlog, parse_resize_command, rejection_frames, resync_frame, stderr_frame,
+        AttachMode, AttachQuery, RESYNC_DRAIN_LIMIT,
+    };
+    use crate::app::ApiError;
     use crate::app::StreamConcurrencyLimiter;
 
+    #[test]
+    fn stderr_frame_is_tagged_text() {
+        let frame: serde_json::Value = serde_json::from_str(&stderr_frame(b"boom\n\xff")).unwrap();
+        assert_eq!(frame["type"], "stderr");
+        assert_eq!(frame["data"], "boom\n\u{fffd}");
+    }
+
+    #[test]
+    fn resync_resets_terminal_and_drain_is_bounded() {
+        assert_eq!(resync_frame(b"tail".to_vec()), b"\x1bctail");
+
+        let (tx, mut rx) = tokio::sync::broadcast::channel(RESYNC_DRAIN_LIMIT * 2);
+        for i in 0..RESYNC_DRAIN_LIMIT + 5 {
+            tx.send(vec![i as u8]).unwrap();
+        }
+        drain_backlog(&mut rx);
+        assert_eq!(rx.len(), 5);
+        drain_backlog(&mut rx);
+        assert!(rx.is_empty());
+    }
+
+    #[test]
+    fn resize_command_is_parsed_and_clamped() {
+        assert_eq!(
+            parse_resize_command(r#"{"type":"resize","rows":40,"cols":120}"#),
+            Some((40, 120))
+        );
+        assert_eq!(
+            parse_resize_command(r#"{"type":"resize","rows":1,"cols":9999}"#),
+            Some((5, 1000))
+        );
+        assert_eq!(parse_resize_command(r#"{"type":"signal","signal":"INT"}"#), None);
+    }
+
+    #[test]
+    fn rejection_carries_error_message_and_close_code() {
+        let (message, close) = rejection_frames(&ApiError::forbidden("没有权限访问服务: mc"));
+        let message: serde_json::Value = serde_json::from_str(&message).unwrap();
+        assert_eq!(message["type"], "error");
+        assert_eq!(message["code"], "Forbidden");
+        assert_eq!(message["message"], "没有权限访问服务: mc");
+        assert_eq!(close.code, 4403);
+        assert_eq!(close.reason, "没有权限访问服务: mc");
+
+        // 关闭原因超长时截断到协议上限
+        let (_, close) = rejection_frames(&ApiError::bad_request("错".repeat(100)));
+        assert_eq!(close.code, 4400);
+        assert!(close.reason.len() <= 123);
+    }
+
+    #[test]
+    fn attach_mode_defaults_to_interactive() {
+        let query: AttachQuery = serde_json::from_str("{}").unwrap();
+        assert_eq!(query.mode, AttachMode::Interactive);
+        let query: AttachQuery = serde_json::from_str(r#"{"mode":"readonly"}"#).unwrap();
+        assert_eq!(query.mode, AttachMode::Readonly);
+    }
+
     #[test]
     fn attach_stream_key_is_isolated_from_sse() {
         let limiter = StreamConcurrencyLimiter::new(1);
@@ -0,0 +1,46 @@
+//! 审计日志查询 API（仅管理员可访问）
+
+use axum::extract::{Query, State};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use hypercraft_core::ServiceError;
+use serde::Deserialize;
+
+use super::super::audit::AuditEntry;
+use super::super::error::ApiError;
+use super::super::middleware::RequireAdmin;
+use super::super::state::AppState;
+
+/// 默认返回条数
+const DEFAULT_AUDIT_LIMIT: usize = 100;
+/// 单次最多返回条数
+const MAX_AUDIT_LIMIT: usize = 1000;
+
+/// 审计日志查询参数
+#[derive(Debug, Default, Deserialize)]
+pub struct AuditQuery {
+    /// 返回最近的条数（默认 100，最多 1000）
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// 仅返回该时间点（RFC 3339）之后的记录
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+}
+
+/// GET /audit?limit=&since= - 读取审计日志（按时间正序）
+pub async fn list_audit(
+    State(state): State<AppState>,
+    RequireAdmin(_): RequireAdmin,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<Vec<AuditEntry>>, ApiError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_AUDIT_LIMIT)
+        .min(MAX_AUDIT_LIMIT);
+    let entries = state
+        .audit
+        .read(query.since, limit)
+        .await
+        .map_err(ServiceError::from)?;
+    Ok(Json(entries))
+}
@@ -5,11 +5,14 @@ use axum::http::header::{HeaderMap, HeaderValue, SET_COOKIE};
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use axum::{Extension, Json};
-use hypercraft_core::{AuthToken, DevTokenLoginRequest, LoginRequest, RefreshRequest, UserSummary};
+use hypercraft_core::{
+    AuthToken, DevTokenLoginRequest, LoginRequest, RefreshRequest, ServiceError, SessionMeta,
+    UserSummary,
+};
 use serde_json::json;
 use std::net::SocketAddr;
 
-use super::super::error::ApiError;
+use super::super::error::{ApiError, ErrorCode};
 use super::super::middleware::{
     extract_cookie_value, AuthInfo, ACCESS_TOKEN_COOKIE, REFRESH_TOKEN_COOKIE,
     CSRF_HEADER,
@@ -17,6 +20,17 @@ use super::super::middleware::{
 use super::super::state::AppState;
 use super::verify_user_2fa;
 
+/// 记录到会话中的客户端信息（仅使用直连地址，不信任代理头）
+fn session_meta(addr: &SocketAddr, headers: &HeaderMap) -> SessionMeta {
+    SessionMeta {
+        ip: Some(addr.ip().to_string()),
+        user_agent: headers
+            .get(axum::http::header::USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string),
+    }
+}
+
 /// 根据请求 Origin 判断是否应下发 Secure cookie（不信任代理头）
 fn cookie_secure(headers: &HeaderMap) -> bool {
     if let Some(origin) = headers
@@ -103,14 +117,19 @@ pub async fn login(
     let ip = addr.ip().to_string();
     tracing::info!("登录请求: 用户={}, IP={}", req.username, ip);
 
-    if !state.login_limiter.allow(&ip).await {
+    if let Err(wait) = state.login_limiter.acquire(&ip).await {
         tracing::warn!("登录限流: 用户={}, IP={}", req.username, ip);
-        return Err(ApiError::too_many_requests("请求过于频繁，请稍后再试"));
+        return Err(ApiError::too_many_requests("请求过于频繁，请稍后再试").with_retry_after(wait));
     }
 
     let result = state
         .user_manager
-        .login(&req.username, &req.password, req.totp_code.as_deref())
+        .login(
+            &req.username,
+            &req.password,
+            req.totp_code.as_deref(),
+            &session_meta(&addr, &headers),
+        )
         .await;
 
     match &result {
@@ -142,9 +161,9 @@ pub async fn devtoken_login(
     let ip = addr.ip().to_string();
     tracing::info!("DevToken 登录请求: IP={}", ip);
 
-    if !state.login_limiter.allow(&ip).await {
+    if let Err(wait) = state.login_limiter.acquire(&ip).await {
         tracing::warn!("DevToken 登录限流: IP={}", ip);
-        return Err(ApiError::too_many_requests("请求过于频繁，请稍后再试"));
+        return Err(ApiError::too_many_requests("请求过于频繁，请稍后再试").with_retry_after(wait));
     }
 
     // 验证 DevToken
@@ -165,10 +184,11 @@ pub async fn devtoken_login(
     }
 
     // 签发 JWT token（使用虚拟 dev 用户）
-    let auth_token = state.user_manager.issue_dev_token().await.map_err(|e| {
+    let meta = session_meta(&addr, &headers);
+    let auth_token = state.user_manager.issue_dev_token(&meta).await.map_err(|e| {
         tracing::error!("DevToken 签发失败: IP={}, 错误={}", ip, e);
         ApiError::new(
-            "INTERNAL_ERROR",
+            ErrorCode::Internal,
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("签发 Token 失败: {}", e),
         )
@@ -191,9 +211,9 @@ pub async fn refresh(
     Json(req): Json<RefreshRequest>,
 ) -> Result<Response, ApiError> {
     let ip = addr.ip().to_string();
-    if !state.refresh_limiter.allow(&ip).await {
+    if let Err(wait) = state.refresh_limiter.acquire(&ip).await {
         tracing::warn!("刷新限流: IP={}", ip);
-        return Err(ApiError::too_many_requests("请求过于频繁，请稍后再试"));
+        return Err(ApiError::too_many_requests("请求过于频繁，请稍后再试").with_retry_after(wait));
     }
 
     // JSON body 优先（CLI）；浏览器可仅依赖 HttpOnly refresh cookie
@@ -205,7 +225,28 @@ pub async fn refresh(
         .or_else(|| extract_cookie_value(&headers, REFRESH_TOKEN_COOKIE))
         .ok_or_else(|| ApiError::unauthorized_with_message("缺少 refresh token"))?;
 
-    let auth_token = state.user_manager.refresh(&refresh_token).await?;
+    let auth_token = match state
+        .user_manager
+        .refresh(&refresh_token, &session_meta(&addr, &headers))
+        .await
+    {
+        Ok(token) => token,
+        Err(ServiceError::RefreshTokenReused(user_id)) => {
+            tracing::warn!("检测到 refresh token 重放: 用户={}, IP={}", user_id, ip);
+            state
+                .audit
+                .record_security_event(
+                    &user_id,
+                    addr,
+                    "auth.refresh_reuse",
+                    &user_id,
+                    "all sessions revoked",
+                )
+                .await;
+            return Err(ServiceError::RefreshTokenReused(user_id).into());
+        }
+        Err(e) => return Err(e.into()),
+    };
 
     Ok(auth_token_response(
         auth_token,
@@ -215,11 +256,27 @@ pub async fn refresh(
     ))
 }
 
-/// POST /auth/logout - 清除浏览器会话 cookie
-pub async fn logout(headers: HeaderMap) -> Response {
+/// POST /auth/logout - 撤销调用者的全部 token 并清除浏览器会话 cookie
+///
+/// 凭据缺失或已失效时仍清除 cookie，保证登出幂等。
+pub async fn logout(State(state): State<AppState>, headers: HeaderMap) -> Response {
     if headers.get(CSRF_HEADER).is_none() {
         return ApiError::forbidden("missing CSRF protection header").into_response();
     }
+    let token = headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::to_string)
+        .or_else(|| extract_cookie_value(&headers, ACCESS_TOKEN_COOKIE))
+        .or_else(|| extract_cookie_value(&headers, REFRESH_TOKEN_COOKIE));
+    if let Some(token) = token {
+        if let Ok(claims) = state.user_manager.verify_token(&token).await {
+            if let Err(e) = state.user_manager.revoke_all_tokens(&claims.sub).await {
+                tracing::warn!("登出时撤销 token 失败: 用户={}, 错误={}", claims.sub, e);
+            }
+        }
+    }
     let secure = cookie_secure(&headers);
     let mut response = (StatusCode::OK, Json(json!({ "success": true }))).into_response();
     append_set_cookie(
@@ -265,6 +322,7 @@ pub async fn get_me(
                 is_admin: true,
                 token_version: 0,
                 refresh_nonce: String::new(),
+                sessions: vec![],
                 totp_config: None,
                 created_at: Some(Utc::now()),
                 updated_at: Some(Utc::now()),
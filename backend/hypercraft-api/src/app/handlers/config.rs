@@ -0,0 +1,59 @@
+//! 配置导入导出（仅管理员）：manifest、调度与分组，不含用户与密钥。
+
+use axum::extract::{Query, State};
+use axum::Json;
+use hypercraft_core::{ConfigBundle, ImportOutcome, ImportReport};
+use serde::Deserialize;
+
+use super::super::error::ApiError;
+use super::super::middleware::RequireAdmin;
+use super::super::state::AppState;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ImportQuery {
+    /// 覆盖已存在的服务与分组
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+/// GET /export - 导出配置包
+pub async fn export_config(
+    State(state): State<AppState>,
+    RequireAdmin(_): RequireAdmin,
+) -> Result<Json<ConfigBundle>, ApiError> {
+    Ok(Json(state.manager.export_config().await?))
+}
+
+/// POST /import - 导入配置包，逐条返回结果
+pub async fn import_config(
+    State(state): State<AppState>,
+    RequireAdmin(auth): RequireAdmin,
+    Query(query): Query<ImportQuery>,
+    Json(bundle): Json<ConfigBundle>,
+) -> Result<Json<ImportReport>, ApiError> {
+    // 导入的 manifest 同样不能绕过密钥引用限制
+    for manifest in &bundle.services {
+        let previous = state.manager.load_manifest(&manifest.id).await.ok();
+        // 已存在且不覆盖的服务会被跳过
+        if previous.is_some() && !query.overwrite {
+            continue;
+        }
+        auth.require_secret_refs(manifest, previous.as_ref())?;
+    }
+    let report = state.manager.import_config(bundle, query.overwrite).await?;
+
+    // 新建或覆盖的服务需要重新同步调度任务
+    for item in &report.services {
+        if !matches!(
+            item.outcome,
+            ImportOutcome::Created | ImportOutcome::Updated
+        ) {
+            continue;
+        }
+        let schedules = state.manager.load_manifest(&item.id).await?.schedules;
+        if let Err(e) = state.scheduler.sync_schedules(&item.id, &schedules).await {
+            tracing::warn!(service_id = %item.id, error = %e, "无法同步导入服务的计划任务");
+        }
+    }
+    Ok(Json(report))
+}
@@ -0,0 +1,57 @@
+//! 服务生命周期事件推送（SSE）
+
+use axum::extract::State;
+use axum::response::sse::{Event, KeepAlive};
+use axum::response::{IntoResponse, Response, Sse};
+use axum::Extension;
+use futures::stream::{self, StreamExt};
+use hypercraft_core::api_key_scopes;
+use std::convert::Infallible;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::instrument;
+
+use crate::app::middleware::AuthInfo;
+use crate::app::rate_limit::StreamConcurrencyLimiter;
+use crate::app::{ApiError, AppState};
+
+/// 单条事件流最长存活时间
+const SSE_MAX_DURATION: Duration = Duration::from_secs(30 * 60);
+
+/// GET /events - 以 SSE 推送有读权限的服务的状态变化
+///
+/// 消费过慢导致丢事件时推送 `lagged` 事件，客户端应重新拉取 /services 对齐状态。
+#[instrument(skip_all)]
+pub async fn stream_events(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthInfo>,
+) -> Result<Response, ApiError> {
+    auth.require_scope(api_key_scopes::READ)?;
+    let permit = state
+        .stream_limiter
+        .try_acquire(format!("events:{}", auth.claims.sub))
+        .ok_or_else(|| ApiError::too_many_requests("too many concurrent event streams"))?;
+
+    let rx = state.manager.subscribe_events();
+    let stream = stream::unfold((rx, auth), |(mut rx, auth)| async move {
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) if auth.can_read(event.service_id()) => {
+                    Event::default().json_data(&event).unwrap_or_default()
+                }
+                Ok(_) => continue,
+                Err(RecvError::Lagged(skipped)) => {
+                    Event::default().event("lagged").data(skipped.to_string())
+                }
+                Err(RecvError::Closed) => return None,
+            };
+            return Some((Ok::<_, Infallible>(event), (rx, auth)));
+        }
+    })
+    .take_until(tokio::time::sleep(SSE_MAX_DURATION));
+
+    let guarded = StreamConcurrencyLimiter::guard_stream(stream, permit);
+    Ok(Sse::new(guarded)
+        .keep_alive(KeepAlive::new().interval(Duration::from_secs(15)))
+        .into_response())
+}
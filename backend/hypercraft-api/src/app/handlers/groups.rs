@@ -1,9 +1,12 @@
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
 use axum::Extension;
 use axum::Json;
-use hypercraft_core::{api_key_scopes, ServiceGroup};
-use serde::Deserialize;
+use hypercraft_core::{
+    api_key_scopes, ChildGroupAction, GroupAction, GroupActionResult, ServiceGroup,
+    ServiceManifest, ServiceSummary,
+};
+use serde::{Deserialize, Deserializer, Serialize};
 use tracing::instrument;
 
 use crate::app::middleware::{AuthInfo, RequireAdmin};
@@ -26,6 +29,9 @@ pub struct CreateGroupRequest {
     pub id: String,
     pub name: String,
     pub color: Option<String>,
+    /// 父分组 ID，缺省为顶层分组
+    #[serde(default)]
+    pub parent: Option<String>,
 }
 
 /// 创建分组
@@ -39,7 +45,7 @@ pub async fn create_group(
     auth.require_manage_create()?;
     let group = state
         .manager
-        .create_group(payload.id, payload.name, payload.color)
+        .create_group(payload.id, payload.name, payload.color, payload.parent)
         .await?;
     Ok(Json(group))
 }
@@ -48,6 +54,17 @@ pub async fn create_group(
 pub struct UpdateGroupRequest {
     pub name: Option<String>,
     pub color: Option<Option<String>>,
+    /// 缺省不变；`null` 移到顶层，字符串挂到对应父分组下
+    #[serde(default, deserialize_with = "deserialize_present")]
+    pub parent: Option<Option<String>>,
+}
+
+/// 区分字段缺省（`None`）与显式 `null`（`Some(None)`）
+fn deserialize_present<'de, D>(deserializer: D) -> Result<Option<Option<String>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer).map(Some)
 }
 
 /// 更新分组
@@ -62,27 +79,37 @@ pub async fn update_group(
     auth.require_manage_create()?;
     let group = state
         .manager
-        .update_group(&id, payload.name, payload.color)
+        .update_group(&id, payload.name, payload.color, payload.parent)
         .await?;
     Ok(Json(group))
 }
 
+#[derive(Debug, Default, Deserialize)]
+pub struct DeleteGroupQuery {
+    /// 子分组的去向；分组有子分组时必填
+    pub children: Option<ChildGroupAction>,
+}
+
 /// 删除分组
 #[instrument(skip_all)]
 pub async fn delete_group(
     State(state): State<AppState>,
     Extension(auth): Extension<AuthInfo>,
     Path(id): Path<String>,
+    Query(query): Query<DeleteGroupQuery>,
 ) -> Result<StatusCode, ApiError> {
     // 管理员 JWT 或带 manage 的 API Key
     auth.require_manage_create()?;
-    state.manager.delete_group(&id).await?;
+    state.manager.delete_group(&id, query.children).await?;
     Ok(StatusCode::NO_CONTENT)
 }
 
 #[derive(Debug, Deserialize)]
 pub struct ReorderGroupsRequest {
     pub group_ids: Vec<String>,
+    /// 要重排的父分组，缺省为顶层分组
+    #[serde(default)]
+    pub parent: Option<String>,
 }
 
 /// 重新排序分组
@@ -94,27 +121,127 @@ pub async fn reorder_groups(
 ) -> Result<Json<Vec<ServiceGroup>>, ApiError> {
     // 管理员 JWT 或带 manage 的 API Key
     auth.require_manage_create()?;
-    let groups = state.manager.reorder_groups(payload.group_ids).await?;
+    let groups = state
+        .manager
+        .reorder_groups(payload.group_ids, payload.parent)
+        .await?;
     Ok(Json(groups))
 }
 
+#[derive(Debug, Serialize)]
+pub struct GroupActionResponse {
+    pub group: String,
+    pub action: GroupAction,
+    pub results: Vec<GroupActionResult>,
+}
+
+/// 对分组内所有服务执行动作；需要每个服务的控制权，部分失败时返回 207
+async fn run_group_action(
+    state: &AppState,
+    auth: &AuthInfo,
+    id: String,
+    action: GroupAction,
+) -> Result<(StatusCode, Json<GroupActionResponse>), ApiError> {
+    auth.require_scope(api_key_scopes::CONTROL)?;
+    for service in state.manager.group_services(&id).await? {
+        auth.require_control(&service.id)?;
+    }
+    let results = state.manager.group_action(&id, action).await?;
+    let code = if results.iter().all(|r| r.ok) {
+        StatusCode::OK
+    } else {
+        StatusCode::MULTI_STATUS
+    };
+    Ok((
+        code,
+        Json(GroupActionResponse {
+            group: id,
+            action,
+            results,
+        }),
+    ))
+}
+
+/// 按 `order` 升序启动分组内的服务
+#[instrument(skip_all)]
+pub async fn start_group(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthInfo>,
+    Path(id): Path<String>,
+) -> Result<(StatusCode, Json<GroupActionResponse>), ApiError> {
+    run_group_action(&state, &auth, id, GroupAction::Start).await
+}
+
+/// 按 `order` 降序停止分组内的服务
+#[instrument(skip_all)]
+pub async fn stop_group(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthInfo>,
+    Path(id): Path<String>,
+) -> Result<(StatusCode, Json<GroupActionResponse>), ApiError> {
+    run_group_action(&state, &auth, id, GroupAction::Stop).await
+}
+
+/// 重启分组内的服务：先全部停止再依次启动
+#[instrument(skip_all)]
+pub async fn restart_group(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthInfo>,
+    Path(id): Path<String>,
+) -> Result<(StatusCode, Json<GroupActionResponse>), ApiError> {
+    run_group_action(&state, &auth, id, GroupAction::Restart).await
+}
+
 #[derive(Debug, Deserialize)]
 pub struct UpdateServiceTagsRequest {
     pub tags: Vec<String>,
 }
 
-/// 更新服务 tags
+/// 更新服务 tags，返回保存后的 manifest
 #[instrument(skip_all)]
 pub async fn update_service_tags(
     State(state): State<AppState>,
     Extension(auth): Extension<AuthInfo>,
     Path(id): Path<String>,
     Json(payload): Json<UpdateServiceTagsRequest>,
-) -> Result<StatusCode, ApiError> {
+) -> Result<Json<ServiceManifest>, ApiError> {
     // 管理员 JWT 或 manage scope
     auth.require_manage_service(&id)?;
     state.manager.update_service_tags(&id, payload.tags).await?;
-    Ok(StatusCode::NO_CONTENT)
+    Ok(Json(state.manager.load_manifest(&id).await?))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkTagRequest {
+    pub ids: Vec<String>,
+    #[serde(default)]
+    pub add: Vec<String>,
+    #[serde(default)]
+    pub remove: Vec<String>,
+}
+
+/// 批量增删服务 tag，返回更新后的服务摘要
+#[instrument(skip_all)]
+pub async fn bulk_tag_services(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthInfo>,
+    Json(payload): Json<BulkTagRequest>,
+) -> Result<Json<Vec<ServiceSummary>>, ApiError> {
+    if payload.ids.is_empty() {
+        return Err(ApiError::bad_request("ids 不能为空"));
+    }
+    if payload.add.is_empty() && payload.remove.is_empty() {
+        return Err(ApiError::bad_request("add 与 remove 不能同时为空"));
+    }
+    // 每个服务都需要 manage 权限，任一无权限则整体拒绝
+    for id in &payload.ids {
+        auth.require_manage_service(id)?;
+    }
+    let summaries = state
+        .manager
+        .bulk_tag(&payload.ids, &payload.add, &payload.remove)
+        .await?;
+    Ok(Json(summaries))
 }
 
 #[derive(Debug, Deserialize)]
@@ -122,21 +249,21 @@ pub struct UpdateServiceGroupRequest {
     pub group: Option<String>,
 }
 
-/// 更新服务分组
+/// 更新服务分组，返回保存后的 manifest
 #[instrument(skip_all)]
 pub async fn update_service_group(
     State(state): State<AppState>,
     Extension(auth): Extension<AuthInfo>,
     Path(id): Path<String>,
     Json(payload): Json<UpdateServiceGroupRequest>,
-) -> Result<StatusCode, ApiError> {
+) -> Result<Json<ServiceManifest>, ApiError> {
     // 管理员 JWT 或 manage scope
     auth.require_manage_service(&id)?;
     state
         .manager
         .update_service_group(&id, payload.group)
         .await?;
-    Ok(StatusCode::NO_CONTENT)
+    Ok(Json(state.manager.load_manifest(&id).await?))
 }
 
 #[derive(Debug, Deserialize)]
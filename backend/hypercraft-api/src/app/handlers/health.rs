@@ -1,29 +1,90 @@
-use axum::extract::ConnectInfo;
+use axum::extract::{ConnectInfo, State};
 use axum::http::{StatusCode, Uri};
 use axum::Json;
+use hypercraft_core::{HealthReport, ServiceError, SubsystemHealth};
 use serde_json::json;
 use std::net::SocketAddr;
 
-pub async fn health() -> Json<serde_json::Value> {
+use crate::app::error::ErrorCode;
+use crate::app::{ApiError, AppState};
+
+/// 存活探针：进程能响应即返回 200，不做任何检查
+pub async fn health_live() -> Json<serde_json::Value> {
     Json(json!({ "status": "ok" }))
 }
 
+/// 就绪探针（`/health` 与 `/health/ready`）：数据目录可写、调度器运行中、用户存储可加载。
+///
+/// 任一子系统异常时返回 503，`failing` 列出失败的子系统，便于负载均衡摘除实例。
+pub async fn health_ready(State(state): State<AppState>) -> (StatusCode, Json<serde_json::Value>) {
+    let mut report = state.manager.health_report();
+    let scheduler = if state.scheduler.is_running() {
+        Ok(())
+    } else {
+        Err(ServiceError::Other("scheduler is not running".into()))
+    };
+    report.push(SubsystemHealth::from_result("scheduler", scheduler));
+    report.push(SubsystemHealth::from_result(
+        "users",
+        state.user_manager.check_store(),
+    ));
+    readiness_response(&report)
+}
+
+fn readiness_response(report: &HealthReport) -> (StatusCode, Json<serde_json::Value>) {
+    if report.is_healthy() {
+        return (
+            StatusCode::OK,
+            Json(json!({ "status": "ok", "subsystems": report.subsystems })),
+        );
+    }
+    let failing = report.failing();
+    tracing::warn!(?failing, "readiness check failed");
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(json!({
+            "status": "degraded",
+            "failing": failing,
+            "subsystems": report.subsystems,
+        })),
+    )
+}
+
 /// 处理 404 错误，记录可疑请求
-pub async fn handler_404(
-    uri: Uri,
-    ConnectInfo(addr): ConnectInfo<SocketAddr>,
-) -> (StatusCode, Json<serde_json::Value>) {
+pub async fn handler_404(uri: Uri, ConnectInfo(addr): ConnectInfo<SocketAddr>) -> ApiError {
     let path = uri.path();
     let ip = addr.ip().to_string();
     
     // 记录所有 404 请求
     tracing::warn!("404 请求: path={}, IP={}", path, ip);
     
-    (
+    ApiError::new(
+        ErrorCode::NotFound,
         StatusCode::NOT_FOUND,
-        Json(json!({
-            "error": "NOT_FOUND",
-            "message": "请求的资源不存在"
-        })),
+        "请求的资源不存在",
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn degraded_report_lists_failing_subsystems() {
+        let mut report = HealthReport::default();
+        report.push(SubsystemHealth::from_result("data_dir", Ok(())));
+        let (status, Json(body)) = readiness_response(&report);
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ok");
+
+        report.push(SubsystemHealth::from_result(
+            "scheduler",
+            Err(ServiceError::Other("scheduler is not running".into())),
+        ));
+        let (status, Json(body)) = readiness_response(&report);
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["failing"], json!(["scheduler"]));
+        assert_eq!(body["subsystems"][1]["healthy"], false);
+        assert!(body["subsystems"][0].get("error").is_none());
+    }
+}
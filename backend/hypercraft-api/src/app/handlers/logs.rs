@@ -1,22 +1,27 @@
 use axum::body::Body;
 use axum::extract::{Path, Query, State};
-use axum::http::{header, StatusCode};
+use axum::http::{header, HeaderMap, StatusCode};
 use axum::response::sse::{Event, KeepAlive};
 use axum::response::{IntoResponse, Response, Sse};
 use axum::Extension;
 use axum::Json;
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
-use futures::stream::StreamExt;
-use serde::Deserialize;
+use chrono::{DateTime, Utc};
+use futures::stream::{StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::convert::Infallible;
+use std::io::SeekFrom;
 use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
 use tracing::instrument;
 
+use crate::app::error::ErrorCode;
 use crate::app::middleware::{AuthInfo, ServicePermission};
 use crate::app::rate_limit::StreamConcurrencyLimiter;
 use crate::app::{ApiError, AppState};
-use hypercraft_core::api_key_scopes;
+use hypercraft_core::{api_key_scopes, LagPolicy, LogMatch, ServiceError};
 
 /// 文本 tail 默认行数
 const DEFAULT_TAIL_LINES: usize = 200;
@@ -26,6 +31,14 @@ const MAX_TAIL_LINES: usize = 5_000;
 const DEFAULT_TAIL_BYTES: usize = 64 * 1024;
 /// 原始字节 tail 上限（1 MiB）
 const MAX_TAIL_BYTES: usize = 1024 * 1024;
+/// 流式原始字节 tail 上限（64 MiB），边读边发不占用等量内存
+const MAX_STREAM_TAIL_BYTES: usize = 64 * 1024 * 1024;
+/// 搜索默认返回条数
+const DEFAULT_SEARCH_LIMIT: usize = 100;
+/// 搜索返回条数上限
+const MAX_SEARCH_LIMIT: usize = 1_000;
+/// 搜索上下文行数上限
+const MAX_SEARCH_CONTEXT: usize = 20;
 /// 单条 SSE 日志流最长存活时间
 const SSE_MAX_DURATION: Duration = Duration::from_secs(30 * 60);
 
@@ -37,6 +50,29 @@ pub struct LogQuery {
     pub follow: Option<bool>,
     /// 输出格式：base64（默认，兼容 Web）或 text
     pub format: Option<String>,
+    /// 仅返回该时间点（含）之后的行，需日志行以时间戳开头
+    pub since: Option<DateTime<Utc>>,
+    /// 仅返回该时间点之前的行
+    pub until: Option<DateTime<Utc>>,
+    /// 原始字节模式下直接流式返回字节（application/octet-stream），不做 base64/JSON 包装
+    pub stream: Option<bool>,
+    /// 日志来源：默认合并输出；`stderr` 读取 `split_stderr` 单独捕获的 stderr.log
+    #[serde(default)]
+    pub source: LogSource,
+    /// follow 时客户端落后的处理：`drop`（默认，插入丢弃提示）或 `resync`（从文件补读，不留空洞）
+    #[serde(default)]
+    pub on_lag: LagPolicy,
+}
+
+/// 日志来源
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogSource {
+    /// latest.log（PTY 输出或合并后的 stdout/stderr）
+    #[default]
+    Output,
+    /// 单独捕获的 stderr.log
+    Stderr,
 }
 
 #[instrument(skip_all)]
@@ -48,7 +84,7 @@ pub async fn get_logs(
 ) -> Result<Response, ApiError> {
     auth.require_scope(api_key_scopes::LOGS)?;
     // 权限检查（需要同时访问 Path 和 Query，无法使用 ServicePermission extractor）
-    if !auth.can_access_service(&id) {
+    if !auth.can_read(&id) {
         return Err(ApiError::forbidden(format!(
             "没有权限访问服务: {}",
             id
@@ -57,6 +93,12 @@ pub async fn get_logs(
 
     let format = query.format.as_deref().unwrap_or("base64");
     let want_text = format.eq_ignore_ascii_case("text");
+    let stderr = query.source == LogSource::Stderr;
+    if stderr && (query.since.is_some() || query.until.is_some() || query.stream.is_some()) {
+        return Err(ApiError::bad_request(
+            "source=stderr 仅支持 tail 与 follow，不能与 since/until/stream 同用",
+        ));
+    }
 
     let follow = query.follow.unwrap_or(false);
     if follow {
@@ -67,17 +109,24 @@ pub async fn get_logs(
 
         let service_id = id.clone();
         let as_text = want_text;
-        let stream = state
-            .manager
-            .follow_logs_raw(&id, Duration::from_millis(100))
-            .await
+        let mut decoder = state.manager.log_text_decoder(&id);
+        let poll = Duration::from_millis(100);
+        let raw = if stderr {
+            state
+                .manager
+                .follow_stderr_raw(&id, poll, query.on_lag)
+                .await
+        } else {
+            state.manager.follow_logs_raw(&id, poll, query.on_lag).await
+        };
+        let stream = raw
             .map_err(ApiError::from)?
             .map(move |data_res| -> Result<Event, Infallible> {
                 match data_res {
                     Ok(data) => {
                         if as_text {
                             // Agent 友好：SSE 直接推纯文本
-                            let text = String::from_utf8_lossy(&data).into_owned();
+                            let text = decoder.decode(&data);
                             Ok(Event::default().data(text))
                         } else {
                             // Web 兼容：base64 编码原始字节
@@ -106,10 +155,36 @@ pub async fn get_logs(
             .into_response());
     }
 
+    if query.since.is_some() || query.until.is_some() {
+        // 按行首时间戳过滤；tail 作为最多返回的行数
+        let lines = clamp_tail_lines(query.tail);
+        let manager = state.manager.clone();
+        let service_id = id.clone();
+        let (since, until) = (query.since, query.until);
+        let text_lines = tokio::task::spawn_blocking(move || {
+            manager.logs_between(&service_id, since, until, lines)
+        })
+        .await
+        .map_err(|e| ApiError::from(ServiceError::Other(e.to_string())))??;
+        let body = text_lines.join("\n");
+        if want_text {
+            return Ok(Response::builder()
+                .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+                .body(Body::from(body))
+                .unwrap());
+        }
+        let encoded = BASE64.encode(body.as_bytes());
+        return Ok(Json(json!({ "id": id, "data": encoded })).into_response());
+    }
+
     if want_text {
         // Agent 友好：按行 tail，纯文本
         let lines = clamp_tail_lines(query.tail);
-        let text_lines = state.manager.tail_logs(&id, lines)?;
+        let text_lines = if stderr {
+            state.manager.tail_stderr(&id, lines)?
+        } else {
+            state.manager.tail_logs(&id, lines)?
+        };
         let body = text_lines.join("\n");
         return Ok(Response::builder()
             .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
@@ -117,18 +192,100 @@ pub async fn get_logs(
             .unwrap());
     }
 
+    if query.stream.unwrap_or(false) {
+        // 逐个分段边读边发，首字节无需等整个 tail 读完
+        let bytes = clamp_stream_tail_bytes(query.tail);
+        let ranges = state.manager.tail_log_ranges(&id, bytes)?;
+        let stream = futures::stream::iter(ranges)
+            .then(|(path, start, len)| async move {
+                let mut file = tokio::fs::File::open(&path).await?;
+                file.seek(SeekFrom::Start(start)).await?;
+                Ok::<_, std::io::Error>(ReaderStream::new(file.take(len)))
+            })
+            .try_flatten();
+        return Ok(Response::builder()
+            .header(header::CONTENT_TYPE, "application/octet-stream")
+            .body(Body::from_stream(stream))
+            .unwrap());
+    }
+
     // 非实时：返回原始字节（base64 编码）
     let bytes = clamp_tail_bytes(query.tail);
-    let data = state.manager.tail_logs_raw(&id, bytes)?;
+    let data = if stderr {
+        state.manager.tail_stderr_raw(&id, bytes)?
+    } else {
+        state.manager.tail_logs_raw(&id, bytes)?
+    };
     let encoded = BASE64.encode(&data);
     Ok(Json(json!({ "id": id, "data": encoded })).into_response())
 }
 
-/// 下载服务配置的日志文件
+/// 清空服务日志（不重启服务）
+#[instrument(skip_all)]
+pub async fn clear_logs(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthInfo>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    // 管理员 JWT 或 manage scope
+    auth.require_manage_service(&id)?;
+    state.manager.clear_logs(&id)?;
+    tracing::info!(service_id = %id, by = %auth.claims.sub, "service logs cleared");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LogSearchQuery {
+    /// 正则表达式
+    pub q: String,
+    /// 每条命中前后的上下文行数
+    pub context: Option<usize>,
+    /// 最多返回的命中条数
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LogSearchResponse {
+    pub id: String,
+    pub matches: Vec<LogMatch>,
+}
+
+/// 按正则搜索 latest.log，返回命中行、上下文与字节偏移
+#[instrument(skip_all)]
+pub async fn search_logs(
+    State(state): State<AppState>,
+    ServicePermission { auth, service_id }: ServicePermission,
+    Query(query): Query<LogSearchQuery>,
+) -> Result<Json<LogSearchResponse>, ApiError> {
+    auth.require_scope(api_key_scopes::LOGS)?;
+    let context = query.context.unwrap_or(0).min(MAX_SEARCH_CONTEXT);
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .min(MAX_SEARCH_LIMIT);
+
+    // 大日志逐行扫描较慢，放到阻塞线程池
+    let manager = state.manager.clone();
+    let id = service_id.clone();
+    let matches =
+        tokio::task::spawn_blocking(move || manager.search_logs(&id, &query.q, context, limit))
+            .await
+            .map_err(|e| ApiError::from(ServiceError::Other(e.to_string())))??;
+
+    Ok(Json(LogSearchResponse {
+        id: service_id,
+        matches,
+    }))
+}
+
+/// 下载服务日志文件：配置了 `log_path` 时下载该文件，否则下载受管的 latest.log。
+///
+/// 支持单段 `Range: bytes=...` 断点/分段下载，响应体按块流式输出。
 #[instrument(skip_all)]
 pub async fn download_log_file(
     State(state): State<AppState>,
     ServicePermission { auth, service_id }: ServicePermission,
+    headers: HeaderMap,
 ) -> Result<Response, ApiError> {
     auth.require_scope(api_key_scopes::LOGS)?;
     tracing::info!(service_id = %service_id, "download_log_file called");
@@ -143,41 +300,180 @@ pub async fn download_log_file(
             ApiError::from(e)
         })?;
 
-    // 检查是否配置了日志路径
-    let log_path = manifest
-        .log_path
-        .as_ref()
-        .ok_or_else(|| {
-            tracing::warn!(service_id = %service_id, "service has no log_path configured");
-            ApiError::bad_request("service has no log_path configured")
-        })?;
+    // 未配置外部日志路径时回退到受管日志
+    let (log_path, filename) = match manifest.log_path.as_ref() {
+        Some(path) => {
+            let filename = std::path::Path::new(path)
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("service.log")
+                .to_string();
+            (std::path::PathBuf::from(path), filename)
+        }
+        None => (
+            state.manager.latest_log_path(&service_id)?,
+            format!("{}-latest.log", service_id),
+        ),
+    };
 
-    tracing::info!(service_id = %service_id, log_path = %log_path, "reading log file");
+    tracing::info!(service_id = %service_id, log_path = %log_path.display(), "reading log file");
 
-    // 读取日志文件内容
-    let content = tokio::fs::read(log_path).await.map_err(|e| {
-        tracing::error!(service_id = %service_id, log_path = %log_path, error = %e, "无法读取日志文件");
+    let open_failed = |e: std::io::Error| {
+        tracing::error!(service_id = %service_id, log_path = %log_path.display(), error = %e, "无法读取日志文件");
         ApiError::new(
-            "IoError",
+            ErrorCode::IoError,
             StatusCode::INTERNAL_SERVER_ERROR,
             "无法读取日志文件".to_string(),
         )
-    })?;
+    };
+    let mut file = tokio::fs::File::open(&log_path)
+        .await
+        .map_err(open_failed)?;
+    let total = file.metadata().await.map_err(open_failed)?.len();
+
+    let range = match headers.get(header::RANGE).and_then(|v| v.to_str().ok()) {
+        Some(value) => match parse_byte_range(value, total) {
+            Ok(range) => range,
+            Err(()) => {
+                return Ok(Response::builder()
+                    .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                    .header(header::CONTENT_RANGE, format!("bytes */{}", total))
+                    .body(Body::empty())
+                    .unwrap());
+            }
+        },
+        None => None,
+    };
 
-    // 获取文件名用于下载
-    let filename = std::path::Path::new(log_path)
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("service.log");
+    let (status, start, length) = match range {
+        Some((start, end)) => (StatusCode::PARTIAL_CONTENT, start, end - start + 1),
+        None => (StatusCode::OK, 0, total),
+    };
+    if start > 0 {
+        file.seek(SeekFrom::Start(start))
+            .await
+            .map_err(open_failed)?;
+    }
 
-    Ok(Response::builder()
+    // 文件可能仍在追加写入，只输出请求时刻确定的长度
+    let body = Body::from_stream(ReaderStream::new(file.take(length)));
+    let mut response = Response::builder()
+        .status(status)
         .header(header::CONTENT_TYPE, "application/octet-stream")
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::CONTENT_LENGTH, length)
         .header(
             header::CONTENT_DISPOSITION,
             format!("attachment; filename=\"{}\"", filename),
-        )
-        .body(Body::from(content))
-        .unwrap())
+        );
+    if status == StatusCode::PARTIAL_CONTENT {
+        response = response.header(
+            header::CONTENT_RANGE,
+            format!("bytes {}-{}/{}", start, start + length - 1, total),
+        );
+    }
+    Ok(response.body(body).unwrap())
+}
+
+/// 解析单段 `bytes=` Range，返回闭区间 `(start, end)`。
+///
+/// `Ok(None)` 表示忽略该头返回完整内容（多段或非 bytes 单位），`Err` 表示范围无法满足。
+fn parse_byte_range(value: &str, total: u64) -> Result<Option<(u64, u64)>, ()> {
+    let Some(spec) = value.trim().strip_prefix("bytes=") else {
+        return Ok(None);
+    };
+    if spec.contains(',') {
+        return Ok(None);
+    }
+    let (first, last) = spec.trim().split_once('-').ok_or(())?;
+    let (start, end) = match (first.trim(), last.trim()) {
+        ("", "") => return Err(()),
+        // 后缀形式：最后 N 字节
+        ("", suffix) => {
+            let n: u64 = suffix.parse().map_err(|_| ())?;
+            if n == 0 || total == 0 {
+                return Err(());
+            }
+            (total.saturating_sub(n), total - 1)
+        }
+        (start, "") => (start.parse().map_err(|_| ())?, total.saturating_sub(1)),
+        (start, end) => {
+            let start: u64 = start.parse().map_err(|_| ())?;
+            let end: u64 = end.parse().map_err(|_| ())?;
+            if end < start {
+                return Err(());
+            }
+            (start, end.min(total.saturating_sub(1)))
+        }
+    };
+    if start >= total {
+        return Err(());
+    }
+    Ok(Some((start, end)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GroupLogQuery {
+    /// 每个服务返回的行数
+    pub tail: Option<usize>,
+}
+
+/// 分组内单个服务的日志片段
+#[derive(Debug, Serialize)]
+pub struct GroupLogSection {
+    pub id: String,
+    pub name: String,
+    pub lines: Vec<String>,
+}
+
+/// 分组日志快照响应
+#[derive(Debug, Serialize)]
+pub struct GroupLogsResponse {
+    pub group: String,
+    pub services: Vec<GroupLogSection>,
+}
+
+/// 获取分组内所有服务的最近日志（按服务分段，非实时）
+#[instrument(skip_all)]
+pub async fn get_group_logs(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthInfo>,
+    Path(group_id): Path<String>,
+    Query(query): Query<GroupLogQuery>,
+) -> Result<Json<GroupLogsResponse>, ApiError> {
+    auth.require_scope(api_key_scopes::LOGS)?;
+
+    let lines = clamp_tail_lines(query.tail);
+    // 包含子分组的服务；只保留调用者有权访问的
+    let members: Vec<_> = state
+        .manager
+        .group_services(&group_id)
+        .await?
+        .into_iter()
+        .filter(|s| auth.can_read(&s.id))
+        .collect();
+
+    // 逐个读取日志文件是阻塞 IO，整体放到 blocking 线程
+    let manager = state.manager.clone();
+    let services = tokio::task::spawn_blocking(move || {
+        members
+            .into_iter()
+            .map(|svc| {
+                Ok(GroupLogSection {
+                    lines: manager.tail_logs(&svc.id, lines)?,
+                    id: svc.id,
+                    name: svc.name,
+                })
+            })
+            .collect::<Result<Vec<_>, ServiceError>>()
+    })
+    .await
+    .map_err(|e| ApiError::from(ServiceError::Other(e.to_string())))??;
+
+    Ok(Json(GroupLogsResponse {
+        group: group_id,
+        services,
+    }))
 }
 
 fn clamp_tail_lines(tail: Option<usize>) -> usize {
@@ -188,9 +484,87 @@ fn clamp_tail_bytes(tail: Option<usize>) -> usize {
     tail.unwrap_or(DEFAULT_TAIL_BYTES).min(MAX_TAIL_BYTES)
 }
 
+fn clamp_stream_tail_bytes(tail: Option<usize>) -> usize {
+    tail.unwrap_or(DEFAULT_TAIL_BYTES)
+        .min(MAX_STREAM_TAIL_BYTES)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use hypercraft_core::{ServiceAccessLevel, ServiceManifest};
+    use tempfile::TempDir;
+
+    /// 建好分组与成员服务的测试状态
+    async fn group_state(dir: &TempDir, members: &[(&str, &str)]) -> AppState {
+        let state = AppState::for_tests(dir.path());
+        for (id, group) in members {
+            let manifest: ServiceManifest = serde_json::from_value(json!({
+                "id": id, "name": id, "command": "sleep", "group": group
+            }))
+            .unwrap();
+            state.manager.create_service(manifest).await.unwrap();
+        }
+        state
+    }
+
+    async fn group_log_ids(state: &AppState, auth: AuthInfo, group: &str) -> Vec<String> {
+        let Json(res) = get_group_logs(
+            State(state.clone()),
+            Extension(auth),
+            Path(group.to_string()),
+            Query(GroupLogQuery { tail: None }),
+        )
+        .await
+        .unwrap();
+        res.services.into_iter().map(|s| s.id).collect()
+    }
+
+    #[tokio::test]
+    async fn group_logs_only_include_readable_services() {
+        let dir = TempDir::new().unwrap();
+        let state = group_state(&dir, &[("web", "prod"), ("db", "prod"), ("misc", "dev")]).await;
+        state
+            .manager
+            .create_group("prod".into(), "Prod".into(), None, None)
+            .await
+            .unwrap();
+
+        let viewer = AuthInfo::test_user(vec![
+            hypercraft_core::ServicePermission::new("web", ServiceAccessLevel::Read),
+            hypercraft_core::ServicePermission::new("misc", ServiceAccessLevel::Read),
+        ]);
+        assert_eq!(group_log_ids(&state, viewer, "prod").await, vec!["web"]);
+        assert!(group_log_ids(&state, AuthInfo::test_user(vec![]), "prod")
+            .await
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn group_logs_include_nested_groups() {
+        let dir = TempDir::new().unwrap();
+        let state = group_state(&dir, &[("api", "prod"), ("web", "prod-web")]).await;
+        state
+            .manager
+            .create_group("prod".into(), "Prod".into(), None, None)
+            .await
+            .unwrap();
+        state
+            .manager
+            .create_group("prod-web".into(), "Web".into(), None, Some("prod".into()))
+            .await
+            .unwrap();
+
+        let viewer = AuthInfo::test_user(vec![
+            hypercraft_core::ServicePermission::new("api", ServiceAccessLevel::Read),
+            hypercraft_core::ServicePermission::new("web", ServiceAccessLevel::Read),
+        ]);
+        assert_eq!(
+            group_log_ids(&state, viewer.clone(), "prod").await,
+            vec!["api", "web"]
+        );
+        assert_eq!(group_log_ids(&state, viewer, "prod-web").await, vec!["web"]);
+    }
 
     #[test]
     fn tail_lines_are_clamped() {
@@ -213,5 +587,30 @@ mod tests {
             clamp_tail_bytes(Some(usize::MAX)),
             MAX_TAIL_BYTES
         );
+        // 流式模式允许更大的 tail，但同样有上限
+        assert_eq!(
+            clamp_stream_tail_bytes(Some(5 * 1024 * 1024)),
+            5 * 1024 * 1024
+        );
+        assert_eq!(
+            clamp_stream_tail_bytes(Some(usize::MAX)),
+            MAX_STREAM_TAIL_BYTES
+        );
+    }
+
+    #[test]
+    fn byte_ranges_are_parsed() {
+        assert_eq!(parse_byte_range("bytes=0-99", 1000), Ok(Some((0, 99))));
+        assert_eq!(parse_byte_range("bytes=900-", 1000), Ok(Some((900, 999))));
+        assert_eq!(parse_byte_range("bytes=-100", 1000), Ok(Some((900, 999))));
+        assert_eq!(parse_byte_range("bytes=-5000", 1000), Ok(Some((0, 999))));
+        assert_eq!(parse_byte_range("bytes=500-5000", 1000), Ok(Some((500, 999))));
+        // 多段与未知单位忽略，返回完整文件
+        assert_eq!(parse_byte_range("bytes=0-1,5-6", 1000), Ok(None));
+        assert_eq!(parse_byte_range("lines=1-2", 1000), Ok(None));
+        assert_eq!(parse_byte_range("bytes=1000-", 1000), Err(()));
+        assert_eq!(parse_byte_range("bytes=5-1", 1000), Err(()));
+        assert_eq!(parse_byte_range("bytes=-0", 1000), Err(()));
+        assert_eq!(parse_byte_range("bytes=0-0", 0), Err(()));
     }
 }
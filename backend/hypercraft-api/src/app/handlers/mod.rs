@@ -1,11 +1,16 @@
 mod agent;
 mod api_keys;
 mod attach;
+mod audit;
 mod auth;
+mod config;
+mod events;
 mod groups;
 mod health;
 mod logs;
+mod secrets;
 mod services;
+mod sessions;
 mod stats;
 mod two_factor;
 mod users;
@@ -19,26 +24,36 @@ pub use agent::{
     agent_update_service,
 };
 pub use api_keys::{
-    create_api_key, get_api_key, list_api_keys, reveal_api_key_secret, revoke_api_key,
-    rotate_api_key, update_api_key,
+    create_api_key, create_user_api_key, get_api_key, list_api_keys, list_user_api_keys,
+    reveal_api_key_secret, revoke_api_key, revoke_user_api_key, rotate_api_key, update_api_key,
 };
-pub use attach::attach_service;
+pub use attach::{attach_service, download_recording, list_recordings};
+pub use audit::list_audit;
 pub use auth::{devtoken_login, get_me, login, logout, refresh};
+pub use config::{export_config, import_config};
+pub use events::stream_events;
 pub use groups::{
-    create_group, delete_group, list_groups, reorder_groups, reorder_services, update_group,
-    update_service_group, update_service_tags,
+    bulk_tag_services, create_group, delete_group, list_groups, reorder_groups, reorder_services,
+    restart_group, start_group, stop_group, update_group, update_service_group,
+    update_service_tags,
 };
-pub use health::{handler_404, health};
-pub use logs::{download_log_file, get_logs};
+pub use health::{handler_404, health_live, health_ready};
+pub use logs::{clear_logs, download_log_file, get_group_logs, get_logs, search_logs};
+pub use secrets::{delete_secret, list_secrets, set_secret};
 pub use services::{
-    create_service, delete_service, get_schedule, get_service, get_status, kill_service,
-    list_services, restart_service, shutdown_service, start_service, stop_service, update_schedule,
-    update_service, validate_cron,
+    batch_services, clone_service, create_service, delete_service, get_schedule, get_service, get_service_stats, get_status, kill_service,
+    list_archived_services, list_schedule_jobs, list_services, pause_scheduler, restart_service, restore_archived_service, resume_scheduler, run_schedule, send_service_input, shutdown_service, signal_service, start_service, stop_service,
+    update_schedule,
+    update_service, validate_schedule, validate_service, TOTAL_COUNT_HEADER,
+};
+pub use sessions::{list_user_sessions, revoke_user_session};
+pub use stats::{get_process_stats, get_system_stats, stream_system_stats};
+pub use two_factor::{
+    disable_2fa, enable_2fa, get_2fa_status, regenerate_recovery_codes, setup_2fa, verify_user_2fa,
 };
-pub use stats::get_system_stats;
-pub use two_factor::{disable_2fa, enable_2fa, setup_2fa, verify_user_2fa};
 pub use users::{
     add_user_service, change_password, create_user, delete_user, get_user,
-    list_assignable_services, list_users, remove_user_service, set_user_services, update_user,
+    list_assignable_services, list_users, remove_user_service, revoke_user_tokens,
+    set_user_services, update_user,
 };
 pub use web::create_web_session;
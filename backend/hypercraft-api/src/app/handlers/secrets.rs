@@ -0,0 +1,45 @@
+//! 服务密钥管理（仅超级管理员）
+//! 值只写不读，服务通过 env 中的 `${SECRET:key}` 引用。
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+use serde::Deserialize;
+
+use super::super::error::ApiError;
+use super::super::middleware::RequireSuperAdmin;
+use super::super::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct SetSecretRequest {
+    pub value: String,
+}
+
+/// GET /secrets - 列出密钥名
+pub async fn list_secrets(
+    State(state): State<AppState>,
+    RequireSuperAdmin(_): RequireSuperAdmin,
+) -> Result<Json<Vec<String>>, ApiError> {
+    Ok(Json(state.user_manager.list_secret_keys()?))
+}
+
+/// PUT /secrets/:key - 写入或覆盖密钥
+pub async fn set_secret(
+    State(state): State<AppState>,
+    RequireSuperAdmin(_): RequireSuperAdmin,
+    Path(key): Path<String>,
+    Json(req): Json<SetSecretRequest>,
+) -> Result<StatusCode, ApiError> {
+    state.user_manager.set_secret(&key, &req.value)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// DELETE /secrets/:key - 删除密钥
+pub async fn delete_secret(
+    State(state): State<AppState>,
+    RequireSuperAdmin(_): RequireSuperAdmin,
+    Path(key): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    state.user_manager.delete_secret(&key)?;
+    Ok(StatusCode::NO_CONTENT)
+}
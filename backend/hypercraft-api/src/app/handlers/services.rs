@@ -1,61 +1,138 @@
-use axum::extract::{Path, State};
+use axum::extract::{ConnectInfo, Path, Query, State};
 use axum::http::StatusCode;
 use axum::Extension;
 use axum::Json;
-use chrono::Utc;
-use hypercraft_core::{Schedule, ServiceManifest, ServiceScheduler, ServiceStatus, ServiceSummary};
+use hypercraft_core::{
+    parse_signal, ArchivedService, LifecycleStats, ManifestValidation, Schedule, ScheduleJobInfo, ScheduleKind,
+    ServiceAccessLevel, ServiceError, ServiceListQuery, ServiceManifest, ServiceScheduler,
+    ServiceStatus, ServiceSummary,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::str::FromStr;
+use std::net::SocketAddr;
 use tracing::instrument;
 
-use crate::app::middleware::{AuthInfo, ServicePermission};
+use crate::app::error::ErrorCode;
+use crate::app::middleware::{AuthInfo, RequireAdmin, ServicePermission};
 use crate::app::{ApiError, AppState};
 use hypercraft_core::api_key_scopes;
 
+/// 过滤后、分页前的条目总数（分页用）
+pub const TOTAL_COUNT_HEADER: &str = "x-total-count";
+
+/// 服务列表响应：分页后的条目 + 总数响应头
+pub type ServiceListResponse = ([(&'static str, String); 1], Json<Vec<ServiceSummary>>);
+
+/// GET /services - 支持 group/state/tag/q 过滤、sort 排序与 limit/offset 分页
 #[instrument(skip_all)]
 pub async fn list_services(
     State(state): State<AppState>,
     Extension(auth): Extension<AuthInfo>,
-) -> Result<Json<Vec<ServiceSummary>>, ApiError> {
+    Query(query): Query<ServiceListQuery>,
+) -> Result<ServiceListResponse, ApiError> {
     auth.require_scope(api_key_scopes::READ)?;
     let services = state.manager.list_services().await?;
 
-    // 默认服务页按 service_ids 展示；控制权限由 can_access_service 独立判断
-    let filtered = services
+    // 默认服务页按 service_ids 展示；读写权限由 can_read / can_control 独立判断
+    let listed = services
         .into_iter()
         .filter(|s| auth.is_service_listed(&s.id))
         .collect();
+    let (total, page) = query.apply(listed);
 
-    Ok(Json(filtered))
+    Ok(([(TOTAL_COUNT_HEADER, total.to_string())], Json(page)))
 }
 
 #[instrument(skip_all)]
 pub async fn create_service(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Extension(auth): Extension<AuthInfo>,
     Json(payload): Json<ServiceManifest>,
 ) -> Result<Json<ServiceManifest>, ApiError> {
     // 管理员 JWT 或带 manage 的 API Key
     auth.require_manage_create()?;
-    let svc = state.manager.create_service(payload).await?;
+    auth.require_secret_refs(&payload, None)?;
+    let id = payload.id.clone();
+    let result = state.manager.create_service(payload).await;
+    state
+        .audit
+        .record(&auth, addr, "service.create", &id, &result)
+        .await;
+    let svc = result?;
+    register_created_service(&state, &auth, &svc).await?;
+    Ok(Json(svc))
+}
+
+/// POST /services/validate - 干跑校验 manifest，返回全部问题，不创建任何内容
+#[instrument(skip_all)]
+pub async fn validate_service(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthInfo>,
+    Json(payload): Json<ServiceManifest>,
+) -> Result<Json<ManifestValidation>, ApiError> {
+    auth.require_manage_create()?;
+    Ok(Json(state.manager.validate_manifest(&payload).await))
+}
 
+#[derive(Debug, Deserialize)]
+pub struct CloneServiceRequest {
+    /// 新服务 ID
+    pub id: String,
+    /// 覆盖源 manifest 的字段（name、args、cwd 等）
+    #[serde(default)]
+    pub overrides: serde_json::Value,
+}
+
+/// POST /services/:id/clone - 以现有服务为模板创建新服务
+#[instrument(skip_all)]
+pub async fn clone_service(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    ServicePermission { auth, service_id }: ServicePermission,
+    Json(payload): Json<CloneServiceRequest>,
+) -> Result<Json<ServiceManifest>, ApiError> {
+    auth.require_manage_create()?;
+    let source = state.manager.load_manifest(&service_id).await?;
+    let manifest = state
+        .manager
+        .cloned_manifest(&source, &payload.id, payload.overrides)?;
+    // overrides 可能新增 ${SECRET:..} 引用，按新建/更新同样的规则检查
+    auth.require_secret_refs(&manifest, Some(&source))?;
+    let result = state.manager.create_service(manifest).await;
+    let target = format!("{service_id} -> {}", payload.id);
+    state
+        .audit
+        .record(&auth, addr, "service.clone", &target, &result)
+        .await;
+    let svc = result?;
+    register_created_service(&state, &auth, &svc).await?;
+    Ok(Json(svc))
+}
+
+/// 新建服务后的收尾：写回创建者的服务权限并同步调度任务
+async fn register_created_service(
+    state: &AppState,
+    auth: &AuthInfo,
+    svc: &ServiceManifest,
+) -> Result<(), ApiError> {
     // 非超管用户 JWT 创建后写回 User.service_ids，让新服务出现在默认列表；API Key 无需白名单
     if !auth.is_super_admin() && !auth.is_api_key() {
         state
             .user_manager
-            .add_service_permission(&auth.claims.sub, &svc.id)
+            .add_service_permission(&auth.claims.sub, &svc.id, ServiceAccessLevel::Control)
             .await?;
     }
 
     // 同步调度任务
-    if let Some(schedule) = &svc.schedule {
-        if let Err(e) = state.scheduler.upsert_schedule(&svc.id, schedule).await {
-            tracing::warn!(service_id = %svc.id, error = %e, "failed to setup schedule");
-        }
+    if let Err(e) = state
+        .scheduler
+        .sync_schedules(&svc.id, &svc.schedules)
+        .await
+    {
+        tracing::warn!(service_id = %svc.id, error = %e, "failed to setup schedule");
     }
-
-    Ok(Json(svc))
+    Ok(())
 }
 
 #[instrument(skip_all)]
@@ -72,45 +149,136 @@ pub async fn get_service(
     })))
 }
 
+/// 删除服务查询参数
+#[derive(Debug, Default, Deserialize)]
+pub struct DeleteServiceQuery {
+    /// 永久删除（仅管理员）；默认只把服务目录移入 data_dir/archive，可恢复
+    #[serde(default)]
+    pub purge: bool,
+    /// 永久删除时保留日志：归档到 data_dir/deleted/<id>/logs
+    #[serde(default)]
+    pub keep_logs: bool,
+}
+
+/// DELETE /services/:id - 默认软删除（归档，可通过 /services/archived 恢复）；`purge=true` 永久删除
 #[instrument(skip_all)]
 pub async fn delete_service(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Extension(auth): Extension<AuthInfo>,
     Path(id): Path<String>,
+    Query(query): Query<DeleteServiceQuery>,
 ) -> Result<StatusCode, ApiError> {
     // 管理员 JWT 可管理全部服务；API Key 需要 manage scope
     auth.require_manage_service(&id)?;
+    if query.purge && !auth.is_admin() {
+        return Err(ApiError::forbidden("永久删除服务需要管理员权限"));
+    }
 
     // 移除调度任务
     let _ = state.scheduler.remove_schedule(&id).await;
 
-    state.manager.delete_service(&id).await?;
+    if query.purge {
+        let result = state.manager.delete_service(&id, query.keep_logs).await;
+        state
+            .audit
+            .record(&auth, addr, "service.delete", &id, &result)
+            .await;
+        result?;
+    } else {
+        let result = state.manager.archive_service(&id).await;
+        state
+            .audit
+            .record(&auth, addr, "service.archive", &id, &result)
+            .await;
+        result?;
+    }
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// GET /services/archived - 列出软删除的服务，最近归档的在前（管理员）
+#[instrument(skip_all)]
+pub async fn list_archived_services(
+    State(state): State<AppState>,
+    RequireAdmin(_): RequireAdmin,
+) -> Result<Json<Vec<ArchivedService>>, ApiError> {
+    Ok(Json(state.manager.list_archived().await?))
+}
+
+/// POST /services/archived/:archive_id/restore - 恢复归档的服务并重新挂上调度（管理员）
+#[instrument(skip_all)]
+pub async fn restore_archived_service(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    RequireAdmin(auth): RequireAdmin,
+    Path(archive_id): Path<String>,
+) -> Result<Json<ServiceManifest>, ApiError> {
+    let result = state.manager.restore_archived(&archive_id).await;
+    state
+        .audit
+        .record(&auth, addr, "service.restore", &archive_id, &result)
+        .await;
+    let manifest = result?;
+    if let Err(e) = state
+        .scheduler
+        .sync_schedules(&manifest.id, &manifest.schedules)
+        .await
+    {
+        tracing::warn!(service_id = %manifest.id, error = %e, "无法恢复计划任务");
+    }
+    Ok(Json(manifest))
+}
+
+/// PUT /services/:id - 幂等 upsert：服务不存在时创建（201），存在时更新并保留 created_at（200），
+/// 均返回保存后的 manifest
 #[instrument(skip_all)]
 pub async fn update_service(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Extension(auth): Extension<AuthInfo>,
     Path(id): Path<String>,
     Json(payload): Json<ServiceManifest>,
-) -> Result<StatusCode, ApiError> {
+) -> Result<(StatusCode, Json<ServiceManifest>), ApiError> {
+    if !state.manager.service_exists(&id).await? {
+        // 与 POST /services 相同的权限；路径与 manifest 中的 ID 必须一致
+        auth.require_manage_create()?;
+        if payload.id != id {
+            return Err(ServiceError::InvalidId.into());
+        }
+        auth.require_secret_refs(&payload, None)?;
+        let result = state.manager.create_service(payload).await;
+        state
+            .audit
+            .record(&auth, addr, "service.create", &id, &result)
+            .await;
+        let svc = result?;
+        register_created_service(&state, &auth, &svc).await?;
+        return Ok((StatusCode::CREATED, Json(svc)));
+    }
+
     // 管理员 JWT 可管理全部服务；API Key 需要 manage scope
     auth.require_manage_service(&id)?;
-
-    state.manager.update_service(&id, payload.clone()).await?;
-
-    // 同步调度任务
-    if let Some(schedule) = &payload.schedule {
-        if let Err(e) = state.scheduler.upsert_schedule(&id, schedule).await {
-            tracing::warn!(service_id = %id, error = %e, "无法更新计划任务");
-        }
-    } else {
-        // 移除调度任务（如果存在）
-        let _ = state.scheduler.remove_schedule(&id).await;
+    let previous = state.manager.load_manifest(&id).await?;
+    auth.require_secret_refs(&payload, Some(&previous))?;
+
+    let result = state.manager.update_service(&id, payload).await;
+    state
+        .audit
+        .record(&auth, addr, "service.update", &id, &result)
+        .await;
+    result?;
+
+    // 同步调度任务（重新读取以拿到补齐的调度 ID）
+    let manifest = state.manager.load_manifest(&id).await?;
+    if let Err(e) = state
+        .scheduler
+        .sync_schedules(&id, &manifest.schedules)
+        .await
+    {
+        tracing::warn!(service_id = %id, error = %e, "无法更新计划任务");
     }
 
-    Ok(StatusCode::NO_CONTENT)
+    Ok((StatusCode::OK, Json(manifest)))
 }
 
 #[instrument(skip_all)]
@@ -119,6 +287,7 @@ pub async fn start_service(
     ServicePermission { auth, service_id }: ServicePermission,
 ) -> Result<Json<ServiceStatus>, ApiError> {
     auth.require_scope(api_key_scopes::CONTROL)?;
+    auth.require_control(&service_id)?;
     let status = state.manager.start(&service_id).await?;
     Ok(Json(status))
 }
@@ -129,6 +298,7 @@ pub async fn stop_service(
     ServicePermission { auth, service_id }: ServicePermission,
 ) -> Result<Json<ServiceStatus>, ApiError> {
     auth.require_scope(api_key_scopes::CONTROL)?;
+    auth.require_control(&service_id)?;
     let status = state.manager.stop(&service_id).await?;
     Ok(Json(status))
 }
@@ -139,6 +309,7 @@ pub async fn shutdown_service(
     ServicePermission { auth, service_id }: ServicePermission,
 ) -> Result<Json<ServiceStatus>, ApiError> {
     auth.require_scope(api_key_scopes::CONTROL)?;
+    auth.require_control(&service_id)?;
     let status = state.manager.shutdown(&service_id).await?;
     Ok(Json(status))
 }
@@ -149,6 +320,7 @@ pub async fn kill_service(
     ServicePermission { auth, service_id }: ServicePermission,
 ) -> Result<Json<ServiceStatus>, ApiError> {
     auth.require_scope(api_key_scopes::CONTROL)?;
+    auth.require_control(&service_id)?;
     let status = state.manager.kill(&service_id).await?;
     Ok(Json(status))
 }
@@ -159,10 +331,63 @@ pub async fn restart_service(
     ServicePermission { auth, service_id }: ServicePermission,
 ) -> Result<Json<ServiceStatus>, ApiError> {
     auth.require_scope(api_key_scopes::CONTROL)?;
+    auth.require_control(&service_id)?;
     let status = state.manager.restart(&service_id).await?;
     Ok(Json(status))
 }
 
+/// POST /services/:id/input 请求体
+#[derive(Debug, Deserialize)]
+pub struct SendInputRequest {
+    /// 原样写入 stdin，需要回车时请自带换行
+    pub data: String,
+}
+
+/// POST /services/:id/input - 不建立 attach 直接向服务 stdin 写入（如 "save-all\n"）
+#[instrument(skip_all)]
+pub async fn send_service_input(
+    State(state): State<AppState>,
+    ServicePermission { auth, service_id }: ServicePermission,
+    Json(payload): Json<SendInputRequest>,
+) -> Result<StatusCode, ApiError> {
+    auth.require_scope(api_key_scopes::CONTROL)?;
+    auth.require_control(&service_id)?;
+    if payload.data.is_empty() {
+        return Err(ApiError::bad_request("data 不能为空"));
+    }
+    state
+        .manager
+        .send_input(&service_id, payload.data.into_bytes())
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /services/:id/signal 请求体
+#[derive(Debug, Deserialize)]
+pub struct SendSignalRequest {
+    /// INT / TERM / HUP / USR1（可带 SIG 前缀）
+    pub signal: String,
+}
+
+/// POST /services/:id/signal - 向服务进程发送信号（如 HUP 触发配置重载）
+#[instrument(skip_all)]
+pub async fn signal_service(
+    State(state): State<AppState>,
+    ServicePermission { auth, service_id }: ServicePermission,
+    Json(payload): Json<SendSignalRequest>,
+) -> Result<StatusCode, ApiError> {
+    auth.require_scope(api_key_scopes::CONTROL)?;
+    auth.require_control(&service_id)?;
+    let signal = parse_signal(&payload.signal).ok_or_else(|| {
+        ApiError::bad_request(format!(
+            "unknown signal {}, expected one of INT, TERM, HUP, USR1, KILL",
+            payload.signal
+        ))
+    })?;
+    state.manager.send_signal(&service_id, signal).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 #[instrument(skip_all)]
 pub async fn get_status(
     State(state): State<AppState>,
@@ -173,19 +398,166 @@ pub async fn get_status(
     Ok(Json(status))
 }
 
+/// GET /services/:id/stats - 持久化的生命周期计数（启动/崩溃/自动重启次数、累计运行时长）
+#[instrument(skip_all)]
+pub async fn get_service_stats(
+    State(state): State<AppState>,
+    ServicePermission { auth, service_id }: ServicePermission,
+) -> Result<Json<LifecycleStats>, ApiError> {
+    auth.require_scope(api_key_scopes::READ)?;
+    let stats = state.manager.lifecycle_stats(&service_id).await?;
+    Ok(Json(stats))
+}
+
+/// 批量操作类型
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchAction {
+    Start,
+    Stop,
+    Restart,
+}
+
+/// 批量操作请求：指定 ids 或 all=true
+#[derive(Debug, Deserialize)]
+pub struct BatchRequest {
+    pub action: BatchAction,
+    #[serde(default)]
+    pub ids: Vec<String>,
+    #[serde(default)]
+    pub all: bool,
+}
+
+/// 单个服务的批量操作结果
+#[derive(Debug, Serialize)]
+pub struct BatchItemResult {
+    pub id: String,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ServiceStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchResponse {
+    pub action: BatchAction,
+    pub results: Vec<BatchItemResult>,
+}
+
+/// 批量启动/停止/重启服务，并发执行；部分失败时返回 207 与逐项结果
+#[instrument(skip_all)]
+pub async fn batch_services(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthInfo>,
+    Json(payload): Json<BatchRequest>,
+) -> Result<(StatusCode, Json<BatchResponse>), ApiError> {
+    auth.require_scope(api_key_scopes::CONTROL)?;
+
+    // all=true 时只包含有权限的服务；显式 ids 中无权限的逐项报错
+    let ids: Vec<String> = if payload.all {
+        state
+            .manager
+            .list_services()
+            .await?
+            .into_iter()
+            .map(|s| s.id)
+            .filter(|id| auth.can_control(id))
+            .collect()
+    } else {
+        let mut seen = std::collections::HashSet::new();
+        let mut ids = payload.ids;
+        ids.retain(|id| seen.insert(id.clone()));
+        ids
+    };
+    if ids.is_empty() && !payload.all {
+        return Err(ApiError::bad_request("ids 不能为空（或指定 all: true）"));
+    }
+
+    let manager = &state.manager;
+    let auth = &auth;
+    let results = futures::future::join_all(ids.into_iter().map(|id| async move {
+        if !auth.can_control(&id) {
+            return BatchItemResult {
+                id,
+                ok: false,
+                status: None,
+                error: Some("没有该服务的控制权限".into()),
+            };
+        }
+        let result = match payload.action {
+            BatchAction::Start => manager.start(&id).await,
+            BatchAction::Stop => manager.stop(&id).await,
+            BatchAction::Restart => manager.restart(&id).await,
+        };
+        match result {
+            Ok(status) => BatchItemResult {
+                id,
+                ok: true,
+                status: Some(status),
+                error: None,
+            },
+            Err(e) => BatchItemResult {
+                id,
+                ok: false,
+                status: None,
+                error: Some(e.to_string()),
+            },
+        }
+    }))
+    .await;
+
+    let code = if results.iter().all(|r| r.ok) {
+        StatusCode::OK
+    } else {
+        StatusCode::MULTI_STATUS
+    };
+    Ok((
+        code,
+        Json(BatchResponse {
+            action: payload.action,
+            results,
+        }),
+    ))
+}
+
+/// 单条调度及其下次运行时间
+#[derive(Debug, Serialize)]
+pub struct ScheduleEntry {
+    #[serde(flatten)]
+    pub schedule: Schedule,
+    pub next_run: Option<String>,
+}
+
 /// Schedule 响应结构
 #[derive(Debug, Serialize)]
 pub struct ScheduleResponse {
-    pub schedule: Option<Schedule>,
-    pub next_run: Option<String>,
+    pub schedules: Vec<ScheduleEntry>,
 }
 
-/// Schedule 更新请求
+/// Schedule 更新请求：整体替换调度列表；兼容旧版单个 `schedule` 字段
 #[derive(Debug, Deserialize)]
 pub struct UpdateScheduleRequest {
+    #[serde(default)]
+    pub schedules: Option<Vec<Schedule>>,
+    #[serde(default)]
     pub schedule: Option<Schedule>,
 }
 
+fn schedule_entries(schedules: Vec<Schedule>) -> Vec<ScheduleEntry> {
+    schedules
+        .into_iter()
+        .map(|schedule| {
+            let next_run = Some(&schedule)
+                .filter(|s| s.enabled)
+                .and_then(|s| ServiceScheduler::schedule_runs(s, 1).ok())
+                .and_then(|runs| runs.into_iter().next())
+                .map(|dt| dt.to_rfc3339());
+            ScheduleEntry { schedule, next_run }
+        })
+        .collect()
+}
+
 /// 获取服务的定时配置
 #[instrument(skip_all)]
 pub async fn get_schedule(
@@ -194,16 +566,8 @@ pub async fn get_schedule(
 ) -> Result<Json<ScheduleResponse>, ApiError> {
     auth.require_scope(api_key_scopes::READ)?;
     let manifest = state.manager.load_manifest(&service_id).await?;
-    let next_run = manifest
-        .schedule
-        .as_ref()
-        .filter(|s| s.enabled && !s.cron.is_empty())
-        .and_then(|s| ServiceScheduler::next_run(&s.cron).ok().flatten())
-        .map(|dt| dt.to_rfc3339());
-
     Ok(Json(ScheduleResponse {
-        schedule: manifest.schedule,
-        next_run,
+        schedules: schedule_entries(manifest.schedules),
     }))
 }
 
@@ -211,6 +575,7 @@ pub async fn get_schedule(
 #[instrument(skip_all)]
 pub async fn update_schedule(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Extension(auth): Extension<AuthInfo>,
     Path(id): Path<String>,
     Json(payload): Json<UpdateScheduleRequest>,
@@ -218,81 +583,241 @@ pub async fn update_schedule(
     // 管理员 JWT 或 manage scope
     auth.require_manage_service(&id)?;
 
-    // 验证 cron 表达式
-    if let Some(schedule) = &payload.schedule {
-        if schedule.enabled && !schedule.cron.is_empty() {
-            ServiceScheduler::validate_cron(&schedule.cron).map_err(|e| {
-                ApiError::bad_request(format!("invalid cron expression: {}", e))
-            })?;
+    let schedules = payload
+        .schedules
+        .unwrap_or_else(|| payload.schedule.into_iter().collect());
+
+    // 验证调度配置与时区
+    for schedule in &schedules {
+        let empty_cron = schedule.kind.is_none() && schedule.cron.is_empty();
+        if schedule.enabled && !empty_cron {
+            ServiceScheduler::validate_schedule(schedule)
+                .map_err(|e| ApiError::bad_request(format!("invalid schedule: {}", e)))?;
+        }
+        if let Some(tz) = schedule
+            .timezone
+            .as_deref()
+            .filter(|tz| !tz.trim().is_empty())
+        {
+            ServiceScheduler::validate_timezone(tz)
+                .map_err(|e| ApiError::bad_request(format!("invalid timezone: {}", e)))?;
         }
     }
 
-    // 加载并更新 manifest
+    // 加载并更新 manifest（保存时补齐调度 ID）
     let mut manifest = state.manager.load_manifest(&id).await?;
-    manifest.schedule = payload.schedule.clone();
-    state.manager.update_service(&id, manifest).await?;
+    manifest.schedules = schedules;
+    let result = state.manager.update_service(&id, manifest).await;
+    state
+        .audit
+        .record(&auth, addr, "service.update_schedule", &id, &result)
+        .await;
+    result?;
+    let manifest = state.manager.load_manifest(&id).await?;
 
     // 同步调度器
-    if let Some(schedule) = &payload.schedule {
-        state.scheduler.upsert_schedule(&id, schedule).await?;
-    } else {
-        state.scheduler.remove_schedule(&id).await?;
-    }
-
-    // 计算下次运行时间
-    let next_run = payload
-        .schedule
-        .as_ref()
-        .filter(|s| s.enabled && !s.cron.is_empty())
-        .and_then(|s| ServiceScheduler::next_run(&s.cron).ok().flatten())
-        .map(|dt| dt.to_rfc3339());
+    state
+        .scheduler
+        .sync_schedules(&id, &manifest.schedules)
+        .await?;
 
     Ok(Json(ScheduleResponse {
-        schedule: payload.schedule,
-        next_run,
+        schedules: schedule_entries(manifest.schedules),
+    }))
+}
+
+/// 全部生效中的调度任务
+#[derive(Debug, Serialize)]
+pub struct ScheduleJobsResponse {
+    /// 调度器是否已全局暂停（暂停时列表为空）
+    pub paused: bool,
+    pub schedules: Vec<ScheduleJobInfo>,
+}
+
+/// GET /schedules - 列出全部生效中的调度任务：后续运行时间、最近触发时间与执行结果（管理员）
+#[instrument(skip_all)]
+pub async fn list_schedule_jobs(
+    State(state): State<AppState>,
+    RequireAdmin(_): RequireAdmin,
+) -> Json<ScheduleJobsResponse> {
+    Json(ScheduleJobsResponse {
+        paused: state.scheduler.is_paused(),
+        schedules: state.scheduler.list_jobs().await,
+    })
+}
+
+/// 调度器暂停状态
+#[derive(Debug, Serialize)]
+pub struct SchedulerStatusResponse {
+    pub paused: bool,
+}
+
+/// POST /scheduler/pause - 暂停全部定时任务，保留各服务的调度配置（管理员）
+#[instrument(skip_all)]
+pub async fn pause_scheduler(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    RequireAdmin(auth): RequireAdmin,
+) -> Json<SchedulerStatusResponse> {
+    state.scheduler.pause().await;
+    state
+        .audit
+        .record(
+            &auth,
+            addr,
+            "scheduler.pause",
+            "scheduler",
+            &Ok::<_, ServiceError>(()),
+        )
+        .await;
+    Json(SchedulerStatusResponse { paused: true })
+}
+
+/// POST /scheduler/resume - 按 manifest 重新挂上全部定时任务（管理员）
+#[instrument(skip_all)]
+pub async fn resume_scheduler(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    RequireAdmin(auth): RequireAdmin,
+) -> Result<Json<SchedulerStatusResponse>, ApiError> {
+    let result = state.scheduler.resume().await;
+    state
+        .audit
+        .record(&auth, addr, "scheduler.resume", "scheduler", &result)
+        .await;
+    result?;
+    Ok(Json(SchedulerStatusResponse {
+        paused: state.scheduler.is_paused(),
     }))
 }
 
-/// 验证 cron 表达式
+/// POST /services/:id/schedule/run 查询参数
+#[derive(Debug, Deserialize)]
+pub struct RunScheduleQuery {
+    /// 调度条目 ID；服务只有一条调度时可省略
+    pub entry: Option<String>,
+}
+
+/// POST /services/:id/schedule/run - 立即执行一次调度动作（含"已运行则跳过"判断），返回执行后的状态
+#[instrument(skip_all)]
+pub async fn run_schedule(
+    State(state): State<AppState>,
+    ServicePermission { auth, service_id }: ServicePermission,
+    Query(query): Query<RunScheduleQuery>,
+) -> Result<Json<ServiceStatus>, ApiError> {
+    auth.require_scope(api_key_scopes::CONTROL)?;
+    auth.require_control(&service_id)?;
+    let status = state
+        .scheduler
+        .run_now(&service_id, query.entry.as_deref())
+        .await?;
+    Ok(Json(status))
+}
+
+/// 验证调度配置：兼容旧版 `{cron}`，也可传 `kind`
 #[derive(Debug, Deserialize)]
-pub struct ValidateCronRequest {
+pub struct ValidateScheduleRequest {
+    #[serde(default)]
     pub cron: String,
+    #[serde(default)]
+    pub kind: Option<ScheduleKind>,
+    /// 按该时区计算后续运行时间
+    #[serde(default)]
+    pub timezone: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
-pub struct ValidateCronResponse {
+pub struct ValidateScheduleResponse {
     pub valid: bool,
     pub next_runs: Vec<String>,
     pub error: Option<String>,
+    /// 无效时为 `InvalidSchedule`，与错误响应的 `code` 取值一致
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<ErrorCode>,
 }
 
 #[instrument(skip_all)]
-pub async fn validate_cron(
-    Json(payload): Json<ValidateCronRequest>,
-) -> Json<ValidateCronResponse> {
-    match ServiceScheduler::validate_cron(&payload.cron) {
-        Ok(_) => {
-            // 计算接下来5次运行时间
-            let next_runs: Vec<String> = cron::Schedule::from_str(&payload.cron)
-                .map(|schedule| {
-                    schedule
-                        .upcoming(Utc)
-                        .take(5)
-                        .map(|dt| dt.to_rfc3339())
-                        .collect()
-                })
-                .unwrap_or_default();
-
-            Json(ValidateCronResponse {
+pub async fn validate_schedule(
+    Json(payload): Json<ValidateScheduleRequest>,
+) -> Json<ValidateScheduleResponse> {
+    let schedule = Schedule {
+        cron: payload.cron,
+        kind: payload.kind,
+        timezone: payload.timezone,
+        ..Default::default()
+    };
+    // 计算接下来5次运行时间
+    let result = ServiceScheduler::validate_schedule(&schedule)
+        .and_then(|_| ServiceScheduler::schedule_runs(&schedule, 5));
+    match result {
+        Ok(runs) => {
+            let next_runs: Vec<String> = runs.iter().map(|dt| dt.to_rfc3339()).collect();
+
+            Json(ValidateScheduleResponse {
                 valid: true,
                 next_runs,
                 error: None,
+                code: None,
             })
         }
-        Err(e) => Json(ValidateCronResponse {
+        Err(e) => Json(ValidateScheduleResponse {
             valid: false,
             next_runs: vec![],
             error: Some(e.to_string()),
+            code: Some(ErrorCode::InvalidSchedule),
         }),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn clone_cannot_add_secret_refs_without_super_admin() {
+        let dir = TempDir::new().unwrap();
+        let state = AppState::for_tests(dir.path());
+        let source: ServiceManifest = serde_json::from_value(json!({
+            "id": "web", "name": "web", "command": "sleep"
+        }))
+        .unwrap();
+        state.manager.create_service(source).await.unwrap();
+
+        let user = state
+            .user_manager
+            .create_user(hypercraft_core::CreateUserRequest {
+                username: "alice".into(),
+                password: "Str0ng-password!".into(),
+                service_ids: vec![],
+            })
+            .await
+            .unwrap();
+        let mut admin = AuthInfo::test_user(vec![]);
+        admin.claims.sub = user.id;
+        admin.claims.is_admin = true;
+        let clone = |id: &str, overrides: serde_json::Value| {
+            clone_service(
+                State(state.clone()),
+                ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 0))),
+                ServicePermission {
+                    auth: admin.clone(),
+                    service_id: "web".into(),
+                },
+                Json(CloneServiceRequest {
+                    id: id.into(),
+                    overrides,
+                }),
+            )
+        };
+
+        let err = clone("leak", json!({ "env": { "A": "${SECRET:any_key}" } }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), StatusCode::FORBIDDEN);
+        assert!(!state.manager.service_exists("leak").await.unwrap());
+
+        let cloned = clone("web-2", json!({ "args": ["1"] })).await.unwrap();
+        assert_eq!(cloned.args, vec!["1".to_string()]);
+    }
+}
@@ -0,0 +1,63 @@
+//! 登录会话 API 处理器
+
+use axum::extract::{ConnectInfo, Path, State};
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use hypercraft_core::SessionSummary;
+use std::net::SocketAddr;
+
+use super::super::error::ApiError;
+use super::super::middleware::AuthInfo;
+use super::super::state::AppState;
+
+/// 会话管理：本人或管理员，且必须是登录会话（API Key 不可）
+fn ensure_can_manage_sessions(auth: &AuthInfo, user_id: &str) -> Result<(), ApiError> {
+    auth.require_session()?;
+    if auth.claims.sub != user_id && !auth.is_admin() {
+        return Err(ApiError::forbidden("只能管理自己的登录会话"));
+    }
+    Ok(())
+}
+
+/// GET /users/:id/sessions - 列出用户的登录会话
+pub async fn list_user_sessions(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthInfo>,
+    Path(user_id): Path<String>,
+) -> Result<Json<Vec<SessionSummary>>, ApiError> {
+    ensure_can_manage_sessions(&auth, &user_id)?;
+    let sessions = state.user_manager.list_sessions(&user_id).await?;
+    let current = auth.claims.session_id.as_deref();
+    let summaries = sessions
+        .into_iter()
+        .map(|session| {
+            let is_current = auth.claims.sub == user_id && current == Some(session.id.as_str());
+            SessionSummary {
+                current: is_current,
+                ..session.into()
+            }
+        })
+        .collect();
+    Ok(Json(summaries))
+}
+
+/// DELETE /users/:id/sessions/:sid - 注销单个会话
+pub async fn revoke_user_session(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Extension(auth): Extension<AuthInfo>,
+    Path((user_id, session_id)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    ensure_can_manage_sessions(&auth, &user_id)?;
+    let result = state
+        .user_manager
+        .revoke_session(&user_id, &session_id)
+        .await;
+    let target = format!("{user_id}/{session_id}");
+    state
+        .audit
+        .record(&auth, addr, "session.revoke", &target, &result)
+        .await;
+    result?;
+    Ok(StatusCode::NO_CONTENT)
+}
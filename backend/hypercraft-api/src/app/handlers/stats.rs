@@ -1,13 +1,34 @@
-//! 系统资源统计 API
+//! 系统与服务进程资源统计 API
 
-use axum::extract::State;
-use axum::Json;
-use hypercraft_core::SystemStats;
-use serde::Serialize;
+use axum::extract::{Query, State};
+use axum::response::sse::{Event, KeepAlive};
+use axum::response::{IntoResponse, Response, Sse};
+use axum::{Extension, Json};
+use futures::stream::{self, StreamExt};
+use hypercraft_core::{api_key_scopes, ProcessStats, SystemStats};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::time::Duration;
 use tracing::instrument;
 
+use crate::app::middleware::AuthInfo;
+use crate::app::rate_limit::StreamConcurrencyLimiter;
 use crate::app::{ApiError, AppState};
 
+/// 推送间隔默认值（秒）
+const DEFAULT_STREAM_INTERVAL_SECS: u64 = 2;
+/// 推送间隔上限（秒）
+const MAX_STREAM_INTERVAL_SECS: u64 = 60;
+/// 单条统计流最长存活时间
+const SSE_MAX_DURATION: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Deserialize)]
+pub struct StatsStreamQuery {
+    /// 推送间隔（秒），1..=60
+    pub interval: Option<u64>,
+}
+
 /// 系统资源响应
 #[derive(Debug, Serialize)]
 pub struct SystemStatsResponse {
@@ -23,3 +44,57 @@ pub async fn get_system_stats(
     let stats = state.manager.get_system_stats();
     Ok(Json(SystemStatsResponse { stats }))
 }
+
+/// 以 SSE 持续推送系统资源统计，所有连接共享同一个刷新任务
+#[instrument(skip_all)]
+pub async fn stream_system_stats(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthInfo>,
+    Query(query): Query<StatsStreamQuery>,
+) -> Result<Response, ApiError> {
+    let permit = state
+        .stream_limiter
+        .try_acquire(format!("stats:{}", auth.claims.sub))
+        .ok_or_else(|| ApiError::too_many_requests("too many concurrent stats streams"))?;
+
+    let period = Duration::from_secs(
+        query
+            .interval
+            .unwrap_or(DEFAULT_STREAM_INTERVAL_SECS)
+            .clamp(1, MAX_STREAM_INTERVAL_SECS),
+    );
+    let feed = state.manager.subscribe_system_stats();
+    let ticker = tokio::time::interval(period);
+    let stream = stream::unfold((feed, ticker), |(feed, mut ticker)| async move {
+        ticker.tick().await;
+        let event = Event::default()
+            .json_data(&*feed.borrow())
+            .unwrap_or_default();
+        Some((Ok::<_, Infallible>(event), (feed, ticker)))
+    })
+    .take_until(tokio::time::sleep(SSE_MAX_DURATION));
+
+    let guarded = StreamConcurrencyLimiter::guard_stream(stream, permit);
+    Ok(Sse::new(guarded)
+        .keep_alive(KeepAlive::new().interval(Duration::from_secs(15)))
+        .into_response())
+}
+
+/// 按服务 ID 返回运行中服务的进程资源占用，仅包含有权限访问的服务
+#[instrument(skip_all)]
+pub async fn get_process_stats(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthInfo>,
+) -> Result<Json<BTreeMap<String, ProcessStats>>, ApiError> {
+    auth.require_scope(api_key_scopes::READ)?;
+    let ids: Vec<String> = state
+        .manager
+        .list_services()
+        .await?
+        .into_iter()
+        .filter(|s| auth.can_read(&s.id))
+        .map(|s| s.id)
+        .collect();
+    let stats = state.manager.process_stats_many(&ids).await?;
+    Ok(Json(stats))
+}
@@ -2,13 +2,16 @@
 //!
 //! @author sky
 
-use axum::extract::State;
+use axum::extract::{Path, State};
 use axum::http::StatusCode;
 use axum::{Extension, Json};
-use hypercraft_core::{Disable2FARequest, Enable2FARequest, Setup2FARequest};
+use hypercraft_core::{
+    Disable2FARequest, Enable2FARequest, RecoveryCodesResponse, RegenerateRecoveryCodesRequest,
+    Setup2FARequest, TwoFactorStatus,
+};
 use serde_json::{json, Value};
 
-use super::super::error::ApiError;
+use super::super::error::{ApiError, ErrorCode};
 use super::super::middleware::AuthInfo;
 use super::super::state::AppState;
 
@@ -24,29 +27,31 @@ pub async fn verify_user_2fa(
     totp_code: Option<&str>,
 ) -> Result<(), ApiError> {
     // 查找用户
-    let user = match state.user_manager.find_by_username(user_id).await {
+    let mut user = match state.user_manager.find_by_username(user_id).await {
         Ok(Some(u)) => u,
         Ok(None) => return Ok(()), // 用户不存在，不验证 2FA
         Err(_) => return Ok(()),    // 查询失败，不验证 2FA
     };
 
     // 检查是否启用了 2FA
-    if let Some(totp_cfg) = &user.totp_config {
-        if totp_cfg.enabled {
-            // 需要 2FA，检查是否提供了验证码
-            let code = totp_code.ok_or_else(|| {
-                ApiError::new("2FA_REQUIRED", StatusCode::UNAUTHORIZED, "请输入双因素认证码")
-            })?;
+    if user.totp_config.as_ref().is_some_and(|cfg| cfg.enabled) {
+        // 需要 2FA，检查是否提供了验证码
+        let code = totp_code.ok_or_else(|| {
+            ApiError::new(
+                ErrorCode::TwoFactorRequired,
+                StatusCode::UNAUTHORIZED,
+                "请输入双因素认证码",
+            )
+        })?;
 
-            // 验证 TOTP 码
-            if !state
-                .user_manager
-                .verify_totp(&user, code)
-                .await
-                .unwrap_or(false)
-            {
-                return Err(ApiError::unauthorized_with_message("双因素认证码错误"));
-            }
+        // 验证 TOTP 码（恢复码会被消费）
+        if !state
+            .user_manager
+            .verify_totp(&mut user, code)
+            .await
+            .unwrap_or(false)
+        {
+            return Err(ApiError::unauthorized_with_message("双因素认证码错误"));
         }
     }
 
@@ -59,6 +64,7 @@ pub async fn setup_2fa(
     Extension(auth): Extension<AuthInfo>,
     Json(_req): Json<Setup2FARequest>,
 ) -> Result<(StatusCode, Json<Value>), ApiError> {
+    auth.require_session()?;
     let response = state.user_manager.setup_2fa(&auth.claims.sub).await?;
 
     Ok((StatusCode::OK, Json(json!(response))))
@@ -70,6 +76,7 @@ pub async fn enable_2fa(
     Extension(auth): Extension<AuthInfo>,
     Json(req): Json<Enable2FARequest>,
 ) -> Result<(StatusCode, Json<Value>), ApiError> {
+    auth.require_session()?;
     state
         .user_manager
         .enable_2fa(
@@ -89,6 +96,7 @@ pub async fn disable_2fa(
     Extension(auth): Extension<AuthInfo>,
     Json(req): Json<Disable2FARequest>,
 ) -> Result<(StatusCode, Json<Value>), ApiError> {
+    auth.require_session()?;
     state
         .user_manager
         .disable_2fa(&auth.claims.sub, &req.verification)
@@ -96,3 +104,31 @@ pub async fn disable_2fa(
 
     Ok((StatusCode::OK, Json(json!({"success": true}))))
 }
+
+/// POST /auth/2fa/recovery-codes - 重新生成恢复码（明文仅返回一次）
+pub async fn regenerate_recovery_codes(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthInfo>,
+    Json(req): Json<RegenerateRecoveryCodesRequest>,
+) -> Result<Json<RecoveryCodesResponse>, ApiError> {
+    auth.require_session()?;
+    let recovery_codes = state
+        .user_manager
+        .regenerate_recovery_codes(&auth.claims.sub, &req.verification)
+        .await?;
+
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
+
+/// GET /users/:id/2fa/status - 查询 2FA 状态与剩余恢复码数量（自己或管理员）
+pub async fn get_2fa_status(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthInfo>,
+    Path(user_id): Path<String>,
+) -> Result<Json<TwoFactorStatus>, ApiError> {
+    if auth.claims.sub != user_id && !auth.is_admin() {
+        return Err(ApiError::forbidden("只能查看自己的双因素认证状态"));
+    }
+    let status = state.user_manager.two_factor_status(&user_id).await?;
+    Ok(Json(status))
+}
@@ -1,10 +1,14 @@
 //! 用户管理 API handlers（仅管理员可访问）
 
-use axum::extract::{Path, State};
+use axum::extract::{ConnectInfo, Path, Query, State};
 use axum::http::StatusCode;
 use axum::Json;
-use hypercraft_core::{CreateUserRequest, ServiceSummary, UpdateUserRequest, UserSummary};
+use hypercraft_core::{
+    CreateUserRequest, ServiceAccessLevel, ServicePermission, ServiceSummary, UpdateUserRequest,
+    UserSummary,
+};
 use serde::Deserialize;
+use std::net::SocketAddr;
 
 use super::super::error::ApiError;
 use super::super::middleware::{AuthInfo, RequireAdmin};
@@ -19,17 +23,24 @@ fn forbid_devtoken_target(id: &str) -> Result<(), ApiError> {
     Ok(())
 }
 
-/// 非管理员赋权时，service_ids 必须全部在本人可访问范围内。
+/// 非管理员赋权时，service_ids 必须全部在本人权限范围内，且不能授出高于自身的级别。
 /// 系统管理员/超管对任意服务有控制权，可给自己或他人分配全量服务。
-fn ensure_service_ids_in_scope(auth: &AuthInfo, service_ids: &[String]) -> Result<(), ApiError> {
+fn ensure_service_ids_in_scope(
+    auth: &AuthInfo,
+    service_ids: &[ServicePermission],
+) -> Result<(), ApiError> {
     if auth.is_admin() {
         return Ok(());
     }
-    for sid in service_ids {
-        if !auth.can_access_service(sid) {
+    for perm in service_ids {
+        let allowed = match perm.level {
+            ServiceAccessLevel::Read => auth.can_read(&perm.id),
+            ServiceAccessLevel::Control => auth.can_control(&perm.id),
+        };
+        if !allowed {
             return Err(ApiError::forbidden(format!(
                 "没有权限分配服务: {}",
-                sid
+                perm.id
             )));
         }
     }
@@ -66,6 +77,7 @@ pub async fn list_assignable_services(
 /// POST /users - 创建用户
 pub async fn create_user(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     RequireAdmin(auth): RequireAdmin,
     Json(req): Json<CreateUserRequest>,
 ) -> Result<(StatusCode, Json<UserSummary>), ApiError> {
@@ -78,7 +90,13 @@ pub async fn create_user(
     // 非管理员创建用户时，初始服务权限不得超出本人范围
     ensure_service_ids_in_scope(&auth, &req.service_ids)?;
     // 密码强度验证由 core 层 UserManager::create_user 执行
-    let user = state.user_manager.create_user(req).await?;
+    let username = req.username.clone();
+    let result = state.user_manager.create_user(req).await;
+    state
+        .audit
+        .record(&auth, addr, "user.create", &username, &result)
+        .await;
+    let user = result?;
     let summary: UserSummary = user.into();
     Ok((StatusCode::CREATED, Json(summary)))
 }
@@ -97,6 +115,7 @@ pub async fn get_user(
 /// PUT /users/:id - 更新用户
 pub async fn update_user(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     RequireAdmin(auth): RequireAdmin,
     Path(id): Path<String>,
     Json(req): Json<UpdateUserRequest>,
@@ -109,7 +128,12 @@ pub async fn update_user(
         ensure_service_ids_in_scope(&auth, service_ids)?;
     }
     // 密码强度验证由 core 层 UserManager::update_user 执行
-    let user = state.user_manager.update_user(&id, req).await?;
+    let result = state.user_manager.update_user(&id, req).await;
+    state
+        .audit
+        .record(&auth, addr, "user.update", &id, &result)
+        .await;
+    let user = result?;
     let summary: UserSummary = user.into();
     Ok(Json(summary))
 }
@@ -117,30 +141,62 @@ pub async fn update_user(
 /// DELETE /users/:id - 删除用户
 pub async fn delete_user(
     State(state): State<AppState>,
-    RequireAdmin(_): RequireAdmin,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    RequireAdmin(auth): RequireAdmin,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    forbid_devtoken_target(&id)?;
+    let result = state.user_manager.delete_user(&id).await;
+    state
+        .audit
+        .record(&auth, addr, "user.delete", &id, &result)
+        .await;
+    result?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /users/:id/revoke-tokens - 强制用户在所有设备上登出
+pub async fn revoke_user_tokens(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    RequireAdmin(auth): RequireAdmin,
     Path(id): Path<String>,
 ) -> Result<StatusCode, ApiError> {
     forbid_devtoken_target(&id)?;
-    state.user_manager.delete_user(&id).await?;
+    let result = state.user_manager.revoke_all_tokens(&id).await;
+    state
+        .audit
+        .record(&auth, addr, "user.revoke_tokens", &id, &result)
+        .await;
+    result?;
     Ok(StatusCode::NO_CONTENT)
 }
 
-/// 服务权限请求
+/// 服务权限请求（元素可为服务 ID 字符串或 `{id, level}`）
 #[derive(Debug, Deserialize)]
 pub struct ServiceIdsRequest {
-    pub service_ids: Vec<String>,
+    pub service_ids: Vec<ServicePermission>,
+}
+
+/// 添加服务权限的查询参数
+#[derive(Debug, Default, Deserialize)]
+pub struct AddServiceQuery {
+    /// 权限级别，默认 control
+    #[serde(default)]
+    pub level: ServiceAccessLevel,
 }
 
 /// PUT /users/:id/services - 设置用户的服务权限
 pub async fn set_user_services(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     RequireAdmin(auth): RequireAdmin,
     Path(id): Path<String>,
     Json(req): Json<ServiceIdsRequest>,
 ) -> Result<Json<UserSummary>, ApiError> {
     forbid_devtoken_target(&id)?;
     ensure_service_ids_in_scope(&auth, &req.service_ids)?;
-    let user = state
+    let result = state
         .user_manager
         .update_user(
             &id,
@@ -150,26 +206,39 @@ pub async fn set_user_services(
                 is_admin: None,
             },
         )
-        .await?;
+        .await;
+    state
+        .audit
+        .record(&auth, addr, "user.set_services", &id, &result)
+        .await;
+    let user = result?;
     let summary: UserSummary = user.into();
     Ok(Json(summary))
 }
 
-/// POST /users/:user_id/services/:service_id - 添加服务权限
+/// POST /users/:user_id/services/:service_id?level=read|control - 添加或调整服务权限
 pub async fn add_user_service(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     RequireAdmin(auth): RequireAdmin,
     Path((user_id, service_id)): Path<(String, String)>,
+    Query(query): Query<AddServiceQuery>,
 ) -> Result<Json<UserSummary>, ApiError> {
     forbid_devtoken_target(&user_id)?;
-    ensure_service_ids_in_scope(&auth, &[service_id.clone()])?;
+    ensure_service_ids_in_scope(&auth, &[ServicePermission::new(&service_id, query.level)])?;
     // 验证服务是否存在
     let _ = state.manager.load_manifest(&service_id).await?;
 
-    let user = state
+    let result = state
         .user_manager
-        .add_service_permission(&user_id, &service_id)
-        .await?;
+        .add_service_permission(&user_id, &service_id, query.level)
+        .await;
+    let target = format!("{user_id}/{service_id}");
+    state
+        .audit
+        .record(&auth, addr, "user.add_service", &target, &result)
+        .await;
+    let user = result?;
     let summary: UserSummary = user.into();
     Ok(Json(summary))
 }
@@ -177,16 +246,29 @@ pub async fn add_user_service(
 /// DELETE /users/:user_id/services/:service_id - 移除服务权限
 pub async fn remove_user_service(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     RequireAdmin(auth): RequireAdmin,
     Path((user_id, service_id)): Path<(String, String)>,
 ) -> Result<Json<UserSummary>, ApiError> {
     forbid_devtoken_target(&user_id)?;
-    // 非管理员只能收回自己权限范围内的服务
-    ensure_service_ids_in_scope(&auth, &[service_id.clone()])?;
-    let user = state
+    // 非管理员只能收回自己有控制权的服务
+    ensure_service_ids_in_scope(
+        &auth,
+        &[ServicePermission::new(
+            &service_id,
+            ServiceAccessLevel::Control,
+        )],
+    )?;
+    let result = state
         .user_manager
         .remove_service_permission(&user_id, &service_id)
-        .await?;
+        .await;
+    let target = format!("{user_id}/{service_id}");
+    state
+        .audit
+        .record(&auth, addr, "user.remove_service", &target, &result)
+        .await;
+    let user = result?;
     let summary: UserSummary = user.into();
     Ok(Json(summary))
 }
@@ -202,16 +284,16 @@ pub struct ChangePasswordRequest {
 /// POST /users/:id/password - 修改用户密码（管理员或本人）
 pub async fn change_password(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Extension(auth): Extension<AuthInfo>,
     Path(id): Path<String>,
     Json(req): Json<ChangePasswordRequest>,
 ) -> Result<Json<UserSummary>, ApiError> {
+    auth.require_session()?;
     // 限流检查（按用户 ID，防止暴力破解当前密码）
-    if !state.password_limiter.allow(&id).await {
+    if let Err(wait) = state.password_limiter.acquire(&id).await {
         tracing::warn!("修改密码限流: UserID={}", id);
-        return Err(ApiError::too_many_requests(
-            "请求过于频繁，请稍后再试",
-        ));
+        return Err(ApiError::too_many_requests("请求过于频繁，请稍后再试").with_retry_after(wait));
     }
 
     // 密码强度验证由 core 层 UserManager::change_password 执行
@@ -227,7 +309,7 @@ pub async fn change_password(
         forbid_devtoken_target(&id)?;
     }
 
-    let user = state
+    let result = state
         .user_manager
         .change_password(
             &id,
@@ -235,7 +317,12 @@ pub async fn change_password(
             &req.new_password,
             is_admin,
         )
-        .await?;
+        .await;
+    state
+        .audit
+        .record(&auth, addr, "user.change_password", &id, &result)
+        .await;
+    let user = result?;
     let summary: UserSummary = user.into();
     Ok(Json(summary))
 }
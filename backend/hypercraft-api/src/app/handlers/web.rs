@@ -3,6 +3,7 @@ use axum::http::{HeaderMap, StatusCode};
 use axum::Json;
 use serde::Serialize;
 
+use crate::app::error::ErrorCode;
 use crate::app::middleware::ServicePermission;
 use crate::app::web_gateway::{
     build_gateway_url, detect_request_scheme, validate_gateway_upstream,
@@ -21,11 +22,13 @@ pub async fn create_web_session(
     ServicePermission { service_id, auth }: ServicePermission,
 ) -> Result<Json<WebSessionResponse>, ApiError> {
     // API Key 不代表可登录用户，禁止将自动化凭据升级为浏览器会话。
-    if auth.is_api_key() {
+    if auth.via_api_key() {
         return Err(ApiError::forbidden(
             "API Key 不能创建 Web Gateway 会话",
         ));
     }
+    // Web 会话可直接操作服务自身的页面，视同控制权
+    auth.require_control(&service_id)?;
 
     let manifest = state.manager.load_manifest(&service_id).await?;
     let web = manifest
@@ -42,7 +45,7 @@ pub async fn create_web_session(
 
     let base_domain = state.web_gateway_base_domain.clone().ok_or_else(|| {
         ApiError::new(
-            "ServiceUnavailable",
+            ErrorCode::ServiceUnavailable,
             StatusCode::SERVICE_UNAVAILABLE,
             "web gateway base domain is not configured",
         )
@@ -1,16 +1,22 @@
 use std::future::Future;
-use std::net::SocketAddr;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::pin::Pin;
+use std::time::Instant;
 
 use axum::body::Body;
 use axum::extract::{ConnectInfo, FromRequestParts, Path, State};
 use axum::http::request::Parts;
-use axum::http::Request;
+use axum::http::{header, HeaderValue, Request};
 use axum::middleware::Next;
-use axum::response::Response;
-use hypercraft_core::{api_key_scopes, API_KEY_RAW_PREFIX, TokenClaims, TokenType};
-
-use super::error::ApiError;
+use axum::response::{IntoResponse, Response};
+use hypercraft_core::{
+	api_key_scopes, secret_refs, service_access_level, ServiceAccessLevel, ServiceManifest,
+	API_KEY_RAW_PREFIX, TokenClaims, TokenType,
+};
+use tracing::Instrument;
+
+use super::error::{ApiError, ErrorCode};
+use super::rate_limit::RATE_LIMIT_REMAINING_HEADER;
 use super::state::AppState;
 use super::web_gateway::{extract_gateway_service_id, handle_web_gateway_request, request_host};
 
@@ -20,6 +26,8 @@ pub struct AuthInfo {
 	pub claims: TokenClaims,
 	/// JWT 为 None（沿用角色能力）；API Key 为 Some(scopes)
 	pub scopes: Option<Vec<String>>,
+	/// DevToken 会话通过 `X-Admin-Identity` 声明的操作者，仅用于审计归属，不改变权限
+	pub admin_identity: Option<String>,
 }
 
 impl AuthInfo {
@@ -28,6 +36,7 @@ impl AuthInfo {
 		Self {
 			claims,
 			scopes: None,
+			admin_identity: None,
 		}
 	}
 
@@ -36,6 +45,7 @@ impl AuthInfo {
 		Self {
 			claims,
 			scopes: Some(scopes),
+			admin_identity: None,
 		}
 	}
 
@@ -54,6 +64,20 @@ impl AuthInfo {
 		matches!(self.claims.token_type, TokenType::ApiKey)
 	}
 
+	/// 是否通过 API Key 认证（含以用户身份鉴权的个人密钥）
+	pub fn via_api_key(&self) -> bool {
+		self.scopes.is_some()
+	}
+
+	/// 凭据类操作（改密码、2FA）只允许登录会话，个人密钥泄露时不能借此接管账号
+	pub fn require_session(&self) -> Result<(), ApiError> {
+		if self.via_api_key() {
+			Err(ApiError::forbidden("API Key 不能执行此操作"))
+		} else {
+			Ok(())
+		}
+	}
+
 	/// 检查 scope：JWT 无 scopes 限制；API Key 必须显式拥有
 	pub fn has_scope(&self, scope: &str) -> bool {
 		match &self.scopes {
@@ -71,12 +95,14 @@ impl AuthInfo {
 		}
 	}
 
-	/// 控制/管理某服务的运行权限。
-	/// 超管、系统管理员、API Key 覆盖全部服务；普通用户 JWT 仅限 service_ids。
-	pub fn can_access_service(&self, service_id: &str) -> bool {
+	/// 对某服务至少拥有 `level` 级别的权限。
+	/// 超管、系统管理员、API Key 覆盖全部服务；普通用户 JWT 按 service_ids 中的级别。
+	fn has_service_level(&self, service_id: &str, level: ServiceAccessLevel) -> bool {
 		match self.claims.token_type {
 			TokenType::User => {
-				self.is_admin() || self.claims.service_ids.contains(&service_id.to_string())
+				self.is_admin()
+					|| service_access_level(&self.claims.service_ids, service_id)
+						.is_some_and(|granted| granted >= level)
 			}
 			TokenType::Web => self.claims.service_id.as_deref() == Some(service_id),
 			TokenType::ApiKey => true,
@@ -84,6 +110,28 @@ impl AuthInfo {
 		}
 	}
 
+	/// 查看服务详情、状态与日志
+	pub fn can_read(&self, service_id: &str) -> bool {
+		self.has_service_level(service_id, ServiceAccessLevel::Read)
+	}
+
+	/// 启停、修改服务与交互式 attach
+	pub fn can_control(&self, service_id: &str) -> bool {
+		self.has_service_level(service_id, ServiceAccessLevel::Control)
+	}
+
+	/// 缺少服务控制权时返回 Forbidden
+	pub fn require_control(&self, service_id: &str) -> Result<(), ApiError> {
+		if self.can_control(service_id) {
+			Ok(())
+		} else {
+			Err(ApiError::forbidden(format!(
+				"没有权限控制服务: {}",
+				service_id
+			)))
+		}
+	}
+
 	/// 默认服务列表是否展示该服务（与控制权分离）。
 	/// 超管与 API Key 全量；系统管理员与普通用户均按 claims.service_ids。
 	pub fn is_service_listed(&self, service_id: &str) -> bool {
@@ -91,18 +139,20 @@ impl AuthInfo {
 			return true;
 		}
 		match self.claims.token_type {
-			TokenType::User => self.claims.service_ids.contains(&service_id.to_string()),
+			TokenType::User => {
+				service_access_level(&self.claims.service_ids, service_id).is_some()
+			}
 			TokenType::Web => self.claims.service_id.as_deref() == Some(service_id),
 			_ => false,
 		}
 	}
 
-	/// 是否可改服务定义：JWT 管理员，或 API Key 持 manage
+	/// 是否可改服务定义：JWT 管理员，或 API Key 持 manage（个人密钥还需所属用户为管理员）
 	pub fn can_manage_service_defs(&self) -> bool {
 		if self.is_api_key() {
 			self.has_scope(api_key_scopes::MANAGE)
 		} else {
-			self.is_admin()
+			self.is_admin() && self.has_scope(api_key_scopes::MANAGE)
 		}
 	}
 
@@ -115,22 +165,42 @@ impl AuthInfo {
 		}
 	}
 
-	/// 修改/删除已有服务：manage 能力 + 服务访问权（系统管理员可管全部）
+	/// 修改/删除已有服务：manage 能力 + 服务控制权（系统管理员可管全部）
 	pub fn require_manage_service(&self, service_id: &str) -> Result<(), ApiError> {
 		self.require_manage_create()?;
-		if !self.can_access_service(service_id) {
-			return Err(ApiError::forbidden(format!(
-				"没有权限访问服务: {}",
-				service_id
-			)));
+		self.require_control(service_id)
+	}
+
+	/// 新增 `${SECRET:key}` 引用等同于读取密钥，只有能管理密钥的超级管理员可以这样做；
+	/// `previous` 中已有的引用保持不变时不受限制
+	pub fn require_secret_refs(
+		&self,
+		manifest: &ServiceManifest,
+		previous: Option<&ServiceManifest>,
+	) -> Result<(), ApiError> {
+		if self.is_super_admin() {
+			return Ok(());
+		}
+		let mut added = secret_refs(&manifest.env);
+		if let Some(previous) = previous {
+			for key in secret_refs(&previous.env) {
+				added.remove(&key);
+			}
+		}
+		if added.is_empty() {
+			return Ok(());
 		}
-		Ok(())
+		let keys: Vec<String> = added.into_iter().collect();
+		Err(ApiError::forbidden(format!(
+			"只有超级管理员可以引用密钥: {}",
+			keys.join(", ")
+		)))
 	}
 }
 
 /// 要求管理员权限的 Extractor（超管或系统管理员）
 #[derive(Debug, Clone)]
-pub struct RequireAdmin(#[allow(dead_code)] pub AuthInfo);
+pub struct RequireAdmin(pub AuthInfo);
 
 impl<S: Send + Sync> FromRequestParts<S> for RequireAdmin {
 	type Rejection = ApiError;
@@ -150,8 +220,8 @@ impl<S: Send + Sync> FromRequestParts<S> for RequireAdmin {
 				.cloned()
 				.ok_or_else(ApiError::unauthorized)?;
 
-			// API Key 永远不能走管理员接口
-			if auth.is_api_key() || !auth.is_admin() {
+			// API Key（含管理员的个人密钥）永远不能走管理员接口
+			if auth.via_api_key() || !auth.is_admin() {
 				return Err(ApiError::forbidden("admin access required"));
 			}
 			Ok(RequireAdmin(auth))
@@ -189,7 +259,8 @@ impl<S: Send + Sync> FromRequestParts<S> for RequireSuperAdmin {
 	}
 }
 
-/// 服务权限检查 Extractor - 从路径参数 :id 提取服务 ID 并验证权限
+/// 服务权限检查 Extractor - 从路径参数 :id 提取服务 ID 并验证读权限；
+/// 需要控制权的 handler 再调用 `auth.require_control`
 #[derive(Debug, Clone)]
 pub struct ServicePermission {
 	pub auth: AuthInfo,
@@ -218,7 +289,7 @@ impl<S: Send + Sync> FromRequestParts<S> for ServicePermission {
 				.await
 				.map_err(|_| ApiError::bad_request("无效的服务"))?;
 
-			if !auth.can_access_service(&service_id) {
+			if !auth.can_read(&service_id) {
 				return Err(ApiError::forbidden(format!(
 					"没有权限访问服务: {}",
 					service_id
@@ -232,6 +303,8 @@ impl<S: Send + Sync> FromRequestParts<S> for ServicePermission {
 /// 不需要认证的路径
 const PUBLIC_PATHS: &[&str] = &[
 	"/health",
+	"/health/live",
+	"/health/ready",
 	"/auth/login",
 	"/auth/devtoken",
 	"/auth/refresh",
@@ -300,26 +373,123 @@ fn requires_csrf_header(request: &Request<Body>) -> bool {
 	)
 }
 
+/// DevToken 会话声明操作者身份的请求头
+pub const ADMIN_IDENTITY_HEADER: &str = "x-admin-identity";
+
+/// 读取 `X-Admin-Identity`：最多 64 个字母、数字或 `._@+-`，其余取值视为错误请求
+fn admin_identity(request: &Request<Body>) -> Result<Option<String>, ApiError> {
+	let Some(value) = request.headers().get(ADMIN_IDENTITY_HEADER) else {
+		return Ok(None);
+	};
+	let identity = value.to_str().map(str::trim).unwrap_or_default();
+	let valid = !identity.is_empty()
+		&& identity.len() <= 64
+		&& identity
+			.bytes()
+			.all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b'@' | b'+' | b'-'));
+	if !valid {
+		return Err(ApiError::bad_request(
+			"invalid X-Admin-Identity header: use up to 64 letters, digits or ._@+-",
+		));
+	}
+	Ok(Some(identity.to_string()))
+}
+
+/// Unix 域套接字连接没有对端 IP，以此占位地址作为 ConnectInfo
+pub const UNIX_SOCKET_PEER: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0);
+
 /// 从请求中提取客户端 IP
 /// 仅使用直连 socket 地址，不信任客户端可控的代理头，避免限流被伪造绕过。
+/// 经 Unix 域套接字接入的请求返回 "local"。
 fn extract_client_ip(request: &Request<Body>) -> String {
 	request
 		.extensions()
 		.get::<ConnectInfo<SocketAddr>>()
-		.map(|ci| ci.0.ip().to_string())
+		.map(|ci| {
+			if ci.0 == UNIX_SOCKET_PEER {
+				"local".to_string()
+			} else {
+				ci.0.ip().to_string()
+			}
+		})
 		.unwrap_or_else(|| "unknown".to_string())
 }
 
+/// 请求 ID 请求头 / 响应头
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+tokio::task_local! {
+	/// 正在处理的请求 ID，供错误响应体与审计日志读取
+	static CURRENT_REQUEST_ID: String;
+}
+
+/// 当前任务正在处理的请求 ID；不在请求上下文中（如后台任务）时为 None
+pub fn current_request_id() -> Option<String> {
+	CURRENT_REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// 客户端提供的请求 ID 仅在长度与字符集安全时沿用，避免日志注入
+fn accept_request_id(value: &HeaderValue) -> Option<String> {
+	let value = value.to_str().ok()?.trim();
+	let valid = !value.is_empty()
+		&& value.len() <= 128
+		&& value
+			.bytes()
+			.all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b':'));
+	valid.then(|| value.to_string())
+}
+
+/// 为每个请求分配 `X-Request-Id`：沿用客户端提供的合法值，否则生成 UUID。
+///
+/// 请求在带 `request_id` 字段的 span 中处理，handler 的 `#[instrument]` span 都挂在其下；
+/// 结束时记录一条访问日志，并在响应头中回显该 ID。
+pub async fn request_id_middleware(request: Request<Body>, next: Next) -> Response {
+	let request_id = request
+		.headers()
+		.get(REQUEST_ID_HEADER)
+		.and_then(accept_request_id)
+		.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+	let method = request.method().clone();
+	let path = request.uri().path().to_string();
+	let client_ip = extract_client_ip(&request);
+	let span = tracing::info_span!(
+		"request",
+		request_id = %request_id,
+		admin_identity = tracing::field::Empty
+	);
+	let started = Instant::now();
+	let mut response = CURRENT_REQUEST_ID
+		.scope(request_id.clone(), next.run(request))
+		.instrument(span.clone())
+		.await;
+
+	let status = response.status().as_u16();
+	let latency_ms = started.elapsed().as_millis() as u64;
+	span.in_scope(|| {
+		// 健康检查由探针高频调用，降为 debug 避免淹没访问日志
+		if path.starts_with("/health") {
+			tracing::debug!(%method, %path, status, latency_ms, client_ip, "access");
+		} else {
+			tracing::info!(%method, %path, status, latency_ms, client_ip, "access");
+		}
+	});
+	if let Ok(value) = HeaderValue::from_str(&request_id) {
+		response.headers_mut().insert(REQUEST_ID_HEADER, value);
+	}
+	response
+}
+
 /// 认证失败时记入限流并返回 Unauthorized
 async fn reject_auth(state: &AppState, client_ip: &str, path: &str, reason: &str) -> ApiError {
-	if !state.auth_limiter.allow(client_ip).await {
+	if let Err(wait) = state.auth_limiter.acquire(client_ip).await {
 		tracing::warn!(
 			"认证限流触发: IP={}, 路径={} ({})",
 			client_ip,
 			path,
 			reason
 		);
-		return ApiError::too_many_requests("请求过于频繁，请稍后再试");
+		return ApiError::too_many_requests("请求过于频繁，请稍后再试").with_retry_after(wait);
 	}
 	ApiError::unauthorized()
 }
@@ -378,12 +548,87 @@ pub async fn auth_middleware(
 		));
 	}
 
-	request
-		.extensions_mut()
-		.insert(AuthInfo::from_claims(claims));
+	let mut auth = AuthInfo::from_claims(claims);
+	// 共用 DevToken 时由调用方声明具体操作者；普通用户的身份已由 token 确定，忽略该头
+	if auth.is_super_admin() {
+		auth.admin_identity = admin_identity(&request)?;
+		match &auth.admin_identity {
+			Some(identity) => {
+				tracing::Span::current().record("admin_identity", identity.as_str());
+			}
+			// 与 CSRF 判定相同：GET/HEAD/OPTIONS 之外都是写请求
+			None if state.require_admin_identity && requires_csrf_header(&request) => {
+				return Err(ApiError::bad_request(
+					"X-Admin-Identity header is required for DevToken write requests",
+				));
+			}
+			None => {}
+		}
+	}
+	request.extensions_mut().insert(auth);
 	Ok(next.run(request).await)
 }
 
+/// 全局请求限流：按直连 IP 计数，覆盖公开与受保护的全部 API 路由
+///
+/// 放行的响应带上 `X-RateLimit-Remaining`，客户端可据此自行放慢。
+pub async fn rate_limit_middleware(
+	State(state): State<AppState>,
+	request: Request<Body>,
+	next: Next,
+) -> Result<Response, ApiError> {
+	let Some(limiter) = &state.global_limiter else {
+		return Ok(next.run(request).await);
+	};
+	let client_ip = extract_client_ip(&request);
+	if let Err(wait) = limiter.acquire(&client_ip).await {
+		tracing::warn!("全局限流触发: IP={}, 路径={}", client_ip, request.uri().path());
+		return Err(ApiError::too_many_requests("请求过于频繁，请稍后再试").with_retry_after(wait));
+	}
+	let remaining = limiter.remaining(&client_ip).await;
+	let mut response = next.run(request).await;
+	response
+		.headers_mut()
+		.insert(RATE_LIMIT_REMAINING_HEADER, HeaderValue::from(remaining));
+	Ok(response)
+}
+
+/// 改写错误响应时最多读取的原始正文长度
+const REJECTION_BODY_LIMIT: usize = 16 * 1024;
+
+/// 把框架层拒绝（JSON/查询参数解析失败、方法不允许、WebSocket 握手失败等）返回的纯文本错误
+/// 改写成与 [`ApiError`] 相同的 `{code, message, request_id}` 结构，原文作为 message。
+///
+/// handler 自己返回的 JSON 错误原样放行；`Allow` 等原有响应头保留。
+pub async fn error_envelope_middleware(request: Request<Body>, next: Next) -> Response {
+	let response = next.run(request).await;
+	let status = response.status();
+	let is_json = response
+		.headers()
+		.get(header::CONTENT_TYPE)
+		.and_then(|v| v.to_str().ok())
+		.is_some_and(|v| v.starts_with("application/json"));
+	if !(status.is_client_error() || status.is_server_error()) || is_json {
+		return response;
+	}
+
+	let (mut parts, body) = response.into_parts();
+	let text = axum::body::to_bytes(body, REJECTION_BODY_LIMIT)
+		.await
+		.map(|bytes| String::from_utf8_lossy(&bytes).trim().to_string())
+		.unwrap_or_default();
+	let message = if text.is_empty() {
+		status.canonical_reason().unwrap_or("error").to_string()
+	} else {
+		text
+	};
+	let mut enveloped = ApiError::new(ErrorCode::from_status(status), status, message).into_response();
+	parts.headers.remove(header::CONTENT_TYPE);
+	parts.headers.remove(header::CONTENT_LENGTH);
+	enveloped.headers_mut().extend(parts.headers);
+	enveloped
+}
+
 pub async fn web_gateway_middleware(
 	State(state): State<AppState>,
 	request: Request<Body>,
@@ -400,3 +645,171 @@ pub async fn web_gateway_middleware(
 	};
 	handle_web_gateway_request(&state, request, service_id).await
 }
+
+#[cfg(test)]
+impl AuthInfo {
+	/// 测试用的普通用户 JWT 身份
+	pub(crate) fn test_user(service_ids: Vec<hypercraft_core::ServicePermission>) -> Self {
+		Self::from_claims(TokenClaims {
+			sub: "u1".into(),
+			username: "alice".into(),
+			iss: None,
+			aud: None,
+			token_type: TokenType::User,
+			service_ids,
+			is_admin: false,
+			token_version: 0,
+			refresh_nonce: None,
+			service_id: None,
+			session_id: None,
+			exp: 0,
+			iat: 0,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use hypercraft_core::ServicePermission;
+
+	fn user_auth(service_ids: Vec<ServicePermission>) -> AuthInfo {
+		AuthInfo::test_user(service_ids)
+	}
+
+	#[test]
+	fn read_level_does_not_grant_control() {
+		let auth = user_auth(vec![
+			ServicePermission::new("viewer", ServiceAccessLevel::Read),
+			ServicePermission::new("owner", ServiceAccessLevel::Control),
+		]);
+		assert!(auth.can_read("viewer"));
+		assert!(!auth.can_control("viewer"));
+		assert!(auth.require_control("viewer").is_err());
+		assert!(auth.can_read("owner") && auth.can_control("owner"));
+		assert!(!auth.can_read("other"));
+		assert!(auth.is_service_listed("viewer"));
+	}
+
+	#[test]
+	fn only_super_admin_adds_secret_refs() {
+		let manifest = |value: &str| {
+			let mut m: ServiceManifest = serde_json::from_value(serde_json::json!({
+				"id": "svc", "name": "svc", "command": "cmd"
+			}))
+			.unwrap();
+			m.env.insert("DB".into(), value.into());
+			m
+		};
+		let mut admin = user_auth(vec![]);
+		admin.claims.is_admin = true;
+		let old = manifest("${SECRET:db}");
+		let new = manifest("${SECRET:db}${SECRET:jwt}");
+
+		assert!(admin.require_secret_refs(&manifest("plain"), None).is_ok());
+		assert!(admin.require_secret_refs(&old, None).is_err());
+		assert!(admin.require_secret_refs(&old, Some(&old)).is_ok());
+		assert!(admin.require_secret_refs(&new, Some(&old)).is_err());
+
+		let mut root = user_auth(vec![]);
+		root.claims.sub = "__devtoken__".into();
+		assert!(root.require_secret_refs(&new, None).is_ok());
+	}
+
+	#[test]
+	fn admin_identity_header_is_validated() {
+		let with = |value: &str| {
+			Request::builder()
+				.header(ADMIN_IDENTITY_HEADER, value)
+				.body(Body::empty())
+				.unwrap()
+		};
+		let plain = Request::builder().body(Body::empty()).unwrap();
+		assert_eq!(admin_identity(&plain).unwrap(), None);
+		assert_eq!(
+			admin_identity(&with(" alice@ops ")).unwrap().as_deref(),
+			Some("alice@ops")
+		);
+		assert!(admin_identity(&with("")).is_err());
+		assert!(admin_identity(&with("bob smith")).is_err());
+		assert!(admin_identity(&with(&"x".repeat(65))).is_err());
+	}
+
+	#[tokio::test]
+	async fn framework_rejections_use_error_envelope() {
+		use axum::http::{Method, StatusCode};
+		use axum::routing::post;
+		use tower::ServiceExt;
+
+		let app = axum::Router::new()
+			.route(
+				"/echo",
+				post(|axum::Json(body): axum::Json<serde_json::Value>| async move { axum::Json(body) }),
+			)
+			.layer(axum::middleware::from_fn(error_envelope_middleware));
+		let read = |res: Response| async move {
+			let bytes = axum::body::to_bytes(res.into_body(), usize::MAX)
+				.await
+				.unwrap();
+			serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()
+		};
+
+		// 缺少 Content-Type: application/json
+		let req = Request::builder()
+			.method(Method::POST)
+			.uri("/echo")
+			.body(Body::from("{}"))
+			.unwrap();
+		let res = app.clone().oneshot(req).await.unwrap();
+		assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+		let body = read(res).await;
+		assert_eq!(body["code"], "UnsupportedMediaType");
+		assert!(!body["message"].as_str().unwrap().is_empty());
+
+		let req = Request::builder().uri("/echo").body(Body::empty()).unwrap();
+		let res = app.clone().oneshot(req).await.unwrap();
+		assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+		assert!(res.headers().contains_key(header::ALLOW));
+		assert_eq!(read(res).await["code"], "MethodNotAllowed");
+
+		// handler 返回的 JSON 正常放行
+		let req = Request::builder()
+			.method(Method::POST)
+			.uri("/echo")
+			.header(header::CONTENT_TYPE, "application/json")
+			.body(Body::from(r#"{"ok":true}"#))
+			.unwrap();
+		let res = app.oneshot(req).await.unwrap();
+		assert_eq!(read(res).await["ok"], true);
+	}
+
+	#[tokio::test]
+	async fn request_id_is_echoed_and_added_to_error_bodies() {
+		use axum::routing::get;
+		use tower::ServiceExt;
+
+		let app = axum::Router::new()
+			.route("/fail", get(|| async { ApiError::bad_request("nope") }))
+			.layer(axum::middleware::from_fn(request_id_middleware));
+		let call = |id: &'static str| {
+			Request::builder()
+				.uri("/fail")
+				.header(REQUEST_ID_HEADER, id)
+				.body(Body::empty())
+				.unwrap()
+		};
+
+		let res = app.clone().oneshot(call("trace-42")).await.unwrap();
+		assert_eq!(res.headers()[REQUEST_ID_HEADER], "trace-42");
+		let bytes = axum::body::to_bytes(res.into_body(), usize::MAX)
+			.await
+			.unwrap();
+		let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+		assert_eq!(body["request_id"], "trace-42");
+
+		// 含非法字符的请求 ID 被替换为新生成的 UUID
+		let res = app.oneshot(call("bad id\"")).await.unwrap();
+		let echoed = res.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+		assert!(uuid::Uuid::parse_str(echoed).is_ok());
+	}
+}
@@ -1,3 +1,4 @@
+mod audit;
 mod error;
 mod handlers;
 mod middleware;
@@ -6,7 +7,9 @@ mod router;
 mod state;
 mod web_gateway;
 
+pub use audit::AuditLog;
 pub use error::ApiError;
-pub use rate_limit::{RateLimiter, StreamConcurrencyLimiter};
-pub use router::app_router;
+pub use middleware::UNIX_SOCKET_PEER;
+pub use rate_limit::{RateLimitConfig, RateLimiter, StreamConcurrencyLimiter};
+pub use router::{app_router, CorsConfig};
 pub use state::AppState;
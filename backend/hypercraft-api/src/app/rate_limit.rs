@@ -6,6 +6,24 @@ use std::time::{Duration, Instant};
 use futures::Stream;
 use tokio::sync::Mutex as AsyncMutex;
 
+/// 剩余请求次数响应头（全局限流放行时为实际余量，429 时为 0）
+pub const RATE_LIMIT_REMAINING_HEADER: &str = "x-ratelimit-remaining";
+/// 额度恢复的时间点（Unix 秒），仅在 429 响应中返回
+pub const RATE_LIMIT_RESET_HEADER: &str = "x-ratelimit-reset";
+
+/// 限流参数：每个 key 在 `window` 内最多 `limit` 次请求。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitConfig {
+    pub limit: usize,
+    pub window: Duration,
+}
+
+impl RateLimitConfig {
+    pub const fn new(limit: usize, window: Duration) -> Self {
+        Self { limit, window }
+    }
+}
+
 /// 简单的滑动窗口限流器（基于内存，按 key 计数）。
 #[derive(Debug)]
 pub struct RateLimiter {
@@ -25,18 +43,24 @@ impl RateLimiter {
         }
     }
 
-    /// 返回是否允许当前请求；超限返回 false。
-    pub async fn allow(&self, key: &str) -> bool {
+    pub fn from_config(config: RateLimitConfig) -> Self {
+        Self::new(config.limit, config.window)
+    }
+
+    /// 尝试占用一次额度；超限时返回需要等待多久才会有新的额度。
+    pub async fn acquire(&self, key: &str) -> Result<(), Duration> {
         let now = Instant::now();
         let key_owned = key.to_string();
         let mut buckets = self.buckets.lock().await;
         let entry = buckets.entry(key_owned.clone()).or_default();
         entry.retain(|t| now.duration_since(*t) < self.window);
         let allowed = if entry.len() >= self.limit {
-            false
+            // 记录按时间顺序追加，最早的一条过期后即可放行
+            let oldest = entry.first().copied().unwrap_or(now);
+            Err(self.window.saturating_sub(now.duration_since(oldest)))
         } else {
             entry.push(now);
-            true
+            Ok(())
         };
         // 清理空桶，避免无限增长
         if entry.is_empty() {
@@ -53,6 +77,7 @@ impl RateLimiter {
     }
 
     /// 检查是否超限（不记录）
+    #[allow(dead_code)]
     pub async fn check(&self, key: &str) -> bool {
         let now = Instant::now();
         let buckets = self.buckets.lock().await;
@@ -67,7 +92,24 @@ impl RateLimiter {
         }
     }
 
+    /// 当前窗口内剩余的请求次数
+    pub async fn remaining(&self, key: &str) -> usize {
+        let now = Instant::now();
+        let buckets = self.buckets.lock().await;
+        let used = buckets
+            .get(key)
+            .map(|entry| {
+                entry
+                    .iter()
+                    .filter(|t| now.duration_since(**t) < self.window)
+                    .count()
+            })
+            .unwrap_or(0);
+        self.limit.saturating_sub(used)
+    }
+
     /// 记录一次访问（不检查限制）
+    #[allow(dead_code)]
     pub async fn record(&self, key: &str) {
         let now = Instant::now();
         let key_owned = key.to_string();
@@ -132,6 +174,7 @@ impl StreamConcurrencyLimiter {
     }
 
     /// 当前 key 占用数（测试与观测用）。
+    #[allow(dead_code)]
     pub fn active_count(&self, key: &str) -> usize {
         self.counts
             .lock()
@@ -179,6 +222,26 @@ impl<S: Stream> Stream for StreamWithPermit<S> {
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn acquire_reports_wait_until_window_frees() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(60));
+        assert_eq!(limiter.acquire("k").await, Ok(()));
+        let wait = limiter.acquire("k").await.unwrap_err();
+        assert!(wait > Duration::from_secs(59) && wait <= Duration::from_secs(60));
+    }
+
+    #[tokio::test]
+    async fn remaining_counts_down_per_key() {
+        let limiter = RateLimiter::from_config(RateLimitConfig::new(2, Duration::from_secs(60)));
+        assert_eq!(limiter.remaining("1.2.3.4").await, 2);
+        assert!(limiter.acquire("1.2.3.4").await.is_ok());
+        assert_eq!(limiter.remaining("1.2.3.4").await, 1);
+        assert!(limiter.acquire("1.2.3.4").await.is_ok());
+        assert!(limiter.acquire("1.2.3.4").await.is_err());
+        assert_eq!(limiter.remaining("1.2.3.4").await, 0);
+        assert_eq!(limiter.remaining("5.6.7.8").await, 2);
+    }
+
     #[test]
     fn stream_permit_enforces_and_releases() {
         let limiter = StreamConcurrencyLimiter::new(2);
@@ -1,7 +1,8 @@
-use axum::middleware::from_fn_with_state;
-use axum::routing::{get, patch, post, put};
+use axum::middleware::{from_fn, from_fn_with_state};
+use axum::routing::{delete, get, patch, post, put};
 use axum::Router;
 use axum::http::{header, HeaderName, HeaderValue, Method};
+use std::time::Duration;
 use tower_http::cors::{AllowOrigin, CorsLayer};
 
 use super::handlers::{
@@ -9,43 +10,88 @@ use super::handlers::{
     agent_delete_service, agent_get_service, agent_get_status, agent_help, agent_kill,
     agent_list_groups, agent_list_services, agent_logs, agent_me, agent_reorder_groups,
     agent_restart, agent_shutdown, agent_start, agent_stop, agent_update_group,
-    agent_update_service, attach_service, change_password, create_api_key, create_group,
+    agent_update_service, attach_service, batch_services, change_password, clear_logs, clone_service, create_api_key, create_group, create_user_api_key,
     create_service, create_user, create_web_session, delete_group, delete_service, delete_user,
-    devtoken_login, disable_2fa, download_log_file, enable_2fa, get_api_key, get_logs, get_me,
-    get_schedule, get_service, get_status, get_system_stats, get_user, handler_404, health,
-    kill_service, list_api_keys, list_assignable_services, list_groups, list_services, list_users,
-    login, logout, refresh, remove_user_service, reorder_groups, reorder_services, restart_service,
-    reveal_api_key_secret, revoke_api_key, rotate_api_key, set_user_services, setup_2fa,
-    shutdown_service, start_service, stop_service, update_api_key, update_group, update_schedule,
-    update_service,
-    update_service_group, update_service_tags, update_user, validate_cron,
+    delete_secret, devtoken_login, disable_2fa, download_log_file, export_config, download_recording, enable_2fa, get_2fa_status, get_api_key, get_group_logs,
+    get_logs, get_me, get_process_stats, get_schedule, get_service, get_service_stats, get_status, get_system_stats, get_user,
+    handler_404, health_live, health_ready, import_config,
+    kill_service, list_api_keys, list_assignable_services, list_audit, list_groups, list_recordings, list_secrets, list_user_api_keys,
+    list_archived_services, list_schedule_jobs, list_services, pause_scheduler, restore_archived_service, resume_scheduler, run_schedule, list_user_sessions, list_users,
+    login, logout, refresh, regenerate_recovery_codes, remove_user_service, reorder_groups, reorder_services, restart_service,
+    reveal_api_key_secret, revoke_api_key, revoke_user_api_key, revoke_user_session, revoke_user_tokens, rotate_api_key, search_logs, send_service_input, set_secret, set_user_services, setup_2fa,
+    shutdown_service, signal_service, start_service, stop_service, stream_events, stream_system_stats, update_api_key, update_group, update_schedule,
+    update_service, restart_group, start_group, stop_group, bulk_tag_services,
+    update_service_group, update_service_tags, update_user, validate_schedule, validate_service, TOTAL_COUNT_HEADER,
 };
-use super::middleware::{auth_middleware, web_gateway_middleware};
+use super::middleware::{
+    auth_middleware, error_envelope_middleware, rate_limit_middleware, request_id_middleware,
+    web_gateway_middleware, ADMIN_IDENTITY_HEADER, REQUEST_ID_HEADER,
+};
+use super::rate_limit::{RATE_LIMIT_REMAINING_HEADER, RATE_LIMIT_RESET_HEADER};
 use super::state::AppState;
 
-/// 根据配置的来源列表构建 CorsLayer
+/// CORS 配置（HC_CORS_*）
+#[derive(Debug, Clone, Default)]
+pub struct CorsConfig {
+    /// 允许的 Origin；为空时回退到本地开发地址
+    pub origins: Vec<String>,
+    /// 允许的方法；为空时使用默认的 GET/POST/PUT/PATCH/DELETE/OPTIONS
+    pub methods: Vec<Method>,
+    /// 在面板所需请求头之外额外允许的请求头
+    pub allow_headers: Vec<HeaderName>,
+    /// 在内置响应头之外额外暴露给浏览器的响应头
+    pub expose_headers: Vec<HeaderName>,
+    /// 预检结果缓存时间（Access-Control-Max-Age）
+    pub max_age: Option<Duration>,
+}
+
+/// 根据 CORS 配置构建 CorsLayer
 ///
 /// Cookie 会话需要 credentials=true，因此不能使用 AllowOrigin::any()。
 /// 未配置时默认放行本地前端端口，生产环境应显式设置 HC_CORS_ORIGINS。
-fn build_cors_layer(cors_origins: Vec<String>) -> CorsLayer {
-    let base = CorsLayer::new()
-        .allow_methods([
+fn build_cors_layer(cors: CorsConfig) -> CorsLayer {
+    let methods = if cors.methods.is_empty() {
+        vec![
             Method::GET,
             Method::POST,
             Method::PUT,
             Method::PATCH,
             Method::DELETE,
             Method::OPTIONS,
-        ])
-        .allow_headers([
-            header::CONTENT_TYPE,
-            header::AUTHORIZATION,
-            header::ACCEPT,
-            HeaderName::from_static("x-hypercraft-csrf"),
-        ])
+        ]
+    } else {
+        cors.methods
+    };
+    let mut allow_headers = vec![
+        header::CONTENT_TYPE,
+        header::AUTHORIZATION,
+        header::ACCEPT,
+        HeaderName::from_static("x-hypercraft-csrf"),
+        HeaderName::from_static(REQUEST_ID_HEADER),
+        HeaderName::from_static(ADMIN_IDENTITY_HEADER),
+    ];
+    allow_headers.extend(cors.allow_headers);
+    // Content-Disposition 供浏览器端 fetch 读取日志/录像下载的文件名
+    let mut expose_headers = vec![
+        HeaderName::from_static(TOTAL_COUNT_HEADER),
+        HeaderName::from_static(RATE_LIMIT_REMAINING_HEADER),
+        HeaderName::from_static(RATE_LIMIT_RESET_HEADER),
+        header::RETRY_AFTER,
+        header::CONTENT_DISPOSITION,
+        HeaderName::from_static(REQUEST_ID_HEADER),
+    ];
+    expose_headers.extend(cors.expose_headers);
+
+    let mut base = CorsLayer::new()
+        .allow_methods(methods)
+        .allow_headers(allow_headers)
+        .expose_headers(expose_headers)
         .allow_credentials(true);
+    if let Some(max_age) = cors.max_age {
+        base = base.max_age(max_age);
+    }
 
-    let origins_src = if cors_origins.is_empty() {
+    let origins_src = if cors.origins.is_empty() {
         tracing::warn!(
             "HC_CORS_ORIGINS 没有配置，默认允许 http://localhost:3000 与 http://127.0.0.1:3000；生产环境请显式配置。"
         );
@@ -54,7 +100,7 @@ fn build_cors_layer(cors_origins: Vec<String>) -> CorsLayer {
             "http://127.0.0.1:3000".to_string(),
         ]
     } else {
-        cors_origins
+        cors.origins
     };
 
     let origins: Vec<HeaderValue> = origins_src
@@ -65,10 +111,12 @@ fn build_cors_layer(cors_origins: Vec<String>) -> CorsLayer {
 }
 
 /// Build the router with routes and middleware wired.
-pub fn app_router(state: AppState, cors_origins: Vec<String>) -> Router {
+pub fn app_router(state: AppState, cors: CorsConfig) -> Router {
     // 公开端点（不需要认证）
     let public_routes = Router::new()
-        .route("/health", get(health))
+        .route("/health", get(health_ready))
+        .route("/health/live", get(health_live))
+        .route("/health/ready", get(health_ready))
         .route("/auth/login", post(login))
         .route("/auth/devtoken", post(devtoken_login))
         .route("/auth/refresh", post(refresh))
@@ -83,6 +131,7 @@ pub fn app_router(state: AppState, cors_origins: Vec<String>) -> Router {
             get(get_user).put(update_user).delete(delete_user),
         )
         .route("/users/:id/services", put(set_user_services))
+        .route("/users/:id/revoke-tokens", post(revoke_user_tokens))
         .route(
             "/users/:user_id/services/:service_id",
             post(add_user_service).delete(remove_user_service),
@@ -93,11 +142,26 @@ pub fn app_router(state: AppState, cors_origins: Vec<String>) -> Router {
             get(get_api_key).put(update_api_key).delete(revoke_api_key),
         )
         .route("/api-keys/:id/secret", get(reveal_api_key_secret))
-        .route("/api-keys/:id/rotate", post(rotate_api_key));
+        .route("/api-keys/:id/rotate", post(rotate_api_key))
+        .route("/audit", get(list_audit))
+        .route("/schedules", get(list_schedule_jobs))
+        .route("/scheduler/pause", post(pause_scheduler))
+        .route("/scheduler/resume", post(resume_scheduler))
+        .route("/export", get(export_config))
+        .route("/import", post(import_config))
+        .route("/secrets", get(list_secrets))
+        .route("/secrets/:key", put(set_secret).delete(delete_secret));
 
     // 服务端点（需要认证，权限由 handler 检查）
     let service_routes = Router::new()
         .route("/services", get(list_services).post(create_service))
+        .route("/services/batch", post(batch_services))
+        .route("/services/validate", post(validate_service))
+        .route("/services/archived", get(list_archived_services))
+        .route(
+            "/services/archived/:archive_id/restore",
+            post(restore_archived_service),
+        )
         .route(
             "/services/:id",
             get(get_service).delete(delete_service).put(update_service),
@@ -107,18 +171,27 @@ pub fn app_router(state: AppState, cors_origins: Vec<String>) -> Router {
         .route("/services/:id/shutdown", post(shutdown_service))
         .route("/services/:id/kill", post(kill_service))
         .route("/services/:id/restart", post(restart_service))
+        .route("/services/:id/input", post(send_service_input))
+        .route("/services/:id/signal", post(signal_service))
+        .route("/services/:id/clone", post(clone_service))
         .route("/services/:id/status", get(get_status))
-        .route("/services/:id/logs", get(get_logs))
+        .route("/services/:id/stats", get(get_service_stats))
+        .route("/services/:id/logs", get(get_logs).delete(clear_logs))
+        .route("/services/:id/logs/search", get(search_logs))
         .route("/services/:id/log-file", get(download_log_file))
         .route("/services/:id/attach", get(attach_service))
+        .route("/services/:id/recordings", get(list_recordings))
+        .route("/services/:id/recordings/:name", get(download_recording))
         .route("/services/:id/web/session", post(create_web_session))
         .route("/services/:id/tags", patch(update_service_tags))
+        .route("/tags/bulk", post(bulk_tag_services))
         .route("/services/:id/group", patch(update_service_group))
         .route(
             "/services/:id/schedule",
             get(get_schedule).put(update_schedule),
         )
-        .route("/schedule/validate", post(validate_cron));
+        .route("/services/:id/schedule/run", post(run_schedule))
+        .route("/schedule/validate", post(validate_schedule));
 
     // Agent 薄封装（API Key / JWT 均可；默认文本日志）
     let agent_routes = Router::new()
@@ -157,20 +230,37 @@ pub fn app_router(state: AppState, cors_origins: Vec<String>) -> Router {
         .route("/groups", get(list_groups).post(create_group))
         .route("/groups/reorder", post(reorder_groups))
         .route("/groups/:id", patch(update_group).delete(delete_group))
+        .route("/groups/:id/logs", get(get_group_logs))
+        .route("/groups/:id/start", post(start_group))
+        .route("/groups/:id/stop", post(stop_group))
+        .route("/groups/:id/restart", post(restart_group))
         .route("/services/reorder", post(reorder_services));
 
-    // 资源统计端点（仅系统级）
+    // 资源统计与事件推送端点（系统级 + 按服务的进程占用 + 生命周期事件）
     let stats_routes = Router::new()
-        .route("/stats/system", get(get_system_stats));
+        .route("/stats/system", get(get_system_stats))
+        .route("/stats/system/stream", get(stream_system_stats))
+        .route("/stats/processes", get(get_process_stats))
+        .route("/events", get(stream_events));
 
-    // 密码更新（认证 + 自己或管理员）
-    let password_routes = Router::new().route("/users/:id/password", post(change_password));
+    // 密码更新、个人 API Key 与登录会话（认证 + 自己或管理员）
+    let password_routes = Router::new()
+        .route("/users/:id/password", post(change_password))
+        .route(
+            "/users/:id/api-keys",
+            get(list_user_api_keys).post(create_user_api_key),
+        )
+        .route("/users/:id/api-keys/:key_id", delete(revoke_user_api_key))
+        .route("/users/:id/sessions", get(list_user_sessions))
+        .route("/users/:id/sessions/:sid", delete(revoke_user_session));
 
     // 2FA 管理端点（需要认证）
     let two_factor_routes = Router::new()
         .route("/auth/2fa/setup", post(setup_2fa))
         .route("/auth/2fa/enable", post(enable_2fa))
         .route("/auth/2fa/disable", post(disable_2fa))
+        .route("/auth/2fa/recovery-codes", post(regenerate_recovery_codes))
+        .route("/users/:id/2fa/status", get(get_2fa_status))
         .route("/auth/me", get(get_me));
 
     // 需要认证的路由（经过 auth_middleware）
@@ -189,7 +279,64 @@ pub fn app_router(state: AppState, cors_origins: Vec<String>) -> Router {
         .merge(public_routes)
         .merge(protected_routes)
         .fallback(handler_404)
-        .layer(build_cors_layer(cors_origins))
+        // 框架层的纯文本拒绝统一成 JSON 错误结构（网关代理的响应不经过这里）
+        .layer(from_fn(error_envelope_middleware))
+        .layer(from_fn_with_state(state.clone(), rate_limit_middleware))
+        .layer(build_cors_layer(cors))
         .layer(from_fn_with_state(state.clone(), web_gateway_middleware))
+        // 最外层：限流、CORS、认证拒绝等所有响应都带上请求 ID
+        .layer(from_fn(request_id_middleware))
         .with_state(state)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn cors_config_extends_headers_and_sets_max_age() {
+        let cors = CorsConfig {
+            origins: vec!["https://panel.example.com".into()],
+            methods: vec![Method::GET],
+            allow_headers: vec![HeaderName::from_static("x-client-version")],
+            expose_headers: vec![HeaderName::from_static("x-trace-id")],
+            max_age: Some(Duration::from_secs(600)),
+        };
+        let app = Router::new()
+            .route("/ping", get(|| async { "pong" }))
+            .layer(build_cors_layer(cors));
+
+        let preflight = Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/ping")
+            .header(header::ORIGIN, "https://panel.example.com")
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+            .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "x-client-version")
+            .body(Body::empty())
+            .unwrap();
+        let res = app.clone().oneshot(preflight).await.unwrap();
+        let headers = res.headers();
+        let allowed = headers[header::ACCESS_CONTROL_ALLOW_HEADERS]
+            .to_str()
+            .unwrap();
+        assert!(allowed.contains("x-client-version"));
+        assert!(allowed.contains("authorization"));
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_METHODS], "GET");
+        assert_eq!(headers[header::ACCESS_CONTROL_MAX_AGE], "600");
+
+        let get = Request::builder()
+            .uri("/ping")
+            .header(header::ORIGIN, "https://panel.example.com")
+            .body(Body::empty())
+            .unwrap();
+        let res = app.oneshot(get).await.unwrap();
+        let exposed = res.headers()[header::ACCESS_CONTROL_EXPOSE_HEADERS]
+            .to_str()
+            .unwrap();
+        assert!(exposed.contains("content-disposition"));
+        assert!(exposed.contains("x-trace-id"));
+    }
+}
@@ -19,6 +19,10 @@ pub struct AppState {
     pub auth_limiter: Arc<crate::app::RateLimiter>,
     /// 密码修改限流（按用户 ID，防止暴力破解当前密码）
     pub password_limiter: Arc<crate::app::RateLimiter>,
+    /// 全局请求限流（按 IP，未配置 HC_GLOBAL_RATE 时关闭）
+    pub global_limiter: Option<Arc<crate::app::RateLimiter>>,
+    /// 管理操作审计日志
+    pub audit: Arc<crate::app::AuditLog>,
     /// SSE / WebSocket 等长连接按身份+服务的并发限制
     pub stream_limiter: Arc<crate::app::StreamConcurrencyLimiter>,
     /// Web 网关对外使用的子域名基础域
@@ -29,4 +33,36 @@ pub struct AppState {
     pub http_client: reqwest::Client,
     /// API 监听地址，用于阻止 Web 上游反向指向控制面。
     pub api_bind: SocketAddr,
+    /// DevToken 会话的写请求是否必须带 `X-Admin-Identity`
+    pub require_admin_identity: bool,
+}
+
+#[cfg(test)]
+impl AppState {
+    /// 测试用状态：服务与用户数据都放在 `data_dir`，不带 DevToken
+    pub(crate) fn for_tests(data_dir: &std::path::Path) -> Self {
+        use crate::app::{AuditLog, RateLimiter, StreamConcurrencyLimiter};
+        use std::time::Duration;
+
+        let manager = Arc::new(ServiceManager::new(data_dir));
+        let limiter = || Arc::new(RateLimiter::new(1000, Duration::from_secs(60)));
+        Self {
+            manager: manager.clone(),
+            user_manager: Arc::new(UserManager::new(data_dir, "test-secret".into())),
+            scheduler: Arc::new(ServiceScheduler::new((*manager).clone())),
+            dev_token: None,
+            login_limiter: limiter(),
+            refresh_limiter: limiter(),
+            auth_limiter: limiter(),
+            password_limiter: limiter(),
+            global_limiter: None,
+            audit: Arc::new(AuditLog::new(data_dir)),
+            stream_limiter: StreamConcurrencyLimiter::new(3),
+            web_gateway_base_domain: None,
+            web_proxy_session_ttl: 3600,
+            http_client: reqwest::Client::new(),
+            api_bind: SocketAddr::from(([127, 0, 0, 1], 0)),
+            require_admin_identity: false,
+        }
+    }
 }
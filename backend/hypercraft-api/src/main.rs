@@ -1,19 +1,71 @@
 mod app;
 
-use app::{app_router, AppState, RateLimiter, StreamConcurrencyLimiter};
-use hypercraft_core::{init_tracing, load_dotenv, ServiceManager, ServiceScheduler, UserManager};
+use app::{
+    app_router, AppState, AuditLog, CorsConfig, RateLimitConfig, RateLimiter,
+    StreamConcurrencyLimiter, UNIX_SOCKET_PEER,
+};
+use hypercraft_core::{
+    init_tracing, load_dotenv, validate_webhook_url, PasswordPolicy, ServiceManager,
+    ServiceScheduler, UserManager, DEFAULT_ATTACH_BUFFER,
+};
 use rand::Rng;
 use std::collections::HashSet;
 use std::env;
+use std::fs;
 use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
-use tracing::info;
+use tracing::{info, warn};
+
+/// API 监听目标：TCP 地址，或 `HC_BIND=unix:/path` 形式的 Unix 域套接字
+#[derive(Debug, Clone)]
+enum BindTarget {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+impl BindTarget {
+    /// 解析 HC_BIND；未设置或无法解析的 TCP 地址回退到 0.0.0.0:8080
+    fn parse(value: Option<&str>) -> Self {
+        match value.map(str::trim) {
+            Some(value) if value.starts_with("unix:") => {
+                let path = value.trim_start_matches("unix:");
+                if path.is_empty() {
+                    panic!("HC_BIND=unix: 缺少套接字路径");
+                }
+                Self::Unix(PathBuf::from(path))
+            }
+            value => Self::Tcp(
+                value
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or_else(|| "0.0.0.0:8080".parse().expect("valid default bind")),
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for BindTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tcp(addr) => write!(f, "{}", addr),
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+/// HC_TLS_CERT / HC_TLS_KEY 指定的 PEM 证书链与私钥路径
+#[derive(Debug, Clone)]
+struct TlsPaths {
+    cert: PathBuf,
+    key: PathBuf,
+}
 
 #[derive(Debug, Clone)]
 struct ApiConfig {
-    bind: SocketAddr,
+    bind: BindTarget,
+    /// 设置后以 HTTPS 提供服务
+    tls: Option<TlsPaths>,
     data_dir: PathBuf,
     /// DevToken（管理员 token）
     dev_token: Option<String>,
@@ -23,16 +75,55 @@ struct ApiConfig {
     jwt_issuer: String,
     /// JWT aud
     jwt_audience: String,
+    /// Access / refresh token 有效期（秒）
+    access_token_ttl: i64,
+    refresh_token_ttl: i64,
+    /// 用户记录主密钥；轮换时另给出旧密钥
+    master_key: Option<String>,
+    master_key_previous: Option<String>,
     allowed_commands: Option<HashSet<String>>,
     allowed_cwd_roots: Vec<PathBuf>,
-    /// 前端面板 Origin 列表（空则使用本地开发地址）
-    cors_origins: Vec<String>,
+    /// 未设置 cwd 的服务的默认工作目录根（各服务使用 `<root>/<id>`）
+    default_cwd_root: Option<PathBuf>,
+    /// 允许经 `${ENV:NAME}` 传给服务的环境变量名
+    env_passthrough: Vec<String>,
+    /// DevToken 会话的写请求是否必须带 X-Admin-Identity
+    require_admin_identity: bool,
+    /// 前端面板 Origin 列表（空则使用本地开发地址）及其余 CORS 参数
+    cors: CorsConfig,
     /// Web 网关的子域名基础域
     web_gateway_base_domain: Option<String>,
     /// Web 代理会话有效期（秒）
     web_proxy_session_ttl: i64,
+    /// 密码强度策略
+    password_policy: PasswordPolicy,
+    /// 全局生命周期 Webhook 地址
+    webhook_url: Option<String>,
+    /// 登录 / 刷新 / Token 认证 / 修改密码限流
+    login_rate: RateLimitConfig,
+    refresh_rate: RateLimitConfig,
+    auth_rate: RateLimitConfig,
+    password_rate: RateLimitConfig,
+    /// 全局请求限流（按 IP），未设置 HC_GLOBAL_RATE 时关闭
+    global_rate: Option<RateLimitConfig>,
+    /// 启动时同时自动启动的服务数上限
+    autostart_concurrency: usize,
+    /// attach / 日志跟随的输出广播容量
+    attach_buffer: usize,
 }
 
+/// Access token 默认有效期：6 小时
+const DEFAULT_ACCESS_TTL_SECS: i64 = 6 * 60 * 60;
+/// Refresh token 默认有效期：7 天
+const DEFAULT_REFRESH_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+/// 默认同时自动启动的服务数
+const DEFAULT_AUTOSTART_CONCURRENCY: usize = 4;
+/// TLS 握手超时：只建连不握手的客户端不能一直占着连接与任务
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 各类限流的默认值：每 IP（或用户）每分钟 10 次
+const DEFAULT_RATE_LIMIT: RateLimitConfig = RateLimitConfig::new(10, Duration::from_secs(60));
+
 /// 生成包含数字、字母和符号的复杂随机密码
 fn generate_secure_password(length: usize) -> String {
     const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ\
@@ -48,12 +139,240 @@ fn generate_secure_password(length: usize) -> String {
         .collect()
 }
 
+/// 读取布尔环境变量（1/true/yes/on 为真），未设置或无法识别时返回 None
+fn env_flag(name: &str) -> Option<bool> {
+    let value = env::var(name).ok()?;
+    match value.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// 读取正整数环境变量，未设置返回 None，非法值告警后忽略
+fn env_positive(name: &str) -> Option<u64> {
+    let value = env::var(name).ok()?;
+    match value.trim().parse::<u64>() {
+        Ok(n) if n > 0 => Some(n),
+        _ => {
+            warn!("{} 必须是正整数，已忽略: {}", name, value);
+            None
+        }
+    }
+}
+
+/// 读取逗号分隔的 CORS 列表（HC_CORS_METHODS 等），非法项直接拒绝启动
+fn cors_list_from_env<T: std::str::FromStr>(name: &str) -> Vec<T> {
+    let Ok(value) = env::var(name) else {
+        return Vec::new();
+    };
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(|t| {
+            // 带凭据的 CORS 不允许通配符，浏览器会把 "*" 当作字面值
+            if t == "*" {
+                panic!("{} 不能使用 *，请逐项列出", name);
+            }
+            // HTTP 方法区分大小写，统一转大写；请求头名本就大小写不敏感
+            t.to_ascii_uppercase()
+                .parse()
+                .unwrap_or_else(|_| panic!("{} 中的 {:?} 无效", name, t))
+        })
+        .collect()
+}
+
+/// 读取 HC_<NAME>_RATE / HC_<NAME>_WINDOW_SECS，未设置的项沿用 `default`
+fn rate_limit_from_env(name: &str, default: RateLimitConfig) -> RateLimitConfig {
+    let limit = env_positive(&format!("HC_{name}_RATE")).map_or(default.limit, |n| n as usize);
+    let window =
+        env_positive(&format!("HC_{name}_WINDOW_SECS")).map_or(default.window, Duration::from_secs);
+    RateLimitConfig::new(limit, window)
+}
+
+/// 持久化 JWT 密钥的文件名（位于数据目录下）
+const JWT_SECRET_FILE: &str = ".jwt_secret";
+
+/// 按 HC_JWT_SECRET / HC_ENV 确定 JWT 签名密钥
+fn jwt_secret_from_env(data_dir: &Path) -> String {
+    resolve_jwt_secret(
+        env::var("HC_JWT_SECRET").ok(),
+        env::var("HC_ENV").ok(),
+        data_dir,
+    )
+}
+
+/// 确定 JWT 签名密钥，并明确记录采用了哪种来源。
+///
+/// 优先使用 `secret`（HC_JWT_SECRET）；`hc_env` 为 production 时未设置则拒绝启动；
+/// 否则复用 `<data_dir>/.jwt_secret`，不存在时生成一次并以 0600 权限保存，
+/// 避免每次重启都让所有用户 token 失效。
+fn resolve_jwt_secret(secret: Option<String>, hc_env: Option<String>, data_dir: &Path) -> String {
+    if let Some(secret) = secret
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+    {
+        info!("JWT 密钥来源: HC_JWT_SECRET");
+        return secret;
+    }
+    let production = hc_env
+        .map(|v| v.trim().eq_ignore_ascii_case("production"))
+        .unwrap_or(false);
+    if production {
+        panic!("HC_ENV=production 时必须设置 HC_JWT_SECRET");
+    }
+
+    let path = data_dir.join(JWT_SECRET_FILE);
+    match fs::read_to_string(&path) {
+        Ok(secret) if !secret.trim().is_empty() => {
+            warn!("HC_JWT_SECRET 未设置；JWT 密钥来源: {}", path.display());
+            return secret.trim().to_string();
+        }
+        Ok(_) => warn!("{} 为空，重新生成 JWT 密钥", path.display()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => panic!("读取 {} 失败: {}", path.display(), e),
+    }
+
+    let secret = generate_secure_password(64);
+    if let Err(e) =
+        fs::create_dir_all(data_dir).and_then(|_| write_private(&path, secret.as_bytes()))
+    {
+        panic!("无法保存 JWT 密钥到 {}: {}", path.display(), e);
+    }
+    warn!(
+        "HC_JWT_SECRET 未设置；已生成 JWT 密钥并保存到 {}（之后重启会复用）",
+        path.display()
+    );
+    secret
+}
+
+/// 以 0600 权限写入
+#[cfg(unix)]
+fn write_private(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(data)
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    fs::write(path, data)
+}
+
+/// 读取密钥类环境变量（去掉首尾空白，空值视为未设置），过短时拒绝启动
+fn master_key_from_env(name: &str) -> Option<String> {
+    let key = env::var(name)
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())?;
+    if key.len() < 32 {
+        panic!("{} 长度过短（<32）；请使用强随机值", name);
+    }
+    Some(key)
+}
+
+/// HC_DEFAULT_CWD 须为不含 `..` 的绝对路径，且与 cwd 一样位于 data_dir 或
+/// HC_ALLOWED_CWD_PREFIXES 之下；否则每个未设置 cwd 的服务都会在创建时被拒绝
+fn check_default_cwd_root(root: &Path, data_dir: &Path, allowed_cwd_roots: &[PathBuf]) {
+    if !root.is_absolute() || root.components().any(|c| c == Component::ParentDir) {
+        panic!(
+            "HC_DEFAULT_CWD 必须是不含 .. 的绝对路径: {}",
+            root.display()
+        );
+    }
+    let allowed = root.starts_with(data_dir)
+        || allowed_cwd_roots
+            .iter()
+            .any(|prefix| prefix.as_os_str() == "*" || root.starts_with(prefix));
+    if !allowed {
+        panic!(
+            "HC_DEFAULT_CWD ({}) 不在数据目录或 HC_ALLOWED_CWD_PREFIXES 之内",
+            root.display()
+        );
+    }
+}
+
+/// 读取 HC_ACCESS_TTL_SECS / HC_REFRESH_TTL_SECS（兼容旧名 HC_ACCESS_TOKEN_TTL / HC_REFRESH_TOKEN_TTL）
+///
+/// 非正数告警后沿用默认值。
+fn token_ttls_from_env() -> (i64, i64) {
+    let ttl = |name: &str, legacy: &str| env_positive(name).or_else(|| env_positive(legacy));
+    resolve_token_ttls(
+        ttl("HC_ACCESS_TTL_SECS", "HC_ACCESS_TOKEN_TTL"),
+        ttl("HC_REFRESH_TTL_SECS", "HC_REFRESH_TOKEN_TTL"),
+    )
+}
+
+/// 未设置的 TTL 取默认值；access 不小于 refresh 时拒绝启动，否则 refresh 会先于 access 失效。
+fn resolve_token_ttls(access: Option<u64>, refresh: Option<u64>) -> (i64, i64) {
+    let ttl = |secs: Option<u64>, default: i64| {
+        secs.map_or(default, |secs| i64::try_from(secs).unwrap_or(i64::MAX))
+    };
+    let access = ttl(access, DEFAULT_ACCESS_TTL_SECS);
+    let refresh = ttl(refresh, DEFAULT_REFRESH_TTL_SECS);
+    if access >= refresh {
+        panic!(
+            "HC_ACCESS_TTL_SECS ({}) 必须小于 HC_REFRESH_TTL_SECS ({})",
+            access, refresh
+        );
+    }
+    (access, refresh)
+}
+
+/// 从 HC_PASSWORD_* 环境变量构建密码策略，未设置的项沿用默认值
+fn password_policy_from_env() -> PasswordPolicy {
+    let mut policy = PasswordPolicy::default();
+    if let Some(min_length) = env::var("HC_PASSWORD_MIN_LEN")
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+    {
+        policy.min_length = min_length;
+    }
+    if let Some(flag) = env_flag("HC_PASSWORD_REQUIRE_UPPER") {
+        policy.require_upper = flag;
+    }
+    if let Some(flag) = env_flag("HC_PASSWORD_REQUIRE_LOWER") {
+        policy.require_lower = flag;
+    }
+    if let Some(flag) = env_flag("HC_PASSWORD_REQUIRE_DIGIT") {
+        policy.require_digit = flag;
+    }
+    if let Some(flag) = env_flag("HC_PASSWORD_REQUIRE_SYMBOL") {
+        policy.require_symbol = flag;
+    }
+    // 单独要求数字或符号时，不再需要“二选一”规则
+    if policy.require_digit || policy.require_symbol {
+        policy.require_digit_or_symbol = false;
+    }
+    // 常见密码表：每行一个
+    if let Some(path) = env::var("HC_PASSWORD_DENYLIST")
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+    {
+        match std::fs::read_to_string(&path) {
+            Ok(content) => policy = policy.with_denylist(content.lines()),
+            Err(e) => warn!("读取 HC_PASSWORD_DENYLIST ({}) 失败: {}", path, e),
+        }
+    }
+    policy
+}
+
 impl ApiConfig {
     fn from_env() -> Self {
-        let bind = env::var("HC_BIND")
-            .ok()
-            .and_then(|s| s.parse().ok())
-            .unwrap_or_else(|| "0.0.0.0:8080".parse().expect("valid default bind"));
+        let bind = BindTarget::parse(env::var("HC_BIND").ok().as_deref());
+        let tls = tls_paths_from_env();
+        if tls.is_some() && matches!(bind, BindTarget::Unix(_)) {
+            panic!("HC_TLS_CERT / HC_TLS_KEY 不能与 HC_BIND=unix: 同时使用");
+        }
 
         let data_dir = env::var("HC_DATA_DIR")
             .map(PathBuf::from)
@@ -81,13 +400,13 @@ impl ApiConfig {
         };
 
         // JWT 密钥，用于签发用户 token（必须独立于 DevToken）
-        let jwt_secret = env::var("HC_JWT_SECRET").unwrap_or_else(|_| {
-            info!("HC_JWT_SECRET 未设置；为本次运行生成随机密钥");
-            uuid::Uuid::new_v4().to_string()
-        });
+        let jwt_secret = jwt_secret_from_env(&data_dir);
         let jwt_issuer = env::var("HC_JWT_ISSUER").unwrap_or_else(|_| "hypercraft-api".into());
         let jwt_audience =
             env::var("HC_JWT_AUDIENCE").unwrap_or_else(|_| "hypercraft-clients".into());
+        let (access_token_ttl, refresh_token_ttl) = token_ttls_from_env();
+        let master_key = master_key_from_env("HC_MASTER_KEY");
+        let master_key_previous = master_key_from_env("HC_MASTER_KEY_PREVIOUS");
 
         let allowed_commands = env::var("HC_ALLOWED_COMMANDS").ok().map(|s| {
             let trimmed = s.trim();
@@ -123,6 +442,25 @@ impl ApiConfig {
             })
             .unwrap_or_default();
 
+        let env_passthrough = env::var("HC_ENV_PASSTHROUGH")
+            .map(|s| {
+                s.split(',')
+                    .map(str::trim)
+                    .filter(|t| !t.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let default_cwd_root = env::var("HC_DEFAULT_CWD")
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .map(PathBuf::from);
+        if let Some(root) = &default_cwd_root {
+            check_default_cwd_root(root, &data_dir, &allowed_cwd_roots);
+        }
+
         // 前端面板 Origin，逗号分隔；空或 "*" 均回退到本地开发地址
         let cors_origins = env::var("HC_CORS_ORIGINS")
             .ok()
@@ -139,6 +477,13 @@ impl ApiConfig {
                 }
             })
             .unwrap_or_default();
+        let cors = CorsConfig {
+            origins: cors_origins,
+            methods: cors_list_from_env("HC_CORS_METHODS"),
+            allow_headers: cors_list_from_env("HC_CORS_HEADERS"),
+            expose_headers: cors_list_from_env("HC_CORS_EXPOSE_HEADERS"),
+            max_age: env_positive("HC_CORS_MAX_AGE").map(Duration::from_secs),
+        };
 
         let web_gateway_base_domain = env::var("HC_WEB_GATEWAY_BASE_DOMAIN")
             .ok()
@@ -150,20 +495,130 @@ impl ApiConfig {
             .and_then(|value| value.parse().ok())
             .unwrap_or(6 * 60 * 60);
 
+        let password_policy = password_policy_from_env();
+
+        // 全局 Webhook：无效地址只告警，不阻止启动
+        let webhook_url = env::var("HC_WEBHOOK_URL")
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .filter(|url| match validate_webhook_url(url) {
+                Ok(_) => true,
+                Err(e) => {
+                    warn!("HC_WEBHOOK_URL 无效，已忽略: {}", e);
+                    false
+                }
+            });
+
+        let global_rate = env_positive("HC_GLOBAL_RATE")
+            .map(|_| rate_limit_from_env("GLOBAL", DEFAULT_RATE_LIMIT));
+
         Self {
             bind,
+            tls,
             data_dir,
             dev_token,
             jwt_secret,
             jwt_issuer,
             jwt_audience,
+            access_token_ttl,
+            refresh_token_ttl,
+            master_key,
+            master_key_previous,
             allowed_commands,
             allowed_cwd_roots,
-            cors_origins,
+            default_cwd_root,
+            env_passthrough,
+            cors,
+            require_admin_identity: env_flag("HC_REQUIRE_ADMIN_IDENTITY").unwrap_or(false),
             web_gateway_base_domain,
             web_proxy_session_ttl,
+            password_policy,
+            webhook_url,
+            login_rate: rate_limit_from_env("LOGIN", DEFAULT_RATE_LIMIT),
+            refresh_rate: rate_limit_from_env("REFRESH", DEFAULT_RATE_LIMIT),
+            auth_rate: rate_limit_from_env("AUTH", DEFAULT_RATE_LIMIT),
+            password_rate: rate_limit_from_env("PASSWORD", DEFAULT_RATE_LIMIT),
+            global_rate,
+            autostart_concurrency: env_positive("HC_AUTOSTART_CONCURRENCY")
+                .map_or(DEFAULT_AUTOSTART_CONCURRENCY, |n| n as usize),
+            attach_buffer: env_positive("HC_ATTACH_BUFFER")
+                .map_or(DEFAULT_ATTACH_BUFFER, |n| n as usize),
+        }
+    }
+}
+
+/// 读取 HC_TLS_CERT / HC_TLS_KEY；两者须同时设置或同时留空
+fn tls_paths_from_env() -> Option<TlsPaths> {
+    let read = |name: &str| {
+        env::var(name)
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .map(PathBuf::from)
+    };
+    match (read("HC_TLS_CERT"), read("HC_TLS_KEY")) {
+        (Some(cert), Some(key)) => Some(TlsPaths { cert, key }),
+        (None, None) => None,
+        _ => panic!("HC_TLS_CERT 与 HC_TLS_KEY 必须同时设置"),
+    }
+}
+
+/// 加载证书链与私钥并构建 TLS acceptor；任何一步失败都给出具体文件与原因
+fn load_tls_acceptor(paths: &TlsPaths) -> anyhow::Result<tokio_rustls::TlsAcceptor> {
+    use tokio_rustls::rustls::pki_types::pem::PemObject;
+    use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+    use tokio_rustls::rustls::{crypto, ServerConfig};
+
+    let certs = CertificateDer::pem_file_iter(&paths.cert)
+        .and_then(|iter| iter.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| anyhow::anyhow!("无法读取 TLS 证书 {}: {}", paths.cert.display(), e))?;
+    if certs.is_empty() {
+        anyhow::bail!("TLS 证书 {} 中没有 PEM 证书", paths.cert.display());
+    }
+    let key = PrivateKeyDer::from_pem_file(&paths.key)
+        .map_err(|e| anyhow::anyhow!("无法读取 TLS 私钥 {}: {}", paths.key.display(), e))?;
+
+    let provider = Arc::new(crypto::ring::default_provider());
+    let mut config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .and_then(|builder| builder.with_no_client_auth().with_single_cert(certs, key))
+        .map_err(|e| {
+            anyhow::anyhow!(
+                "TLS 证书 {} 与私钥 {} 无效: {}",
+                paths.cert.display(),
+                paths.key.display(),
+                e
+            )
+        })?;
+    // 仅提供 HTTP/1.1，WebSocket 升级依赖它
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Ok(tokio_rustls::TlsAcceptor::from(Arc::new(config)))
+}
+
+/// 按主密钥配置准备用户存储：需要时先从旧密钥轮换，再逐条校验能否解密。
+///
+/// 密钥错误时返回错误、拒绝启动（fail closed），而不是让用户“消失”。
+fn prepare_user_store(user_manager: &UserManager, config: &ApiConfig) -> anyhow::Result<()> {
+    if let Some(previous) = config.master_key_previous.as_deref() {
+        // 已用新密钥加密（例如轮换后重启但未移除旧密钥）时跳过
+        if user_manager.verify_master_key().is_err() {
+            let count =
+                user_manager.rotate_master_key(Some(previous), config.master_key.as_deref())?;
+            warn!(
+                count,
+                "已将用户记录从 HC_MASTER_KEY_PREVIOUS 轮换到 HC_MASTER_KEY，确认无误后请移除 HC_MASTER_KEY_PREVIOUS"
+            );
         }
     }
+    let encrypted = user_manager.verify_master_key()?;
+    if encrypted > 0 {
+        info!(count = encrypted, "已用 HC_MASTER_KEY 加密明文用户记录");
+    }
+    if config.master_key.is_some() {
+        info!("用户记录使用 HC_MASTER_KEY 加密存储");
+    }
+    Ok(())
 }
 
 /// 限制 worker 线程数，避免在高核心数服务器上创建过多线程
@@ -176,16 +631,52 @@ async fn main() -> anyhow::Result<()> {
 
     let config = ApiConfig::from_env();
     info!("在 {} 启动 API", config.bind);
+    // 证书或私钥有问题时在启动阶段直接失败，而不是等到首个连接握手
+    let tls_acceptor = config.tls.as_ref().map(load_tls_acceptor).transpose()?;
 
-    let manager = Arc::new(ServiceManager::with_policy(
+    // 创建用户管理器
+    let mut user_manager = UserManager::new(config.data_dir.clone(), config.jwt_secret.clone())
+        .with_claims_context(config.jwt_issuer.clone(), config.jwt_audience.clone())
+        .with_ttl(config.access_token_ttl, config.refresh_token_ttl)
+        .with_password_policy(config.password_policy.clone());
+    if let Some(key) = &config.master_key {
+        user_manager = user_manager.with_master_key(key.clone());
+    }
+    let user_manager = Arc::new(user_manager);
+    user_manager.ensure_dirs()?;
+    prepare_user_store(&user_manager, &config)?;
+
+    // 挂载密钥库以解析 env 中的 ${SECRET:key}
+    let mut manager = ServiceManager::with_policy(
         config.data_dir.clone(),
         config.allowed_commands.clone(),
         config.allowed_cwd_roots.clone(),
-    ));
+    )
+    .with_secrets((*user_manager).clone())
+    .with_env_passthrough(config.env_passthrough.clone())
+    .with_attach_buffer(config.attach_buffer);
+    if let Some(url) = &config.webhook_url {
+        manager = manager.with_webhook_url(url.clone());
+    }
+    if let Some(root) = &config.default_cwd_root {
+        manager = manager.with_default_cwd_root(root.clone());
+    }
+    let manager = Arc::new(manager);
     manager.ensure_base_dirs()?;
+    // 数据目录不可写（磁盘满/只读）时直接拒绝启动，而不是让后续写入悄悄失败
+    manager.check_writable()?;
+    // 先挂上 Webhook 分发，auto_start 产生的事件也能通知到
+    manager.spawn_webhook_dispatcher();
+
+    // 接管 API 重启前仍在运行的进程，避免 auto_start 重复拉起
+    match manager.reconcile().await {
+        Ok(adopted) if !adopted.is_empty() => info!(count = adopted.len(), "已接管运行中的服务"),
+        Ok(_) => {}
+        Err(e) => tracing::warn!(error = %e, "对账运行中的服务失败"),
+    }
 
     // 自动启动配置了 auto_start 的服务
-    auto_start_services(&manager).await;
+    auto_start_services(&manager, config.autostart_concurrency).await;
 
     // 初始化定时调度器
     let scheduler = Arc::new(ServiceScheduler::new((*manager).clone()));
@@ -198,17 +689,13 @@ async fn main() -> anyhow::Result<()> {
         }
     }
 
-    // 创建用户管理器
-    let user_manager = Arc::new(
-        UserManager::new(config.data_dir.clone(), config.jwt_secret.clone())
-            .with_claims_context(config.jwt_issuer.clone(), config.jwt_audience.clone()),
-    );
-    user_manager.ensure_dirs()?;
-
-    let login_limiter = Arc::new(RateLimiter::new(10, Duration::from_secs(60)));
-    let refresh_limiter = Arc::new(RateLimiter::new(10, Duration::from_secs(60)));
-    let auth_limiter = Arc::new(RateLimiter::new(10, Duration::from_secs(60)));
-    let password_limiter = Arc::new(RateLimiter::new(10, Duration::from_secs(60)));
+    let login_limiter = Arc::new(RateLimiter::from_config(config.login_rate));
+    let refresh_limiter = Arc::new(RateLimiter::from_config(config.refresh_rate));
+    let auth_limiter = Arc::new(RateLimiter::from_config(config.auth_rate));
+    let password_limiter = Arc::new(RateLimiter::from_config(config.password_rate));
+    let global_limiter = config
+        .global_rate
+        .map(|rate| Arc::new(RateLimiter::from_config(rate)));
     // 每身份+服务的 SSE / attach 并发上限（key 带类型前缀，互不影响）
     let stream_limiter = StreamConcurrencyLimiter::new(3);
     let http_client = reqwest::Client::builder()
@@ -226,26 +713,52 @@ async fn main() -> anyhow::Result<()> {
         refresh_limiter,
         auth_limiter,
         password_limiter,
+        global_limiter,
         stream_limiter,
+        audit: Arc::new(AuditLog::new(config.data_dir.clone())),
         web_gateway_base_domain: config.web_gateway_base_domain.clone(),
         web_proxy_session_ttl: config.web_proxy_session_ttl,
         http_client,
-        api_bind: config.bind,
+        // Unix 域套接字没有 TCP 端口需要防回环，占位地址的端口 0 不会与任何上游匹配
+        api_bind: match &config.bind {
+            BindTarget::Tcp(addr) => *addr,
+            BindTarget::Unix(_) => UNIX_SOCKET_PEER,
+        },
+        require_admin_identity: config.require_admin_identity,
     };
 
-    let app = app_router(state, config.cors_origins.clone());
-    let listener = tokio::net::TcpListener::bind(config.bind).await?;
-
-    // Graceful shutdown 处理
-    let server = axum::serve(
-        listener,
-        app.into_make_service_with_connect_info::<SocketAddr>(),
-    )
-    .with_graceful_shutdown(shutdown_signal());
+    let app = app_router(state, config.cors.clone());
+    let served = match (&config.bind, tls_acceptor) {
+        (BindTarget::Tcp(addr), Some(acceptor)) => {
+            let listener = tokio::net::TcpListener::bind(addr).await?;
+            info!("服务器准备就绪（HTTPS），按 Ctrl+C 停止");
+            serve_tls(listener, acceptor, app).await
+        }
+        (BindTarget::Tcp(addr), None) => {
+            let listener = tokio::net::TcpListener::bind(addr).await?;
 
-    info!("服务器准备就绪，按 Ctrl+C 停止");
+            // Graceful shutdown 处理
+            let server = axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .with_graceful_shutdown(shutdown_signal());
 
-    if let Err(e) = server.await {
+            info!("服务器准备就绪，按 Ctrl+C 停止");
+            server.await.map_err(anyhow::Error::from)
+        }
+        #[cfg(unix)]
+        (BindTarget::Unix(path), _) => {
+            let listener = bind_unix_socket(path)?;
+            info!("服务器准备就绪，按 Ctrl+C 停止");
+            let served = serve_unix(listener, app).await;
+            let _ = fs::remove_file(path);
+            served
+        }
+        #[cfg(not(unix))]
+        (BindTarget::Unix(_), _) => Err(anyhow::anyhow!("HC_BIND=unix: 仅支持 Unix 平台")),
+    };
+    if let Err(e) = served {
         tracing::error!(error = %e, "服务器错误");
     }
 
@@ -265,6 +778,143 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// 绑定 Unix 域套接字并设置 0660 权限；会先清理上次异常退出遗留的套接字文件
+#[cfg(unix)]
+fn bind_unix_socket(path: &Path) -> anyhow::Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+    if let Ok(meta) = fs::symlink_metadata(path) {
+        if !meta.file_type().is_socket() {
+            anyhow::bail!("{} 已存在且不是套接字", path.display());
+        }
+        fs::remove_file(path)?;
+    }
+    let listener = tokio::net::UnixListener::bind(path)?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o660))?;
+    Ok(listener)
+}
+
+/// 在 Unix 域套接字上提供服务（HTTP/1.1，支持 WebSocket 升级）。
+///
+/// 收到关闭信号后停止接受新连接，通知现有连接优雅关闭并等待它们结束。
+#[cfg(unix)]
+async fn serve_unix(listener: tokio::net::UnixListener, app: axum::Router) -> anyhow::Result<()> {
+    let (stop_tx, stop_rx) = tokio::sync::watch::channel(false);
+    // 每个连接持有一个发送端，全部释放后 recv 返回 None，即所有连接已结束
+    let (done_tx, mut done_rx) = tokio::sync::mpsc::channel::<()>(1);
+
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    warn!(error = %e, "接受 Unix 套接字连接失败");
+                    continue;
+                }
+            },
+            _ = &mut shutdown => break,
+        };
+        // 没有对端 IP，统一注入占位地址，限流与审计中显示为 local
+        tokio::spawn(serve_connection(
+            stream,
+            app.clone(),
+            UNIX_SOCKET_PEER,
+            stop_rx.clone(),
+            done_tx.clone(),
+        ));
+    }
+
+    drop(listener);
+    let _ = stop_tx.send(true);
+    drop(done_tx);
+    let _ = done_rx.recv().await;
+    Ok(())
+}
+
+/// 在 TCP 上提供 HTTPS 服务（HTTP/1.1，支持 WebSocket 升级与 SSE）。
+///
+/// TLS 握手在各连接的任务中进行，慢握手不会阻塞 accept，超过 [`TLS_HANDSHAKE_TIMEOUT`]
+/// 即断开；关闭流程与 [`serve_unix`] 相同。
+async fn serve_tls(
+    listener: tokio::net::TcpListener,
+    acceptor: tokio_rustls::TlsAcceptor,
+    app: axum::Router,
+) -> anyhow::Result<()> {
+    let (stop_tx, stop_rx) = tokio::sync::watch::channel(false);
+    let (done_tx, mut done_rx) = tokio::sync::mpsc::channel::<()>(1);
+
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+    loop {
+        let (stream, peer) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!(error = %e, "接受 TCP 连接失败");
+                    continue;
+                }
+            },
+            _ = &mut shutdown => break,
+        };
+        let acceptor = acceptor.clone();
+        let app = app.clone();
+        let stop_rx = stop_rx.clone();
+        let done_tx = done_tx.clone();
+        tokio::spawn(async move {
+            match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                Ok(Ok(stream)) => serve_connection(stream, app, peer, stop_rx, done_tx).await,
+                Ok(Err(e)) => tracing::debug!(error = %e, %peer, "TLS 握手失败"),
+                Err(_) => tracing::debug!(%peer, "TLS 握手超时"),
+            }
+        });
+    }
+
+    drop(listener);
+    let _ = stop_tx.send(true);
+    drop(done_tx);
+    let _ = done_rx.recv().await;
+    Ok(())
+}
+
+/// 在单个连接上运行 HTTP/1.1 服务，并注入对端地址供 `ConnectInfo` 提取。
+///
+/// `stop_rx` 变化时优雅关闭连接；`done_tx` 随连接结束释放，供调用方等待全部连接退出。
+async fn serve_connection<S>(
+    stream: S,
+    app: axum::Router,
+    peer: SocketAddr,
+    mut stop_rx: tokio::sync::watch::Receiver<bool>,
+    done_tx: tokio::sync::mpsc::Sender<()>,
+) where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static,
+{
+    use axum::extract::ConnectInfo;
+    use hyper_util::rt::TokioIo;
+    use hyper_util::service::TowerToHyperService;
+    use tower::ServiceExt;
+
+    let app = app.map_request(move |mut req: axum::http::Request<hyper::body::Incoming>| {
+        req.extensions_mut().insert(ConnectInfo(peer));
+        req
+    });
+    let conn = hyper::server::conn::http1::Builder::new()
+        .serve_connection(TokioIo::new(stream), TowerToHyperService::new(app))
+        .with_upgrades();
+    tokio::pin!(conn);
+    let result = tokio::select! {
+        result = conn.as_mut() => result,
+        _ = stop_rx.changed() => {
+            conn.as_mut().graceful_shutdown();
+            conn.await
+        }
+    };
+    if let Err(e) = result {
+        tracing::debug!(error = %e, %peer, "连接异常结束");
+    }
+    drop(done_tx);
+}
+
 /// 等待关闭信号 (Ctrl+C / SIGTERM)
 async fn shutdown_signal() {
     let ctrl_c = async {
@@ -290,8 +940,8 @@ async fn shutdown_signal() {
     }
 }
 
-/// 自动启动配置了 auto_start: true 的服务
-async fn auto_start_services(manager: &Arc<ServiceManager>) {
+/// 自动启动配置了 auto_start: true 的服务，最多 `concurrency` 个同时进行
+async fn auto_start_services(manager: &Arc<ServiceManager>, concurrency: usize) {
     info!("检查启用自动启动的服务...");
 
     // 获取所有服务列表
@@ -303,39 +953,122 @@ async fn auto_start_services(manager: &Arc<ServiceManager>) {
         }
     };
 
+    // 加载 manifest 检查 auto_start
+    let mut auto_start_ids = Vec::new();
     for summary in services {
-        // 加载 manifest 检查 auto_start
-        let manifest = match manager.load_manifest(&summary.id).await {
-            Ok(m) => m,
+        match manager.load_manifest(&summary.id).await {
+            Ok(m) if m.auto_start => auto_start_ids.push(summary.id),
+            Ok(_) => {}
             Err(e) => {
                 tracing::warn!(service_id = %summary.id, error = %e, "加载 manifest 失败");
-                continue;
             }
-        };
+        }
+    }
+    auto_start_ids.sort();
+    if auto_start_ids.is_empty() {
+        return;
+    }
 
-        if !manifest.auto_start {
-            continue;
+    // 按依赖并发启动（前置服务就绪后才启动依赖方）；依赖缺失或成环时逐个回退
+    info!(count = auto_start_ids.len(), concurrency, "正在自动启动服务...");
+    match manager.start_many(&auto_start_ids, concurrency).await {
+        Ok(results) => {
+            let failed: Vec<_> = results.iter().filter(|(_, r)| r.is_err()).collect();
+            for (id, result) in &failed {
+                if let Err(e) = result {
+                    tracing::error!(service_id = %id, error = %e, "服务自动启动失败");
+                }
+            }
+            info!(
+                succeeded = results.len() - failed.len(),
+                failed = failed.len(),
+                "自动启动完成"
+            );
+            return;
         }
+        Err(e) => tracing::error!(error = %e, "计算服务依赖顺序失败，改为逐个启动"),
+    }
 
+    for id in auto_start_ids {
         // 检查服务是否已经在运行
-        let status = match manager.status(&summary.id).await {
+        let status = match manager.status(&id).await {
             Ok(s) => s,
             Err(e) => {
-                tracing::warn!(service_id = %summary.id, error = %e, "获取服务状态失败");
+                tracing::warn!(service_id = %id, error = %e, "获取服务状态失败");
                 continue;
             }
         };
 
         if status.state == hypercraft_core::ServiceState::Running {
-            info!(service_id = %summary.id, "服务已在运行，跳过自动启动");
+            info!(service_id = %id, "服务已在运行，跳过自动启动");
             continue;
         }
 
-        // 启动服务
-        info!(service_id = %summary.id, "正在自动启动服务...");
-        match manager.start(&summary.id).await {
-            Ok(_) => info!(service_id = %summary.id, "服务自动启动成功"),
-            Err(e) => tracing::error!(service_id = %summary.id, error = %e, "服务自动启动失败"),
+        // 启动服务（含依赖）
+        info!(service_id = %id, "正在自动启动服务...");
+        match manager.start_with_dependencies(&id).await {
+            Ok(_) => info!(service_id = %id, "服务自动启动成功"),
+            Err(e) => tracing::error!(service_id = %id, error = %e, "服务自动启动失败"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    #[should_panic(expected = "HC_JWT_SECRET")]
+    fn production_requires_jwt_secret() {
+        let dir = TempDir::new().unwrap();
+        resolve_jwt_secret(None, Some(" Production ".into()), dir.path());
+    }
+
+    #[test]
+    fn jwt_secret_prefers_env_value() {
+        let dir = TempDir::new().unwrap();
+        let secret = resolve_jwt_secret(
+            Some(" s3cret ".into()),
+            Some("production".into()),
+            dir.path(),
+        );
+        assert_eq!(secret, "s3cret");
+        assert!(!dir.path().join(JWT_SECRET_FILE).exists());
+    }
+
+    #[test]
+    fn generated_jwt_secret_is_persisted_and_reused() {
+        let dir = TempDir::new().unwrap();
+        let first = resolve_jwt_secret(None, None, dir.path());
+        let path = dir.path().join(JWT_SECRET_FILE);
+        assert_eq!(fs::read_to_string(&path).unwrap(), first);
+        assert_eq!(
+            resolve_jwt_secret(Some("  ".into()), None, dir.path()),
+            first
+        );
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn token_ttls_default_and_override() {
+        assert_eq!(
+            resolve_token_ttls(None, None),
+            (DEFAULT_ACCESS_TTL_SECS, DEFAULT_REFRESH_TTL_SECS)
+        );
+        assert_eq!(resolve_token_ttls(Some(60), Some(120)), (60, 120));
+        assert_eq!(resolve_token_ttls(Some(60), Some(u64::MAX)), (60, i64::MAX));
+    }
+
+    #[test]
+    #[should_panic(expected = "HC_ACCESS_TTL_SECS")]
+    fn access_ttl_must_be_shorter_than_refresh_ttl() {
+        resolve_token_ttls(Some(DEFAULT_REFRESH_TTL_SECS as u64), None);
+    }
+}
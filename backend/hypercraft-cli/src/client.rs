@@ -1,26 +1,31 @@
 use reqwest::header::{HeaderMap, AUTHORIZATION};
-use serde_json::{json, Value};
 
-/// Build an HTTP client with optional Bearer token default header.
-pub fn build_client(token: &Option<String>) -> anyhow::Result<reqwest::Client> {
-    let mut builder = reqwest::Client::builder();
+/// Build an HTTP client with optional Bearer token and `X-Admin-Identity` default headers.
+pub fn build_client(
+    token: &Option<String>,
+    admin_identity: Option<&str>,
+) -> anyhow::Result<reqwest::Client> {
+    let mut headers = HeaderMap::new();
     if let Some(tok) = token {
-        let mut headers = HeaderMap::new();
         headers.insert(AUTHORIZATION, format!("Bearer {}", tok).parse()?);
-        builder = builder.default_headers(headers);
     }
-    Ok(builder.build()?)
+    if let Some(identity) = admin_identity {
+        headers.insert("x-admin-identity", identity.parse()?);
+    }
+    Ok(reqwest::Client::builder()
+        .default_headers(headers)
+        .build()?)
+}
+
+/// Typed API client sharing the connection pool and default headers of `client`.
+pub fn api(client: &reqwest::Client, base: &str) -> hypercraft_client::Client {
+    hypercraft_client::Client::from_reqwest(client.clone(), base)
 }
 
 /// Normalize non-2xx responses into errors while returning the response on success.
+///
+/// Error bodies are decoded the same way as in the typed client (code, message, request id,
+/// `Retry-After`), so raw and typed calls report failures identically.
 pub async fn handle_error(resp: reqwest::Response) -> anyhow::Result<reqwest::Response> {
-    if resp.status().is_success() {
-        return Ok(resp);
-    }
-    let status = resp.status();
-    let body: Value = resp
-        .json()
-        .await
-        .unwrap_or_else(|_| json!({"message": "unknown error"}));
-    Err(anyhow::anyhow!("request failed {}: {}", status, body))
+    Ok(hypercraft_client::error_for_status(resp).await?)
 }
@@ -0,0 +1,196 @@
+//! 登录凭证存储：`~/.config/hypercraft/credentials.json`（0600），按 profile 或 api_base 区分。
+//!
+//! `hc login` / `hc refresh` 写入，未通过参数、环境变量或 profile 提供 token 时自动读取；
+//! access token 临近过期时用 refresh token 换新并写回。
+
+use crate::ops::{request_refresh, AuthToken};
+use crate::profile::{warn_if_world_readable, write_private};
+use anyhow::Context;
+use crossterm::style::Stylize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 距离过期不足该秒数时提前刷新
+const REFRESH_MARGIN_SECS: i64 = 60;
+
+/// 凭证文件路径
+pub fn credentials_path() -> anyhow::Result<PathBuf> {
+    Ok(crate::profile::config_path()?.with_file_name("credentials.json"))
+}
+
+/// 凭证的存储键：选中 profile 时按 profile，否则按 api_base
+pub fn credential_key(profile: Option<&str>, api_base: &str) -> String {
+    match profile {
+        Some(name) => format!("profile:{name}"),
+        None => api_base.trim_end_matches('/').to_string(),
+    }
+}
+
+/// 单个服务端的登录凭证
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredCredential {
+    pub api_base: String,
+    pub access_token: String,
+    pub refresh_token: String,
+    /// access token 过期时间（Unix 秒）
+    pub expires_at: i64,
+}
+
+impl StoredCredential {
+    fn needs_refresh(&self, now: i64) -> bool {
+        now >= self.expires_at - REFRESH_MARGIN_SECS
+    }
+}
+
+/// 凭证文件内容：存储键 → 凭证
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CredentialStore(BTreeMap<String, StoredCredential>);
+
+impl CredentialStore {
+    /// 读取凭证，文件不存在时返回空
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e).with_context(|| format!("读取 {} 失败", path.display())),
+        };
+        warn_if_world_readable(path);
+        serde_json::from_str(&content).with_context(|| format!("解析 {} 失败", path.display()))
+    }
+
+    /// 写回凭证，文件权限固定为 0600
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_vec_pretty(self)?;
+        write_private(path, &content).with_context(|| format!("写入 {} 失败", path.display()))
+    }
+
+    /// 取出凭证；记录的 api_base 与当前不一致（profile 改过地址）时视为不存在
+    pub fn get(&self, key: &str, api_base: &str) -> Option<&StoredCredential> {
+        self.0
+            .get(key)
+            .filter(|cred| cred.api_base == api_base.trim_end_matches('/'))
+    }
+
+    pub fn insert(&mut self, key: &str, api_base: &str, token: &AuthToken, now: i64) {
+        self.0.insert(
+            key.to_string(),
+            StoredCredential {
+                api_base: api_base.trim_end_matches('/').to_string(),
+                access_token: token.access_token.clone(),
+                refresh_token: token.refresh_token.clone(),
+                expires_at: now + token.expires_in,
+            },
+        );
+    }
+
+    pub fn remove(&mut self, key: &str) -> bool {
+        self.0.remove(key).is_some()
+    }
+}
+
+/// 当前 Unix 时间（秒）
+pub fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// 保存 login / refresh 返回的 token
+pub fn save_token(path: &Path, key: &str, api_base: &str, token: &AuthToken) -> anyhow::Result<()> {
+    let mut store = CredentialStore::load(path)?;
+    store.insert(key, api_base, token, unix_now());
+    store.save(path)
+}
+
+/// 读取已保存的 access token，临近过期时先用 refresh token 换新。
+///
+/// 刷新失败只提示重新登录，按未登录处理。
+pub async fn stored_access_token(
+    path: &Path,
+    key: &str,
+    api_base: &str,
+) -> anyhow::Result<Option<String>> {
+    let mut store = CredentialStore::load(path)?;
+    let Some(cred) = store.get(key, api_base).cloned() else {
+        return Ok(None);
+    };
+    if !cred.needs_refresh(unix_now()) {
+        return Ok(Some(cred.access_token));
+    }
+
+    match request_refresh(&reqwest::Client::new(), api_base, &cred.refresh_token).await {
+        Ok(token) => {
+            store.insert(key, api_base, &token, unix_now());
+            store.save(path)?;
+            Ok(Some(token.access_token))
+        }
+        Err(e) => {
+            // 走 stderr，避免污染 --output json 的标准输出
+            eprintln!(
+                "  {} 登录凭证已过期且自动刷新失败（{}），请重新执行 hc login",
+                "⚠".yellow(),
+                e
+            );
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(expires_in: i64) -> AuthToken {
+        AuthToken {
+            access_token: "access".into(),
+            refresh_token: "refresh".into(),
+            expires_in,
+            token_type: "Bearer".into(),
+        }
+    }
+
+    #[test]
+    fn credentials_are_keyed_and_expire_with_margin() {
+        let mut store = CredentialStore::default();
+        let key = credential_key(None, "http://hc/");
+        assert_eq!(key, "http://hc");
+        store.insert(&key, "http://hc/", &token(900), 1_000);
+
+        let cred = store.get(&key, "http://hc").unwrap();
+        assert_eq!(cred.expires_at, 1_900);
+        assert!(!cred.needs_refresh(1_000));
+        assert!(cred.needs_refresh(1_900 - REFRESH_MARGIN_SECS));
+        // profile 改了地址后旧凭证不再使用
+        assert!(store.get(&key, "http://other").is_none());
+
+        assert!(store.remove(&key));
+        assert!(store.get(&key, "http://hc").is_none());
+    }
+
+    #[tokio::test]
+    async fn fresh_token_is_read_without_refresh() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("credentials.json");
+        let key = credential_key(Some("prod"), "http://127.0.0.1:1");
+        assert_eq!(key, "profile:prod");
+        assert!(stored_access_token(&path, &key, "http://127.0.0.1:1")
+            .await
+            .unwrap()
+            .is_none());
+
+        save_token(&path, &key, "http://127.0.0.1:1", &token(3600)).unwrap();
+        let access = stored_access_token(&path, &key, "http://127.0.0.1:1")
+            .await
+            .unwrap();
+        assert_eq!(access.as_deref(), Some("access"));
+    }
+}
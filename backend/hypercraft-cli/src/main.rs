@@ -1,16 +1,27 @@
 mod client;
+mod credentials;
 mod ops;
+mod profile;
 
-use clap::{Parser, Subcommand};
-use hypercraft_core::init_tracing;
+use chrono::{DateTime, Utc};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
+use credentials::CredentialStore;
+use hypercraft_core::{init_tracing, ServiceListQuery};
+use ops::ui::{print_info, print_success};
 use ops::{
-    add_user_service, attach_service, create_service, create_service_interactive, create_user,
-    delete_service, delete_user, get_schedule, get_service, get_user, list_services, list_users,
-    login, logs_service, refresh_token, remove_schedule, remove_user_service, restart_service,
-    set_schedule, set_user_services, shell_loop, start_service, status_service, stop_service,
-    toggle_schedule, update_service, update_user_password, OutputFormat, ScheduleAction,
+    add_profile, add_user_service, apply_service, attach_service, batch_services, bulk_tag, clear_logs, clone_service, create_service, create_service_interactive, create_user,
+    delete_secret, delete_service, delete_user, export_config, get_schedule, get_service, get_user, group_action,
+    import_config, list_archived, list_profiles, list_schedules, list_secrets,
+    list_services, list_user_sessions, list_users, login, logs_service, parse_time_bound, refresh_token, remove_schedule, search_logs,
+    remove_user_service, restart_service, restore_archived, revoke_user_session, revoke_user_tokens, run_schedule, schedule_trigger, set_schedule, set_secret,
+    set_scheduler_paused, set_user_services, shell_loop, signal_service, start_service, status_service, system_stats, stop_service,
+    toggle_schedule, update_service, update_user_password, use_profile, validate_service,
+    watch_services, AuthToken, ManifestFormat, OutputFormat, ScheduleAction,
 };
+use profile::{CliConfig, ConnectionArgs, DEFAULT_API_BASE};
 use std::path::PathBuf;
+use std::process::ExitCode;
 
 /// CLI wrapper around the Hypercraft HTTP API.
 #[derive(Parser)]
@@ -22,13 +33,21 @@ use std::path::PathBuf;
 )]
 struct Cli {
     /// API base url
-    #[arg(long, env = "HC_API_BASE", default_value = "http://127.0.0.1:8080")]
+    #[arg(long, env = "HC_API_BASE", default_value = DEFAULT_API_BASE)]
     api_base: String,
 
     /// Bearer token for authentication
     #[arg(long, env = "HC_DEV_TOKEN")]
     token: Option<String>,
 
+    /// Operator name recorded in the audit log for DevToken sessions (X-Admin-Identity)
+    #[arg(long, env = "HC_ADMIN_IDENTITY")]
+    admin_identity: Option<String>,
+
+    /// 使用 ~/.config/hypercraft/config.toml 中的命名 profile
+    #[arg(long, env = "HC_PROFILE")]
+    profile: Option<String>,
+
     /// Output format
     #[arg(long, value_enum, default_value = "table")]
     output: OutputFormat,
@@ -41,31 +60,127 @@ struct Cli {
 enum Commands {
     // ==================== 服务管理 ====================
     /// List services
-    List,
+    List {
+        /// 按分组过滤（空字符串表示未分组）
+        #[arg(long)]
+        group: Option<String>,
+        /// 按状态过滤
+        #[arg(long, value_parser = ["running", "stopped", "unknown"])]
+        state: Option<String>,
+        /// 按标签过滤
+        #[arg(long)]
+        tag: Option<String>,
+        /// 名称或 ID 包含的关键字（忽略大小写）
+        #[arg(long, short)]
+        query: Option<String>,
+        /// 排序方式
+        #[arg(long, value_parser = ["order", "name", "state"], default_value = "order")]
+        sort: String,
+        /// 最多返回的条数
+        #[arg(long)]
+        limit: Option<usize>,
+        /// 跳过的条数
+        #[arg(long)]
+        offset: Option<usize>,
+    },
     /// Show service manifest + status
     Get { id: String },
     /// Create service（文件或交互式引导）
     Create {
-        /// manifest 文件路径
+        /// manifest 文件路径（JSON/YAML/TOML，按扩展名识别；`-` 读取标准输入）
         file: Option<PathBuf>,
+        /// 覆盖扩展名识别的格式（从标准输入读取时使用）
+        #[arg(long, value_enum)]
+        format: Option<ManifestFormat>,
         /// 交互式创建
         #[arg(long, short)]
         interactive: bool,
     },
+    /// 按 manifest 创建或更新服务（幂等，适合声明式部署）
+    Apply {
+        /// manifest 文件路径（JSON/YAML/TOML，按扩展名识别；`-` 读取标准输入）
+        file: PathBuf,
+        /// 覆盖扩展名识别的格式
+        #[arg(long, value_enum)]
+        format: Option<ManifestFormat>,
+    },
+    /// 干跑校验 manifest 文件（不创建服务），有问题时以非零退出码结束
+    Validate {
+        /// manifest 文件路径（`-` 读取标准输入）
+        file: PathBuf,
+        /// 覆盖扩展名识别的格式
+        #[arg(long, value_enum)]
+        format: Option<ManifestFormat>,
+    },
     /// 进入交互 shell（hc>）
     Shell,
-    /// Delete a service
-    Delete { id: String },
+    /// Delete a service（默认归档到 data_dir/archive，可用 `archive restore` 恢复）
+    Delete {
+        id: String,
+        /// 永久删除，不可恢复（仅管理员）
+        #[arg(long, default_value_t = false)]
+        purge: bool,
+        /// 永久删除时保留日志（归档到 data_dir/deleted/<id>/logs）
+        #[arg(long, default_value_t = false, requires = "purge")]
+        keep_logs: bool,
+    },
     /// Restart a service
     Restart { id: String },
+    /// 向服务进程发送信号（如 HUP 触发配置重载）
+    Signal {
+        id: String,
+        /// 信号: INT, TERM, HUP, USR1
+        signal: String,
+    },
     /// Update service manifest from file
-    Update { id: String, file: PathBuf },
+    Update {
+        id: String,
+        /// manifest 文件路径（`-` 读取标准输入）
+        file: PathBuf,
+        /// 覆盖扩展名识别的格式
+        #[arg(long, value_enum)]
+        format: Option<ManifestFormat>,
+        /// 跳过变更确认
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// 以现有服务为模板创建新服务
+    Clone {
+        /// 源服务 ID
+        src: String,
+        /// 新服务 ID
+        new_id: String,
+        /// 新服务名称（默认同新 ID）
+        #[arg(long)]
+        name: Option<String>,
+        /// 覆盖字段（JSON 对象），如 '{"args":["--port","8002"]}'
+        #[arg(long)]
+        overrides: Option<String>,
+    },
     /// Start a service
     Start { id: String },
     /// Stop a service
     Stop { id: String },
     /// Show status
     Status { id: String },
+    /// 批量启动/停止/重启服务
+    Batch {
+        /// 动作: start, stop, restart
+        action: String,
+        /// 服务 ID 列表（逗号分隔）
+        #[arg(value_delimiter = ',')]
+        ids: Vec<String>,
+        /// 作用于全部有权限的服务
+        #[arg(long, default_value_t = false)]
+        all: bool,
+    },
+    // ==================== 分组 ====================
+    /// 分组批量操作命令
+    #[command(subcommand)]
+    Group(GroupCommands),
+    /// 批量增删服务 tag
+    #[command(subcommand)]
+    Tag(TagCommands),
     /// Show logs (tail)
     Logs {
         id: String,
@@ -74,15 +189,73 @@ enum Commands {
         /// 持续跟随
         #[arg(long, default_value_t = false)]
         follow: bool,
+        /// 按正则搜索日志（忽略 --tail/--follow）
+        #[arg(long)]
+        grep: Option<String>,
+        /// 搜索结果前后的上下文行数
+        #[arg(long, short = 'C', default_value_t = 0)]
+        context: usize,
+        /// 最多返回的匹配数
+        #[arg(long, default_value_t = 100)]
+        limit: usize,
+        /// 清空日志（服务保持运行）
+        #[arg(long, default_value_t = false, conflicts_with_all = ["follow", "grep"])]
+        clear: bool,
+        /// 只看该时间之后的日志：30m / 2h / 1d 或 RFC 3339（需日志行以时间戳开头）
+        #[arg(long, value_parser = parse_time_bound, conflicts_with_all = ["follow", "grep", "clear"])]
+        since: Option<DateTime<Utc>>,
+        /// 只看该时间之前的日志，格式同 --since
+        #[arg(long, value_parser = parse_time_bound, conflicts_with_all = ["follow", "grep", "clear"])]
+        until: Option<DateTime<Utc>>,
     },
     /// attach 到服务终端（WebSocket）
-    Attach { id: String },
+    Attach {
+        id: String,
+        /// 只读观察，不发送输入
+        #[arg(long, default_value_t = false)]
+        readonly: bool,
+        /// 连接意外断开时自动重连并重放最近日志
+        #[arg(long, default_value_t = false)]
+        reconnect: bool,
+    },
+    /// 查看系统资源统计
+    Stats {
+        /// 持续输出（SSE 推送）
+        #[arg(long, short)]
+        watch: bool,
+        /// 推送间隔（秒）
+        #[arg(long, short, default_value_t = 2)]
+        interval: u64,
+    },
+    /// 全屏实时看板：全部服务的状态、PID、运行时长与 CPU/内存，可直接启停选中服务
+    Watch {
+        /// 刷新间隔（秒）
+        #[arg(long, short, default_value_t = 1)]
+        interval: u64,
+    },
+
+    // ==================== 配置迁移（仅管理员）====================
+    /// 导出全部服务、调度与分组（不含用户与密钥），输出到标准输出
+    Export,
+    /// 从 export 生成的 JSON 导入配置
+    Import {
+        /// 配置包文件
+        file: PathBuf,
+        /// 覆盖已存在的服务与分组
+        #[arg(long, default_value_t = false)]
+        overwrite: bool,
+    },
 
     // ==================== 定时调度 ====================
     /// 定时调度管理命令
     #[command(subcommand)]
     Schedule(ScheduleCommands),
 
+    // ==================== 归档（仅管理员）====================
+    /// 已删除（归档）服务的查看与恢复
+    #[command(subcommand)]
+    Archive(ArchiveCommands),
+
     // ==================== 认证 ====================
     /// 用户登录，获取 access token
     Login {
@@ -92,56 +265,126 @@ enum Commands {
         /// 密码
         #[arg(long, short)]
         password: String,
+        /// 双因素认证码或恢复码（未提供时按需交互输入）
+        #[arg(long)]
+        totp_code: Option<String>,
+        /// 不把 token 保存到 ~/.config/hypercraft/credentials.json
+        #[arg(long, default_value_t = false)]
+        no_save: bool,
     },
     /// 刷新 access token
     Refresh {
-        /// Refresh token
+        /// Refresh token（省略时使用已保存的凭证）
         #[arg(long, short)]
-        refresh_token: String,
+        refresh_token: Option<String>,
+        /// 不把新 token 写回凭证文件
+        #[arg(long, default_value_t = false)]
+        no_save: bool,
     },
+    /// 清除已保存的登录凭证
+    Logout,
 
     // ==================== 用户管理（仅管理员）====================
     /// 用户管理命令
     #[command(subcommand)]
     User(UserCommands),
+
+    // ==================== 密钥管理（仅超级管理员）====================
+    /// 服务密钥命令（env 中以 ${SECRET:key} 引用）
+    #[command(subcommand)]
+    Secret(SecretCommands),
+
+    // ==================== 本地配置 ====================
+    /// 服务端 profile 管理（~/.config/hypercraft/config.toml）
+    #[command(subcommand)]
+    Profile(ProfileCommands),
+}
+
+#[derive(Subcommand)]
+enum ArchiveCommands {
+    /// 列出归档的服务，最近删除的在前
+    List,
+    /// 恢复归档的服务（同 ID 的服务已存在时失败）
+    Restore {
+        /// 归档 ID（`<id>-<时间戳>`，见 `archive list`）
+        archive_id: String,
+    },
 }
 
 #[derive(Subcommand)]
 enum ScheduleCommands {
+    /// 列出全部生效中的定时任务：后续运行时间、最近触发与执行结果（管理员）
+    List,
+    /// 立即执行一次服务的计划动作（已运行时 start 跳过、未运行时 stop 跳过）
+    Run {
+        /// 服务 ID
+        id: String,
+        /// 调度条目 ID（服务有多条调度时必填）
+        #[arg(long)]
+        entry: Option<String>,
+    },
+    /// 暂停全部定时任务，保留调度配置（管理员）
+    Pause,
+    /// 恢复全部定时任务（管理员）
+    Resume,
     /// 查看服务的定时配置
     Get {
         /// 服务 ID
         id: String,
     },
-    /// 设置服务的定时配置
+    /// 添加服务的定时配置（指定 --entry 时替换该条）
     Set {
         /// 服务 ID
         id: String,
+        /// 要替换的调度条目 ID
+        #[arg(long)]
+        entry: Option<String>,
         /// Cron 表达式 (秒 分 时 日 月 周)
         /// 示例: "0 0 8 * * *" 每天 08:00
         #[arg(long, short)]
-        cron: String,
+        cron: Option<String>,
+        /// 固定间隔（秒），如 5400 表示每 90 分钟
+        #[arg(long)]
+        every: Option<u64>,
+        /// 一次性执行时间（RFC 3339），如 2025-01-01T08:00:00Z
+        #[arg(long)]
+        at: Option<String>,
         /// 触发动作: start, restart, stop
         #[arg(long, short, default_value = "start")]
         action: String,
         /// 是否启用（默认启用）
         #[arg(long, default_value_t = true)]
         enabled: bool,
+        /// IANA 时区，如 Asia/Shanghai（默认 UTC）
+        #[arg(long, short = 'z')]
+        timezone: Option<String>,
+        /// 停机错过触发时，恢复后补执行一次
+        #[arg(long)]
+        catch_up: bool,
     },
-    /// 移除服务的定时配置
+    /// 移除服务的定时配置（默认全部，--entry 仅移除一条）
     Remove {
         /// 服务 ID
         id: String,
+        /// 调度条目 ID
+        #[arg(long)]
+        entry: Option<String>,
     },
     /// 启用服务的定时任务
     Enable {
         /// 服务 ID
         id: String,
+        /// 调度条目 ID（默认全部）
+        #[arg(long)]
+        entry: Option<String>,
     },
     /// 禁用服务的定时任务
     Disable {
         /// 服务 ID
         id: String,
+        /// 调度条目 ID（默认全部）
+        #[arg(long)]
+        entry: Option<String>,
     },
 }
 
@@ -196,6 +439,9 @@ enum UserCommands {
         user_id: String,
         /// 服务 ID
         service_id: String,
+        /// 权限级别：read 仅查看，control 可启停与修改
+        #[arg(long, default_value = "control", value_parser = ["read", "control"])]
+        level: String,
     },
     /// 移除用户服务权限
     Revoke {
@@ -204,85 +450,471 @@ enum UserCommands {
         /// 服务 ID
         service_id: String,
     },
+    /// 撤销用户的全部 token（所有设备立即登出）
+    RevokeTokens {
+        /// 用户 ID
+        id: String,
+    },
+    /// 列出用户的登录会话
+    Sessions {
+        /// 用户 ID
+        id: String,
+    },
+    /// 注销用户的单个登录会话
+    KillSession {
+        /// 用户 ID
+        id: String,
+        /// 会话 ID
+        session_id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum GroupCommands {
+    /// 按 order 升序启动分组（含子分组）内的服务
+    Start { id: String },
+    /// 按 order 降序停止分组内的服务
+    Stop { id: String },
+    /// 先停止再依次启动分组内的服务
+    Restart { id: String },
+}
+
+#[derive(Subcommand)]
+enum TagCommands {
+    /// 给多个服务添加 tag
+    Add {
+        tag: String,
+        /// 服务 ID 列表（逗号分隔）
+        #[arg(long, value_delimiter = ',', required = true)]
+        ids: Vec<String>,
+    },
+    /// 从多个服务移除 tag
+    Remove {
+        tag: String,
+        /// 服务 ID 列表（逗号分隔）
+        #[arg(long, value_delimiter = ',', required = true)]
+        ids: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum SecretCommands {
+    /// 列出密钥名
+    List,
+    /// 写入或覆盖密钥
+    Set {
+        /// 密钥名
+        key: String,
+        /// 密钥值（省略时从标准输入读取）
+        #[arg(long)]
+        value: Option<String>,
+    },
+    /// 删除密钥
+    Delete {
+        /// 密钥名
+        key: String,
+    },
+}
+
+/// 保存 login / refresh 得到的 token，表格模式下提示保存位置
+fn save_credentials(
+    path: &std::path::Path,
+    key: &str,
+    api_base: &str,
+    token: &AuthToken,
+    output: OutputFormat,
+) -> anyhow::Result<()> {
+    credentials::save_token(path, key, api_base, token)?;
+    if matches!(output, OutputFormat::Table) {
+        print_success(&format!(
+            "凭证已保存到 {}，后续命令将自动使用",
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Subcommand)]
+enum ProfileCommands {
+    /// 新增或覆盖 profile
+    Add {
+        /// profile 名称
+        name: String,
+        /// API base url
+        #[arg(long)]
+        api_base: String,
+        /// Bearer token（可选，以 0600 权限保存）
+        #[arg(long)]
+        token: Option<String>,
+        /// 同时设为默认 profile
+        #[arg(long = "use", default_value_t = false)]
+        make_current: bool,
+    },
+    /// 列出 profile
+    List,
+    /// 设置默认 profile
+    Use {
+        /// profile 名称
+        name: String,
+    },
 }
 
 #[tokio::main]
-async fn main() -> anyhow::Result<()> {
+async fn main() -> ExitCode {
     // 读取仓库根或当前目录的 .env
     hypercraft_core::load_dotenv();
     init_tracing();
-    let cli = Cli::parse();
-    let client = client::build_client(&cli.token)?;
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let output = cli.output;
+    match run(matches, cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            report_error(&err, output);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// 打印失败原因：json/ndjson 输出时为一行错误 JSON，API 错误带上 `code`，便于脚本按错误码分支
+fn report_error(err: &anyhow::Error, output: OutputFormat) {
+    let api = err
+        .downcast_ref::<hypercraft_client::ClientError>()
+        .and_then(|e| e.api());
+    match output {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let value = match api {
+                Some(api) => serde_json::json!({
+                    "status": api.status,
+                    "code": api.code,
+                    "message": api.message,
+                    "details": api.details,
+                    "request_id": api.request_id,
+                }),
+                None => serde_json::json!({
+                    "status": null,
+                    "code": null,
+                    "message": format!("{:#}", err),
+                }),
+            };
+            eprintln!("{}", value);
+        }
+        // API 错误的文本形如 `request failed (409 AlreadyRunning): ...`，已含错误码
+        OutputFormat::Table => eprintln!("Error: {:?}", err),
+    }
+}
+
+async fn run(matches: ArgMatches, cli: Cli) -> anyhow::Result<()> {
+    let config_path = profile::config_path()?;
+
+    // profile 命令只读写本地配置，不依赖（可能无效的）当前 profile
+    if let Commands::Profile(profile_cmd) = &cli.command {
+        match profile_cmd {
+            ProfileCommands::Add {
+                name,
+                api_base,
+                token,
+                make_current,
+            } => add_profile(&config_path, name, api_base, token.clone(), *make_current)?,
+            ProfileCommands::List => list_profiles(&config_path, cli.output)?,
+            ProfileCommands::Use { name } => use_profile(&config_path, name)?,
+        }
+        return Ok(());
+    }
+
+    let config = CliConfig::load(&config_path)?;
+    let explicit = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+    let (api_base, token) = ConnectionArgs {
+        api_base: cli.api_base.clone(),
+        api_base_explicit: explicit("api_base"),
+        token: cli.token.clone(),
+        token_explicit: explicit("token"),
+    }
+    .resolve(config.select(cli.profile.as_deref())?);
+
+    // 没有显式 token 时使用 hc login 保存的凭证（认证命令自己处理凭证，不自动刷新）
+    let credentials_path = credentials::credentials_path()?;
+    let credential_key = credentials::credential_key(
+        cli.profile.as_deref().or(config.current.as_deref()),
+        &api_base,
+    );
+    let auth_command = matches!(
+        cli.command,
+        Commands::Login { .. } | Commands::Refresh { .. } | Commands::Logout
+    );
+    let token = match token {
+        Some(token) => Some(token),
+        None if !auth_command => {
+            credentials::stored_access_token(&credentials_path, &credential_key, &api_base).await?
+        }
+        None => None,
+    };
+    let client = client::build_client(&token, cli.admin_identity.as_deref())?;
 
     match cli.command {
         // 服务管理命令
-        Commands::List => list_services(&client, &cli.api_base, cli.output).await?,
-        Commands::Get { id } => get_service(&client, &cli.api_base, &id, cli.output).await?,
-        Commands::Create { file, interactive } => {
+        Commands::List {
+            group,
+            state,
+            tag,
+            query,
+            sort,
+            limit,
+            offset,
+        } => {
+            let query = ServiceListQuery {
+                group,
+                state: state.map(|s| serde_json::from_value(serde_json::Value::String(s))).transpose()?,
+                tag,
+                q: query,
+                sort: serde_json::from_value(serde_json::Value::String(sort))?,
+                offset,
+                limit,
+            };
+            list_services(&client, &api_base, &query, cli.output).await?
+        }
+        Commands::Get { id } => get_service(&client, &api_base, &id, cli.output).await?,
+        Commands::Create {
+            file,
+            format,
+            interactive,
+        } => {
             if interactive {
-                create_service_interactive(&client, &cli.api_base, cli.output).await?
+                create_service_interactive(&client, &api_base, cli.output).await?
             } else if let Some(path) = file {
-                create_service(&client, &cli.api_base, path, cli.output).await?
+                create_service(&client, &api_base, path, format, cli.output).await?
             } else {
                 anyhow::bail!("请提供 --file 或使用 --interactive");
             }
         }
         Commands::Shell => {
-            shell_loop(&client, &cli.api_base, cli.output, cli.token.as_deref()).await?
+            shell_loop(&client, &api_base, cli.output, token.as_deref()).await?
+        }
+        Commands::Delete {
+            id,
+            purge,
+            keep_logs,
+        } => delete_service(&client, &api_base, &id, purge, keep_logs).await?,
+        Commands::Start { id } => start_service(&client, &api_base, &id, cli.output).await?,
+        Commands::Stop { id } => stop_service(&client, &api_base, &id, cli.output).await?,
+        Commands::Status { id } => status_service(&client, &api_base, &id, cli.output).await?,
+        Commands::Batch { action, ids, all } => {
+            batch_services(&client, &api_base, &action, &ids, all, cli.output).await?
         }
-        Commands::Delete { id } => delete_service(&client, &cli.api_base, &id).await?,
-        Commands::Start { id } => start_service(&client, &cli.api_base, &id, cli.output).await?,
-        Commands::Stop { id } => stop_service(&client, &cli.api_base, &id, cli.output).await?,
-        Commands::Status { id } => status_service(&client, &cli.api_base, &id, cli.output).await?,
         Commands::Restart { id } => {
-            restart_service(&client, &cli.api_base, &id, cli.output).await?
+            restart_service(&client, &api_base, &id, cli.output).await?
+        }
+        Commands::Group(group_cmd) => {
+            let (id, action) = match group_cmd {
+                GroupCommands::Start { id } => (id, "start"),
+                GroupCommands::Stop { id } => (id, "stop"),
+                GroupCommands::Restart { id } => (id, "restart"),
+            };
+            group_action(&client, &api_base, &id, action, cli.output).await?
+        }
+        Commands::Tag(tag_cmd) => match tag_cmd {
+            TagCommands::Add { tag, ids } => {
+                bulk_tag(&client, &api_base, &ids, &[tag], &[], cli.output).await?
+            }
+            TagCommands::Remove { tag, ids } => {
+                bulk_tag(&client, &api_base, &ids, &[], &[tag], cli.output).await?
+            }
+        },
+        Commands::Signal { id, signal } => {
+            signal_service(&client, &api_base, &id, &signal, cli.output).await?
+        }
+        Commands::Apply { file, format } => {
+            apply_service(&client, &api_base, file, format, cli.output).await?
+        }
+        Commands::Validate { file, format } => {
+            validate_service(&client, &api_base, file, format, cli.output).await?
         }
-        Commands::Update { id, file } => {
-            update_service(&client, &cli.api_base, &id, file, cli.output).await?
+        Commands::Update {
+            id,
+            file,
+            format,
+            yes,
+        } => update_service(&client, &api_base, &id, file, format, yes, cli.output).await?,
+        Commands::Clone {
+            src,
+            new_id,
+            name,
+            overrides,
+        } => {
+            clone_service(
+                &client,
+                &api_base,
+                &src,
+                &new_id,
+                name,
+                overrides,
+                cli.output,
+            )
+            .await?
         }
-        Commands::Logs { id, tail, follow } => {
-            logs_service(&client, &cli.api_base, &id, tail, follow, cli.output).await?
+        Commands::Logs {
+            id,
+            tail,
+            follow,
+            grep,
+            context,
+            limit,
+            clear,
+            since,
+            until,
+        } => match grep {
+            _ if clear => clear_logs(&client, &api_base, &id).await?,
+            Some(pattern) => {
+                search_logs(&client, &api_base, &id, &pattern, context, limit, cli.output)
+                    .await?
+            }
+            None => {
+                logs_service(
+                    &client, &api_base, &id, tail, follow, since, until, cli.output,
+                )
+                .await?
+            }
+        },
+        Commands::Stats { watch, interval } => {
+            system_stats(&client, &api_base, watch, interval, cli.output).await?
         }
-        Commands::Attach { id } => attach_service(&cli.api_base, &id, cli.token.as_deref()).await?,
+        Commands::Watch { interval } => watch_services(&client, &api_base, interval).await?,
+        Commands::Attach {
+            id,
+            readonly,
+            reconnect,
+        } => attach_service(&api_base, &id, token.as_deref(), readonly, reconnect).await?,
 
         // 定时调度命令
+        Commands::Export => export_config(&client, &api_base).await?,
+        Commands::Import { file, overwrite } => {
+            import_config(&client, &api_base, &file, overwrite, cli.output).await?
+        }
+        Commands::Archive(archive_cmd) => match archive_cmd {
+            ArchiveCommands::List => list_archived(&client, &api_base, cli.output).await?,
+            ArchiveCommands::Restore { archive_id } => {
+                restore_archived(&client, &api_base, &archive_id, cli.output).await?
+            }
+        },
         Commands::Schedule(sched_cmd) => match sched_cmd {
+            ScheduleCommands::List => list_schedules(&client, &api_base, cli.output).await?,
+            ScheduleCommands::Run { id, entry } => {
+                run_schedule(&client, &api_base, &id, entry.as_deref(), cli.output).await?
+            }
+            ScheduleCommands::Pause => {
+                set_scheduler_paused(&client, &api_base, true, cli.output).await?
+            }
+            ScheduleCommands::Resume => {
+                set_scheduler_paused(&client, &api_base, false, cli.output).await?
+            }
             ScheduleCommands::Get { id } => {
-                get_schedule(&client, &cli.api_base, &id, cli.output).await?
+                get_schedule(&client, &api_base, &id, cli.output).await?
             }
             ScheduleCommands::Set {
                 id,
+                entry,
                 cron,
+                every,
+                at,
                 action,
                 enabled,
+                timezone,
+                catch_up,
             } => {
                 let action: ScheduleAction = action.parse().map_err(|e: String| anyhow::anyhow!(e))?;
-                set_schedule(&client, &cli.api_base, &id, &cron, action, enabled, cli.output)
-                    .await?
+                let (cron, kind) = schedule_trigger(cron, every, at)?;
+                set_schedule(
+                    &client,
+                    &api_base,
+                    &id,
+                    entry.as_deref(),
+                    cron,
+                    kind,
+                    action,
+                    enabled,
+                    timezone,
+                    catch_up,
+                    cli.output,
+                )
+                .await?
             }
-            ScheduleCommands::Remove { id } => {
-                remove_schedule(&client, &cli.api_base, &id, cli.output).await?
+            ScheduleCommands::Remove { id, entry } => {
+                remove_schedule(&client, &api_base, &id, entry.as_deref(), cli.output).await?
             }
-            ScheduleCommands::Enable { id } => {
-                toggle_schedule(&client, &cli.api_base, &id, true, cli.output).await?
+            ScheduleCommands::Enable { id, entry } => {
+                toggle_schedule(&client, &api_base, &id, entry.as_deref(), true, cli.output)
+                    .await?
             }
-            ScheduleCommands::Disable { id } => {
-                toggle_schedule(&client, &cli.api_base, &id, false, cli.output).await?
+            ScheduleCommands::Disable { id, entry } => {
+                toggle_schedule(&client, &api_base, &id, entry.as_deref(), false, cli.output)
+                    .await?
             }
         },
 
         // 认证命令
-        Commands::Login { username, password } => {
-            login(&client, &cli.api_base, &username, &password, cli.output).await?;
+        Commands::Login {
+            username,
+            password,
+            totp_code,
+            no_save,
+        } => {
+            let auth_token = login(
+                &client,
+                &api_base,
+                &username,
+                &password,
+                totp_code.as_deref(),
+                cli.output,
+            )
+            .await?;
+            if !no_save {
+                save_credentials(
+                    &credentials_path,
+                    &credential_key,
+                    &api_base,
+                    &auth_token,
+                    cli.output,
+                )?;
+            }
         }
-        Commands::Refresh { refresh_token: rt } => {
-            refresh_token(&client, &cli.api_base, &rt, cli.output).await?;
+        Commands::Refresh {
+            refresh_token: rt,
+            no_save,
+        } => {
+            let rt = match rt {
+                Some(rt) => rt,
+                None => CredentialStore::load(&credentials_path)?
+                    .get(&credential_key, &api_base)
+                    .map(|cred| cred.refresh_token.clone())
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("未提供 --refresh-token，且没有已保存的登录凭证")
+                    })?,
+            };
+            let auth_token = refresh_token(&client, &api_base, &rt, cli.output).await?;
+            if !no_save {
+                save_credentials(
+                    &credentials_path,
+                    &credential_key,
+                    &api_base,
+                    &auth_token,
+                    cli.output,
+                )?;
+            }
+        }
+        Commands::Logout => {
+            let mut store = CredentialStore::load(&credentials_path)?;
+            if store.remove(&credential_key) {
+                store.save(&credentials_path)?;
+                print_success(&format!("已清除 {} 的登录凭证", credential_key));
+            } else {
+                print_info(&format!("{} 没有已保存的登录凭证", credential_key));
+            }
         }
 
         // 用户管理命令
         Commands::User(user_cmd) => match user_cmd {
-            UserCommands::List => list_users(&client, &cli.api_base, cli.output).await?,
-            UserCommands::Get { id } => get_user(&client, &cli.api_base, &id, cli.output).await?,
+            UserCommands::List => list_users(&client, &api_base, cli.output).await?,
+            UserCommands::Get { id } => get_user(&client, &api_base, &id, cli.output).await?,
             UserCommands::Create {
                 username,
                 password,
@@ -290,7 +922,7 @@ async fn main() -> anyhow::Result<()> {
             } => {
                 create_user(
                     &client,
-                    &cli.api_base,
+                    &api_base,
                     &username,
                     &password,
                     services.unwrap_or_default(),
@@ -298,7 +930,7 @@ async fn main() -> anyhow::Result<()> {
                 )
                 .await?
             }
-            UserCommands::Delete { id } => delete_user(&client, &cli.api_base, &id).await?,
+            UserCommands::Delete { id } => delete_user(&client, &api_base, &id).await?,
             UserCommands::Password {
                 id,
                 password,
@@ -306,7 +938,7 @@ async fn main() -> anyhow::Result<()> {
             } => {
                 update_user_password(
                     &client,
-                    &cli.api_base,
+                    &api_base,
                     &id,
                     &password,
                     current.as_deref(),
@@ -315,22 +947,52 @@ async fn main() -> anyhow::Result<()> {
                 .await?
             }
             UserCommands::SetServices { id, services } => {
-                set_user_services(&client, &cli.api_base, &id, services, cli.output).await?
+                set_user_services(&client, &api_base, &id, services, cli.output).await?
             }
             UserCommands::Grant {
                 user_id,
                 service_id,
+                level,
             } => {
-                add_user_service(&client, &cli.api_base, &user_id, &service_id, cli.output).await?
+                add_user_service(
+                    &client,
+                    &api_base,
+                    &user_id,
+                    &service_id,
+                    &level,
+                    cli.output,
+                )
+                .await?
             }
             UserCommands::Revoke {
                 user_id,
                 service_id,
             } => {
-                remove_user_service(&client, &cli.api_base, &user_id, &service_id, cli.output)
+                remove_user_service(&client, &api_base, &user_id, &service_id, cli.output)
                     .await?
             }
+            UserCommands::RevokeTokens { id } => {
+                revoke_user_tokens(&client, &api_base, &id).await?
+            }
+            UserCommands::Sessions { id } => {
+                list_user_sessions(&client, &api_base, &id, cli.output).await?
+            }
+            UserCommands::KillSession { id, session_id } => {
+                revoke_user_session(&client, &api_base, &id, &session_id).await?
+            }
+        },
+
+        // 密钥管理命令
+        Commands::Secret(secret_cmd) => match secret_cmd {
+            SecretCommands::List => list_secrets(&client, &api_base, cli.output).await?,
+            SecretCommands::Set { key, value } => {
+                set_secret(&client, &api_base, &key, value).await?
+            }
+            SecretCommands::Delete { key } => delete_secret(&client, &api_base, &key).await?,
         },
+
+        // 已在上方处理
+        Commands::Profile(_) => {}
     }
 
     Ok(())
@@ -343,6 +1005,205 @@ mod tests {
     #[test]
     fn clap_parses() {
         let args = ["hc", "list"];
-        let _ = Cli::parse_from(&args);
+        let _ = Cli::parse_from(args);
+    }
+
+    #[test]
+    fn clap_parses_watch() {
+        let cli = Cli::parse_from(["hc", "watch", "-i", "3"]);
+        match cli.command {
+            Commands::Watch { interval } => assert_eq!(interval, 3),
+            _ => panic!("expected watch command"),
+        }
+    }
+
+    #[test]
+    fn clap_parses_import() {
+        let cli = Cli::parse_from(["hc", "import", "backup.json", "--overwrite"]);
+        match cli.command {
+            Commands::Import { file, overwrite } => {
+                assert_eq!(file, PathBuf::from("backup.json"));
+                assert!(overwrite);
+            }
+            _ => panic!("expected import command"),
+        }
+    }
+
+    #[test]
+    fn clap_parses_list_filters() {
+        let cli = Cli::parse_from(["hc", "list", "--state", "running", "--group", "web"]);
+        match cli.command {
+            Commands::List {
+                group, state, sort, ..
+            } => {
+                assert_eq!(group.as_deref(), Some("web"));
+                assert_eq!(state.as_deref(), Some("running"));
+                assert_eq!(sort, "order");
+            }
+            _ => panic!("expected list command"),
+        }
+    }
+
+    #[test]
+    fn clap_parses_profile_add() {
+        let cli = Cli::parse_from([
+            "hc",
+            "--profile",
+            "prod",
+            "profile",
+            "add",
+            "staging",
+            "--api-base",
+            "http://s",
+            "--use",
+        ]);
+        assert_eq!(cli.profile.as_deref(), Some("prod"));
+        match cli.command {
+            Commands::Profile(ProfileCommands::Add {
+                name,
+                api_base,
+                token,
+                make_current,
+            }) => {
+                assert_eq!(name, "staging");
+                assert_eq!(api_base, "http://s");
+                assert!(token.is_none());
+                assert!(make_current);
+            }
+            _ => panic!("expected profile add command"),
+        }
+    }
+
+    #[test]
+    fn clap_parses_refresh_without_token() {
+        let cli = Cli::parse_from(["hc", "refresh", "--no-save"]);
+        match cli.command {
+            Commands::Refresh {
+                refresh_token,
+                no_save,
+            } => {
+                assert!(refresh_token.is_none());
+                assert!(no_save);
+            }
+            _ => panic!("expected refresh command"),
+        }
+        assert!(matches!(
+            Cli::parse_from(["hc", "logout"]).command,
+            Commands::Logout
+        ));
+    }
+
+    #[test]
+    fn clap_parses_create_from_stdin_with_format() {
+        let cli = Cli::parse_from(["hc", "create", "-", "--format", "yaml"]);
+        match cli.command {
+            Commands::Create { file, format, .. } => {
+                assert_eq!(file, Some(PathBuf::from("-")));
+                assert_eq!(format, Some(ManifestFormat::Yaml));
+            }
+            _ => panic!("expected create command"),
+        }
+    }
+
+    #[test]
+    fn clap_parses_apply() {
+        let cli = Cli::parse_from(["hc", "apply", "mc.json"]);
+        match cli.command {
+            Commands::Apply { file, format } => {
+                assert_eq!(file, PathBuf::from("mc.json"));
+                assert_eq!(format, None);
+            }
+            _ => panic!("expected apply command"),
+        }
+    }
+
+    #[test]
+    fn clap_parses_delete_purge() {
+        let cli = Cli::parse_from(["hc", "delete", "mc"]);
+        assert!(matches!(
+            cli.command,
+            Commands::Delete {
+                purge: false,
+                keep_logs: false,
+                ..
+            }
+        ));
+        let cli = Cli::parse_from(["hc", "delete", "mc", "--purge", "--keep-logs"]);
+        assert!(matches!(
+            cli.command,
+            Commands::Delete {
+                purge: true,
+                keep_logs: true,
+                ..
+            }
+        ));
+        assert!(Cli::try_parse_from(["hc", "delete", "mc", "--keep-logs"]).is_err());
+    }
+
+    #[test]
+    fn clap_parses_update_yes() {
+        let cli = Cli::parse_from(["hc", "update", "mc", "mc.toml", "-y"]);
+        match cli.command {
+            Commands::Update { id, yes, .. } => {
+                assert_eq!(id, "mc");
+                assert!(yes);
+            }
+            _ => panic!("expected update command"),
+        }
+    }
+
+    #[test]
+    fn clap_parses_logs_time_range() {
+        let cli = Cli::parse_from([
+            "hc",
+            "logs",
+            "mc",
+            "--since",
+            "1h",
+            "--until",
+            "2026-01-01T00:00:00Z",
+        ]);
+        match cli.command {
+            Commands::Logs { since, until, .. } => {
+                assert!(since.is_some());
+                assert_eq!(until.unwrap().to_rfc3339(), "2026-01-01T00:00:00+00:00");
+            }
+            _ => panic!("expected logs command"),
+        }
+        assert!(Cli::try_parse_from(["hc", "logs", "mc", "--since", "1h", "--follow"]).is_err());
+    }
+
+    #[test]
+    fn clap_parses_batch_ids() {
+        let cli = Cli::parse_from(["hc", "batch", "stop", "a,b"]);
+        match cli.command {
+            Commands::Batch { action, ids, all } => {
+                assert_eq!(action, "stop");
+                assert_eq!(ids, vec!["a", "b"]);
+                assert!(!all);
+            }
+            _ => panic!("expected batch command"),
+        }
+    }
+
+    #[test]
+    fn clap_parses_tag_add_ids() {
+        let cli = Cli::parse_from(["hc", "tag", "add", "web", "--ids", "a,b,c"]);
+        match cli.command {
+            Commands::Tag(TagCommands::Add { tag, ids }) => {
+                assert_eq!(tag, "web");
+                assert_eq!(ids, ["a", "b", "c"]);
+            }
+            _ => panic!("expected tag add command"),
+        }
+    }
+
+    #[test]
+    fn clap_parses_group_start() {
+        let cli = Cli::parse_from(["hc", "group", "start", "web"]);
+        assert!(matches!(
+            cli.command,
+            Commands::Group(GroupCommands::Start { id }) if id == "web"
+        ));
     }
 }
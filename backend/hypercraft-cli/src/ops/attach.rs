@@ -8,37 +8,77 @@ use std::io::{self, Write};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::net::TcpStream;
 use tokio::sync::mpsc;
 use tokio_tungstenite::{
     connect_async,
     tungstenite::{client::IntoClientRequest, protocol::Message},
+    MaybeTlsStream, WebSocketStream,
 };
 
+/// 重连退避上限
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+/// 连续重连失败的最大次数
+const MAX_RECONNECT_ATTEMPTS: u32 = 10;
+
+enum InputEvent {
+    Bytes(Vec<u8>),
+    Signal(&'static str),
+    Resize(u16, u16),
+    Quit,
+}
+
+/// 一次 WebSocket 会话的结束原因
+#[derive(Debug, PartialEq, Eq)]
+enum SessionEnd {
+    /// 用户按下 Ctrl+Q
+    UserQuit,
+    /// 服务端主动结束（拒绝 attach、正常关闭、空闲超时），重连无意义
+    Finished,
+    /// 连接意外断开，可重连
+    Dropped,
+}
+
 /// Attach to a running service via WebSocket, forwarding stdin/stdout.
-pub async fn attach_service(base: &str, id: &str, token: Option<&str>) -> anyhow::Result<()> {
-    let ws_url = build_ws_url(base, &format!("services/{}/attach", id));
-    let mut request = ws_url.into_client_request()?;
-    request
-        .headers_mut()
-        .insert("User-Agent", "hypercraft-cli".parse()?);
-    if let Some(tok) = token {
+///
+/// `readonly` 以只读观察者接入：只显示输出，本地按键（除 Ctrl+Q）不会发送。
+/// `reconnect` 时连接意外断开会按退避重试，服务端在新连接上重放最近日志以恢复终端。
+pub async fn attach_service(
+    base: &str,
+    id: &str,
+    token: Option<&str>,
+    readonly: bool,
+    reconnect: bool,
+) -> anyhow::Result<()> {
+    let path = if readonly {
+        format!("services/{}/attach?mode=readonly", id)
+    } else {
+        format!("services/{}/attach", id)
+    };
+    let ws_url = build_ws_url(base, &path);
+    let connect = || async {
+        let mut request = ws_url.as_str().into_client_request()?;
         request
             .headers_mut()
-            .insert("Authorization", format!("Bearer {}", tok).parse()?);
-    }
-
-    let (ws_stream, _resp) = connect_async(request).await?;
-    println!(
-        "# attach to {}. Real PTY stream; remote echoes input. \
-         Ctrl+Q exits; Ctrl+C/Ctrl+I/Ctrl+T/Ctrl+K send INT/INT/TERM/KILL.",
-        id
-    );
-    let (mut write, mut read) = ws_stream.split();
+            .insert("User-Agent", "hypercraft-cli".parse()?);
+        if let Some(tok) = token {
+            request
+                .headers_mut()
+                .insert("Authorization", format!("Bearer {}", tok).parse()?);
+        }
+        let (ws_stream, _resp) = connect_async(request).await?;
+        anyhow::Ok(ws_stream)
+    };
 
-    enum InputEvent {
-        Bytes(Vec<u8>),
-        Signal(&'static str),
-        Quit,
+    let mut ws_stream = connect().await?;
+    if readonly {
+        println!("# attach to {} (read-only). Ctrl+Q exits.", id);
+    } else {
+        println!(
+            "# attach to {}. Real PTY stream; remote echoes input. \
+             Ctrl+Q exits; Ctrl+C/Ctrl+I/Ctrl+T/Ctrl+K send INT/INT/TERM/KILL.",
+            id
+        );
     }
 
     let (input_tx, mut input_rx) = mpsc::channel::<InputEvent>(64);
@@ -113,11 +153,12 @@ pub async fn attach_service(base: &str, id: &str, token: Option<&str>) -> anyhow
                         let _ = input_tx.blocking_send(InputEvent::Bytes(bytes));
                     }
                 }
-                Event::Paste(s) => {
-                    if !s.is_empty() {
-                        let bytes = s.into_bytes();
-                        let _ = input_tx.blocking_send(InputEvent::Bytes(bytes));
-                    }
+                Event::Resize(cols, rows) => {
+                    let _ = input_tx.blocking_send(InputEvent::Resize(rows, cols));
+                }
+                Event::Paste(s) if !s.is_empty() => {
+                    let bytes = s.into_bytes();
+                    let _ = input_tx.blocking_send(InputEvent::Bytes(bytes));
                 }
                 _ => {}
             }
@@ -128,24 +169,117 @@ pub async fn attach_service(base: &str, id: &str, token: Option<&str>) -> anyhow
         Ok(())
     });
 
+    loop {
+        match run_session(ws_stream, &mut input_rx, readonly).await {
+            SessionEnd::Dropped if reconnect => {}
+            _ => break,
+        }
+
+        let mut attempt = 0;
+        let reconnected = loop {
+            if attempt == MAX_RECONNECT_ATTEMPTS {
+                break None;
+            }
+            let delay = reconnect_delay(attempt);
+            attempt += 1;
+            print!(
+                "\r\n# connection lost, reconnecting in {}s ({}/{}). Ctrl+Q exits.\r\n",
+                delay.as_secs(),
+                attempt,
+                MAX_RECONNECT_ATTEMPTS
+            );
+            let _ = io::stdout().flush();
+            // 等待期间仍响应 Ctrl+Q；其他按键丢弃
+            let quit = tokio::select! {
+                _ = tokio::time::sleep(delay) => false,
+                input = wait_for_quit(&mut input_rx) => input,
+            };
+            if quit {
+                break None;
+            }
+            if let Ok(stream) = connect().await {
+                break Some(stream);
+            }
+        };
+        match reconnected {
+            Some(stream) => {
+                // 清屏后由服务端重放的日志恢复终端内容
+                print!("\x1b[2J\x1b[H");
+                let _ = io::stdout().flush();
+                ws_stream = stream;
+            }
+            None => break,
+        }
+    }
+
+    stop.store(true, Ordering::Relaxed);
+    // Ensure terminal mode is restored on exit.
+    let _ = crossterm::terminal::disable_raw_mode();
+    Ok(())
+}
+
+/// 第 `attempt` 次重连前的等待时间：1s、2s、4s … 最多 30s
+fn reconnect_delay(attempt: u32) -> Duration {
+    Duration::from_secs(1u64 << attempt.min(5)).min(MAX_RECONNECT_DELAY)
+}
+
+/// 服务端以这些关闭码拒绝 attach 时重连也不会成功（4000 + HTTP 状态码）
+fn close_is_final(code: u16) -> bool {
+    matches!(code, 1000 | 4400 | 4401 | 4403 | 4404)
+}
+
+/// 丢弃输入直到 Ctrl+Q；输入线程结束时同样视为退出
+async fn wait_for_quit(input_rx: &mut mpsc::Receiver<InputEvent>) -> bool {
+    loop {
+        match input_rx.recv().await {
+            Some(InputEvent::Quit) | None => return true,
+            Some(_) => {}
+        }
+    }
+}
+
+/// 转发一次 WebSocket 会话，直到用户退出或连接结束
+async fn run_session(
+    ws_stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    input_rx: &mut mpsc::Receiver<InputEvent>,
+    readonly: bool,
+) -> SessionEnd {
+    let (mut write, mut read) = ws_stream.split();
+    // 每次连接都同步本地终端尺寸；之后由 Resize 事件（SIGWINCH）触发
+    if !readonly {
+        if let Ok((cols, rows)) = crossterm::terminal::size() {
+            let body = json!({"type": "resize", "rows": rows, "cols": cols});
+            let _ = write.send(Message::Text(body.to_string())).await;
+        }
+    }
+    let mut idle_timeout = false;
+
     loop {
         tokio::select! {
             maybe_input = input_rx.recv() => {
                 match maybe_input {
                     Some(InputEvent::Quit) | None => {
                         let _ = write.send(Message::Close(None)).await;
-                        break;
+                        return SessionEnd::UserQuit;
                     }
+                    // 只读会话不发送任何输入或控制命令
+                    Some(_) if readonly => {}
                     Some(InputEvent::Signal(sig)) => {
                         let body = json!({"type": "signal", "signal": sig});
                         let payload = Message::Text(body.to_string());
                         if write.send(payload).await.is_err() {
-                            break;
+                            return SessionEnd::Dropped;
+                        }
+                    }
+                    Some(InputEvent::Resize(rows, cols)) => {
+                        let body = json!({"type": "resize", "rows": rows, "cols": cols});
+                        if write.send(Message::Text(body.to_string())).await.is_err() {
+                            return SessionEnd::Dropped;
                         }
                     }
                     Some(InputEvent::Bytes(bytes)) => {
                         if write.send(Message::Binary(bytes)).await.is_err() {
-                            break;
+                            return SessionEnd::Dropped;
                         }
                     }
                 }
@@ -158,6 +292,8 @@ pub async fn attach_service(base: &str, id: &str, token: Option<&str>) -> anyhow
                                 match ty {
                                     "notice" => {
                                         if let Some(msg) = v.get("message").and_then(|d| d.as_str()) {
+                                            // 服务端空闲超时后断开，不再重连
+                                            idle_timeout |= msg == "attach idle timeout";
                                             println!("# {}", msg);
                                         }
                                     }
@@ -166,6 +302,14 @@ pub async fn attach_service(base: &str, id: &str, token: Option<&str>) -> anyhow
                                             eprintln!("error: {}", msg);
                                         }
                                     }
+                                    "stderr" => {
+                                        // 单独捕获的 stderr（split_stderr）写到本地 stderr
+                                        if let Some(data) = v.get("data").and_then(|d| d.as_str()) {
+                                            let mut stderr = io::stderr();
+                                            let _ = stderr.write_all(&convert_lf_to_crlf(data.as_bytes()));
+                                            let _ = stderr.flush();
+                                        }
+                                    }
                                     _ => {
                                         println!("{text}");
                                     }
@@ -184,17 +328,21 @@ pub async fn attach_service(base: &str, id: &str, token: Option<&str>) -> anyhow
                         let _ = stdout.write_all(&converted);
                         let _ = stdout.flush();
                     }
-                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Ok(Message::Close(Some(frame)))) if close_is_final(frame.code.into()) => {
+                        return SessionEnd::Finished;
+                    }
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => {
+                        return if idle_timeout {
+                            SessionEnd::Finished
+                        } else {
+                            SessionEnd::Dropped
+                        };
+                    }
                     _ => {}
                 }
             }
         }
     }
-
-    stop.store(true, Ordering::Relaxed);
-    // Ensure terminal mode is restored on exit.
-    let _ = crossterm::terminal::disable_raw_mode();
-    Ok(())
 }
 
 fn build_ws_url(base: &str, path: &str) -> String {
@@ -221,3 +369,24 @@ fn convert_lf_to_crlf(data: &[u8]) -> Vec<u8> {
     }
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reconnect_backs_off_up_to_cap() {
+        let delays: Vec<u64> = (0..8).map(|n| reconnect_delay(n).as_secs()).collect();
+        assert_eq!(delays, vec![1, 2, 4, 8, 16, 30, 30, 30]);
+    }
+
+    #[test]
+    fn rejected_attach_is_not_retried() {
+        assert!(close_is_final(4403));
+        assert!(close_is_final(1000));
+        // 服务未运行、并发超限等可能恢复，继续重连
+        assert!(!close_is_final(4409));
+        assert!(!close_is_final(4429));
+        assert!(!close_is_final(1006));
+    }
+}
@@ -0,0 +1,99 @@
+//! 配置导入导出 CLI 操作（仅管理员）
+
+use super::output::{print_output, OutputFormat};
+use super::ui::{print_error, print_header, print_success, print_table_header, print_table_row};
+use anyhow::anyhow;
+use crossterm::style::Stylize;
+use hypercraft_core::{ImportItemResult, ImportOutcome, ImportReport};
+use reqwest::Client;
+use std::path::Path;
+
+/// 导出配置包到标准输出（`hc export > backup.json`）
+pub async fn export_config(client: &Client, base: &str) -> anyhow::Result<()> {
+    let url = format!("{}/export", base);
+    let resp = client.get(&url).send().await?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        return Err(anyhow!("导出配置失败 ({}): {}", status, body));
+    }
+
+    let bundle: serde_json::Value = resp.json().await?;
+    println!("{}", serde_json::to_string_pretty(&bundle)?);
+    Ok(())
+}
+
+/// 从文件导入配置包，逐条打印结果；有失败条目时返回错误
+pub async fn import_config(
+    client: &Client,
+    base: &str,
+    file: &Path,
+    overwrite: bool,
+    output: OutputFormat,
+) -> anyhow::Result<()> {
+    let content = std::fs::read_to_string(file)?;
+    let bundle: serde_json::Value = serde_json::from_str(&content)?;
+
+    let url = format!("{}/import", base);
+    let resp = client
+        .post(&url)
+        .query(&[("overwrite", overwrite)])
+        .json(&bundle)
+        .send()
+        .await?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        print_error(&format!("导入配置失败 ({}): {}", status, body));
+        return Ok(());
+    }
+
+    let report: ImportReport = resp.json().await?;
+    match output {
+        OutputFormat::Json | OutputFormat::Ndjson => print_output(&report, output)?,
+        OutputFormat::Table => {
+            print_header("📦 导入结果");
+            println!();
+            print_items("分组", &report.groups);
+            print_items("服务", &report.services);
+        }
+    }
+
+    let failed = report
+        .groups
+        .iter()
+        .chain(&report.services)
+        .filter(|item| item.outcome == ImportOutcome::Failed)
+        .count();
+    if failed > 0 {
+        return Err(anyhow!("{} 个条目导入失败", failed));
+    }
+    if matches!(output, OutputFormat::Table) {
+        print_success("导入完成");
+    }
+    Ok(())
+}
+
+fn print_items(title: &str, items: &[ImportItemResult]) {
+    if items.is_empty() {
+        return;
+    }
+    println!("  {}", title.white().bold());
+    print_table_header(&[("ID", 24), ("RESULT", 10), ("DETAIL", 40)]);
+    for item in items {
+        let outcome = match item.outcome {
+            ImportOutcome::Created => "created",
+            ImportOutcome::Updated => "updated",
+            ImportOutcome::Skipped => "skipped",
+            ImportOutcome::Failed => "failed",
+        };
+        print_table_row(&[
+            (item.id.as_str(), 24),
+            (outcome, 10),
+            (item.error.as_deref().unwrap_or(""), 40),
+        ]);
+    }
+    println!();
+}
@@ -1,7 +1,11 @@
-use super::output::OutputFormat;
-use super::ui::{print_header, print_hint, print_info, print_section};
+use super::output::{print_output, OutputFormat};
+use super::ui::{
+    finish_progress_error, finish_progress_success, print_error, print_header, print_hint,
+    print_info, print_progress, print_section, print_success,
+};
 use crate::client::handle_error;
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{DateTime, Utc};
 use crossterm::event::{self, Event, KeyCode, KeyModifiers};
 use crossterm::style::Stylize;
 use crossterm::terminal;
@@ -17,20 +21,56 @@ pub struct LogsResponse {
     pub data: String,
 }
 
-/// Tail logs.
+/// Parse a `--since` / `--until` value: a duration ago (`30s`, `15m`, `2h`, `1d`) or RFC 3339.
+pub fn parse_time_bound(value: &str) -> Result<DateTime<Utc>, String> {
+    let value = value.trim();
+    if let Ok(ts) = DateTime::parse_from_rfc3339(value) {
+        return Ok(ts.with_timezone(&Utc));
+    }
+    let split = value.len().saturating_sub(1);
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| {
+        format!("expected a duration like 30m/2h/1d or an RFC 3339 time, got '{value}'")
+    })?;
+    let ago = match unit {
+        "s" => chrono::Duration::seconds(amount),
+        "m" => chrono::Duration::minutes(amount),
+        "h" => chrono::Duration::hours(amount),
+        "d" => chrono::Duration::days(amount),
+        _ => {
+            return Err(format!(
+                "unknown duration unit in '{value}' (use s, m, h or d)"
+            ))
+        }
+    };
+    Ok(Utc::now() - ago)
+}
+
+/// Tail logs, optionally limited to a `since..until` time range.
+#[allow(clippy::too_many_arguments)]
 pub async fn logs_service(
     client: &reqwest::Client,
     base: &str,
     id: &str,
     tail: usize,
     follow: bool,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
     output: OutputFormat,
 ) -> anyhow::Result<()> {
-    let url = format!(
-        "{}/services/{}/logs?tail={}&follow={}",
-        base, id, tail, follow
-    );
-    let resp = client.get(url).send().await?;
+    let mut query = vec![("tail", tail.to_string()), ("follow", follow.to_string())];
+    if follow {
+        // 输出直接写到终端，落后时宁可延迟也不要插入丢弃提示打乱画面
+        query.push(("on_lag", "resync".to_string()));
+    }
+    if let Some(since) = since {
+        query.push(("since", since.to_rfc3339()));
+    }
+    if let Some(until) = until {
+        query.push(("until", until.to_rfc3339()));
+    }
+    let url = format!("{}/services/{}/logs", base, id);
+    let resp = client.get(url).query(&query).send().await?;
 
     if follow {
         print_header(&format!("📜 FOLLOW LOGS: {}", id.to_uppercase()));
@@ -107,6 +147,11 @@ pub async fn logs_service(
     let lines: Vec<&str> = content.lines().collect();
 
     match output {
+        OutputFormat::Ndjson => {
+            for line in &lines {
+                print_output(serde_json::json!({ "id": logs.id, "line": line }), output)?;
+            }
+        }
         OutputFormat::Json => {
             // JSON 输出保持 lines 格式以兼容
             println!(
@@ -126,10 +171,21 @@ pub async fn logs_service(
                 println!("  {}", "No logs available.".dark_grey().italic());
                 println!();
             } else {
-                print_info(&format!(
-                    "Showing last {} lines",
-                    lines.len().to_string().cyan()
-                ));
+                let count = lines.len().to_string().cyan();
+                if since.is_some() || until.is_some() {
+                    let bound = |ts: Option<DateTime<Utc>>| {
+                        ts.map(|ts| ts.with_timezone(&chrono::Local).format("%F %T").to_string())
+                            .unwrap_or_else(|| "…".to_string())
+                    };
+                    print_info(&format!(
+                        "Showing {} lines from {} to {}",
+                        count,
+                        bound(since),
+                        bound(until)
+                    ));
+                } else {
+                    print_info(&format!("Showing last {} lines", count));
+                }
                 println!();
                 println!("  {}", "─".repeat(60).dark_grey());
 
@@ -146,3 +202,128 @@ pub async fn logs_service(
     }
     Ok(())
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LogMatch {
+    pub line_number: u64,
+    pub offset: u64,
+    pub line: String,
+    #[serde(default)]
+    pub before: Vec<String>,
+    #[serde(default)]
+    pub after: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LogSearchResponse {
+    pub id: String,
+    pub matches: Vec<LogMatch>,
+}
+
+/// Search logs with a regex.
+pub async fn search_logs(
+    client: &reqwest::Client,
+    base: &str,
+    id: &str,
+    pattern: &str,
+    context: usize,
+    limit: usize,
+    output: OutputFormat,
+) -> anyhow::Result<()> {
+    let url = format!("{}/services/{}/logs/search", base, id);
+    let resp = client
+        .get(url)
+        .query(&[
+            ("q", pattern.to_string()),
+            ("context", context.to_string()),
+            ("limit", limit.to_string()),
+        ])
+        .send()
+        .await?;
+    let result: LogSearchResponse = handle_error(resp).await?.json().await?;
+
+    match output {
+        OutputFormat::Json | OutputFormat::Ndjson => print_output(&result, output)?,
+        OutputFormat::Table => {
+            print_header(&format!("🔍 LOG SEARCH: {}", id.to_uppercase()));
+
+            if result.matches.is_empty() {
+                println!();
+                println!("  {}", "No matching lines.".dark_grey().italic());
+                println!();
+                return Ok(());
+            }
+
+            print_info(&format!(
+                "{} matches for {}",
+                result.matches.len().to_string().cyan(),
+                pattern.yellow()
+            ));
+            println!();
+            println!("  {}", "─".repeat(60).dark_grey());
+
+            for (index, m) in result.matches.iter().enumerate() {
+                if index > 0 && context > 0 {
+                    println!("  {}", "--".dark_grey());
+                }
+                let first = m.line_number.saturating_sub(m.before.len() as u64);
+                for (i, line) in m.before.iter().enumerate() {
+                    let number = format!("{:>6}-", first + i as u64);
+                    println!("  {} {}", number.dark_grey(), line.clone().dark_grey());
+                }
+                let number = format!("{:>6}:", m.line_number);
+                println!("  {} {}", number.green(), m.line);
+                for (i, line) in m.after.iter().enumerate() {
+                    let number = format!("{:>6}-", m.line_number + 1 + i as u64);
+                    println!("  {} {}", number.dark_grey(), line.clone().dark_grey());
+                }
+            }
+
+            println!("  {}", "─".repeat(60).dark_grey());
+            println!();
+        }
+    }
+    Ok(())
+}
+
+/// Truncate the service log without restarting it.
+pub async fn clear_logs(client: &reqwest::Client, base: &str, id: &str) -> anyhow::Result<()> {
+    print_header(&format!("🧹 CLEAR LOGS: {}", id.to_uppercase()));
+
+    print_progress("Clearing logs");
+    let url = format!("{}/services/{}/logs", base, id);
+    let resp = client.delete(url).send().await?;
+
+    match handle_error(resp).await {
+        Ok(_) => {
+            finish_progress_success("Logs cleared");
+            println!();
+            print_success(&format!("Logs of '{}' have been cleared.", id));
+            println!();
+        }
+        Err(e) => {
+            finish_progress_error("Clear failed");
+            print_error(&format!("{}", e));
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_relative_and_absolute_time_bounds() {
+        let ts = parse_time_bound("2026-01-01T10:00:00+08:00").unwrap();
+        assert_eq!(ts.to_rfc3339(), "2026-01-01T02:00:00+00:00");
+
+        let ago = Utc::now() - parse_time_bound("2h").unwrap();
+        assert!((ago.num_seconds() - 7200).abs() <= 5);
+        assert!(parse_time_bound("1d").unwrap() < parse_time_bound("30m").unwrap());
+
+        assert!(parse_time_bound("5w").is_err());
+        assert!(parse_time_bound("yesterday").is_err());
+    }
+}
@@ -1,23 +1,36 @@
 mod attach;
+mod config;
 mod logs;
 mod output;
+mod profile;
+mod secrets;
 mod services;
 mod shell;
+mod stats;
 pub mod ui;
 mod users;
+mod watch;
 
 pub use attach::attach_service;
-pub use logs::logs_service;
+pub use config::{export_config, import_config};
+pub use logs::{clear_logs, logs_service, parse_time_bound, search_logs};
 pub use output::OutputFormat;
+pub use profile::{add_profile, list_profiles, use_profile};
+pub use secrets::{delete_secret, list_secrets, set_secret};
 pub use services::schedule::{
-    get_schedule, remove_schedule, set_schedule, toggle_schedule, ScheduleAction,
+    get_schedule, list_schedules, remove_schedule, run_schedule, schedule_trigger, set_schedule,
+    set_scheduler_paused, toggle_schedule, ScheduleAction,
 };
 pub use services::{
-    create_service, create_service_interactive, delete_service, get_service, list_services,
-    restart_service, start_service, status_service, stop_service, update_service,
+    apply_service, batch_services, bulk_tag, clone_service, create_service, create_service_interactive, delete_service, get_service, group_action,
+    list_archived, list_services, restore_archived, restart_service, signal_service, start_service, status_service, stop_service,
+    update_service, validate_service, ManifestFormat,
 };
 pub use shell::shell_loop;
+pub use stats::system_stats;
 pub use users::{
-    add_user_service, create_user, delete_user, get_user, list_users, login, refresh_token,
-    remove_user_service, set_user_services, update_user_password,
+    add_user_service, create_user, delete_user, get_user, list_user_sessions, list_users, login,
+    prompt_new_password, refresh_token, remove_user_service, request_refresh, revoke_user_session,
+    revoke_user_tokens, set_user_services, setup_two_factor, update_user_password, AuthToken,
 };
+pub use watch::watch_services;
@@ -1,16 +1,36 @@
 use clap::ValueEnum;
+use serde::Serialize;
 
 #[derive(ValueEnum, Clone, Copy, Debug)]
 pub enum OutputFormat {
     Table,
     Json,
+    /// One compact JSON value per line; lists emit one line per item.
+    Ndjson,
 }
 
-#[allow(dead_code)]
-pub fn print_output<T: serde::Serialize>(value: T, output: OutputFormat) -> anyhow::Result<()> {
+/// Print a single value: pretty JSON, or one compact line for ndjson.
+pub fn print_output<T: Serialize>(value: T, output: OutputFormat) -> anyhow::Result<()> {
     match output {
-        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&value)?),
-        OutputFormat::Table => println!("{}", serde_json::to_string_pretty(&value)?),
+        OutputFormat::Ndjson => println!("{}", serde_json::to_string(&value)?),
+        OutputFormat::Json | OutputFormat::Table => {
+            println!("{}", serde_json::to_string_pretty(&value)?)
+        }
+    }
+    Ok(())
+}
+
+/// Print a list: a pretty JSON array, or one compact line per item for ndjson.
+pub fn print_output_list<T: Serialize>(items: &[T], output: OutputFormat) -> anyhow::Result<()> {
+    match output {
+        OutputFormat::Ndjson => {
+            for item in items {
+                println!("{}", serde_json::to_string(item)?);
+            }
+        }
+        OutputFormat::Json | OutputFormat::Table => {
+            println!("{}", serde_json::to_string_pretty(items)?)
+        }
     }
     Ok(())
 }
@@ -18,9 +38,14 @@ pub fn print_output<T: serde::Serialize>(value: T, output: OutputFormat) -> anyh
 #[cfg(test)]
 mod tests {
     use super::OutputFormat;
+    use clap::ValueEnum;
 
     #[test]
     fn output_format_variants() {
         assert!(matches!(OutputFormat::Json, OutputFormat::Json));
+        assert!(matches!(
+            OutputFormat::from_str("ndjson", true),
+            Ok(OutputFormat::Ndjson)
+        ));
     }
 }
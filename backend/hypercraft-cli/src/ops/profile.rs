@@ -0,0 +1,96 @@
+//! 服务端 profile 管理（本地配置文件，不访问 API）
+
+use super::output::{print_output_list, OutputFormat};
+use super::ui::{print_empty, print_header, print_hint, print_success};
+use crate::profile::{CliConfig, Profile};
+use crossterm::style::Stylize;
+use serde_json::json;
+use std::path::Path;
+
+/// 新增或覆盖 profile；`make_current` 时同时设为默认
+pub fn add_profile(
+    path: &Path,
+    name: &str,
+    api_base: &str,
+    token: Option<String>,
+    make_current: bool,
+) -> anyhow::Result<()> {
+    let mut config = CliConfig::load(path)?;
+    config.profile.insert(
+        name.to_string(),
+        Profile {
+            api_base: api_base.trim_end_matches('/').to_string(),
+            token,
+        },
+    );
+    if make_current {
+        config.current = Some(name.to_string());
+    }
+    config.save(path)?;
+    print_success(&format!("已保存 profile {} 到 {}", name, path.display()));
+    if !make_current && config.current.as_deref() != Some(name) {
+        print_hint(&format!(
+            "使用 hc --profile {name} ... 或 hc profile use {name} 启用"
+        ));
+    }
+    Ok(())
+}
+
+/// 列出 profile（token 不回显）
+pub fn list_profiles(path: &Path, output: OutputFormat) -> anyhow::Result<()> {
+    let config = CliConfig::load(path)?;
+    let current = config.current.as_deref();
+
+    match output {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let items: Vec<_> = config
+                .profile
+                .iter()
+                .map(|(name, profile)| {
+                    json!({
+                        "name": name,
+                        "api_base": profile.api_base,
+                        "has_token": profile.token.is_some(),
+                        "current": current == Some(name.as_str()),
+                    })
+                })
+                .collect();
+            print_output_list(&items, output)?;
+        }
+        OutputFormat::Table => {
+            print_header("🔧 Profiles");
+            if config.profile.is_empty() {
+                print_empty("暂无 profile，使用 hc profile add 添加");
+                return Ok(());
+            }
+            println!();
+            for (name, profile) in &config.profile {
+                let marker = if current == Some(name.as_str()) {
+                    "*".green().to_string()
+                } else {
+                    " ".to_string()
+                };
+                let token = if profile.token.is_some() {
+                    "token ✓".dark_grey()
+                } else {
+                    "no token".dark_grey()
+                };
+                println!("  {} {:<16} {}  {}", marker, name, profile.api_base, token);
+            }
+            println!();
+        }
+    }
+    Ok(())
+}
+
+/// 设置默认 profile
+pub fn use_profile(path: &Path, name: &str) -> anyhow::Result<()> {
+    let mut config = CliConfig::load(path)?;
+    if !config.profile.contains_key(name) {
+        anyhow::bail!("profile 不存在: {name}");
+    }
+    config.current = Some(name.to_string());
+    config.save(path)?;
+    print_success(&format!("默认 profile 已切换为 {name}"));
+    Ok(())
+}
@@ -0,0 +1,92 @@
+//! 服务密钥管理 CLI 操作（仅超级管理员）
+
+use super::output::{print_output_list, OutputFormat};
+use super::ui::{print_error, print_header, print_success};
+use crossterm::style::Stylize;
+use reqwest::Client;
+use serde_json::json;
+
+/// 列出密钥名
+pub async fn list_secrets(client: &Client, base: &str, output: OutputFormat) -> anyhow::Result<()> {
+    let url = format!("{}/secrets", base);
+    let resp = client.get(&url).send().await?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        print_error(&format!("获取密钥列表失败 ({}): {}", status, body));
+        return Ok(());
+    }
+
+    let keys: Vec<String> = resp.json().await?;
+
+    match output {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            print_output_list(&keys, output)?;
+        }
+        OutputFormat::Table => {
+            print_header("🔑 密钥列表");
+            println!();
+            if keys.is_empty() {
+                println!("  {}", "暂无密钥".dark_grey());
+            } else {
+                for key in keys {
+                    println!("  {}  {}", key, format!("${{SECRET:{}}}", key).dark_grey());
+                }
+            }
+            println!();
+        }
+    }
+
+    Ok(())
+}
+
+/// 写入密钥；未提供 value 时从标准输入读取一行，避免明文留在 shell 历史
+pub async fn set_secret(
+    client: &Client,
+    base: &str,
+    key: &str,
+    value: Option<String>,
+) -> anyhow::Result<()> {
+    let value = match value {
+        Some(value) => value,
+        None => {
+            let mut line = String::new();
+            std::io::stdin().read_line(&mut line)?;
+            line.trim_end_matches(['\r', '\n']).to_string()
+        }
+    };
+
+    let url = format!("{}/secrets/{}", base, key);
+    let resp = client
+        .put(&url)
+        .json(&json!({ "value": value }))
+        .send()
+        .await?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        print_error(&format!("写入密钥失败 ({}): {}", status, body));
+        return Ok(());
+    }
+
+    print_success(&format!("密钥 {} 已保存", key));
+    Ok(())
+}
+
+/// 删除密钥
+pub async fn delete_secret(client: &Client, base: &str, key: &str) -> anyhow::Result<()> {
+    let url = format!("{}/secrets/{}", base, key);
+    let resp = client.delete(&url).send().await?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        print_error(&format!("删除密钥失败 ({}): {}", status, body));
+        return Ok(());
+    }
+
+    print_success(&format!("密钥 {} 已删除", key));
+    Ok(())
+}
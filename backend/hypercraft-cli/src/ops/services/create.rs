@@ -1,12 +1,15 @@
 //! Service creation operations (file-based and interactive).
 
 use super::create_service_from_manifest;
-use crate::ops::output::OutputFormat;
+use super::manifest::{read_manifest, ManifestFormat};
+use crate::client::{api, handle_error};
+use crate::ops::output::{print_output, OutputFormat};
+use crate::ops::ui::{print_error, print_success, print_table_header, print_table_row};
 use crossterm::style::Stylize;
 use dialoguer::{theme::ColorfulTheme, Confirm, Editor, Input, Select};
-use hypercraft_core::ServiceManifest;
+use hypercraft_client::Applied;
+use hypercraft_core::{ManifestValidation, ServiceManifest, MANIFEST_SCHEMA_VERSION};
 use std::collections::BTreeMap;
-use std::fs;
 use std::path::PathBuf;
 
 /// Create service from manifest file.
@@ -14,13 +17,83 @@ pub async fn create_service(
     client: &reqwest::Client,
     base: &str,
     file: PathBuf,
+    format: Option<ManifestFormat>,
     output: OutputFormat,
 ) -> anyhow::Result<()> {
-    let data = fs::read_to_string(file)?;
-    let manifest: ServiceManifest = serde_json::from_str(&data)?;
+    let manifest = read_manifest(&file, format)?;
     create_service_from_manifest(client, base, manifest, output).await
 }
 
+/// Create or update a service from a manifest file (`PUT /services/:id` upsert).
+pub async fn apply_service(
+    client: &reqwest::Client,
+    base: &str,
+    file: PathBuf,
+    format: Option<ManifestFormat>,
+    output: OutputFormat,
+) -> anyhow::Result<()> {
+    let manifest = read_manifest(&file, format)?;
+    if manifest.id.is_empty() {
+        anyhow::bail!("manifest has no id");
+    }
+
+    let Applied {
+        manifest: applied,
+        created,
+    } = api(client, base).apply_service(&manifest).await?;
+
+    match output {
+        OutputFormat::Json | OutputFormat::Ndjson => print_output(&applied, output)?,
+        OutputFormat::Table => print_success(&format!(
+            "Service '{}' {}.",
+            applied.id,
+            if created { "created" } else { "updated" }
+        )),
+    }
+    Ok(())
+}
+
+/// Dry-run validate a manifest file against the server; returns an error when invalid
+/// so CI can fail on the exit code.
+pub async fn validate_service(
+    client: &reqwest::Client,
+    base: &str,
+    file: PathBuf,
+    format: Option<ManifestFormat>,
+    output: OutputFormat,
+) -> anyhow::Result<()> {
+    let manifest = read_manifest(&file, format)?;
+
+    let url = format!("{}/services/validate", base);
+    let resp = client.post(url).json(&manifest).send().await?;
+    let resp = handle_error(resp).await?;
+    let report: ManifestValidation = resp.json().await?;
+
+    match output {
+        OutputFormat::Json | OutputFormat::Ndjson => print_output(&report, output)?,
+        OutputFormat::Table if report.valid => {
+            print_success(&format!("{} is valid", file.display()))
+        }
+        OutputFormat::Table => {
+            print_error(&format!(
+                "{} has {} problem(s)",
+                file.display(),
+                report.problems.len()
+            ));
+            print_table_header(&[("FIELD", 16), ("PROBLEM", 60)]);
+            for problem in &report.problems {
+                print_table_row(&[(problem.field.as_str(), 16), (problem.message.as_str(), 60)]);
+            }
+            println!();
+        }
+    }
+
+    if !report.valid {
+        anyhow::bail!("manifest validation failed");
+    }
+    Ok(())
+}
+
 /// Interactive manifest creation helper with beautiful UI.
 pub async fn create_service_interactive(
     client: &reqwest::Client,
@@ -70,26 +143,45 @@ pub async fn create_service_interactive(
 
     // Create Service
     let manifest = ServiceManifest {
+        schema_version: MANIFEST_SCHEMA_VERSION,
         id: id.clone(),
         name,
         command,
         args,
         env,
+        env_file: None,
         cwd,
         auto_start,
         auto_restart,
+        restart_backoff_ms: None,
+        max_restarts: None,
+        restart_window_secs: None,
         shutdown_command: None,
+        shutdown_timeout_secs: None,
+        kill_signal: None,
+        kill_timeout_secs: None,
         run_as,
+        memory_limit_mb: None,
+        cpu_quota_percent: None,
         created_at: None,
         tags: vec![],
         group: None,
         order: 0,
+        depends_on: vec![],
         log_path,
+        log_keep_files: None,
+        log_encoding: None,
+        start_grace_ms: 300,
         pty_rows: 300,
         terminal_tui: false,
+        tty: true,
+        split_stderr: false,
         clear_log_on_start,
-        schedule: None,
+        schedules: vec![],
         web: None,
+        health_check: None,
+        webhooks: vec![],
+        detached: false,
     };
 
     println!();
@@ -340,6 +432,7 @@ fn prompt_environment(theme: &ColorfulTheme) -> anyhow::Result<BTreeMap<String,
     Ok(env)
 }
 
+#[allow(clippy::type_complexity)]
 fn prompt_advanced_options(theme: &ColorfulTheme) -> anyhow::Result<(bool, bool, Option<String>, Option<String>, bool)> {
     print_step(5, "Advanced Options");
 
@@ -410,6 +503,7 @@ fn prompt_advanced_options(theme: &ColorfulTheme) -> anyhow::Result<(bool, bool,
     Ok((auto_restart, auto_start, run_as, log_path, clear_log_on_start))
 }
 
+#[allow(clippy::too_many_arguments)]
 fn preview_and_confirm(
     theme: &ColorfulTheme,
     id: &str,
@@ -0,0 +1,124 @@
+//! Field-by-field manifest diff shown before `update` applies a change.
+
+use crossterm::style::Stylize;
+use serde_json::{Map, Value};
+
+/// One changed field, addressed by a dotted path such as `env.JAVA_OPTS`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldChange {
+    Added(String, Value),
+    Removed(String, Value),
+    Changed(String, Value, Value),
+}
+
+/// Compare two manifests. Objects are walked key by key; arrays and scalars compare whole.
+/// `null` counts as absent, so an unset optional field never shows up as a change.
+pub fn manifest_diff(old: &Value, new: &Value) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    diff_value("", old, new, &mut changes);
+    changes
+}
+
+fn diff_value(path: &str, old: &Value, new: &Value, changes: &mut Vec<FieldChange>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => diff_object(path, old, new, changes),
+        _ if old == new => {}
+        (Value::Null, _) => changes.push(FieldChange::Added(path.to_string(), new.clone())),
+        (_, Value::Null) => changes.push(FieldChange::Removed(path.to_string(), old.clone())),
+        _ => changes.push(FieldChange::Changed(
+            path.to_string(),
+            old.clone(),
+            new.clone(),
+        )),
+    }
+}
+
+fn diff_object(
+    path: &str,
+    old: &Map<String, Value>,
+    new: &Map<String, Value>,
+    changes: &mut Vec<FieldChange>,
+) {
+    let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        let child = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{path}.{key}")
+        };
+        let old = old.get(key).unwrap_or(&Value::Null);
+        let new = new.get(key).unwrap_or(&Value::Null);
+        diff_value(&child, old, new, changes);
+    }
+}
+
+/// Print changes as `+ added`, `- removed`, `~ changed` lines.
+pub fn print_diff(changes: &[FieldChange]) {
+    for change in changes {
+        match change {
+            FieldChange::Added(path, value) => {
+                println!("  {}", format!("+ {path}: {}", compact(value)).green());
+            }
+            FieldChange::Removed(path, value) => {
+                println!("  {}", format!("- {path}: {}", compact(value)).red());
+            }
+            FieldChange::Changed(path, old, new) => {
+                println!(
+                    "  {} {}: {} {} {}",
+                    "~".yellow(),
+                    path.as_str().yellow(),
+                    compact(old).red(),
+                    "→".dark_grey(),
+                    compact(new).green()
+                );
+            }
+        }
+    }
+}
+
+fn compact(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reports_added_removed_and_changed_fields() {
+        let old = json!({
+            "id": "mc",
+            "args": ["-jar", "a.jar"],
+            "env": { "A": "1", "B": "2" },
+            "cwd": "/srv",
+            "log_path": null,
+        });
+        let new = json!({
+            "id": "mc",
+            "args": ["-jar", "b.jar"],
+            "env": { "A": "1", "C": "3" },
+            "cwd": null,
+            "log_path": null,
+        });
+        assert_eq!(
+            manifest_diff(&old, &new),
+            vec![
+                FieldChange::Changed(
+                    "args".into(),
+                    json!(["-jar", "a.jar"]),
+                    json!(["-jar", "b.jar"])
+                ),
+                FieldChange::Removed("cwd".into(), json!("/srv")),
+                FieldChange::Removed("env.B".into(), json!("2")),
+                FieldChange::Added("env.C".into(), json!("3")),
+            ]
+        );
+        assert!(manifest_diff(&old, &old).is_empty());
+    }
+}
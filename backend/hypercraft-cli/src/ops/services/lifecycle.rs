@@ -1,14 +1,16 @@
 //! Service lifecycle operations: start, stop, restart, status.
 
-use crate::client::handle_error;
-use crate::ops::output::OutputFormat;
+use crate::client::{api, handle_error};
+use crate::ops::output::{print_output, print_output_list, OutputFormat};
 use crate::ops::ui::{
     finish_progress_error, finish_progress_success, format_state, format_uptime, print_error,
-    print_header, print_hint, print_kv_colored, print_progress, print_section, print_success,
+    print_header, print_hint, print_kv, print_kv_colored, print_progress, print_section, print_success,
     KvColor,
 };
+use anyhow::anyhow;
 use crossterm::style::Stylize;
-use hypercraft_core::ServiceStatus;
+use hypercraft_core::{LifecycleStats, ServiceStatus};
+use serde_json::{json, Value};
 
 /// Start service.
 pub async fn start_service(
@@ -18,23 +20,16 @@ pub async fn start_service(
     output: OutputFormat,
 ) -> anyhow::Result<()> {
     match output {
-        OutputFormat::Json => {
-            let url = format!("{}/services/{}/start", base, id);
-            let resp = client.post(url).send().await?;
-            let resp = handle_error(resp).await?;
-            let status: ServiceStatus = resp.json().await?;
-            println!("{}", serde_json::to_string_pretty(&status)?);
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let status = api(client, base).start(id).await?;
+            print_output(&status, output)?;
         }
         OutputFormat::Table => {
             print_header(&format!("▶️  START SERVICE: {}", id.to_uppercase()));
 
             print_progress("Starting service");
-            let url = format!("{}/services/{}/start", base, id);
-            let resp = client.post(url).send().await?;
-
-            match handle_error(resp).await {
-                Ok(resp) => {
-                    let status: ServiceStatus = resp.json().await?;
+            match api(client, base).start(id).await {
+                Ok(status) => {
                     finish_progress_success("Service started");
                     println!();
 
@@ -46,7 +41,7 @@ pub async fn start_service(
                     finish_progress_error("Failed to start");
                     println!();
                     print_error(&format!("{}", e));
-                    return Err(e);
+                    return Err(e.into());
                 }
             }
         }
@@ -62,23 +57,16 @@ pub async fn stop_service(
     output: OutputFormat,
 ) -> anyhow::Result<()> {
     match output {
-        OutputFormat::Json => {
-            let url = format!("{}/services/{}/stop", base, id);
-            let resp = client.post(url).send().await?;
-            let resp = handle_error(resp).await?;
-            let status: ServiceStatus = resp.json().await?;
-            println!("{}", serde_json::to_string_pretty(&status)?);
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let status = api(client, base).stop(id).await?;
+            print_output(&status, output)?;
         }
         OutputFormat::Table => {
             print_header(&format!("⏹️  STOP SERVICE: {}", id.to_uppercase()));
 
             print_progress("Stopping service");
-            let url = format!("{}/services/{}/stop", base, id);
-            let resp = client.post(url).send().await?;
-
-            match handle_error(resp).await {
-                Ok(resp) => {
-                    let status: ServiceStatus = resp.json().await?;
+            match api(client, base).stop(id).await {
+                Ok(status) => {
                     finish_progress_success("Service stopped");
                     println!();
 
@@ -91,7 +79,7 @@ pub async fn stop_service(
                     finish_progress_error("Failed to stop");
                     println!();
                     print_error(&format!("{}", e));
-                    return Err(e);
+                    return Err(e.into());
                 }
             }
         }
@@ -107,23 +95,16 @@ pub async fn restart_service(
     output: OutputFormat,
 ) -> anyhow::Result<()> {
     match output {
-        OutputFormat::Json => {
-            let url = format!("{}/services/{}/restart", base, id);
-            let resp = client.post(url).send().await?;
-            let resp = handle_error(resp).await?;
-            let status: ServiceStatus = resp.json().await?;
-            println!("{}", serde_json::to_string_pretty(&status)?);
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let status = api(client, base).restart(id).await?;
+            print_output(&status, output)?;
         }
         OutputFormat::Table => {
             print_header(&format!("🔄 RESTART SERVICE: {}", id.to_uppercase()));
 
             print_progress("Restarting service");
-            let url = format!("{}/services/{}/restart", base, id);
-            let resp = client.post(url).send().await?;
-
-            match handle_error(resp).await {
-                Ok(resp) => {
-                    let status: ServiceStatus = resp.json().await?;
+            match api(client, base).restart(id).await {
+                Ok(status) => {
                     finish_progress_success("Service restarted");
                     println!();
 
@@ -136,7 +117,7 @@ pub async fn restart_service(
                     finish_progress_error("Failed to restart");
                     println!();
                     print_error(&format!("{}", e));
-                    return Err(e);
+                    return Err(e.into());
                 }
             }
         }
@@ -144,6 +125,36 @@ pub async fn restart_service(
     Ok(())
 }
 
+/// Send a signal (INT, TERM, HUP, USR1) to the service process.
+pub async fn signal_service(
+    client: &reqwest::Client,
+    base: &str,
+    id: &str,
+    signal: &str,
+    output: OutputFormat,
+) -> anyhow::Result<()> {
+    let url = format!("{}/services/{}/signal", base, id);
+    let resp = client
+        .post(url)
+        .json(&json!({ "signal": signal }))
+        .send()
+        .await?;
+    match output {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            handle_error(resp).await?;
+            println!("{}", json!({ "id": id, "signal": signal }));
+        }
+        OutputFormat::Table => match handle_error(resp).await {
+            Ok(_) => print_success(&format!("Sent {} to '{}'.", signal.to_uppercase(), id)),
+            Err(e) => {
+                print_error(&format!("{}", e));
+                return Err(e);
+            }
+        },
+    }
+    Ok(())
+}
+
 /// Query status.
 pub async fn status_service(
     client: &reqwest::Client,
@@ -151,16 +162,23 @@ pub async fn status_service(
     id: &str,
     output: OutputFormat,
 ) -> anyhow::Result<()> {
-    let url = format!("{}/services/{}/status", base, id);
-    let resp = client.get(url).send().await?;
-    let resp = handle_error(resp).await?;
-    let status: ServiceStatus = resp.json().await?;
+    let status = api(client, base).status(id).await?;
+    let stats = fetch_lifecycle_stats(client, base, id).await;
 
     match output {
-        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&status)?),
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let mut value = serde_json::to_value(&status)?;
+            if let (Some(obj), Some(stats)) = (value.as_object_mut(), &stats) {
+                obj.insert("lifecycle".to_string(), serde_json::to_value(stats)?);
+            }
+            print_output(&value, output)?;
+        }
         OutputFormat::Table => {
             print_header(&format!("📊 STATUS: {}", id.to_uppercase()));
             print_service_status(&status);
+            if let Some(stats) = &stats {
+                print_lifecycle_stats(stats);
+            }
             println!();
 
             // Show helpful hints based on state
@@ -177,7 +195,117 @@ pub async fn status_service(
     Ok(())
 }
 
-fn print_service_status(status: &ServiceStatus) {
+/// Lifecycle counters for a service; `None` when the server does not report them.
+async fn fetch_lifecycle_stats(
+    client: &reqwest::Client,
+    base: &str,
+    id: &str,
+) -> Option<LifecycleStats> {
+    let url = format!("{}/services/{}/stats", base, id);
+    let resp = client.get(url).send().await.ok()?;
+    let resp = handle_error(resp).await.ok()?;
+    resp.json().await.ok()
+}
+
+/// Start/stop/restart several services at once.
+pub async fn batch_services(
+    client: &reqwest::Client,
+    base: &str,
+    action: &str,
+    ids: &[String],
+    all: bool,
+    output: OutputFormat,
+) -> anyhow::Result<()> {
+    if !matches!(action, "start" | "stop" | "restart") {
+        return Err(anyhow!("invalid action '{}': expected start, stop or restart", action));
+    }
+    if !all && ids.is_empty() {
+        return Err(anyhow!("specify service ids (id1,id2) or --all"));
+    }
+
+    let url = format!("{}/services/batch", base);
+    let body = json!({ "action": action, "ids": ids, "all": all });
+    let resp = client.post(url).json(&body).send().await?;
+    let resp = handle_error(resp).await?;
+    let result: Value = resp.json().await?;
+    print_action_results(
+        &format!("📦 BATCH {}", action.to_uppercase()),
+        &result,
+        output,
+    )
+}
+
+/// Start/stop/restart every service in a group, ordered by the services' `order`.
+pub async fn group_action(
+    client: &reqwest::Client,
+    base: &str,
+    id: &str,
+    action: &str,
+    output: OutputFormat,
+) -> anyhow::Result<()> {
+    if !matches!(action, "start" | "stop" | "restart") {
+        return Err(anyhow!("invalid action '{}': expected start, stop or restart", action));
+    }
+
+    let url = format!("{}/groups/{}/{}", base, id, action);
+    let resp = client.post(url).send().await?;
+    let resp = handle_error(resp).await?;
+    let result: Value = resp.json().await?;
+    print_action_results(
+        &format!("📁 GROUP {} {}", id, action.to_uppercase()),
+        &result,
+        output,
+    )
+}
+
+/// Print the per-service `results` of a batch or group action; fails if any item failed.
+fn print_action_results(title: &str, result: &Value, output: OutputFormat) -> anyhow::Result<()> {
+    let items = result
+        .get("results")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let failed = items
+        .iter()
+        .filter(|r| !r.get("ok").and_then(|v| v.as_bool()).unwrap_or(false))
+        .count();
+
+    match output {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(result)?),
+        OutputFormat::Ndjson => print_output_list(&items, output)?,
+        OutputFormat::Table => {
+            print_header(title);
+            for item in &items {
+                let id = item.get("id").and_then(|v| v.as_str()).unwrap_or("?");
+                if item.get("ok").and_then(|v| v.as_bool()).unwrap_or(false) {
+                    let state = item
+                        .pointer("/status/state")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("unknown");
+                    print_success(&format!("{:<24} {}", id, format_state(state)));
+                } else {
+                    let error = item.get("error").and_then(|v| v.as_str()).unwrap_or("failed");
+                    print_error(&format!("{:<24} {}", id, error));
+                }
+            }
+            println!();
+            println!(
+                "  Total: {}  |  {} OK  |  {} Failed",
+                items.len().to_string().white().bold(),
+                (items.len() - failed).to_string().green(),
+                failed.to_string().red()
+            );
+            println!();
+        }
+    }
+
+    if failed > 0 {
+        return Err(anyhow!("{} of {} operation(s) failed", failed, items.len()));
+    }
+    Ok(())
+}
+
+pub(super) fn print_service_status(status: &ServiceStatus) {
     print_section("Service Status");
 
     let state_str = format!("{:?}", status.state);
@@ -194,4 +322,47 @@ fn print_service_status(status: &ServiceStatus) {
     if let Some(uptime_ms) = status.uptime_ms {
         print_kv_colored("Uptime", &format_uptime(uptime_ms), KvColor::Green);
     }
+
+    if let Some(healthy) = status.healthy {
+        if healthy {
+            print_kv_colored("Health", "healthy", KvColor::Green);
+        } else {
+            print_kv_colored("Health", "unhealthy", KvColor::Red);
+        }
+    }
+
+    if let Some(code) = status.last_exit_code {
+        let exit = match &status.last_exit_signal {
+            Some(signal) => format!("{} ({})", code, signal),
+            None => code.to_string(),
+        };
+        let color = if code == 0 && status.last_exit_signal.is_none() {
+            KvColor::Green
+        } else {
+            KvColor::Red
+        };
+        print_kv_colored("Last Exit", &exit, color);
+    }
+    if let Some(at) = status.last_exit_at {
+        print_kv("Exited At", &at.to_rfc3339());
+    }
+}
+
+fn print_lifecycle_stats(stats: &LifecycleStats) {
+    print_section("Lifecycle");
+    print_kv("Starts", &stats.total_starts.to_string());
+    let crash_color = if stats.total_crashes == 0 {
+        KvColor::Green
+    } else {
+        KvColor::Red
+    };
+    print_kv_colored("Crashes", &stats.total_crashes.to_string(), crash_color);
+    print_kv("Auto Restarts", &stats.auto_restarts.to_string());
+    print_kv("Total Uptime", &format_uptime(stats.cumulative_uptime_ms));
+    if let Some(at) = stats.last_start_at {
+        print_kv("Last Start", &at.to_rfc3339());
+    }
+    if let Some(at) = stats.last_crash_at {
+        print_kv("Last Crash", &at.to_rfc3339());
+    }
 }
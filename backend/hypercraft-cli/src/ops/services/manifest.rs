@@ -0,0 +1,102 @@
+//! Manifest file loading (JSON / YAML / TOML).
+
+use clap::ValueEnum;
+use hypercraft_core::ServiceManifest;
+use std::fs;
+use std::path::Path;
+
+/// Manifest serialization format.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ManifestFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl ManifestFormat {
+    /// Detect the format from the file extension; anything unrecognized is JSON.
+    pub fn from_path(path: &Path) -> Self {
+        match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            Some("yaml" | "yml") => Self::Yaml,
+            Some("toml") => Self::Toml,
+            _ => Self::Json,
+        }
+    }
+
+    fn parse(self, data: &str) -> anyhow::Result<ServiceManifest> {
+        Ok(match self {
+            Self::Json => serde_json::from_str(data)?,
+            #[cfg(feature = "yaml")]
+            Self::Yaml => serde_yaml::from_str(data)?,
+            #[cfg(not(feature = "yaml"))]
+            Self::Yaml => {
+                anyhow::bail!("YAML support is not compiled in (enable the `yaml` feature)")
+            }
+            Self::Toml => toml::from_str(data)?,
+        })
+    }
+}
+
+/// Read a manifest from `path` (`-` reads stdin). `format` overrides extension detection.
+pub fn read_manifest(
+    path: &Path,
+    format: Option<ManifestFormat>,
+) -> anyhow::Result<ServiceManifest> {
+    let data = if path == Path::new("-") {
+        std::io::read_to_string(std::io::stdin())?
+    } else {
+        fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("{}: failed to read file: {}", path.display(), e))?
+    };
+    let format = format.unwrap_or_else(|| ManifestFormat::from_path(path));
+    format
+        .parse(&data)
+        .map_err(|e| anyhow::anyhow!("{}: invalid {:?} manifest: {}", path.display(), format, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_format_from_extension() {
+        let cases = [
+            ("a.yml", ManifestFormat::Yaml),
+            ("a.YAML", ManifestFormat::Yaml),
+            ("a.toml", ManifestFormat::Toml),
+            ("a.json", ManifestFormat::Json),
+            ("-", ManifestFormat::Json),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(
+                ManifestFormat::from_path(Path::new(path)),
+                expected,
+                "{path}"
+            );
+        }
+    }
+
+    #[test]
+    fn parses_same_manifest_in_every_format() {
+        let json =
+            r#"{"id":"mc","name":"Minecraft","command":"java","args":["-jar","server.jar"]}"#;
+        let toml = "id = \"mc\"\nname = \"Minecraft\"\ncommand = \"java\"\nargs = [\"-jar\", \"server.jar\"]\n";
+        let from_json = ManifestFormat::Json.parse(json).unwrap();
+        let from_toml = ManifestFormat::Toml.parse(toml).unwrap();
+        assert_eq!(from_toml.id, from_json.id);
+        assert_eq!(from_toml.args, from_json.args);
+
+        #[cfg(feature = "yaml")]
+        {
+            let yaml = "id: mc\nname: Minecraft\ncommand: java\nargs: [-jar, server.jar]\n";
+            let from_yaml = ManifestFormat::Yaml.parse(yaml).unwrap();
+            assert_eq!(from_yaml.command, from_json.command);
+            assert_eq!(from_yaml.args, from_json.args);
+        }
+    }
+}
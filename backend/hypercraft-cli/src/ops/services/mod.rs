@@ -1,39 +1,48 @@
 //! Service management operations.
 
 mod create;
+mod diff;
 mod lifecycle;
+mod manifest;
 pub mod schedule;
+mod tags;
 
-use super::output::OutputFormat;
+use super::output::{print_output, print_output_list, OutputFormat};
 use super::ui::{
-    finish_progress_error, finish_progress_success, format_state, print_empty, print_error,
-    print_header, print_hint, print_kv, print_kv_colored, print_progress, print_section,
-    print_success, print_table_header, print_warning, KvColor,
+    finish_progress_error, finish_progress_success, format_state, format_uptime, print_empty,
+    print_error, print_header, print_hint, print_info, print_kv, print_kv_colored, print_progress,
+    print_section, print_success, print_table_header, print_warning, KvColor,
 };
-use crate::client::handle_error;
+use crate::client::{api, handle_error};
 use crossterm::style::Stylize;
-use hypercraft_core::{ServiceManifest, ServiceSummary};
+use dialoguer::{theme::ColorfulTheme, Confirm};
+use hypercraft_client::{DeleteOptions, ServicePage};
+use hypercraft_core::{ServiceListQuery, ServiceManifest};
 use serde_json::Value;
-use std::fs;
+use std::io::IsTerminal;
 use std::path::PathBuf;
 
 // Re-exports
-pub use create::{create_service, create_service_interactive};
-pub use lifecycle::{restart_service, start_service, status_service, stop_service};
+pub use create::{apply_service, create_service, create_service_interactive, validate_service};
+pub use lifecycle::{
+    batch_services, group_action, restart_service, signal_service, start_service, status_service,
+    stop_service,
+};
+pub use manifest::ManifestFormat;
+pub use tags::bulk_tag;
 
 /// List services.
 pub async fn list_services(
     client: &reqwest::Client,
     base: &str,
+    query: &ServiceListQuery,
     output: OutputFormat,
 ) -> anyhow::Result<()> {
-    let url = format!("{}/services", base);
-    let resp = client.get(url).send().await?;
-    let resp = handle_error(resp).await?;
-    let services: Vec<ServiceSummary> = resp.json().await?;
+    // 分页时以服务端返回的过滤后总数为准
+    let ServicePage { services, total } = api(client, base).list_services(query).await?;
 
     match output {
-        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&services)?),
+        OutputFormat::Json | OutputFormat::Ndjson => print_output_list(&services, output)?,
         OutputFormat::Table => {
             print_header("📋 SERVICE LIST");
 
@@ -51,13 +60,20 @@ pub async fn list_services(
 
             println!(
                 "  Total: {}  |  {} Running  |  {} Stopped",
-                services.len().to_string().white().bold(),
+                total.unwrap_or(services.len()).to_string().white().bold(),
                 running.to_string().green(),
                 stopped.to_string().dark_grey()
             );
+            if total.is_some_and(|t| t > services.len()) {
+                println!(
+                    "  Showing {} from offset {}",
+                    services.len(),
+                    query.offset.unwrap_or(0)
+                );
+            }
             println!();
 
-            print_table_header(&[("ID", 24), ("NAME", 20), ("STATUS", 14)]);
+            print_table_header(&[("ID", 24), ("NAME", 20), ("UPTIME", 12), ("STATUS", 14)]);
 
             for svc in &services {
                 let state_str = format!("{:?}", svc.state);
@@ -68,10 +84,17 @@ pub async fn list_services(
                 } else {
                     svc.name.clone()
                 };
+                // 运行时长由 started_at 推算，无需逐个查询状态
+                let uptime = svc
+                    .started_at
+                    .and_then(|t| u64::try_from((chrono::Utc::now() - t).num_milliseconds()).ok())
+                    .map(format_uptime)
+                    .unwrap_or_else(|| "-".into());
                 println!(
-                    "  {:<24} {:<20} {}",
+                    "  {:<24} {:<20} {:<12} {}",
                     svc.id.as_str().cyan(),
                     name_display,
+                    uptime,
                     status_display
                 );
             }
@@ -95,7 +118,7 @@ pub async fn get_service(
     let json: Value = resp.json().await?;
 
     match output {
-        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&json)?),
+        OutputFormat::Json | OutputFormat::Ndjson => print_output(&json, output)?,
         OutputFormat::Table => {
             print_header(&format!("📦 SERVICE: {}", id.to_uppercase()));
 
@@ -163,6 +186,16 @@ pub async fn get_service(
                 if let Some(uptime) = status.get("uptime_ms").and_then(|v| v.as_u64()) {
                     print_kv_colored("Uptime", &super::ui::format_uptime(uptime), KvColor::Green);
                 }
+                if let Some(code) = status.get("last_exit_code").and_then(|v| v.as_i64()) {
+                    let exit = match status.get("last_exit_signal").and_then(|v| v.as_str()) {
+                        Some(signal) => format!("{} ({})", code, signal),
+                        None => code.to_string(),
+                    };
+                    print_kv("Last Exit", &exit);
+                }
+                if let Some(at) = status.get("last_exit_at").and_then(|v| v.as_str()) {
+                    print_kv("Exited At", at);
+                }
             }
             println!();
         }
@@ -170,36 +203,77 @@ pub async fn get_service(
     Ok(())
 }
 
-/// Update manifest by id.
+/// Update manifest by id, after showing a diff against the current one.
+///
+/// `yes` skips the confirmation prompt.
 pub async fn update_service(
     client: &reqwest::Client,
     base: &str,
     id: &str,
     file: PathBuf,
+    format: Option<ManifestFormat>,
+    yes: bool,
     output: OutputFormat,
 ) -> anyhow::Result<()> {
     print_header(&format!("🔄 UPDATE SERVICE: {}", id.to_uppercase()));
 
     print_progress("Reading manifest file");
-    let data = match fs::read_to_string(&file) {
-        Ok(d) => {
+    let manifest = match manifest::read_manifest(&file, format) {
+        Ok(m) => {
             finish_progress_success("Manifest file loaded");
-            d
+            m
         }
         Err(e) => {
-            finish_progress_error(&format!("Failed to read file: {}", e));
-            return Err(e.into());
+            finish_progress_error(&e.to_string());
+            return Err(e);
         }
     };
 
-    let manifest: ServiceManifest = match serde_json::from_str(&data) {
-        Ok(m) => m,
+    print_progress("Fetching current configuration");
+    let resp = client
+        .get(format!("{}/services/{}", base, id))
+        .send()
+        .await?;
+    let current: ServiceManifest = match handle_error(resp).await {
+        Ok(resp) => {
+            let mut detail: Value = resp.json().await?;
+            finish_progress_success("Current configuration loaded");
+            serde_json::from_value(detail["manifest"].take())?
+        }
         Err(e) => {
-            print_error(&format!("Invalid JSON: {}", e));
-            return Err(e.into());
+            finish_progress_error("Fetch failed");
+            return Err(e);
         }
     };
 
+    // 两边都经 ServiceManifest 序列化，缺省字段不会误报
+    let changes = diff::manifest_diff(
+        &serde_json::to_value(&current)?,
+        &serde_json::to_value(&manifest)?,
+    );
+    print_section("Changes");
+    if changes.is_empty() {
+        print_info("No changes; the manifest matches the current configuration.");
+        println!();
+        return Ok(());
+    }
+    diff::print_diff(&changes);
+    println!();
+
+    if !yes {
+        if !std::io::stdin().is_terminal() {
+            anyhow::bail!("refusing to update without confirmation; pass --yes");
+        }
+        let confirmed = Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Apply these changes?")
+            .default(false)
+            .interact()?;
+        if !confirmed {
+            println!("  {} Update cancelled.", "✗".red());
+            return Ok(());
+        }
+    }
+
     print_progress("Updating service configuration");
     let url = format!("{}/services/{}", base, id);
     let resp = client.put(url).json(&manifest).send().await?;
@@ -225,28 +299,152 @@ pub async fn update_service(
     Ok(())
 }
 
-/// Delete a service.
-pub async fn delete_service(client: &reqwest::Client, base: &str, id: &str) -> anyhow::Result<()> {
+/// Clone an existing service's manifest under a new id.
+pub async fn clone_service(
+    client: &reqwest::Client,
+    base: &str,
+    src: &str,
+    new_id: &str,
+    name: Option<String>,
+    overrides: Option<String>,
+    output: OutputFormat,
+) -> anyhow::Result<()> {
+    let mut overrides: Value = match overrides {
+        Some(raw) => serde_json::from_str(&raw)
+            .map_err(|e| anyhow::anyhow!("invalid --overrides JSON: {}", e))?,
+        None => Value::Object(Default::default()),
+    };
+    if let (Some(name), Some(map)) = (name, overrides.as_object_mut()) {
+        map.insert("name".into(), Value::String(name));
+    }
+
+    print_header(&format!("📄 CLONE SERVICE: {} → {}", src, new_id));
+    print_progress("Cloning service");
+    let url = format!("{}/services/{}/clone", base, src);
+    let resp = client
+        .post(url)
+        .json(&serde_json::json!({ "id": new_id, "overrides": overrides }))
+        .send()
+        .await?;
+
+    match handle_error(resp).await {
+        Ok(resp) => {
+            let created: ServiceManifest = resp.json().await?;
+            finish_progress_success("Service cloned");
+            match output {
+                OutputFormat::Json | OutputFormat::Ndjson => print_output(&created, output)?,
+                OutputFormat::Table => {
+                    println!();
+                    print_success(&format!("Service '{}' created from '{}'", created.id, src));
+                    print_kv("Name", &created.name);
+                    print_kv("Command", &created.command);
+                    println!();
+                }
+            }
+            Ok(())
+        }
+        Err(e) => {
+            finish_progress_error("Clone failed");
+            print_error(&format!("{}", e));
+            Err(e)
+        }
+    }
+}
+
+/// Delete a service: archive it by default, or remove it permanently with `purge`.
+pub async fn delete_service(
+    client: &reqwest::Client,
+    base: &str,
+    id: &str,
+    purge: bool,
+    keep_logs: bool,
+) -> anyhow::Result<()> {
     print_header(&format!("🗑️  DELETE SERVICE: {}", id.to_uppercase()));
 
-    print_warning(&format!("This will permanently delete service '{}'", id));
+    if purge {
+        print_warning(&format!("This will permanently delete service '{}'", id));
+        if keep_logs {
+            print_hint("Logs will be archived to data_dir/deleted/<id>/logs");
+        }
+    } else {
+        print_info(&format!(
+            "Service '{}' will be moved to data_dir/archive (use --purge to delete permanently)",
+            id
+        ));
+    }
     println!();
 
     print_progress("Deleting service");
-    let url = format!("{}/services/{}", base, id);
-    let resp = client.delete(url).send().await?;
+    let options = DeleteOptions { purge, keep_logs };
 
-    match handle_error(resp).await {
-        Ok(_) => {
+    match api(client, base).delete_service(id, options).await {
+        Ok(()) => {
             finish_progress_success("Service deleted");
             println!();
-            print_success(&format!("Service '{}' has been deleted.", id));
+            if purge {
+                print_success(&format!("Service '{}' has been deleted.", id));
+            } else {
+                print_success(&format!("Service '{}' has been archived.", id));
+                print_hint(
+                    "Use 'archive list' and 'archive restore <archive-id>' to bring it back",
+                );
+            }
             println!();
         }
         Err(e) => {
             finish_progress_error("Delete failed");
             print_error(&format!("{}", e));
-            return Err(e);
+            return Err(e.into());
+        }
+    }
+    Ok(())
+}
+
+/// List soft-deleted services (admin only).
+pub async fn list_archived(
+    client: &reqwest::Client,
+    base: &str,
+    output: OutputFormat,
+) -> anyhow::Result<()> {
+    let archived = api(client, base).list_archived().await?;
+
+    match output {
+        OutputFormat::Json | OutputFormat::Ndjson => print_output_list(&archived, output)?,
+        OutputFormat::Table => {
+            print_header("ARCHIVED SERVICES");
+            if archived.is_empty() {
+                print_empty("No archived services.");
+                return Ok(());
+            }
+            print_table_header(&[("ARCHIVE ID", 40), ("NAME", 20), ("ARCHIVED AT", 20)]);
+            for entry in &archived {
+                println!(
+                    "  {:<40} {:<20} {}",
+                    entry.archive_id.as_str().cyan(),
+                    entry.name,
+                    entry.archived_at.format("%Y-%m-%d %H:%M:%S")
+                );
+            }
+            println!();
+            print_hint("Use 'archive restore <archive-id>' to restore a service");
+        }
+    }
+    Ok(())
+}
+
+/// Restore an archived service (admin only).
+pub async fn restore_archived(
+    client: &reqwest::Client,
+    base: &str,
+    archive_id: &str,
+    output: OutputFormat,
+) -> anyhow::Result<()> {
+    let manifest = api(client, base).restore_archived(archive_id).await?;
+
+    match output {
+        OutputFormat::Json | OutputFormat::Ndjson => print_output(&manifest, output)?,
+        OutputFormat::Table => {
+            print_success(&format!("Service '{}' has been restored.", manifest.id));
         }
     }
     Ok(())
@@ -259,13 +457,10 @@ pub(crate) async fn create_service_from_manifest(
     manifest: ServiceManifest,
     output: OutputFormat,
 ) -> anyhow::Result<()> {
-    let url = format!("{}/services", base);
-    let resp = client.post(url).json(&manifest).send().await?;
-    let resp = handle_error(resp).await?;
-    let created: ServiceManifest = resp.json().await?;
+    let created = api(client, base).create_service(&manifest).await?;
 
     match output {
-        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&created)?),
+        OutputFormat::Json | OutputFormat::Ndjson => print_output(&created, output)?,
         OutputFormat::Table => {
             // Don't print anything here - the caller handles output
         }
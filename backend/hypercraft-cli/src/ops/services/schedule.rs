@@ -1,13 +1,15 @@
 //! Schedule management operations for services.
 
-use super::super::output::OutputFormat;
+use super::super::output::{print_output, print_output_list, OutputFormat};
 use super::super::ui::{
     finish_progress_error, finish_progress_success, print_empty, print_error, print_header,
     print_hint, print_kv, print_kv_colored, print_progress, print_section, print_success,
     print_warning, KvColor,
 };
+use super::lifecycle::print_service_status;
 use crate::client::handle_error;
 use crossterm::style::Stylize;
+use hypercraft_core::{ScheduleKind, ServiceStatus};
 use serde::{Deserialize, Serialize};
 
 /// Schedule action type
@@ -37,81 +39,222 @@ impl std::str::FromStr for ScheduleAction {
             "start" => Ok(ScheduleAction::Start),
             "restart" => Ok(ScheduleAction::Restart),
             "stop" => Ok(ScheduleAction::Stop),
-            _ => Err(format!("invalid action: {}, expected: start|restart|stop", s)),
+            _ => Err(format!(
+                "invalid action: {}, expected: start|restart|stop",
+                s
+            )),
         }
     }
 }
 
 /// Schedule configuration
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Schedule {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
     pub enabled: bool,
+    #[serde(default)]
     pub cron: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<ScheduleKind>,
     pub action: ScheduleAction,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timezone: Option<String>,
+    #[serde(default)]
+    pub catch_up: bool,
+    /// Only present in responses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_run: Option<String>,
 }
 
 /// Response from get schedule API
 #[derive(Debug, Serialize, Deserialize)]
 struct ScheduleResponse {
-    schedule: Option<Schedule>,
-    next_run: Option<String>,
+    schedules: Vec<Schedule>,
+}
+
+/// Outcome of the most recent run of a schedule entry.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScheduleRun {
+    pub at: String,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// An active schedule entry with its runtime state, as returned by `GET /schedules`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScheduleJob {
+    pub service_id: String,
+    #[serde(flatten)]
+    pub schedule: Schedule,
+    #[serde(default)]
+    pub next_runs: Vec<String>,
+    pub last_fired_at: Option<String>,
+    pub last_result: Option<ScheduleRun>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScheduleJobsResponse {
+    #[serde(default)]
+    paused: bool,
+    schedules: Vec<ScheduleJob>,
+}
+
+/// Scheduler pause state returned by `/scheduler/pause` and `/scheduler/resume`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SchedulerStatus {
+    pub paused: bool,
 }
 
 /// Request to update schedule
 #[derive(Debug, Serialize)]
 struct UpdateScheduleRequest {
-    schedule: Option<Schedule>,
+    schedules: Vec<Schedule>,
 }
 
-/// Get schedule for a service.
-pub async fn get_schedule(
+async fn fetch_schedules(
     client: &reqwest::Client,
     base: &str,
     id: &str,
-    output: OutputFormat,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<Vec<Schedule>> {
     let url = format!("{}/services/{}/schedule", base, id);
     let resp = client.get(url).send().await?;
     let resp = handle_error(resp).await?;
     let data: ScheduleResponse = resp.json().await?;
+    Ok(data.schedules)
+}
+
+async fn put_schedules(
+    client: &reqwest::Client,
+    base: &str,
+    id: &str,
+    schedules: Vec<Schedule>,
+) -> anyhow::Result<ScheduleResponse> {
+    let url = format!("{}/services/{}/schedule", base, id);
+    let req = UpdateScheduleRequest { schedules };
+    let resp = client.put(url).json(&req).send().await?;
+    let resp = handle_error(resp).await?;
+    Ok(resp.json().await?)
+}
+
+fn print_schedule(schedule: &Schedule) {
+    print_section(&format!("Schedule {}", schedule.id));
+    print_schedule_fields(schedule);
+}
+
+fn print_schedule_fields(schedule: &Schedule) {
+    print_kv_colored(
+        "Status",
+        if schedule.enabled {
+            "Enabled"
+        } else {
+            "Disabled"
+        },
+        if schedule.enabled {
+            KvColor::Green
+        } else {
+            KvColor::Grey
+        },
+    );
+    match &schedule.kind {
+        Some(ScheduleKind::Cron(cron)) => print_kv_colored("Cron", cron, KvColor::Yellow),
+        Some(ScheduleKind::Interval { every_secs }) => {
+            print_kv_colored("Every", &format!("{}s", every_secs), KvColor::Yellow)
+        }
+        Some(ScheduleKind::Once { at }) => {
+            print_kv_colored("Once At", &at.to_rfc3339(), KvColor::Yellow)
+        }
+        None => print_kv_colored("Cron", &schedule.cron, KvColor::Yellow),
+    }
+    print_kv("Action", &schedule.action.to_string());
+    if let Some(tz) = &schedule.timezone {
+        print_kv("Timezone", tz);
+    }
+    if schedule.catch_up {
+        print_kv("Catch Up", "yes");
+    }
+    if let Some(next) = &schedule.next_run {
+        print_kv_colored("Next Run", next, KvColor::Cyan);
+    }
+}
+
+/// Get schedules for a service.
+pub async fn get_schedule(
+    client: &reqwest::Client,
+    base: &str,
+    id: &str,
+    output: OutputFormat,
+) -> anyhow::Result<()> {
+    let schedules = fetch_schedules(client, base, id).await?;
 
     match output {
-        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&data)?),
+        OutputFormat::Json | OutputFormat::Ndjson => print_output_list(&schedules, output)?,
         OutputFormat::Table => {
             print_header(&format!("SCHEDULE: {}", id.to_uppercase()));
 
-            match data.schedule {
-                Some(schedule) => {
-                    print_section("Configuration");
-                    print_kv_colored(
-                        "Status",
-                        if schedule.enabled { "Enabled" } else { "Disabled" },
-                        if schedule.enabled {
-                            KvColor::Green
-                        } else {
-                            KvColor::Grey
-                        },
-                    );
-                    print_kv_colored("Cron", &schedule.cron, KvColor::Yellow);
-                    print_kv("Action", &schedule.action.to_string());
-                    if let Some(tz) = &schedule.timezone {
-                        print_kv("Timezone", tz);
-                    }
+            if schedules.is_empty() {
+                print_empty("No schedule configured for this service.");
+                println!();
+                print_hint("Use 'schedule set <id> --cron \"...\"' to configure a schedule");
+            } else {
+                for schedule in &schedules {
+                    print_schedule(schedule);
+                }
+                println!();
+                if let Some(schedule) = schedules.iter().find(|s| s.kind.is_none()) {
+                    print_cron_help(&schedule.cron);
+                }
+            }
+            println!();
+        }
+    }
+    Ok(())
+}
 
-                    if let Some(next) = &data.next_run {
-                        print_section("Next Execution");
-                        print_kv_colored("Next Run", next, KvColor::Cyan);
-                    }
+/// List every active schedule across services with next runs and last result (admin).
+pub async fn list_schedules(
+    client: &reqwest::Client,
+    base: &str,
+    output: OutputFormat,
+) -> anyhow::Result<()> {
+    let url = format!("{}/schedules", base);
+    let resp = client.get(url).send().await?;
+    let resp = handle_error(resp).await?;
+    let ScheduleJobsResponse {
+        paused,
+        schedules: jobs,
+    } = resp.json().await?;
 
-                    println!();
-                    print_cron_help(&schedule.cron);
+    match output {
+        OutputFormat::Json | OutputFormat::Ndjson => print_output_list(&jobs, output)?,
+        OutputFormat::Table => {
+            print_header("SCHEDULES");
+            if paused {
+                print_warning(
+                    "Scheduler is paused. Use 'schedule resume' to re-arm all schedules.",
+                );
+            } else if jobs.is_empty() {
+                print_empty("No active schedules.");
+            }
+            for job in &jobs {
+                print_section(&format!("{} / {}", job.service_id, job.schedule.id));
+                print_schedule_fields(&job.schedule);
+                if !job.next_runs.is_empty() {
+                    print_kv_colored("Next Runs", &job.next_runs.join(", "), KvColor::Cyan);
                 }
-                None => {
-                    print_empty("No schedule configured for this service.");
-                    println!();
-                    print_hint("Use 'schedule set <id> --cron \"...\"' to configure a schedule");
+                print_kv(
+                    "Last Fired",
+                    job.last_fired_at.as_deref().unwrap_or("never"),
+                );
+                match &job.last_result {
+                    Some(run) if run.ok => print_kv_colored("Last Result", "ok", KvColor::Green),
+                    Some(run) => print_kv_colored(
+                        "Last Result",
+                        run.error.as_deref().unwrap_or("error"),
+                        KvColor::Red,
+                    ),
+                    None => print_kv_colored("Last Result", "-", KvColor::Grey),
                 }
             }
             println!();
@@ -120,24 +263,55 @@ pub async fn get_schedule(
     Ok(())
 }
 
-/// Set schedule for a service.
+/// Build a schedule kind from the mutually exclusive `--cron`, `--every`, `--at` options.
+///
+/// Returns `(cron, kind)`: plain cron keeps using the legacy `cron` field.
+pub fn schedule_trigger(
+    cron: Option<String>,
+    every: Option<u64>,
+    at: Option<String>,
+) -> anyhow::Result<(String, Option<ScheduleKind>)> {
+    match (cron, every, at) {
+        (Some(cron), None, None) => Ok((cron, None)),
+        (None, Some(every_secs), None) => {
+            Ok((String::new(), Some(ScheduleKind::Interval { every_secs })))
+        }
+        (None, None, Some(at)) => {
+            let kind = serde_json::from_value(serde_json::json!({ "once": { "at": at } }))
+                .map_err(|_| {
+                    anyhow::anyhow!("invalid --at '{}': expected RFC 3339 datetime", at)
+                })?;
+            Ok((String::new(), Some(kind)))
+        }
+        _ => Err(anyhow::anyhow!(
+            "specify exactly one of --cron, --every or --at"
+        )),
+    }
+}
+
+/// Add a schedule to a service, or replace the entry with id `entry`.
+#[allow(clippy::too_many_arguments)]
 pub async fn set_schedule(
     client: &reqwest::Client,
     base: &str,
     id: &str,
-    cron: &str,
+    entry: Option<&str>,
+    cron: String,
+    kind: Option<ScheduleKind>,
     action: ScheduleAction,
     enabled: bool,
+    timezone: Option<String>,
+    catch_up: bool,
     output: OutputFormat,
 ) -> anyhow::Result<()> {
     print_header(&format!("SET SCHEDULE: {}", id.to_uppercase()));
 
-    // Validate cron expression first
-    print_progress("Validating cron expression");
+    // Validate schedule first
+    print_progress("Validating schedule");
     let validate_url = format!("{}/schedule/validate", base);
     let validate_resp = client
         .post(&validate_url)
-        .json(&serde_json::json!({ "cron": cron }))
+        .json(&serde_json::json!({ "cron": cron, "kind": kind, "timezone": timezone }))
         .send()
         .await?;
     let validate_resp = handle_error(validate_resp).await?;
@@ -148,58 +322,48 @@ pub async fn set_schedule(
         .and_then(|v| v.as_bool())
         .unwrap_or(false)
     {
-        finish_progress_error("Invalid cron expression");
+        finish_progress_error("Invalid schedule");
         if let Some(err) = validate_result.get("error").and_then(|v| v.as_str()) {
             print_error(err);
         }
-        return Err(anyhow::anyhow!("invalid cron expression"));
+        return Err(anyhow::anyhow!("invalid schedule"));
     }
-    finish_progress_success("Cron expression valid");
-
-    // Update schedule
-    print_progress("Updating schedule");
-    let url = format!("{}/services/{}/schedule", base, id);
-    let req = UpdateScheduleRequest {
-        schedule: Some(Schedule {
-            enabled,
-            cron: cron.to_string(),
-            action,
-            timezone: None,
-        }),
+    finish_progress_success("Schedule valid");
+
+    let mut schedules = fetch_schedules(client, base, id).await?;
+    let schedule = Schedule {
+        id: entry.unwrap_or_default().to_string(),
+        enabled,
+        cron,
+        kind,
+        action,
+        timezone,
+        catch_up,
+        next_run: None,
     };
-    let resp = client.put(url).json(&req).send().await?;
+    match entry {
+        Some(entry) => match schedules.iter_mut().find(|s| s.id == entry) {
+            Some(existing) => *existing = schedule,
+            None => {
+                print_error(&format!("Schedule '{}' not found", entry));
+                return Err(anyhow::anyhow!("schedule not found"));
+            }
+        },
+        None => schedules.push(schedule),
+    }
 
-    match handle_error(resp).await {
-        Ok(resp) => {
+    print_progress("Updating schedule");
+    match put_schedules(client, base, id, schedules).await {
+        Ok(data) => {
             finish_progress_success("Schedule updated");
-            let data: ScheduleResponse = resp.json().await?;
-
             match output {
-                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&data)?),
+                OutputFormat::Json | OutputFormat::Ndjson => print_output(&data, output)?,
                 OutputFormat::Table => {
                     println!();
                     print_success(&format!("Schedule for '{}' has been configured!", id));
-
-                    if let Some(schedule) = &data.schedule {
-                        print_section("Configuration");
-                        print_kv_colored(
-                            "Status",
-                            if schedule.enabled { "Enabled" } else { "Disabled" },
-                            if schedule.enabled {
-                                KvColor::Green
-                            } else {
-                                KvColor::Grey
-                            },
-                        );
-                        print_kv_colored("Cron", &schedule.cron, KvColor::Yellow);
-                        print_kv("Action", &schedule.action.to_string());
-                    }
-
-                    if let Some(next) = &data.next_run {
-                        print_section("Next Execution");
-                        print_kv_colored("Next Run", next, KvColor::Cyan);
+                    for schedule in &data.schedules {
+                        print_schedule(schedule);
                     }
-
                     println!();
                 }
             }
@@ -214,27 +378,43 @@ pub async fn set_schedule(
     Ok(())
 }
 
-/// Remove schedule from a service.
+/// Remove one schedule entry, or all schedules, from a service.
 pub async fn remove_schedule(
     client: &reqwest::Client,
     base: &str,
     id: &str,
+    entry: Option<&str>,
     output: OutputFormat,
 ) -> anyhow::Result<()> {
     print_header(&format!("REMOVE SCHEDULE: {}", id.to_uppercase()));
 
-    print_warning(&format!(
-        "This will remove the scheduled task for service '{}'",
-        id
-    ));
+    let schedules = match entry {
+        Some(entry) => {
+            print_warning(&format!(
+                "This will remove scheduled task '{}' of service '{}'",
+                entry, id
+            ));
+            let mut schedules = fetch_schedules(client, base, id).await?;
+            let before = schedules.len();
+            schedules.retain(|s| s.id != entry);
+            if schedules.len() == before {
+                print_error(&format!("Schedule '{}' not found", entry));
+                return Err(anyhow::anyhow!("schedule not found"));
+            }
+            schedules
+        }
+        None => {
+            print_warning(&format!(
+                "This will remove all scheduled tasks for service '{}'",
+                id
+            ));
+            vec![]
+        }
+    };
     println!();
 
     print_progress("Removing schedule");
-    let url = format!("{}/services/{}/schedule", base, id);
-    let req = UpdateScheduleRequest { schedule: None };
-    let resp = client.put(url).json(&req).send().await?;
-
-    match handle_error(resp).await {
+    match put_schedules(client, base, id, schedules).await {
         Ok(_) => {
             finish_progress_success("Schedule removed");
             println!();
@@ -249,61 +429,128 @@ pub async fn remove_schedule(
     }
 
     match output {
-        OutputFormat::Json => println!(r#"{{"removed": true}}"#),
+        OutputFormat::Json | OutputFormat::Ndjson => println!(r#"{{"removed": true}}"#),
         OutputFormat::Table => {}
     }
 
     Ok(())
 }
 
-/// Enable or disable a schedule.
+/// Run a service's scheduled action once right now (`entry` picks one of several schedules).
+pub async fn run_schedule(
+    client: &reqwest::Client,
+    base: &str,
+    id: &str,
+    entry: Option<&str>,
+    output: OutputFormat,
+) -> anyhow::Result<()> {
+    let url = format!("{}/services/{}/schedule/run", base, id);
+    let mut req = client.post(url);
+    if let Some(entry) = entry {
+        req = req.query(&[("entry", entry)]);
+    }
+
+    match output {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let resp = handle_error(req.send().await?).await?;
+            let status: ServiceStatus = resp.json().await?;
+            print_output(&status, output)?;
+        }
+        OutputFormat::Table => {
+            print_header(&format!("RUN SCHEDULE: {}", id.to_uppercase()));
+            print_progress("Running scheduled action");
+            match handle_error(req.send().await?).await {
+                Ok(resp) => {
+                    let status: ServiceStatus = resp.json().await?;
+                    finish_progress_success("Scheduled action executed");
+                    println!();
+                    print_service_status(&status);
+                    println!();
+                }
+                Err(e) => {
+                    finish_progress_error("Scheduled action failed");
+                    println!();
+                    print_error(&format!("{}", e));
+                    return Err(e);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Pause or resume the whole scheduler (admin).
+pub async fn set_scheduler_paused(
+    client: &reqwest::Client,
+    base: &str,
+    pause: bool,
+    output: OutputFormat,
+) -> anyhow::Result<()> {
+    let verb = if pause { "pause" } else { "resume" };
+    let url = format!("{}/scheduler/{}", base, verb);
+    let resp = client.post(url).send().await?;
+    let resp = handle_error(resp).await?;
+    let status: SchedulerStatus = resp.json().await?;
+
+    match output {
+        OutputFormat::Json | OutputFormat::Ndjson => print_output(&status, output)?,
+        OutputFormat::Table => {
+            if status.paused {
+                print_success("Scheduler paused. Schedules are kept and re-armed on resume.");
+            } else {
+                print_success("Scheduler resumed.");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Enable or disable one schedule entry, or all of them.
 pub async fn toggle_schedule(
     client: &reqwest::Client,
     base: &str,
     id: &str,
+    entry: Option<&str>,
     enable: bool,
     output: OutputFormat,
 ) -> anyhow::Result<()> {
     let action_str = if enable { "ENABLE" } else { "DISABLE" };
     print_header(&format!("{} SCHEDULE: {}", action_str, id.to_uppercase()));
 
-    // Get current schedule
-    let url = format!("{}/services/{}/schedule", base, id);
-    let resp = client.get(&url).send().await?;
-    let resp = handle_error(resp).await?;
-    let data: ScheduleResponse = resp.json().await?;
-
-    let schedule = match data.schedule {
-        Some(mut s) => {
-            s.enabled = enable;
-            s
-        }
-        None => {
-            print_error("No schedule configured for this service. Use 'schedule set' first.");
-            return Err(anyhow::anyhow!("no schedule configured"));
+    let mut schedules = fetch_schedules(client, base, id).await?;
+    if schedules.is_empty() {
+        print_error("No schedule configured for this service. Use 'schedule set' first.");
+        return Err(anyhow::anyhow!("no schedule configured"));
+    }
+    let mut matched = false;
+    for schedule in schedules.iter_mut() {
+        if entry.is_none_or(|entry| schedule.id == entry) {
+            schedule.enabled = enable;
+            matched = true;
         }
-    };
+    }
+    if !matched {
+        print_error(&format!(
+            "Schedule '{}' not found",
+            entry.unwrap_or_default()
+        ));
+        return Err(anyhow::anyhow!("schedule not found"));
+    }
 
     print_progress(&format!(
         "{} schedule",
         if enable { "Enabling" } else { "Disabling" }
     ));
-    let req = UpdateScheduleRequest {
-        schedule: Some(schedule),
-    };
-    let resp = client.put(&url).json(&req).send().await?;
 
-    match handle_error(resp).await {
-        Ok(resp) => {
+    match put_schedules(client, base, id, schedules).await {
+        Ok(data) => {
             finish_progress_success(&format!(
                 "Schedule {}",
                 if enable { "enabled" } else { "disabled" }
             ));
 
-            let data: ScheduleResponse = resp.json().await?;
-
             match output {
-                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&data)?),
+                OutputFormat::Json | OutputFormat::Ndjson => print_output(&data, output)?,
                 OutputFormat::Table => {
                     println!();
                     print_success(&format!(
@@ -311,12 +558,8 @@ pub async fn toggle_schedule(
                         id,
                         if enable { "enabled" } else { "disabled" }
                     ));
-
-                    if enable {
-                        if let Some(next) = &data.next_run {
-                            print_section("Next Execution");
-                            print_kv_colored("Next Run", next, KvColor::Cyan);
-                        }
+                    for schedule in &data.schedules {
+                        print_schedule(schedule);
                     }
                     println!();
                 }
@@ -370,16 +613,7 @@ fn print_cron_help(cron: &str) {
 
     println!();
     println!("  {}", "Common Examples:".dark_grey());
-    println!(
-        "    {} - Every day at 8:00 AM",
-        "0 0 8 * * *".yellow()
-    );
-    println!(
-        "    {} - Weekdays at 6:30 AM",
-        "0 30 6 * * 1-5".yellow()
-    );
-    println!(
-        "    {} - Every 2 hours",
-        "0 0 */2 * * *".yellow()
-    );
+    println!("    {} - Every day at 8:00 AM", "0 0 8 * * *".yellow());
+    println!("    {} - Weekdays at 6:30 AM", "0 30 6 * * 1-5".yellow());
+    println!("    {} - Every 2 hours", "0 0 */2 * * *".yellow());
 }
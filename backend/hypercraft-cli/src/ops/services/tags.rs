@@ -0,0 +1,50 @@
+//! Bulk tag edits across services.
+
+use super::super::output::{print_output_list, OutputFormat};
+use super::super::ui::{print_header, print_hint, print_success, print_table_header};
+use crate::client::handle_error;
+use anyhow::anyhow;
+use crossterm::style::Stylize;
+use hypercraft_core::ServiceSummary;
+use serde_json::json;
+
+/// Add and/or remove tags on several services at once.
+pub async fn bulk_tag(
+    client: &reqwest::Client,
+    base: &str,
+    ids: &[String],
+    add: &[String],
+    remove: &[String],
+    output: OutputFormat,
+) -> anyhow::Result<()> {
+    if ids.is_empty() {
+        return Err(anyhow!("specify service ids with --ids id1,id2"));
+    }
+
+    let url = format!("{}/tags/bulk", base);
+    let body = json!({ "ids": ids, "add": add, "remove": remove });
+    let resp = client.post(url).json(&body).send().await?;
+    let resp = handle_error(resp).await?;
+    let services: Vec<ServiceSummary> = resp.json().await?;
+
+    match output {
+        OutputFormat::Json | OutputFormat::Ndjson => print_output_list(&services, output)?,
+        OutputFormat::Table => {
+            print_header("🏷  TAGS UPDATED");
+            print_table_header(&[("ID", 24), ("TAGS", 40)]);
+            for svc in &services {
+                let tags = if svc.tags.is_empty() {
+                    "-".to_string()
+                } else {
+                    svc.tags.join(", ")
+                };
+                println!("  {:<24} {}", svc.id.as_str().cyan(), tags);
+            }
+            println!();
+            print_success(&format!("Updated {} service(s)", services.len()));
+            print_hint("Use 'list --tag <tag>' to list services by tag");
+            println!();
+        }
+    }
+    Ok(())
+}
@@ -1,12 +1,13 @@
-use super::ui::{print_error, print_header};
+use super::ui::{box_inner_width, boxed_title, print_error, print_header};
 use super::{
-    add_user_service, attach_service, create_service, create_service_interactive, create_user,
-    delete_service, delete_user, get_service, get_user, list_services, list_users, login,
-    logs_service, remove_user_service, restart_service, set_user_services, start_service,
-    status_service, stop_service, update_service, update_user_password, OutputFormat,
+    add_user_service, attach_service, batch_services, clone_service, create_service, create_service_interactive, create_user,
+    delete_service, delete_user, get_schedule, get_service, get_user, group_action, list_services, list_users, login,
+    list_user_sessions, logs_service, prompt_new_password, remove_schedule, remove_user_service, restart_service, revoke_user_session, revoke_user_tokens, set_schedule, set_user_services, setup_two_factor, signal_service, start_service,
+    status_service, stop_service, update_service, update_user_password, OutputFormat, ScheduleAction,
 };
 use anyhow::anyhow;
 use crossterm::style::Stylize;
+use hypercraft_core::ServiceListQuery;
 use rustyline::completion::{Completer, Pair};
 use rustyline::error::ReadlineError;
 use rustyline::highlight::Highlighter;
@@ -21,19 +22,118 @@ use tokio::sync::Mutex;
 
 /// All available commands for completion
 const COMMANDS: &[&str] = &[
-    "list", "ls", "info", "get", "create", "create-i", "new", "update", "delete", "rm", "start",
-    "stop", "restart", "status", "logs", "attach", "help", "exit", "quit",
-    "login", "user",
+    "list", "ls", "info", "get", "create", "create-i", "new", "clone", "update", "delete", "rm", "start",
+    "stop", "restart", "signal", "status", "batch", "group", "logs", "attach", "help", "exit", "quit",
+    "login", "user", "schedule", "2fa",
 ];
 
 /// Commands that need service ID as argument
 const SERVICE_ID_COMMANDS: &[&str] = &[
-    "info", "get", "delete", "rm", "start", "stop", "restart", "status", "logs", "attach", "update",
+    "info", "get", "delete", "rm", "start", "stop", "restart", "signal", "status", "logs", "attach",
+    "update", "clone",
 ];
 
+/// Subcommands completed as the second word
+const SUBCOMMANDS: &[(&str, &[&str])] = &[
+    (
+        "user",
+        &[
+            "list",
+            "get",
+            "create",
+            "delete",
+            "password",
+            "services",
+            "grant",
+            "revoke",
+            "revoke-tokens",
+            "sessions",
+            "kill-session",
+        ],
+    ),
+    ("schedule", &["get", "set", "remove"]),
+    ("2fa", &["setup"]),
+    ("group", &["start", "stop", "restart"]),
+];
+
+/// Subcommands whose next argument is a service ID
+const SERVICE_ID_SUBCOMMANDS: &[(&str, &str)] = &[
+    ("schedule", "get"),
+    ("schedule", "set"),
+    ("schedule", "remove"),
+];
+
+/// Whether a line carries a password and must stay out of history
+fn contains_password(tokens: &[String]) -> bool {
+    let words: Vec<&str> = tokens.iter().map(String::as_str).collect();
+    match words.as_slice() {
+        ["login", _, _, ..] => true,
+        ["user", "create" | "new", _, password, ..] => *password != "-",
+        ["user", "password" | "passwd", _, _, ..] => true,
+        _ => false,
+    }
+}
+
+/// Which id cache a completion draws from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum IdKind {
+    Service,
+    User,
+    Group,
+}
+
+/// `user` subcommands whose next argument is a user ID
+const USER_ID_COMMANDS: &[&str] = &[
+    "get",
+    "info",
+    "delete",
+    "rm",
+    "password",
+    "passwd",
+    "services",
+    "set-services",
+    "grant",
+    "revoke",
+    "revoke-tokens",
+    "logout",
+    "sessions",
+    "kill-session",
+];
+
+/// Commands that take a group ID as argument
+const GROUP_ID_COMMANDS: &[&str] = &["list", "ls"];
+
+/// Id set to complete for the word at `index`, based on the leading command
+fn id_kind_at(tokens: &[&str], index: usize) -> Option<IdKind> {
+    let cmd = *tokens.first()?;
+    match index {
+        1 if SERVICE_ID_COMMANDS.contains(&cmd) => Some(IdKind::Service),
+        1 if GROUP_ID_COMMANDS.contains(&cmd) => Some(IdKind::Group),
+        2 if cmd == "user" && USER_ID_COMMANDS.contains(tokens.get(1)?) => Some(IdKind::User),
+        2 if cmd == "group" => Some(IdKind::Group),
+        2 if SERVICE_ID_SUBCOMMANDS.contains(&(cmd, *tokens.get(1)?)) => Some(IdKind::Service),
+        3 if cmd == "user" && matches!(*tokens.get(1)?, "grant" | "revoke") => {
+            Some(IdKind::Service)
+        }
+        _ => None,
+    }
+}
+
 /// Shared state for completer
 struct CompleterState {
     service_ids: Vec<String>,
+    user_ids: Vec<String>,
+    group_ids: Vec<String>,
+}
+
+impl CompleterState {
+    fn ids_mut(&mut self, kind: IdKind) -> &mut Vec<String> {
+        match kind {
+            IdKind::Service => &mut self.service_ids,
+            IdKind::User => &mut self.user_ids,
+            IdKind::Group => &mut self.group_ids,
+        }
+    }
 }
 
 /// Custom completer for hypercraft shell
@@ -47,23 +147,37 @@ impl HcCompleter {
         Self {
             state: Arc::new(Mutex::new(CompleterState {
                 service_ids: Vec::new(),
+                user_ids: Vec::new(),
+                group_ids: Vec::new(),
             })),
         }
     }
 
-    fn update_services_blocking(&self, ids: Vec<String>) {
+    fn update_ids_blocking(&self, kind: IdKind, ids: Vec<String>) {
         // Use try_lock for non-async context
         if let Ok(mut state) = self.state.try_lock() {
-            state.service_ids = ids;
+            *state.ids_mut(kind) = ids;
         }
     }
 
-    fn get_services_blocking(&self) -> Vec<String> {
+    fn get_ids_blocking(&self, kind: IdKind) -> Vec<String> {
         self.state
             .try_lock()
-            .map(|s| s.service_ids.clone())
+            .map(|mut s| s.ids_mut(kind).clone())
             .unwrap_or_default()
     }
+
+    /// Refresh one id cache from the API (e.g. users stay empty for non-admins)
+    async fn refresh(&self, client: &reqwest::Client, base: &str, kind: IdKind) {
+        let path = match kind {
+            IdKind::Service => "services",
+            IdKind::User => "users",
+            IdKind::Group => "groups",
+        };
+        if let Ok(ids) = fetch_ids(client, base, path).await {
+            self.update_ids_blocking(kind, ids);
+        }
+    }
 }
 
 impl Completer for HcCompleter {
@@ -77,6 +191,7 @@ impl Completer for HcCompleter {
     ) -> rustyline::Result<(usize, Vec<Pair>)> {
         let line_to_cursor = &line[..pos];
         let tokens: Vec<&str> = line_to_cursor.split_whitespace().collect();
+        let start = line_to_cursor.rfind(' ').map(|i| i + 1).unwrap_or(0);
 
         // If empty or typing first word -> complete commands
         if tokens.is_empty() || (tokens.len() == 1 && !line_to_cursor.ends_with(' ')) {
@@ -89,29 +204,41 @@ impl Completer for HcCompleter {
                     replacement: cmd.to_string(),
                 })
                 .collect();
-            let start = line_to_cursor.rfind(' ').map(|i| i + 1).unwrap_or(0);
             return Ok((start, matches));
         }
 
-        // If typing second word for service commands -> complete service IDs
-        let cmd = tokens[0];
-        if SERVICE_ID_COMMANDS.contains(&cmd) {
-            let prefix = if line_to_cursor.ends_with(' ') {
-                ""
-            } else {
-                tokens.get(1).copied().unwrap_or("")
-            };
+        let (index, prefix) = if line_to_cursor.ends_with(' ') {
+            (tokens.len(), "")
+        } else {
+            (tokens.len() - 1, tokens[tokens.len() - 1])
+        };
+
+        // Second word of commands with subcommands -> complete subcommands
+        if index == 1 {
+            if let Some((_, subs)) = SUBCOMMANDS.iter().find(|(name, _)| *name == tokens[0]) {
+                let matches: Vec<Pair> = subs
+                    .iter()
+                    .filter(|sub| sub.starts_with(prefix))
+                    .map(|sub| Pair {
+                        display: sub.to_string(),
+                        replacement: sub.to_string(),
+                    })
+                    .collect();
+                return Ok((start, matches));
+            }
+        }
 
-            let service_ids = self.get_services_blocking();
-            let matches: Vec<Pair> = service_ids
-                .iter()
+        // Service / user / group IDs depending on the leading command
+        if let Some(kind) = id_kind_at(&tokens, index) {
+            let matches: Vec<Pair> = self
+                .get_ids_blocking(kind)
+                .into_iter()
                 .filter(|id| id.starts_with(prefix))
                 .map(|id| Pair {
                     display: id.clone(),
-                    replacement: id.clone(),
+                    replacement: id,
                 })
                 .collect();
-            let start = line_to_cursor.rfind(' ').map(|i| i + 1).unwrap_or(0);
             return Ok((start, matches));
         }
 
@@ -141,28 +268,16 @@ impl Hinter for HcCompleter {
             }
         }
 
-        // Hint for service IDs
-        let cmd = tokens.first().copied().unwrap_or("");
-        if SERVICE_ID_COMMANDS.contains(&cmd)
-            && (tokens.len() == 1 || (tokens.len() == 2 && !line.ends_with(' ')))
-        {
-            let prefix = if line.ends_with(' ') {
-                ""
-            } else {
-                tokens.get(1).copied().unwrap_or("")
-            };
-
-            if !prefix.is_empty() {
-                let service_ids = self.get_services_blocking();
-                for id in &service_ids {
-                    if id.starts_with(prefix) && id != prefix {
-                        return Some(id[prefix.len()..].to_string());
-                    }
-                }
-            }
+        // Hint for IDs (only once something has been typed)
+        if tokens.len() < 2 || line.ends_with(' ') {
+            return None;
         }
-
-        None
+        let prefix = tokens[tokens.len() - 1];
+        let kind = id_kind_at(&tokens, tokens.len() - 1)?;
+        self.get_ids_blocking(kind)
+            .into_iter()
+            .find(|id| id.starts_with(prefix) && id != prefix)
+            .map(|id| id[prefix.len()..].to_string())
     }
 }
 
@@ -211,9 +326,9 @@ pub async fn shell_loop(
         let _ = rl.load_history(path);
     }
 
-    // Initial fetch of service IDs for completion
-    if let Ok(ids) = fetch_service_ids(client, base).await {
-        completer.update_services_blocking(ids);
+    // Initial fetch of IDs for completion
+    for kind in [IdKind::Service, IdKind::User, IdKind::Group] {
+        completer.refresh(client, base, kind).await;
     }
 
     loop {
@@ -226,7 +341,10 @@ pub async fn shell_loop(
                     continue;
                 }
 
-                let _ = rl.add_history_entry(line);
+                // 含明文密码的行不进历史
+                if !shell_words::split(line).is_ok_and(|t| contains_password(&t)) {
+                    let _ = rl.add_history_entry(line);
+                }
 
                 if matches!(line, "exit" | "quit") {
                     println!();
@@ -249,11 +367,17 @@ pub async fn shell_loop(
 
                 let result = execute_command(client, base, output, token, cmd, args).await;
 
-                // Refresh service IDs after mutations
-                if matches!(cmd, "create" | "create-i" | "new" | "delete" | "rm") {
-                    if let Ok(ids) = fetch_service_ids(client, base).await {
-                        completer.update_services_blocking(ids);
-                    }
+                // Refresh cached IDs after mutations
+                if matches!(cmd, "create" | "create-i" | "new" | "clone" | "delete" | "rm") {
+                    completer.refresh(client, base, IdKind::Service).await;
+                }
+                if cmd == "user"
+                    && matches!(
+                        args.first().map(String::as_str),
+                        Some("create" | "new" | "delete" | "rm")
+                    )
+                {
+                    completer.refresh(client, base, IdKind::User).await;
                 }
 
                 if let Err(e) = result {
@@ -292,7 +416,13 @@ async fn execute_command(
     args: &[String],
 ) -> anyhow::Result<()> {
     match cmd {
-        "list" | "ls" => list_services(client, base, output).await,
+        "list" | "ls" => {
+            let query = ServiceListQuery {
+                group: args.first().cloned(),
+                ..Default::default()
+            };
+            list_services(client, base, &query, output).await
+        }
         "get" | "info" => match args {
             [id] => get_service(client, base, id, output).await,
             _ => Err(anyhow!("usage: info <id>")),
@@ -300,21 +430,35 @@ async fn execute_command(
         "create" => match args {
             [file] => {
                 let path = PathBuf::from(file);
-                create_service(client, base, path, output).await
+                create_service(client, base, path, None, output).await
             }
             _ => Err(anyhow!("usage: create <file>")),
         },
         "create-i" | "new" => create_service_interactive(client, base, output).await,
+        "clone" => match args {
+            [src, new_id] => clone_service(client, base, src, new_id, None, None, output).await,
+            _ => Err(anyhow!("usage: clone <src> <new-id>")),
+        },
         "update" => match args {
             [id, file] => {
                 let path = PathBuf::from(file);
-                update_service(client, base, id, path, output).await
+                update_service(client, base, id, path, None, false, output).await
             }
-            _ => Err(anyhow!("usage: update <id> <file>")),
+            [id, file, flag] if flag == "--yes" || flag == "-y" => {
+                let path = PathBuf::from(file);
+                update_service(client, base, id, path, None, true, output).await
+            }
+            _ => Err(anyhow!("usage: update <id> <file> [--yes]")),
         },
         "delete" | "rm" => match args {
-            [id] => delete_service(client, base, id).await,
-            _ => Err(anyhow!("usage: delete <id>")),
+            [id] => delete_service(client, base, id, false, false).await,
+            [id, flag] if flag.as_str() == "--purge" => {
+                delete_service(client, base, id, true, false).await
+            }
+            [id, purge, keep] if purge.as_str() == "--purge" && keep.as_str() == "--keep-logs" => {
+                delete_service(client, base, id, true, true).await
+            }
+            _ => Err(anyhow!("usage: delete <id> [--purge [--keep-logs]]")),
         },
         "start" => match args {
             [id] => start_service(client, base, id, output).await,
@@ -328,10 +472,33 @@ async fn execute_command(
             [id] => restart_service(client, base, id, output).await,
             _ => Err(anyhow!("usage: restart <id>")),
         },
+        "signal" => match args {
+            [id, signal] => signal_service(client, base, id, signal, output).await,
+            _ => Err(anyhow!("usage: signal <id> <INT|TERM|HUP|USR1>")),
+        },
         "status" => match args {
             [id] => status_service(client, base, id, output).await,
             _ => Err(anyhow!("usage: status <id>")),
         },
+        "batch" => match args {
+            [action, flag] if flag.as_str() == "--all" => {
+                batch_services(client, base, action, &[], true, output).await
+            }
+            [action, ids] => {
+                let ids: Vec<String> = ids
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(String::from)
+                    .collect();
+                batch_services(client, base, action, &ids, false, output).await
+            }
+            _ => Err(anyhow!("usage: batch <start|stop|restart> <id1,id2|--all>")),
+        },
+        "group" => match args {
+            [action, id] => group_action(client, base, id, action, output).await,
+            _ => Err(anyhow!("usage: group <start|stop|restart> <group-id>")),
+        },
         "logs" => {
             let id = args
                 .first()
@@ -345,25 +512,32 @@ async fn execute_command(
                     tail = n;
                 }
             }
-            logs_service(client, base, id, tail, follow, output).await
+            logs_service(client, base, id, tail, follow, None, None, output).await
         }
         "attach" => match args {
-            [id] => attach_service(base, id, token).await,
-            _ => Err(anyhow!("usage: attach <id>")),
+            [id] => attach_service(base, id, token, false, false).await,
+            [id, flag] if flag.as_str() == "--readonly" => {
+                attach_service(base, id, token, true, false).await
+            }
+            _ => Err(anyhow!("usage: attach <id> [--readonly]")),
         },
         // 认证命令
         "login" => match args {
             [username, password] => {
-                login(client, base, username, password, output).await?;
+                login(client, base, username, password, None, output).await?;
+                Ok(())
+            }
+            [username, password, code] => {
+                login(client, base, username, password, Some(code), output).await?;
                 Ok(())
             }
-            _ => Err(anyhow!("usage: login <username> <password>")),
+            _ => Err(anyhow!("usage: login <username> <password> [totp_code]")),
         },
         // 用户管理命令
         "user" => {
             if args.is_empty() {
                 return Err(anyhow!(
-                    "usage: user <subcommand>\n  subcommands: list, get, create, delete, password, grant, revoke"
+                    "usage: user <subcommand>\n  subcommands: list, get, create, delete, password, grant, revoke, revoke-tokens, sessions, kill-session"
                 ));
             }
             let subcmd = args[0].as_str();
@@ -375,15 +549,21 @@ async fn execute_command(
                     _ => Err(anyhow!("usage: user get <user_id>")),
                 },
                 "create" | "new" => match subargs {
-                    [username, password] => {
-                        create_user(client, base, username, password, vec![], output).await
+                    // 省略密码或用 - 占位时隐藏输入
+                    [username] => {
+                        let password = prompt_new_password()?;
+                        create_user(client, base, username, &password, vec![], output).await
                     }
                     [username, password, services @ ..] => {
+                        let password = match password.as_str() {
+                            "-" => prompt_new_password()?,
+                            _ => password.clone(),
+                        };
                         let svc_ids: Vec<String> = services.to_vec();
-                        create_user(client, base, username, password, svc_ids, output).await
+                        create_user(client, base, username, &password, svc_ids, output).await
                     }
                     _ => Err(anyhow!(
-                        "usage: user create <username> <password> [service_ids...]"
+                        "usage: user create <username> [password|-] [service_ids...]"
                     )),
                 },
                 "delete" | "rm" => match subargs {
@@ -391,6 +571,10 @@ async fn execute_command(
                     _ => Err(anyhow!("usage: user delete <user_id>")),
                 },
                 "password" | "passwd" => match subargs {
+                    [id] => {
+                        let password = prompt_new_password()?;
+                        update_user_password(client, base, id, &password, None, output).await
+                    }
                     [id, password] => {
                         update_user_password(client, base, id, password, None, output).await
                     }
@@ -406,7 +590,7 @@ async fn execute_command(
                         .await
                     }
                     _ => Err(anyhow!(
-                        "usage: user password <user_id> <new_password> [current_password]"
+                        "usage: user password <user_id> [new_password] [current_password]"
                     )),
                 },
                 "services" | "set-services" => match subargs {
@@ -418,9 +602,15 @@ async fn execute_command(
                 },
                 "grant" => match subargs {
                     [user_id, service_id] => {
-                        add_user_service(client, base, user_id, service_id, output).await
+                        add_user_service(client, base, user_id, service_id, "control", output)
+                            .await
+                    }
+                    [user_id, service_id, level] if level == "read" || level == "control" => {
+                        add_user_service(client, base, user_id, service_id, level, output).await
                     }
-                    _ => Err(anyhow!("usage: user grant <user_id> <service_id>")),
+                    _ => Err(anyhow!(
+                        "usage: user grant <user_id> <service_id> [read|control]"
+                    )),
                 },
                 "revoke" => match subargs {
                     [user_id, service_id] => {
@@ -428,12 +618,61 @@ async fn execute_command(
                     }
                     _ => Err(anyhow!("usage: user revoke <user_id> <service_id>")),
                 },
+                "revoke-tokens" | "logout" => match subargs {
+                    [id] => revoke_user_tokens(client, base, id).await,
+                    _ => Err(anyhow!("usage: user revoke-tokens <user_id>")),
+                },
+                "sessions" => match subargs {
+                    [id] => list_user_sessions(client, base, id, output).await,
+                    _ => Err(anyhow!("usage: user sessions <user_id>")),
+                },
+                "kill-session" => match subargs {
+                    [id, session_id] => revoke_user_session(client, base, id, session_id).await,
+                    _ => Err(anyhow!("usage: user kill-session <user_id> <session_id>")),
+                },
                 _ => Err(anyhow!(
-                    "unknown user subcommand: {}. Try: list, get, create, delete, password, grant, revoke",
+                    "unknown user subcommand: {}. Try: list, get, create, delete, password, grant, revoke, revoke-tokens, sessions, kill-session",
                     subcmd
                 )),
             }
         }
+        "schedule" => match args {
+            [sub, id] if sub == "get" => get_schedule(client, base, id, output).await,
+            [sub, id, cron, rest @ ..] if sub == "set" && rest.len() <= 2 => {
+                let action: ScheduleAction = match rest.first() {
+                    Some(action) => action.parse().map_err(|e: String| anyhow!(e))?,
+                    None => ScheduleAction::Start,
+                };
+                let timezone = rest.get(1).cloned();
+                set_schedule(
+                    client,
+                    base,
+                    id,
+                    None,
+                    cron.clone(),
+                    None,
+                    action,
+                    true,
+                    timezone,
+                    false,
+                    output,
+                )
+                .await
+            }
+            [sub, id] if sub == "remove" || sub == "rm" => {
+                remove_schedule(client, base, id, None, output).await
+            }
+            [sub, id, entry] if sub == "remove" || sub == "rm" => {
+                remove_schedule(client, base, id, Some(entry), output).await
+            }
+            _ => Err(anyhow!(
+                "usage: schedule get <id> | schedule set <id> \"<cron>\" [start|restart|stop] [timezone] | schedule remove <id> [entry]"
+            )),
+        },
+        "2fa" => match args {
+            [sub] if sub == "setup" => setup_two_factor(client, base, output).await,
+            _ => Err(anyhow!("usage: 2fa setup")),
+        },
         "help" => {
             print_help();
             Ok(())
@@ -448,32 +687,34 @@ async fn execute_command(
     }
 }
 
-async fn fetch_service_ids(client: &reqwest::Client, base: &str) -> anyhow::Result<Vec<String>> {
-    use hypercraft_core::ServiceSummary;
-    let url = format!("{}/services", base);
+/// Fetch the `id` of every item listed at `GET {base}/{path}`
+async fn fetch_ids(
+    client: &reqwest::Client,
+    base: &str,
+    path: &str,
+) -> anyhow::Result<Vec<String>> {
+    #[derive(serde::Deserialize)]
+    struct Item {
+        id: String,
+    }
+    let url = format!("{}/{}", base, path);
     let resp = client.get(&url).send().await?;
     if resp.status().is_success() {
-        let services: Vec<ServiceSummary> = resp.json().await?;
-        Ok(services.into_iter().map(|s| s.id).collect())
+        let items: Vec<Item> = resp.json().await?;
+        Ok(items.into_iter().map(|item| item.id).collect())
     } else {
         Ok(vec![])
     }
 }
 
+/// Banner title shown when the shell starts
+const BANNER_TITLE: &str = "🚀 HYPERCRAFT SHELL";
+
 fn print_shell_banner() {
     println!();
-    println!(
-        "{}",
-        "╔══════════════════════════════════════════════════════════════╗".dark_cyan()
-    );
-    println!(
-        "{}",
-        "║                    🚀 HYPERCRAFT SHELL                       ║".dark_cyan()
-    );
-    println!(
-        "{}",
-        "╚══════════════════════════════════════════════════════════════╝".dark_cyan()
-    );
+    for line in boxed_title(BANNER_TITLE, box_inner_width(62)) {
+        println!("{}", line.dark_cyan());
+    }
     println!();
     println!(
         "  {}  Interactive mode. Type {} for commands, {} to exit.",
@@ -494,12 +735,13 @@ fn print_help() {
 
     println!("  {}", "SERVICE MANAGEMENT".white().bold());
     println!("  {}", "─".repeat(50).dark_grey());
-    print_cmd("list", "ls", "List all services");
+    print_cmd("list [group]", "ls", "List services, optionally in one group");
     print_cmd("info <id>", "get", "Show service details");
     print_cmd("create-i", "new", "Create service interactively");
     print_cmd("create <file>", "", "Create service from JSON file");
-    print_cmd("update <id> <file>", "", "Update service config");
-    print_cmd("delete <id>", "rm", "Delete a service");
+    print_cmd("clone <src> <new-id>", "", "Create service from another's config");
+    print_cmd("update <id> <file> [-y]", "", "Update service config (shows a diff first)");
+    print_cmd("delete <id> [--purge [--keep-logs]]", "rm", "Archive (or purge) a service");
     println!();
 
     println!("  {}", "LIFECYCLE CONTROL".white().bold());
@@ -507,29 +749,43 @@ fn print_help() {
     print_cmd("start <id>", "", "Start a service");
     print_cmd("stop <id>", "", "Stop a service");
     print_cmd("restart <id>", "", "Restart a service");
+    print_cmd("signal <id> <signal>", "", "Send INT/TERM/HUP/USR1 to a service");
     print_cmd("status <id>", "", "Show service status");
+    print_cmd("batch <action> <ids|--all>", "", "Start/stop/restart many services");
+    print_cmd("group <action> <group>", "", "Start/stop/restart a group in order");
     println!();
 
     println!("  {}", "MONITORING".white().bold());
     println!("  {}", "─".repeat(50).dark_grey());
     print_cmd("logs <id> [n] [-f]", "", "View logs (n=lines, -f=follow)");
-    print_cmd("attach <id>", "", "Attach to service console");
+    print_cmd("attach <id> [--readonly]", "", "Attach to service console");
     println!();
 
     println!("  {}", "AUTHENTICATION".white().bold());
     println!("  {}", "─".repeat(50).dark_grey());
-    print_cmd("login <user> <pass>", "", "Login and get access token");
+    print_cmd("login <user> <pass> [code]", "", "Login and get access token");
+    print_cmd("2fa setup", "", "Enable two-factor authentication");
+    println!();
+
+    println!("  {}", "SCHEDULING".white().bold());
+    println!("  {}", "─".repeat(50).dark_grey());
+    print_cmd("schedule get <id>", "", "Show service schedules");
+    print_cmd("schedule set <id> \"<cron>\" [action] [tz]", "", "Add a cron schedule");
+    print_cmd("schedule remove <id> [entry]", "rm", "Remove one or all schedules");
     println!();
 
     println!("  {}", "USER MANAGEMENT (Admin)".white().bold());
     println!("  {}", "─".repeat(50).dark_grey());
     print_cmd("user list", "ls", "List all users");
     print_cmd("user get <id>", "info", "Show user details");
-    print_cmd("user create <u> <p>", "new", "Create user");
+    print_cmd("user create <u> [p]", "new", "Create user (prompts if p omitted)");
     print_cmd("user delete <id>", "rm", "Delete user");
-    print_cmd("user password <id> <p> [old]", "", "Update password");
-    print_cmd("user grant <uid> <sid>", "", "Grant service access");
+    print_cmd("user password <id> [p] [old]", "", "Update password (prompts if p omitted)");
+    print_cmd("user grant <uid> <sid> [read|control]", "", "Grant service access");
     print_cmd("user revoke <uid> <sid>", "", "Revoke service access");
+    print_cmd("user revoke-tokens <id>", "logout", "Log user out everywhere");
+    print_cmd("user sessions <id>", "", "List login sessions");
+    print_cmd("user kill-session <id> <sid>", "", "Revoke one login session");
     println!();
 
     println!("  {}", "SHELL".white().bold());
@@ -551,3 +807,60 @@ fn print_cmd(cmd: &str, alias: &str, desc: &str) {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(line: &str) -> Vec<String> {
+        shell_words::split(line).unwrap()
+    }
+
+    #[test]
+    fn password_lines_are_kept_out_of_history() {
+        assert!(contains_password(&split("login admin s3cret")));
+        assert!(contains_password(&split("user create bob s3cret svc1")));
+        assert!(contains_password(&split("user passwd u1 new old")));
+
+        assert!(!contains_password(&split("login")));
+        assert!(!contains_password(&split("user create bob")));
+        assert!(!contains_password(&split("user create bob - svc1")));
+        assert!(!contains_password(&split("user password u1")));
+        assert!(!contains_password(&split(
+            "schedule set mc \"0 0 8 * * *\" restart"
+        )));
+    }
+    #[test]
+    fn completes_ids_by_leading_command() {
+        assert_eq!(id_kind_at(&["info"], 1), Some(IdKind::Service));
+        assert_eq!(id_kind_at(&["ls"], 1), Some(IdKind::Group));
+        assert_eq!(id_kind_at(&["user", "get"], 2), Some(IdKind::User));
+        assert_eq!(
+            id_kind_at(&["user", "grant", "u1"], 3),
+            Some(IdKind::Service)
+        );
+        assert_eq!(id_kind_at(&["schedule", "set"], 2), Some(IdKind::Service));
+        assert_eq!(id_kind_at(&["group", "stop"], 2), Some(IdKind::Group));
+        assert_eq!(id_kind_at(&["user", "create"], 2), None);
+        assert_eq!(id_kind_at(&["info", "svc"], 2), None);
+    }
+    #[test]
+    fn banner_is_intact_and_aligned() {
+        let lines = boxed_title(BANNER_TITLE, 40);
+        assert!(lines[1].contains('🚀'));
+        assert!(lines[1].contains("HYPERCRAFT SHELL"));
+        // 每行显示宽度一致（emoji 占 2 列）
+        let width = |s: &str| {
+            s.chars()
+                .map(|c| {
+                    if c.is_ascii() || "═║╔╗╚╝".contains(c) {
+                        1
+                    } else {
+                        2
+                    }
+                })
+                .sum::<usize>()
+        };
+        assert!(lines.iter().all(|line| width(line) == 42));
+    }
+}
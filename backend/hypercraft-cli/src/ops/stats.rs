@@ -0,0 +1,83 @@
+//! System resource stats, one-shot or streamed over SSE.
+
+use super::output::{print_output, OutputFormat};
+use super::ui::{format_bytes, print_header, print_info, print_kv};
+use crate::client::handle_error;
+use futures::StreamExt;
+use hypercraft_core::SystemStats;
+
+/// Show system stats; with `watch`, keep printing updates from the SSE stream.
+pub async fn system_stats(
+    client: &reqwest::Client,
+    base: &str,
+    watch: bool,
+    interval: u64,
+    output: OutputFormat,
+) -> anyhow::Result<()> {
+    if !watch {
+        let resp = client.get(format!("{}/stats/system", base)).send().await?;
+        let stats: SystemStats = handle_error(resp).await?.json().await?;
+        match output {
+            OutputFormat::Json | OutputFormat::Ndjson => print_output(&stats, output)?,
+            OutputFormat::Table => {
+                print_header("📊 SYSTEM STATS");
+                print_kv("CPU", &format!("{:.1}%", stats.cpu_usage));
+                print_kv(
+                    "Memory",
+                    &usage(stats.memory_used, stats.memory_total, stats.memory_usage),
+                );
+                print_kv(
+                    "Disk",
+                    &usage(stats.disk_used, stats.disk_total, stats.disk_usage),
+                );
+                println!();
+            }
+        }
+        return Ok(());
+    }
+
+    let url = format!("{}/stats/system/stream?interval={}", base, interval);
+    let resp = handle_error(client.get(url).send().await?).await?;
+    if matches!(output, OutputFormat::Table) {
+        print_header("📊 SYSTEM STATS (live)");
+        print_info("Press Ctrl+C to stop.");
+        println!();
+    }
+
+    // SSE 事件可能跨 chunk，按行缓冲
+    let mut stream = resp.bytes_stream();
+    let mut buf = String::new();
+    while let Some(chunk) = stream.next().await {
+        buf.push_str(&String::from_utf8_lossy(&chunk?));
+        while let Some(pos) = buf.find('\n') {
+            let line: String = buf.drain(..=pos).collect();
+            let Some(data) = line.trim_end().strip_prefix("data:") else {
+                continue;
+            };
+            let Ok(stats) = serde_json::from_str::<SystemStats>(data.trim()) else {
+                continue;
+            };
+            match output {
+                OutputFormat::Json | OutputFormat::Ndjson => {
+                    println!("{}", serde_json::to_string(&stats)?)
+                }
+                OutputFormat::Table => println!(
+                    "  CPU {:>5.1}%  MEM {}  DISK {}",
+                    stats.cpu_usage,
+                    usage(stats.memory_used, stats.memory_total, stats.memory_usage),
+                    usage(stats.disk_used, stats.disk_total, stats.disk_usage),
+                ),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn usage(used: u64, total: u64, percent: f32) -> String {
+    format!(
+        "{} / {} ({:.1}%)",
+        format_bytes(used),
+        format_bytes(total),
+        percent
+    )
+}
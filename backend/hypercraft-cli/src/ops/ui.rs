@@ -16,27 +16,38 @@ fn display_width(s: &str) -> usize {
         .sum()
 }
 
-/// Print a section header with box drawing characters.
-pub fn print_header(title: &str) {
-    let inner_width: usize = 58; // Fixed inner width
+/// Inner width of a box: `preferred`, shrunk to fit the terminal (borders included).
+pub fn box_inner_width(preferred: usize) -> usize {
+    match crossterm::terminal::size() {
+        Ok((cols, _)) => preferred.min((cols as usize).saturating_sub(2)).max(20),
+        Err(_) => preferred,
+    }
+}
+
+/// Top, title and bottom lines of a box with `title` centered inside.
+pub fn boxed_title(title: &str, inner_width: usize) -> [String; 3] {
     let title_width = display_width(title);
     let total_padding = inner_width.saturating_sub(title_width);
     let left_pad = total_padding / 2;
     let right_pad = total_padding - left_pad;
-
-    println!();
-    println!("{}", format!("╔{}╗", "═".repeat(inner_width)).dark_cyan());
-    println!(
-        "{}",
+    [
+        format!("╔{}╗", "═".repeat(inner_width)),
         format!(
             "║{}{}{}║",
             " ".repeat(left_pad),
             title,
             " ".repeat(right_pad)
-        )
-        .dark_cyan()
-    );
-    println!("{}", format!("╚{}╝", "═".repeat(inner_width)).dark_cyan());
+        ),
+        format!("╚{}╝", "═".repeat(inner_width)),
+    ]
+}
+
+/// Print a section header with box drawing characters.
+pub fn print_header(title: &str) {
+    println!();
+    for line in boxed_title(title, box_inner_width(58)) {
+        println!("{}", line.dark_cyan());
+    }
     println!();
 }
 
@@ -131,6 +142,22 @@ pub fn format_uptime(ms: u64) -> String {
     }
 }
 
+/// Format a byte count with binary units (KiB, MiB, ...).
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
 /// Format state with color.
 pub fn format_state(state: &str) -> String {
     match state.to_lowercase().as_str() {
@@ -1,11 +1,15 @@
 //! 用户管理 CLI 操作
 
-use super::ui::{print_error, print_header, print_kv, print_section, print_success};
-use super::OutputFormat;
+use super::output::{print_output, print_output_list, OutputFormat};
+use super::ui::{print_error, print_header, print_hint, print_kv, print_section, print_success};
+use crate::client::handle_error;
 use crossterm::style::Stylize;
+use dialoguer::{theme::ColorfulTheme, Input};
+use hypercraft_core::Setup2FAResponse;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::io::IsTerminal;
 
 /// 用户摘要（从 API 返回）
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,9 +17,31 @@ pub struct UserSummary {
     pub id: String,
     pub username: String,
     pub service_ids: Vec<String>,
+    /// 各服务的权限级别（旧版服务端不返回）
+    #[serde(default)]
+    pub service_permissions: Vec<ServicePermission>,
     pub created_at: Option<String>,
 }
 
+/// 服务权限（read / control）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServicePermission {
+    pub id: String,
+    pub level: String,
+}
+
+/// 登录会话摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub id: String,
+    pub issued_at: String,
+    pub last_used_at: String,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    #[serde(default)]
+    pub current: bool,
+}
+
 /// 认证响应
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthToken {
@@ -26,34 +52,55 @@ pub struct AuthToken {
 }
 
 /// 用户登录
+///
+/// 账号启用了 2FA 而未提供验证码时，终端下会提示输入后重试
 pub async fn login(
     client: &Client,
     base: &str,
     username: &str,
     password: &str,
+    totp_code: Option<&str>,
     output: OutputFormat,
 ) -> anyhow::Result<AuthToken> {
     let url = format!("{}/auth/login", base);
-    let resp = client
-        .post(&url)
-        .json(&json!({
-            "username": username,
-            "password": password
-        }))
-        .send()
-        .await?;
+    let mut totp_code = totp_code.map(str::to_string);
+    let resp = loop {
+        let resp = client
+            .post(&url)
+            .json(&json!({
+                "username": username,
+                "password": password,
+                "totp_code": totp_code,
+            }))
+            .send()
+            .await?;
+
+        if resp.status().is_success() {
+            break resp;
+        }
 
-    if !resp.status().is_success() {
         let status = resp.status();
         let body = resp.text().await.unwrap_or_default();
+        let two_factor_required = serde_json::from_str::<serde_json::Value>(&body)
+            .is_ok_and(|v| v.get("code").and_then(|c| c.as_str()) == Some("2FA_REQUIRED"));
+        if two_factor_required && totp_code.is_none() {
+            if !std::io::stdin().is_terminal() {
+                anyhow::bail!("该账号已启用双因素认证，请通过 --totp-code 提供验证码");
+            }
+            let code: String = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt("双因素认证码（或恢复码）")
+                .interact_text()?;
+            totp_code = Some(code.trim().to_string());
+            continue;
+        }
         anyhow::bail!("login failed ({}): {}", status, body);
-    }
+    };
 
     let token: AuthToken = resp.json().await?;
 
     match output {
-        OutputFormat::Json => {
-            println!("{}", serde_json::to_string_pretty(&token)?);
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            print_output(&token, output)?;
         }
         OutputFormat::Table => {
             print_header("🔐 登录成功");
@@ -72,12 +119,11 @@ pub async fn login(
     Ok(token)
 }
 
-/// 刷新 token
-pub async fn refresh_token(
+/// 用 refresh token 换取新 token（不输出）
+pub async fn request_refresh(
     client: &Client,
     base: &str,
     refresh_token: &str,
-    output: OutputFormat,
 ) -> anyhow::Result<AuthToken> {
     let url = format!("{}/auth/refresh", base);
     let resp = client
@@ -94,11 +140,21 @@ pub async fn refresh_token(
         anyhow::bail!("refresh failed ({}): {}", status, body);
     }
 
-    let token: AuthToken = resp.json().await?;
+    Ok(resp.json().await?)
+}
+
+/// 刷新 token
+pub async fn refresh_token(
+    client: &Client,
+    base: &str,
+    refresh_token: &str,
+    output: OutputFormat,
+) -> anyhow::Result<AuthToken> {
+    let token = request_refresh(client, base, refresh_token).await?;
 
     match output {
-        OutputFormat::Json => {
-            println!("{}", serde_json::to_string_pretty(&token)?);
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            print_output(&token, output)?;
         }
         OutputFormat::Table => {
             print_success("Token 刷新成功");
@@ -124,8 +180,8 @@ pub async fn list_users(client: &Client, base: &str, output: OutputFormat) -> an
     let users: Vec<UserSummary> = resp.json().await?;
 
     match output {
-        OutputFormat::Json => {
-            println!("{}", serde_json::to_string_pretty(&users)?);
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            print_output_list(&users, output)?;
         }
         OutputFormat::Table => {
             print_header("👥 用户列表");
@@ -190,8 +246,8 @@ pub async fn get_user(
     let user: UserSummary = resp.json().await?;
 
     match output {
-        OutputFormat::Json => {
-            println!("{}", serde_json::to_string_pretty(&user)?);
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            print_output(&user, output)?;
         }
         OutputFormat::Table => {
             print_header(&format!("👤 用户: {}", user.username));
@@ -205,7 +261,17 @@ pub async fn get_user(
                 println!("  {}", "无".dark_grey());
             } else {
                 for sid in &user.service_ids {
-                    println!("  • {}", sid.as_str().cyan());
+                    let level = user
+                        .service_permissions
+                        .iter()
+                        .find(|p| &p.id == sid)
+                        .map(|p| p.level.as_str())
+                        .unwrap_or("control");
+                    println!(
+                        "  • {} {}",
+                        sid.as_str().cyan(),
+                        format!("({})", level).dark_grey()
+                    );
                 }
             }
             println!();
@@ -245,8 +311,8 @@ pub async fn create_user(
     let user: UserSummary = resp.json().await?;
 
     match output {
-        OutputFormat::Json => {
-            println!("{}", serde_json::to_string_pretty(&user)?);
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            print_output(&user, output)?;
         }
         OutputFormat::Table => {
             print_success(&format!("用户 {} 创建成功", user.username));
@@ -273,6 +339,178 @@ pub async fn delete_user(client: &Client, base: &str, id: &str) -> anyhow::Resul
     Ok(())
 }
 
+/// 列出用户的登录会话
+pub async fn list_user_sessions(
+    client: &Client,
+    base: &str,
+    id: &str,
+    output: OutputFormat,
+) -> anyhow::Result<()> {
+    let url = format!("{}/users/{}/sessions", base, id);
+    let resp = client.get(&url).send().await?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        print_error(&format!("获取会话列表失败 ({}): {}", status, body));
+        return Ok(());
+    }
+
+    let sessions: Vec<SessionSummary> = resp.json().await?;
+
+    match output {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            print_output_list(&sessions, output)?;
+        }
+        OutputFormat::Table => {
+            print_header("🔑 登录会话");
+            println!();
+
+            if sessions.is_empty() {
+                println!("  {}", "暂无会话".dark_grey());
+            } else {
+                println!(
+                    "  {:<16}  {:<19}  {:<19}  {:<15}  {}",
+                    "ID".bold(),
+                    "登录时间".bold(),
+                    "最近使用".bold(),
+                    "IP".bold(),
+                    "User-Agent".bold()
+                );
+                println!("  {}", "─".repeat(100).dark_grey());
+
+                for session in sessions {
+                    let id = if session.current {
+                        format!("{}*", session.id).green().to_string()
+                    } else {
+                        session.id.clone().dark_grey().to_string()
+                    };
+                    println!(
+                        "  {:<16}  {:<19}  {:<19}  {:<15}  {}",
+                        id,
+                        session.issued_at.chars().take(19).collect::<String>(),
+                        session.last_used_at.chars().take(19).collect::<String>(),
+                        session.ip.as_deref().unwrap_or("-"),
+                        session.user_agent.as_deref().unwrap_or("-")
+                    );
+                }
+            }
+            println!();
+        }
+    }
+
+    Ok(())
+}
+
+/// 注销用户的单个登录会话
+pub async fn revoke_user_session(
+    client: &Client,
+    base: &str,
+    id: &str,
+    session_id: &str,
+) -> anyhow::Result<()> {
+    let url = format!("{}/users/{}/sessions/{}", base, id, session_id);
+    let resp = client.delete(&url).send().await?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        print_error(&format!("注销会话失败 ({}): {}", status, body));
+        return Ok(());
+    }
+
+    print_success(&format!("会话 {} 已注销", session_id));
+    Ok(())
+}
+
+/// 撤销用户的全部 token（所有设备立即登出）
+pub async fn revoke_user_tokens(client: &Client, base: &str, id: &str) -> anyhow::Result<()> {
+    let url = format!("{}/users/{}/revoke-tokens", base, id);
+    let resp = client.post(&url).send().await?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        anyhow::bail!("撤销 token 失败 ({}): {}", status, body);
+    }
+
+    print_success(&format!("用户 {} 的全部 token 已撤销", id));
+    Ok(())
+}
+
+/// 隐藏输入新密码并确认一次
+pub fn prompt_new_password() -> anyhow::Result<String> {
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("未提供密码且标准输入不是终端，无法提示输入");
+    }
+    let password = rpassword::prompt_password("  新密码: ")?;
+    if password.is_empty() {
+        anyhow::bail!("密码不能为空");
+    }
+    if rpassword::prompt_password("  确认密码: ")? != password {
+        anyhow::bail!("两次输入的密码不一致");
+    }
+    Ok(password)
+}
+
+/// 为当前登录用户启用 2FA：生成 secret，输入验证码确认后启用
+pub async fn setup_two_factor(
+    client: &Client,
+    base: &str,
+    output: OutputFormat,
+) -> anyhow::Result<()> {
+    let resp = client
+        .post(format!("{}/auth/2fa/setup", base))
+        .json(&json!({}))
+        .send()
+        .await?;
+    let setup: Setup2FAResponse = handle_error(resp).await?.json().await?;
+
+    // 交互流程，secret 始终显示，--output 只影响最终结果
+    print_header("🔐 启用双因素认证");
+    println!();
+    print_kv("Secret", &setup.secret);
+    print_kv("URI", &setup.qr_uri);
+    println!();
+    print_hint("在认证器 App 中添加上面的 secret 或 URI，然后输入生成的 6 位验证码");
+    let code: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("验证码")
+        .interact_text()?;
+
+    let resp = client
+        .post(format!("{}/auth/2fa/enable", base))
+        .json(&json!({
+            "totp_code": code.trim(),
+            "secret": setup.secret,
+            "recovery_codes": setup.recovery_codes,
+        }))
+        .send()
+        .await?;
+    handle_error(resp).await?;
+
+    match output {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            print_output(
+                json!({
+                    "enabled": true,
+                    "recovery_codes": setup.recovery_codes,
+                }),
+                output,
+            )?;
+        }
+        OutputFormat::Table => {
+            print_success("双因素认证已启用");
+            println!();
+            print_section("恢复码（仅显示一次，请妥善保存）");
+            for code in &setup.recovery_codes {
+                println!("    {}", code.as_str().cyan());
+            }
+            println!();
+        }
+    }
+    Ok(())
+}
+
 /// 更新用户密码
 pub async fn update_user_password(
     client: &Client,
@@ -300,8 +538,8 @@ pub async fn update_user_password(
     let user: UserSummary = resp.json().await?;
 
     match output {
-        OutputFormat::Json => {
-            println!("{}", serde_json::to_string_pretty(&user)?);
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            print_output(&user, output)?;
         }
         OutputFormat::Table => {
             print_success(&format!("用户 {} 密码已更新", user.username));
@@ -338,8 +576,8 @@ pub async fn set_user_services(
     let user: UserSummary = resp.json().await?;
 
     match output {
-        OutputFormat::Json => {
-            println!("{}", serde_json::to_string_pretty(&user)?);
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            print_output(&user, output)?;
         }
         OutputFormat::Table => {
             print_success(&format!("用户 {} 的服务权限已更新", user.username));
@@ -363,10 +601,11 @@ pub async fn add_user_service(
     base: &str,
     user_id: &str,
     service_id: &str,
+    level: &str,
     output: OutputFormat,
 ) -> anyhow::Result<()> {
     let url = format!("{}/users/{}/services/{}", base, user_id, service_id);
-    let resp = client.post(&url).send().await?;
+    let resp = client.post(&url).query(&[("level", level)]).send().await?;
 
     if !resp.status().is_success() {
         let status = resp.status();
@@ -378,13 +617,13 @@ pub async fn add_user_service(
     let user: UserSummary = resp.json().await?;
 
     match output {
-        OutputFormat::Json => {
-            println!("{}", serde_json::to_string_pretty(&user)?);
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            print_output(&user, output)?;
         }
         OutputFormat::Table => {
             print_success(&format!(
-                "已为用户 {} 添加服务 {} 的访问权限",
-                user.username, service_id
+                "已为用户 {} 添加服务 {} 的 {} 权限",
+                user.username, service_id, level
             ));
         }
     }
@@ -413,8 +652,8 @@ pub async fn remove_user_service(
     let user: UserSummary = resp.json().await?;
 
     match output {
-        OutputFormat::Json => {
-            println!("{}", serde_json::to_string_pretty(&user)?);
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            print_output(&user, output)?;
         }
         OutputFormat::Table => {
             print_success(&format!(
@@ -0,0 +1,304 @@
+//! Full-screen live dashboard of all services, refreshed on an interval.
+
+use super::ui::{format_bytes, format_uptime};
+use crate::client::handle_error;
+use chrono::Utc;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::Stylize;
+use crossterm::{cursor, execute, queue, terminal};
+use hypercraft_core::{ProcessStats, ServiceState, ServiceSummary};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// 标题、空行、表头与底部提示/消息占用的行数（另留一行余量）
+const CHROME_LINES: usize = 6;
+
+/// Column widths: id, name, state, pid, uptime, cpu, memory.
+const COLUMNS: [(&str, usize); 7] = [
+    ("ID", 20),
+    ("NAME", 20),
+    ("STATE", 9),
+    ("PID", 8),
+    ("UPTIME", 12),
+    ("CPU", 7),
+    ("MEM", 10),
+];
+
+enum WatchKey {
+    Up,
+    Down,
+    Action(&'static str),
+    Quit,
+}
+
+/// One dashboard row: the service summary joined with its process stats.
+struct WatchRow {
+    id: String,
+    name: String,
+    state: ServiceState,
+    stats: Option<ProcessStats>,
+    uptime_ms: Option<u64>,
+}
+
+/// Run the dashboard until `q`, Esc or Ctrl+C.
+///
+/// Arrow keys (or j/k) move the selection; `s`, `x` and `r` start, stop and
+/// restart the selected service.
+pub async fn watch_services(
+    client: &reqwest::Client,
+    base: &str,
+    interval: u64,
+) -> anyhow::Result<()> {
+    let (key_tx, mut key_rx) = mpsc::channel(16);
+    let stop = Arc::new(AtomicBool::new(false));
+    let stop_for_thread = stop.clone();
+    let input = tokio::task::spawn_blocking(move || read_keys(key_tx, stop_for_thread));
+
+    terminal::enable_raw_mode()?;
+    execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+    let _guard = scopeguard::guard((), |_| {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    });
+
+    let mut ticker = tokio::time::interval(Duration::from_secs(interval.max(1)));
+    let mut rows = Vec::new();
+    let mut selected = 0usize;
+    let mut message = String::new();
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => match fetch_rows(client, base).await {
+                Ok(fresh) => rows = fresh,
+                Err(e) => message = format!("refresh failed: {}", e),
+            },
+            key = key_rx.recv() => match key {
+                None | Some(WatchKey::Quit) => break,
+                Some(WatchKey::Up) => selected = selected.saturating_sub(1),
+                Some(WatchKey::Down) => selected += 1,
+                Some(WatchKey::Action(action)) => {
+                    if let Some(row) = rows.get(selected) {
+                        message = run_action(client, base, &row.id, action).await;
+                        ticker.reset_immediately();
+                    }
+                }
+            },
+        }
+        selected = selected.min(rows.len().saturating_sub(1));
+        render(&rows, selected, &message)?;
+    }
+
+    stop.store(true, Ordering::Relaxed);
+    let _ = input.await;
+    Ok(())
+}
+
+/// 读取按键并转换为看板操作；阻塞线程中运行，`stop` 置位后退出
+fn read_keys(tx: mpsc::Sender<WatchKey>, stop: Arc<AtomicBool>) -> anyhow::Result<()> {
+    while !stop.load(Ordering::Relaxed) {
+        if !event::poll(Duration::from_millis(50))? {
+            continue;
+        }
+        let Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind,
+            ..
+        }) = event::read()?
+        else {
+            continue;
+        };
+        // 只处理按下事件，忽略释放事件（Windows 上会触发两次）
+        if kind != KeyEventKind::Press {
+            continue;
+        }
+        let key = match code {
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => WatchKey::Quit,
+            KeyCode::Char('q') | KeyCode::Esc => WatchKey::Quit,
+            KeyCode::Up | KeyCode::Char('k') => WatchKey::Up,
+            KeyCode::Down | KeyCode::Char('j') => WatchKey::Down,
+            KeyCode::Char('s') => WatchKey::Action("start"),
+            KeyCode::Char('x') => WatchKey::Action("stop"),
+            KeyCode::Char('r') => WatchKey::Action("restart"),
+            _ => continue,
+        };
+        if tx.blocking_send(key).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Fetch all services and the process stats of the running ones.
+async fn fetch_rows(client: &reqwest::Client, base: &str) -> anyhow::Result<Vec<WatchRow>> {
+    let resp = client.get(format!("{}/services", base)).send().await?;
+    let services: Vec<ServiceSummary> = handle_error(resp).await?.json().await?;
+    let resp = client
+        .get(format!("{}/stats/processes", base))
+        .send()
+        .await?;
+    let mut stats: BTreeMap<String, ProcessStats> = handle_error(resp).await?.json().await?;
+
+    let now = Utc::now();
+    Ok(services
+        .into_iter()
+        .map(|svc| {
+            let stats = stats.remove(&svc.id);
+            let uptime_ms = match &stats {
+                Some(stats) => Some(stats.uptime_secs * 1000),
+                None => svc
+                    .started_at
+                    .and_then(|at| u64::try_from((now - at).num_milliseconds()).ok()),
+            };
+            WatchRow {
+                id: svc.id,
+                name: svc.name,
+                state: svc.state,
+                stats,
+                uptime_ms,
+            }
+        })
+        .collect())
+}
+
+async fn run_action(client: &reqwest::Client, base: &str, id: &str, action: &str) -> String {
+    let url = format!("{}/services/{}/{}", base, id, action);
+    let result = match client.post(url).send().await {
+        Ok(resp) => handle_error(resp).await.map(|_| ()),
+        Err(e) => Err(e.into()),
+    };
+    match result {
+        Ok(()) => format!("{} {}: ok", action, id),
+        Err(e) => format!("{} {} failed: {}", action, id, e),
+    }
+}
+
+fn render(rows: &[WatchRow], selected: usize, message: &str) -> anyhow::Result<()> {
+    let (cols, lines) = terminal::size().unwrap_or((80, 24));
+    let cols = cols as usize;
+    let visible = (lines as usize).saturating_sub(CHROME_LINES).max(1);
+    let offset = selected.saturating_sub(visible - 1);
+
+    let mut out = io::stdout();
+    queue!(
+        out,
+        cursor::MoveTo(0, 0),
+        terminal::Clear(terminal::ClearType::All)
+    )?;
+    let running = rows
+        .iter()
+        .filter(|r| r.state == ServiceState::Running)
+        .count();
+    let title = format!(
+        " HYPERCRAFT WATCH  {} services, {} running  {}",
+        rows.len(),
+        running,
+        Utc::now().format("%H:%M:%S")
+    );
+    write!(out, "{}\r\n\r\n", fit(&title, cols).dark_cyan().bold())?;
+
+    let header: Vec<String> = COLUMNS.iter().map(|(name, _)| name.to_string()).collect();
+    write!(
+        out,
+        "{}\r\n",
+        fit(&format_cells(&header), cols).white().bold()
+    )?;
+
+    for (index, row) in rows.iter().enumerate().skip(offset).take(visible) {
+        let line = fit(&format_cells(&row_cells(row)), cols);
+        let line = if index == selected {
+            line.reverse().to_string()
+        } else if row.state == ServiceState::Running {
+            line.green().to_string()
+        } else {
+            line.dark_grey().to_string()
+        };
+        write!(out, "{}\r\n", line)?;
+    }
+    if rows.is_empty() {
+        write!(out, "{}\r\n", " No services found.".dark_grey().italic())?;
+    }
+
+    let help = " ↑/↓ select  s start  x stop  r restart  q quit";
+    queue!(out, cursor::MoveTo(0, lines.saturating_sub(2)))?;
+    write!(out, "{}\r\n", fit(help, cols).dark_grey())?;
+    write!(out, "{}", fit(&format!(" {}", message), cols).yellow())?;
+    out.flush()?;
+    Ok(())
+}
+
+fn row_cells(row: &WatchRow) -> Vec<String> {
+    let dash = || "-".to_string();
+    let stats = row.stats.as_ref();
+    vec![
+        row.id.clone(),
+        row.name.clone(),
+        format!("{:?}", row.state),
+        stats.map(|s| s.pid.to_string()).unwrap_or_else(dash),
+        row.uptime_ms.map(format_uptime).unwrap_or_else(dash),
+        stats
+            .map(|s| format!("{:.1}%", s.cpu_usage))
+            .unwrap_or_else(dash),
+        stats.map(|s| format_bytes(s.memory)).unwrap_or_else(dash),
+    ]
+}
+
+/// Lay out cells in the fixed column widths, truncating overlong values.
+fn format_cells(cells: &[String]) -> String {
+    let line: Vec<String> = cells
+        .iter()
+        .zip(COLUMNS)
+        .map(|(cell, (_, width))| format!("{:<width$}", fit(cell, width), width = width))
+        .collect();
+    format!(" {}", line.join(" "))
+}
+
+/// Truncate `s` to at most `width` characters, marking the cut with `…`.
+fn fit(s: &str, width: usize) -> String {
+    if s.chars().count() <= width {
+        return s.to_string();
+    }
+    let mut cut: String = s.chars().take(width.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_fill_placeholders_and_truncate() {
+        let row = WatchRow {
+            id: "a-very-long-service-identifier".into(),
+            name: "web".into(),
+            state: ServiceState::Stopped,
+            stats: None,
+            uptime_ms: None,
+        };
+        let line = format_cells(&row_cells(&row));
+        assert!(line.starts_with(" a-very-long-service…"));
+        assert!(line.contains("Stopped"));
+        assert!(line.trim_end().ends_with('-'));
+
+        let running = WatchRow {
+            state: ServiceState::Running,
+            stats: Some(ProcessStats {
+                pid: 42,
+                cpu_usage: 12.5,
+                memory: 2048,
+                ..Default::default()
+            }),
+            uptime_ms: Some(61_000),
+            ..row
+        };
+        let line = format_cells(&row_cells(&running));
+        assert!(line.contains("42"));
+        assert!(line.contains("1m 1s"));
+        assert!(line.contains("12.5%"));
+        assert!(line.contains("2.0 KiB"));
+    }
+}
@@ -0,0 +1,230 @@
+//! CLI 配置文件：`~/.config/hypercraft/config.toml` 中的命名服务端 profile。
+//!
+//! ```toml
+//! current = "prod"
+//!
+//! [profile.prod]
+//! api_base = "https://hc.example.com"
+//! token = "..."
+//! ```
+
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// 既没有 profile 也没有 `--api-base` / `HC_API_BASE` 时使用的地址
+pub const DEFAULT_API_BASE: &str = "http://127.0.0.1:8080";
+
+/// 单个服务端 profile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    pub api_base: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+/// 配置文件内容
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct CliConfig {
+    /// `hc profile use` 选中的默认 profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current: Option<String>,
+    #[serde(default)]
+    pub profile: BTreeMap<String, Profile>,
+}
+
+/// 配置文件路径
+pub fn config_path() -> anyhow::Result<PathBuf> {
+    let home = dirs_next::home_dir().ok_or_else(|| anyhow!("无法确定用户主目录"))?;
+    Ok(home.join(".config").join("hypercraft").join("config.toml"))
+}
+
+impl CliConfig {
+    /// 读取配置，文件不存在时返回空配置。
+    ///
+    /// 文件里可能有 token，其他用户可读时打印警告。
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e).with_context(|| format!("读取 {} 失败", path.display())),
+        };
+        warn_if_world_readable(path);
+        toml::from_str(&content).with_context(|| format!("解析 {} 失败", path.display()))
+    }
+
+    /// 写回配置，文件权限固定为 0600
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = toml::to_string_pretty(self)?;
+        write_private(path, content.as_bytes())
+            .with_context(|| format!("写入 {} 失败", path.display()))
+    }
+
+    /// 选中的 profile：`--profile` / `HC_PROFILE` 优先，其次是 `current`
+    pub fn select(&self, name: Option<&str>) -> anyhow::Result<Option<&Profile>> {
+        match name.or(self.current.as_deref()) {
+            Some(name) => self
+                .profile
+                .get(name)
+                .map(Some)
+                .ok_or_else(|| anyhow!("profile 不存在: {name}（可用 hc profile list 查看）")),
+            None => Ok(None),
+        }
+    }
+}
+
+/// 全局参数的原始值，以及是否由命令行显式给出（而非环境变量或默认值）
+pub struct ConnectionArgs {
+    pub api_base: String,
+    pub api_base_explicit: bool,
+    pub token: Option<String>,
+    pub token_explicit: bool,
+}
+
+impl ConnectionArgs {
+    /// 合并出最终的 api_base 与 token。
+    ///
+    /// 优先级：命令行参数 > 选中的 profile > 环境变量 > 默认值；
+    /// 未选中 profile 时与原先的参数/环境变量行为一致。
+    /// 选中 profile 后 token 只取 profile 自身或显式的 `--token`，
+    /// 不会把 `HC_DEV_TOKEN` 发往 profile 指向的服务器。
+    pub fn resolve(self, profile: Option<&Profile>) -> (String, Option<String>) {
+        let Some(profile) = profile else {
+            return (self.api_base, self.token);
+        };
+        let api_base = if self.api_base_explicit {
+            self.api_base
+        } else {
+            profile.api_base.clone()
+        };
+        let token = if self.token_explicit {
+            self.token
+        } else {
+            profile.token.clone()
+        };
+        (api_base, token)
+    }
+}
+
+#[cfg(unix)]
+pub fn warn_if_world_readable(path: &Path) {
+    use crossterm::style::Stylize;
+    use std::os::unix::fs::PermissionsExt;
+    if let Ok(meta) = fs::metadata(path) {
+        if meta.permissions().mode() & 0o004 != 0 {
+            // 走 stderr，避免污染 --output json / export 的标准输出
+            eprintln!(
+                "  {} {} 对所有用户可读，其中的 token 可能泄露；请执行 chmod 600 {}",
+                "⚠".yellow(),
+                path.display(),
+                path.display()
+            );
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub fn warn_if_world_readable(_path: &Path) {}
+
+/// 以 0600 权限写入；已存在的文件也会收紧权限
+#[cfg(unix)]
+pub fn write_private(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(data)
+}
+
+#[cfg(not(unix))]
+pub fn write_private(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    fs::write(path, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(api_base_explicit: bool, token: Option<&str>, token_explicit: bool) -> ConnectionArgs {
+        ConnectionArgs {
+            api_base: "http://from-cli".into(),
+            api_base_explicit,
+            token: token.map(str::to_string),
+            token_explicit,
+        }
+    }
+
+    #[test]
+    fn profile_fills_in_unless_flag_is_explicit() {
+        let prod = Profile {
+            api_base: "https://prod".into(),
+            token: Some("prod-token".into()),
+        };
+
+        // 无 profile：保持原行为
+        let (base, token) = args(false, Some("env-token"), false).resolve(None);
+        assert_eq!(base, "http://from-cli");
+        assert_eq!(token.as_deref(), Some("env-token"));
+
+        // profile 覆盖环境变量/默认值
+        let (base, token) = args(false, Some("env-token"), false).resolve(Some(&prod));
+        assert_eq!(base, "https://prod");
+        assert_eq!(token.as_deref(), Some("prod-token"));
+
+        // 命令行显式参数优先于 profile
+        let (base, token) = args(true, Some("flag-token"), true).resolve(Some(&prod));
+        assert_eq!(base, "http://from-cli");
+        assert_eq!(token.as_deref(), Some("flag-token"));
+
+        // profile 未保存 token 时不回退到环境变量
+        let anonymous = Profile {
+            api_base: "https://prod".into(),
+            token: None,
+        };
+        let (_, token) = args(false, Some("env-token"), false).resolve(Some(&anonymous));
+        assert_eq!(token, None);
+    }
+
+    #[test]
+    fn save_and_load_round_trip_with_private_permissions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hypercraft").join("config.toml");
+        assert!(CliConfig::load(&path).unwrap().profile.is_empty());
+
+        let mut config = CliConfig::default();
+        config.profile.insert(
+            "prod".into(),
+            Profile {
+                api_base: "https://prod".into(),
+                token: Some("t".into()),
+            },
+        );
+        config.current = Some("prod".into());
+        config.save(&path).unwrap();
+
+        let loaded = CliConfig::load(&path).unwrap();
+        assert_eq!(
+            loaded.select(None).unwrap().unwrap().api_base,
+            "https://prod"
+        );
+        assert!(loaded.select(Some("staging")).is_err());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+}
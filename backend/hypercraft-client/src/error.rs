@@ -0,0 +1,168 @@
+//! Client errors, including the structured error body returned by the API.
+
+use reqwest::header::RETRY_AFTER;
+use serde::Deserialize;
+use serde_json::Value;
+use std::fmt;
+
+pub type Result<T> = std::result::Result<T, ClientError>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    /// Transport failure or an undecodable response body.
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    /// The server answered with a non-2xx status.
+    #[error(transparent)]
+    Api(Box<ApiError>),
+    /// A token or header value could not be encoded into a request header.
+    #[error("invalid header value: {0}")]
+    InvalidHeader(String),
+}
+
+impl From<ApiError> for ClientError {
+    fn from(err: ApiError) -> Self {
+        Self::Api(Box::new(err))
+    }
+}
+
+impl ClientError {
+    /// The API error, if the server rejected the request.
+    pub fn api(&self) -> Option<&ApiError> {
+        match self {
+            Self::Api(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// True when the server returned 404.
+    pub fn is_not_found(&self) -> bool {
+        self.api().is_some_and(|err| err.status == 404)
+    }
+}
+
+/// A non-2xx response, decoded from the API's `{"code", "message", ...}` body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiError {
+    pub status: u16,
+    /// Machine-readable error code such as `NotFound`, `Forbidden` or `2FA_REQUIRED`;
+    /// `Unknown` when the body was not the usual JSON error.
+    pub code: String,
+    pub message: String,
+    /// Extra structured information (e.g. allowed commands on a policy violation).
+    pub details: Option<Value>,
+    /// Server-side request id, useful when reporting a problem.
+    pub request_id: Option<String>,
+    /// Seconds to wait before retrying, from `Retry-After` on 429 responses.
+    pub retry_after: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    code: String,
+    message: String,
+    #[serde(default)]
+    details: Option<Value>,
+    #[serde(default)]
+    request_id: Option<String>,
+}
+
+impl ApiError {
+    /// Build the error from a failed response, consuming its body.
+    pub async fn from_response(resp: reqwest::Response) -> Self {
+        let status = resp.status().as_u16();
+        let retry_after = resp
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse().ok());
+        let text = resp.text().await.unwrap_or_default();
+        Self::from_body(status, &text, retry_after)
+    }
+
+    fn from_body(status: u16, text: &str, retry_after: Option<u64>) -> Self {
+        match serde_json::from_str::<ErrorBody>(text) {
+            Ok(body) => Self {
+                status,
+                code: body.code,
+                message: body.message,
+                details: body.details,
+                request_id: body.request_id,
+                retry_after,
+            },
+            Err(_) => Self {
+                status,
+                code: "Unknown".into(),
+                message: if text.trim().is_empty() {
+                    "unknown error".into()
+                } else {
+                    text.trim().to_string()
+                },
+                details: None,
+                request_id: None,
+                retry_after,
+            },
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "request failed ({} {}): {}",
+            self.status, self.code, self.message
+        )?;
+        if let Some(secs) = self.retry_after {
+            write!(f, " (retry after {}s)", secs)?;
+        }
+        if let Some(request_id) = &self.request_id {
+            write!(f, " [request id {}]", request_id)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// Pass successful responses through and turn failures into [`ClientError::Api`].
+pub async fn error_for_status(resp: reqwest::Response) -> Result<reqwest::Response> {
+    if resp.status().is_success() {
+        Ok(resp)
+    } else {
+        Err(ApiError::from_response(resp).await.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_api_error_body() {
+        let err = ApiError::from_body(
+            409,
+            r#"{"code":"AlreadyRunning","message":"service mc already running","request_id":"r-1"}"#,
+            None,
+        );
+        assert_eq!(err.code, "AlreadyRunning");
+        assert_eq!(err.message, "service mc already running");
+        assert_eq!(err.request_id.as_deref(), Some("r-1"));
+        assert_eq!(
+            err.to_string(),
+            "request failed (409 AlreadyRunning): service mc already running [request id r-1]"
+        );
+    }
+
+    #[test]
+    fn falls_back_to_raw_body() {
+        let err = ApiError::from_body(502, "bad gateway\n", Some(3));
+        assert_eq!(err.code, "Unknown");
+        assert_eq!(err.message, "bad gateway");
+        assert_eq!(
+            err.to_string(),
+            "request failed (502 Unknown): bad gateway (retry after 3s)"
+        );
+        assert_eq!(ApiError::from_body(500, "", None).message, "unknown error");
+    }
+}
@@ -0,0 +1,386 @@
+//! Typed client for the Hypercraft HTTP API.
+//!
+//! Wraps `reqwest` with one method per endpoint, request/response types shared with the
+//! server (`hypercraft-core`), and [`ClientError`] decoded from the API's JSON error bodies.
+//!
+//! ```no_run
+//! # async fn demo() -> hypercraft_client::Result<()> {
+//! use hypercraft_client::Client;
+//!
+//! let client = Client::new("http://127.0.0.1:8080")?.with_token("hc_xxx")?;
+//! for svc in client.list_services(&Default::default()).await?.services {
+//!     println!("{} {:?}", svc.id, svc.state);
+//! }
+//! client.restart("mc").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod error;
+
+pub use error::{error_for_status, ApiError, ClientError, Result};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use hypercraft_core::{
+    ArchivedService, AuthToken, LoginRequest, RefreshRequest, Schedule, ScheduleJobInfo,
+    ServiceListQuery, ServiceManifest, ServiceStatus, ServiceSummary, UserSummary,
+};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// Response header carrying the unpaginated match count of `GET /services`.
+const TOTAL_COUNT_HEADER: &str = "x-total-count";
+
+/// A page of `GET /services`.
+#[derive(Debug, Clone)]
+pub struct ServicePage {
+    pub services: Vec<ServiceSummary>,
+    /// Number of services matching the filters before `limit`/`offset` were applied.
+    pub total: Option<usize>,
+}
+
+/// `GET /services/:id`: the stored manifest together with the live status.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceDetail {
+    pub manifest: ServiceManifest,
+    pub status: ServiceStatus,
+}
+
+/// Result of `PUT /services/:id`.
+#[derive(Debug, Clone)]
+pub struct Applied {
+    pub manifest: ServiceManifest,
+    /// True when the service did not exist and was created.
+    pub created: bool,
+}
+
+/// Options for `DELETE /services/:id`.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct DeleteOptions {
+    /// Delete permanently (admin only) instead of archiving.
+    pub purge: bool,
+    /// With `purge`, keep the logs under `data_dir/deleted/<id>/logs`.
+    pub keep_logs: bool,
+}
+
+/// A schedule entry with its next run time, as returned by `/services/:id/schedule`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleEntry {
+    #[serde(flatten)]
+    pub schedule: Schedule,
+    pub next_run: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ScheduleList {
+    schedules: Vec<ScheduleEntry>,
+}
+
+/// `GET /schedules`: every armed schedule and whether the scheduler is paused.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleJobs {
+    #[serde(default)]
+    pub paused: bool,
+    pub schedules: Vec<ScheduleJobInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SchedulerStatus {
+    paused: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawLogs {
+    data: String,
+}
+
+/// Client for one Hypercraft server.
+#[derive(Debug, Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    headers: HeaderMap,
+}
+
+impl Client {
+    /// Client for `base_url` (e.g. `http://127.0.0.1:8080`) without credentials.
+    pub fn new(base_url: impl Into<String>) -> Result<Self> {
+        Ok(Self::from_reqwest(
+            reqwest::Client::builder().build()?,
+            base_url,
+        ))
+    }
+
+    /// Reuse an existing `reqwest::Client`, e.g. one with default headers already set.
+    pub fn from_reqwest(http: reqwest::Client, base_url: impl Into<String>) -> Self {
+        Self {
+            http,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            headers: HeaderMap::new(),
+        }
+    }
+
+    /// Authenticate with a user access token, API key or dev token (`Authorization: Bearer`).
+    pub fn with_token(self, token: &str) -> Result<Self> {
+        self.with_header(AUTHORIZATION.as_str(), &format!("Bearer {}", token))
+    }
+
+    /// Declare who is acting when sharing a dev token (`X-Admin-Identity`).
+    pub fn with_admin_identity(self, identity: &str) -> Result<Self> {
+        self.with_header("x-admin-identity", identity)
+    }
+
+    fn with_header(mut self, name: &'static str, value: &str) -> Result<Self> {
+        let value = HeaderValue::from_str(value)
+            .map_err(|_| ClientError::InvalidHeader(name.to_string()))?;
+        self.headers.insert(name, value);
+        Ok(self)
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// The underlying HTTP client, for endpoints not covered here.
+    pub fn http(&self) -> &reqwest::Client {
+        &self.http
+    }
+
+    /// Request builder for `path` (relative to the base URL) with the client's credentials.
+    pub fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.http
+            .request(method, format!("{}{}", self.base_url, path))
+            .headers(self.headers.clone())
+    }
+
+    async fn send(&self, req: RequestBuilder) -> Result<reqwest::Response> {
+        error_for_status(req.send().await?).await
+    }
+
+    async fn send_json<T: DeserializeOwned>(&self, req: RequestBuilder) -> Result<T> {
+        Ok(self.send(req).await?.json().await?)
+    }
+
+    // ==================== Auth ====================
+
+    /// `POST /auth/login`. A missing 2FA code fails with code `2FA_REQUIRED`.
+    pub async fn login(
+        &self,
+        username: &str,
+        password: &str,
+        totp_code: Option<&str>,
+    ) -> Result<AuthToken> {
+        let body = LoginRequest {
+            username: username.to_string(),
+            password: password.to_string(),
+            totp_code: totp_code.map(str::to_string),
+        };
+        self.send_json(self.request(Method::POST, "/auth/login").json(&body))
+            .await
+    }
+
+    /// `POST /auth/refresh`: exchange a refresh token for a new token pair.
+    pub async fn refresh(&self, refresh_token: &str) -> Result<AuthToken> {
+        let body = RefreshRequest {
+            refresh_token: Some(refresh_token.to_string()),
+        };
+        self.send_json(self.request(Method::POST, "/auth/refresh").json(&body))
+            .await
+    }
+
+    /// `GET /auth/me`
+    pub async fn me(&self) -> Result<UserSummary> {
+        self.send_json(self.request(Method::GET, "/auth/me")).await
+    }
+
+    // ==================== Services ====================
+
+    /// `GET /services` with filters, sorting and pagination.
+    pub async fn list_services(&self, query: &ServiceListQuery) -> Result<ServicePage> {
+        let resp = self
+            .send(self.request(Method::GET, "/services").query(query))
+            .await?;
+        let total = resp
+            .headers()
+            .get(TOTAL_COUNT_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok());
+        Ok(ServicePage {
+            services: resp.json().await?,
+            total,
+        })
+    }
+
+    /// `GET /services/:id`
+    pub async fn get_service(&self, id: &str) -> Result<ServiceDetail> {
+        self.send_json(self.request(Method::GET, &format!("/services/{}", id)))
+            .await
+    }
+
+    /// `GET /services/:id/status`
+    pub async fn status(&self, id: &str) -> Result<ServiceStatus> {
+        self.send_json(self.request(Method::GET, &format!("/services/{}/status", id)))
+            .await
+    }
+
+    /// `POST /services`
+    pub async fn create_service(&self, manifest: &ServiceManifest) -> Result<ServiceManifest> {
+        self.send_json(self.request(Method::POST, "/services").json(manifest))
+            .await
+    }
+
+    /// `PUT /services/:id`: create the service or update it in place.
+    pub async fn apply_service(&self, manifest: &ServiceManifest) -> Result<Applied> {
+        let path = format!("/services/{}", manifest.id);
+        let resp = self
+            .send(self.request(Method::PUT, &path).json(manifest))
+            .await?;
+        let created = resp.status() == StatusCode::CREATED;
+        Ok(Applied {
+            manifest: resp.json().await?,
+            created,
+        })
+    }
+
+    /// `DELETE /services/:id`: archive by default, see [`DeleteOptions`].
+    pub async fn delete_service(&self, id: &str, options: DeleteOptions) -> Result<()> {
+        let path = format!("/services/{}", id);
+        self.send(self.request(Method::DELETE, &path).query(&options))
+            .await?;
+        Ok(())
+    }
+
+    /// `GET /services/archived` (admin)
+    pub async fn list_archived(&self) -> Result<Vec<ArchivedService>> {
+        self.send_json(self.request(Method::GET, "/services/archived"))
+            .await
+    }
+
+    /// `POST /services/archived/:archive_id/restore` (admin)
+    pub async fn restore_archived(&self, archive_id: &str) -> Result<ServiceManifest> {
+        let path = format!("/services/archived/{}/restore", archive_id);
+        self.send_json(self.request(Method::POST, &path)).await
+    }
+
+    // ==================== Lifecycle ====================
+
+    async fn lifecycle(&self, id: &str, action: &str) -> Result<ServiceStatus> {
+        let path = format!("/services/{}/{}", id, action);
+        self.send_json(self.request(Method::POST, &path)).await
+    }
+
+    /// `POST /services/:id/start`
+    pub async fn start(&self, id: &str) -> Result<ServiceStatus> {
+        self.lifecycle(id, "start").await
+    }
+
+    /// `POST /services/:id/stop`
+    pub async fn stop(&self, id: &str) -> Result<ServiceStatus> {
+        self.lifecycle(id, "stop").await
+    }
+
+    /// `POST /services/:id/restart`
+    pub async fn restart(&self, id: &str) -> Result<ServiceStatus> {
+        self.lifecycle(id, "restart").await
+    }
+
+    /// `POST /services/:id/shutdown`: run the manifest's graceful shutdown command.
+    pub async fn shutdown(&self, id: &str) -> Result<ServiceStatus> {
+        self.lifecycle(id, "shutdown").await
+    }
+
+    /// `POST /services/:id/kill`
+    pub async fn kill(&self, id: &str) -> Result<ServiceStatus> {
+        self.lifecycle(id, "kill").await
+    }
+
+    /// `GET /services/:id/logs`: the last `bytes` bytes of the log, raw.
+    pub async fn tail_logs(&self, id: &str, bytes: usize) -> Result<Vec<u8>> {
+        let path = format!("/services/{}/logs", id);
+        let logs: RawLogs = self
+            .send_json(self.request(Method::GET, &path).query(&[("tail", bytes)]))
+            .await?;
+        // The server always sends valid base64; treat a corrupt body as an empty log.
+        Ok(BASE64.decode(logs.data).unwrap_or_default())
+    }
+
+    // ==================== Schedules ====================
+
+    /// `GET /services/:id/schedule`
+    pub async fn get_schedules(&self, id: &str) -> Result<Vec<ScheduleEntry>> {
+        let path = format!("/services/{}/schedule", id);
+        let list: ScheduleList = self.send_json(self.request(Method::GET, &path)).await?;
+        Ok(list.schedules)
+    }
+
+    /// `PUT /services/:id/schedule`: replace all schedules of a service.
+    pub async fn set_schedules(
+        &self,
+        id: &str,
+        schedules: &[Schedule],
+    ) -> Result<Vec<ScheduleEntry>> {
+        let path = format!("/services/{}/schedule", id);
+        let body = serde_json::json!({ "schedules": schedules });
+        let list: ScheduleList = self
+            .send_json(self.request(Method::PUT, &path).json(&body))
+            .await?;
+        Ok(list.schedules)
+    }
+
+    /// `POST /services/:id/schedule/run`: run a schedule's action now.
+    pub async fn run_schedule(&self, id: &str, entry: Option<&str>) -> Result<ServiceStatus> {
+        let path = format!("/services/{}/schedule/run", id);
+        let mut req = self.request(Method::POST, &path);
+        if let Some(entry) = entry {
+            req = req.query(&[("entry", entry)]);
+        }
+        self.send_json(req).await
+    }
+
+    /// `GET /schedules` (admin)
+    pub async fn list_schedules(&self) -> Result<ScheduleJobs> {
+        self.send_json(self.request(Method::GET, "/schedules"))
+            .await
+    }
+
+    /// `POST /scheduler/pause` or `/scheduler/resume` (admin); returns the new paused state.
+    pub async fn set_scheduler_paused(&self, paused: bool) -> Result<bool> {
+        let path = if paused {
+            "/scheduler/pause"
+        } else {
+            "/scheduler/resume"
+        };
+        let status: SchedulerStatus = self.send_json(self.request(Method::POST, path)).await?;
+        Ok(status.paused)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base_url_drops_trailing_slash() {
+        let client = Client::new("http://127.0.0.1:8080/").unwrap();
+        assert_eq!(client.base_url(), "http://127.0.0.1:8080");
+        let req = client
+            .with_token("hc_key")
+            .unwrap()
+            .request(Method::GET, "/services")
+            .build()
+            .unwrap();
+        assert_eq!(req.url().as_str(), "http://127.0.0.1:8080/services");
+        assert_eq!(req.headers()[AUTHORIZATION], "Bearer hc_key");
+    }
+
+    #[test]
+    fn invalid_token_is_rejected() {
+        let client = Client::new("http://127.0.0.1:8080").unwrap();
+        assert!(matches!(
+            client.with_token("bad\ntoken"),
+            Err(ClientError::InvalidHeader(_))
+        ));
+    }
+}
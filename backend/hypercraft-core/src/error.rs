@@ -1,3 +1,4 @@
+use std::fmt;
 use thiserror::Error;
 
 /// Common result type for core operations.
@@ -13,22 +14,131 @@ pub enum ServiceError {
     AlreadyRunning(String),
     #[error("service not running: {0}")]
     NotRunning(String),
+    /// `attach_or_wait` gave up: the service did not come up within the given seconds.
+    #[error("service {0} did not start within {1}s")]
+    StartTimeout(String, u64),
+    /// The process is alive (e.g. adopted from its pid file after an API restart) but this
+    /// manager holds no PTY handles for it, so attach, resize and console input are unavailable.
+    #[error("service {0} is running but not owned by this manager instance; restart it to attach")]
+    NotOwned(String),
     #[error("invalid service id")]
     InvalidId,
     #[error("policy violation: {0}")]
-    PolicyViolation(String),
+    PolicyViolation(PolicyReason),
     #[error("invalid schedule: {0}")]
     InvalidSchedule(String),
+    #[error("invalid pattern: {0}")]
+    InvalidPattern(String),
+    /// Time-range log queries need lines that start with a timestamp.
+    #[error("service {0} has no timestamped logs")]
+    NoTimestampedLogs(String),
+    /// Group hierarchy changes that would leave a dangling parent or a cycle.
+    #[error("invalid group: {0}")]
+    InvalidGroup(String),
+    /// The signal cannot be delivered on this platform (non-Unix only supports KILL).
+    #[error("signal not supported on this platform: {0}")]
+    UnsupportedSignal(String),
+    /// A request field has an unacceptable value (empty name, unknown scope, ...).
+    #[error("invalid input: {0}")]
+    InvalidInput(String),
+    /// The request is valid but conflicts with the current state of the resource.
+    #[error("conflict: {0}")]
+    Conflict(String),
     #[error("failed to spawn process: {0}")]
     SpawnFailed(String),
     #[error("unauthorized: {0}")]
     Unauthorized(String),
     #[error("two-factor authentication required: {0}")]
     TwoFactorRequired(String),
+    /// A refresh token that had already been rotated out was presented again. Every session
+    /// of the user (whose id this carries) has been revoked as a precaution.
+    #[error("refresh token reuse detected for user {0}")]
+    RefreshTokenReused(String),
+    /// The data dir cannot be written: disk full, read-only filesystem or a failed write probe.
+    #[error("storage unavailable: {0}")]
+    StorageUnavailable(String),
     #[error("io error: {0}")]
-    Io(#[from] std::io::Error),
+    Io(std::io::Error),
     #[error("serde error: {0}")]
     Serde(#[from] serde_json::Error),
     #[error("other error: {0}")]
     Other(String),
 }
+
+impl From<std::io::Error> for ServiceError {
+    /// `ENOSPC`/`EROFS` become [`ServiceError::StorageUnavailable`] so callers can tell a
+    /// full or read-only disk apart from ordinary IO failures.
+    fn from(err: std::io::Error) -> Self {
+        match err.kind() {
+            std::io::ErrorKind::StorageFull | std::io::ErrorKind::ReadOnlyFilesystem => {
+                ServiceError::StorageUnavailable(err.to_string())
+            }
+            _ => ServiceError::Io(err),
+        }
+    }
+}
+
+/// Why a manifest or request was rejected by policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyReason {
+    /// The command is not on the `HC_ALLOWED_COMMANDS` allowlist.
+    Command {
+        command: String,
+        allowed: Vec<String>,
+    },
+    /// A path (`cwd` or `env_file`) lies outside the data dir and `HC_ALLOWED_CWD_PREFIXES`.
+    PathRoot {
+        field: &'static str,
+        path: String,
+        allowed_roots: Vec<String>,
+    },
+    /// A path field (`cwd` or a path-like `command`) is malformed or does not exist.
+    InvalidPath {
+        field: &'static str,
+        path: String,
+        problem: &'static str,
+    },
+    /// Any other rule; the message says which.
+    Other(String),
+}
+
+impl fmt::Display for PolicyReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyReason::Command { command, allowed } => {
+                write!(
+                    f,
+                    "command not allowed: {command} (allowed: {})",
+                    allowed.join(", ")
+                )
+            }
+            PolicyReason::PathRoot {
+                field,
+                path,
+                allowed_roots,
+            } => write!(
+                f,
+                "{field} not allowed: {path} (allowed roots: {})",
+                allowed_roots.join(", ")
+            ),
+            PolicyReason::InvalidPath {
+                field,
+                path,
+                problem,
+            } => write!(f, "invalid {field} {path}: {problem}"),
+            PolicyReason::Other(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl From<String> for PolicyReason {
+    fn from(msg: String) -> Self {
+        PolicyReason::Other(msg)
+    }
+}
+
+impl From<&str> for PolicyReason {
+    fn from(msg: &str) -> Self {
+        PolicyReason::Other(msg.to_string())
+    }
+}
@@ -7,19 +7,36 @@ mod models;
 pub mod user;
 mod web;
 
-pub use error::{Result, ServiceError};
-pub use manager::scheduler::ServiceScheduler;
-pub use manager::{AttachHandle, ServiceManager, SystemStats};
-pub use manifest::{Schedule, ScheduleAction, ServiceManifest, WebConfig};
-pub use models::{ServiceGroup, ServiceState, ServiceStatus, ServiceSummary};
+pub use error::{PolicyReason, Result, ServiceError};
+pub use manager::scheduler::{ScheduleJobInfo, ScheduleRunResult, ServiceScheduler};
+pub use manager::{
+    parse_signal, secret_refs, AttachHandle, LagPolicy, LogMatch, LogTextDecoder, ProcessStats, RecordingInfo, ServiceManager,
+    SessionRecorder, SystemStats, DEFAULT_ATTACH_BUFFER,
+};
+pub use manifest::{
+    HealthCheck, Schedule, ScheduleAction, ScheduleKind, ServiceManifest, WebConfig, WebhookConfig,
+    WebhookEvent, MANIFEST_SCHEMA_VERSION,
+};
+pub use models::{
+    ArchivedService, ChildGroupAction, ConfigBundle, GroupAction, GroupActionResult, HealthReport,
+    ImportItemResult, ImportOutcome, ImportReport, LifecycleStats, ManifestValidation,
+    ServiceEvent, ServiceGroup, ServiceListQuery, ServiceSort, ServiceState, ServiceStatus,
+    ServiceSummary, StateChangeReason, StopOutcome, SubsystemHealth, ValidationProblem,
+    CONFIG_BUNDLE_VERSION,
+};
 pub use user::{
-    api_key_scopes, ApiKey, ApiKeySecretResponse, ApiKeySummary, AuthToken, CreateApiKeyRequest,
-    CreateApiKeyResponse, CreateUserRequest, DevTokenLoginRequest, Disable2FARequest,
-    Enable2FARequest, LoginRequest, RefreshRequest, Setup2FARequest, Setup2FAResponse, TokenClaims,
-    TokenType, TwoFactorVerification, UpdateApiKeyRequest, UpdateUserRequest, User, UserManager,
-    UserSummary, API_KEY_RAW_PREFIX,
+    api_key_scopes, service_access_level, ApiKey, ApiKeySecretResponse, ApiKeySummary, AuthToken,
+    CreateApiKeyRequest, CreateApiKeyResponse, CreateUserApiKeyRequest, CreateUserRequest,
+    DevTokenLoginRequest, Disable2FARequest, Enable2FARequest, LoginRequest, PasswordPolicy,
+    RecoveryCodesResponse, RefreshRequest, RegenerateRecoveryCodesRequest, ServiceAccessLevel,
+    ServicePermission, SessionMeta, SessionSummary, Setup2FARequest, Setup2FAResponse, TokenClaims,
+    TokenType, TwoFactorStatus, TwoFactorVerification, UpdateApiKeyRequest, UpdateUserRequest,
+    User, UserManager, UserSession, UserSummary, API_KEY_RAW_PREFIX,
+};
+pub use web::{
+    is_public_ip, validate_health_check_url, validate_service_webhook_url,
+    validate_web_upstream_url, validate_webhook_url,
 };
-pub use web::validate_web_upstream_url;
 
 /// 从当前目录向上查找并加载最近的 `.env`（仓库根一份即可）
 ///
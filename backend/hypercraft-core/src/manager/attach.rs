@@ -1,7 +1,21 @@
 use super::*;
 use portable_pty::PtySize;
+use std::fs;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+
+/// `attach_or_wait` 的兜底轮询间隔（事件可能因订阅者落后而丢失）
+const ATTACH_WAIT_POLL: Duration = Duration::from_millis(200);
+
+/// 输出广播默认缓冲的消息数；慢订阅者落后超过该数量会收到 Lagged 并丢弃旧输出
+pub const DEFAULT_ATTACH_BUFFER: usize = 1024;
 
 impl ServiceManager {
+    /// 新建 stdout/stderr 输出广播，容量由 [`ServiceManager::with_attach_buffer`] 配置
+    pub(super) fn output_channel(&self) -> broadcast::Sender<Vec<u8>> {
+        broadcast::channel(self.attach_buffer).0
+    }
+
     /// 建立 attach：需要当前 manager 已经持有子进程句柄。
     pub async fn attach(&self, id: &str) -> Result<AttachHandle> {
         let status = self.status(id).await?;
@@ -14,29 +28,108 @@ impl ServiceManager {
                 pid: entry.pid,
                 input: entry.input.clone(),
                 output: entry.output.subscribe(),
+                stderr: entry.stderr.as_ref().map(|tx| tx.subscribe()),
             })
         } else {
-            Err(ServiceError::Other(
-                "服务正在运行，但当前 API 进程未持有 stdin/stdout 句柄，无法 attach；请通过本 API 重启后再试"
-                    .into(),
-            ))
+            // 进程由 pid 文件接管（如 API 重启前启动），当前进程没有 PTY 句柄
+            Err(ServiceError::NotOwned(id.to_string()))
         }
     }
 
-    /// 调整运行中服务的 PTY 尺寸，用于触发 TUI 程序重绘当前屏幕。
-    pub async fn resize_pty(&self, id: &str, rows: u16, cols: u16) -> Result<()> {
-        let guard = self.runtime.lock().await;
-        let Some(entry) = guard.get(id) else {
+    /// 同 `attach`，但服务尚未运行时最多等待 `timeout` 直到它启动。
+    ///
+    /// 适合“先启动再 attach”的流程：收到该服务的状态事件或每隔一小段时间重试一次；
+    /// 刚启动时状态可能已是 Running 而句柄尚未登记，这段窗口内的 NotOwned 也会重试。
+    /// 超时返回 StartTimeout；`timeout` 为 0 时等同 `attach`。
+    pub async fn attach_or_wait(&self, id: &str, timeout: Duration) -> Result<AttachHandle> {
+        if timeout.is_zero() {
+            return self.attach(id).await;
+        }
+        // 先订阅再检查，避免错过两者之间的启动事件
+        let mut events = self.subscribe_events();
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut waited = false;
+        loop {
+            match self.attach(id).await {
+                Err(ServiceError::NotRunning(_)) => {}
+                Err(ServiceError::NotOwned(_)) if waited => {}
+                result => return result,
+            }
+            waited = true;
+
+            let state_changed = async {
+                loop {
+                    match events.recv().await {
+                        Ok(event) if event.service_id() == id => break,
+                        Ok(_) => {}
+                        Err(broadcast::error::RecvError::Lagged(_)) => break,
+                        Err(broadcast::error::RecvError::Closed) => std::future::pending().await,
+                    }
+                }
+            };
+            tokio::select! {
+                _ = state_changed => {}
+                _ = tokio::time::sleep(ATTACH_WAIT_POLL) => {}
+                _ = tokio::time::sleep_until(deadline) => {
+                    return Err(ServiceError::StartTimeout(id.to_string(), timeout.as_secs()));
+                }
+            }
+        }
+    }
+
+    /// 向运行中服务的 stdin 写入数据（不需要建立 attach），与 shutdown 共用输入通道。
+    pub async fn send_input(&self, id: &str, data: Vec<u8>) -> Result<()> {
+        let status = self.status(id).await?;
+        if !matches!(status.state, ServiceState::Running) {
             return Err(ServiceError::NotRunning(id.to_string()));
+        }
+        let input = {
+            let guard = self.runtime.lock().await;
+            guard.get(id).map(|h| h.input.clone())
         };
-        entry
-            .pty
-            .resize(PtySize {
-                rows,
-                cols,
-                pixel_width: 0,
-                pixel_height: 0,
-            })
-            .map_err(|error| ServiceError::Other(error.to_string()))
+        let tx = input.ok_or_else(|| ServiceError::NotOwned(id.to_string()))?;
+        tx.send(data)
+            .await
+            .map_err(|_| ServiceError::NotRunning(id.to_string()))
     }
+
+    /// 调整运行中服务的 PTY 尺寸，用于触发 TUI 程序重绘当前屏幕。
+    ///
+    /// 尺寸会记录到 runtime/pty_size.json，重新 attach 或重启时沿用。
+    pub async fn resize_pty(&self, id: &str, rows: u16, cols: u16) -> Result<()> {
+        {
+            let guard = self.runtime.lock().await;
+            let Some(entry) = guard.get(id) else {
+                return Err(ServiceError::NotRunning(id.to_string()));
+            };
+            // 分离模式没有 PTY，尺寸只记录下来
+            if let Some(pty) = &entry.pty {
+                pty.resize(PtySize {
+                    rows,
+                    cols,
+                    pixel_width: 0,
+                    pixel_height: 0,
+                })
+                .map_err(|error| ServiceError::Other(error.to_string()))?;
+            }
+        }
+        let record = PtySizeRecord { rows, cols };
+        if let Ok(data) = serde_json::to_vec(&record) {
+            let _ = fs::write(self.pty_size_path(id), data);
+        }
+        Ok(())
+    }
+
+    /// 最近一次记录的 PTY 尺寸 (rows, cols)
+    pub fn last_pty_size(&self, id: &str) -> Option<(u16, u16)> {
+        let data = fs::read(self.pty_size_path(id)).ok()?;
+        let record: PtySizeRecord = serde_json::from_slice(&data).ok()?;
+        Some((record.rows, record.cols))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PtySizeRecord {
+    rows: u16,
+    cols: u16,
 }
@@ -0,0 +1,123 @@
+//! 配置导入导出：manifest（含调度）与分组打包为单个 JSON，便于迁移主机。
+
+use super::*;
+use crate::models::{
+    ConfigBundle, ImportItemResult, ImportOutcome, ImportReport, ServiceGroup,
+    CONFIG_BUNDLE_VERSION,
+};
+use tracing::instrument;
+
+impl ServiceManager {
+    /// 导出全部服务 manifest 与分组；不包含用户、API Key 与密钥。
+    #[instrument(skip(self))]
+    pub async fn export_config(&self) -> Result<ConfigBundle> {
+        let mut ids = self.service_ids().await?;
+        ids.sort();
+        let mut services = Vec::with_capacity(ids.len());
+        for id in ids {
+            services.push(self.load_manifest(&id).await?);
+        }
+        Ok(ConfigBundle {
+            version: CONFIG_BUNDLE_VERSION,
+            exported_at: Some(chrono::Utc::now()),
+            groups: self.list_groups().await?,
+            services,
+        })
+    }
+
+    /// 导入配置包：已存在的分组与服务仅在 `overwrite` 时覆盖。
+    ///
+    /// 每个服务独立校验 ID 与策略，单个失败不影响其余条目，结果逐条记录在报告中。
+    #[instrument(skip(self, bundle))]
+    pub async fn import_config(
+        &self,
+        bundle: ConfigBundle,
+        overwrite: bool,
+    ) -> Result<ImportReport> {
+        if bundle.version > CONFIG_BUNDLE_VERSION {
+            return Err(ServiceError::PolicyViolation(
+                format!(
+                    "unsupported bundle version {} (max {})",
+                    bundle.version, CONFIG_BUNDLE_VERSION
+                )
+                .into(),
+            ));
+        }
+        let mut report = ImportReport::default();
+
+        let mut groups = self.list_groups().await?;
+        for group in bundle.groups {
+            let outcome = merge_group(&mut groups, group.clone(), overwrite);
+            report.groups.push(ImportItemResult {
+                id: group.id,
+                outcome,
+                error: (outcome == ImportOutcome::Failed).then(|| "group id is empty".into()),
+            });
+        }
+        self.save_groups_async(&groups).await?;
+
+        for manifest in bundle.services {
+            let id = manifest.id.clone();
+            let result = self.import_service(manifest, overwrite).await;
+            report.services.push(match result {
+                Ok(outcome) => ImportItemResult {
+                    id,
+                    outcome,
+                    error: None,
+                },
+                Err(e) => ImportItemResult {
+                    id,
+                    outcome: ImportOutcome::Failed,
+                    error: Some(e.to_string()),
+                },
+            });
+        }
+        Ok(report)
+    }
+
+    /// 导入单个服务：复用 create/update 的 ID、策略与 env 引用校验。
+    async fn import_service(
+        &self,
+        manifest: ServiceManifest,
+        overwrite: bool,
+    ) -> Result<ImportOutcome> {
+        self.validate_id(&manifest.id)?;
+        let exists = tokio::fs::try_exists(self.manifest_path(&manifest.id))
+            .await
+            .unwrap_or(false);
+        match (exists, overwrite) {
+            (false, _) => {
+                self.create_service(manifest).await?;
+                Ok(ImportOutcome::Created)
+            }
+            (true, true) => {
+                let id = manifest.id.clone();
+                self.update_service(&id, manifest).await?;
+                Ok(ImportOutcome::Updated)
+            }
+            (true, false) => Ok(ImportOutcome::Skipped),
+        }
+    }
+}
+
+/// 合并单个分组到现有列表
+fn merge_group(
+    groups: &mut Vec<ServiceGroup>,
+    group: ServiceGroup,
+    overwrite: bool,
+) -> ImportOutcome {
+    if group.id.trim().is_empty() {
+        return ImportOutcome::Failed;
+    }
+    match groups.iter_mut().find(|g| g.id == group.id) {
+        Some(existing) if overwrite => {
+            *existing = group;
+            ImportOutcome::Updated
+        }
+        Some(_) => ImportOutcome::Skipped,
+        None => {
+            groups.push(group);
+            ImportOutcome::Created
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! 资源限制：Linux 上通过 cgroup v2 为服务进程设置内存与 CPU 上限。
+//!
+//! 每个服务对应 `/sys/fs/cgroup/hypercraft/<id>`，进程启动后立即迁入；
+//! 其它平台接受配置但只打印警告。
+
+use super::*;
+
+/// cgroup v2 统一挂载点
+#[cfg(target_os = "linux")]
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+/// hypercraft 的父 cgroup
+#[cfg(target_os = "linux")]
+const CGROUP_PARENT: &str = "hypercraft";
+/// cpu.max 的周期（微秒）
+const CPU_PERIOD_US: u64 = 100_000;
+
+impl ServiceManager {
+    /// 将刚启动的进程放入带限额的 cgroup；未配置限额时直接返回。
+    pub(super) fn apply_resource_limits(&self, manifest: &ServiceManifest, pid: u32) -> Result<()> {
+        let limits = cgroup_limits(manifest)?;
+        if limits.is_empty() {
+            return Ok(());
+        }
+        #[cfg(target_os = "linux")]
+        {
+            apply_cgroup(&manifest.id, pid, &limits)
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = pid;
+            tracing::warn!(
+                service_id = %manifest.id,
+                "memory_limit_mb / cpu_quota_percent are only enforced on Linux, ignoring"
+            );
+            Ok(())
+        }
+    }
+
+    /// 删除服务时清理其 cgroup（尽力而为）
+    pub(super) fn remove_cgroup(&self, id: &str) {
+        #[cfg(target_os = "linux")]
+        {
+            let group = Path::new(CGROUP_ROOT).join(CGROUP_PARENT).join(id);
+            let _ = std::fs::remove_dir(group);
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = id;
+    }
+}
+
+/// `memory_limit_mb` 换算为字节；溢出 u64 时拒绝
+pub(super) fn memory_limit_bytes(mb: u64) -> Result<u64> {
+    mb.checked_mul(1024 * 1024).ok_or_else(|| {
+        ServiceError::PolicyViolation(format!("memory_limit_mb is too large: {mb}").into())
+    })
+}
+
+/// 需要写入的 cgroup 控制文件及其取值
+fn cgroup_limits(manifest: &ServiceManifest) -> Result<Vec<(&'static str, String)>> {
+    let mut limits = Vec::new();
+    if let Some(mb) = manifest.memory_limit_mb {
+        limits.push(("memory.max", memory_limit_bytes(mb)?.to_string()));
+    }
+    if let Some(percent) = manifest.cpu_quota_percent {
+        let quota = u64::from(percent) * CPU_PERIOD_US / 100;
+        limits.push(("cpu.max", format!("{} {}", quota, CPU_PERIOD_US)));
+    }
+    Ok(limits)
+}
+
+#[cfg(target_os = "linux")]
+fn apply_cgroup(id: &str, pid: u32, limits: &[(&'static str, String)]) -> Result<()> {
+    let write = |path: PathBuf, value: &str| {
+        std::fs::write(&path, value).map_err(|e| {
+            ServiceError::SpawnFailed(format!("cgroup write {}: {}", path.display(), e))
+        })
+    };
+
+    let root = Path::new(CGROUP_ROOT);
+    if !root.join("cgroup.controllers").is_file() {
+        return Err(ServiceError::SpawnFailed(
+            "resource limits require cgroup v2 mounted at /sys/fs/cgroup".into(),
+        ));
+    }
+
+    // 逐级开启需要的控制器：根 -> hypercraft -> 服务
+    let controllers: Vec<String> = limits
+        .iter()
+        .filter_map(|(file, _)| file.split('.').next())
+        .map(|c| format!("+{c}"))
+        .collect();
+    let controllers = controllers.join(" ");
+    let parent = root.join(CGROUP_PARENT);
+    let group = parent.join(id);
+    std::fs::create_dir_all(&group).map_err(|e| {
+        ServiceError::SpawnFailed(format!("cgroup create {}: {}", group.display(), e))
+    })?;
+    write(root.join("cgroup.subtree_control"), &controllers)?;
+    write(parent.join("cgroup.subtree_control"), &controllers)?;
+
+    for (file, value) in limits {
+        write(group.join(file), value)?;
+    }
+    write(group.join("cgroup.procs"), &pid.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cgroup_limits_translate_manifest_fields() {
+        let mut manifest: ServiceManifest = serde_json::from_value(serde_json::json!({
+            "id": "svc", "name": "svc", "command": "java"
+        }))
+        .unwrap();
+        assert!(cgroup_limits(&manifest).unwrap().is_empty());
+
+        manifest.memory_limit_mb = Some(512);
+        manifest.cpu_quota_percent = Some(150);
+        assert_eq!(
+            cgroup_limits(&manifest).unwrap(),
+            vec![
+                ("memory.max", "536870912".to_string()),
+                ("cpu.max", "150000 100000".to_string()),
+            ]
+        );
+
+        manifest.memory_limit_mb = Some(u64::MAX);
+        assert!(matches!(
+            cgroup_limits(&manifest),
+            Err(ServiceError::PolicyViolation(_))
+        ));
+    }
+}
@@ -0,0 +1,218 @@
+//! 服务依赖：按 `depends_on` 拓扑排序，先启动前置服务并等待其就绪。
+
+use super::*;
+use futures::stream::{FuturesUnordered, StreamExt};
+use tokio::time::{Duration, Instant};
+use tracing::instrument;
+
+/// 等待依赖就绪的最长时间
+const DEPENDENCY_READY_TIMEOUT: Duration = Duration::from_secs(60);
+/// 就绪轮询间隔
+const DEPENDENCY_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+impl ServiceManager {
+    /// 计算启动顺序：包含 `ids` 及其传递依赖，前置服务排在前面。
+    ///
+    /// 依赖不存在返回 NotFound，存在环时返回 PolicyViolation 并给出环路。
+    pub async fn startup_order(&self, ids: &[String]) -> Result<Vec<String>> {
+        let manifests = self.dependency_closure(ids).await?;
+        topo_sort(ids, &dependency_graph(&manifests))
+    }
+
+    /// 加载 `ids` 及其传递依赖的 manifest
+    async fn dependency_closure(&self, ids: &[String]) -> Result<HashMap<String, ServiceManifest>> {
+        let mut manifests: HashMap<String, ServiceManifest> = HashMap::new();
+        let mut pending: Vec<String> = ids.to_vec();
+        while let Some(id) = pending.pop() {
+            if manifests.contains_key(&id) {
+                continue;
+            }
+            let manifest = self.load_manifest(&id).await?;
+            pending.extend(manifest.depends_on.iter().cloned());
+            manifests.insert(id, manifest);
+        }
+        Ok(manifests)
+    }
+
+    /// 并发启动一批服务（含传递依赖），同时进行的启动不超过 `concurrency` 个。
+    ///
+    /// 服务只在其全部依赖启动成功（并就绪）后才开始启动；依赖失败的服务不再尝试。
+    /// 互不依赖的服务按 manifest `order`、再按 ID 排队。已在运行的服务计为成功。
+    /// 依赖不存在或成环时整体返回错误；否则按完成顺序返回每个服务的结果。
+    pub async fn start_many(
+        &self,
+        ids: &[String],
+        concurrency: usize,
+    ) -> Result<Vec<(String, Result<ServiceStatus>)>> {
+        let manifests = self.dependency_closure(ids).await?;
+        let graph = dependency_graph(&manifests);
+        let mut roots = ids.to_vec();
+        roots.sort_by(|a, b| (manifests[a].order, a).cmp(&(manifests[b].order, b)));
+        let mut pending = topo_sort(&roots, &graph)?;
+        let has_dependents: HashSet<&str> = graph.values().flatten().map(String::as_str).collect();
+
+        let mut succeeded: HashSet<String> = HashSet::new();
+        let mut failed: HashSet<String> = HashSet::new();
+        let mut results = Vec::with_capacity(pending.len());
+        let mut running = FuturesUnordered::new();
+        loop {
+            let mut i = 0;
+            while i < pending.len() && running.len() < concurrency.max(1) {
+                let deps = &graph[&pending[i]];
+                if let Some(dep) = deps.iter().find(|dep| failed.contains(*dep)) {
+                    let err =
+                        ServiceError::SpawnFailed(format!("dependency {dep} failed to start"));
+                    let id = pending.remove(i);
+                    failed.insert(id.clone());
+                    results.push((id, Err(err)));
+                } else if deps.iter().all(|dep| succeeded.contains(dep)) {
+                    let id = pending.remove(i);
+                    let wait = has_dependents.contains(id.as_str());
+                    running.push(self.start_if_stopped(id, wait));
+                } else {
+                    i += 1;
+                }
+            }
+            let Some((id, result)) = running.next().await else {
+                break;
+            };
+            if result.is_ok() {
+                succeeded.insert(id.clone());
+            } else {
+                failed.insert(id.clone());
+            }
+            results.push((id, result));
+        }
+        Ok(results)
+    }
+
+    /// 未运行时启动服务；`wait` 为 true 时还要等到就绪，供依赖它的服务使用
+    async fn start_if_stopped(&self, id: String, wait: bool) -> (String, Result<ServiceStatus>) {
+        let result = async {
+            let status = self.status(&id).await?;
+            let status = if status.state == ServiceState::Running {
+                tracing::info!(service_id = %id, "already running, skipping start");
+                status
+            } else {
+                self.start(&id).await?
+            };
+            if wait {
+                self.wait_until_ready(&id).await?;
+            }
+            Ok(status)
+        }
+        .await;
+        (id, result)
+    }
+
+    /// 启动服务前先按依赖顺序启动前置服务，并等待每个依赖进入 Running
+    /// （配置了健康检查时还需探测通过）。
+    #[instrument(skip(self))]
+    pub async fn start_with_dependencies(&self, id: &str) -> Result<ServiceStatus> {
+        let order = self.startup_order(&[id.to_string()]).await?;
+        for dep in order.iter().filter(|dep| dep.as_str() != id) {
+            let status = self.status(dep).await?;
+            if status.state != ServiceState::Running {
+                tracing::info!(service_id = %id, dependency = %dep, "starting dependency");
+                self.start(dep).await?;
+            }
+            self.wait_until_ready(dep).await?;
+        }
+        self.start(id).await
+    }
+
+    /// 等待服务 Running 且健康检查（若配置）通过
+    async fn wait_until_ready(&self, id: &str) -> Result<()> {
+        let needs_health = self.load_manifest(id).await?.health_check.is_some();
+        let deadline = Instant::now() + DEPENDENCY_READY_TIMEOUT;
+        loop {
+            let status = self.status(id).await?;
+            let running = status.state == ServiceState::Running;
+            if running && (!needs_health || status.healthy == Some(true)) {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(ServiceError::SpawnFailed(format!(
+                    "dependency {id} not ready after {}s",
+                    DEPENDENCY_READY_TIMEOUT.as_secs()
+                )));
+            }
+            tokio::time::sleep(DEPENDENCY_POLL_INTERVAL).await;
+        }
+    }
+}
+
+fn dependency_graph(manifests: &HashMap<String, ServiceManifest>) -> HashMap<String, Vec<String>> {
+    manifests
+        .iter()
+        .map(|(id, manifest)| (id.clone(), manifest.depends_on.clone()))
+        .collect()
+}
+
+/// 深度优先拓扑排序，依赖排在被依赖者之前；保持 `roots` 的相对顺序。
+fn topo_sort(roots: &[String], graph: &HashMap<String, Vec<String>>) -> Result<Vec<String>> {
+    fn visit(
+        id: &str,
+        graph: &HashMap<String, Vec<String>>,
+        done: &mut HashSet<String>,
+        path: &mut Vec<String>,
+        order: &mut Vec<String>,
+    ) -> Result<()> {
+        if done.contains(id) {
+            return Ok(());
+        }
+        if let Some(pos) = path.iter().position(|p| p == id) {
+            let mut chain = path[pos..].to_vec();
+            chain.push(id.to_string());
+            return Err(ServiceError::PolicyViolation(
+                format!("dependency cycle: {}", chain.join(" -> ")).into(),
+            ));
+        }
+        path.push(id.to_string());
+        for dep in graph.get(id).map(Vec::as_slice).unwrap_or_default() {
+            visit(dep, graph, done, path, order)?;
+        }
+        path.pop();
+        done.insert(id.to_string());
+        order.push(id.to_string());
+        Ok(())
+    }
+
+    let mut done = HashSet::new();
+    let mut order = Vec::new();
+    for root in roots {
+        visit(root, graph, &mut done, &mut Vec::new(), &mut order)?;
+    }
+    Ok(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+        edges
+            .iter()
+            .map(|(id, deps)| (id.to_string(), deps.iter().map(|d| d.to_string()).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn topo_sort_puts_dependencies_first() {
+        let g = graph(&[("app", &["db", "cache"]), ("cache", &["db"]), ("db", &[])]);
+        let order = topo_sort(&["app".into()], &g).unwrap();
+        assert_eq!(order, vec!["db", "cache", "app"]);
+    }
+
+    #[test]
+    fn topo_sort_reports_cycle_chain() {
+        let g = graph(&[("a", &["b"]), ("b", &["c"]), ("c", &["a"])]);
+        let err = topo_sort(&["a".into()], &g).unwrap_err();
+        match err {
+            ServiceError::PolicyViolation(msg) => {
+                assert_eq!(msg.to_string(), "dependency cycle: a -> b -> c -> a")
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! 分离模式：子进程在独立会话中运行，不依赖 API 进程持有的 PTY，API 重启后仍然存活。
+//!
+//! 子进程的 stdout/stderr 直接追加到日志文件，attach 通过尾随日志获取输出；
+//! stdin 连接 runtime 目录下的 FIFO，attach 的输入写入该 FIFO。
+//! 这两端都可以在 API 重启后重新打开，因此 `reconcile` 能为接管的进程恢复 attach。
+
+use super::*;
+use std::fs::OpenOptions;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::atomic::AtomicBool;
+use tokio::time::Duration;
+
+/// 日志尾随的轮询间隔
+const TAIL_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+impl ServiceManager {
+    /// 以分离模式启动子进程（仅 Unix）：新建会话脱离 API 的进程组，stdin 接 FIFO，输出写日志。
+    #[cfg(unix)]
+    pub(super) fn spawn_detached_process(
+        &self,
+        manifest: &ServiceManifest,
+    ) -> Result<std::process::Child> {
+        use std::os::unix::process::CommandExt;
+
+        let fifo = self.stdin_fifo_path(&manifest.id);
+        let _ = std::fs::remove_file(&fifo);
+        make_fifo(&fifo)?;
+        // 读写方式打开：open 不会阻塞，且没有 attach 写入时子进程也不会读到 EOF
+        let stdin = OpenOptions::new().read(true).write(true).open(&fifo)?;
+        let log = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.log_path(&manifest.id))?;
+
+        let (program, args) = lifecycle::command_line(manifest);
+        let mut cmd = std::process::Command::new(program);
+        cmd.args(args)
+            .stdin(stdin)
+            .stdout(log.try_clone()?)
+            .stderr(log)
+            .envs(self.spawn_env(manifest)?);
+        if let Some(cwd) = self.effective_cwd(manifest) {
+            cmd.current_dir(cwd);
+        }
+        // SAFETY: setsid 是 async-signal-safe 的，fork 之后调用不会触碰锁或分配内存
+        unsafe {
+            cmd.pre_exec(|| {
+                if libc::setsid() == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| ServiceError::SpawnFailed(e.to_string()))?;
+        if let Err(e) = self.apply_resource_limits(manifest, child.id()) {
+            let _ = child.kill();
+            return Err(e);
+        }
+        Ok(child)
+    }
+
+    #[cfg(not(unix))]
+    pub(super) fn spawn_detached_process(
+        &self,
+        _manifest: &ServiceManifest,
+    ) -> Result<std::process::Child> {
+        Err(ServiceError::SpawnFailed(
+            "detached mode is only supported on Unix".into(),
+        ))
+    }
+
+    /// 为分离进程建立 runtime 句柄：输出来自日志尾随，输入写入 FIFO。
+    ///
+    /// 启动时与 `reconcile` 接管时共用；进程退出后尾随任务自行结束。
+    pub(super) fn detached_handles(
+        &self,
+        id: &str,
+        pid: u32,
+        stop_requested: Arc<AtomicBool>,
+        healthy: Arc<StdMutex<Option<bool>>>,
+    ) -> Result<RuntimeHandles> {
+        let fifo = open_fifo_writer(&self.stdin_fifo_path(id))?;
+        let (in_tx, in_rx) = mpsc::channel::<Vec<u8>>(64);
+        self.spawn_input_handler(Box::new(fifo), in_rx);
+
+        let out_tx = self.output_channel();
+        let output_cursor = logs::OutputCursor::default();
+        self.spawn_log_tail(
+            self.log_path(id),
+            pid,
+            out_tx.clone(),
+            output_cursor.clone(),
+        );
+
+        Ok(RuntimeHandles {
+            pid,
+            input: in_tx,
+            output: out_tx,
+            output_cursor,
+            stderr: None,
+            stderr_cursor: logs::OutputCursor::default(),
+            pty: None,
+            stop_requested,
+            healthy,
+        })
+    }
+
+    /// 接管 API 重启前启动的分离进程：重新挂上日志与 FIFO，使其可以继续 attach。
+    pub(super) async fn adopt_detached(&self, id: &str, pid: u32) -> Result<()> {
+        let manifest = self.load_manifest(id).await?;
+        if !manifest.detached || !self.stdin_fifo_path(id).exists() {
+            return Ok(());
+        }
+        let handles = self.detached_handles(
+            id,
+            pid,
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(StdMutex::new(None)),
+        )?;
+        let mut guard = self.runtime.lock().await;
+        guard.entry(id.to_string()).or_insert(handles);
+        Ok(())
+    }
+
+    /// 尾随日志文件：只在有订阅者时读取新增内容并广播，进程退出后结束。
+    ///
+    /// 已广播到的位置记在 `cursor` 中（子进程自己写日志，文件长度可能超前于广播）。
+    fn spawn_log_tail(
+        &self,
+        log_path: PathBuf,
+        pid: u32,
+        out_tx: broadcast::Sender<Vec<u8>>,
+        cursor: logs::OutputCursor,
+    ) {
+        let manager = self.clone();
+        // 起点在创建句柄时确定，避免任务首次调度前写入的输出被跳过
+        *cursor.lock() = file_len(&log_path);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(TAIL_POLL_INTERVAL).await;
+                {
+                    let mut offset = cursor.lock();
+                    let len = file_len(&log_path);
+                    if len < *offset {
+                        // 日志被清空或截断，从头读起
+                        *offset = 0;
+                    }
+                    if out_tx.receiver_count() == 0 {
+                        *offset = len;
+                    } else if len > *offset {
+                        if let Some(chunk) = read_range(&log_path, *offset, len) {
+                            *offset += chunk.len() as u64;
+                            let _ = out_tx.send(chunk);
+                        }
+                    }
+                }
+                let alive = manager
+                    .process_alive(pid)
+                    .map(|(alive, _)| alive)
+                    .unwrap_or(false);
+                if !alive {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+/// 创建 FIFO（权限 0600，仅 API 所属用户可写入）
+#[cfg(unix)]
+fn make_fifo(path: &Path) -> Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| ServiceError::SpawnFailed("invalid fifo path".into()))?;
+    // SAFETY: c_path 是以 NUL 结尾的有效路径
+    if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } == -1 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(())
+}
+
+/// 以非阻塞方式打开 FIFO 写端：读端（子进程）已不存在时立即报错而不是挂起
+#[cfg(unix)]
+fn open_fifo_writer(path: &Path) -> Result<std::fs::File> {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    Ok(OpenOptions::new()
+        .write(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(path)?)
+}
+
+#[cfg(not(unix))]
+fn open_fifo_writer(_path: &Path) -> Result<std::fs::File> {
+    Err(ServiceError::SpawnFailed(
+        "detached mode is only supported on Unix".into(),
+    ))
+}
+
+fn file_len(path: &Path) -> u64 {
+    std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+/// 读取文件 `[from, to)` 区间的内容
+fn read_range(path: &Path, from: u64, to: u64) -> Option<Vec<u8>> {
+    let mut file = std::fs::File::open(path).ok()?;
+    file.seek(SeekFrom::Start(from)).ok()?;
+    let mut buf = Vec::with_capacity((to - from) as usize);
+    file.take(to - from).read_to_end(&mut buf).ok()?;
+    Some(buf)
+}
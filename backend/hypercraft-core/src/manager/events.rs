@@ -0,0 +1,60 @@
+//! 生命周期事件广播：服务状态变化时推送给订阅者（SSE 等）。
+
+use super::*;
+use crate::manifest::ScheduleAction;
+use crate::models::{ServiceEvent, StateChangeReason};
+use chrono::{DateTime, Utc};
+
+/// 事件广播缓冲；慢订阅者落后超过该数量会收到 Lagged 并丢弃旧事件
+pub(super) const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+impl ServiceManager {
+    /// 订阅服务生命周期事件。
+    pub fn subscribe_events(&self) -> broadcast::Receiver<ServiceEvent> {
+        self.events.subscribe()
+    }
+
+    /// 广播一次状态变化；没有订阅者时直接丢弃。
+    pub(super) fn emit_state_changed(
+        &self,
+        id: &str,
+        state: ServiceState,
+        reason: StateChangeReason,
+        pid: Option<u32>,
+        exit_code: Option<i32>,
+    ) {
+        let _ = self.events.send(ServiceEvent::StateChanged {
+            id: id.to_string(),
+            state,
+            reason,
+            pid,
+            exit_code,
+            at: Utc::now(),
+        });
+    }
+
+    /// 广播健康检查结论变化。
+    pub(super) fn emit_health_changed(&self, id: &str, healthy: bool) {
+        let _ = self.events.send(ServiceEvent::HealthChanged {
+            id: id.to_string(),
+            healthy,
+            at: Utc::now(),
+        });
+    }
+
+    /// 广播调度条目触发。
+    pub(super) fn emit_schedule_fired(
+        &self,
+        id: &str,
+        schedule_id: &str,
+        action: ScheduleAction,
+        at: DateTime<Utc>,
+    ) {
+        let _ = self.events.send(ServiceEvent::ScheduleFired {
+            id: id.to_string(),
+            schedule_id: schedule_id.to_string(),
+            action,
+            at,
+        });
+    }
+}
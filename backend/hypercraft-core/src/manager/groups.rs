@@ -1,5 +1,5 @@
 use super::*;
-use crate::models::ServiceGroup;
+use crate::models::{ChildGroupAction, GroupAction, GroupActionResult, ServiceGroup};
 use tracing::instrument;
 
 impl ServiceManager {
@@ -9,6 +9,9 @@ impl ServiceManager {
     }
 
     /// 加载所有分组（异步版本）
+    ///
+    /// 按树的先序返回：父分组在前，其后是按 `order` 排好的子分组，
+    /// 前端据 `parent` 即可还原层级。
     #[instrument(skip(self))]
     pub async fn list_groups(&self) -> Result<Vec<ServiceGroup>> {
         let path = self.groups_path();
@@ -17,11 +20,11 @@ impl ServiceManager {
         }
         let data = tokio::fs::read(&path).await?;
         let groups: Vec<ServiceGroup> = serde_json::from_slice(&data)?;
-        Ok(groups)
+        Ok(tree_order(groups))
     }
 
     /// 保存分组列表（异步版本）
-    async fn save_groups_async(&self, groups: &[ServiceGroup]) -> Result<()> {
+    pub(super) async fn save_groups_async(&self, groups: &[ServiceGroup]) -> Result<()> {
         let data = serde_json::to_vec(groups)?;
         tokio::fs::write(self.groups_path(), data).await?;
         Ok(())
@@ -34,6 +37,7 @@ impl ServiceManager {
         id: String,
         name: String,
         color: Option<String>,
+        parent: Option<String>,
     ) -> Result<ServiceGroup> {
         let mut groups = self.list_groups().await?;
 
@@ -41,15 +45,16 @@ impl ServiceManager {
         if groups.iter().any(|g| g.id == id) {
             return Err(ServiceError::AlreadyExists(id));
         }
-
-        // 计算新分组的顺序
-        let max_order = groups.iter().map(|g| g.order).max().unwrap_or(-1);
+        if let Some(parent) = &parent {
+            validate_parent(&groups, &id, parent)?;
+        }
 
         let group = ServiceGroup {
+            order: next_sibling_order(&groups, parent.as_deref()),
             id,
             name,
-            order: max_order + 1,
             color,
+            parent,
         };
 
         groups.push(group.clone());
@@ -65,9 +70,22 @@ impl ServiceManager {
         id: &str,
         name: Option<String>,
         color: Option<Option<String>>,
+        parent: Option<Option<String>>,
     ) -> Result<ServiceGroup> {
         let mut groups = self.list_groups().await?;
 
+        // 换父分组时排到新父分组的末尾
+        let reparent = match parent {
+            Some(parent) if groups.iter().any(|g| g.id == id && g.parent != parent) => {
+                if let Some(parent) = &parent {
+                    validate_parent(&groups, id, parent)?;
+                }
+                let order = next_sibling_order(&groups, parent.as_deref());
+                Some((parent, order))
+            }
+            _ => None,
+        };
+
         let group = groups
             .iter_mut()
             .find(|g| g.id == id)
@@ -79,6 +97,10 @@ impl ServiceManager {
         if let Some(c) = color {
             group.color = c;
         }
+        if let Some((parent, order)) = reparent {
+            group.parent = parent;
+            group.order = order;
+        }
 
         let updated = group.clone();
         self.save_groups_async(&groups).await?;
@@ -87,14 +109,35 @@ impl ServiceManager {
     }
 
     /// 删除分组（不会删除服务，只是将服务的 group 设为 None）
+    ///
+    /// 有子分组时必须通过 `children` 明确子分组的去向。
     #[instrument(skip(self))]
-    pub async fn delete_group(&self, id: &str) -> Result<()> {
+    pub async fn delete_group(&self, id: &str, children: Option<ChildGroupAction>) -> Result<()> {
         let mut groups = self.list_groups().await?;
-        let initial_len = groups.len();
-        groups.retain(|g| g.id != id);
+        let deleted = groups
+            .iter()
+            .position(|g| g.id == id)
+            .map(|index| groups.remove(index))
+            .ok_or_else(|| ServiceError::NotFound(id.to_string()))?;
 
-        if groups.len() == initial_len {
-            return Err(ServiceError::NotFound(id.to_string()));
+        if groups.iter().any(|g| g.parent.as_deref() == Some(id)) {
+            let new_parent = match children {
+                Some(ChildGroupAction::Reparent) => deleted.parent,
+                Some(ChildGroupAction::Orphan) => None,
+                None => {
+                    return Err(ServiceError::InvalidGroup(format!(
+                        "group {id} has child groups; choose children=reparent or children=orphan"
+                    )))
+                }
+            };
+            let first = next_sibling_order(&groups, new_parent.as_deref());
+            let children = groups
+                .iter_mut()
+                .filter(|g| g.parent.as_deref() == Some(id));
+            for (order, group) in (first..).zip(children) {
+                group.parent = new_parent.clone();
+                group.order = order;
+            }
         }
 
         self.save_groups_async(&groups).await?;
@@ -112,20 +155,127 @@ impl ServiceManager {
         Ok(())
     }
 
-    /// 重新排序分组
+    /// 分组（含子分组）内的服务，按 `order`、ID 排序
+    #[instrument(skip(self))]
+    pub async fn group_services(&self, group_id: &str) -> Result<Vec<ServiceSummary>> {
+        let groups = self.list_groups().await?;
+        if !groups.iter().any(|g| g.id == group_id) {
+            return Err(ServiceError::NotFound(group_id.to_string()));
+        }
+        // 先序排列保证子分组紧跟在祖先之后
+        let mut members: HashSet<&str> = HashSet::from([group_id]);
+        for group in &groups {
+            if group.parent.as_deref().is_some_and(|p| members.contains(p)) {
+                members.insert(group.id.as_str());
+            }
+        }
+
+        let mut services: Vec<ServiceSummary> = self
+            .list_services()
+            .await?
+            .into_iter()
+            .filter(|s| s.group.as_deref().is_some_and(|g| members.contains(g)))
+            .collect();
+        services.sort_by(|a, b| a.order.cmp(&b.order).then_with(|| a.id.cmp(&b.id)));
+        Ok(services)
+    }
+
+    /// 对分组内所有服务执行启动/停止/重启
+    ///
+    /// 相同 `order` 的服务并发执行；启动按 `order` 升序逐批进行，停止按降序，
+    /// 重启先按降序全部停止再按升序启动。已处于目标状态的服务视为成功，
+    /// 单个服务失败不影响其他服务。
+    #[instrument(skip(self))]
+    pub async fn group_action(
+        &self,
+        group_id: &str,
+        action: GroupAction,
+    ) -> Result<Vec<GroupActionResult>> {
+        let services = self.group_services(group_id).await?;
+        let mut batches: Vec<Vec<String>> = Vec::new();
+        let mut last_order = None;
+        for service in services {
+            if last_order != Some(service.order) {
+                batches.push(Vec::new());
+                last_order = Some(service.order);
+            }
+            if let Some(batch) = batches.last_mut() {
+                batch.push(service.id);
+            }
+        }
+
+        let mut results: HashMap<String, GroupActionResult> = HashMap::new();
+        if matches!(action, GroupAction::Stop | GroupAction::Restart) {
+            for batch in batches.iter().rev() {
+                let stopped =
+                    futures::future::join_all(batch.iter().map(|id| self.ensure_stopped(id))).await;
+                for (id, result) in batch.iter().zip(stopped) {
+                    results.insert(id.clone(), action_result(id, result));
+                }
+            }
+        }
+        if matches!(action, GroupAction::Start | GroupAction::Restart) {
+            for batch in &batches {
+                // 重启时停止失败的服务不再启动
+                let pending: Vec<&String> = batch
+                    .iter()
+                    .filter(|id| results.get(*id).is_none_or(|r| r.ok))
+                    .collect();
+                let started =
+                    futures::future::join_all(pending.iter().map(|id| self.ensure_started(id)))
+                        .await;
+                for (id, result) in pending.into_iter().zip(started) {
+                    results.insert(id.clone(), action_result(id, result));
+                }
+            }
+        }
+
+        Ok(batches
+            .into_iter()
+            .flatten()
+            .filter_map(|id| results.remove(&id))
+            .collect())
+    }
+
+    /// 停止服务，已停止的服务直接返回当前状态
+    async fn ensure_stopped(&self, id: &str) -> Result<ServiceStatus> {
+        match self.stop(id).await {
+            Err(ServiceError::NotRunning(_)) => self.status(id).await,
+            result => result,
+        }
+    }
+
+    /// 启动服务，已运行的服务直接返回当前状态
+    async fn ensure_started(&self, id: &str) -> Result<ServiceStatus> {
+        match self.start(id).await {
+            Err(ServiceError::AlreadyRunning(_)) => self.status(id).await,
+            result => result,
+        }
+    }
+
+    /// 重新排序 `parent` 下的子分组（`None` 为顶层分组）
     #[instrument(skip(self, group_ids))]
-    pub async fn reorder_groups(&self, group_ids: Vec<String>) -> Result<Vec<ServiceGroup>> {
+    pub async fn reorder_groups(
+        &self,
+        group_ids: Vec<String>,
+        parent: Option<String>,
+    ) -> Result<Vec<ServiceGroup>> {
         let mut groups = self.list_groups().await?;
 
         // 按照传入的顺序重新排列
         for (index, gid) in group_ids.iter().enumerate() {
             if let Some(group) = groups.iter_mut().find(|g| &g.id == gid) {
+                if group.parent != parent {
+                    return Err(ServiceError::InvalidGroup(format!(
+                        "group {gid} is not a child of {}",
+                        parent.as_deref().unwrap_or("the top level")
+                    )));
+                }
                 group.order = index as i32;
             }
         }
 
-        // 按 order 排序
-        groups.sort_by_key(|g| g.order);
+        let groups = tree_order(groups);
         self.save_groups_async(&groups).await?;
 
         Ok(groups)
@@ -139,6 +289,57 @@ impl ServiceManager {
         self.update_service(id, manifest).await
     }
 
+    /// 批量增删多个服务的 tag，返回更新后的摘要
+    ///
+    /// 先删后加，同一 tag 同时出现在两边时保留；任一服务不存在时不做任何修改。
+    #[instrument(skip(self))]
+    pub async fn bulk_tag(
+        &self,
+        ids: &[String],
+        add: &[String],
+        remove: &[String],
+    ) -> Result<Vec<ServiceSummary>> {
+        let mut seen = HashSet::new();
+        let mut manifests = Vec::new();
+        for id in ids.iter().filter(|id| seen.insert(id.as_str())) {
+            manifests.push(self.load_manifest(id).await?);
+        }
+        let clean = |tags: &[String]| -> Vec<String> {
+            tags.iter()
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect()
+        };
+        let (add, remove) = (clean(add), clean(remove));
+
+        let mut summaries = Vec::with_capacity(manifests.len());
+        for mut manifest in manifests {
+            let before = manifest.tags.clone();
+            manifest.tags.retain(|t| !remove.contains(t));
+            for tag in &add {
+                if !manifest.tags.contains(tag) {
+                    manifest.tags.push(tag.clone());
+                }
+            }
+            let id = manifest.id.clone();
+            if manifest.tags != before {
+                self.update_service(&id, manifest.clone()).await?;
+            }
+            let status = self.status(&id).await?;
+            summaries.push(ServiceSummary {
+                id,
+                name: manifest.name,
+                started_at: status.started_at(),
+                state: status.state,
+                tags: manifest.tags,
+                group: manifest.group,
+                order: manifest.order,
+                created_at: manifest.created_at,
+            });
+        }
+        Ok(summaries)
+    }
+
     /// 更新服务的分组
     #[instrument(skip(self))]
     pub async fn update_service_group(&self, id: &str, group: Option<String>) -> Result<()> {
@@ -170,3 +371,93 @@ impl ServiceManager {
         Ok(())
     }
 }
+
+fn action_result(id: &str, result: Result<ServiceStatus>) -> GroupActionResult {
+    match result {
+        Ok(status) => GroupActionResult {
+            id: id.to_string(),
+            ok: true,
+            status: Some(status),
+            error: None,
+        },
+        Err(e) => GroupActionResult {
+            id: id.to_string(),
+            ok: false,
+            status: None,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+/// 校验 `id` 可以挂到 `parent` 下：父分组存在，且不是 `id` 自身或其后代
+fn validate_parent(groups: &[ServiceGroup], id: &str, parent: &str) -> Result<()> {
+    let mut current = Some(parent);
+    while let Some(gid) = current {
+        if gid == id {
+            return Err(ServiceError::InvalidGroup(format!(
+                "group {id} cannot be nested under {parent}: it would create a cycle"
+            )));
+        }
+        current = match groups.iter().find(|g| g.id == gid) {
+            Some(group) => group.parent.as_deref(),
+            None if gid == parent => {
+                return Err(ServiceError::InvalidGroup(format!(
+                    "parent group not found: {parent}"
+                )))
+            }
+            None => None,
+        };
+    }
+    Ok(())
+}
+
+/// `parent` 下新分组的顺序：排在现有兄弟分组之后
+fn next_sibling_order(groups: &[ServiceGroup], parent: Option<&str>) -> i32 {
+    groups
+        .iter()
+        .filter(|g| g.parent.as_deref() == parent)
+        .map(|g| g.order)
+        .max()
+        .unwrap_or(-1)
+        + 1
+}
+
+/// 按树的先序排列：兄弟分组按 `order`，父分组缺失或成环的分组当作顶层处理
+pub(super) fn tree_order(mut groups: Vec<ServiceGroup>) -> Vec<ServiceGroup> {
+    groups.sort_by_key(|g| g.order);
+    let mut ordered: Vec<ServiceGroup> = Vec::with_capacity(groups.len());
+    let mut placed = vec![false; groups.len()];
+
+    fn visit(
+        index: usize,
+        groups: &[ServiceGroup],
+        placed: &mut [bool],
+        ordered: &mut Vec<ServiceGroup>,
+    ) {
+        placed[index] = true;
+        ordered.push(groups[index].clone());
+        let id = groups[index].id.as_str();
+        for child in 0..groups.len() {
+            if !placed[child] && groups[child].parent.as_deref() == Some(id) {
+                visit(child, groups, placed, ordered);
+            }
+        }
+    }
+
+    let is_root = |g: &ServiceGroup| match g.parent.as_deref() {
+        None => true,
+        Some(parent) => !groups.iter().any(|other| other.id == parent),
+    };
+    for index in 0..groups.len() {
+        if !placed[index] && is_root(&groups[index]) {
+            visit(index, &groups, &mut placed, &mut ordered);
+        }
+    }
+    // 成环的分组没有根，依次当作顶层补上
+    for index in 0..groups.len() {
+        if !placed[index] {
+            visit(index, &groups, &mut placed, &mut ordered);
+        }
+    }
+    ordered
+}
@@ -0,0 +1,147 @@
+//! HTTP 健康检查：按 manifest 配置周期探测，维护健康结论并在持续不健康时触发重启。
+
+use super::*;
+use crate::manifest::HealthCheck;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::time::{Duration, Instant};
+
+/// 连续探测结果计数，按阈值得出健康结论
+#[derive(Debug, Default)]
+struct ProbeCounter {
+    successes: u32,
+    failures: u32,
+}
+
+impl ProbeCounter {
+    /// 记录一次探测结果；达到阈值时返回新的结论
+    fn record(&mut self, ok: bool, check: &HealthCheck) -> Option<bool> {
+        if ok {
+            self.successes += 1;
+            self.failures = 0;
+            (self.successes >= check.healthy_threshold.max(1)).then_some(true)
+        } else {
+            self.failures += 1;
+            self.successes = 0;
+            (self.failures >= check.unhealthy_threshold.max(1)).then_some(false)
+        }
+    }
+}
+
+impl ServiceManager {
+    /// 启动健康检查任务：进程退出（pid 不再属于该服务）或主动停止后结束。
+    pub(super) fn spawn_health_checker(
+        &self,
+        id: String,
+        pid: u32,
+        check: HealthCheck,
+        auto_restart: bool,
+        healthy: Arc<StdMutex<Option<bool>>>,
+        stop_flag: Arc<AtomicBool>,
+    ) {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let client = match reqwest::Client::builder()
+                .timeout(Duration::from_secs(check.timeout_secs.max(1)))
+                .redirect(reqwest::redirect::Policy::none())
+                .build()
+            {
+                Ok(c) => c,
+                Err(e) => {
+                    tracing::warn!(service_id = %id, error = %e, "failed to build health check client");
+                    return;
+                }
+            };
+            let interval = Duration::from_secs(check.interval_secs.max(1));
+            let mut counter = ProbeCounter::default();
+            let mut unhealthy_since: Option<Instant> = None;
+
+            loop {
+                tokio::time::sleep(interval).await;
+                if stop_flag.load(Ordering::Relaxed) || !manager.is_current_pid(&id, pid).await {
+                    break;
+                }
+
+                let ok = match client.get(&check.url).send().await {
+                    Ok(resp) => resp.status().is_success() || resp.status().is_redirection(),
+                    Err(_) => false,
+                };
+                let Some(verdict) = counter.record(ok, &check) else {
+                    continue;
+                };
+
+                let previous = {
+                    let mut cell = healthy.lock().unwrap_or_else(|e| e.into_inner());
+                    cell.replace(verdict)
+                };
+                if previous != Some(verdict) {
+                    tracing::info!(service_id = %id, healthy = verdict, "health check state changed");
+                    manager.emit_health_changed(&id, verdict);
+                }
+
+                if verdict {
+                    unhealthy_since = None;
+                    continue;
+                }
+                let since = *unhealthy_since.get_or_insert_with(Instant::now);
+                if auto_restart
+                    && since.elapsed() >= Duration::from_secs(check.unhealthy_restart_secs)
+                {
+                    tracing::warn!(
+                        service_id = %id,
+                        "unhealthy for {}s, restarting",
+                        since.elapsed().as_secs()
+                    );
+                    if let Err(e) = manager.restart(&id).await {
+                        tracing::error!("health restart failed for {}: {}", id, e);
+                    }
+                    // 新进程会启动新的探测任务
+                    break;
+                }
+            }
+        });
+    }
+
+    /// 该服务当前在 runtime 中登记的进程是否仍是 `pid`
+    async fn is_current_pid(&self, id: &str, pid: u32) -> bool {
+        let guard = self.runtime.lock().await;
+        guard.get(id).map(|h| h.pid) == Some(pid)
+    }
+
+    /// 读取运行中服务的健康结论
+    pub(super) async fn health_of(&self, id: &str) -> Option<bool> {
+        let guard = self.runtime.lock().await;
+        let entry = guard.get(id)?;
+        let healthy = *entry.healthy.lock().unwrap_or_else(|e| e.into_inner());
+        healthy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(healthy_threshold: u32, unhealthy_threshold: u32) -> HealthCheck {
+        HealthCheck {
+            url: "http://127.0.0.1:1/health".into(),
+            interval_secs: 1,
+            timeout_secs: 1,
+            healthy_threshold,
+            unhealthy_threshold,
+            unhealthy_restart_secs: 60,
+        }
+    }
+
+    #[test]
+    fn probe_counter_applies_thresholds() {
+        let check = check(2, 3);
+        let mut counter = ProbeCounter::default();
+        assert_eq!(counter.record(true, &check), None);
+        assert_eq!(counter.record(true, &check), Some(true));
+        assert_eq!(counter.record(false, &check), None);
+        assert_eq!(counter.record(false, &check), None);
+        assert_eq!(counter.record(false, &check), Some(false));
+        // 一次成功会重置失败计数
+        assert_eq!(counter.record(true, &check), None);
+        assert_eq!(counter.record(false, &check), None);
+    }
+}
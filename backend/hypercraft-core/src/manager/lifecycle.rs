@@ -1,11 +1,13 @@
 //! 服务生命周期管理：启动、停止、重启等核心操作。
 
 use super::*;
+use crate::models::StateChangeReason;
+use futures::future::join_all;
 use portable_pty::{native_pty_system, CommandBuilder, PtySize};
 use std::fs::{self, File, OpenOptions};
 use std::io::{Read, Seek, SeekFrom, Write};
-use std::path::Path;
 use std::sync::atomic::{AtomicBool, Ordering};
+use sysinfo::Signal;
 use tokio::task;
 use tokio::time::Duration;
 use tracing::instrument;
@@ -13,16 +15,83 @@ use tracing::instrument;
 /// 日志文件最大大小（2MB），超过此值触发截断
 const LOG_MAX_SIZE: u64 = 2 * 1024 * 1024;
 /// 截断后保留的大小（1MB）
-const LOG_RETAIN_SIZE: u64 = 1 * 1024 * 1024;
+const LOG_RETAIN_SIZE: u64 = 1024 * 1024;
+/// 默认保留的滚动日志分段数
+const DEFAULT_LOG_KEEP_FILES: u32 = 3;
 /// 每写入多少行检查一次文件大小
 const LOG_CHECK_INTERVAL: u32 = 100;
 /// PTY 默认宽度
 const DEFAULT_PTY_COLS: u16 = 155;
+/// 自动重启默认初始退避
+const DEFAULT_RESTART_BACKOFF_MS: u64 = 1000;
+/// 自动重启退避上限
+const MAX_RESTART_BACKOFF_MS: u64 = 5 * 60 * 1000;
+/// 默认重启计数滑动窗口
+const DEFAULT_RESTART_WINDOW_SECS: u64 = 60;
+/// 启动宽限期上限
+const MAX_START_GRACE_MS: u64 = 60 * 1000;
+/// 宽限期内检查进程是否已退出的间隔
+const START_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// 配置了 `kill_signal` 但未设置 `kill_timeout_secs` 时的等待时间
+const DEFAULT_KILL_TIMEOUT_SECS: u64 = 10;
+
+/// 自动重启时间戳记录，跨越重启链传递；手动 start/restart 会重新创建以清零计数
+type RestartHistory = Arc<StdMutex<Vec<std::time::Instant>>>;
 
 impl ServiceManager {
     /// 刷新状态：优先查看 runtime 句柄，其次 pid 文件。
     #[instrument(skip(self))]
     pub async fn status(&self, id: &str) -> Result<ServiceStatus> {
+        self.status_with(id, None).await
+    }
+
+    /// 批量刷新所有服务状态：整批共用一次全量进程快照。
+    #[instrument(skip(self))]
+    pub async fn refresh_all_status(&self) -> Result<HashMap<String, ServiceStatus>> {
+        let ids = self.service_ids().await?;
+        let snapshot = self.process_snapshot();
+        let futures = ids.into_iter().map(|id| {
+            let snapshot = snapshot.clone();
+            async move {
+                let status = self.status_with(&id, Some(&snapshot)).await?;
+                Ok::<_, ServiceError>((id, status))
+            }
+        });
+        join_all(futures).await.into_iter().collect()
+    }
+
+    /// 启动时对账：pid 文件指向的进程仍存活时按运行中接管（不持有 runtime 句柄），
+    /// 已退出的则清理过期 pid 文件。
+    ///
+    /// 返回被接管的服务 ID；这些服务可以查询状态、停止与发送信号。
+    /// 分离模式的服务会重新挂上日志与 FIFO，可以继续 attach，其余的无法 attach。
+    #[instrument(skip(self))]
+    pub async fn reconcile(&self) -> Result<Vec<String>> {
+        let statuses = self.refresh_all_status().await?;
+        let owned: HashSet<String> = self.runtime.lock().await.keys().cloned().collect();
+        let mut adopted: Vec<(String, u32)> = statuses
+            .into_iter()
+            .filter(|(id, status)| {
+                matches!(status.state, ServiceState::Running) && !owned.contains(id)
+            })
+            .filter_map(|(id, status)| status.pid.map(|pid| (id, pid)))
+            .collect();
+        adopted.sort();
+        for (id, pid) in &adopted {
+            tracing::info!(service_id = %id, pid, "adopted running process from pid file");
+            if let Err(e) = self.adopt_detached(id, *pid).await {
+                tracing::warn!(service_id = %id, error = %e, "failed to reattach detached service");
+            }
+        }
+        Ok(adopted.into_iter().map(|(id, _)| id).collect())
+    }
+
+    /// 状态查询实现；`snapshot` 为批量查询时共享的进程快照。
+    pub(super) async fn status_with(
+        &self,
+        id: &str,
+        snapshot: Option<&HashMap<u32, u64>>,
+    ) -> Result<ServiceStatus> {
         // 优先检查 runtime 句柄，并确认进程仍存活；若已退出则清理缓存。
         // 注意：必须在独立作用域中获取锁再取出 pid，避免 MutexGuard 跨 await 导致死锁。
         let runtime_pid = {
@@ -30,12 +99,14 @@ impl ServiceManager {
             guard.get(id).map(|h| h.pid)
         };
         if let Some(runtime_pid) = runtime_pid {
-            if let Some((alive, uptime)) = self.process_alive(runtime_pid) {
+            if let Some((alive, uptime)) = self.process_alive_in(runtime_pid, snapshot) {
                 if alive {
                     return Ok(ServiceStatus {
                         state: ServiceState::Running,
                         pid: Some(runtime_pid),
                         uptime_ms: uptime,
+                        healthy: self.health_of(id).await,
+                        ..Default::default()
                     });
                 }
             }
@@ -44,28 +115,45 @@ impl ServiceManager {
         }
         let pid_opt = self.read_pid(id)?;
         if let Some(pid) = pid_opt {
-            if let Some((alive, uptime)) = self.process_alive(pid) {
+            if let Some((alive, uptime)) = self.process_alive_in(pid, snapshot) {
                 if alive {
                     return Ok(ServiceStatus {
                         state: ServiceState::Running,
                         pid: Some(pid),
                         uptime_ms: uptime,
+                        ..Default::default()
                     });
                 }
             }
             // stale pid file
             let _ = fs::remove_file(self.pid_path(id));
         }
+        let last_exit = self.read_last_exit(id);
         Ok(ServiceStatus {
             state: ServiceState::Stopped,
             pid: None,
             uptime_ms: None,
+            last_exit_code: last_exit.as_ref().map(|e| e.code),
+            last_exit_signal: last_exit.as_ref().and_then(|e| e.signal.clone()),
+            last_exit_at: last_exit.map(|e| e.at),
+            ..Default::default()
         })
     }
 
     /// 启动服务：使用 PTY 收发，并持续写日志以便 tail。
     #[instrument(skip(self))]
     pub async fn start(&self, id: &str) -> Result<ServiceStatus> {
+        self.start_with_history(id, RestartHistory::default(), StateChangeReason::Started)
+            .await
+    }
+
+    /// 启动服务并沿用给定的自动重启记录
+    async fn start_with_history(
+        &self,
+        id: &str,
+        history: RestartHistory,
+        reason: StateChangeReason,
+    ) -> Result<ServiceStatus> {
         let manifest = self.load_manifest(id).await?;
         let current = self.status(id).await?;
         if matches!(current.state, ServiceState::Running) {
@@ -81,47 +169,98 @@ impl ServiceManager {
             let _ = fs::write(&log_path, "");
         }
 
+        // 同时确认 cwd 仍存在、含路径的命令仍可找到
         self.enforce_policy(&manifest)?;
-        if let Some(cwd) = manifest.cwd.as_ref() {
-            if !Path::new(cwd).exists() {
-                return Err(ServiceError::SpawnFailed(format!(
-                    "working directory not found: {cwd}"
-                )));
+        // 未设置 cwd 时为服务准备独立的默认工作目录
+        if manifest.cwd.is_none() {
+            if let Some(dir) = self.default_cwd(id) {
+                fs::create_dir_all(&dir).map_err(|e| storage_error(&dir, e))?;
             }
         }
 
-        let (mut child, master_pty, reader, writer, pid) =
-            self.spawn_pty_process(&manifest).await?;
+        let stop_requested = Arc::new(AtomicBool::new(false));
+        let healthy = Arc::new(StdMutex::new(None));
+        let (mut child, handles) = if manifest.detached {
+            // 分离模式：子进程自行写日志，attach 通过日志尾随与 FIFO 对接
+            let child = self.spawn_detached_process(&manifest)?;
+            let handles =
+                self.detached_handles(id, child.id(), stop_requested.clone(), healthy.clone())?;
+            let child: Box<dyn portable_pty::Child + Send + Sync> = Box::new(child);
+            (child, handles)
+        } else {
+            let (child, pty, reader, stderr_reader, writer, pid) = if manifest.tty {
+                let (child, master_pty, reader, writer, pid) =
+                    self.spawn_pty_process(&manifest).await?;
+                (child, Some(master_pty), reader, None, writer, pid)
+            } else {
+                // 管道模式：不分配 PTY，输出仍走下方同一套日志与广播
+                let piped = self.spawn_piped_process(&manifest)?;
+                let pid = piped.child.id();
+                let child: Box<dyn portable_pty::Child + Send + Sync> = Box::new(piped.child);
+                (child, None, piped.output, piped.stderr, piped.stdin, pid)
+            };
 
-        let (out_tx, _) = broadcast::channel(200);
-        let (in_tx, in_rx) = mpsc::channel::<Vec<u8>>(64);
+            let out_tx = self.output_channel();
+            let output_cursor = logs::OutputCursor::default();
+            let stderr_cursor = logs::OutputCursor::default();
+            let (in_tx, in_rx) = mpsc::channel::<Vec<u8>>(64);
 
-        // 输出：写入日志并广播给 attach。
-        self.spawn_output_handler(reader, log_path.clone(), out_tx.clone());
+            // 输出：写入日志并广播给 attach。
+            let keep_files = manifest.log_keep_files.unwrap_or(DEFAULT_LOG_KEEP_FILES);
+            self.spawn_output_handler(
+                reader,
+                log_path.clone(),
+                out_tx.clone(),
+                output_cursor.clone(),
+                keep_files,
+            );
 
-        // 输入：接收 attach 写入 PTY。
-        self.spawn_input_handler(writer, in_rx);
+            // 单独捕获的 stderr：写入 stderr.log 并走独立广播
+            let err_tx = stderr_reader.map(|stderr_reader| {
+                let err_tx = self.output_channel();
+                let stderr_path = self.stderr_log_path(id);
+                if manifest.clear_log_on_start {
+                    let _ = fs::write(&stderr_path, "");
+                }
+                self.spawn_output_handler(
+                    stderr_reader,
+                    stderr_path,
+                    err_tx.clone(),
+                    stderr_cursor.clone(),
+                    keep_files,
+                );
+                err_tx
+            });
 
-        let stop_requested = Arc::new(AtomicBool::new(false));
+            // 输入：接收 attach 写入 PTY。
+            self.spawn_input_handler(writer, in_rx);
+
+            let handles = RuntimeHandles {
+                pid,
+                input: in_tx,
+                output: out_tx,
+                output_cursor,
+                stderr: err_tx,
+                stderr_cursor,
+                pty,
+                stop_requested: stop_requested.clone(),
+                healthy: healthy.clone(),
+            };
+            (child, handles)
+        };
+        let pid = handles.pid;
         {
             let mut guard = self.runtime.lock().await;
-            guard.insert(
-                id.to_string(),
-                RuntimeHandles {
-                    pid,
-                    input: in_tx.clone(),
-                    output: out_tx.clone(),
-                    pty: master_pty,
-                    stop_requested: stop_requested.clone(),
-                },
-            );
+            guard.insert(id.to_string(), handles);
         }
 
         self.write_pid(id, pid)?;
+        self.record_start(id, reason == StateChangeReason::AutoRestart);
 
-        // 若子进程在极短时间内退出，视为启动失败并清理。
-        tokio::time::sleep(Duration::from_millis(300)).await;
-        if let Ok(Some(status)) = child.try_wait() {
+        // 若子进程在宽限期内退出，视为启动失败并清理。
+        if let Some(status) = wait_start_grace(&mut child, manifest.start_grace_ms).await {
+            self.write_last_exit(id, &status);
+            self.record_exit(id, Duration::ZERO, status.exit_code() != 0);
             let _ = fs::remove_file(self.pid_path(id));
             let mut guard = self.runtime.lock().await;
             guard.remove(id);
@@ -138,6 +277,17 @@ impl ServiceManager {
             )));
         }
 
+        if let Some(check) = manifest.health_check.clone() {
+            self.spawn_health_checker(
+                id.to_string(),
+                pid,
+                check,
+                manifest.auto_restart,
+                healthy,
+                stop_requested.clone(),
+            );
+        }
+
         // 避免僵尸进程：后台等待并清理 runtime，支持自动重启。
         self.spawn_wait_handler(
             child,
@@ -145,18 +295,29 @@ impl ServiceManager {
             log_path,
             manifest.auto_restart,
             stop_requested,
+            history,
         );
+        self.emit_state_changed(id, ServiceState::Running, reason, Some(pid), None);
 
         Ok(ServiceStatus {
             state: ServiceState::Running,
             pid: Some(pid),
             uptime_ms: Some(0),
+            ..Default::default()
         })
     }
 
     /// 优雅关闭服务：发送配置的关闭命令（如 "stop"），等待进程自行退出
     #[instrument(skip(self))]
     pub async fn shutdown(&self, id: &str) -> Result<ServiceStatus> {
+        self.send_shutdown(id).await.map(|(status, _)| status)
+    }
+
+    /// 发送关闭命令；配置了 `shutdown_timeout_secs` 时同时启动看守任务并返回其句柄。
+    async fn send_shutdown(
+        &self,
+        id: &str,
+    ) -> Result<(ServiceStatus, Option<task::JoinHandle<Result<StopOutcome>>>)> {
         let manifest = self.load_manifest(id).await?;
         let status = self.status(id).await?;
         if !matches!(status.state, ServiceState::Running) {
@@ -182,16 +343,48 @@ impl ServiceManager {
             let _ = tx.send(cmd_with_newline.into_bytes()).await;
         }
 
-        Ok(ServiceStatus {
-            state: ServiceState::Running, // 还在运行，等待自行退出
-            pid: status.pid,
-            uptime_ms: status.uptime_ms,
-        })
+        // 配置了超时则在后台看守，超时仍存活就强制终止
+        let watchdog = match (manifest.shutdown_timeout_secs, status.pid) {
+            (Some(secs), Some(pid)) => {
+                Some(self.spawn_shutdown_watchdog(id.to_string(), pid, Duration::from_secs(secs)))
+            }
+            _ => None,
+        };
+
+        Ok((
+            ServiceStatus {
+                state: ServiceState::Running, // 还在运行，等待自行退出
+                pid: status.pid,
+                uptime_ms: status.uptime_ms,
+                ..Default::default()
+            },
+            watchdog,
+        ))
     }
 
-    /// 强制终止服务：直接杀进程
+    /// 终止服务：配置了 `kill_signal` 时先发送该信号，等待 `kill_timeout_secs` 仍存活再 SIGKILL；
+    /// 否则直接 SIGKILL。
+    ///
+    /// 先发信号时返回的 `stop_outcome` 标明进程是自行退出还是被强杀。
     #[instrument(skip(self))]
     pub async fn kill(&self, id: &str) -> Result<ServiceStatus> {
+        // manifest 缺失（只剩 pid 文件）或信号就是 KILL 时按默认直接强杀
+        let graceful = self.load_manifest(id).await.ok().and_then(|manifest| {
+            let signal = parse_signal(manifest.kill_signal.as_deref()?)?;
+            let timeout = manifest
+                .kill_timeout_secs
+                .unwrap_or(DEFAULT_KILL_TIMEOUT_SECS);
+            (signal != Signal::Kill).then(|| (signal, Duration::from_secs(timeout)))
+        });
+        self.kill_with(id, graceful).await
+    }
+
+    /// `graceful` 为 None 时直接强杀进程树
+    async fn kill_with(
+        &self,
+        id: &str,
+        graceful: Option<(Signal, Duration)>,
+    ) -> Result<ServiceStatus> {
         // pid 文件可能已被清理，但 runtime 仍缓存（或反之），因此两者都要尝试。
         let (runtime_pid, stop_flag) = {
             let guard = self.runtime.lock().await;
@@ -216,8 +409,33 @@ impl ServiceManager {
             guard.remove(id);
         }
 
+        let outcome = match graceful {
+            // 信号投递失败（如当前平台不支持）时不再等待，直接强杀
+            Some((signal, timeout)) => {
+                let exited =
+                    self.kill_process(pid, signal) && self.wait_for_exit(pid, timeout).await;
+                if !exited {
+                    tracing::warn!(
+                        service_id = %id,
+                        pid,
+                        "{} did not stop the process within {}s, sending SIGKILL",
+                        signal,
+                        timeout.as_secs()
+                    );
+                }
+                Some(if exited {
+                    StopOutcome::Graceful
+                } else {
+                    StopOutcome::Forced
+                })
+            }
+            None => None,
+        };
+
         // 若进程已退出或 pid 已经失效，则视为幂等成功。
-        let _ = self.kill_process(pid);
+        if outcome != Some(StopOutcome::Graceful) {
+            let _ = self.kill_process(pid, Signal::Kill);
+        }
 
         // 等待进程退出，最多等待 1 秒
         let mut attempts = 0;
@@ -234,23 +452,49 @@ impl ServiceManager {
         }
 
         let _ = fs::remove_file(self.pid_path(id));
+        self.emit_state_changed(
+            id,
+            ServiceState::Stopped,
+            StateChangeReason::Stopped,
+            Some(pid),
+            None,
+        );
 
         Ok(ServiceStatus {
             state: ServiceState::Stopped,
             pid: None,
             uptime_ms: None,
+            stop_outcome: outcome,
+            ..Default::default()
         })
     }
 
-    /// 停止服务：优先优雅关闭，如果没配置关闭命令则强制终止
+    /// 停止服务：优先优雅关闭，如果没配置关闭命令则按 [`kill`](Self::kill) 终止（含 `kill_signal`）
+    ///
+    /// 同时配置了 `shutdown_timeout_secs` 时等待进程退出，超时强制终止，
+    /// 返回的 `stop_outcome` 标明是优雅退出还是被强杀。
     #[instrument(skip(self))]
     pub async fn stop(&self, id: &str) -> Result<ServiceStatus> {
         let manifest = self.load_manifest(id).await?;
-        if manifest.shutdown_command.is_some() {
-            self.shutdown(id).await
-        } else {
-            self.kill(id).await
+        if manifest.shutdown_command.is_none() {
+            return self.kill(id).await;
         }
+        let (status, watchdog) = self.send_shutdown(id).await?;
+        let Some(watchdog) = watchdog else {
+            return Ok(status);
+        };
+        // 超时与强杀都由看守任务处理，这里只等它的结论
+        let outcome = watchdog
+            .await
+            .map_err(|e| ServiceError::Other(format!("shutdown watchdog failed: {e}")))??;
+
+        Ok(ServiceStatus {
+            state: ServiceState::Stopped,
+            pid: None,
+            uptime_ms: None,
+            stop_outcome: Some(outcome),
+            ..Default::default()
+        })
     }
 
     /// Restart：先停后启（停失败则报错）。
@@ -263,27 +507,42 @@ impl ServiceManager {
         self.start(id).await
     }
 
-    /// 停止所有正在运行的服务（用于 shutdown）
+    /// 停止所有正在运行的服务（用于 shutdown）；分离模式的服务保持运行
     #[instrument(skip(self))]
     pub async fn stop_all_services(&self) -> Result<()> {
         let services = self.list_services().await?;
-        let running: Vec<_> = services
-            .into_iter()
-            .filter(|s| s.state == ServiceState::Running)
-            .collect();
+        let mut running = Vec::new();
+        for summary in services {
+            if summary.state != ServiceState::Running {
+                continue;
+            }
+            let detached = self
+                .load_manifest(&summary.id)
+                .await
+                .map(|m| m.detached)
+                .unwrap_or(false);
+            if detached {
+                tracing::info!("leaving detached service running: {}", summary.id);
+            } else {
+                running.push(summary);
+            }
+        }
 
         if running.is_empty() {
             return Ok(());
         }
 
         tracing::info!("stopping {} running services...", running.len());
+        let stopping: HashSet<String> = running.iter().map(|s| s.id.clone()).collect();
 
-        for summary in running {
+        // 并发停止，避免多个服务的关闭超时串行累加
+        let stops = running.into_iter().map(|summary| async move {
             tracing::info!("stopping service: {}", summary.id);
             if let Err(e) = self.stop(&summary.id).await {
                 tracing::warn!("failed to stop service {}: {}", summary.id, e);
             }
-        }
+        });
+        join_all(stops).await;
 
         // 等待所有服务停止，最多等待 5 秒
         let start = tokio::time::Instant::now();
@@ -293,7 +552,7 @@ impl ServiceManager {
             let services = self.list_services().await?;
             let still_running = services
                 .iter()
-                .filter(|s| s.state == ServiceState::Running)
+                .filter(|s| s.state == ServiceState::Running && stopping.contains(&s.id))
                 .count();
 
             if still_running == 0 {
@@ -329,44 +588,42 @@ impl ServiceManager {
         Box<dyn Write + Send>,
         u32,
     )> {
+        // 优先沿用 attach 端最近一次调整的尺寸
+        let (rows, cols) = self
+            .last_pty_size(&manifest.id)
+            .unwrap_or((manifest.pty_rows.clamp(5, 500), DEFAULT_PTY_COLS));
         let pty_system = native_pty_system();
         let pair = pty_system
             .openpty(PtySize {
-                rows: manifest.pty_rows.clamp(5, 500),
-                cols: DEFAULT_PTY_COLS,
+                rows,
+                cols,
                 pixel_width: 0,
                 pixel_height: 0,
             })
             .map_err(|e| ServiceError::SpawnFailed(e.to_string()))?;
 
-        // 如果指定了 run_as 用户（仅 Linux），使用 sudo -u 包装命令
-        #[cfg(target_os = "linux")]
-        let (actual_command, actual_args) = if let Some(ref user) = manifest.run_as {
-            let mut sudo_args = vec!["-u".to_string(), user.clone(), manifest.command.clone()];
-            sudo_args.extend(manifest.args.clone());
-            ("sudo".to_string(), sudo_args)
-        } else {
-            (manifest.command.clone(), manifest.args.clone())
-        };
-        #[cfg(not(target_os = "linux"))]
-        let (actual_command, actual_args) = (manifest.command.clone(), manifest.args.clone());
-
+        let (actual_command, actual_args) = command_line(manifest);
         let mut cmd = CommandBuilder::new(&actual_command);
         cmd.args(actual_args);
-        if let Some(cwd) = manifest.cwd.as_ref() {
+        if let Some(cwd) = self.effective_cwd(manifest) {
             cmd.cwd(cwd);
         }
-        for (k, v) in manifest.env.iter() {
+        // 引用与 env_file 在此解析，结果只传给子进程，不落盘
+        for (k, v) in self.spawn_env(manifest)? {
             cmd.env(k, v);
         }
 
-        let child = pair
+        let mut child = pair
             .slave
             .spawn_command(cmd)
             .map_err(|e| ServiceError::SpawnFailed(e.to_string()))?;
         let pid = child
             .process_id()
             .ok_or_else(|| ServiceError::SpawnFailed("missing pid".into()))?;
+        if let Err(e) = self.apply_resource_limits(manifest, pid) {
+            let _ = child.kill();
+            return Err(e);
+        }
 
         let reader = pair
             .master
@@ -386,6 +643,8 @@ impl ServiceManager {
         mut reader: Box<dyn Read + Send>,
         log_path: std::path::PathBuf,
         out_tx: broadcast::Sender<Vec<u8>>,
+        cursor: logs::OutputCursor,
+        keep_files: u32,
     ) {
         task::spawn_blocking(move || {
             let mut buf = [0u8; 4096];
@@ -394,22 +653,28 @@ impl ServiceManager {
                 .append(true)
                 .open(&log_path)
                 .ok();
+            cursor.advance_to(log_file.as_mut());
             // 写入字节计数，用于定期检查文件大小
             let mut byte_count: u64 = 0;
             loop {
                 match reader.read(&mut buf) {
                     Ok(0) => break,
                     Ok(n) => {
-                        // 广播原始数据给实时 attach
-                        let _ = out_tx.send(buf[..n].to_vec());
-                        // 直接写入原始数据到日志（不过滤，保留所有控制序列）
-                        if let Some(file) = log_file.as_mut() {
-                            let _ = file.write_all(&buf[..n]);
-                            let _ = file.flush();
-                            byte_count += n as u64;
+                        {
+                            // 先写日志再广播，都在游标锁内完成，follow 的起点才与广播对齐
+                            let mut pos = cursor.lock();
+                            // 直接写入原始数据到日志（不过滤，保留所有控制序列）
+                            if let Some(file) = log_file.as_mut() {
+                                let _ = file.write_all(&buf[..n]);
+                                let _ = file.flush();
+                                byte_count += n as u64;
+                                *pos = file.stream_position().unwrap_or(*pos);
+                            }
+                            // 广播原始数据给实时 attach
+                            let _ = out_tx.send(buf[..n].to_vec());
                         }
 
-                        // 定期检查文件大小，超限则截断
+                        // 定期检查文件大小，超限则滚动（keep_files 为 0 时原地截断）
                         if byte_count >= LOG_CHECK_INTERVAL as u64 * 100 {
                             byte_count = 0;
                             let need_truncate = log_file
@@ -419,12 +684,17 @@ impl ServiceManager {
                                 .unwrap_or(false);
                             if need_truncate {
                                 drop(log_file.take());
-                                truncate_log_file(&log_path, LOG_RETAIN_SIZE);
+                                if keep_files == 0 {
+                                    truncate_log_file(&log_path, LOG_RETAIN_SIZE);
+                                } else {
+                                    rotate_log_files(&log_path, keep_files);
+                                }
                                 log_file = OpenOptions::new()
                                     .create(true)
                                     .append(true)
                                     .open(&log_path)
                                     .ok();
+                                cursor.advance_to(log_file.as_mut());
                             }
                         }
                     }
@@ -435,7 +705,7 @@ impl ServiceManager {
     }
 
     /// 启动输入处理任务：接收 attach 写入 PTY
-    fn spawn_input_handler(
+    pub(super) fn spawn_input_handler(
         &self,
         mut writer: Box<dyn Write + Send>,
         mut in_rx: mpsc::Receiver<Vec<u8>>,
@@ -458,14 +728,17 @@ impl ServiceManager {
         log_path: std::path::PathBuf,
         auto_restart: bool,
         stop_flag: Arc<AtomicBool>,
+        history: RestartHistory,
     ) {
         let runtime = self.runtime.clone();
         let pid_path = self.pid_path(&id);
         let manager = self.clone();
+        let pid = child.process_id();
+        let started = std::time::Instant::now();
 
         task::spawn(async move {
             let log_path_wait = log_path.clone();
-            let _wait_result = task::spawn_blocking(move || {
+            let wait_result = task::spawn_blocking(move || {
                 let result = child.wait();
                 // 记录退出状态，便于排查启动后瞬停。
                 if let Ok(status) = &result {
@@ -481,34 +754,248 @@ impl ServiceManager {
                 result
             })
             .await;
+            let exit_code = match &wait_result {
+                Ok(Ok(status)) => {
+                    manager.write_last_exit(&id, status);
+                    Some(status.exit_code() as i32)
+                }
+                _ => None,
+            };
 
-            let _ = tokio::fs::remove_file(&pid_path).await;
-            {
+            // 句柄可能已被 kill/status 移除，或已属于重启后的新进程（此时不能误删其句柄与 pid 文件）
+            let handle_pid = {
                 let mut map = runtime.lock().await;
-                map.remove(&id);
+                let current = map.get(&id).map(|h| h.pid);
+                if current.is_some() && current == pid {
+                    map.remove(&id);
+                }
+                current
+            };
+            let superseded = handle_pid.is_some() && handle_pid != pid;
+            if !superseded {
+                let _ = tokio::fs::remove_file(&pid_path).await;
             }
 
             // 自动重启：只有非主动停止且开启了 auto_restart 才重启
             let was_stopped = stop_flag.load(Ordering::Relaxed);
+            let reason = if was_stopped {
+                StateChangeReason::Stopped
+            } else if exit_code == Some(0) {
+                StateChangeReason::Exited
+            } else {
+                StateChangeReason::Crashed
+            };
+            manager.record_exit(&id, started.elapsed(), reason == StateChangeReason::Crashed);
+            // 句柄已不在且是主动停止，说明由 kill 清理，kill 已自行广播
+            let killed = handle_pid.is_none() && was_stopped;
+            if !superseded && !killed {
+                manager.emit_state_changed(&id, ServiceState::Stopped, reason, pid, exit_code);
+            }
             if auto_restart && !was_stopped {
-                tracing::info!("auto_restart enabled, restarting service: {}", id);
-                tokio::time::sleep(Duration::from_secs(1)).await;
-                manager.spawn_restart(id);
+                manager.spawn_restart(id, history).await;
             }
         });
     }
 
-    /// 内部自动重启方法
-    fn spawn_restart(&self, id: String) {
+    /// 等待进程退出，超时返回 false
+    async fn wait_for_exit(&self, pid: u32, timeout: Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let alive = self
+                .process_alive(pid)
+                .map(|(alive, _)| alive)
+                .unwrap_or(false);
+            if !alive {
+                return true;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+    }
+
+    /// 关闭超时后强制终止；仅当 pid 仍属于该服务时才动手，避免误杀重启后的新进程
+    async fn force_kill_after_timeout(&self, id: &str, pid: u32, secs: u64) -> Result<()> {
+        let current = self.status(id).await?;
+        if current.pid != Some(pid) {
+            return Err(ServiceError::NotRunning(id.to_string()));
+        }
+        tracing::warn!(service_id = %id, pid, "shutdown timed out after {}s, force killing", secs);
+        let _ = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.log_path(id))
+            .and_then(|mut f| {
+                writeln!(f, "shutdown timed out after {secs}s, force killing")?;
+                Ok(())
+            });
+        // 关闭命令已超时，不再走 kill_signal 的等待
+        self.kill_with(id, None).await.map(|_| ())
+    }
+
+    /// 启动关闭看守任务：超时后进程仍存活则强制终止，结果为优雅退出或被强杀。
+    ///
+    /// 句柄被丢弃（如 `shutdown` 立即返回）时任务照常运行。
+    fn spawn_shutdown_watchdog(
+        &self,
+        id: String,
+        pid: u32,
+        timeout: Duration,
+    ) -> task::JoinHandle<Result<StopOutcome>> {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            if manager.wait_for_exit(pid, timeout).await {
+                return Ok(StopOutcome::Graceful);
+            }
+            match manager
+                .force_kill_after_timeout(&id, pid, timeout.as_secs())
+                .await
+            {
+                // pid 已不属于该服务：原进程在超时判定后才退出
+                Ok(()) | Err(ServiceError::NotRunning(_)) => Ok(StopOutcome::Forced),
+                Err(e) => {
+                    tracing::error!("force kill after shutdown timeout failed for {}: {}", id, e);
+                    Err(e)
+                }
+            }
+        })
+    }
+
+    /// 内部自动重启方法：按窗口内重启次数指数退避，超过 max_restarts 则放弃并保持停止
+    async fn spawn_restart(&self, id: String, history: RestartHistory) {
+        let manifest = match self.load_manifest(&id).await {
+            Ok(m) => m,
+            Err(e) => {
+                tracing::error!("auto_restart failed for {}: {}", id, e);
+                return;
+            }
+        };
+        let window = Duration::from_secs(
+            manifest
+                .restart_window_secs
+                .unwrap_or(DEFAULT_RESTART_WINDOW_SECS),
+        );
+        let attempts = {
+            let mut times = history.lock().unwrap_or_else(|e| e.into_inner());
+            let now = std::time::Instant::now();
+            times.retain(|t| now.duration_since(*t) < window);
+            times.push(now);
+            times.len() as u32
+        };
+
+        if let Some(max) = manifest.max_restarts {
+            if attempts > max {
+                let msg = format!(
+                    "auto_restart gave up: {} restarts within {}s exceeds max_restarts={}",
+                    attempts - 1,
+                    window.as_secs(),
+                    max
+                );
+                tracing::warn!(service_id = %id, "{}", msg);
+                let _ = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(self.log_path(&id))
+                    .and_then(|mut f| {
+                        writeln!(f, "{msg}")?;
+                        Ok(())
+                    });
+                return;
+            }
+        }
+
+        let delay = restart_backoff(
+            manifest
+                .restart_backoff_ms
+                .unwrap_or(DEFAULT_RESTART_BACKOFF_MS),
+            attempts,
+        );
+        tracing::info!(
+            "auto_restart enabled, restarting service {} in {}ms (attempt {})",
+            id,
+            delay.as_millis(),
+            attempts
+        );
+
         let manager = self.clone();
         tokio::spawn(async move {
-            if let Err(e) = manager.start(&id).await {
+            tokio::time::sleep(delay).await;
+            // 等待期间被手动启动或删除则不再重启
+            match manager.status(&id).await {
+                Ok(status) if status.state == ServiceState::Stopped => {}
+                _ => return,
+            }
+            if let Err(e) = manager
+                .start_with_history(&id, history, StateChangeReason::AutoRestart)
+                .await
+            {
                 tracing::error!("auto_restart failed for {}: {}", id, e);
             }
         });
     }
 }
 
+/// 宽限期内轮询子进程：期间退出则返回退出状态，快速崩溃可尽早发现
+async fn wait_start_grace(
+    child: &mut Box<dyn portable_pty::Child + Send + Sync>,
+    grace_ms: u64,
+) -> Option<portable_pty::ExitStatus> {
+    let deadline =
+        tokio::time::Instant::now() + Duration::from_millis(grace_ms.min(MAX_START_GRACE_MS));
+    loop {
+        if let Ok(Some(status)) = child.try_wait() {
+            return Some(status);
+        }
+        let now = tokio::time::Instant::now();
+        if now >= deadline {
+            return None;
+        }
+        tokio::time::sleep(START_POLL_INTERVAL.min(deadline - now)).await;
+    }
+}
+
+/// 实际执行的命令行：指定 run_as 用户时（仅 Linux）用 sudo -u 包装
+pub(super) fn command_line(manifest: &ServiceManifest) -> (String, Vec<String>) {
+    #[cfg(target_os = "linux")]
+    if let Some(ref user) = manifest.run_as {
+        let mut sudo_args = vec!["-u".to_string(), user.clone(), manifest.command.clone()];
+        sudo_args.extend(manifest.args.clone());
+        return ("sudo".to_string(), sudo_args);
+    }
+    (manifest.command.clone(), manifest.args.clone())
+}
+
+/// 计算第 `attempt` 次（从 1 开始）自动重启的退避时长：base * 2^(attempt-1)，封顶 MAX_RESTART_BACKOFF_MS
+fn restart_backoff(base_ms: u64, attempt: u32) -> Duration {
+    let shift = attempt.saturating_sub(1).min(32);
+    let ms = base_ms
+        .saturating_mul(1u64 << shift)
+        .min(MAX_RESTART_BACKOFF_MS);
+    Duration::from_millis(ms)
+}
+
+/// 滚动日志：latest.log.N-1 → .N … latest.log → .1，并删除超出保留数的旧分段
+fn rotate_log_files(path: &std::path::Path, keep: u32) {
+    for n in (1..keep).rev() {
+        let from = super::logs::segment_path(path, n);
+        if from.exists() {
+            let _ = fs::rename(&from, super::logs::segment_path(path, n + 1));
+        }
+    }
+    let _ = fs::rename(path, super::logs::segment_path(path, 1));
+
+    // 保留数调小后遗留的分段
+    let mut n = keep + 1;
+    loop {
+        let stale = super::logs::segment_path(path, n);
+        if fs::remove_file(&stale).is_err() {
+            break;
+        }
+        n += 1;
+    }
+}
+
 /// 截断日志文件，保留末尾指定大小的内容
 fn truncate_log_file(path: &std::path::Path, retain_size: u64) {
     let Ok(mut file) = File::open(path) else {
@@ -535,13 +1022,7 @@ fn truncate_log_file(path: &std::path::Path, retain_size: u64) {
     }
     drop(file);
 
-    // 调整到行边界：跳过第一个不完整的行
-    let line_start = retained
-        .iter()
-        .position(|&b| b == b'\n')
-        .map(|i| i + 1)
-        .unwrap_or(0);
-    let retained = &retained[line_start..];
+    let retained = &retained[safe_cut_offset(&retained)..];
 
     // 覆写文件
     if let Ok(mut file) = File::create(path) {
@@ -549,3 +1030,86 @@ fn truncate_log_file(path: &std::path::Path, retain_size: u64) {
         let _ = file.write_all(retained);
     }
 }
+
+/// 截断后保留内容的安全起点，保证标记不会落在多字节字符或转义序列中间。
+///
+/// 优先跳到第一个完整行；没有换行时从第一个 ESC（新转义序列起点）开始；
+/// 两者都没有则只跳过开头的 UTF-8 续字节。
+fn safe_cut_offset(data: &[u8]) -> usize {
+    if let Some(i) = data.iter().position(|&b| b == b'\n') {
+        return i + 1;
+    }
+    if let Some(i) = data.iter().position(|&b| b == 0x1b) {
+        return i;
+    }
+    data.iter()
+        .position(|&b| b & 0xC0 != 0x80)
+        .unwrap_or(data.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotate_log_files_shifts_and_prunes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let latest = dir.path().join("latest.log");
+        for (name, body) in [
+            ("latest.log", "c"),
+            ("latest.log.1", "b"),
+            ("latest.log.2", "a"),
+            ("latest.log.3", "stale"),
+        ] {
+            fs::write(dir.path().join(name), body).unwrap();
+        }
+
+        rotate_log_files(&latest, 2);
+
+        assert!(!latest.exists());
+        assert_eq!(fs::read_to_string(dir.path().join("latest.log.1")).unwrap(), "c");
+        assert_eq!(fs::read_to_string(dir.path().join("latest.log.2")).unwrap(), "b");
+        assert!(!dir.path().join("latest.log.3").exists());
+    }
+
+    #[test]
+    fn truncate_log_file_cuts_at_safe_boundary() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("latest.log");
+
+        // 保留区间从 "中" 的第二个字节开始，且没有换行
+        fs::write(&path, "ab中文字").unwrap();
+        truncate_log_file(&path, 8);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "[... log truncated ...]\n文字"
+        );
+
+        // 保留区间从转义序列中间开始：丢弃残余参数，从下一个 ESC 续上
+        fs::write(&path, "xx\x1b[31mred\x1b[0m").unwrap();
+        truncate_log_file(&path, 11);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "[... log truncated ...]\n\x1b[0m"
+        );
+
+        fs::write(&path, "old line\npartial\nkept\n").unwrap();
+        truncate_log_file(&path, 12);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "[... log truncated ...]\nkept\n"
+        );
+    }
+
+    #[test]
+    fn restart_backoff_doubles_and_caps() {
+        assert_eq!(restart_backoff(1000, 1), Duration::from_millis(1000));
+        assert_eq!(restart_backoff(1000, 2), Duration::from_millis(2000));
+        assert_eq!(restart_backoff(1000, 4), Duration::from_millis(8000));
+        assert_eq!(
+            restart_backoff(1000, 40),
+            Duration::from_millis(MAX_RESTART_BACKOFF_MS)
+        );
+        assert_eq!(restart_backoff(0, 3), Duration::ZERO);
+    }
+}
@@ -1,99 +1,263 @@
 use super::*;
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use encoding_rs::Encoding;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
 use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncSeekExt, AsyncWriteExt};
 use tokio::sync::Mutex;
 
+/// 日志搜索命中的一行及其上下文
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogMatch {
+    /// 行号（从 1 开始）
+    pub line_number: usize,
+    /// 该行在 latest.log 中的起始字节偏移
+    pub offset: u64,
+    pub line: String,
+    /// 命中行之前的上下文
+    pub before: Vec<String>,
+    /// 命中行之后的上下文
+    pub after: Vec<String>,
+}
+
+/// 实时跟随时订阅者落后于输出广播（broadcast Lagged）的处理方式
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LagPolicy {
+    /// 插入 `[dropped N messages]` 提示后继续跟随广播
+    #[default]
+    Drop,
+    /// 从日志文件补读被丢弃的部分，之后改为轮询文件：输出连续，代价是轮询间隔的延迟
+    Resync,
+}
+
+/// 把实时日志的原始字节块解码为文本，跨块截断的多字节字符会留到下一块
+pub struct LogTextDecoder {
+    decoder: Option<encoding_rs::Decoder>,
+}
+
+impl LogTextDecoder {
+    pub fn decode(&mut self, chunk: &[u8]) -> String {
+        let Some(decoder) = self.decoder.as_mut() else {
+            return String::from_utf8_lossy(chunk).into_owned();
+        };
+        let capacity = decoder
+            .max_utf8_buffer_length(chunk.len())
+            .unwrap_or(chunk.len() * 3);
+        let mut text = String::with_capacity(capacity);
+        let _ = decoder.decode_to_string(chunk, &mut text, false);
+        text
+    }
+}
+
 impl ServiceManager {
-    /// 返回日志末尾的原始字节（用于 attach 回放）
-    pub fn tail_logs_raw(&self, id: &str, max_bytes: usize) -> Result<Vec<u8>> {
-        let path = self.log_path(id);
-        if !path.exists() || max_bytes == 0 {
-            return Ok(vec![]);
-        }
-        let mut file = File::open(path)?;
-        let meta = file.metadata()?;
-        let file_len = meta.len() as usize;
-        let read_size = max_bytes.min(file_len);
-        
-        if read_size == 0 {
-            return Ok(vec![]);
+    /// 按服务配置的 `log_encoding` 创建实时日志解码器；未配置时按有损 UTF-8 处理
+    pub fn log_text_decoder(&self, id: &str) -> LogTextDecoder {
+        LogTextDecoder {
+            decoder: self
+                .log_encoding(id)
+                .map(|encoding| encoding.new_decoder_without_bom_handling()),
         }
-        
-        // 从文件末尾读取
-        let start_pos = file_len.saturating_sub(read_size);
-        file.seek(SeekFrom::Start(start_pos as u64))?;
-        let mut buf = vec![0u8; read_size];
-        file.read_exact(&mut buf)?;
-        
-        Ok(buf)
     }
 
-    /// 返回日志末尾 N 行。
-    pub fn tail_logs(&self, id: &str, lines: usize) -> Result<Vec<String>> {
+    /// 受管日志 latest.log 的路径；文件尚不存在时返回 NotFound
+    pub fn latest_log_path(&self, id: &str) -> Result<PathBuf> {
+        self.validate_id(id)?;
         let path = self.log_path(id);
-        if !path.exists() || lines == 0 {
-            return Ok(vec![]);
+        if !path.is_file() {
+            return Err(ServiceError::NotFound(format!("{id}/latest.log")));
         }
-        let mut file = File::open(path)?;
-        let meta = file.metadata()?;
-        let mut pos: i64 = meta.len() as i64;
-        let mut chunks: Vec<Vec<u8>> = Vec::new();
-        let mut newline_count = 0usize;
-        const CHUNK_SIZE: usize = 8 * 1024;
-
-        while pos > 0 && newline_count <= lines {
-            let read_size = CHUNK_SIZE.min(pos as usize);
-            pos -= read_size as i64;
-            file.seek(SeekFrom::Start(pos as u64))?;
-            let mut buf = vec![0u8; read_size];
-            file.read_exact(&mut buf)?;
-
-            // 从尾部开始计数，找到开始位置后截断
-            let mut boundary = None;
-            for (idx, &b) in buf.iter().enumerate().rev() {
-                if b == b'\n' {
-                    newline_count += 1;
-                    if newline_count > lines {
-                        boundary = Some(idx + 1);
-                        break;
-                    }
+        Ok(path)
+    }
+
+    /// 按正则逐行搜索 latest.log，返回最多 `limit` 条命中及前后 `context` 行。
+    ///
+    /// 非法正则返回 InvalidPattern；日志不存在时返回空结果。
+    pub fn search_logs(
+        &self,
+        id: &str,
+        pattern: &str,
+        context: usize,
+        limit: usize,
+    ) -> Result<Vec<LogMatch>> {
+        let regex =
+            regex::Regex::new(pattern).map_err(|e| ServiceError::InvalidPattern(e.to_string()))?;
+        let file = match File::open(self.log_path(id)) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
+
+        let encoding = self.log_encoding(id);
+        let mut reader = BufReader::new(file);
+        let mut raw = Vec::new();
+        let mut offset = 0u64;
+        let mut line_number = 0usize;
+        let mut recent: VecDeque<String> = VecDeque::with_capacity(context);
+        let mut matches: Vec<LogMatch> = Vec::new();
+        loop {
+            raw.clear();
+            let read = reader.read_until(b'\n', &mut raw)?;
+            if read == 0 {
+                break;
+            }
+            let line = decode_line(&raw, encoding);
+            let line_offset = offset;
+            offset += read as u64;
+            line_number += 1;
+
+            // 补齐尚未收满的下文
+            for m in matches.iter_mut().rev() {
+                if m.line_number + context < line_number {
+                    break;
                 }
+                m.after.push(line.clone());
             }
 
-            if let Some(start) = boundary {
-                chunks.push(buf.split_off(start));
+            if matches.len() < limit && regex.is_match(&line) {
+                matches.push(LogMatch {
+                    line_number,
+                    offset: line_offset,
+                    line: line.clone(),
+                    before: recent.iter().cloned().collect(),
+                    after: Vec::new(),
+                });
+            } else if matches.len() >= limit
+                && matches.last().is_none_or(|m| m.after.len() >= context)
+            {
                 break;
-            } else {
-                chunks.push(buf);
             }
 
-            if pos <= 0 {
-                break;
+            if context > 0 {
+                if recent.len() == context {
+                    recent.pop_front();
+                }
+                recent.push_back(line);
+            }
+        }
+        Ok(matches)
+    }
+
+    /// 返回时间戳落在 `[since, until)` 内的日志行（按时间正序，最多保留最新的 `limit` 行）。
+    ///
+    /// 从最旧的滚动分段开始扫描；行首没有时间戳的行（如堆栈续行）归属上一条带时间戳的行。
+    /// 日志非空但找不到任何时间戳时返回 NoTimestampedLogs。
+    pub fn logs_between(
+        &self,
+        id: &str,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+        limit: usize,
+    ) -> Result<Vec<String>> {
+        self.validate_id(id)?;
+        let encoding = self.log_encoding(id);
+        let mut result: VecDeque<String> = VecDeque::new();
+        let mut current: Option<DateTime<Utc>> = None;
+        let mut saw_lines = false;
+        let mut raw = Vec::new();
+        'segments: for path in self.log_segments(id).into_iter().rev() {
+            let mut reader = BufReader::new(File::open(&path)?);
+            loop {
+                raw.clear();
+                if reader.read_until(b'\n', &mut raw)? == 0 {
+                    break;
+                }
+                let line = decode_line(&raw, encoding);
+                saw_lines = true;
+                if let Some(ts) = parse_log_timestamp(&line) {
+                    current = Some(ts);
+                }
+                let Some(ts) = current else {
+                    continue;
+                };
+                if until.is_some_and(|until| ts >= until) {
+                    break 'segments;
+                }
+                if since.is_none_or(|since| ts >= since) {
+                    if result.len() == limit {
+                        result.pop_front();
+                    }
+                    result.push_back(line);
+                }
             }
         }
+        if saw_lines && current.is_none() {
+            return Err(ServiceError::NoTimestampedLogs(id.to_string()));
+        }
+        Ok(result.into())
+    }
 
-        if chunks.is_empty() {
+    /// 返回日志末尾的原始字节（用于 attach 回放）
+    ///
+    /// 当前 latest.log 不足 `max_bytes` 时继续向滚动分段（latest.log.1 …）回溯。
+    pub fn tail_logs_raw(&self, id: &str, max_bytes: usize) -> Result<Vec<u8>> {
+        read_tail_bytes(|| self.log_segments(id), max_bytes)
+    }
+
+    /// 单独捕获的 stderr.log 末尾的原始字节（`split_stderr`）
+    pub fn tail_stderr_raw(&self, id: &str, max_bytes: usize) -> Result<Vec<u8>> {
+        read_tail_bytes(|| self.stderr_log_segments(id), max_bytes)
+    }
+
+    /// 日志末尾 `max_bytes` 字节所在的文件区间 `(路径, 起始偏移, 长度)`，按从旧到新排列
+    ///
+    /// 只确定区间、不读内容，供调用方边读边流式输出；长度以调用时刻的文件大小为准。
+    pub fn tail_log_ranges(&self, id: &str, max_bytes: usize) -> Result<Vec<(PathBuf, u64, u64)>> {
+        tail_ranges(self.log_segments(id), max_bytes)
+    }
+
+    /// 返回日志末尾 N 行。
+    ///
+    /// 当前 latest.log 行数不足时继续向滚动分段回溯，跨分段边界的半行会被拼接完整。
+    pub fn tail_logs(&self, id: &str, lines: usize) -> Result<Vec<String>> {
+        self.tail_segment_lines(id, self.log_segments(id), lines)
+    }
+
+    /// 单独捕获的 stderr.log 末尾 N 行（`split_stderr`）
+    pub fn tail_stderr(&self, id: &str, lines: usize) -> Result<Vec<String>> {
+        self.tail_segment_lines(id, self.stderr_log_segments(id), lines)
+    }
+
+    /// 从给定分段（从新到旧）中取末尾 N 行，按服务配置的编码解码
+    fn tail_segment_lines(
+        &self,
+        id: &str,
+        segments: Vec<PathBuf>,
+        lines: usize,
+    ) -> Result<Vec<String>> {
+        if lines == 0 {
             return Ok(vec![]);
         }
+        let mut data: Vec<u8> = Vec::new();
+        for path in segments {
+            let newlines = data.iter().filter(|&&b| b == b'\n').count();
+            if newlines > lines {
+                break;
+            }
+            let mut chunk = read_tail_lines(&path, lines - newlines)?;
+            chunk.extend_from_slice(&data);
+            data = chunk;
+        }
 
-        chunks.reverse();
-        let mut data = Vec::new();
-        for chunk in chunks {
-            data.extend_from_slice(&chunk);
+        if data.is_empty() {
+            return Ok(vec![]);
         }
 
         // 拆分成行再解码，避免一次性加载整文件
+        let encoding = self.log_encoding(id);
         let mut result = Vec::new();
         let mut start = 0usize;
         for (i, &b) in data.iter().enumerate() {
             if b == b'\n' {
-                result.push(decode_line(&data[start..=i]));
+                result.push(decode_line(&data[start..=i], encoding));
                 start = i + 1;
             }
         }
         if start < data.len() {
-            result.push(decode_line(&data[start..]));
+            result.push(decode_line(&data[start..], encoding));
         }
 
         if result.len() > lines {
@@ -104,95 +268,387 @@ impl ServiceManager {
         Ok(result)
     }
 
+    /// 清空日志：原地截断 latest.log 并删除历史分段，运行中的服务无需重启。
+    /// 单独捕获的 stderr.log 一并清空。
+    ///
+    /// 输出任务以 append 模式写入，截断后的写入会从新的文件末尾继续；
+    /// 不删除/重命名 latest.log，避免写入方仍持有旧文件句柄。
+    pub fn clear_logs(&self, id: &str) -> Result<()> {
+        self.validate_id(id)?;
+        if !self.manifest_path(id).exists() {
+            return Err(ServiceError::NotFound(id.to_string()));
+        }
+        let heads = [self.log_path(id), self.stderr_log_path(id)];
+        let segments = self
+            .log_segments(id)
+            .into_iter()
+            .chain(self.stderr_log_segments(id));
+        for segment in segments {
+            if heads.contains(&segment) {
+                match std::fs::OpenOptions::new().write(true).open(&segment) {
+                    Ok(file) => file.set_len(0)?,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(e) => return Err(e.into()),
+                }
+            } else {
+                std::fs::remove_file(&segment)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 服务配置的日志编码；未配置或 manifest 读取失败时返回 None（按默认规则猜测）
+    fn log_encoding(&self, id: &str) -> Option<&'static Encoding> {
+        let data = std::fs::read(self.manifest_path(id)).ok()?;
+        let manifest: ServiceManifest = serde_json::from_slice(&data).ok()?;
+        resolve_log_encoding(manifest.log_encoding.as_deref()?).ok()
+    }
+
+    /// 现存的日志分段，按从新到旧排列：latest.log、latest.log.1、latest.log.2 …
+    pub(super) fn log_segments(&self, id: &str) -> Vec<PathBuf> {
+        existing_segments(&self.log_path(id))
+    }
+
+    /// 现存的 stderr 日志分段，按从新到旧排列：stderr.log、stderr.log.1 …
+    fn stderr_log_segments(&self, id: &str) -> Vec<PathBuf> {
+        existing_segments(&self.stderr_log_path(id))
+    }
+
     /// 追踪日志（follow）- 返回原始字节流，不按行切割
     /// 优先使用 broadcast channel（如果服务正在运行且由当前进程管理），
-    /// 否则回退到文件轮询方式。
+    /// 否则回退到文件轮询方式。订阅者落后时按 `on_lag` 处理。
     pub async fn follow_logs_raw(
         &self,
         id: &str,
         poll: std::time::Duration,
+        on_lag: LagPolicy,
     ) -> Result<futures::stream::BoxStream<'static, std::io::Result<Vec<u8>>>> {
         // 尝试订阅 broadcast channel
         let maybe_rx = {
             let guard = self.runtime.lock().await;
-            guard.get(id).map(|h| h.output.subscribe())
+            guard.get(id).map(|h| h.output_cursor.subscribe(&h.output))
         };
 
-        if let Some(mut rx) = maybe_rx {
-            // 使用 broadcast channel 实时获取输出
-            let id_owned = id.to_string();
-            let stream = async_stream::stream! {
-                tracing::debug!(service_id = %id_owned, "Started following logs via broadcast channel (raw)");
-                loop {
-                    match rx.recv().await {
-                        Ok(bytes) => {
-                            // 直接返回原始字节，不做任何处理
-                            yield Ok(bytes);
-                        }
-                        Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
-                            tracing::warn!(service_id = %id_owned, dropped = n, "Log receiver lagged");
-                            // 返回一个提示消息
-                            yield Ok(format!("[dropped {} messages]\n", n).into_bytes());
-                        }
-                        Err(tokio::sync::broadcast::error::RecvError::Closed) => {
-                            tracing::info!(service_id = %id_owned, "Broadcast channel closed, stopping log follow");
-                            break;
-                        }
-                    }
-                }
-            };
-            return Ok(Box::pin(stream));
+        if let Some((rx, offset)) = maybe_rx {
+            let path = self.log_path(id);
+            return Ok(follow_broadcast(id, rx, path, offset, poll, on_lag));
         }
 
         // 回退到文件轮询方式
-        self.follow_logs_file_raw(id, poll).await
+        self.follow_logs_file_raw(id, self.log_path(id), poll).await
+    }
+
+    /// 追踪单独捕获的 stderr（`split_stderr`），行为同 [`Self::follow_logs_raw`]
+    pub async fn follow_stderr_raw(
+        &self,
+        id: &str,
+        poll: std::time::Duration,
+        on_lag: LagPolicy,
+    ) -> Result<futures::stream::BoxStream<'static, std::io::Result<Vec<u8>>>> {
+        let maybe_rx = {
+            let guard = self.runtime.lock().await;
+            guard
+                .get(id)
+                .and_then(|h| h.stderr.as_ref().map(|tx| h.stderr_cursor.subscribe(tx)))
+        };
+        if let Some((rx, offset)) = maybe_rx {
+            let path = self.stderr_log_path(id);
+            return Ok(follow_broadcast(id, rx, path, offset, poll, on_lag));
+        }
+        self.follow_logs_file_raw(id, self.stderr_log_path(id), poll)
+            .await
     }
 
     /// 通过文件轮询方式追踪日志（raw 版本）
     async fn follow_logs_file_raw(
         &self,
         id: &str,
+        path: PathBuf,
         poll: std::time::Duration,
     ) -> Result<futures::stream::BoxStream<'static, std::io::Result<Vec<u8>>>> {
-        let path = self.log_path(id);
         // 确保文件存在
         if !path.exists() {
             std::fs::create_dir_all(self.logs_dir(id))?;
             std::fs::write(&path, b"")?;
         }
 
+        // 从文件末尾开始
+        let offset = tokio::fs::metadata(&path).await?.len();
+        Ok(follow_file(path, offset, poll))
+    }
+}
+
+/// 从 `offset` 起轮询读取日志文件；文件被滚动或截断变短后重新打开新文件从头读
+fn follow_file(
+    path: PathBuf,
+    mut offset: u64,
+    poll: std::time::Duration,
+) -> futures::stream::BoxStream<'static, std::io::Result<Vec<u8>>> {
+    let stream = async_stream::try_stream! {
         // 使用 OpenOptions 以共享模式打开文件，允许其他进程同时写入
         let file = tokio::fs::OpenOptions::new().read(true).open(&path).await?;
         let mut reader = tokio::io::BufReader::new(file);
-        // 从文件末尾开始
-        reader.seek(std::io::SeekFrom::End(0)).await?;
-
-        let stream = async_stream::try_stream! {
-            let mut buf = [0u8; 4096];
-            loop {
-                match tokio::io::AsyncReadExt::read(&mut reader, &mut buf).await {
-                    Ok(0) => {
-                        // 没有新数据，等待后继续
-                        tokio::time::sleep(poll).await;
-                        continue;
-                    }
-                    Ok(n) => {
-                        yield buf[..n].to_vec();
-                    }
-                    Err(e) => {
-                        // 记录错误但继续尝试
-                        tracing::warn!("Error reading log file: {}", e);
-                        tokio::time::sleep(poll).await;
-                        continue;
+        if tokio::fs::metadata(&path).await?.len() >= offset {
+            reader.seek(std::io::SeekFrom::Start(offset)).await?;
+        } else {
+            offset = 0;
+        }
+        let mut buf = [0u8; 4096];
+        loop {
+            match tokio::io::AsyncReadExt::read(&mut reader, &mut buf).await {
+                Ok(0) => {
+                    // 日志被滚动或截断后文件变短，重新打开新的 latest.log
+                    let current_len = tokio::fs::metadata(&path)
+                        .await
+                        .map(|m| m.len())
+                        .unwrap_or(0);
+                    if current_len < offset {
+                        if let Ok(file) = tokio::fs::File::open(&path).await {
+                            reader = tokio::io::BufReader::new(file);
+                            offset = 0;
+                            continue;
+                        }
                     }
+                    // 没有新数据，等待后继续
+                    tokio::time::sleep(poll).await;
+                    continue;
+                }
+                Ok(n) => {
+                    offset += n as u64;
+                    yield buf[..n].to_vec();
+                }
+                Err(e) => {
+                    // 记录错误但继续尝试
+                    tracing::warn!("Error reading log file: {}", e);
+                    tokio::time::sleep(poll).await;
+                    continue;
                 }
             }
+        }
+    };
+    Box::pin(stream)
+}
+
+/// `head` 及其现存的滚动分段，按从新到旧排列
+fn existing_segments(head: &Path) -> Vec<PathBuf> {
+    let mut segments = Vec::new();
+    if head.exists() {
+        segments.push(head.to_path_buf());
+    }
+    let mut n = 1;
+    loop {
+        let path = segment_path(head, n);
+        if !path.exists() {
+            break;
+        }
+        segments.push(path);
+        n += 1;
+    }
+    segments
+}
+
+/// 分段（从新到旧）末尾 `max_bytes` 字节所在的文件区间，按从旧到新排列
+fn tail_ranges(segments: Vec<PathBuf>, max_bytes: usize) -> Result<Vec<(PathBuf, u64, u64)>> {
+    let mut remaining = max_bytes as u64;
+    let mut ranges = Vec::new();
+    for path in segments {
+        if remaining == 0 {
+            break;
+        }
+        let size = match std::fs::metadata(&path) {
+            Ok(meta) => meta.len(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let len = size.min(remaining);
+        if len > 0 {
+            ranges.push((path, size - len, len));
+            remaining -= len;
+        }
+    }
+    ranges.reverse();
+    Ok(ranges)
+}
+
+/// 读取分段末尾 `max_bytes` 字节
+///
+/// 输出线程可能在区间确定后滚动或截断日志；读到的内容不完整时重新确定区间再读一次，
+/// 仍不完整则返回已读到的部分，而不是把 IO 错误抛给调用方。
+fn read_tail_bytes(segments: impl Fn() -> Vec<PathBuf>, max_bytes: usize) -> Result<Vec<u8>> {
+    let (data, complete) = read_ranges(tail_ranges(segments(), max_bytes)?)?;
+    if complete {
+        return Ok(data);
+    }
+    Ok(read_ranges(tail_ranges(segments(), max_bytes)?)?.0)
+}
+
+/// 按区间读取内容，返回 `(数据, 是否完整)`；文件已被移走或截断到区间之内时视为不完整
+fn read_ranges(ranges: Vec<(PathBuf, u64, u64)>) -> Result<(Vec<u8>, bool)> {
+    let mut data: Vec<u8> = Vec::new();
+    let mut complete = true;
+    for (path, start, len) in ranges {
+        let mut file = match File::open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                complete = false;
+                continue;
+            }
+            Err(e) => return Err(e.into()),
         };
+        file.seek(SeekFrom::Start(start))?;
+        let read = file.take(len).read_to_end(&mut data)?;
+        complete &= read as u64 == len;
+    }
+    Ok((data, complete))
+}
+
+/// 输出广播在日志文件中的位置：下一条广播内容的起始偏移。
+///
+/// 写日志与广播的一方在锁内推进它；订阅方在同一把锁内订阅，
+/// 拿到的偏移正是收到的第一条广播在文件中的位置。
+#[derive(Debug, Clone, Default)]
+pub(super) struct OutputCursor(Arc<StdMutex<u64>>);
+
+impl OutputCursor {
+    pub(super) fn lock(&self) -> std::sync::MutexGuard<'_, u64> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 订阅广播，并返回与之对齐的文件偏移
+    pub(super) fn subscribe(
+        &self,
+        tx: &broadcast::Sender<Vec<u8>>,
+    ) -> (broadcast::Receiver<Vec<u8>>, u64) {
+        let pos = self.lock();
+        (tx.subscribe(), *pos)
+    }
+
+    /// 以追加模式（重新）打开日志后，把位置移到文件末尾
+    pub(super) fn advance_to(&self, file: Option<&mut std::fs::File>) {
+        if let Some(pos) = file.and_then(|f| f.seek(SeekFrom::End(0)).ok()) {
+            *self.lock() = pos;
+        }
+    }
+}
 
-        Ok(Box::pin(stream))
+/// 把输出广播转换为原始字节流；接收端落后时按 `on_lag` 插入丢弃提示或改从 `path` 补读
+///
+/// `offset` 是第一条广播在 `path` 中的位置（见 [`OutputCursor::subscribe`]）。
+pub(super) fn follow_broadcast(
+    id: &str,
+    mut rx: broadcast::Receiver<Vec<u8>>,
+    path: PathBuf,
+    mut offset: u64,
+    poll: std::time::Duration,
+    on_lag: LagPolicy,
+) -> futures::stream::BoxStream<'static, std::io::Result<Vec<u8>>> {
+    let id_owned = id.to_string();
+    let stream = async_stream::stream! {
+        tracing::debug!(service_id = %id_owned, "Started following logs via broadcast channel (raw)");
+        loop {
+            match rx.recv().await {
+                Ok(bytes) => {
+                    // 直接返回原始字节，不做任何处理
+                    offset += bytes.len() as u64;
+                    yield Ok(bytes);
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) if on_lag == LagPolicy::Resync => {
+                    tracing::debug!(service_id = %id_owned, dropped = n, "Log receiver lagged, resyncing from file");
+                    // 不再回到广播：之后的输出都从文件轮询，保证不重不漏
+                    drop(rx);
+                    let mut file_stream = follow_file(path, offset, poll);
+                    while let Some(item) = futures::StreamExt::next(&mut file_stream).await {
+                        yield item;
+                    }
+                    break;
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                    tracing::warn!(service_id = %id_owned, dropped = n, "Log receiver lagged");
+                    // 返回一个提示消息
+                    yield Ok(format!("[dropped {} messages]\n", n).into_bytes());
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                    tracing::info!(service_id = %id_owned, "Broadcast channel closed, stopping log follow");
+                    break;
+                }
+            }
+        }
+    };
+    Box::pin(stream)
+}
+
+/// 滚动分段路径：`latest.log` + n → `latest.log.n`
+pub(super) fn segment_path(path: &Path, n: u32) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!(".{n}"));
+    PathBuf::from(name)
+}
+
+/// 从文件末尾向前读取，返回包含最后 `lines` 行（含末尾不完整行）的原始字节
+///
+/// 读取过程中文件被截断（日志滚动）时重新读一次；仍被截断则返回已读到的部分。
+fn read_tail_lines(path: &Path, lines: usize) -> Result<Vec<u8>> {
+    match read_tail_lines_once(path, lines)? {
+        (data, true) => Ok(data),
+        (_, false) => Ok(read_tail_lines_once(path, lines)?.0),
     }
 }
 
+/// [`read_tail_lines`] 的单次读取，返回 `(数据, 是否完整)`
+///
+/// 读取范围限定在打开时的文件长度内，且从不越过当前 EOF 读取。
+fn read_tail_lines_once(path: &Path, lines: usize) -> Result<(Vec<u8>, bool)> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((vec![], true)),
+        Err(e) => return Err(e.into()),
+    };
+    let meta = file.metadata()?;
+    let mut pos: i64 = meta.len() as i64;
+    let mut chunks: Vec<Vec<u8>> = Vec::new();
+    let mut newline_count = 0usize;
+    const CHUNK_SIZE: usize = 8 * 1024;
+
+    while pos > 0 && newline_count <= lines {
+        let read_size = CHUNK_SIZE.min(pos as usize);
+        pos -= read_size as i64;
+        file.seek(SeekFrom::Start(pos as u64))?;
+        let mut buf = Vec::with_capacity(read_size);
+        (&mut file).take(read_size as u64).read_to_end(&mut buf)?;
+        if buf.len() < read_size {
+            // 打开后文件被截断：丢弃这块（可能来自新内容），交给调用方重试
+            chunks.reverse();
+            return Ok((chunks.concat(), false));
+        }
+
+        // 从尾部开始计数，找到开始位置后截断
+        let mut boundary = None;
+        for (idx, &b) in buf.iter().enumerate().rev() {
+            if b == b'\n' {
+                newline_count += 1;
+                if newline_count > lines {
+                    boundary = Some(idx + 1);
+                    break;
+                }
+            }
+        }
+
+        if let Some(start) = boundary {
+            chunks.push(buf.split_off(start));
+            break;
+        } else {
+            chunks.push(buf);
+        }
+
+        if pos <= 0 {
+            break;
+        }
+    }
+
+    chunks.reverse();
+    Ok((chunks.concat(), true))
+}
+
 #[allow(dead_code)]
 /// 后台转发 stdout/stderr：写入日志文件并广播给 attach 订阅者。
 pub(super) fn spawn_output_forward<R>(
@@ -222,7 +678,7 @@ pub(super) fn spawn_output_forward<R>(
                         let _ = file.write_all(&buf).await;
                         let _ = file.flush().await;
                     }
-                    let line = decode_line(&buf);
+                    let line = decode_line(&buf, None);
                     let _ = tx.send(line);
                 }
                 Err(_) => break,
@@ -231,10 +687,73 @@ pub(super) fn spawn_output_forward<R>(
     });
 }
 
-/// 尝试从 UTF-8 解码，否则回退 GB18030；移除行尾换行。
-fn decode_line(raw: &[u8]) -> String {
-    let mut s = match std::str::from_utf8(raw) {
-        Ok(v) => v.to_string(),
+/// 解析行首时间戳：RFC 3339，或本地时间 `YYYY-MM-DD HH:MM:SS`（`T` 分隔、带小数秒亦可）。
+///
+/// 忽略行首的 ANSI 颜色、空白与 `[`，无法识别时返回 None。
+pub(super) fn parse_log_timestamp(line: &str) -> Option<DateTime<Utc>> {
+    let mut rest = line;
+    loop {
+        let trimmed = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '[');
+        match trimmed.strip_prefix("\x1b[") {
+            Some(escape) => {
+                let end = escape.find(|c: char| c.is_ascii_alphabetic())?;
+                rest = &escape[end + 1..];
+            }
+            None => {
+                rest = trimmed;
+                break;
+            }
+        }
+    }
+
+    let token = rest
+        .split(|c: char| c.is_whitespace() || c == ']')
+        .next()
+        .unwrap_or_default();
+    if let Ok(ts) = DateTime::parse_from_rfc3339(token) {
+        return Some(ts.with_timezone(&Utc));
+    }
+
+    let prefix = rest.get(..19)?;
+    let naive = NaiveDateTime::parse_from_str(prefix, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(prefix, "%Y-%m-%dT%H:%M:%S"))
+        .ok()?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|ts| ts.with_timezone(&Utc))
+}
+
+/// 按 WHATWG 标签查找日志编码；UTF-16 不按字节换行分割，不支持
+fn resolve_log_encoding(label: &str) -> Result<&'static Encoding> {
+    match Encoding::for_label_no_replacement(label.trim().as_bytes()) {
+        Some(encoding)
+            if encoding != encoding_rs::UTF_16LE && encoding != encoding_rs::UTF_16BE =>
+        {
+            Ok(encoding)
+        }
+        _ => Err(ServiceError::PolicyViolation(
+            format!("unsupported log_encoding: {label}").into(),
+        )),
+    }
+}
+
+/// 校验 manifest 中的 `log_encoding`
+pub(super) fn validate_log_encoding(manifest: &ServiceManifest) -> Result<()> {
+    match &manifest.log_encoding {
+        Some(label) => resolve_log_encoding(label).map(|_| ()),
+        None => Ok(()),
+    }
+}
+
+/// 按配置的编码解码；未配置时尝试 UTF-8，否则回退 GB18030。移除行尾换行。
+fn decode_line(raw: &[u8], encoding: Option<&'static Encoding>) -> String {
+    let decoded = match encoding {
+        Some(encoding) => Ok(encoding.decode_without_bom_handling(raw).0.into_owned()),
+        None => std::str::from_utf8(raw).map(str::to_string),
+    };
+    let mut s = match decoded {
+        Ok(v) => v,
         Err(_) => {
             // UTF-8 解码失败，尝试 GB18030
             let (cow, _, had_errors) = encoding_rs::GB18030.decode(raw);
@@ -1,24 +1,46 @@
 use crate::error::{Result, ServiceError};
-use crate::manifest::ServiceManifest;
-use crate::models::{ServiceState, ServiceStatus, ServiceSummary};
+use crate::manifest::{ServiceManifest, MANIFEST_SCHEMA_VERSION};
+use crate::models::{
+    ArchivedService, HealthReport, ServiceEvent, ServiceState, ServiceStatus, ServiceSummary,
+    StopOutcome, SubsystemHealth,
+};
+use crate::user::UserManager;
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex as StdMutex};
 use sysinfo::System;
-use tokio::sync::{broadcast, mpsc, Mutex};
+use tokio::sync::{broadcast, mpsc, watch, Mutex};
 
 mod attach;
+mod bundle;
+mod cgroup;
+mod dependencies;
+mod detached;
+mod env;
+mod events;
 mod groups;
+mod health;
 mod lifecycle;
 mod logs;
+mod piped;
 mod policy;
 mod process;
+mod recordings;
 pub mod scheduler;
 mod signal;
 mod stats;
 mod storage;
+mod validation;
+mod webhooks;
 
-pub use stats::SystemStats;
+use process::ProcessSnapshot;
+
+pub use attach::DEFAULT_ATTACH_BUFFER;
+pub use env::secret_refs;
+pub use logs::{LagPolicy, LogMatch, LogTextDecoder};
+pub use recordings::{RecordingInfo, SessionRecorder};
+pub use signal::parse_signal;
+pub use stats::{ProcessStats, SystemStats};
 
 /// attach 会话句柄：暴露写入 stdin 的通道与订阅 stdout/stderr 的广播。
 #[derive(Debug)]
@@ -26,6 +48,8 @@ pub struct AttachHandle {
     pub pid: u32,
     pub input: mpsc::Sender<Vec<u8>>,
     pub output: broadcast::Receiver<Vec<u8>>,
+    /// 单独捕获的 stderr（`split_stderr`），未分离时为 None
+    pub stderr: Option<broadcast::Receiver<Vec<u8>>>,
 }
 
 /// 运行时缓存：保存已经由当前 manager 启动的子进程句柄，便于 attach。
@@ -33,11 +57,19 @@ struct RuntimeHandles {
     pid: u32,
     input: mpsc::Sender<Vec<u8>>,
     output: broadcast::Sender<Vec<u8>>,
+    /// `output` 下一条广播在 latest.log 中的起始偏移
+    output_cursor: logs::OutputCursor,
+    /// 单独捕获的 stderr 广播（仅管道模式且开启 split_stderr）
+    stderr: Option<broadcast::Sender<Vec<u8>>>,
+    /// `stderr` 下一条广播在 stderr.log 中的起始偏移（未分离 stderr 时不使用）
+    stderr_cursor: logs::OutputCursor,
     /// Hold PTY master to keep the pseudoterminal alive for Windows ConPTY.
-    #[allow(dead_code)]
-    pty: Box<dyn portable_pty::MasterPty + Send>,
+    /// 分离模式的进程没有 PTY。
+    pty: Option<Box<dyn portable_pty::MasterPty + Send>>,
     /// 是否是主动停止（stop 调用），用于区分自动重启
     stop_requested: Arc<std::sync::atomic::AtomicBool>,
+    /// 健康检查结论，由 health 探测任务更新
+    healthy: Arc<StdMutex<Option<bool>>>,
 }
 
 impl std::fmt::Debug for RuntimeHandles {
@@ -54,8 +86,28 @@ pub struct ServiceManager {
     data_dir: PathBuf,
     allowed_commands: Option<HashSet<String>>,
     allowed_cwd_roots: Vec<PathBuf>,
+    /// 未设置 cwd 的服务使用 `<root>/<id>` 作为工作目录
+    default_cwd_root: Option<PathBuf>,
+    /// 允许经 `${ENV:NAME}` 传给服务的 API 进程环境变量名
+    env_passthrough: HashSet<String>,
     runtime: Arc<Mutex<HashMap<String, RuntimeHandles>>>,
     system: Arc<StdMutex<System>>,
+    /// 全量进程快照（短 TTL），供批量状态查询复用
+    process_snapshot: Arc<StdMutex<Option<ProcessSnapshot>>>,
+    /// 共享的系统资源快照推送（有订阅者时才刷新）
+    stats_feed: Arc<StdMutex<Option<watch::Sender<SystemStats>>>>,
+    /// 密钥库，用于解析 env 中的 `${SECRET:key}`
+    secrets: Option<UserManager>,
+    /// 生命周期事件广播（状态变化推送）
+    events: broadcast::Sender<ServiceEvent>,
+    /// 全局 Webhook 地址，接收所有服务的生命周期事件
+    webhook_url: Option<String>,
+    /// 串行化生命周期计数文件的读改写
+    lifecycle_stats_lock: Arc<StdMutex<()>>,
+    /// 串行化调度触发时间文件的读改写
+    schedule_fires_lock: Arc<StdMutex<()>>,
+    /// attach / 日志跟随的输出广播容量
+    attach_buffer: usize,
 }
 
 impl ServiceManager {
@@ -73,15 +125,83 @@ impl ServiceManager {
             data_dir: data_dir.as_ref().to_path_buf(),
             allowed_commands,
             allowed_cwd_roots,
+            default_cwd_root: None,
+            env_passthrough: HashSet::new(),
             runtime: Arc::new(Mutex::new(HashMap::new())),
             system: Arc::new(StdMutex::new(System::new())),
+            process_snapshot: Arc::new(StdMutex::new(None)),
+            stats_feed: Arc::new(StdMutex::new(None)),
+            secrets: None,
+            events: broadcast::channel(events::EVENT_CHANNEL_CAPACITY).0,
+            webhook_url: None,
+            lifecycle_stats_lock: Arc::new(StdMutex::new(())),
+            schedule_fires_lock: Arc::new(StdMutex::new(())),
+            attach_buffer: DEFAULT_ATTACH_BUFFER,
         }
     }
 
+    /// 挂载密钥库，启用 `${SECRET:key}` 引用。
+    pub fn with_secrets(mut self, secrets: UserManager) -> Self {
+        self.secrets = Some(secrets);
+        self
+    }
+
+    /// 配置全局 Webhook，所有服务的生命周期事件都会投递到该地址。
+    pub fn with_webhook_url(mut self, url: impl Into<String>) -> Self {
+        self.webhook_url = Some(url.into());
+        self
+    }
+
+    /// 设置 attach / 日志跟随的输出广播容量（至少为 1），只影响之后启动的服务。
+    pub fn with_attach_buffer(mut self, capacity: usize) -> Self {
+        self.attach_buffer = capacity.max(1);
+        self
+    }
+
+    /// 设置默认工作目录根：未设置 cwd 的服务在 `<root>/<id>` 下运行，首次启动时自动创建。
+    pub fn with_default_cwd_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.default_cwd_root = Some(root.into());
+        self
+    }
+
+    /// 设置允许经 `${ENV:NAME}` 引用的环境变量名；`HC_` 开头的名字始终拒绝。
+    pub fn with_env_passthrough(mut self, names: impl IntoIterator<Item = String>) -> Self {
+        self.env_passthrough = names.into_iter().collect();
+        self
+    }
+
     /// 确保基础目录存在。
     pub fn ensure_base_dirs(&self) -> Result<()> {
-        std::fs::create_dir_all(self.services_dir())?;
-        Ok(())
+        let dir = self.services_dir();
+        std::fs::create_dir_all(&dir).map_err(|e| storage_error(&dir, e))
+    }
+
+    /// 启动探测：在 services 目录写入、落盘并删除哨兵文件，确认数据目录可写。
+    ///
+    /// 磁盘已满或只读时尽早失败，避免服务“创建成功”却没有真正写入磁盘。
+    pub fn check_writable(&self) -> Result<()> {
+        let dir = self.services_dir();
+        let probe = dir.join(".write-probe");
+        std::fs::create_dir_all(&dir)
+            .and_then(|_| {
+                let mut file = std::fs::File::create(&probe)?;
+                std::io::Write::write_all(&mut file, b"ok")?;
+                file.sync_all()
+            })
+            .and_then(|_| std::fs::remove_file(&probe))
+            .map_err(|e| storage_error(&dir, e))
+    }
+
+    /// 就绪检查：目前包含数据目录可写（`data_dir`）。
+    ///
+    /// 调度器、用户存储等不归 ServiceManager 管理的子系统由调用方追加到报告中。
+    pub fn health_report(&self) -> HealthReport {
+        let mut report = HealthReport::default();
+        report.push(SubsystemHealth::from_result(
+            "data_dir",
+            self.check_writable(),
+        ));
+        report
     }
 
     /// services 根目录：<data_dir>/services
@@ -109,16 +229,61 @@ impl ServiceManager {
         self.runtime_dir(id).join("pid")
     }
 
+    /// 上次退出记录路径
+    fn last_exit_path(&self, id: &str) -> PathBuf {
+        self.runtime_dir(id).join("last_exit.json")
+    }
+
+    /// 生命周期计数记录路径
+    fn lifecycle_stats_path(&self, id: &str) -> PathBuf {
+        self.runtime_dir(id).join("lifecycle_stats.json")
+    }
+
+    /// 最近一次 PTY 尺寸记录路径
+    fn pty_size_path(&self, id: &str) -> PathBuf {
+        self.runtime_dir(id).join("pty_size.json")
+    }
+
+    /// 分离模式的 stdin FIFO 路径
+    fn stdin_fifo_path(&self, id: &str) -> PathBuf {
+        self.runtime_dir(id).join("stdin.fifo")
+    }
+
+    /// 调度条目最近触发时间记录路径
+    fn schedule_fires_path(&self, id: &str) -> PathBuf {
+        self.runtime_dir(id).join("schedule_fires.json")
+    }
+
+    /// attach 录制目录
+    fn recordings_dir(&self, id: &str) -> PathBuf {
+        self.service_dir(id).join("recordings")
+    }
+
     /// logs 根目录
     fn logs_dir(&self, id: &str) -> PathBuf {
         self.service_dir(id).join("logs")
     }
 
+    /// 软删除服务的归档根目录：<data_dir>/archive
+    fn archive_dir(&self) -> PathBuf {
+        self.data_dir.join("archive")
+    }
+
+    /// 已删除服务的归档目录：<data_dir>/deleted/<id>
+    fn deleted_dir(&self, id: &str) -> PathBuf {
+        self.data_dir.join("deleted").join(id)
+    }
+
     /// 默认日志文件 latest.log
     fn log_path(&self, id: &str) -> PathBuf {
         self.logs_dir(id).join("latest.log")
     }
 
+    /// 单独捕获的 stderr 日志路径（与 latest.log 同目录）
+    fn stderr_log_path(&self, id: &str) -> PathBuf {
+        self.logs_dir(id).join("stderr.log")
+    }
+
     /// 校验服务 id：字母数字开头，仅允许 `[A-Za-z0-9_-]`，长度 1..=64。
     /// 明确拒绝 `.` / `..` 以及任何含路径分隔或点号的 ID，防止目录穿越。
     fn validate_id(&self, id: &str) -> Result<()> {
@@ -145,6 +310,11 @@ impl ServiceManager {
     }
 }
 
+/// 数据目录读写失败：错误中带上路径，便于定位是哪块磁盘出了问题
+fn storage_error(path: &std::path::Path, err: std::io::Error) -> ServiceError {
+    ServiceError::StorageUnavailable(format!("{}: {}", path.display(), err))
+}
+
 // 扩展实现拆分在子模块中
 #[cfg(test)]
 mod tests {
@@ -153,26 +323,45 @@ mod tests {
 
     fn manifest(id: &str) -> ServiceManifest {
         ServiceManifest {
+            schema_version: MANIFEST_SCHEMA_VERSION,
             id: id.to_string(),
             name: id.to_string(),
             command: "cmd".into(), // dummy; not spawned in tests
             args: vec![],
             env: std::collections::BTreeMap::new(),
+            env_file: None,
             cwd: None,
             auto_start: false,
             auto_restart: false,
+            restart_backoff_ms: None,
+            max_restarts: None,
+            restart_window_secs: None,
             clear_log_on_start: true,
             shutdown_command: None,
+            shutdown_timeout_secs: None,
+            kill_signal: None,
+            kill_timeout_secs: None,
             run_as: None,
+            memory_limit_mb: None,
+            cpu_quota_percent: None,
             created_at: None,
             tags: vec![],
             group: None,
             order: 0,
+            depends_on: vec![],
             log_path: None,
+            log_keep_files: None,
+            log_encoding: None,
+            start_grace_ms: 300,
             pty_rows: 300,
             terminal_tui: false,
-            schedule: None,
+            tty: true,
+            split_stderr: false,
+            schedules: vec![],
             web: None,
+            health_check: None,
+            webhooks: vec![],
+            detached: false,
         }
     }
 
@@ -190,14 +379,304 @@ mod tests {
         assert!(list.iter().any(|s| s.id == "svc2"));
     }
 
+    #[tokio::test]
+    async fn refresh_all_status_scales_to_many_services() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        for i in 0..500 {
+            let id = format!("svc{i}");
+            manager.create_service(manifest(&id)).await.unwrap();
+            // 偶数服务指向当前测试进程，模拟运行中
+            if i % 2 == 0 {
+                manager.write_pid(&id, std::process::id()).unwrap();
+            }
+        }
+
+        let statuses = manager.refresh_all_status().await.unwrap();
+        assert_eq!(statuses.len(), 500);
+        let running = statuses
+            .values()
+            .filter(|s| matches!(s.state, ServiceState::Running))
+            .count();
+        assert_eq!(running, 250);
+        assert!(matches!(statuses["svc1"].state, ServiceState::Stopped));
+
+        let list = manager.list_services().await.unwrap();
+        assert_eq!(list.len(), 500);
+        let svc0 = list.iter().find(|s| s.id == "svc0").unwrap();
+        assert!(matches!(svc0.state, ServiceState::Running));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn detached_service_can_be_reattached_by_new_manager() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        let mut svc = manifest("echo");
+        svc.command = "sh".into();
+        svc.args = vec!["-c".into(), "while read l; do echo got:$l; done".into()];
+        svc.detached = true;
+        manager.create_service(svc).await.unwrap();
+        manager.start("echo").await.unwrap();
+
+        async fn roundtrip(manager: &ServiceManager, line: &str) -> String {
+            let handle = manager.attach("echo").await.unwrap();
+            let mut output = handle.output;
+            handle
+                .input
+                .send(format!("{line}\n").into_bytes())
+                .await
+                .unwrap();
+            let mut seen = String::new();
+            let expected = format!("got:{line}");
+            tokio::time::timeout(std::time::Duration::from_secs(5), async {
+                while !seen.contains(&expected) {
+                    let chunk = output.recv().await.unwrap();
+                    seen.push_str(&String::from_utf8_lossy(&chunk));
+                }
+            })
+            .await
+            .unwrap();
+            seen
+        }
+        roundtrip(&manager, "one").await;
+
+        // 模拟 API 重启：新的 manager 实例没有 runtime 句柄，靠 pid 文件接管
+        let restarted = ServiceManager::new(dir.path());
+        assert_eq!(
+            restarted.reconcile().await.unwrap(),
+            vec!["echo".to_string()]
+        );
+        roundtrip(&restarted, "two").await;
+
+        restarted.kill("echo").await.unwrap();
+        assert_eq!(
+            restarted.status("echo").await.unwrap().state,
+            ServiceState::Stopped
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn start_grace_period_catches_delayed_crash() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        for (id, grace) in [("slow_crash", 2000), ("default_grace", 300)] {
+            let mut svc = manifest(id);
+            svc.command = "sh".into();
+            svc.args = vec!["-c".into(), "sleep 0.5; exit 1".into()];
+            svc.start_grace_ms = grace;
+            manager.create_service(svc).await.unwrap();
+        }
+
+        let started = std::time::Instant::now();
+        let err = manager.start("slow_crash").await.unwrap_err();
+        assert!(matches!(err, ServiceError::SpawnFailed(_)), "got {err:?}");
+        // 轮询应在进程退出后尽快返回，而不是等满宽限期
+        assert!(started.elapsed() < std::time::Duration::from_millis(1500));
+
+        manager.start("default_grace").await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn lifecycle_events_report_start_and_crash() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        let mut svc = manifest("crashy");
+        svc.command = "sh".into();
+        svc.args = vec!["-c".into(), "sleep 1; exit 3".into()];
+        manager.create_service(svc).await.unwrap();
+
+        let mut events = manager.subscribe_events();
+        manager.start("crashy").await.unwrap();
+
+        async fn next(events: &mut broadcast::Receiver<ServiceEvent>) -> ServiceEvent {
+            tokio::time::timeout(std::time::Duration::from_secs(5), events.recv())
+                .await
+                .unwrap()
+                .unwrap()
+        }
+        let ServiceEvent::StateChanged { state, reason, .. } = next(&mut events).await else {
+            panic!("expected state change");
+        };
+        assert_eq!(state, ServiceState::Running);
+        assert_eq!(reason, crate::models::StateChangeReason::Started);
+
+        let ServiceEvent::StateChanged {
+            id,
+            state,
+            reason,
+            exit_code,
+            ..
+        } = next(&mut events).await
+        else {
+            panic!("expected state change");
+        };
+        assert_eq!(id, "crashy");
+        assert_eq!(state, ServiceState::Stopped);
+        assert_eq!(reason, crate::models::StateChangeReason::Crashed);
+        assert_eq!(exit_code, Some(3));
+    }
+
+    #[tokio::test]
+    async fn validate_manifest_collects_problems_without_writing() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        assert!(manager.validate_manifest(&manifest("svc1")).await.valid);
+
+        manager.create_service(manifest("svc1")).await.unwrap();
+        let mut m = manifest("svc1");
+        m.cwd = Some(dir.path().join("missing").to_string_lossy().into());
+        m.schedules = vec![crate::Schedule {
+            cron: "not a cron".into(),
+            ..Default::default()
+        }];
+        let report = manager.validate_manifest(&m).await;
+        assert!(!report.valid);
+        let fields: Vec<_> = report.problems.iter().map(|p| p.field.as_str()).collect();
+        assert_eq!(fields, ["id", "cwd", "schedules[0]"]);
+
+        let report = manager.validate_manifest(&manifest("../evil")).await;
+        assert_eq!(report.problems[0].field, "id");
+        assert_eq!(manager.list_services().await.unwrap().len(), 1);
+
+        // cwd 已报告无效时不再追加默认工作目录的问题
+        let outside = ServiceManager::new(dir.path()).with_default_cwd_root("/outside-root");
+        let mut m = manifest("svc2");
+        m.cwd = Some("relative".into());
+        let report = outside.validate_manifest(&m).await;
+        let fields: Vec<_> = report.problems.iter().map(|p| p.field.as_str()).collect();
+        assert_eq!(fields, ["cwd"]);
+        assert_eq!(
+            outside.validate_manifest(&manifest("svc2")).await.problems[0].field,
+            "cwd"
+        );
+    }
+
+    #[tokio::test]
+    async fn export_import_round_trip() {
+        let src_dir = TempDir::new().unwrap();
+        let src = ServiceManager::new(src_dir.path());
+        src.create_group("web".into(), "Web".into(), None, None)
+            .await
+            .unwrap();
+        let mut svc = manifest("svc1");
+        svc.group = Some("web".into());
+        src.create_service(svc).await.unwrap();
+        src.create_service(manifest("svc2")).await.unwrap();
+        let mut bundle = src.export_config().await.unwrap();
+        assert_eq!(bundle.services.len(), 2);
+
+        let dst_dir = TempDir::new().unwrap();
+        let dst = ServiceManager::new(dst_dir.path());
+        let mut existing = manifest("svc2");
+        existing.name = "keep me".into();
+        dst.create_service(existing).await.unwrap();
+        bundle.services.push(manifest("../evil"));
+
+        let report = dst.import_config(bundle.clone(), false).await.unwrap();
+        let outcome = |id: &str| {
+            report
+                .services
+                .iter()
+                .find(|r| r.id == id)
+                .map(|r| r.outcome)
+                .unwrap()
+        };
+        assert_eq!(outcome("svc1"), crate::models::ImportOutcome::Created);
+        assert_eq!(outcome("svc2"), crate::models::ImportOutcome::Skipped);
+        assert_eq!(outcome("../evil"), crate::models::ImportOutcome::Failed);
+        assert_eq!(
+            report.groups[0].outcome,
+            crate::models::ImportOutcome::Created
+        );
+        assert_eq!(dst.load_manifest("svc2").await.unwrap().name, "keep me");
+        assert_eq!(
+            dst.load_manifest("svc1").await.unwrap().group.as_deref(),
+            Some("web")
+        );
+
+        let report = dst.import_config(bundle, true).await.unwrap();
+        assert_eq!(
+            report.services[1].outcome,
+            crate::models::ImportOutcome::Updated
+        );
+        assert_eq!(dst.load_manifest("svc2").await.unwrap().name, "svc2");
+    }
+
     #[tokio::test]
     async fn delete_requires_existing() {
         let dir = TempDir::new().unwrap();
         let manager = ServiceManager::new(dir.path());
-        let err = manager.delete_service("missing").await.unwrap_err();
+        let err = manager.delete_service("missing", false).await.unwrap_err();
         matches!(err, ServiceError::NotFound(_));
     }
 
+    #[tokio::test]
+    async fn delete_keep_logs_archives_log_dir() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        manager.create_service(manifest("svc1")).await.unwrap();
+        std::fs::write(manager.log_path("svc1"), "hello\n").unwrap();
+
+        manager.delete_service("svc1", true).await.unwrap();
+
+        assert!(!manager.service_dir("svc1").exists());
+        let archived = dir.path().join("deleted/svc1/logs/latest.log");
+        assert_eq!(std::fs::read_to_string(archived).unwrap(), "hello\n");
+    }
+
+    #[tokio::test]
+    async fn archive_and_restore_service() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        manager.create_service(manifest("svc1")).await.unwrap();
+        std::fs::write(manager.log_path("svc1"), "hello\n").unwrap();
+
+        let archived = manager.archive_service("svc1").await.unwrap();
+        assert_eq!(archived.service_id, "svc1");
+        assert_eq!(archived.name, "svc1");
+        assert!(!manager.service_exists("svc1").await.unwrap());
+        assert!(dir
+            .path()
+            .join("archive")
+            .join(&archived.archive_id)
+            .is_dir());
+
+        let listed = manager.list_archived().await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].archive_id, archived.archive_id);
+        assert_eq!(listed[0].archived_at, archived.archived_at);
+
+        // 同 ID 的新服务存在时不能恢复
+        manager.create_service(manifest("svc1")).await.unwrap();
+        assert!(matches!(
+            manager.restore_archived(&archived.archive_id).await,
+            Err(ServiceError::AlreadyExists(_))
+        ));
+        manager.delete_service("svc1", false).await.unwrap();
+
+        let restored = manager
+            .restore_archived(&archived.archive_id)
+            .await
+            .unwrap();
+        assert_eq!(restored.id, "svc1");
+        assert_eq!(
+            std::fs::read_to_string(manager.log_path("svc1")).unwrap(),
+            "hello\n"
+        );
+        assert!(manager.list_archived().await.unwrap().is_empty());
+        assert!(matches!(
+            manager.restore_archived(&archived.archive_id).await,
+            Err(ServiceError::NotFound(_))
+        ));
+        assert!(matches!(
+            manager.restore_archived("../svc1-20240101000000000").await,
+            Err(ServiceError::InvalidId)
+        ));
+    }
+
     #[tokio::test]
     async fn tail_logs_empty_ok() {
         let dir = TempDir::new().unwrap();
@@ -206,6 +685,984 @@ mod tests {
         assert!(lines.is_empty());
     }
 
+    #[tokio::test]
+    async fn tail_logs_reads_across_rotated_segment() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        manager.create_service(manifest("svc1")).await.unwrap();
+        let latest = manager.log_path("svc1");
+        std::fs::write(logs::segment_path(&latest, 1), "one\ntwo\nthr").unwrap();
+        std::fs::write(&latest, "ee\nfour\n").unwrap();
+
+        let lines = manager.tail_logs("svc1", 3).unwrap();
+        assert_eq!(lines, vec!["two", "three", "four"]);
+
+        let raw = manager.tail_logs_raw("svc1", 10).unwrap();
+        assert_eq!(raw, b"hree\nfour\n".to_vec());
+
+        let ranges = manager.tail_log_ranges("svc1", 10).unwrap();
+        assert_eq!(
+            ranges,
+            [
+                (logs::segment_path(&latest, 1), 9, 2),
+                (latest.clone(), 0, 8)
+            ]
+        );
+        assert!(manager.tail_log_ranges("svc1", 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn attach_buffer_sets_output_channel_capacity() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path()).with_attach_buffer(2);
+        let tx = manager.output_channel();
+        let mut rx = tx.subscribe();
+        for chunk in [b"a", b"b", b"c"] {
+            tx.send(chunk.to_vec()).unwrap();
+        }
+        assert!(matches!(
+            rx.try_recv(),
+            Err(broadcast::error::TryRecvError::Lagged(1))
+        ));
+
+        // 容量 0 会让 broadcast::channel panic，按 1 处理
+        let manager = ServiceManager::new(dir.path()).with_attach_buffer(0);
+        assert_eq!(manager.attach_buffer, 1);
+        manager.output_channel();
+    }
+
+    #[tokio::test]
+    async fn follow_broadcast_resyncs_from_file_on_lag() {
+        use futures::StreamExt;
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("latest.log");
+        std::fs::write(&path, "old\n").unwrap();
+        let poll = std::time::Duration::from_millis(10);
+        let append = |data: &[u8]| {
+            let mut file = std::fs::OpenOptions::new()
+                .append(true)
+                .open(&path)
+                .unwrap();
+            std::io::Write::write_all(&mut file, data).unwrap();
+        };
+        let timeout = std::time::Duration::from_secs(5);
+
+        // 订阅前已写入文件但尚未广播的 "x\n"（如分离模式的尾随）也应由广播送达，不能被跳过
+        let cursor = logs::OutputCursor::default();
+        *cursor.lock() = 4;
+        append(b"x\n");
+
+        // 容量 2 时发 5 条，订阅者落后 3 条
+        let tx = broadcast::channel(2).0;
+        let (rx_drop, offset) = cursor.subscribe(&tx);
+        assert_eq!(offset, 4);
+        let (rx_resync, _) = cursor.subscribe(&tx);
+        let mut drop_stream =
+            logs::follow_broadcast("svc", rx_drop, path.clone(), offset, poll, LagPolicy::Drop);
+        let mut resync_stream = logs::follow_broadcast(
+            "svc",
+            rx_resync,
+            path.clone(),
+            offset,
+            poll,
+            LagPolicy::Resync,
+        );
+        tx.send(b"x\n".to_vec()).unwrap();
+        for chunk in ["a\n", "b\n", "c\n", "d\n"] {
+            append(chunk.as_bytes());
+            tx.send(chunk.as_bytes().to_vec()).unwrap();
+        }
+
+        let first = drop_stream.next().await.unwrap().unwrap();
+        assert_eq!(first, b"[dropped 3 messages]\n");
+        assert_eq!(drop_stream.next().await.unwrap().unwrap(), b"c\n");
+
+        let mut resynced = Vec::new();
+        while resynced.len() < 10 {
+            let chunk = tokio::time::timeout(timeout, resync_stream.next())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            resynced.extend(chunk);
+        }
+        assert_eq!(resynced, b"x\na\nb\nc\nd\n");
+
+        // 之后的输出继续从文件读到
+        append(b"e\n");
+        let next = tokio::time::timeout(timeout, resync_stream.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(next, b"e\n");
+    }
+
+    #[tokio::test]
+    async fn tail_logs_tolerates_concurrent_rotation_and_truncation() {
+        use std::io::Write;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        manager.create_service(manifest("svc1")).await.unwrap();
+        let latest = manager.log_path("svc1");
+
+        // 模拟输出线程：持续追加，并交替滚动（rename）与原地截断
+        let stop = Arc::new(AtomicBool::new(false));
+        let writer = {
+            let stop = stop.clone();
+            let latest = latest.clone();
+            std::thread::spawn(move || {
+                let mut n = 0u64;
+                while !stop.load(Ordering::Relaxed) {
+                    let mut file = std::fs::OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(&latest)
+                        .unwrap();
+                    for _ in 0..64 {
+                        writeln!(file, "line-{n:08} {}", "x".repeat(40)).unwrap();
+                        n += 1;
+                    }
+                    drop(file);
+                    match (n / 64) % 3 {
+                        0 => {
+                            let _ = std::fs::rename(&latest, logs::segment_path(&latest, 1));
+                        }
+                        1 => {
+                            let _ = std::fs::File::create(&latest);
+                        }
+                        _ => {}
+                    }
+                }
+            })
+        };
+
+        for _ in 0..500 {
+            manager.tail_logs("svc1", 100).unwrap();
+            manager.tail_logs_raw("svc1", 16 * 1024).unwrap();
+        }
+        stop.store(true, Ordering::Relaxed);
+        writer.join().unwrap();
+
+        let lines = manager.tail_logs("svc1", 10).unwrap();
+        assert!(lines.iter().all(|l| l.starts_with("line-")));
+    }
+
+    #[tokio::test]
+    async fn clear_logs_truncates_in_place() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        manager.create_service(manifest("svc1")).await.unwrap();
+        let latest = manager.log_path("svc1");
+        std::fs::write(logs::segment_path(&latest, 1), "old\n").unwrap();
+        std::fs::write(&latest, "first\n").unwrap();
+
+        // 模拟输出任务持有的 append 句柄
+        let mut writer = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&latest)
+            .unwrap();
+        manager.clear_logs("svc1").unwrap();
+        std::io::Write::write_all(&mut writer, b"second\n").unwrap();
+
+        assert_eq!(std::fs::read_to_string(&latest).unwrap(), "second\n");
+        assert!(!logs::segment_path(&latest, 1).exists());
+        assert!(matches!(
+            manager.clear_logs("missing"),
+            Err(ServiceError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn search_logs_returns_context_and_offsets() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        manager.create_service(manifest("svc1")).await.unwrap();
+        std::fs::write(
+            manager.log_path("svc1"),
+            "boot\nERROR disk\nok\nok\nERROR net\ntail\n",
+        )
+        .unwrap();
+
+        let hits = manager.search_logs("svc1", "^ERROR", 1, 10).unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].line_number, 2);
+        assert_eq!(hits[0].offset, 5);
+        assert_eq!(hits[0].before, vec!["boot"]);
+        assert_eq!(hits[0].after, vec!["ok"]);
+        assert_eq!(hits[1].line, "ERROR net");
+        assert_eq!(hits[1].after, vec!["tail"]);
+
+        let limited = manager.search_logs("svc1", "ERROR", 0, 1).unwrap();
+        assert_eq!(limited.len(), 1);
+
+        assert!(matches!(
+            manager.search_logs("svc1", "(unclosed", 0, 10),
+            Err(ServiceError::InvalidPattern(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn logs_between_filters_by_leading_timestamp() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        manager.create_service(manifest("svc1")).await.unwrap();
+        let latest = manager.log_path("svc1");
+        std::fs::write(
+            logs::segment_path(&latest, 1),
+            "2026-01-01T10:00:00Z boot\n",
+        )
+        .unwrap();
+        std::fs::write(
+            &latest,
+            "[2026-01-01T10:05:00Z] ERROR disk\n  at frame\n\x1b[32m2026-01-01T10:10:00Z\x1b[0m ok\n",
+        )
+        .unwrap();
+
+        let at = |s: &str| s.parse::<chrono::DateTime<chrono::Utc>>().unwrap();
+        let lines = manager
+            .logs_between("svc1", Some(at("2026-01-01T10:01:00Z")), None, 100)
+            .unwrap();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], "  at frame");
+
+        let lines = manager
+            .logs_between("svc1", None, Some(at("2026-01-01T10:05:00Z")), 100)
+            .unwrap();
+        assert_eq!(lines, vec!["2026-01-01T10:00:00Z boot"]);
+
+        let last = manager.logs_between("svc1", None, None, 1).unwrap();
+        assert_eq!(last.len(), 1);
+        assert!(last[0].ends_with("ok"));
+
+        assert!(logs::parse_log_timestamp("2026-01-01 10:00:00,123 INFO").is_some());
+        std::fs::write(logs::segment_path(&latest, 1), "").unwrap();
+        std::fs::write(&latest, "plain\nlines\n").unwrap();
+        assert!(matches!(
+            manager.logs_between("svc1", None, None, 100),
+            Err(ServiceError::NoTimestampedLogs(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn nested_groups_keep_tree_consistent() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        let ids = |groups: Vec<crate::models::ServiceGroup>| -> Vec<String> {
+            groups.into_iter().map(|g| g.id).collect()
+        };
+        for (id, parent) in [
+            ("a", None),
+            ("b", None),
+            ("a1", Some("a")),
+            ("a2", Some("a")),
+        ] {
+            manager
+                .create_group(id.into(), id.into(), None, parent.map(Into::into))
+                .await
+                .unwrap();
+        }
+        let err = manager
+            .create_group("x".into(), "x".into(), None, Some("missing".into()))
+            .await;
+        assert!(matches!(err, Err(ServiceError::InvalidGroup(_))));
+        assert_eq!(
+            ids(manager.list_groups().await.unwrap()),
+            ["a", "a1", "a2", "b"]
+        );
+
+        // 不能挂到自身或后代下
+        for parent in ["a", "a1"] {
+            let err = manager
+                .update_group("a", None, None, Some(Some(parent.into())))
+                .await;
+            assert!(matches!(err, Err(ServiceError::InvalidGroup(_))));
+        }
+        let moved = manager
+            .update_group("b", None, None, Some(Some("a1".into())))
+            .await
+            .unwrap();
+        assert_eq!(moved.parent.as_deref(), Some("a1"));
+
+        let reordered = manager
+            .reorder_groups(vec!["a2".into(), "a1".into()], Some("a".into()))
+            .await
+            .unwrap();
+        assert_eq!(ids(reordered), ["a", "a2", "a1", "b"]);
+        let err = manager.reorder_groups(vec!["a1".into()], None).await;
+        assert!(matches!(err, Err(ServiceError::InvalidGroup(_))));
+
+        let err = manager.delete_group("a", None).await;
+        assert!(matches!(err, Err(ServiceError::InvalidGroup(_))));
+        manager
+            .delete_group("a", Some(crate::models::ChildGroupAction::Reparent))
+            .await
+            .unwrap();
+        let groups = manager.list_groups().await.unwrap();
+        assert!(groups
+            .iter()
+            .filter(|g| g.id != "b")
+            .all(|g| g.parent.is_none()));
+        manager
+            .delete_group("a1", Some(crate::models::ChildGroupAction::Orphan))
+            .await
+            .unwrap();
+        assert_eq!(ids(manager.list_groups().await.unwrap()), ["a2", "b"]);
+    }
+
+    #[tokio::test]
+    async fn group_action_covers_subgroups_in_order() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        for (id, parent) in [("g", None), ("g1", Some("g")), ("other", None)] {
+            manager
+                .create_group(id.into(), id.into(), None, parent.map(Into::into))
+                .await
+                .unwrap();
+        }
+        for (id, group, order) in [("a", "g1", 2), ("b", "g", 1), ("c", "other", 0)] {
+            let mut svc = manifest(id);
+            svc.group = Some(group.into());
+            svc.order = order;
+            manager.create_service(svc).await.unwrap();
+        }
+
+        let ids: Vec<_> = manager
+            .group_services("g")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(ids, ["b", "a"]);
+
+        let results = manager
+            .group_action("g", crate::models::GroupAction::Stop)
+            .await
+            .unwrap();
+        let ids: Vec<_> = results.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, ["b", "a"]);
+        assert!(results.iter().all(|r| r.ok));
+        assert!(matches!(
+            manager
+                .group_action("missing", crate::models::GroupAction::Start)
+                .await,
+            Err(ServiceError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn bulk_tag_adds_and_removes_across_services() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        let mut svc = manifest("a");
+        svc.tags = vec!["old".into(), "keep".into()];
+        manager.create_service(svc).await.unwrap();
+        manager.create_service(manifest("b")).await.unwrap();
+
+        let ids = vec!["a".to_string(), "b".to_string(), "a".to_string()];
+        let summaries = manager
+            .bulk_tag(&ids, &["web".into(), " ".into()], &["old".into()])
+            .await
+            .unwrap();
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].tags, ["keep", "web"]);
+        assert_eq!(summaries[1].tags, ["web"]);
+        assert_eq!(
+            manager.load_manifest("a").await.unwrap().tags,
+            ["keep", "web"]
+        );
+
+        // 有不存在的服务时整体不生效
+        let ids = vec!["b".to_string(), "missing".to_string()];
+        let err = manager.bulk_tag(&ids, &[], &["web".into()]).await;
+        assert!(matches!(err, Err(ServiceError::NotFound(_))));
+        assert_eq!(manager.load_manifest("b").await.unwrap().tags, ["web"]);
+    }
+
+    #[tokio::test]
+    async fn logs_decode_with_configured_encoding() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        let mut svc = manifest("jp");
+        svc.log_encoding = Some("klingon".into());
+        assert!(matches!(
+            manager.create_service(svc.clone()).await,
+            Err(ServiceError::PolicyViolation(_))
+        ));
+        svc.log_encoding = Some("shift_jis".into());
+        manager.create_service(svc).await.unwrap();
+
+        // "日本語" 的 Shift-JIS 编码，默认的 UTF-8/GB18030 猜测会解错
+        let (bytes, _, _) = encoding_rs::SHIFT_JIS.encode("日本語 ok\r\n");
+        std::fs::write(manager.log_path("jp"), bytes.as_ref()).unwrap();
+        assert_eq!(manager.tail_logs("jp", 10).unwrap(), ["日本語 ok"]);
+        assert_eq!(manager.search_logs("jp", "本", 0, 10).unwrap().len(), 1);
+
+        // 实时流按块解码，被截断的双字节字符留到下一块
+        let mut decoder = manager.log_text_decoder("jp");
+        let (head, rest) = bytes.split_at(1);
+        let text = decoder.decode(head) + &decoder.decode(rest);
+        assert_eq!(text, "日本語 ok\r\n");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn attach_or_wait_waits_for_start() {
+        use std::time::Duration;
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        let mut svc = manifest("late");
+        svc.command = "sh".into();
+        svc.args = vec!["-c".into(), "sleep 5".into()];
+        manager.create_service(svc).await.unwrap();
+
+        assert!(matches!(
+            manager.attach_or_wait("late", Duration::ZERO).await,
+            Err(ServiceError::NotRunning(_))
+        ));
+        assert!(matches!(
+            manager
+                .attach_or_wait("late", Duration::from_millis(300))
+                .await,
+            Err(ServiceError::StartTimeout(_, _))
+        ));
+
+        let starter = manager.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            starter.start("late").await.unwrap();
+        });
+        let handle = manager
+            .attach_or_wait("late", Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(handle.pid > 0);
+        manager.kill("late").await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn lifecycle_stats_count_starts_and_crashes() {
+        use std::time::Duration;
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        let mut svc = manifest("flaky");
+        svc.command = "sh".into();
+        svc.args = vec!["-c".into(), "sleep 0.5; exit 3".into()];
+        manager.create_service(svc).await.unwrap();
+        assert_eq!(
+            manager.lifecycle_stats("flaky").await.unwrap(),
+            crate::models::LifecycleStats::default()
+        );
+
+        manager.start("flaky").await.unwrap();
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        let stats = loop {
+            let stats = manager.lifecycle_stats("flaky").await.unwrap();
+            if stats.total_crashes > 0 || tokio::time::Instant::now() >= deadline {
+                break stats;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        };
+        assert_eq!(stats.total_starts, 1);
+        assert_eq!(stats.total_crashes, 1);
+        assert_eq!(stats.auto_restarts, 0);
+        assert!(stats.last_start_at.is_some() && stats.last_crash_at.is_some());
+        assert!(stats.cumulative_uptime_ms > 0);
+
+        assert!(matches!(
+            manager.lifecycle_stats("missing").await,
+            Err(ServiceError::NotFound(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn kill_sends_configured_signal_before_sigkill() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+
+        let mut bogus = manifest("bogus");
+        bogus.kill_signal = Some("STOP".into());
+        assert!(matches!(
+            manager.create_service(bogus).await,
+            Err(ServiceError::InvalidInput(_))
+        ));
+
+        // 管道输出异步写入日志，按行轮询
+        let wait_line = |id: &'static str, line: &'static str| {
+            let manager = manager.clone();
+            async move {
+                let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(5);
+                while !manager
+                    .tail_logs(id, 10)
+                    .unwrap()
+                    .contains(&line.to_string())
+                {
+                    assert!(
+                        tokio::time::Instant::now() < deadline,
+                        "{id} never logged {line}"
+                    );
+                    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                }
+            }
+        };
+
+        // 捕获 TERM 后自行退出：不需要强杀
+        let mut svc = manifest("flush");
+        svc.command = "sh".into();
+        svc.args = vec![
+            "-c".into(),
+            "trap 'echo flushed; exit 0' TERM; echo ready; while true; do sleep 0.1; done".into(),
+        ];
+        svc.tty = false;
+        svc.kill_signal = Some("term".into());
+        manager.create_service(svc).await.unwrap();
+        manager.start("flush").await.unwrap();
+        wait_line("flush", "ready").await;
+        let status = manager.kill("flush").await.unwrap();
+        assert_eq!(status.stop_outcome, Some(StopOutcome::Graceful));
+        wait_line("flush", "flushed").await;
+
+        // 忽略 TERM：超时后升级为 SIGKILL
+        let mut svc = manifest("stubborn");
+        svc.command = "sh".into();
+        svc.args = vec![
+            "-c".into(),
+            "trap '' TERM; echo ready; while true; do sleep 0.1; done".into(),
+        ];
+        svc.tty = false;
+        svc.kill_signal = Some("SIGTERM".into());
+        svc.kill_timeout_secs = Some(1);
+        manager.create_service(svc).await.unwrap();
+        manager.start("stubborn").await.unwrap();
+        wait_line("stubborn", "ready").await;
+        let status = manager.kill("stubborn").await.unwrap();
+        assert_eq!(status.stop_outcome, Some(StopOutcome::Forced));
+        assert_eq!(
+            manager.status("stubborn").await.unwrap().state,
+            ServiceState::Stopped
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn stop_force_kills_after_shutdown_timeout() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        let wait_ready = |id: &'static str| {
+            let manager = manager.clone();
+            async move {
+                let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(5);
+                while !manager
+                    .tail_logs(id, 10)
+                    .unwrap()
+                    .contains(&"ready".to_string())
+                {
+                    assert!(tokio::time::Instant::now() < deadline, "{id} never started");
+                    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                }
+            }
+        };
+
+        // 读到关闭命令就退出
+        let mut svc = manifest("polite");
+        svc.command = "sh".into();
+        svc.args = vec![
+            "-c".into(),
+            "echo ready; read cmd; echo \"got $cmd\"".into(),
+        ];
+        svc.tty = false;
+        svc.shutdown_command = Some("quit".into());
+        svc.shutdown_timeout_secs = Some(5);
+        manager.create_service(svc).await.unwrap();
+        manager.start("polite").await.unwrap();
+        wait_ready("polite").await;
+        let status = manager.stop("polite").await.unwrap();
+        assert_eq!(status.stop_outcome, Some(StopOutcome::Graceful));
+
+        // 忽略关闭命令：超时后强制终止
+        let mut svc = manifest("deaf");
+        svc.command = "sh".into();
+        svc.args = vec![
+            "-c".into(),
+            "echo ready; while true; do sleep 0.1; done".into(),
+        ];
+        svc.tty = false;
+        svc.shutdown_command = Some("quit".into());
+        svc.shutdown_timeout_secs = Some(1);
+        manager.create_service(svc).await.unwrap();
+        manager.start("deaf").await.unwrap();
+        wait_ready("deaf").await;
+        let started = std::time::Instant::now();
+        let status = manager.stop("deaf").await.unwrap();
+        assert_eq!(status.stop_outcome, Some(StopOutcome::Forced));
+        assert!(started.elapsed() >= std::time::Duration::from_secs(1));
+        assert_eq!(
+            manager.status("deaf").await.unwrap().state,
+            ServiceState::Stopped
+        );
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(5);
+        while !manager
+            .tail_logs("deaf", 10)
+            .unwrap()
+            .iter()
+            .any(|line| line.contains("shutdown timed out after 1s"))
+        {
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "timeout was not logged"
+            );
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+    }
+
+    /// run_as 的停止路径：实际进程是 `sudo -u user cmd` 的子进程，sudo 或服务本身
+    /// 二次 fork 后孤儿会被重新挂到 init 下，只按父子关系收集会漏掉它。
+    /// 这里用一个会 double fork 的 shell 模拟，验证按进程组补全后孤儿也被终止。
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn kill_reaches_reparented_processes_in_service_group() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        let pid_file = dir.path().join("orphan.pid");
+
+        let mut svc = manifest("forking");
+        svc.command = "sh".into();
+        svc.args = vec![
+            "-c".into(),
+            format!(
+                "(sleep 300 & echo $! > {}); while true; do sleep 0.1; done",
+                pid_file.display()
+            ),
+        ];
+        svc.tty = false;
+        manager.create_service(svc).await.unwrap();
+        manager.start("forking").await.unwrap();
+
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(5);
+        let orphan: u32 = loop {
+            if let Some(pid) = std::fs::read_to_string(&pid_file)
+                .ok()
+                .and_then(|s| s.trim().parse().ok())
+            {
+                break pid;
+            }
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "orphan never started"
+            );
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        };
+        let service_pid = manager.read_pid("forking").unwrap().unwrap();
+        // 子 shell 退出后 sleep 不再是服务进程的后代，但仍在服务进程组中
+        let parent_of = |pid: u32| {
+            std::fs::read_to_string(format!("/proc/{pid}/stat"))
+                .ok()
+                .and_then(|stat| {
+                    let rest = stat.rsplit_once(") ")?.1.to_string();
+                    let mut fields = rest.split_whitespace();
+                    let state = fields.next()?.to_string();
+                    let ppid: u32 = fields.next()?.parse().ok()?;
+                    Some((state, ppid))
+                })
+        };
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(5);
+        while parent_of(orphan).map(|(_, ppid)| ppid) == Some(service_pid) {
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "orphan never reparented"
+            );
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+
+        manager.kill("forking").await.unwrap();
+
+        // 僵尸进程（等待 init 回收）视为已终止
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(5);
+        while parent_of(orphan).is_some_and(|(state, _)| state != "Z") {
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "orphan {orphan} survived stop"
+            );
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn piped_service_runs_without_tty() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        let mut svc = manifest("piped");
+        svc.command = "sh".into();
+        svc.args = vec![
+            "-c".into(),
+            "[ -t 1 ] && echo tty || echo pipe; echo oops >&2; read l; echo got:$l; sleep 5".into(),
+        ];
+        svc.tty = false;
+        manager.create_service(svc).await.unwrap();
+        manager.start("piped").await.unwrap();
+        manager.send_input("piped", b"hi\n".to_vec()).await.unwrap();
+
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(5);
+        let lines = loop {
+            let lines = manager.tail_logs("piped", 10).unwrap();
+            if lines.iter().any(|l| l == "got:hi") || tokio::time::Instant::now() >= deadline {
+                break lines;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        };
+        assert!(lines.contains(&"pipe".to_string()), "{lines:?}");
+        assert!(lines.contains(&"oops".to_string()), "{lines:?}");
+        assert!(lines.contains(&"got:hi".to_string()), "{lines:?}");
+        manager.kill("piped").await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn split_stderr_goes_to_its_own_log() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        let mut svc = manifest("split");
+        svc.command = "sh".into();
+        svc.args = vec!["-c".into(), "echo out; echo err >&2; sleep 5".into()];
+        svc.tty = false;
+        svc.split_stderr = true;
+        manager.create_service(svc).await.unwrap();
+        manager.start("split").await.unwrap();
+
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(5);
+        let (lines, errors) = loop {
+            let lines = manager.tail_logs("split", 10).unwrap();
+            let errors = manager.tail_stderr("split", 10).unwrap();
+            let done = !lines.is_empty() && !errors.is_empty();
+            if done || tokio::time::Instant::now() >= deadline {
+                break (lines, errors);
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        };
+        assert_eq!(lines, ["out"]);
+        assert_eq!(errors, ["err"]);
+        assert_eq!(manager.tail_stderr_raw("split", 1024).unwrap(), b"err\n");
+
+        manager.clear_logs("split").unwrap();
+        assert!(manager.tail_stderr("split", 10).unwrap().is_empty());
+        manager.kill("split").await.unwrap();
+    }
+
+    /// 旧版（v1）service.json：没有 schema_version，调度为单个 `schedule` 对象
+    const V1_MANIFEST: &str = r#"{
+        "id": "svc1",
+        "name": "Legacy",
+        "command": "cmd",
+        "args": ["--port", "25565"],
+        "env": {"MODE": "prod"},
+        "cwd": null,
+        "auto_start": true,
+        "auto_restart": false,
+        "schedule": {"enabled": true, "cron": "0 0 8 * * *", "action": "restart"},
+        "tags": ["game"],
+        "group": null,
+        "order": 0,
+        "log_path": null
+    }"#;
+
+    #[tokio::test]
+    async fn v1_manifest_is_migrated_and_written_back() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        manager.create_service(manifest("svc1")).await.unwrap();
+        std::fs::write(manager.manifest_path("svc1"), V1_MANIFEST).unwrap();
+
+        let loaded = manager.load_manifest("svc1").await.unwrap();
+        assert_eq!(loaded.schema_version, MANIFEST_SCHEMA_VERSION);
+        assert_eq!(loaded.name, "Legacy");
+        assert_eq!(loaded.args, vec!["--port", "25565"]);
+        assert!(loaded.auto_start);
+        assert_eq!(loaded.schedules.len(), 1);
+        assert_eq!(loaded.schedules[0].action, crate::ScheduleAction::Restart);
+
+        let raw = std::fs::read(manager.manifest_path("svc1")).unwrap();
+        let on_disk: serde_json::Value = serde_json::from_slice(&raw).unwrap();
+        assert_eq!(on_disk["schema_version"], MANIFEST_SCHEMA_VERSION);
+        assert!(on_disk.get("schedule").is_none());
+        assert_eq!(
+            on_disk["schedules"][0]["id"],
+            loaded.schedules[0].id.as_str()
+        );
+
+        // 已是当前版本时不再改动
+        let mut value = on_disk.clone();
+        assert!(!ServiceManager::migrate_manifest(&mut value).unwrap());
+        assert_eq!(value, on_disk);
+    }
+
+    #[tokio::test]
+    async fn newer_manifest_schema_is_rejected() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        manager.create_service(manifest("svc1")).await.unwrap();
+        let mut raw = serde_json::to_value(manifest("svc1")).unwrap();
+        raw["schema_version"] = (MANIFEST_SCHEMA_VERSION + 1).into();
+        std::fs::write(manager.manifest_path("svc1"), raw.to_string()).unwrap();
+
+        let err = manager.load_manifest("svc1").await.unwrap_err();
+        assert!(err.to_string().contains("newer than supported"));
+    }
+
+    #[tokio::test]
+    async fn invalid_cwd_and_command_paths_are_rejected_on_create() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::with_policy(dir.path(), None, vec![PathBuf::from("*")]);
+        let app_dir = dir.path().join("app");
+        std::fs::create_dir_all(app_dir.join("bin")).unwrap();
+        std::fs::write(app_dir.join("bin").join("run.sh"), b"").unwrap();
+
+        let invalid = |cwd: String, command: &str| {
+            let mut m = manifest("svc1");
+            m.cwd = Some(cwd);
+            m.command = command.into();
+            m
+        };
+        let app = app_dir.to_string_lossy().to_string();
+        let cases = [
+            (invalid("app".into(), "cmd"), "cwd"),
+            (invalid(format!("{app}/../app"), "cmd"), "cwd"),
+            (invalid(format!("{app}/bin/run.sh"), "cmd"), "cwd"),
+            (invalid(app.clone(), "./bin/missing.sh"), "command"),
+        ];
+        for (m, expected) in cases {
+            match manager.create_service(m).await.unwrap_err() {
+                ServiceError::PolicyViolation(crate::PolicyReason::InvalidPath {
+                    field, ..
+                }) => assert_eq!(field, expected),
+                other => panic!("expected {expected} rejection, got {other:?}"),
+            }
+        }
+
+        // 相对命令按 cwd 解析
+        manager
+            .create_service(invalid(app, "./bin/run.sh"))
+            .await
+            .unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn cwd_templates_and_default_root_are_resolved() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("work");
+        let manager = ServiceManager::new(dir.path()).with_default_cwd_root(&root);
+
+        let pwd = |id: &str| {
+            let mut m = manifest(id);
+            m.command = "sh".into();
+            m.args = vec!["-c".into(), "pwd -P; exec sleep 30".into()];
+            m.tty = false;
+            m
+        };
+        let wait_pwd = |id: &'static str| {
+            let manager = manager.clone();
+            async move {
+                let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(5);
+                loop {
+                    if let Some(line) = manager.tail_logs(id, 10).unwrap().into_iter().next() {
+                        return PathBuf::from(line);
+                    }
+                    assert!(
+                        tokio::time::Instant::now() < deadline,
+                        "{id} never printed its cwd"
+                    );
+                    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                }
+            }
+        };
+
+        // 未设置 cwd：首次启动时创建 <root>/<id>
+        manager.create_service(pwd("plain")).await.unwrap();
+        manager.start("plain").await.unwrap();
+        assert_eq!(
+            wait_pwd("plain").await,
+            root.join("plain").canonicalize().unwrap()
+        );
+        manager.kill("plain").await.unwrap();
+
+        // ${id} / ${name} 按服务展开
+        let named = dir.path().join("srv").join("tpl-Named");
+        std::fs::create_dir_all(&named).unwrap();
+        let mut svc = pwd("tpl");
+        svc.name = "Named".into();
+        svc.cwd = Some(format!("{}/srv/${{id}}-${{name}}", dir.path().display()));
+        manager.create_service(svc).await.unwrap();
+        manager.start("tpl").await.unwrap();
+        assert_eq!(wait_pwd("tpl").await, named.canonicalize().unwrap());
+        manager.kill("tpl").await.unwrap();
+
+        let mut svc = pwd("unknown");
+        svc.cwd = Some(format!("{}/${{user}}", dir.path().display()));
+        assert!(matches!(
+            manager.create_service(svc).await,
+            Err(ServiceError::PolicyViolation(
+                crate::PolicyReason::InvalidPath { field: "cwd", .. }
+            ))
+        ));
+
+        // 默认根目录同样受工作目录白名单约束
+        let outside = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path()).with_default_cwd_root(outside.path());
+        assert!(matches!(
+            manager.create_service(pwd("outside")).await,
+            Err(ServiceError::PolicyViolation(
+                crate::PolicyReason::PathRoot { field: "cwd", .. }
+            ))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn start_many_skips_dependents_of_failed_services() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        for (id, script, deps) in [
+            ("db", "exit 1", vec![]),
+            ("app", "sleep 30", vec!["db".to_string()]),
+            ("cache", "sleep 30", vec![]),
+        ] {
+            let mut svc = manifest(id);
+            svc.command = "sh".into();
+            svc.args = vec!["-c".into(), script.into()];
+            svc.depends_on = deps;
+            manager.create_service(svc).await.unwrap();
+        }
+
+        let results = manager
+            .start_many(&["app".into(), "cache".into()], 2)
+            .await
+            .unwrap();
+        let result = |id: &str| &results.iter().find(|(r, _)| r == id).unwrap().1;
+        assert_eq!(results.len(), 3);
+        assert!(result("db").is_err());
+        match result("app") {
+            Err(ServiceError::SpawnFailed(msg)) => assert!(msg.contains("dependency db")),
+            other => panic!("unexpected result: {other:?}"),
+        }
+        assert_eq!(
+            result("cache").as_ref().unwrap().state,
+            ServiceState::Running
+        );
+        assert_eq!(
+            manager.status("app").await.unwrap().state,
+            ServiceState::Stopped
+        );
+
+        manager.stop("cache").await.unwrap();
+    }
+
     #[tokio::test]
     async fn update_keeps_created_at() {
         let dir = TempDir::new().unwrap();
@@ -227,6 +1684,29 @@ mod tests {
         assert_eq!(loaded.created_at, base.created_at);
     }
 
+    #[tokio::test]
+    async fn legacy_single_schedule_loads_with_stable_id() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        manager.create_service(manifest("svc1")).await.unwrap();
+
+        // 旧版 manifest 只有单个 schedule 对象
+        let mut raw = serde_json::to_value(manifest("svc1")).unwrap();
+        let obj = raw.as_object_mut().unwrap();
+        obj.remove("schedules");
+        obj.insert(
+            "schedule".into(),
+            serde_json::json!({"enabled": true, "cron": "0 0 8 * * *", "action": "start"}),
+        );
+        std::fs::write(manager.manifest_path("svc1"), raw.to_string()).unwrap();
+
+        let first = manager.load_manifest("svc1").await.unwrap();
+        assert_eq!(first.schedules.len(), 1);
+        assert!(!first.schedules[0].id.is_empty());
+        let second = manager.load_manifest("svc1").await.unwrap();
+        assert_eq!(second.schedules[0].id, first.schedules[0].id);
+    }
+
     #[tokio::test]
     async fn policy_rejects_command() {
         let dir = TempDir::new().unwrap();
@@ -267,6 +1747,36 @@ mod tests {
             .unwrap();
     }
 
+    #[tokio::test]
+    async fn clone_service_copies_manifest_only() {
+        let dir = TempDir::new().unwrap();
+        let mgr = ServiceManager::new(dir.path());
+        let mut src = manifest("web-1");
+        src.args = vec!["--port".into(), "8001".into()];
+        mgr.create_service(src).await.unwrap();
+        std::fs::write(mgr.log_path("web-1"), "old logs").unwrap();
+
+        let clone = mgr
+            .clone_service(
+                "web-1",
+                "web-2",
+                serde_json::json!({ "args": ["--port", "8002"], "id": "ignored" }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(clone.id, "web-2");
+        assert_eq!(clone.name, "web-2");
+        assert_eq!(clone.args, vec!["--port", "8002"]);
+        assert_eq!(clone.command, "cmd");
+        assert!(!mgr.log_path("web-2").exists());
+
+        let err = mgr
+            .clone_service("web-1", "web-2", serde_json::Value::Null)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::AlreadyExists(_)));
+    }
+
     #[tokio::test]
     async fn policy_rejects_same_basename_path_bypass() {
         let dir = TempDir::new().unwrap();
@@ -319,6 +1829,7 @@ mod tests {
         let manager = ServiceManager::with_policy(dir.path(), Some(allowed), vec![]);
 
         let mut ok = manifest("svc_path_ok");
+        let allowed_path_for_report = allowed_path.clone();
         ok.command = allowed_path;
         manager.create_service(ok).await.unwrap();
 
@@ -332,7 +1843,33 @@ mod tests {
         let mut evil = manifest("svc_path_evil");
         evil.command = other.canonicalize().unwrap().to_string_lossy().into();
         let err = manager.create_service(evil).await.unwrap_err();
-        assert!(matches!(err, ServiceError::PolicyViolation(_)));
+        match err {
+            ServiceError::PolicyViolation(crate::PolicyReason::Command { allowed, .. }) => {
+                assert_eq!(allowed, vec![allowed_path_for_report])
+            }
+            other => panic!("expected command rejection, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn policy_reports_rejected_cwd_and_allowed_roots() {
+        let dir = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        let mut m = manifest("svc1");
+        m.cwd = Some(outside.path().to_string_lossy().into());
+        let err = manager.create_service(m).await.unwrap_err();
+        match err {
+            ServiceError::PolicyViolation(crate::PolicyReason::PathRoot {
+                field,
+                allowed_roots,
+                ..
+            }) => {
+                assert_eq!(field, "cwd");
+                assert_eq!(allowed_roots.len(), 1);
+            }
+            other => panic!("expected cwd rejection, got {other:?}"),
+        }
     }
 
     #[tokio::test]
@@ -340,7 +1877,7 @@ mod tests {
         let dir = TempDir::new().unwrap();
         let manager = ServiceManager::with_policy(dir.path(), None, vec![PathBuf::from("*")]);
         let mut m = manifest("svc1");
-        m.cwd = Some("C:/anywhere".into());
+        m.cwd = Some(dir.path().to_string_lossy().into());
         m.web = Some(crate::WebConfig {
             enabled: true,
             upstream: "http://example.com:3000".into(),
@@ -375,6 +1912,121 @@ mod tests {
         matches!(err, ServiceError::NotRunning(_));
     }
 
+    #[tokio::test]
+    async fn reconcile_adopts_live_pid_and_clears_stale() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        manager.create_service(manifest("live")).await.unwrap();
+        manager.create_service(manifest("stale")).await.unwrap();
+        // 测试进程自身充当“API 重启前启动的服务”
+        manager.write_pid("live", std::process::id()).unwrap();
+        let (program, args): (&str, &[&str]) = if cfg!(windows) {
+            ("cmd", &["/C", "exit"])
+        } else {
+            ("true", &[])
+        };
+        let mut exited = std::process::Command::new(program)
+            .args(args)
+            .spawn()
+            .unwrap();
+        let dead_pid = exited.id();
+        exited.wait().unwrap();
+        manager.write_pid("stale", dead_pid).unwrap();
+
+        assert_eq!(manager.reconcile().await.unwrap(), vec!["live".to_string()]);
+        assert!(manager.read_pid("stale").unwrap().is_none());
+        let err = manager.attach("live").await.unwrap_err();
+        assert!(matches!(err, ServiceError::NotOwned(_)), "got {err:?}");
+    }
+
+    #[tokio::test]
+    async fn send_input_requires_owned_running_service() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        manager.create_service(manifest("svc1")).await.unwrap();
+        let err = manager
+            .send_input("svc1", b"x\n".to_vec())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::NotRunning(_)), "got {err:?}");
+
+        manager.write_pid("svc1", std::process::id()).unwrap();
+        let err = manager
+            .send_input("svc1", b"x\n".to_vec())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::NotOwned(_)), "got {err:?}");
+    }
+
+    #[tokio::test]
+    async fn send_signal_parses_names_and_requires_running() {
+        assert_eq!(parse_signal("HUP"), Some(sysinfo::Signal::Hangup));
+        assert_eq!(parse_signal("sigusr1"), Some(sysinfo::Signal::User1));
+        assert_eq!(parse_signal("STOP"), None);
+
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        manager.create_service(manifest("svc1")).await.unwrap();
+        let err = manager
+            .send_signal("svc1", sysinfo::Signal::Kill)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::NotRunning(_)), "got {err:?}");
+    }
+
+    #[tokio::test]
+    async fn recycled_pid_is_reported_stopped() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        manager.create_service(manifest("svc1")).await.unwrap();
+        manager.write_pid("svc1", std::process::id()).unwrap();
+        let content = std::fs::read_to_string(manager.pid_path("svc1")).unwrap();
+        assert_eq!(
+            content.lines().count(),
+            2,
+            "start time recorded: {content:?}"
+        );
+        assert_eq!(
+            manager.status("svc1").await.unwrap().state,
+            ServiceState::Running
+        );
+
+        // 同一 pid 但启动时间不同：模拟服务崩溃后 pid 被其它进程复用
+        std::fs::write(
+            manager.pid_path("svc1"),
+            format!("{}\n1", std::process::id()),
+        )
+        .unwrap();
+        let status = manager.status("svc1").await.unwrap();
+        assert_eq!(status.state, ServiceState::Stopped);
+        assert!(status.pid.is_none());
+        assert!(!manager.pid_path("svc1").exists());
+        let err = manager.stop("svc1").await.unwrap_err();
+        assert!(matches!(err, ServiceError::NotRunning(_)), "got {err:?}");
+    }
+
+    #[test]
+    fn check_writable_reports_unusable_data_dir() {
+        let dir = TempDir::new().unwrap();
+        ServiceManager::new(dir.path()).check_writable().unwrap();
+        assert!(!dir.path().join("services/.write-probe").exists());
+
+        // data_dir 是普通文件，无法在其下创建目录
+        let file = dir.path().join("not-a-dir");
+        std::fs::write(&file, "").unwrap();
+        let err = ServiceManager::new(&file).check_writable().unwrap_err();
+        match err {
+            ServiceError::StorageUnavailable(msg) => assert!(msg.contains("not-a-dir"), "{msg}"),
+            other => panic!("unexpected error: {other:?}"),
+        }
+
+        let full = std::io::Error::from(std::io::ErrorKind::StorageFull);
+        assert!(matches!(
+            ServiceError::from(full),
+            ServiceError::StorageUnavailable(_)
+        ));
+    }
+
     #[tokio::test]
     async fn stop_is_idempotent_when_pid_missing() {
         let dir = TempDir::new().unwrap();
@@ -387,4 +2039,18 @@ mod tests {
         let status = manager.stop("svc1").await.unwrap();
         assert_eq!(status.state, ServiceState::Stopped);
     }
+
+    #[tokio::test]
+    async fn status_reports_last_exit_when_stopped() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        manager.create_service(manifest("svc1")).await.unwrap();
+        manager.write_last_exit("svc1", &portable_pty::ExitStatus::with_exit_code(3));
+
+        let status = manager.status("svc1").await.unwrap();
+        assert_eq!(status.state, ServiceState::Stopped);
+        assert_eq!(status.last_exit_code, Some(3));
+        assert!(status.last_exit_signal.is_none());
+        assert!(status.last_exit_at.is_some());
+    }
 }
@@ -0,0 +1,150 @@
+//! 管道模式（`tty: false`）：不分配 PTY，子进程的 stdout/stderr 经管道读取。
+//!
+//! 两路输出按整行合并成一个读取端，交给与 PTY 相同的输出处理（写日志 + 广播），
+//! 因此同一行内不会交错；没有换行的输出要等到换行或进程退出才会出现。
+//! 开启 `split_stderr` 时 stderr 不参与合并，单独交给调用方写入 stderr.log。
+//! stdin 同样接管道，`send_input` 与关闭命令照常可用。
+
+use super::*;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc as std_mpsc;
+
+/// 管道模式的子进程
+pub(super) struct PipedProcess {
+    pub child: Child,
+    /// 输出读取端（未分离时已合并 stderr）
+    pub output: Box<dyn Read + Send>,
+    /// 单独的 stderr 读取端，仅 `split_stderr` 时存在
+    pub stderr: Option<Box<dyn Read + Send>>,
+    pub stdin: Box<dyn Write + Send>,
+}
+
+impl ServiceManager {
+    /// 以管道方式启动子进程。
+    pub(super) fn spawn_piped_process(&self, manifest: &ServiceManifest) -> Result<PipedProcess> {
+        let (program, args) = lifecycle::command_line(manifest);
+        let mut cmd = Command::new(program);
+        cmd.args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .envs(self.spawn_env(manifest)?);
+        if let Some(cwd) = self.effective_cwd(manifest) {
+            cmd.current_dir(cwd);
+        }
+        // 独立进程组：与 PTY 模式一样不接收 API 所在终端的 Ctrl+C
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
+
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| ServiceError::SpawnFailed(e.to_string()))?;
+        if let Err(e) = self.apply_resource_limits(manifest, child.id()) {
+            let _ = child.kill();
+            return Err(e);
+        }
+
+        let (Some(stdin), Some(stdout), Some(stderr)) =
+            (child.stdin.take(), child.stdout.take(), child.stderr.take())
+        else {
+            let _ = child.kill();
+            return Err(ServiceError::SpawnFailed("missing stdio pipes".into()));
+        };
+        let (output, stderr): (Box<dyn Read + Send>, Option<Box<dyn Read + Send>>) =
+            if manifest.split_stderr {
+                (Box::new(stdout), Some(Box::new(stderr)))
+            } else {
+                let merged = LineMerger::new(vec![Box::new(stdout), Box::new(stderr)]);
+                (Box::new(merged), None)
+            };
+        Ok(PipedProcess {
+            child,
+            output,
+            stderr,
+            stdin: Box::new(stdin),
+        })
+    }
+}
+
+/// 把多路输出按整行合并为一个 `Read`；所有输入结束后读到 EOF。
+struct LineMerger {
+    rx: std_mpsc::Receiver<Vec<u8>>,
+    pending: Vec<u8>,
+    pos: usize,
+}
+
+impl LineMerger {
+    fn new(sources: Vec<Box<dyn Read + Send>>) -> Self {
+        let (tx, rx) = std_mpsc::channel();
+        for source in sources {
+            let tx = tx.clone();
+            std::thread::spawn(move || {
+                let mut reader = BufReader::new(source);
+                loop {
+                    let mut line = Vec::new();
+                    match reader.read_until(b'\n', &mut line) {
+                        Ok(0) | Err(_) => break,
+                        Ok(_) => {
+                            if tx.send(line).is_err() {
+                                break;
+                            }
+                        }
+                    }
+                }
+            });
+        }
+        Self {
+            rx,
+            pending: Vec::new(),
+            pos: 0,
+        }
+    }
+}
+
+impl Read for LineMerger {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.pos >= self.pending.len() {
+            match self.rx.recv() {
+                Ok(line) => {
+                    self.pending = line;
+                    self.pos = 0;
+                }
+                Err(_) => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.pending.len() - self.pos);
+        buf[..n].copy_from_slice(&self.pending[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_merger_keeps_lines_whole() {
+        let mut merger = LineMerger::new(vec![
+            Box::new(&b"out one\nout two\n"[..]),
+            Box::new(&b"err one\n"[..]),
+        ]);
+        let mut text = String::new();
+        merger.read_to_string(&mut text).unwrap();
+
+        let mut lines: Vec<&str> = text.split_inclusive('\n').collect();
+        lines.sort_unstable();
+        assert_eq!(lines, ["err one\n", "out one\n", "out two\n"]);
+        assert!(text.find("out one").unwrap() < text.find("out two").unwrap());
+
+        // 末尾没有换行的输出在 EOF 时照样交出
+        let mut merger = LineMerger::new(vec![Box::new(&b"a\nb"[..])]);
+        let mut text = String::new();
+        merger.read_to_string(&mut text).unwrap();
+        assert_eq!(text, "a\nb");
+    }
+}
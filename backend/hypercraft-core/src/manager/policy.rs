@@ -1,52 +1,208 @@
 use super::*;
-use crate::{validate_web_upstream_url, WebConfig};
+use crate::error::PolicyReason;
+use crate::{
+    validate_health_check_url, validate_service_webhook_url, validate_web_upstream_url, WebConfig,
+};
 use std::path::{Component, Path};
 
 impl ServiceManager {
     /// 策略校验：命令 & 工作目录白名单。
     pub(super) fn enforce_policy(&self, manifest: &ServiceManifest) -> Result<()> {
+        self.enforce_policy_inner(manifest, true)
+    }
+
+    /// 同 [`enforce_policy`](Self::enforce_policy)；`check_cwd` 为 false 时跳过 cwd
+    /// 与默认工作目录检查（干跑校验已单独报告了无效的 cwd）。
+    pub(super) fn enforce_policy_inner(
+        &self,
+        manifest: &ServiceManifest,
+        check_cwd: bool,
+    ) -> Result<()> {
         // 命令白名单：裸名仅匹配裸名；含路径时必须与路径型条目规范化后精确相等
         if let Some(allowed) = &self.allowed_commands {
             if !is_command_allowed(&manifest.command, allowed) {
-                return Err(ServiceError::PolicyViolation(format!(
-                    "command not allowed: {}",
-                    manifest.command
-                )));
+                let mut allowed: Vec<String> = allowed.iter().cloned().collect();
+                allowed.sort();
+                return Err(ServiceError::PolicyViolation(PolicyReason::Command {
+                    command: manifest.command.clone(),
+                    allowed,
+                }));
             }
         }
 
-        // cwd 白名单：必须在 data_dir 或配置的前缀下
-        if let Some(cwd) = &manifest.cwd {
-            // 特殊值 "*" 表示无限制
-            if !self.allowed_cwd_roots.iter().any(|p| p.as_os_str() == "*") {
-                let cwd_path = PathBuf::from(cwd);
-                let canonical = cwd_path
-                    .canonicalize()
-                    .map_err(|_| ServiceError::PolicyViolation("cwd not accessible".into()))?;
-                let mut ok = canonical.starts_with(&self.data_dir);
-                if !ok {
-                    for root in &self.allowed_cwd_roots {
-                        if canonical.starts_with(root) {
-                            ok = true;
-                            break;
-                        }
-                    }
-                }
-                if !ok {
-                    return Err(ServiceError::PolicyViolation(format!(
-                        "cwd not allowed: {}",
-                        canonical.display()
-                    )));
-                }
+        // cwd：绝对路径、无 `..`、目录存在，且在 data_dir 或配置的前缀下；
+        // 未设置时检查默认工作目录（启动时才创建，这里只按路径判断）
+        match &manifest.cwd {
+            _ if !check_cwd => {}
+            Some(cwd) => {
+                self.check_cwd(&expand_cwd(cwd, manifest))?;
+            }
+            None => self.check_default_cwd(&manifest.id)?,
+        }
+        self.check_command_path(manifest)?;
+
+        // env_file 与 cwd 共用白名单；文件可在启动前才创建，不存在时按词法规范化判断
+        if let Some(path) = self.env_file_path(manifest) {
+            let normalized = path
+                .canonicalize()
+                .unwrap_or_else(|_| lexical_normalize(&path));
+            if !self.path_allowed(&normalized) {
+                return Err(self.path_not_allowed("env_file", &normalized));
             }
         }
 
+        if manifest.memory_limit_mb == Some(0) || manifest.cpu_quota_percent == Some(0) {
+            return Err(ServiceError::PolicyViolation(
+                "memory_limit_mb and cpu_quota_percent must be greater than 0".into(),
+            ));
+        }
+        if let Some(mb) = manifest.memory_limit_mb {
+            cgroup::memory_limit_bytes(mb)?;
+        }
+
         if let Some(web) = &manifest.web {
             self.validate_web_upstream(web)?;
         }
+        if let Some(check) = &manifest.health_check {
+            validate_health_check_url(&check.url)?;
+        }
+        for hook in &manifest.webhooks {
+            validate_service_webhook_url(&hook.url)?;
+        }
+        Ok(())
+    }
+
+    /// 校验（已展开模板的）工作目录，返回规范化后的路径。
+    ///
+    /// 在创建/更新时就拒绝相对路径、`..` 穿越与不存在的目录，而不是等到首次启动才失败。
+    pub(super) fn check_cwd(&self, cwd: &str) -> Result<PathBuf> {
+        let invalid = |problem| {
+            ServiceError::PolicyViolation(PolicyReason::InvalidPath {
+                field: "cwd",
+                path: cwd.to_string(),
+                problem,
+            })
+        };
+        if cwd.contains("${") {
+            return Err(invalid(
+                "contains an unknown template token (only ${id} and ${name} are supported)",
+            ));
+        }
+        let path = Path::new(cwd);
+        if !path.is_absolute() {
+            return Err(invalid("must be an absolute path"));
+        }
+        if path.components().any(|c| matches!(c, Component::ParentDir)) {
+            return Err(invalid("must not contain `..`"));
+        }
+        let canonical = path
+            .canonicalize()
+            .map_err(|_| invalid("does not exist or is not accessible"))?;
+        if !canonical.is_dir() {
+            return Err(invalid("is not a directory"));
+        }
+        if !self.path_allowed(&canonical) {
+            return Err(self.path_not_allowed("cwd", &canonical));
+        }
+        Ok(canonical)
+    }
+
+    /// 含路径的命令必须指向已存在的文件：相对路径按实际工作目录解析（两者都未设置时按
+    /// API 进程的当前目录，与启动时一致）。裸命令名启动时经 PATH 查找，这里不检查。
+    fn check_command_path(&self, manifest: &ServiceManifest) -> Result<()> {
+        if is_bare_command(&manifest.command) {
+            return Ok(());
+        }
+        let command = Path::new(&manifest.command);
+        let resolved = if command.is_absolute() {
+            command.to_path_buf()
+        } else {
+            let base = match self.effective_cwd(manifest) {
+                Some(cwd) => cwd,
+                None => std::env::current_dir()?,
+            };
+            lexical_normalize(&base.join(command))
+        };
+        if !resolved.is_file() {
+            return Err(ServiceError::PolicyViolation(PolicyReason::InvalidPath {
+                field: "command",
+                path: resolved.display().to_string(),
+                problem: "does not exist or is not a file",
+            }));
+        }
+        Ok(())
+    }
+
+    /// 未设置 cwd 时的默认工作目录 `<root>/<id>`；未配置默认根目录时为 None。
+    pub(super) fn default_cwd(&self, id: &str) -> Option<PathBuf> {
+        self.default_cwd_root.as_ref().map(|root| root.join(id))
+    }
+
+    /// 启动时实际使用的工作目录：展开 cwd 中的模板，未设置时取默认工作目录；
+    /// 都没有时为 None，子进程继承 API 进程的当前目录。
+    pub(super) fn effective_cwd(&self, manifest: &ServiceManifest) -> Option<PathBuf> {
+        match &manifest.cwd {
+            Some(cwd) => Some(PathBuf::from(expand_cwd(cwd, manifest))),
+            None => self.default_cwd(&manifest.id),
+        }
+    }
+
+    /// 默认工作目录须为绝对路径并位于白名单内；目录可以尚不存在。
+    fn check_default_cwd(&self, id: &str) -> Result<()> {
+        let Some(dir) = self.default_cwd(id) else {
+            return Ok(());
+        };
+        if !dir.is_absolute() {
+            return Err(ServiceError::PolicyViolation(PolicyReason::InvalidPath {
+                field: "cwd",
+                path: dir.display().to_string(),
+                problem: "default working directory must be an absolute path",
+            }));
+        }
+        let normalized = dir
+            .canonicalize()
+            .unwrap_or_else(|_| lexical_normalize(&dir));
+        if !self.path_allowed(&normalized) {
+            return Err(self.path_not_allowed("cwd", &normalized));
+        }
         Ok(())
     }
 
+    /// 路径是否位于 data_dir 或配置的前缀下（特殊值 "*" 表示无限制）。
+    pub(super) fn path_allowed(&self, path: &Path) -> bool {
+        path.starts_with(&self.data_dir)
+            || self
+                .allowed_cwd_roots
+                .iter()
+                .any(|root| root.as_os_str() == "*" || path.starts_with(root))
+    }
+
+    /// 路径越出白名单时的结构化错误，附带允许的根目录列表。
+    pub(super) fn path_not_allowed(&self, field: &'static str, path: &Path) -> ServiceError {
+        let allowed_roots = std::iter::once(&self.data_dir)
+            .chain(&self.allowed_cwd_roots)
+            .map(|root| root.display().to_string())
+            .collect();
+        ServiceError::PolicyViolation(PolicyReason::PathRoot {
+            field,
+            path: path.display().to_string(),
+            allowed_roots,
+        })
+    }
+
+    /// env_file 的绝对路径：相对路径基于 cwd，未设置 cwd 时基于服务目录。
+    pub(super) fn env_file_path(&self, manifest: &ServiceManifest) -> Option<PathBuf> {
+        let file = PathBuf::from(manifest.env_file.as_ref()?);
+        if file.is_absolute() {
+            return Some(file);
+        }
+        let base = match &manifest.cwd {
+            Some(cwd) => PathBuf::from(expand_cwd(cwd, manifest)),
+            None => self.service_dir(&manifest.id),
+        };
+        Some(base.join(file))
+    }
+
     /// Web 上游地址必须限定为宿主机本地地址，避免代理能力被滥用。
     fn validate_web_upstream(&self, web: &WebConfig) -> Result<()> {
         if !web.enabled {
@@ -145,3 +301,9 @@ fn is_command_allowed(command: &str, allowed: &HashSet<String>) -> bool {
             .unwrap_or(false)
     })
 }
+
+/// 展开 cwd 中的模板变量 `${id}` 与 `${name}`
+pub(super) fn expand_cwd(cwd: &str, manifest: &ServiceManifest) -> String {
+    cwd.replace("${id}", &manifest.id)
+        .replace("${name}", &manifest.name)
+}
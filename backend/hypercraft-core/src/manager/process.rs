@@ -1,33 +1,164 @@
 //! 进程管理底层操作：PID 文件读写、进程状态检查、进程终止。
 
 use super::*;
+use crate::models::LifecycleStats;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use std::fs;
-use sysinfo::{Pid, ProcessRefreshKind};
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, ProcessRefreshKind, Signal};
+
+/// 全量进程快照的有效期；仪表盘并发轮询时共享同一次刷新
+const PROCESS_SNAPSHOT_TTL: Duration = Duration::from_secs(1);
+
+/// 一次全量刷新得到的存活进程表：pid → 运行时长（毫秒）
+#[derive(Debug, Clone)]
+pub(super) struct ProcessSnapshot {
+    taken_at: Instant,
+    uptimes: Arc<HashMap<u32, u64>>,
+}
+
+/// 上次退出记录，落盘为 runtime/last_exit.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct ExitRecord {
+    pub code: i32,
+    #[serde(default)]
+    pub signal: Option<String>,
+    pub at: DateTime<Utc>,
+}
 
 impl ServiceManager {
+    /// 记录进程退出状态；写入失败只记日志，不影响清理流程。
+    pub(super) fn write_last_exit(&self, id: &str, status: &portable_pty::ExitStatus) {
+        // portable_pty 未暴露信号字段，只能从 Display 文本中提取
+        let text = status.to_string();
+        let record = ExitRecord {
+            code: status.exit_code() as i32,
+            signal: text.strip_prefix("Terminated by ").map(str::to_string),
+            at: Utc::now(),
+        };
+        let result = fs::create_dir_all(self.runtime_dir(id))
+            .map_err(ServiceError::from)
+            .and_then(|_| Ok(serde_json::to_vec(&record)?))
+            .and_then(|data| Ok(fs::write(self.last_exit_path(id), data)?));
+        if let Err(e) = result {
+            tracing::warn!(service_id = %id, error = %e, "failed to record exit status");
+        }
+    }
+
+    /// 读取上次退出记录（不存在或损坏时返回 None）。
+    pub(super) fn read_last_exit(&self, id: &str) -> Option<ExitRecord> {
+        let data = fs::read(self.last_exit_path(id)).ok()?;
+        serde_json::from_slice(&data).ok()
+    }
+
+    /// 服务的生命周期计数（启动/崩溃/自动重启次数与累计运行时长）。
+    pub async fn lifecycle_stats(&self, id: &str) -> Result<LifecycleStats> {
+        self.load_manifest(id).await?;
+        Ok(self.read_lifecycle_stats(id))
+    }
+
+    /// 读取生命周期计数；不存在或损坏时从零开始。
+    fn read_lifecycle_stats(&self, id: &str) -> LifecycleStats {
+        fs::read(self.lifecycle_stats_path(id))
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    /// 读改写生命周期计数；写入失败只记日志，不影响启停流程。
+    fn update_lifecycle_stats(&self, id: &str, f: impl FnOnce(&mut LifecycleStats)) {
+        let _guard = self
+            .lifecycle_stats_lock
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let mut stats = self.read_lifecycle_stats(id);
+        f(&mut stats);
+        let result = fs::create_dir_all(self.runtime_dir(id))
+            .map_err(ServiceError::from)
+            .and_then(|_| Ok(serde_json::to_vec(&stats)?))
+            .and_then(|data| Ok(fs::write(self.lifecycle_stats_path(id), data)?));
+        if let Err(e) = result {
+            tracing::warn!(service_id = %id, error = %e, "failed to record lifecycle stats");
+        }
+    }
+
+    /// 记录一次成功拉起的进程
+    pub(super) fn record_start(&self, id: &str, auto_restart: bool) {
+        self.update_lifecycle_stats(id, |stats| {
+            stats.total_starts += 1;
+            if auto_restart {
+                stats.auto_restarts += 1;
+            }
+            stats.last_start_at = Some(Utc::now());
+        });
+    }
+
+    /// 记录一次进程退出：累加运行时长，非零退出且非主动停止计为崩溃
+    pub(super) fn record_exit(&self, id: &str, uptime: Duration, crashed: bool) {
+        self.update_lifecycle_stats(id, |stats| {
+            stats.cumulative_uptime_ms = stats
+                .cumulative_uptime_ms
+                .saturating_add(u64::try_from(uptime.as_millis()).unwrap_or(u64::MAX));
+            if crashed {
+                stats.total_crashes += 1;
+                stats.last_crash_at = Some(Utc::now());
+            }
+        });
+    }
+
     /// 读取 PID 文件，返回进程 ID（如果存在）。
+    ///
+    /// pid 已被系统复用给其它进程（启动时间与记录不符）时清理 pid 文件并返回 None，
+    /// 避免把无关进程当作服务（状态、kill、信号都会基于这里的 pid）。
     pub(super) fn read_pid(&self, id: &str) -> Result<Option<u32>> {
         let path = self.pid_path(id);
         if !path.exists() {
             return Ok(None);
         }
-        // pid 文件单行整数
-        let content = fs::read_to_string(path)?;
-        let pid: u32 = content
+        // 第一行 pid，第二行（可选）进程启动时间
+        let content = fs::read_to_string(&path)?;
+        let mut lines = content.lines();
+        let pid: u32 = lines
+            .next()
+            .unwrap_or_default()
             .trim()
             .parse()
             .map_err(|_| ServiceError::Other("invalid pid".into()))?;
+        let recorded_start = lines.next().and_then(|l| l.trim().parse::<u64>().ok());
+        if let (Some(recorded), Some(actual)) = (recorded_start, self.process_start_time(pid)) {
+            if recorded != actual {
+                tracing::warn!(service_id = %id, pid, "pid reused by another process, clearing");
+                let _ = fs::remove_file(&path);
+                return Ok(None);
+            }
+        }
         Ok(Some(pid))
     }
 
-    /// 写入 PID 文件。
+    /// 写入 PID 文件，同时记录进程启动时间用于识别 pid 复用。
     pub(super) fn write_pid(&self, id: &str, pid: u32) -> Result<()> {
         fs::create_dir_all(self.runtime_dir(id))?;
+        let content = match self.process_start_time(pid) {
+            Some(start) => format!("{pid}\n{start}"),
+            None => pid.to_string(),
+        };
         // 直接覆盖写入，保持简单
-        fs::write(self.pid_path(id), pid.to_string())?;
+        fs::write(self.pid_path(id), content)?;
         Ok(())
     }
 
+    /// 进程启动时间（Unix 秒）；进程不存在时返回 None。
+    fn process_start_time(&self, pid: u32) -> Option<u64> {
+        let mut sys = self.system.lock().ok()?;
+        let pid_sysinfo = Pid::from(pid as usize);
+        if !sys.refresh_process_specifics(pid_sysinfo, ProcessRefreshKind::new()) {
+            return None;
+        }
+        sys.process(pid_sysinfo)
+            .map(|proc_ref| proc_ref.start_time())
+    }
+
     /// 查询进程存活与粗略运行时长（毫秒）。
     ///
     /// 返回 `Some((alive, uptime_ms))`，其中：
@@ -52,28 +183,77 @@ impl ServiceManager {
         })
     }
 
-    /// 杀死进程树；失败返回 false。
+    /// 获取全量进程快照：TTL 内复用缓存，否则对所有进程做一次 refresh_processes。
+    ///
+    /// 批量查询时只锁一次 `System`，避免逐个 refresh_process_specifics 串行化。
+    pub(super) fn process_snapshot(&self) -> Arc<HashMap<u32, u64>> {
+        let mut cache = self
+            .process_snapshot
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if let Some(snapshot) = cache.as_ref() {
+            if snapshot.taken_at.elapsed() < PROCESS_SNAPSHOT_TTL {
+                return snapshot.uptimes.clone();
+            }
+        }
+
+        let uptimes = match self.system.lock() {
+            Ok(mut sys) => {
+                sys.refresh_processes_specifics(ProcessRefreshKind::new());
+                sys.processes()
+                    .iter()
+                    .map(|(pid, proc_ref)| (pid.as_u32(), proc_ref.run_time().saturating_mul(1000)))
+                    .collect()
+            }
+            Err(_) => HashMap::new(),
+        };
+        let uptimes = Arc::new(uptimes);
+        *cache = Some(ProcessSnapshot {
+            taken_at: Instant::now(),
+            uptimes: uptimes.clone(),
+        });
+        uptimes
+    }
+
+    /// 与 [`process_alive`](Self::process_alive) 相同，但优先查快照。
+    ///
+    /// 快照中没有的 pid 可能是刚启动的进程，回退到单进程刷新确认。
+    pub(super) fn process_alive_in(
+        &self,
+        pid: u32,
+        snapshot: Option<&HashMap<u32, u64>>,
+    ) -> Option<(bool, Option<u64>)> {
+        match snapshot.and_then(|s| s.get(&pid)) {
+            Some(uptime_ms) => Some((true, Some(*uptime_ms))),
+            None => self.process_alive(pid),
+        }
+    }
+
+    /// 向整个进程树发送 `signal`（强杀时为 `Signal::Kill`）；失败返回 false。
     ///
     /// 对于 Java OOM 等场景，单独杀父进程可能无效，需要杀死整个进程树。
-    /// - Windows: 使用 taskkill /F /T 强制杀死进程树
-    /// - Unix: 先收集子进程，再从叶子节点向上逐一 SIGKILL
-    pub(super) fn kill_process(&self, pid: u32) -> bool {
-        // 优先使用平台原生方式强制杀死进程树
-        if self.kill_process_tree_native(pid) {
+    /// - Windows: 使用 taskkill /T；`Signal::Kill` 加 /F 强制终止，其他信号不加 /F，请求进程自行关闭
+    /// - Unix: 先收集子进程及同进程组的进程，再从叶子节点向上逐一发送信号
+    pub(super) fn kill_process(&self, pid: u32, signal: Signal) -> bool {
+        // 优先使用平台原生方式处理进程树
+        if self.kill_process_tree_native(pid, signal) {
             return true;
         }
 
-        // 回退：使用 sysinfo 逐个杀死进程树
-        self.kill_process_tree_fallback(pid)
+        // 回退：使用 sysinfo 逐个向进程树发送信号
+        self.kill_process_tree_fallback(pid, signal)
     }
 
-    /// 使用平台原生命令杀死进程树
+    /// 使用平台原生命令终止进程树
     #[cfg(windows)]
-    fn kill_process_tree_native(&self, pid: u32) -> bool {
-        // taskkill /F 强制终止，/T 终止进程树
-        let output = std::process::Command::new("taskkill")
-            .args(["/F", "/T", "/PID", &pid.to_string()])
-            .output();
+    fn kill_process_tree_native(&self, pid: u32, signal: Signal) -> bool {
+        // /T 终止进程树；/F 强制终止
+        let pid = pid.to_string();
+        let mut args = vec!["/T", "/PID", pid.as_str()];
+        if signal == Signal::Kill {
+            args.insert(0, "/F");
+        }
+        let output = std::process::Command::new("taskkill").args(&args).output();
 
         match output {
             Ok(out) => out.status.success(),
@@ -82,15 +262,15 @@ impl ServiceManager {
     }
 
     #[cfg(unix)]
-    fn kill_process_tree_native(&self, _pid: u32) -> bool {
+    fn kill_process_tree_native(&self, _pid: u32, _signal: Signal) -> bool {
         // Unix 上不使用进程组杀死，直接返回 false 让 fallback 方案处理
         // fallback 方案通过 sysinfo 收集父子关系，逐个杀死进程树中的进程
         // 这样可以避免误杀 screen/tmux/SSH 等外部会话中的进程
         false
     }
 
-    /// 使用 sysinfo 收集并杀死进程树（回退方案）
-    fn kill_process_tree_fallback(&self, pid: u32) -> bool {
+    /// 使用 sysinfo 收集进程树并逐个发送信号（回退方案）
+    fn kill_process_tree_fallback(&self, pid: u32, signal: Signal) -> bool {
         let mut sys = match self.system.lock() {
             Ok(guard) => guard,
             Err(_) => return false,
@@ -105,19 +285,26 @@ impl ServiceManager {
         let mut tree_pids = Vec::new();
         self.collect_process_tree(&sys, root_pid, &mut tree_pids);
 
+        // 补上父子关系已断开、但仍在服务进程组中的进程（被重新挂到 init 的孤儿）
+        for member in process_group_members(&sys, root_pid) {
+            if !tree_pids.contains(&member) {
+                tree_pids.push(member);
+            }
+        }
+
         if tree_pids.is_empty() {
             // 进程已不存在，视为成功
             return true;
         }
 
-        // 从叶子节点向上杀死（反转顺序），避免子进程成为僵尸
+        // 从叶子节点向上杀死（反转顺序），避免子进程成为僵尸；孤儿排在最前
         tree_pids.reverse();
 
         let mut all_killed = true;
         for tree_pid in tree_pids {
             if let Some(process) = sys.process(tree_pid) {
-                // 直接使用 SIGKILL，不再尝试温和的 SIGTERM
-                let killed = process.kill();
+                // 当前平台不支持的信号视为失败，由调用方升级为 SIGKILL
+                let killed = process.kill_with(signal).unwrap_or(false);
                 if !killed {
                     all_killed = false;
                 }
@@ -128,7 +315,7 @@ impl ServiceManager {
     }
 
     /// 递归收集进程树中的所有进程 ID
-    fn collect_process_tree(&self, sys: &sysinfo::System, pid: Pid, result: &mut Vec<Pid>) {
+    pub(super) fn collect_process_tree(&self, sys: &sysinfo::System, pid: Pid, result: &mut Vec<Pid>) {
         // 先添加当前进程
         if sys.process(pid).is_some() {
             result.push(pid);
@@ -142,3 +329,28 @@ impl ServiceManager {
         }
     }
 }
+
+/// 列出进程组 `pgid` 中的所有进程。
+///
+/// 服务进程在 PTY、管道、分离三种模式下都是自己进程组的组长（pgid 等于服务 pid）。
+/// run_as 时实际进程是 `sudo -u user cmd` 的子进程；sudo 先退出、或服务二次 fork
+/// 成守护进程时，父子关系会断开，但进程组保持不变，按 pgid 仍能找到这些孤儿。
+/// 自行 setsid 脱离进程组的进程无法覆盖。API 自身所在的进程组永远不会匹配。
+#[cfg(unix)]
+fn process_group_members(sys: &sysinfo::System, pgid: Pid) -> Vec<Pid> {
+    let pgid = pgid.as_u32() as libc::pid_t;
+    // SAFETY: getpgrp/getpgid 只读取内核中的进程组信息，无内存安全前提
+    if pgid == unsafe { libc::getpgrp() } {
+        return Vec::new();
+    }
+    sys.processes()
+        .keys()
+        .filter(|pid| unsafe { libc::getpgid(pid.as_u32() as libc::pid_t) } == pgid)
+        .copied()
+        .collect()
+}
+
+#[cfg(not(unix))]
+fn process_group_members(_sys: &sysinfo::System, _pgid: Pid) -> Vec<Pid> {
+    Vec::new()
+}
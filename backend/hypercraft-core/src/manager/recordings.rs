@@ -0,0 +1,171 @@
+//! attach 会话录制：把 PTY 输出按 asciinema v2 格式写入 <service>/recordings/*.cast。
+
+use super::*;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::time::Instant;
+use tokio::fs::File;
+use tokio::io::{AsyncWriteExt, BufWriter};
+
+/// 录制文件信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingInfo {
+    pub name: String,
+    pub size: u64,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// 单次 attach 的录制写入器（异步 IO，可直接在 attach 循环中调用），每个事件写入后立即刷新
+pub struct SessionRecorder {
+    writer: BufWriter<File>,
+    started: Instant,
+    /// 跨 chunk 的 UTF-8 解码状态：被截断在末尾的多字节字符留到下一段再输出
+    decoder: encoding_rs::Decoder,
+}
+
+impl std::fmt::Debug for SessionRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionRecorder").finish_non_exhaustive()
+    }
+}
+
+impl SessionRecorder {
+    /// 追加一段输出事件：[elapsed, "o", data]
+    pub async fn write_output(&mut self, data: &[u8]) -> Result<()> {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let capacity = self
+            .decoder
+            .max_utf8_buffer_length(data.len())
+            .unwrap_or(data.len() * 3);
+        let mut text = String::with_capacity(capacity);
+        let _ = self.decoder.decode_to_string(data, &mut text, false);
+        if text.is_empty() {
+            return Ok(());
+        }
+        let event = serde_json::json!([elapsed, "o", text]);
+        self.writer
+            .write_all(format!("{event}\n").as_bytes())
+            .await?;
+        self.writer.flush().await?;
+        Ok(())
+    }
+}
+
+impl ServiceManager {
+    /// 新建录制文件并写入 asciinema v2 头部
+    pub async fn start_recording(&self, id: &str, rows: u16, cols: u16) -> Result<SessionRecorder> {
+        let dir = self.recordings_dir(id);
+        tokio::fs::create_dir_all(&dir).await?;
+        let now = Utc::now();
+        let path = dir.join(format!("{}.cast", now.format("%Y%m%d-%H%M%S%.3f")));
+        let mut writer = BufWriter::new(File::create(&path).await?);
+        let header = serde_json::json!({
+            "version": 2,
+            "width": cols,
+            "height": rows,
+            "timestamp": now.timestamp(),
+            "title": id,
+        });
+        writer.write_all(format!("{header}\n").as_bytes()).await?;
+        writer.flush().await?;
+        Ok(SessionRecorder {
+            writer,
+            started: Instant::now(),
+            decoder: encoding_rs::UTF_8.new_decoder_without_bom_handling(),
+        })
+    }
+
+    /// 列出录制文件，最新的在前
+    pub fn list_recordings(&self, id: &str) -> Result<Vec<RecordingInfo>> {
+        let dir = self.recordings_dir(id);
+        if !dir.exists() {
+            return Ok(vec![]);
+        }
+        let mut items = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !name.ends_with(".cast") {
+                continue;
+            }
+            let meta = entry.metadata()?;
+            items.push(RecordingInfo {
+                name,
+                size: meta.len(),
+                created_at: meta.modified().ok().map(DateTime::<Utc>::from),
+            });
+        }
+        items.sort_by(|a, b| b.name.cmp(&a.name));
+        Ok(items)
+    }
+
+    /// 解析录制文件路径；拒绝带路径分隔符的名称
+    pub fn recording_path(&self, id: &str, name: &str) -> Result<PathBuf> {
+        if !name.ends_with(".cast") || name.contains(['/', '\\']) || name.starts_with('.') {
+            return Err(ServiceError::PolicyViolation(
+                format!("invalid recording name: {name}").into(),
+            ));
+        }
+        let path = self.recordings_dir(id).join(name);
+        if !path.is_file() {
+            return Err(ServiceError::NotFound(name.to_string()));
+        }
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn recording_writes_asciinema_header_and_events() {
+        let dir = TempDir::new().unwrap();
+        let mgr = ServiceManager::new(dir.path());
+
+        let mut recorder = mgr.start_recording("svc", 24, 80).await.unwrap();
+        recorder.write_output(b"hello\r\n").await.unwrap();
+        drop(recorder);
+
+        let list = mgr.list_recordings("svc").unwrap();
+        assert_eq!(list.len(), 1);
+        let path = mgr.recording_path("svc", &list[0].name).unwrap();
+        let content = fs::read_to_string(path).unwrap();
+        let mut lines = content.lines();
+        let header: serde_json::Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+        assert_eq!(header["version"], 2);
+        assert_eq!(header["width"], 80);
+        let event: serde_json::Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+        assert_eq!(event[1], "o");
+        assert_eq!(event[2], "hello\r\n");
+
+        assert!(mgr.recording_path("svc", "../service.json").is_err());
+    }
+
+    #[tokio::test]
+    async fn recording_keeps_utf8_split_across_chunks() {
+        let dir = TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        let mut recorder = manager.start_recording("svc", 24, 80).await.unwrap();
+        // "你好" 的 UTF-8 字节被切在字符中间
+        let bytes = "你好".as_bytes();
+        recorder.write_output(&bytes[..2]).await.unwrap();
+        recorder.write_output(&bytes[2..4]).await.unwrap();
+        recorder.write_output(&bytes[4..]).await.unwrap();
+        drop(recorder);
+
+        let name = &manager.list_recordings("svc").unwrap()[0].name;
+        let cast = fs::read_to_string(manager.recordings_dir("svc").join(name)).unwrap();
+        let data: String = cast
+            .lines()
+            .skip(1)
+            .map(|line| {
+                let event: serde_json::Value = serde_json::from_str(line).unwrap();
+                event[2].as_str().unwrap().to_string()
+            })
+            .collect();
+        assert_eq!(data, "你好");
+    }
+}
@@ -1,26 +1,68 @@
 //! 定时调度器：基于 cron 表达式的服务定时启动/重启/停止。
-//! 
+//!
 //! 使用纯 tokio 实现，不依赖重量级的 tokio-cron-scheduler。
+//! 每次触发都会记录到 runtime/schedule_fires.json，供 `catch_up` 判断停机期间是否错过执行。
 
 use crate::error::{Result, ServiceError};
-use crate::manifest::{Schedule, ScheduleAction};
+use crate::manifest::{Schedule, ScheduleAction, ScheduleKind};
 use crate::ServiceManager;
-use chrono::Utc;
+use chrono::{DateTime, FixedOffset, Utc};
+use chrono_tz::Tz;
 use cron::Schedule as CronSchedule;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fs;
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
 use tokio::sync::RwLock;
 use tokio::task::JoinHandle;
 use tracing::{error, info, warn};
 
+/// 服务 ID -> (调度条目 ID -> 任务句柄)
+type JobMap = HashMap<String, HashMap<String, JoinHandle<()>>>;
+
+/// (服务 ID, 调度条目 ID) -> 最近一次执行结果
+type RunMap = HashMap<(String, String), ScheduleRunResult>;
+
+/// 下次运行时间展示条数
+const INTROSPECT_NEXT_RUNS: usize = 3;
+
+/// 调度条目最近一次执行的结果（仅保存在内存中，API 重启后清空）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleRunResult {
+    pub at: DateTime<Utc>,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 一条生效中的调度任务及其运行情况
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleJobInfo {
+    pub service_id: String,
+    #[serde(flatten)]
+    pub schedule: Schedule,
+    /// 接下来的若干次运行时间（按调度时区）
+    pub next_runs: Vec<DateTime<FixedOffset>>,
+    /// 最近一次触发时间（持久化，跨重启保留）
+    pub last_fired_at: Option<DateTime<Utc>>,
+    pub last_result: Option<ScheduleRunResult>,
+}
+
 /// 调度器：管理所有服务的定时任务
 #[derive(Clone)]
 pub struct ServiceScheduler {
-    /// 服务 ID -> 任务句柄
-    jobs: Arc<RwLock<HashMap<String, JoinHandle<()>>>>,
+    /// 每个服务可有多条调度任务
+    jobs: Arc<RwLock<JobMap>>,
     /// ServiceManager 引用
     manager: ServiceManager,
+    /// `start` 之后、`shutdown` 之前为 true
+    running: Arc<AtomicBool>,
+    /// 全局暂停：暂停期间不挂任何任务，已到点的任务也不执行
+    paused: Arc<AtomicBool>,
+    /// 各调度条目最近一次执行结果
+    runs: Arc<StdMutex<RunMap>>,
 }
 
 impl ServiceScheduler {
@@ -29,132 +71,376 @@ impl ServiceScheduler {
         Self {
             jobs: Arc::new(RwLock::new(HashMap::new())),
             manager,
+            running: Arc::new(AtomicBool::new(false)),
+            paused: Arc::new(AtomicBool::new(false)),
+            runs: Arc::new(StdMutex::new(HashMap::new())),
         }
     }
 
-    /// 启动调度器（现在是空操作，任务按需创建）
+    /// 启动调度器（任务按需创建，这里只标记为运行中）
     pub async fn start(&self) -> Result<()> {
+        self.running.store(true, Ordering::SeqCst);
         info!("计划任务已就绪");
         Ok(())
     }
 
     /// 停止调度器，取消所有任务
     pub async fn shutdown(&self) -> Result<()> {
+        self.running.store(false, Ordering::SeqCst);
         let mut jobs = self.jobs.write().await;
-        for (id, handle) in jobs.drain() {
-            handle.abort();
+        for (id, entries) in jobs.drain() {
+            for handle in entries.into_values() {
+                handle.abort();
+            }
             info!("取消了服务的计划任务: {}", id);
         }
         info!("计划任务调度器已停止");
         Ok(())
     }
 
-    /// 为指定服务添加或更新定时任务
-    pub async fn upsert_schedule(&self, service_id: &str, schedule: &Schedule) -> Result<()> {
-        // 先移除旧任务
+    /// 调度器是否在运行（已 `start` 且未 `shutdown`），供就绪检查使用
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// 暂停全部定时任务（维护期间使用）：取消已挂的任务，manifest 中的调度配置保持不变。
+    ///
+    /// 暂停期间新增或修改的调度只保存配置，待 `resume` 时统一挂上。暂停状态不持久化，
+    /// API 重启后调度照常运行。返回被取消的任务数。
+    pub async fn pause(&self) -> usize {
+        self.paused.store(true, Ordering::SeqCst);
+        let mut jobs = self.jobs.write().await;
+        let mut cancelled = 0;
+        for entries in jobs.drain().map(|(_, entries)| entries) {
+            for handle in entries.into_values() {
+                handle.abort();
+                cancelled += 1;
+            }
+        }
+        info!("计划任务已暂停，取消了 {} 个任务", cancelled);
+        cancelled
+    }
+
+    /// 恢复定时任务：按各服务 manifest 重新挂上全部调度（不补执行暂停期间错过的触发）
+    pub async fn resume(&self) -> Result<()> {
+        self.paused.store(false, Ordering::SeqCst);
+        self.arm_from_manifests(false).await?;
+        info!("计划任务已恢复");
+        Ok(())
+    }
+
+    /// 调度器是否处于暂停状态
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// 用 manifest 中的调度列表替换该服务的全部定时任务
+    pub async fn sync_schedules(&self, service_id: &str, schedules: &[Schedule]) -> Result<()> {
         self.remove_schedule(service_id).await?;
+        for schedule in schedules {
+            self.upsert_schedule(service_id, schedule).await?;
+        }
+        Ok(())
+    }
 
-        // 如果未启用或 cron 为空，直接返回
-        if !schedule.enabled || schedule.cron.is_empty() {
+    /// 为指定服务添加或更新一条定时任务（按 `schedule.id` 区分）
+    pub async fn upsert_schedule(&self, service_id: &str, schedule: &Schedule) -> Result<()> {
+        // 先移除同 ID 的旧任务
+        self.remove_schedule_entry(service_id, &schedule.id).await?;
+
+        // 如果未启用、cron 为空或调度器已暂停，直接返回
+        let kind = schedule.effective_kind();
+        if self.is_paused()
+            || !schedule.enabled
+            || matches!(&kind, ScheduleKind::Cron(cron) if cron.is_empty())
+        {
             return Ok(());
         }
+        Self::validate_schedule(schedule)?;
+        if let ScheduleKind::Once { at } = &kind {
+            if *at <= Utc::now() {
+                info!(
+                    "服务 {} 的一次性计划 {} 已过期，跳过",
+                    service_id, schedule.id
+                );
+                return Ok(());
+            }
+        }
 
-        // 验证并解析 cron 表达式
-        let cron_schedule = Self::parse_cron(&schedule.cron)?;
+        // 解析 cron 表达式与时区
+        let cron_schedule = match &kind {
+            ScheduleKind::Cron(cron) => Some(Self::parse_cron(cron)?),
+            _ => None,
+        };
+        let tz = Self::parse_optional_timezone(schedule.timezone.as_deref())?;
 
         let manager = self.manager.clone();
+        let jobs = self.jobs.clone();
+        let runs = self.runs.clone();
+        let paused = self.paused.clone();
         let sid = service_id.to_string();
+        let schedule_id = schedule.id.clone();
         let action = schedule.action.clone();
-        let cron_expr = schedule.cron.clone();
+        let job_kind = kind.clone();
+
+        // 持有写锁直到句柄登记完成，避免一次性任务在登记前就尝试移除自身
+        let mut job_map = self.jobs.write().await;
+        // 等锁期间调度器可能已被暂停
+        if self.is_paused() {
+            return Ok(());
+        }
 
         // 启动定时任务
         let handle = tokio::spawn(async move {
             loop {
                 // 计算下次执行时间
                 let now = Utc::now();
-                let next = match cron_schedule.upcoming(Utc).next() {
-                    Some(t) => t,
-                    None => {
-                        warn!("没有找到服务 {} 的下一个计划时间", sid);
-                        break;
+                let next = match &job_kind {
+                    ScheduleKind::Cron(_) => cron_schedule
+                        .as_ref()
+                        .and_then(|c| Self::upcoming(c, tz).next()),
+                    ScheduleKind::Interval { every_secs } => {
+                        Some(now + chrono::Duration::seconds((*every_secs).max(1) as i64))
                     }
+                    ScheduleKind::Once { at } => Some(*at),
+                };
+                let Some(next) = next else {
+                    warn!("没有找到服务 {} 的下一个计划时间", sid);
+                    break;
                 };
 
                 // 等待到下次执行时间
                 let duration = (next - now).to_std().unwrap_or_default();
                 tokio::time::sleep(duration).await;
+                // 暂停与任务被取消之间可能恰好到点，此时不执行
+                if paused.load(Ordering::SeqCst) {
+                    break;
+                }
 
                 // 执行任务
                 info!("计划任务触发，服务: {}", sid);
-                let result = match action {
-                    ScheduleAction::Start => {
-                        match manager.status(&sid).await {
-                            Ok(status) if status.state == crate::models::ServiceState::Stopped => {
-                                manager.start(&sid).await.map(|_| ())
-                            }
-                            Ok(_) => {
-                                info!("服务 {} 已运行，跳过计划启动", sid);
-                                Ok(())
-                            }
-                            Err(e) => Err(e),
+                let fired_at = Utc::now();
+                if let Err(e) = manager.record_schedule_fire(&sid, &schedule_id, fired_at) {
+                    warn!("记录服务 {} 的计划触发时间失败: {}", sid, e);
+                }
+                manager.emit_schedule_fired(&sid, &schedule_id, action.clone(), fired_at);
+                let result = Self::run_action(&manager, &sid, &action).await;
+                if let Err(e) = &result {
+                    error!("计划任务 {:?} 失败，服务: {}，错误: {}", action, sid, e);
+                }
+                Self::record_run(&runs, &sid, &schedule_id, &result);
+
+                // 一次性任务执行后从调度表移除自身
+                if matches!(job_kind, ScheduleKind::Once { .. }) {
+                    let mut jobs = jobs.write().await;
+                    if let Some(entries) = jobs.get_mut(&sid) {
+                        if entries.get(&schedule_id).map(|h| h.id()) == Some(tokio::task::id()) {
+                            entries.remove(&schedule_id);
                         }
-                    }
-                    ScheduleAction::Restart => manager.restart(&sid).await.map(|_| ()),
-                    ScheduleAction::Stop => {
-                        match manager.status(&sid).await {
-                            Ok(status) if status.state == crate::models::ServiceState::Running => {
-                                manager.stop(&sid).await.map(|_| ())
-                            }
-                            Ok(_) => {
-                                info!("服务 {} 未运行，跳过计划停止", sid);
-                                Ok(())
-                            }
-                            Err(e) => Err(e),
+                        if entries.is_empty() {
+                            jobs.remove(&sid);
                         }
                     }
-                };
-
-                if let Err(e) = result {
-                    error!(
-                        "计划任务 {:?} 失败，服务: {}，错误: {}",
-                        action, sid, e
-                    );
+                    break;
                 }
             }
         });
 
-        self.jobs.write().await.insert(service_id.to_string(), handle);
+        job_map
+            .entry(service_id.to_string())
+            .or_default()
+            .insert(schedule.id.clone(), handle);
+        drop(job_map);
         info!(
-            "已为服务 {} 添加计划任务: {} ({:?})",
-            service_id, cron_expr, schedule.action
+            "已为服务 {} 添加计划任务 {}: {:?} ({:?})",
+            service_id, schedule.id, kind, schedule.action
         );
 
         Ok(())
     }
 
-    /// 移除指定服务的定时任务
+    /// 立即执行一次服务的调度动作（不影响下次计划时间），返回执行后的服务状态。
+    ///
+    /// 服务只有一条调度时可省略 `schedule_id`；未启用的调度也可手动执行。
+    /// 结果计入 `list_jobs` 的最近执行结果，但不写触发记录，不影响补执行判断。
+    pub async fn run_now(
+        &self,
+        service_id: &str,
+        schedule_id: Option<&str>,
+    ) -> Result<crate::models::ServiceStatus> {
+        let manifest = self.manager.load_manifest(service_id).await?;
+        let schedule = match schedule_id {
+            Some(entry) => manifest
+                .schedules
+                .iter()
+                .find(|s| s.id == entry)
+                .ok_or_else(|| ServiceError::NotFound(format!("{service_id}/schedules/{entry}")))?,
+            None => match manifest.schedules.as_slice() {
+                [only] => only,
+                [] => {
+                    return Err(ServiceError::InvalidSchedule(format!(
+                        "服务 {} 没有配置计划任务",
+                        service_id
+                    )))
+                }
+                _ => {
+                    return Err(ServiceError::InvalidSchedule(format!(
+                        "服务 {} 有多条计划任务，请指定要执行的条目",
+                        service_id
+                    )))
+                }
+            },
+        };
+
+        info!("手动执行计划任务 {}，服务: {}", schedule.id, service_id);
+        let result = Self::run_action(&self.manager, service_id, &schedule.action).await;
+        Self::record_run(&self.runs, service_id, &schedule.id, &result);
+        result?;
+        self.manager.status(service_id).await
+    }
+
+    /// 执行调度动作
+    async fn run_action(
+        manager: &ServiceManager,
+        sid: &str,
+        action: &ScheduleAction,
+    ) -> Result<()> {
+        match action {
+            ScheduleAction::Start => match manager.status(sid).await {
+                Ok(status) if status.state == crate::models::ServiceState::Stopped => {
+                    manager.start(sid).await.map(|_| ())
+                }
+                Ok(_) => {
+                    info!("服务 {} 已运行，跳过计划启动", sid);
+                    Ok(())
+                }
+                Err(e) => Err(e),
+            },
+            ScheduleAction::Restart => manager.restart(sid).await.map(|_| ()),
+            ScheduleAction::Stop => match manager.status(sid).await {
+                Ok(status) if status.state == crate::models::ServiceState::Running => {
+                    manager.stop(sid).await.map(|_| ())
+                }
+                Ok(_) => {
+                    info!("服务 {} 未运行，跳过计划停止", sid);
+                    Ok(())
+                }
+                Err(e) => Err(e),
+            },
+        }
+    }
+
+    /// 移除指定服务的全部定时任务
     pub async fn remove_schedule(&self, service_id: &str) -> Result<()> {
-        if let Some(handle) = self.jobs.write().await.remove(service_id) {
-            handle.abort();
+        if let Some(entries) = self.jobs.write().await.remove(service_id) {
+            for handle in entries.into_values() {
+                handle.abort();
+            }
             info!("取消了服务的计划任务: {}", service_id);
         }
         Ok(())
     }
 
+    /// 移除指定服务的某一条定时任务
+    pub async fn remove_schedule_entry(&self, service_id: &str, schedule_id: &str) -> Result<()> {
+        let mut jobs = self.jobs.write().await;
+        if let Some(entries) = jobs.get_mut(service_id) {
+            if let Some(handle) = entries.remove(schedule_id) {
+                handle.abort();
+                info!("取消了服务 {} 的计划任务: {}", service_id, schedule_id);
+            }
+            if entries.is_empty() {
+                jobs.remove(service_id);
+            }
+        }
+        Ok(())
+    }
+
+    /// 记录一次执行结果
+    fn record_run(runs: &StdMutex<RunMap>, sid: &str, schedule_id: &str, result: &Result<()>) {
+        let run = ScheduleRunResult {
+            at: Utc::now(),
+            ok: result.is_ok(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        };
+        runs.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert((sid.to_string(), schedule_id.to_string()), run);
+    }
+
+    /// 列出全部生效中的调度任务：下次运行时间、最近触发时间与执行结果。
+    ///
+    /// 按服务 ID 排序，同一服务内保持 manifest 中的顺序；清单读取失败的服务跳过。
+    pub async fn list_jobs(&self) -> Vec<ScheduleJobInfo> {
+        let mut active: Vec<(String, Vec<String>)> = {
+            let jobs = self.jobs.read().await;
+            jobs.iter()
+                .map(|(sid, entries)| (sid.clone(), entries.keys().cloned().collect()))
+                .collect()
+        };
+        active.sort();
+
+        let mut infos = Vec::new();
+        for (sid, schedule_ids) in active {
+            let manifest = match self.manager.load_manifest(&sid).await {
+                Ok(manifest) => manifest,
+                Err(e) => {
+                    warn!("加载服务 {} 的清单失败: {}", sid, e);
+                    continue;
+                }
+            };
+            let fires = self.manager.schedule_fires(&sid);
+            for schedule in manifest.schedules {
+                if !schedule_ids.contains(&schedule.id) {
+                    continue;
+                }
+                let last_result = self
+                    .runs
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .get(&(sid.clone(), schedule.id.clone()))
+                    .cloned();
+                infos.push(ScheduleJobInfo {
+                    service_id: sid.clone(),
+                    next_runs: Self::schedule_runs(&schedule, INTROSPECT_NEXT_RUNS)
+                        .unwrap_or_default(),
+                    last_fired_at: fires.get(&schedule.id).copied(),
+                    last_result,
+                    schedule,
+                });
+            }
+        }
+        infos
+    }
+
+    /// 当前正在运行的调度条目 ID
+    pub async fn active_schedule_ids(&self, service_id: &str) -> Vec<String> {
+        let jobs = self.jobs.read().await;
+        let mut ids: Vec<String> = jobs
+            .get(service_id)
+            .map(|entries| entries.keys().cloned().collect())
+            .unwrap_or_default();
+        ids.sort();
+        ids
+    }
+
     /// 重新加载所有服务的定时任务
     pub async fn reload_all(&self) -> Result<()> {
+        self.arm_from_manifests(true).await
+    }
+
+    /// 按 manifest 挂上所有服务的定时任务；`catch_up` 为 true 时先补执行停机期间错过的触发
+    async fn arm_from_manifests(&self, catch_up: bool) -> Result<()> {
         let services = self.manager.list_services().await?;
 
         for summary in services {
             match self.manager.load_manifest(&summary.id).await {
                 Ok(manifest) => {
-                    if let Some(schedule) = &manifest.schedule {
-                        if let Err(e) = self.upsert_schedule(&summary.id, schedule).await {
-                            warn!(
-                                "加载服务 {} 的计划任务失败: {}",
-                                summary.id, e
-                            );
-                        }
+                    if catch_up {
+                        self.catch_up_missed(&summary.id, &manifest.schedules);
+                    }
+                    if let Err(e) = self.sync_schedules(&summary.id, &manifest.schedules).await {
+                        warn!("加载服务 {} 的计划任务失败: {}", summary.id, e);
                     }
                 }
                 Err(e) => {
@@ -166,6 +452,86 @@ impl ServiceScheduler {
         Ok(())
     }
 
+    /// 对开启 `catch_up` 且停机期间错过触发的调度条目补执行一次
+    fn catch_up_missed(&self, service_id: &str, schedules: &[Schedule]) {
+        let fires = self.manager.schedule_fires(service_id);
+        let now = Utc::now();
+        for schedule in schedules.iter().filter(|s| s.enabled && s.catch_up) {
+            match Self::missed_run(schedule, fires.get(&schedule.id).copied(), now) {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(e) => {
+                    warn!(
+                        "服务 {} 的计划 {} 无法判断错过执行: {}",
+                        service_id, schedule.id, e
+                    );
+                    continue;
+                }
+            }
+            info!("补执行服务 {} 错过的计划任务 {}", service_id, schedule.id);
+            if let Err(e) = self
+                .manager
+                .record_schedule_fire(service_id, &schedule.id, now)
+            {
+                warn!("记录服务 {} 的计划触发时间失败: {}", service_id, e);
+            }
+            self.manager.emit_schedule_fired(
+                service_id,
+                &schedule.id,
+                schedule.action.clone(),
+                now,
+            );
+            let manager = self.manager.clone();
+            let runs = self.runs.clone();
+            let sid = service_id.to_string();
+            let schedule_id = schedule.id.clone();
+            let action = schedule.action.clone();
+            tokio::spawn(async move {
+                let result = Self::run_action(&manager, &sid, &action).await;
+                if let Err(e) = &result {
+                    error!(
+                        "补执行计划任务 {:?} 失败，服务: {}，错误: {}",
+                        action, sid, e
+                    );
+                }
+                Self::record_run(&runs, &sid, &schedule_id, &result);
+            });
+        }
+    }
+
+    /// 上次触发之后的下一次计划时间是否已经过去。
+    ///
+    /// 没有触发记录的 cron/间隔调度无从比较，视为未错过；一次性调度只要未执行过即视为错过。
+    fn missed_run(
+        schedule: &Schedule,
+        last_fire: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Result<bool> {
+        let due = match schedule.effective_kind() {
+            ScheduleKind::Cron(cron) => {
+                let Some(last) = last_fire else {
+                    return Ok(false);
+                };
+                let cron = Self::parse_cron(&cron)?;
+                match Self::parse_optional_timezone(schedule.timezone.as_deref())? {
+                    Some(tz) => cron
+                        .after(&last.with_timezone(&tz))
+                        .next()
+                        .map(|t| t.with_timezone(&Utc)),
+                    None => cron.after(&last).next(),
+                }
+            }
+            ScheduleKind::Interval { every_secs } => {
+                last_fire.map(|last| last + chrono::Duration::seconds(every_secs.max(1) as i64))
+            }
+            ScheduleKind::Once { at } => match last_fire {
+                Some(last) if last >= at => None,
+                _ => Some(at),
+            },
+        };
+        Ok(due.is_some_and(|due| due <= now))
+    }
+
     /// 验证 cron 表达式
     pub fn validate_cron(cron: &str) -> Result<()> {
         Self::parse_cron(cron)?;
@@ -179,10 +545,122 @@ impl ServiceScheduler {
         })
     }
 
-    /// 获取下次执行时间
-    pub fn next_run(cron: &str) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+    /// 验证一条调度配置：cron 表达式、间隔、时区
+    pub fn validate_schedule(schedule: &Schedule) -> Result<()> {
+        match schedule.effective_kind() {
+            ScheduleKind::Cron(cron) => Self::validate_cron(&cron)?,
+            ScheduleKind::Interval { every_secs } => {
+                if every_secs == 0 {
+                    return Err(ServiceError::InvalidSchedule(
+                        "间隔必须大于 0 秒".to_string(),
+                    ));
+                }
+            }
+            ScheduleKind::Once { .. } => {}
+        }
+        Self::parse_optional_timezone(schedule.timezone.as_deref())?;
+        Ok(())
+    }
+
+    /// 接下来 `count` 次执行时间（任意调度类型），按调度时区显示
+    pub fn schedule_runs(schedule: &Schedule, count: usize) -> Result<Vec<DateTime<FixedOffset>>> {
+        let tz = Self::parse_optional_timezone(schedule.timezone.as_deref())?;
+        let display = |t: DateTime<Utc>| match tz {
+            Some(tz) => t.with_timezone(&tz).fixed_offset(),
+            None => t.fixed_offset(),
+        };
+        let now = Utc::now();
+        Ok(match schedule.effective_kind() {
+            ScheduleKind::Cron(cron) => {
+                Self::upcoming_runs(&cron, schedule.timezone.as_deref(), count)?
+            }
+            ScheduleKind::Interval { every_secs } => (1..=count as i64)
+                .map(|n| display(now + chrono::Duration::seconds(n * every_secs.max(1) as i64)))
+                .collect(),
+            ScheduleKind::Once { at } => {
+                if at > now && count > 0 {
+                    vec![display(at)]
+                } else {
+                    vec![]
+                }
+            }
+        })
+    }
+
+    /// 验证时区名称（IANA 格式，如 Asia/Shanghai）
+    pub fn validate_timezone(timezone: &str) -> Result<()> {
+        Self::parse_optional_timezone(Some(timezone))?;
+        Ok(())
+    }
+
+    /// 解析时区；未设置或为空时按 UTC 计算
+    fn parse_optional_timezone(timezone: Option<&str>) -> Result<Option<Tz>> {
+        match timezone.map(str::trim).filter(|tz| !tz.is_empty()) {
+            Some(name) => name
+                .parse::<Tz>()
+                .map(Some)
+                .map_err(|_| ServiceError::InvalidSchedule(format!("未知的时区 '{}'", name))),
+            None => Ok(None),
+        }
+    }
+
+    /// 按时区展开 cron 的后续触发时间（统一转换为 UTC）
+    fn upcoming(
+        schedule: &CronSchedule,
+        tz: Option<Tz>,
+    ) -> Box<dyn Iterator<Item = DateTime<Utc>> + '_> {
+        match tz {
+            Some(tz) => Box::new(schedule.upcoming(tz).map(|t| t.with_timezone(&Utc))),
+            None => Box::new(schedule.upcoming(Utc)),
+        }
+    }
+
+    /// 接下来 `count` 次执行时间，按调度时区显示
+    pub fn upcoming_runs(
+        cron: &str,
+        timezone: Option<&str>,
+        count: usize,
+    ) -> Result<Vec<DateTime<FixedOffset>>> {
         let schedule = Self::parse_cron(cron)?;
-        Ok(schedule.upcoming(Utc).next())
+        let tz = Self::parse_optional_timezone(timezone)?;
+        Ok(Self::upcoming(&schedule, tz)
+            .take(count)
+            .map(|t| match tz {
+                Some(tz) => t.with_timezone(&tz).fixed_offset(),
+                None => t.fixed_offset(),
+            })
+            .collect())
+    }
+
+    /// 获取下次执行时间（按调度时区）
+    pub fn next_run(cron: &str, timezone: Option<&str>) -> Result<Option<DateTime<FixedOffset>>> {
+        Ok(Self::upcoming_runs(cron, timezone, 1)?.into_iter().next())
+    }
+}
+
+impl ServiceManager {
+    /// 各调度条目最近一次触发时间
+    fn schedule_fires(&self, id: &str) -> HashMap<String, DateTime<Utc>> {
+        fs::read(self.schedule_fires_path(id))
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    /// 记录调度条目的触发时间（多个条目可能同时触发，读改写需串行）
+    fn record_schedule_fire(&self, id: &str, schedule_id: &str, at: DateTime<Utc>) -> Result<()> {
+        let _guard = self
+            .schedule_fires_lock
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let mut fires = self.schedule_fires(id);
+        fires.insert(schedule_id.to_string(), at);
+        fs::create_dir_all(self.runtime_dir(id))?;
+        fs::write(
+            self.schedule_fires_path(id),
+            serde_json::to_vec_pretty(&fires)?,
+        )?;
+        Ok(())
     }
 }
 
@@ -202,9 +680,252 @@ mod tests {
         assert!(ServiceScheduler::validate_cron("").is_err());
     }
 
+    #[test]
+    fn concurrent_schedule_fires_are_all_recorded() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        let now = Utc::now();
+        let threads: Vec<_> = (0..16)
+            .map(|i| {
+                let manager = manager.clone();
+                std::thread::spawn(move || {
+                    manager
+                        .record_schedule_fire("svc", &format!("s{i}"), now)
+                        .unwrap();
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(manager.schedule_fires("svc").len(), 16);
+    }
+
+    #[tokio::test]
+    async fn sync_schedules_tracks_multiple_entries() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let scheduler = ServiceScheduler::new(ServiceManager::new(dir.path()));
+        let schedule = |id: &str, enabled: bool| Schedule {
+            id: id.into(),
+            enabled,
+            cron: "0 0 8 * * *".into(),
+            ..Default::default()
+        };
+
+        scheduler
+            .sync_schedules(
+                "svc",
+                &[
+                    schedule("a", true),
+                    schedule("b", true),
+                    schedule("c", false),
+                ],
+            )
+            .await
+            .unwrap();
+        assert_eq!(scheduler.active_schedule_ids("svc").await, vec!["a", "b"]);
+
+        scheduler.remove_schedule_entry("svc", "a").await.unwrap();
+        assert_eq!(scheduler.active_schedule_ids("svc").await, vec!["b"]);
+
+        scheduler.remove_schedule("svc").await.unwrap();
+        assert!(scheduler.active_schedule_ids("svc").await.is_empty());
+    }
+
     #[test]
     fn test_next_run() {
-        let next = ServiceScheduler::next_run("0 0 8 * * *").unwrap();
+        let next = ServiceScheduler::next_run("0 0 8 * * *", None).unwrap();
         assert!(next.is_some());
     }
+
+    #[test]
+    fn validate_schedule_covers_all_kinds() {
+        let interval = Schedule {
+            kind: Some(ScheduleKind::Interval { every_secs: 5400 }),
+            ..Default::default()
+        };
+        assert!(ServiceScheduler::validate_schedule(&interval).is_ok());
+        assert_eq!(
+            ServiceScheduler::schedule_runs(&interval, 3).unwrap().len(),
+            3
+        );
+
+        let zero = Schedule {
+            kind: Some(ScheduleKind::Interval { every_secs: 0 }),
+            ..Default::default()
+        };
+        assert!(ServiceScheduler::validate_schedule(&zero).is_err());
+
+        let past = Schedule {
+            kind: Some(ScheduleKind::Once {
+                at: Utc::now() - chrono::Duration::hours(1),
+            }),
+            ..Default::default()
+        };
+        assert!(ServiceScheduler::schedule_runs(&past, 5)
+            .unwrap()
+            .is_empty());
+
+        // 未设置 kind 时沿用 cron 字段
+        let legacy = Schedule {
+            cron: "invalid".into(),
+            ..Default::default()
+        };
+        assert!(ServiceScheduler::validate_schedule(&legacy).is_err());
+    }
+
+    #[tokio::test]
+    async fn once_schedule_removes_itself_after_firing() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let scheduler = ServiceScheduler::new(ServiceManager::new(dir.path()));
+        let once = Schedule {
+            id: "once".into(),
+            enabled: true,
+            kind: Some(ScheduleKind::Once {
+                at: Utc::now() + chrono::Duration::milliseconds(50),
+            }),
+            ..Default::default()
+        };
+        scheduler.upsert_schedule("svc", &once).await.unwrap();
+        assert_eq!(scheduler.active_schedule_ids("svc").await, vec!["once"]);
+
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        assert!(scheduler.active_schedule_ids("svc").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn list_jobs_reports_last_fire_and_result() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        let manifest: crate::ServiceManifest = serde_json::from_value(serde_json::json!({
+            "id": "svc",
+            "name": "svc",
+            "command": "sh",
+            "schedules": [{
+                "id": "tick",
+                "enabled": true,
+                "kind": { "interval": { "every_secs": 1 } },
+                "action": "stop",
+            }],
+        }))
+        .unwrap();
+        let every = manifest.schedules[0].clone();
+        manager.create_service(manifest).await.unwrap();
+        let scheduler = ServiceScheduler::new(manager);
+        scheduler.upsert_schedule("svc", &every).await.unwrap();
+
+        let jobs = scheduler.list_jobs().await;
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].service_id, "svc");
+        assert_eq!(jobs[0].schedule.id, "tick");
+        assert_eq!(jobs[0].next_runs.len(), 3);
+        assert!(jobs[0].last_fired_at.is_none() && jobs[0].last_result.is_none());
+
+        tokio::time::sleep(std::time::Duration::from_millis(1300)).await;
+        let jobs = scheduler.list_jobs().await;
+        assert!(jobs[0].last_fired_at.is_some());
+        assert!(jobs[0].last_result.as_ref().is_some_and(|r| r.ok));
+        scheduler.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn pause_cancels_jobs_and_resume_rearms_from_manifests() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        let manifest: crate::ServiceManifest = serde_json::from_value(serde_json::json!({
+            "id": "svc",
+            "name": "svc",
+            "command": "sh",
+            "schedules": [{ "id": "daily", "enabled": true, "cron": "0 0 8 * * *" }],
+        }))
+        .unwrap();
+        let daily = manifest.schedules[0].clone();
+        manager.create_service(manifest).await.unwrap();
+        let scheduler = ServiceScheduler::new(manager);
+        scheduler.upsert_schedule("svc", &daily).await.unwrap();
+
+        assert_eq!(scheduler.pause().await, 1);
+        assert!(scheduler.is_paused());
+        assert!(scheduler.active_schedule_ids("svc").await.is_empty());
+        // 暂停期间的修改只保留配置，不挂任务
+        scheduler.upsert_schedule("svc", &daily).await.unwrap();
+        assert!(scheduler.active_schedule_ids("svc").await.is_empty());
+
+        scheduler.resume().await.unwrap();
+        assert!(!scheduler.is_paused());
+        assert_eq!(scheduler.active_schedule_ids("svc").await, vec!["daily"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn run_now_executes_action_with_running_guard() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let manager = ServiceManager::new(dir.path());
+        let manifest: crate::ServiceManifest = serde_json::from_value(serde_json::json!({
+            "id": "svc",
+            "name": "svc",
+            "command": "sh",
+            "args": ["-c", "sleep 30"],
+            "schedules": [{ "id": "nightly", "enabled": false, "cron": "0 0 3 * * *" }],
+        }))
+        .unwrap();
+        manager.create_service(manifest).await.unwrap();
+        let scheduler = ServiceScheduler::new(manager.clone());
+
+        let status = scheduler.run_now("svc", None).await.unwrap();
+        assert_eq!(status.state, crate::models::ServiceState::Running);
+        // 已在运行时跳过启动，仍返回成功
+        let again = scheduler.run_now("svc", Some("nightly")).await.unwrap();
+        assert_eq!(again.pid, status.pid);
+        assert!(matches!(
+            scheduler.run_now("svc", Some("missing")).await,
+            Err(ServiceError::NotFound(_))
+        ));
+
+        manager.stop("svc").await.unwrap();
+    }
+
+    #[test]
+    fn missed_run_detects_skipped_triggers() {
+        let at = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        let daily = Schedule {
+            cron: "0 0 3 * * *".into(),
+            ..Default::default()
+        };
+        let last = Some(at("2025-01-01T03:00:00Z"));
+        let missed =
+            |s: &Schedule, last, now: &str| ServiceScheduler::missed_run(s, last, at(now)).unwrap();
+
+        assert!(!missed(&daily, last, "2025-01-02T02:59:00Z"));
+        assert!(missed(&daily, last, "2025-01-02T03:30:00Z"));
+        assert!(!missed(&daily, None, "2025-01-05T00:00:00Z"));
+
+        let once = Schedule {
+            kind: Some(ScheduleKind::Once {
+                at: at("2025-01-01T12:00:00Z"),
+            }),
+            ..Default::default()
+        };
+        assert!(missed(&once, None, "2025-01-02T00:00:00Z"));
+        assert!(!missed(
+            &once,
+            Some(at("2025-01-01T12:00:00Z")),
+            "2025-01-02T00:00:00Z"
+        ));
+    }
+
+    #[test]
+    fn next_run_applies_timezone() {
+        use chrono::Timelike;
+
+        let next = ServiceScheduler::next_run("0 0 8 * * *", Some("Asia/Shanghai"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(next.hour(), 8);
+        assert_eq!(next.offset().local_minus_utc(), 8 * 3600);
+        assert_eq!(next.with_timezone(&Utc).hour(), 0);
+
+        assert!(ServiceScheduler::validate_timezone("Mars/Olympus").is_err());
+        assert!(ServiceScheduler::validate_timezone("Europe/Berlin").is_ok());
+    }
 }
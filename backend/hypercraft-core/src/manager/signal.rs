@@ -1,11 +1,39 @@
 //! 信号处理：向服务进程发送系统信号。
 
 use super::*;
-use sysinfo::{Pid, System};
+use sysinfo::{Pid, Signal, System};
+
+/// 解析信号名（不区分大小写，可带 `SIG` 前缀），如 `HUP`、`sigterm`。
+pub fn parse_signal(name: &str) -> Option<Signal> {
+    let name = name.trim().to_ascii_uppercase();
+    match name.strip_prefix("SIG").unwrap_or(&name) {
+        "INT" => Some(Signal::Interrupt),
+        "TERM" => Some(Signal::Term),
+        "HUP" => Some(Signal::Hangup),
+        "USR1" => Some(Signal::User1),
+        "KILL" => Some(Signal::Kill),
+        _ => None,
+    }
+}
+
+/// 校验 manifest 中的 `kill_signal`：必须是 [`parse_signal`] 认识的信号名
+pub(super) fn validate_kill_signal(manifest: &ServiceManifest) -> Result<()> {
+    match manifest.kill_signal.as_deref() {
+        Some(name) if parse_signal(name).is_none() => Err(ServiceError::InvalidInput(format!(
+            "unsupported kill_signal: {name} (expected INT, TERM, HUP, USR1 or KILL)"
+        ))),
+        _ => Ok(()),
+    }
+}
 
 impl ServiceManager {
-    /// 发送信号（如 Ctrl+C 对应的 INT），失败则返回 NotRunning。
-    pub async fn send_signal(&self, id: &str, signal: sysinfo::Signal) -> Result<()> {
+    /// 发送信号（如 Ctrl+C 对应的 INT、重载配置的 HUP）。
+    ///
+    /// 当前平台无法投递的信号返回 UnsupportedSignal（非 Unix 只支持 KILL），进程不存在返回 NotRunning。
+    pub async fn send_signal(&self, id: &str, signal: Signal) -> Result<()> {
+        if !sysinfo::SUPPORTED_SIGNALS.contains(&signal) {
+            return Err(ServiceError::UnsupportedSignal(signal.to_string()));
+        }
         let pid = self
             .read_pid(id)?
             .ok_or_else(|| ServiceError::NotRunning(id.to_string()))?;
@@ -17,9 +45,6 @@ impl ServiceManager {
             if process.kill_with(signal).unwrap_or(false) {
                 return Ok(());
             }
-            if process.kill() {
-                return Ok(());
-            }
             return Err(ServiceError::Other("signal not delivered".into()));
         }
         
@@ -1,8 +1,10 @@
-//! 系统资源监控
+//! 系统与服务进程资源监控
 
 use super::*;
 use serde::{Deserialize, Serialize};
-use sysinfo::{CpuRefreshKind, Disks, MemoryRefreshKind, RefreshKind};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use sysinfo::{CpuRefreshKind, Disks, MemoryRefreshKind, Pid, RefreshKind};
 
 /// 系统资源统计
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,7 +25,102 @@ pub struct SystemStats {
     pub disk_usage: f32,
 }
 
+/// 共享系统资源快照的刷新周期
+const STATS_FEED_TICK: Duration = Duration::from_secs(1);
+
+/// 单个服务的进程资源占用（主进程及其子进程合计）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProcessStats {
+    /// 主进程 PID
+    pub pid: u32,
+    /// CPU 使用率，按单核计算，多核时可超过 100
+    pub cpu_usage: f32,
+    /// 常驻内存 (bytes)
+    pub memory: u64,
+    /// 线程数（非 Linux 平台按进程计数）
+    pub threads: usize,
+    /// 进程树中的进程数
+    pub processes: usize,
+    /// 主进程运行时长 (秒)
+    pub uptime_secs: u64,
+}
+
 impl ServiceManager {
+    /// 单个服务的进程资源占用；服务未运行时返回 None
+    pub async fn process_stats(&self, id: &str) -> Result<Option<ProcessStats>> {
+        let mut stats = self.process_stats_many(&[id.to_string()]).await?;
+        Ok(stats.remove(id))
+    }
+
+    /// 批量获取服务进程资源占用，只包含正在运行的服务。
+    ///
+    /// CPU 使用率基于两次刷新之间的差值，首次调用可能为 0。
+    pub async fn process_stats_many(
+        &self,
+        ids: &[String],
+    ) -> Result<BTreeMap<String, ProcessStats>> {
+        let mut pids = Vec::new();
+        for id in ids {
+            if let Some(pid) = self.status(id).await?.pid {
+                pids.push((id.clone(), pid));
+            }
+        }
+        if pids.is_empty() {
+            return Ok(BTreeMap::new());
+        }
+
+        let mut sys = self.system.lock().unwrap_or_else(|e| e.into_inner());
+        sys.refresh_processes();
+
+        let mut result = BTreeMap::new();
+        for (id, pid) in pids {
+            let root = Pid::from(pid as usize);
+            let mut tree = Vec::new();
+            self.collect_process_tree(&sys, root, &mut tree);
+            if tree.is_empty() {
+                continue;
+            }
+            let mut stats = ProcessStats {
+                pid,
+                uptime_secs: sys.process(root).map(|p| p.run_time()).unwrap_or(0),
+                processes: tree.len(),
+                ..Default::default()
+            };
+            for process in tree.iter().filter_map(|p| sys.process(*p)) {
+                stats.cpu_usage += process.cpu_usage();
+                stats.memory += process.memory();
+                stats.threads += process.tasks().map_or(1, |tasks| tasks.len().max(1));
+            }
+            result.insert(id, stats);
+        }
+        Ok(result)
+    }
+
+    /// 订阅系统资源快照。
+    ///
+    /// 所有订阅者共享同一个刷新任务：首个订阅者启动任务，全部取消订阅后任务退出。
+    pub fn subscribe_system_stats(&self) -> watch::Receiver<SystemStats> {
+        let mut feed = self.stats_feed.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(tx) = feed.as_ref().filter(|tx| !tx.is_closed()) {
+            return tx.subscribe();
+        }
+
+        let (tx, rx) = watch::channel(self.get_system_stats());
+        *feed = Some(tx.clone());
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(STATS_FEED_TICK);
+            loop {
+                ticker.tick().await;
+                if tx.is_closed() {
+                    break;
+                }
+                tx.send_replace(manager.get_system_stats());
+            }
+        });
+        rx
+    }
+
     /// 获取系统资源统计
     pub fn get_system_stats(&self) -> SystemStats {
         let mut sys = self.system.lock().unwrap_or_else(|e| e.into_inner());
@@ -79,3 +176,41 @@ impl ServiceManager {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn process_stats_reads_tracked_pid() {
+        let dir = TempDir::new().unwrap();
+        let mgr = ServiceManager::new(dir.path());
+        std::fs::create_dir_all(mgr.runtime_dir("self")).unwrap();
+        mgr.write_pid("self", std::process::id()).unwrap();
+
+        let stats = mgr.process_stats("self").await.unwrap().unwrap();
+        assert_eq!(stats.pid, std::process::id());
+        assert!(stats.memory > 0);
+        assert!(stats.threads >= 1);
+        assert!(stats.processes >= 1);
+
+        assert!(mgr.process_stats("missing").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn system_stats_subscribers_share_one_feed() {
+        let dir = TempDir::new().unwrap();
+        let mgr = ServiceManager::new(dir.path());
+
+        let first = mgr.subscribe_system_stats();
+        let second = mgr.subscribe_system_stats();
+        assert!(first.same_channel(&second));
+        assert!(first.borrow().memory_total > 0);
+
+        drop(first);
+        drop(second);
+        let third = mgr.subscribe_system_stats();
+        assert!(third.borrow().memory_total > 0);
+    }
+}
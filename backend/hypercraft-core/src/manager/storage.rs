@@ -1,7 +1,11 @@
 use super::*;
+use chrono::{DateTime, NaiveDateTime, Utc};
 use futures::future::join_all;
 use tracing::instrument;
 
+/// 归档条目名中的时间戳格式（UTC，精确到毫秒）
+const ARCHIVE_TIMESTAMP_FORMAT: &str = "%Y%m%d%H%M%S%3f";
+
 impl ServiceManager {
     /// 创建并落盘 manifest。
     #[instrument(skip(self, manifest))]
@@ -9,6 +13,9 @@ impl ServiceManager {
         self.ensure_base_dirs_async().await?;
         self.validate_id(&manifest.id)?;
         self.enforce_policy(&manifest)?;
+        self.validate_env_refs(&manifest.env)?;
+        logs::validate_log_encoding(&manifest)?;
+        signal::validate_kill_signal(&manifest)?;
 
         let manifest_path = self.manifest_path(&manifest.id);
         if tokio::fs::try_exists(&manifest_path).await.unwrap_or(false) {
@@ -22,12 +29,66 @@ impl ServiceManager {
         if manifest.created_at.is_none() {
             manifest.created_at = Some(chrono::Utc::now());
         }
+        manifest.schema_version = MANIFEST_SCHEMA_VERSION;
+        manifest.assign_schedule_ids();
 
         let data = serde_json::to_vec(&manifest)?;
         tokio::fs::write(&manifest_path, data).await?;
         Ok(manifest)
     }
 
+    /// 以已有服务为模板创建新服务：替换 id/name，合并 `overrides` 中的字段。
+    ///
+    /// 只复制 manifest，不复制 runtime/logs；`created_at` 重新生成。
+    #[instrument(skip(self, overrides))]
+    pub async fn clone_service(
+        &self,
+        src_id: &str,
+        new_id: &str,
+        overrides: serde_json::Value,
+    ) -> Result<ServiceManifest> {
+        let source = self.load_manifest(src_id).await?;
+        let manifest = self.cloned_manifest(&source, new_id, overrides)?;
+        self.create_service(manifest).await
+    }
+
+    /// 构造克隆后的 manifest（不落盘），便于调用方在创建前做权限检查。
+    pub fn cloned_manifest(
+        &self,
+        source: &ServiceManifest,
+        new_id: &str,
+        overrides: serde_json::Value,
+    ) -> Result<ServiceManifest> {
+        self.validate_id(new_id)?;
+
+        let mut value = serde_json::to_value(source)?;
+        let fields = value
+            .as_object_mut()
+            .ok_or_else(|| ServiceError::InvalidInput("manifest is not an object".into()))?;
+        fields.insert("name".into(), new_id.into());
+        match overrides {
+            serde_json::Value::Object(map) => fields.extend(map),
+            serde_json::Value::Null => {}
+            _ => {
+                return Err(ServiceError::PolicyViolation(
+                    "overrides must be a JSON object".into(),
+                ))
+            }
+        }
+        fields.insert("id".into(), new_id.into());
+        fields.remove("created_at");
+
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// 服务是否存在（先校验 ID，非法 ID 返回 InvalidId）
+    pub async fn service_exists(&self, id: &str) -> Result<bool> {
+        self.validate_id(id)?;
+        Ok(tokio::fs::try_exists(self.manifest_path(id))
+            .await
+            .unwrap_or(false))
+    }
+
     /// 更新 manifest（保持 id 不变，补齐 created_at）。
     #[instrument(skip(self, manifest))]
     pub async fn update_service(&self, id: &str, mut manifest: ServiceManifest) -> Result<()> {
@@ -42,54 +103,47 @@ impl ServiceManager {
         }
 
         self.enforce_policy(&manifest)?;
+        self.validate_env_refs(&manifest.env)?;
+        logs::validate_log_encoding(&manifest)?;
+        signal::validate_kill_signal(&manifest)?;
 
         if manifest.created_at.is_none() {
             if let Ok(existing) = self.load_manifest(id).await {
                 manifest.created_at = existing.created_at;
             }
         }
+        manifest.schema_version = MANIFEST_SCHEMA_VERSION;
+        manifest.assign_schedule_ids();
 
         let data = serde_json::to_vec(&manifest)?;
         tokio::fs::write(&manifest_path, data).await?;
         Ok(())
     }
 
-    /// 列出服务以及状态（并发查询优化）。
+    /// 列出服务以及状态（并发查询，共享一次全量进程快照）。
     #[instrument(skip(self))]
     pub async fn list_services(&self) -> Result<Vec<ServiceSummary>> {
-        self.ensure_base_dirs_async().await?;
-        
-        // 先收集所有服务 ID
-        let services_dir = self.services_dir();
-        let mut service_ids = Vec::new();
-        let mut entries = tokio::fs::read_dir(&services_dir).await?;
-        while let Some(entry) = entries.next_entry().await? {
-            let file_type = entry.file_type().await?;
-            if !file_type.is_dir() {
-                continue;
-            }
-            let id = entry.file_name().to_string_lossy().to_string();
-            let manifest_path = entry.path().join("service.json");
-            if tokio::fs::try_exists(&manifest_path).await.unwrap_or(false) {
-                service_ids.push(id);
-            }
-        }
+        let service_ids = self.service_ids().await?;
+        let snapshot = self.process_snapshot();
 
         // 并发加载所有 manifest 和 status
         let futures: Vec<_> = service_ids
             .into_iter()
             .map(|id| {
                 let manager = self.clone();
+                let snapshot = snapshot.clone();
                 async move {
                     let manifest = manager.load_manifest(&id).await?;
-                    let status = manager.status(&id).await?;
+                    let status = manager.status_with(&id, Some(&snapshot)).await?;
                     Ok::<_, ServiceError>(ServiceSummary {
                         id,
                         name: manifest.name,
+                        started_at: status.started_at(),
                         state: status.state,
                         tags: manifest.tags,
                         group: manifest.group,
                         order: manifest.order,
+                        created_at: manifest.created_at,
                     })
                 }
             })
@@ -103,6 +157,27 @@ impl ServiceManager {
         Ok(summaries)
     }
 
+    /// 扫描 services 目录，返回所有存在 manifest 的服务 ID。
+    pub(super) async fn service_ids(&self) -> Result<Vec<String>> {
+        self.ensure_base_dirs_async().await?;
+
+        let services_dir = self.services_dir();
+        let mut service_ids = Vec::new();
+        let mut entries = tokio::fs::read_dir(&services_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let file_type = entry.file_type().await?;
+            if !file_type.is_dir() {
+                continue;
+            }
+            let id = entry.file_name().to_string_lossy().to_string();
+            let manifest_path = entry.path().join("service.json");
+            if tokio::fs::try_exists(&manifest_path).await.unwrap_or(false) {
+                service_ids.push(id);
+            }
+        }
+        Ok(service_ids)
+    }
+
     /// 从磁盘读 manifest（异步版本）。
     pub async fn load_manifest(&self, id: &str) -> Result<ServiceManifest> {
         let path = self.manifest_path(id);
@@ -110,13 +185,74 @@ impl ServiceManager {
             return Err(ServiceError::NotFound(id.to_string()));
         }
         let data = tokio::fs::read(&path).await?;
-        let manifest: ServiceManifest = serde_json::from_slice(&data)?;
+        let mut value: serde_json::Value = serde_json::from_slice(&data)?;
+        let migrated = Self::migrate_manifest(&mut value)?;
+        let mut manifest: ServiceManifest = serde_json::from_value(value)?;
+        // 旧版 manifest 的调度条目没有 ID：补齐后回写，保证 ID 稳定
+        let ids_assigned = manifest.assign_schedule_ids();
+        if migrated || ids_assigned {
+            if let Ok(data) = serde_json::to_vec(&manifest) {
+                let _ = tokio::fs::write(&path, data).await;
+            }
+        }
         Ok(manifest)
     }
 
-    /// 删除服务，要求已停止。
+    /// 把旧版 manifest JSON 逐版本升级到 [`MANIFEST_SCHEMA_VERSION`]，返回是否做了升级。
+    ///
+    /// 没有 `schema_version` 的文件视为版本 1；比当前程序更新的版本直接拒绝，
+    /// 避免旧程序按自己的理解回写而丢失字段。
+    pub fn migrate_manifest(value: &mut serde_json::Value) -> Result<bool> {
+        let fields = value
+            .as_object_mut()
+            .ok_or_else(|| ServiceError::InvalidInput("manifest is not an object".into()))?;
+        let mut version = match fields.get("schema_version") {
+            None => 1,
+            Some(raw) => raw
+                .as_u64()
+                .and_then(|v| u32::try_from(v).ok())
+                .filter(|v| *v >= 1)
+                .ok_or_else(|| {
+                    ServiceError::InvalidInput(format!("invalid manifest schema_version: {raw}"))
+                })?,
+        };
+        if version > MANIFEST_SCHEMA_VERSION {
+            return Err(ServiceError::InvalidInput(format!(
+                "manifest schema_version {version} is newer than supported version {MANIFEST_SCHEMA_VERSION}"
+            )));
+        }
+        if version == MANIFEST_SCHEMA_VERSION {
+            return Ok(false);
+        }
+
+        while version < MANIFEST_SCHEMA_VERSION {
+            match version {
+                // v1 -> v2：单个 `schedule` 对象（或 null）改为 `schedules` 数组
+                1 => {
+                    if let Some(schedule) = fields.remove("schedule") {
+                        if !fields.contains_key("schedules") {
+                            let schedules = match schedule {
+                                serde_json::Value::Null => vec![],
+                                serde_json::Value::Array(list) => list,
+                                one => vec![one],
+                            };
+                            fields.insert("schedules".into(), schedules.into());
+                        }
+                    }
+                }
+                _ => unreachable!("no migration from manifest schema_version {version}"),
+            }
+            version += 1;
+        }
+        fields.insert("schema_version".into(), MANIFEST_SCHEMA_VERSION.into());
+        Ok(true)
+    }
+
+    /// 永久删除服务（不可恢复），要求已停止；需要可恢复的删除请用 [`Self::archive_service`]。
+    ///
+    /// `keep_logs` 为 true 时先把日志目录移动到 `<data_dir>/deleted/<id>/logs` 再删除服务目录。
     #[instrument(skip(self))]
-    pub async fn delete_service(&self, id: &str) -> Result<()> {
+    pub async fn delete_service(&self, id: &str, keep_logs: bool) -> Result<()> {
         let status = self.status(id).await?;
         if matches!(status.state, ServiceState::Running) {
             return Err(ServiceError::AlreadyRunning(id.to_string()));
@@ -125,13 +261,131 @@ impl ServiceManager {
         if !tokio::fs::try_exists(&dir).await.unwrap_or(false) {
             return Err(ServiceError::NotFound(id.to_string()));
         }
+        if keep_logs {
+            self.archive_logs(id).await?;
+        }
         tokio::fs::remove_dir_all(dir).await?;
+        self.remove_cgroup(id);
+        Ok(())
+    }
+
+    /// 软删除：把已停止服务的整个目录（manifest、日志、运行时状态）移动到
+    /// `<data_dir>/archive/<id>-<时间戳>`，之后可用 [`Self::restore_archived`] 恢复。
+    #[instrument(skip(self))]
+    pub async fn archive_service(&self, id: &str) -> Result<ArchivedService> {
+        self.validate_id(id)?;
+        let status = self.status(id).await?;
+        if matches!(status.state, ServiceState::Running) {
+            return Err(ServiceError::AlreadyRunning(id.to_string()));
+        }
+        let dir = self.service_dir(id);
+        if !tokio::fs::try_exists(&dir).await.unwrap_or(false) {
+            return Err(ServiceError::NotFound(id.to_string()));
+        }
+        let name = self.load_manifest(id).await?.name;
+
+        let archive_id = format!("{id}-{}", Utc::now().format(ARCHIVE_TIMESTAMP_FORMAT));
+        let target = self.archive_dir().join(&archive_id);
+        tokio::fs::create_dir_all(self.archive_dir()).await?;
+        if tokio::fs::try_exists(&target).await.unwrap_or(false) {
+            return Err(ServiceError::AlreadyExists(archive_id));
+        }
+        tokio::fs::rename(&dir, &target).await?;
+        self.remove_cgroup(id);
+        tracing::info!(service_id = %id, archive = %target.display(), "archived service");
+
+        let (service_id, archived_at) = self
+            .parse_archive_id(&archive_id)
+            .ok_or(ServiceError::InvalidId)?;
+        Ok(ArchivedService {
+            archive_id,
+            service_id,
+            name,
+            archived_at,
+        })
+    }
+
+    /// 列出已归档的服务，最近归档的在前。
+    pub async fn list_archived(&self) -> Result<Vec<ArchivedService>> {
+        let mut entries = match tokio::fs::read_dir(self.archive_dir()).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
+        let mut archived = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let archive_id = entry.file_name().to_string_lossy().into_owned();
+            let Some((service_id, archived_at)) = self.parse_archive_id(&archive_id) else {
+                continue;
+            };
+            // 只取名称，不做迁移与回写，归档内容保持原样
+            let name = tokio::fs::read(entry.path().join("service.json"))
+                .await
+                .ok()
+                .and_then(|data| serde_json::from_slice::<serde_json::Value>(&data).ok())
+                .and_then(|value| value.get("name")?.as_str().map(str::to_string))
+                .unwrap_or_else(|| service_id.clone());
+            archived.push(ArchivedService {
+                archive_id,
+                service_id,
+                name,
+                archived_at,
+            });
+        }
+        archived.sort_by_key(|a| std::cmp::Reverse(a.archived_at));
+        Ok(archived)
+    }
+
+    /// 把归档条目移回 `<data_dir>/services/<id>`；同 ID 的服务已存在时返回 AlreadyExists。
+    #[instrument(skip(self))]
+    pub async fn restore_archived(&self, archive_id: &str) -> Result<ServiceManifest> {
+        let (service_id, _) = self
+            .parse_archive_id(archive_id)
+            .ok_or(ServiceError::InvalidId)?;
+        let source = self.archive_dir().join(archive_id);
+        if !tokio::fs::try_exists(&source).await.unwrap_or(false) {
+            return Err(ServiceError::NotFound(archive_id.to_string()));
+        }
+        if self.service_exists(&service_id).await? {
+            return Err(ServiceError::AlreadyExists(service_id));
+        }
+        self.ensure_base_dirs_async().await?;
+        tokio::fs::rename(&source, self.service_dir(&service_id)).await?;
+        tracing::info!(service_id = %service_id, archive = %archive_id, "restored archived service");
+        self.load_manifest(&service_id).await
+    }
+
+    /// 解析 `<id>-<时间戳>` 形式的归档条目名；ID 不合法或时间戳无法解析时返回 None。
+    fn parse_archive_id(&self, archive_id: &str) -> Option<(String, DateTime<Utc>)> {
+        let (id, stamp) = archive_id.rsplit_once('-')?;
+        self.validate_id(id).ok()?;
+        let at = NaiveDateTime::parse_from_str(stamp, ARCHIVE_TIMESTAMP_FORMAT).ok()?;
+        Some((id.to_string(), at.and_utc()))
+    }
+
+    /// 将服务日志归档到 `<data_dir>/deleted/<id>/logs`；同名归档已存在时追加时间戳后缀。
+    async fn archive_logs(&self, id: &str) -> Result<()> {
+        let logs_dir = self.logs_dir(id);
+        if !tokio::fs::try_exists(&logs_dir).await.unwrap_or(false) {
+            return Ok(());
+        }
+        let archive_root = self.deleted_dir(id);
+        tokio::fs::create_dir_all(&archive_root).await?;
+        let mut target = archive_root.join("logs");
+        if tokio::fs::try_exists(&target).await.unwrap_or(false) {
+            let suffix = chrono::Utc::now().format("%Y%m%d%H%M%S");
+            target = archive_root.join(format!("logs-{suffix}"));
+        }
+        tokio::fs::rename(&logs_dir, &target).await?;
+        tracing::info!(service_id = %id, archive = %target.display(), "archived service logs");
         Ok(())
     }
 
     /// 确保基础目录存在（异步版本）。
     pub async fn ensure_base_dirs_async(&self) -> Result<()> {
-        tokio::fs::create_dir_all(self.services_dir()).await?;
-        Ok(())
+        let dir = self.services_dir();
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .map_err(|e| storage_error(&dir, e))
     }
 }
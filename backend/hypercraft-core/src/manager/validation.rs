@@ -0,0 +1,74 @@
+//! 创建前的干跑校验：与 `create_service` 相同的检查，但收集全部问题且不写盘。
+
+use super::*;
+use crate::error::PolicyReason;
+use crate::models::{ManifestValidation, ValidationProblem};
+use crate::ServiceScheduler;
+
+impl ServiceManager {
+    /// 校验 manifest 能否创建：ID、重复 ID、工作目录、策略、环境变量引用与调度。
+    ///
+    /// 各项检查互不短路，返回全部问题；不会创建任何目录或文件。
+    pub async fn validate_manifest(&self, manifest: &ServiceManifest) -> ManifestValidation {
+        let mut problems = Vec::new();
+        let mut push = |field: &str, err: ServiceError| {
+            problems.push(ValidationProblem {
+                field: field.to_string(),
+                message: err.to_string(),
+            })
+        };
+
+        match self.validate_id(&manifest.id) {
+            Ok(()) => {
+                let exists = tokio::fs::try_exists(self.manifest_path(&manifest.id))
+                    .await
+                    .unwrap_or(false);
+                if exists {
+                    push("id", ServiceError::AlreadyExists(manifest.id.clone()));
+                }
+            }
+            Err(e) => push("id", e),
+        }
+
+        // cwd 无效时单独报告，策略检查跳过 cwd（包括默认工作目录）以免重复
+        let mut cwd_valid = true;
+        if let Some(cwd) = &manifest.cwd {
+            if let Err(e) = self.check_cwd(&policy::expand_cwd(cwd, manifest)) {
+                push("cwd", e);
+                cwd_valid = false;
+            }
+        }
+        if let Err(e) = self.enforce_policy_inner(manifest, cwd_valid) {
+            push(policy_field(&e), e);
+        }
+        if let Err(e) = self.validate_env_refs(&manifest.env) {
+            push("env", e);
+        }
+        if let Err(e) = logs::validate_log_encoding(manifest) {
+            push("log_encoding", e);
+        }
+        if let Err(e) = signal::validate_kill_signal(manifest) {
+            push("kill_signal", e);
+        }
+        for (i, schedule) in manifest.schedules.iter().enumerate() {
+            if let Err(e) = ServiceScheduler::validate_schedule(schedule) {
+                push(&format!("schedules[{i}]"), e);
+            }
+        }
+
+        ManifestValidation {
+            valid: problems.is_empty(),
+            problems,
+        }
+    }
+}
+
+/// 策略错误对应的 manifest 字段
+fn policy_field(err: &ServiceError) -> &'static str {
+    match err {
+        ServiceError::PolicyViolation(PolicyReason::Command { .. }) => "command",
+        ServiceError::PolicyViolation(PolicyReason::PathRoot { field, .. }) => field,
+        ServiceError::PolicyViolation(PolicyReason::InvalidPath { field, .. }) => field,
+        _ => "manifest",
+    }
+}
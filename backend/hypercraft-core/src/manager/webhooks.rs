@@ -0,0 +1,379 @@
+//! 生命周期 Webhook：订阅事件广播，异步投递到全局与服务级接收地址。
+//!
+//! 投递在独立任务中进行（带超时与有限重试），不会阻塞启停流程。
+//! 服务级地址投递前会解析主机，拒绝内网等非公网地址；全局 `HC_WEBHOOK_URL` 不受限制。
+
+use super::*;
+use crate::manifest::{ScheduleAction, WebhookConfig, WebhookEvent};
+use crate::models::StateChangeReason;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+use std::net::SocketAddr;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::Duration;
+
+/// 单次投递超时
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+/// 最多尝试次数（含首次）
+const WEBHOOK_MAX_ATTEMPTS: u32 = 3;
+/// 重试初始退避，之后每次翻倍
+const WEBHOOK_RETRY_BACKOFF: Duration = Duration::from_millis(500);
+/// 未配置模板时的消息格式
+const DEFAULT_WEBHOOK_TEMPLATE: &str =
+    "[hypercraft] {name} ({id}) {event}: state={state} exit_code={exit_code}";
+
+/// POST 给接收方的 JSON
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize)]
+pub(super) struct WebhookPayload {
+    pub event: WebhookEvent,
+    pub service_id: String,
+    pub service_name: String,
+    pub state: Option<ServiceState>,
+    pub exit_code: Option<i32>,
+    pub healthy: Option<bool>,
+    /// 调度触发事件的条目 ID 与动作
+    pub schedule_id: Option<String>,
+    pub action: Option<ScheduleAction>,
+    pub at: DateTime<Utc>,
+    /// 按模板渲染的消息（Slack 读取 text）
+    pub text: String,
+    /// 同 text（Discord 读取 content）
+    pub content: String,
+}
+
+impl ServiceManager {
+    /// 启动 Webhook 分发任务：需要在 tokio 运行时内调用，进程存活期间持续运行。
+    pub fn spawn_webhook_dispatcher(&self) {
+        let client = match webhook_client_builder().build() {
+            Ok(c) => c,
+            Err(e) => {
+                tracing::warn!(error = %e, "failed to build webhook client");
+                return;
+            }
+        };
+        let mut rx = self.subscribe_events();
+        let manager = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let event = match rx.recv().await {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped, "webhook dispatcher lagged, events dropped");
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                manager.dispatch_webhooks(&client, &event).await;
+            }
+        });
+    }
+
+    /// 计算事件的投递目标并逐个异步投递。
+    async fn dispatch_webhooks(&self, client: &reqwest::Client, event: &ServiceEvent) {
+        let kind = webhook_event(event);
+        let manifest = self.load_manifest(event.service_id()).await.ok();
+        let global = self.webhook_url.as_ref().map(|url| WebhookConfig {
+            url: url.clone(),
+            events: Vec::new(),
+            template: None,
+        });
+        // 标记服务级地址：投递前需要检查解析结果
+        let targets = global
+            .into_iter()
+            .map(|hook| (hook, false))
+            .chain(
+                manifest
+                    .iter()
+                    .flat_map(|m| m.webhooks.iter().cloned().map(|hook| (hook, true))),
+            )
+            .filter(|(hook, _)| hook.accepts(kind));
+
+        let name = manifest
+            .as_ref()
+            .map(|m| m.name.clone())
+            .unwrap_or_else(|| event.service_id().to_string());
+        for (hook, restricted) in targets {
+            let payload = build_payload(event, &name, hook.template.as_deref());
+            tokio::spawn(deliver(client.clone(), hook.url, payload, restricted));
+        }
+    }
+}
+
+/// 事件对应的 Webhook 类型
+fn webhook_event(event: &ServiceEvent) -> WebhookEvent {
+    match event {
+        ServiceEvent::StateChanged { reason, .. } => match reason {
+            StateChangeReason::Started => WebhookEvent::Started,
+            StateChangeReason::AutoRestart => WebhookEvent::AutoRestart,
+            StateChangeReason::Stopped => WebhookEvent::Stopped,
+            StateChangeReason::Exited => WebhookEvent::Exited,
+            StateChangeReason::Crashed => WebhookEvent::Crashed,
+        },
+        ServiceEvent::HealthChanged { .. } => WebhookEvent::HealthChanged,
+        ServiceEvent::ScheduleFired { .. } => WebhookEvent::ScheduleFired,
+    }
+}
+
+fn build_payload(event: &ServiceEvent, name: &str, template: Option<&str>) -> WebhookPayload {
+    let kind = webhook_event(event);
+    let (state, exit_code, healthy, schedule, at) = match event {
+        ServiceEvent::StateChanged {
+            state,
+            exit_code,
+            at,
+            ..
+        } => (Some(state.clone()), *exit_code, None, None, *at),
+        ServiceEvent::HealthChanged { healthy, at, .. } => (None, None, Some(*healthy), None, *at),
+        ServiceEvent::ScheduleFired {
+            schedule_id,
+            action,
+            at,
+            ..
+        } => (
+            None,
+            None,
+            None,
+            Some((schedule_id.clone(), action.clone())),
+            *at,
+        ),
+    };
+    let (schedule_id, action) = schedule.unzip();
+    let text = render_template(
+        template.unwrap_or(DEFAULT_WEBHOOK_TEMPLATE),
+        &[
+            ("id", event.service_id().to_string()),
+            ("name", name.to_string()),
+            ("event", enum_label(&kind)),
+            (
+                "state",
+                state.as_ref().map(enum_label).unwrap_or_else(|| "-".into()),
+            ),
+            (
+                "exit_code",
+                exit_code
+                    .map(|c| c.to_string())
+                    .unwrap_or_else(|| "-".into()),
+            ),
+            (
+                "healthy",
+                healthy.map(|h| h.to_string()).unwrap_or_else(|| "-".into()),
+            ),
+            (
+                "action",
+                action
+                    .as_ref()
+                    .map(enum_label)
+                    .unwrap_or_else(|| "-".into()),
+            ),
+        ],
+    );
+    WebhookPayload {
+        event: kind,
+        service_id: event.service_id().to_string(),
+        service_name: name.to_string(),
+        state,
+        exit_code,
+        healthy,
+        schedule_id,
+        action,
+        at,
+        content: text.clone(),
+        text,
+    }
+}
+
+/// 替换模板中的 `{key}` 占位符
+fn render_template(template: &str, values: &[(&str, String)]) -> String {
+    values
+        .iter()
+        .fold(template.to_string(), |text, (key, value)| {
+            text.replace(&format!("{{{key}}}"), value)
+        })
+}
+
+/// 取枚举的 serde 名称（如 `auto_restart`、`running`）
+fn enum_label<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn webhook_client_builder() -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+}
+
+/// 为服务级地址构建专用客户端：解析主机并要求全部地址都是公网地址，
+/// 再把连接固定到这些地址，避免 DNS 重绑定绕过检查。
+async fn pinned_client(url: &str) -> std::result::Result<reqwest::Client, String> {
+    let url = url::Url::parse(url).map_err(|e| e.to_string())?;
+    let port = url.port_or_known_default().unwrap_or(80);
+    let (domain, addrs): (Option<&str>, Vec<SocketAddr>) = match url.host() {
+        Some(url::Host::Domain(domain)) => (
+            Some(domain),
+            tokio::net::lookup_host((domain, port))
+                .await
+                .map_err(|e| e.to_string())?
+                .collect(),
+        ),
+        Some(url::Host::Ipv4(ip)) => (None, vec![SocketAddr::new(ip.into(), port)]),
+        Some(url::Host::Ipv6(ip)) => (None, vec![SocketAddr::new(ip.into(), port)]),
+        None => (None, Vec::new()),
+    };
+    if addrs.is_empty() || addrs.iter().any(|addr| !crate::is_public_ip(addr.ip())) {
+        return Err("host resolves to a non-public address".into());
+    }
+    let mut builder = webhook_client_builder();
+    if let Some(domain) = domain {
+        builder = builder.resolve_to_addrs(domain, &addrs);
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+/// 投递一次事件：非 2xx 或网络错误时按退避重试，最终失败只记日志。
+///
+/// `restricted` 为服务级地址，只投递到解析结果全部为公网的主机。
+async fn deliver(client: reqwest::Client, url: String, payload: WebhookPayload, restricted: bool) {
+    // 地址中常含令牌，日志只记录主机名
+    let host = url::Url::parse(&url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .unwrap_or_default();
+    let client = if restricted {
+        match pinned_client(&url).await {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::warn!(
+                    service_id = %payload.service_id,
+                    host = %host,
+                    error = %e,
+                    "webhook target not allowed"
+                );
+                return;
+            }
+        }
+    } else {
+        client
+    };
+    let mut backoff = WEBHOOK_RETRY_BACKOFF;
+    for attempt in 1..=WEBHOOK_MAX_ATTEMPTS {
+        match client.post(&url).json(&payload).send().await {
+            Ok(resp) if resp.status().is_success() => return,
+            Ok(resp) => tracing::warn!(
+                service_id = %payload.service_id,
+                host = %host,
+                attempt,
+                status = %resp.status(),
+                "webhook rejected"
+            ),
+            Err(e) => tracing::warn!(
+                service_id = %payload.service_id,
+                host = %host,
+                attempt,
+                error = %e,
+                "webhook delivery failed"
+            ),
+        }
+        if attempt < WEBHOOK_MAX_ATTEMPTS {
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+    }
+    tracing::error!(service_id = %payload.service_id, host = %host, "webhook gave up");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crashed() -> ServiceEvent {
+        ServiceEvent::StateChanged {
+            id: "web".into(),
+            state: ServiceState::Stopped,
+            reason: StateChangeReason::Crashed,
+            pid: Some(42),
+            exit_code: Some(137),
+            at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn payload_renders_template() {
+        let payload = build_payload(
+            &crashed(),
+            "Web Server",
+            Some("{name}/{id} {event} {state} {exit_code}"),
+        );
+        assert_eq!(payload.event, WebhookEvent::Crashed);
+        assert_eq!(payload.text, "Web Server/web crashed stopped 137");
+        assert_eq!(payload.content, payload.text);
+
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["event"], "crashed");
+        assert_eq!(json["exit_code"], 137);
+        assert!(json.get("healthy").is_none());
+    }
+
+    #[test]
+    fn default_template_fills_missing_fields() {
+        let event = ServiceEvent::HealthChanged {
+            id: "web".into(),
+            healthy: false,
+            at: Utc::now(),
+        };
+        let payload = build_payload(&event, "web", None);
+        assert_eq!(payload.event, WebhookEvent::HealthChanged);
+        assert_eq!(
+            payload.text,
+            "[hypercraft] web (web) health_changed: state=- exit_code=-"
+        );
+        assert_eq!(payload.healthy, Some(false));
+    }
+
+    #[test]
+    fn schedule_fired_payload_carries_action() {
+        let event = ServiceEvent::ScheduleFired {
+            id: "web".into(),
+            schedule_id: "nightly".into(),
+            action: ScheduleAction::Restart,
+            at: Utc::now(),
+        };
+        let payload = build_payload(&event, "web", Some("{id} {event} {action}"));
+        assert_eq!(payload.event, WebhookEvent::ScheduleFired);
+        assert_eq!(payload.text, "web schedule_fired restart");
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["schedule_id"], "nightly");
+        assert_eq!(json["action"], "restart");
+    }
+
+    #[tokio::test]
+    async fn service_webhooks_refuse_internal_addresses() {
+        for url in [
+            "http://127.0.0.1:9/hook",
+            "http://localhost:9/hook",
+            "http://[::1]:9/hook",
+        ] {
+            assert!(pinned_client(url).await.is_err(), "{url}");
+        }
+    }
+
+    #[test]
+    fn event_filter_empty_accepts_all() {
+        let hook = WebhookConfig {
+            url: "https://hooks.example.com/x".into(),
+            events: vec![],
+            template: None,
+        };
+        assert!(hook.accepts(WebhookEvent::Started));
+        let hook = WebhookConfig {
+            events: vec![WebhookEvent::Crashed],
+            ..hook
+        };
+        assert!(hook.accepts(webhook_event(&crashed())));
+        assert!(!hook.accepts(WebhookEvent::Stopped));
+    }
+}
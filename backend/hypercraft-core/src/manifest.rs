@@ -18,6 +18,88 @@ pub struct WebConfig {
     pub health_path: Option<String>,
 }
 
+/// HTTP 健康检查配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthCheck {
+    /// 探测地址（仅允许本机 http/https）
+    pub url: String,
+    /// 探测间隔（秒）
+    #[serde(default = "default_health_interval_secs")]
+    pub interval_secs: u64,
+    /// 单次探测超时（秒）
+    #[serde(default = "default_health_timeout_secs")]
+    pub timeout_secs: u64,
+    /// 连续成功多少次判定为健康
+    #[serde(default = "default_healthy_threshold")]
+    pub healthy_threshold: u32,
+    /// 连续失败多少次判定为不健康
+    #[serde(default = "default_unhealthy_threshold")]
+    pub unhealthy_threshold: u32,
+    /// 持续不健康多少秒后自动重启（需开启 auto_restart）
+    #[serde(default = "default_unhealthy_restart_secs")]
+    pub unhealthy_restart_secs: u64,
+}
+
+/// Webhook 可订阅的生命周期事件
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    /// 手动、定时或依赖触发的启动
+    Started,
+    /// 主动停止（关闭命令或强制终止）
+    Stopped,
+    /// 进程自行以 0 退出
+    Exited,
+    /// 进程以非 0 状态退出
+    Crashed,
+    /// 自动重启拉起
+    AutoRestart,
+    /// 健康检查结论变化
+    HealthChanged,
+    /// 调度条目触发（含补执行）
+    ScheduleFired,
+}
+
+/// 生命周期 Webhook 配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// 接收地址（http/https）
+    pub url: String,
+    /// 订阅的事件，为空表示全部
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+    /// 消息模板，可用占位符：{id} {name} {event} {state} {exit_code} {healthy} {action}
+    #[serde(default)]
+    pub template: Option<String>,
+}
+
+impl WebhookConfig {
+    /// 是否订阅了该事件
+    pub fn accepts(&self, event: WebhookEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+fn default_health_interval_secs() -> u64 {
+    10
+}
+
+fn default_health_timeout_secs() -> u64 {
+    5
+}
+
+fn default_healthy_threshold() -> u32 {
+    1
+}
+
+fn default_unhealthy_threshold() -> u32 {
+    3
+}
+
+fn default_unhealthy_restart_secs() -> u64 {
+    60
+}
+
 /// 定时调度动作
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "lowercase")]
@@ -31,39 +113,72 @@ pub enum ScheduleAction {
     Stop,
 }
 
+/// 调度类型：cron、固定间隔或一次性
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduleKind {
+    /// Cron 表达式 (秒 分 时 日 月 周)
+    Cron(String),
+    /// 每隔 `every_secs` 秒触发一次
+    Interval { every_secs: u64 },
+    /// 在指定时间触发一次
+    Once { at: DateTime<Utc> },
+}
+
 /// 定时调度配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Schedule {
+    /// 稳定 ID，保存 manifest 时自动生成
+    #[serde(default)]
+    pub id: String,
     /// 是否启用定时调度
     #[serde(default)]
     pub enabled: bool,
     /// Cron 表达式 (秒 分 时 日 月 周)
     /// 示例: "0 0 8 * * *" 每天 08:00 启动
     /// 示例: "0 30 6 * * 1-5" 工作日 06:30 启动
+    /// 未设置 `kind` 时使用
+    #[serde(default)]
     pub cron: String,
+    /// 调度类型，缺省为 `cron` 字段对应的 Cron
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<ScheduleKind>,
     /// 调度触发的动作
     #[serde(default)]
     pub action: ScheduleAction,
-    /// 时区（可选，默认使用系统时区）
+    /// IANA 时区（如 Asia/Shanghai），未设置时按 UTC
     #[serde(default)]
     pub timezone: Option<String>,
+    /// 宿主停机期间错过的触发，在调度器重新加载时补执行一次
+    #[serde(default)]
+    pub catch_up: bool,
 }
 
 impl Default for Schedule {
     fn default() -> Self {
         Self {
+            id: String::new(),
             enabled: false,
             cron: String::new(),
+            kind: None,
             action: ScheduleAction::Start,
             timezone: None,
+            catch_up: false,
         }
     }
 }
 
+/// 当前 manifest 存储格式版本；结构变化需要迁移时递增，并在
+/// `ServiceManager::migrate_manifest` 中补充对应的升级步骤
+pub const MANIFEST_SCHEMA_VERSION: u32 = 2;
+
 /// 服务清单结构体
 /// 包含服务的完整配置信息，可序列化为 JSON 或反序列化自 JSON
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServiceManifest {
+    /// 存储格式版本；没有该字段的旧文件视为 1，加载时升级到 [`MANIFEST_SCHEMA_VERSION`]
+    #[serde(default = "default_schema_version")]
+    pub schema_version: u32,
     /// 服务的唯一标识符
     pub id: String,
     /// 服务的显示名称
@@ -73,9 +188,12 @@ pub struct ServiceManifest {
     /// 启动命令的参数列表
     #[serde(default)]
     pub args: Vec<String>,
-    /// 环境变量映射表
+    /// 环境变量映射表，值支持 `${ENV:NAME}` 与 `${SECRET:key}` 引用（启动时解析）
     #[serde(default)]
     pub env: BTreeMap<String, String>,
+    /// 额外加载的 .env 文件（KEY=VALUE）；相对路径基于 cwd，同名变量以 `env` 为准
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_file: Option<String>,
     /// 服务的工作目录
     #[serde(default)]
     pub cwd: Option<String>,
@@ -85,15 +203,40 @@ pub struct ServiceManifest {
     /// 服务退出时是否自动重启
     #[serde(default)]
     pub auto_restart: bool,
+    /// 自动重启的初始退避（毫秒），窗口内每多一次重启翻倍（默认 1000）
+    #[serde(default)]
+    pub restart_backoff_ms: Option<u64>,
+    /// 滑动窗口内允许的最大自动重启次数，超过后放弃重启（默认不限）
+    #[serde(default)]
+    pub max_restarts: Option<u32>,
+    /// 统计重启次数的滑动窗口（秒，默认 60）
+    #[serde(default)]
+    pub restart_window_secs: Option<u64>,
     /// 启动时是否清空日志文件（默认 true）
     #[serde(default = "default_clear_log_on_start")]
     pub clear_log_on_start: bool,
     /// 优雅关闭时执行的命令
     #[serde(default)]
     pub shutdown_command: Option<String>,
+    /// 发送关闭命令后等待进程退出的秒数，超时强制终止（未设置则只发送命令）
+    #[serde(default)]
+    pub shutdown_timeout_secs: Option<u64>,
+    /// kill 时先发送的信号（如 TERM、INT），等待 `kill_timeout_secs` 后仍存活再 SIGKILL；
+    /// 未设置时直接 SIGKILL
+    #[serde(default)]
+    pub kill_signal: Option<String>,
+    /// 配置了 `kill_signal` 时等待进程退出的秒数，默认 10 秒
+    #[serde(default)]
+    pub kill_timeout_secs: Option<u64>,
     /// 服务运行的用户账户（如适用）
     #[serde(default)]
     pub run_as: Option<String>,
+    /// 内存上限（MB），Linux 上通过 cgroup v2 的 memory.max 生效
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_limit_mb: Option<u64>,
+    /// CPU 配额百分比（100 表示一个核），Linux 上通过 cgroup v2 的 cpu.max 生效
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_quota_percent: Option<u32>,
     /// 服务创建的时间戳
     #[serde(default)]
     pub created_at: Option<DateTime<Utc>>,
@@ -106,21 +249,100 @@ pub struct ServiceManifest {
     /// 服务在组内的排序顺序
     #[serde(default)]
     pub order: i32,
+    /// 启动前需要先运行的服务 ID
+    #[serde(default)]
+    pub depends_on: Vec<String>,
     /// 服务日志的输出路径
     #[serde(default)]
     pub log_path: Option<String>,
+    /// 日志滚动时保留的历史分段数（latest.log.1..N，默认 3；0 表示原地截断不保留）
+    #[serde(default)]
+    pub log_keep_files: Option<u32>,
+    /// 日志字符编码（如 utf-8、gb18030、shift_jis、big5、latin1）；
+    /// 未设置时先按 UTF-8 解码，失败再尝试 GB18030
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_encoding: Option<String>,
+    /// 启动宽限期（毫秒）：期间进程退出视为启动失败，默认 300
+    #[serde(default = "default_start_grace_ms")]
+    pub start_grace_ms: u64,
     /// 后端 PTY 行数，TUI 服务可调小以避免全屏程序撑高布局
     #[serde(default = "default_pty_rows")]
     pub pty_rows: u16,
     /// 是否按 TUI 终端渲染，启用后 attach 不回放历史 raw 日志
     #[serde(default)]
     pub terminal_tui: bool,
-    /// 定时调度配置
+    /// 是否在 PTY 中运行（默认 true）。关闭后改用管道读取 stdout/stderr，按整行合并写入日志，
+    /// 适合检测到终端会改变行为（颜色、缓冲）的程序；此时 attach 只能查看输出
+    #[serde(default = "default_tty")]
+    pub tty: bool,
+    /// 单独捕获 stderr（仅 `tty: false` 时生效）：写入 latest.log 旁的 stderr.log，
+    /// 不再混入 latest.log；默认关闭，两路输出合并
+    #[serde(default)]
+    pub split_stderr: bool,
+    /// 分离模式（仅 Unix）：进程独立会话运行，API 重启后继续存活并在启动时重新接管；
+    /// 输出直接追加到日志（不做滚动），输入经 runtime 目录下的 FIFO 传递，不分配 PTY
     #[serde(default)]
-    pub schedule: Option<Schedule>,
+    pub detached: bool,
+    /// 定时调度配置，可配置多条；兼容旧版单个 `schedule` 对象
+    #[serde(default, alias = "schedule", deserialize_with = "deserialize_schedules")]
+    pub schedules: Vec<Schedule>,
     /// 内嵌 Web 服务配置
     #[serde(default)]
     pub web: Option<WebConfig>,
+    /// HTTP 健康检查配置
+    #[serde(default)]
+    pub health_check: Option<HealthCheck>,
+    /// 生命周期事件 Webhook（在全局 HC_WEBHOOK_URL 之外追加）
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+}
+
+impl Schedule {
+    /// 实际生效的调度类型
+    pub fn effective_kind(&self) -> ScheduleKind {
+        self.kind
+            .clone()
+            .unwrap_or_else(|| ScheduleKind::Cron(self.cron.clone()))
+    }
+}
+
+impl ServiceManifest {
+    /// 为缺少 ID 或 ID 重复的调度条目生成新 ID，返回是否有改动
+    pub fn assign_schedule_ids(&mut self) -> bool {
+        let mut seen = std::collections::HashSet::new();
+        let mut changed = false;
+        for schedule in &mut self.schedules {
+            if schedule.id.is_empty() || !seen.insert(schedule.id.clone()) {
+                schedule.id = uuid::Uuid::new_v4().simple().to_string()[..8].to_string();
+                seen.insert(schedule.id.clone());
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+/// 接受 null、单个对象或数组
+fn deserialize_schedules<'de, D>(deserializer: D) -> Result<Vec<Schedule>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        Many(Vec<Schedule>),
+        One(Schedule),
+    }
+
+    Ok(match Option::<OneOrMany>::deserialize(deserializer)? {
+        Some(OneOrMany::Many(list)) => list,
+        Some(OneOrMany::One(schedule)) => vec![schedule],
+        None => vec![],
+    })
+}
+
+fn default_schema_version() -> u32 {
+    MANIFEST_SCHEMA_VERSION
 }
 
 fn default_clear_log_on_start() -> bool {
@@ -130,3 +352,11 @@ fn default_clear_log_on_start() -> bool {
 fn default_pty_rows() -> u16 {
     300
 }
+
+fn default_tty() -> bool {
+    true
+}
+
+fn default_start_grace_ms() -> u64 {
+    300
+}
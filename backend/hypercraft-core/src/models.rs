@@ -1,6 +1,9 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 
+use crate::manifest::{ScheduleAction, ServiceManifest};
+
 /// Minimal listing info for a service.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServiceSummary {
@@ -13,25 +16,149 @@ pub struct ServiceSummary {
     pub group: Option<String>,
     #[serde(default)]
     pub order: i32,
+    /// Creation time recorded in the manifest.
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    /// When the current run started; `None` unless running.
+    #[serde(default)]
+    pub started_at: Option<DateTime<Utc>>,
 }
 
 /// Runtime state enumeration.
 #[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum ServiceState {
     Running,
     Stopped,
+    #[default]
     Unknown,
 }
 
+/// How a stop request ended the process.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StopOutcome {
+    /// Process exited on its own after the shutdown command or the manifest's `kill_signal`.
+    Graceful,
+    /// Shutdown (or the `kill_signal` grace period) timed out and the process tree was
+    /// force-killed.
+    Forced,
+}
+
+/// Why a service changed state.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StateChangeReason {
+    /// Started by a user, schedule or dependency.
+    Started,
+    /// Started again by the auto-restart supervisor.
+    AutoRestart,
+    /// Stopped on request (shutdown command or kill).
+    Stopped,
+    /// Exited on its own with status 0.
+    Exited,
+    /// Exited on its own with a non-zero status.
+    Crashed,
+}
+
+/// Lifecycle event broadcast by `ServiceManager`.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServiceEvent {
+    StateChanged {
+        id: String,
+        state: ServiceState,
+        reason: StateChangeReason,
+        #[serde(default)]
+        pid: Option<u32>,
+        #[serde(default)]
+        exit_code: Option<i32>,
+        at: DateTime<Utc>,
+    },
+    /// Health check verdict flipped for a running service.
+    HealthChanged {
+        id: String,
+        healthy: bool,
+        at: DateTime<Utc>,
+    },
+    /// A schedule entry fired (on time or as a catch-up run), before its action runs.
+    ScheduleFired {
+        id: String,
+        schedule_id: String,
+        action: ScheduleAction,
+        at: DateTime<Utc>,
+    },
+}
+
+impl ServiceEvent {
+    /// Id of the service the event is about.
+    pub fn service_id(&self) -> &str {
+        match self {
+            ServiceEvent::StateChanged { id, .. }
+            | ServiceEvent::HealthChanged { id, .. }
+            | ServiceEvent::ScheduleFired { id, .. } => id,
+        }
+    }
+}
+
 /// Detailed status for a service.
 #[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ServiceStatus {
     pub state: ServiceState,
     pub pid: Option<u32>,
     pub uptime_ms: Option<u64>,
+    /// HTTP health probe result while running; `None` when no probe is configured
+    /// or no verdict has been reached yet.
+    #[serde(default)]
+    pub healthy: Option<bool>,
+    /// Set by `stop` when a shutdown timeout is configured.
+    #[serde(default)]
+    pub stop_outcome: Option<StopOutcome>,
+    /// Exit code of the last run (only reported while stopped).
+    #[serde(default)]
+    pub last_exit_code: Option<i32>,
+    /// Signal that terminated the last run, if any.
+    #[serde(default)]
+    pub last_exit_signal: Option<String>,
+    /// When the last run exited.
+    #[serde(default)]
+    pub last_exit_at: Option<DateTime<Utc>>,
+}
+
+impl ServiceStatus {
+    /// Start time of the current run, derived from `uptime_ms` while running.
+    pub fn started_at(&self) -> Option<DateTime<Utc>> {
+        if self.state != ServiceState::Running {
+            return None;
+        }
+        let uptime = chrono::Duration::milliseconds(i64::try_from(self.uptime_ms?).ok()?);
+        Some(Utc::now() - uptime)
+    }
+}
+
+/// Lifecycle counters persisted per service; they survive manager restarts.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LifecycleStats {
+    /// Successful spawns, including auto-restarts.
+    #[serde(default)]
+    pub total_starts: u64,
+    /// Runs that exited on their own with a non-zero status.
+    #[serde(default)]
+    pub total_crashes: u64,
+    /// Starts issued by the auto-restart supervisor.
+    #[serde(default)]
+    pub auto_restarts: u64,
+    #[serde(default)]
+    pub last_start_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_crash_at: Option<DateTime<Utc>>,
+    /// Total run time of finished runs, in milliseconds.
+    #[serde(default)]
+    pub cumulative_uptime_ms: u64,
 }
 
 /// Service group for organizing services.
@@ -43,4 +170,334 @@ pub struct ServiceGroup {
     pub order: i32,
     #[serde(default)]
     pub color: Option<String>,
+    /// Parent group id; `None` for top-level groups.
+    #[serde(default)]
+    pub parent: Option<String>,
+}
+
+/// What happens to child groups when their parent is deleted.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChildGroupAction {
+    /// Move children up to the deleted group's parent.
+    Reparent,
+    /// Make children top-level groups.
+    Orphan,
+}
+
+/// Lifecycle action applied to every service in a group.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupAction {
+    Start,
+    Stop,
+    Restart,
+}
+
+/// Outcome of a group action for one service.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupActionResult {
+    pub id: String,
+    pub ok: bool,
+    pub status: Option<ServiceStatus>,
+    pub error: Option<String>,
+}
+
+/// Sort key for service listings.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ServiceSort {
+    /// Manual ordering (`order`, then id).
+    #[default]
+    Order,
+    Name,
+    /// Running first, then by name.
+    State,
+}
+
+/// Filters and paging for service listings; every field is optional.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ServiceListQuery {
+    /// Exact group id; an empty string matches ungrouped services.
+    pub group: Option<String>,
+    pub state: Option<ServiceState>,
+    pub tag: Option<String>,
+    /// Case-insensitive substring of the name or id.
+    pub q: Option<String>,
+    #[serde(default)]
+    pub sort: ServiceSort,
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+impl ServiceListQuery {
+    /// Whether a service passes the filters (paging aside).
+    pub fn matches(&self, service: &ServiceSummary) -> bool {
+        if let Some(group) = &self.group {
+            let matched = match service.group.as_deref() {
+                Some(g) => g == group,
+                None => group.is_empty(),
+            };
+            if !matched {
+                return false;
+            }
+        }
+        if self.state.as_ref().is_some_and(|s| *s != service.state) {
+            return false;
+        }
+        if let Some(tag) = &self.tag {
+            if !service.tags.iter().any(|t| t == tag) {
+                return false;
+            }
+        }
+        if let Some(q) = self.q.as_deref().map(str::to_lowercase) {
+            if !service.name.to_lowercase().contains(&q) && !service.id.to_lowercase().contains(&q)
+            {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Filter, sort and page `services`; returns the filtered total and the page.
+    pub fn apply(&self, services: Vec<ServiceSummary>) -> (usize, Vec<ServiceSummary>) {
+        let mut filtered: Vec<ServiceSummary> =
+            services.into_iter().filter(|s| self.matches(s)).collect();
+        match self.sort {
+            ServiceSort::Order => {
+                filtered.sort_by(|a, b| a.order.cmp(&b.order).then_with(|| a.id.cmp(&b.id)))
+            }
+            ServiceSort::Name => filtered.sort_by(|a, b| {
+                a.name
+                    .to_lowercase()
+                    .cmp(&b.name.to_lowercase())
+                    .then_with(|| a.id.cmp(&b.id))
+            }),
+            ServiceSort::State => filtered.sort_by(|a, b| {
+                state_rank(&a.state)
+                    .cmp(&state_rank(&b.state))
+                    .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+                    .then_with(|| a.id.cmp(&b.id))
+            }),
+        }
+        let total = filtered.len();
+        let page = filtered
+            .into_iter()
+            .skip(self.offset.unwrap_or(0))
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect();
+        (total, page)
+    }
+}
+
+fn state_rank(state: &ServiceState) -> u8 {
+    match state {
+        ServiceState::Running => 0,
+        ServiceState::Stopped => 1,
+        ServiceState::Unknown => 2,
+    }
+}
+
+/// Format version written into exported bundles.
+pub const CONFIG_BUNDLE_VERSION: u32 = 1;
+
+/// Portable configuration: service manifests (with their schedules) and groups.
+///
+/// Users, API keys and secrets are deliberately left out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigBundle {
+    pub version: u32,
+    #[serde(default)]
+    pub exported_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub groups: Vec<ServiceGroup>,
+    #[serde(default)]
+    pub services: Vec<ServiceManifest>,
+}
+
+/// What happened to one entry during an import.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportOutcome {
+    Created,
+    Updated,
+    /// Already present and `overwrite` was not requested.
+    Skipped,
+    Failed,
+}
+
+/// Per-entry import result.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportItemResult {
+    pub id: String,
+    pub outcome: ImportOutcome,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// A soft-deleted service kept under `<data_dir>/archive` until restored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedService {
+    /// Archive entry name (`<id>-<timestamp>`); pass it to restore the service.
+    pub archive_id: String,
+    pub service_id: String,
+    pub name: String,
+    pub archived_at: DateTime<Utc>,
+}
+
+/// Result of `ServiceManager::import_config`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub groups: Vec<ImportItemResult>,
+    pub services: Vec<ImportItemResult>,
+}
+
+/// One problem found while validating a manifest without creating it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationProblem {
+    /// Manifest field the problem refers to (e.g. `id`, `command`, `schedules[0]`).
+    pub field: String,
+    pub message: String,
+}
+
+/// Result of `ServiceManager::validate_manifest`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ManifestValidation {
+    pub valid: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub problems: Vec<ValidationProblem>,
+}
+
+/// Readiness of one subsystem in a [`HealthReport`].
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubsystemHealth {
+    pub name: String,
+    pub healthy: bool,
+    /// Why the check failed; absent when healthy.
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl SubsystemHealth {
+    /// Turn the outcome of a check into a subsystem entry.
+    pub fn from_result(name: impl Into<String>, result: crate::Result<()>) -> Self {
+        Self {
+            name: name.into(),
+            healthy: result.is_ok(),
+            error: result.err().map(|e| e.to_string()),
+        }
+    }
+}
+
+/// Result of `ServiceManager::health_report`, extended by callers with their own checks.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub subsystems: Vec<SubsystemHealth>,
+}
+
+impl HealthReport {
+    pub fn push(&mut self, check: SubsystemHealth) {
+        self.subsystems.push(check);
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.subsystems.iter().all(|s| s.healthy)
+    }
+
+    /// Names of the subsystems whose check failed.
+    pub fn failing(&self) -> Vec<&str> {
+        self.subsystems
+            .iter()
+            .filter(|s| !s.healthy)
+            .map(|s| s.name.as_str())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(id: &str, state: ServiceState, group: Option<&str>, order: i32) -> ServiceSummary {
+        ServiceSummary {
+            id: id.into(),
+            name: id.to_uppercase(),
+            state,
+            tags: vec![format!("tag-{}", id)],
+            group: group.map(str::to_string),
+            order,
+            created_at: None,
+            started_at: None,
+        }
+    }
+
+    #[test]
+    fn summary_timestamps_are_optional_and_derived() {
+        let old: ServiceSummary =
+            serde_json::from_str(r#"{"id":"a","name":"A","state":"running"}"#).unwrap();
+        assert!(old.created_at.is_none() && old.started_at.is_none());
+
+        let mut status = ServiceStatus {
+            state: ServiceState::Running,
+            uptime_ms: Some(60_000),
+            ..Default::default()
+        };
+        let started = status.started_at().unwrap();
+        let elapsed = Utc::now() - started;
+        assert!(elapsed >= chrono::Duration::seconds(60));
+        assert!(elapsed < chrono::Duration::seconds(70));
+
+        status.state = ServiceState::Stopped;
+        assert!(status.started_at().is_none());
+    }
+
+    #[test]
+    fn list_query_filters_sorts_and_pages() {
+        let services = vec![
+            summary("web", ServiceState::Running, Some("g1"), 2),
+            summary("db", ServiceState::Stopped, Some("g1"), 1),
+            summary("cache", ServiceState::Running, None, 3),
+        ];
+
+        let (total, page) = ServiceListQuery::default().apply(services.clone());
+        assert_eq!(total, 3);
+        let ids: Vec<_> = page.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["db", "web", "cache"]);
+
+        let query = ServiceListQuery {
+            state: Some(ServiceState::Running),
+            sort: ServiceSort::Name,
+            ..Default::default()
+        };
+        let (total, page) = query.apply(services.clone());
+        assert_eq!(total, 2);
+        assert_eq!(page[0].id, "cache");
+
+        let query = ServiceListQuery {
+            group: Some(String::new()),
+            ..Default::default()
+        };
+        assert_eq!(query.apply(services.clone()).0, 1);
+
+        let query = ServiceListQuery {
+            q: Some("EB".into()),
+            tag: Some("tag-web".into()),
+            ..Default::default()
+        };
+        assert_eq!(query.apply(services.clone()).1[0].id, "web");
+
+        let query = ServiceListQuery {
+            sort: ServiceSort::State,
+            offset: Some(1),
+            limit: Some(1),
+            ..Default::default()
+        };
+        let (total, page) = query.apply(services);
+        assert_eq!(total, 3);
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].id, "web");
+    }
 }
@@ -66,23 +66,81 @@ impl UserManager {
         &self,
         req: CreateApiKeyRequest,
         created_by: &str,
+    ) -> Result<CreateApiKeyResponse> {
+        self.insert_api_key(&req.name, req.scopes, req.expires_at, created_by, None)
+    }
+
+    /// 为用户创建个人 API Key：以该用户身份鉴权，明文只返回一次且不可再查看
+    #[instrument(skip(self, req))]
+    pub async fn create_user_api_key(
+        &self,
+        user_id: &str,
+        req: CreateUserApiKeyRequest,
+        created_by: &str,
+    ) -> Result<CreateApiKeyResponse> {
+        // 确认用户存在
+        self.get_user(user_id).await?;
+        let scopes = if req.scopes.is_empty() {
+            api_key_scopes::ALL.iter().map(|s| s.to_string()).collect()
+        } else {
+            req.scopes
+        };
+        self.insert_api_key(
+            &req.name,
+            scopes,
+            req.expires_at,
+            created_by,
+            Some(user_id.to_string()),
+        )
+    }
+
+    /// 列出用户的个人 API Key（含已撤销）
+    pub async fn list_user_api_keys(&self, user_id: &str) -> Result<Vec<ApiKey>> {
+        let mut keys = self.list_api_keys().await?;
+        keys.retain(|k| k.owner_id.as_deref() == Some(user_id));
+        Ok(keys)
+    }
+
+    /// 撤销用户的个人 API Key；不属于该用户时按不存在处理
+    #[instrument(skip(self))]
+    pub async fn revoke_user_api_key(&self, user_id: &str, id: &str) -> Result<ApiKey> {
+        let key = self.get_api_key(id).await?;
+        if key.owner_id.as_deref() != Some(user_id) {
+            return Err(ServiceError::NotFound(format!("api_key:{}", id)));
+        }
+        self.revoke_api_key(id).await
+    }
+
+    fn insert_api_key(
+        &self,
+        name: &str,
+        scopes: Vec<String>,
+        expires_at: Option<chrono::DateTime<Utc>>,
+        created_by: &str,
+        owner_id: Option<String>,
     ) -> Result<CreateApiKeyResponse> {
         self.ensure_api_key_dirs()?;
 
-        let name = req.name.trim().to_string();
+        let name = name.trim().to_string();
         if name.is_empty() {
-            return Err(ServiceError::Other("name is required".into()));
+            return Err(ServiceError::InvalidInput("name is required".into()));
         }
-        if req.scopes.is_empty() {
-            return Err(ServiceError::Other("scopes must not be empty".into()));
+        if scopes.is_empty() {
+            return Err(ServiceError::InvalidInput(
+                "scopes must not be empty".into(),
+            ));
         }
-        api_key_scopes::validate(&req.scopes)
-            .map_err(|e| ServiceError::Other(e))?;
+        api_key_scopes::validate(&scopes).map_err(ServiceError::InvalidInput)?;
 
         let id = uuid::Uuid::new_v4().to_string();
         let secret = Self::generate_raw_api_key(&id);
         let key_hash = Self::hash_api_key_raw(&secret);
-        let encrypted_secret = Some(self.encrypt_api_key_secret(&secret)?);
+        // 个人密钥只保存哈希
+        let encrypted_secret = if owner_id.is_some() {
+            None
+        } else {
+            Some(self.encrypt_api_key_secret(&secret)?)
+        };
         // 展示用前缀：前缀 + id 前 8 位
         let key_prefix = format!(
             "{}{}",
@@ -99,12 +157,13 @@ impl UserManager {
             encrypted_secret,
             // API Key 不再做服务白名单，落盘恒为空
             service_ids: vec![],
-            scopes: req.scopes,
+            scopes,
             created_by: created_by.to_string(),
+            owner_id,
             created_at: now,
             last_used_at: None,
             revoked_at: None,
-            expires_at: req.expires_at,
+            expires_at,
         };
 
         self.persist_api_key(&key)?;
@@ -117,10 +176,13 @@ impl UserManager {
     /// 解密并返回完整明文（管理员随时可查看）
     pub async fn reveal_api_key_secret(&self, id: &str) -> Result<ApiKeySecretResponse> {
         let key = self.get_api_key(id).await?;
+        if key.owner_id.is_some() {
+            return Err(ServiceError::Conflict(
+                "个人密钥不保存明文，请重新生成".into(),
+            ));
+        }
         let encrypted = key.encrypted_secret.as_deref().ok_or_else(|| {
-            ServiceError::Other(
-                "此密钥创建于旧版本，无法查看明文，请重新生成".into(),
-            )
+            ServiceError::Conflict("此密钥创建于旧版本，无法查看明文，请重新生成".into())
         })?;
         let secret = self.decrypt_api_key_secret(encrypted)?;
         Ok(ApiKeySecretResponse {
@@ -161,7 +223,7 @@ impl UserManager {
                 }
             }
         }
-        keys.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        keys.sort_by_key(|k| std::cmp::Reverse(k.created_at));
         Ok(keys)
     }
 
@@ -170,21 +232,23 @@ impl UserManager {
     pub async fn update_api_key(&self, id: &str, req: UpdateApiKeyRequest) -> Result<ApiKey> {
         let mut key = self.get_api_key(id).await?;
         if key.revoked_at.is_some() {
-            return Err(ServiceError::Other("api key already revoked".into()));
+            return Err(ServiceError::Conflict("api key already revoked".into()));
         }
 
         if let Some(name) = req.name {
             let name = name.trim().to_string();
             if name.is_empty() {
-                return Err(ServiceError::Other("name is required".into()));
+                return Err(ServiceError::InvalidInput("name is required".into()));
             }
             key.name = name;
         }
         if let Some(scopes) = req.scopes {
             if scopes.is_empty() {
-                return Err(ServiceError::Other("scopes must not be empty".into()));
+                return Err(ServiceError::InvalidInput(
+                    "scopes must not be empty".into(),
+                ));
             }
-            api_key_scopes::validate(&scopes).map_err(ServiceError::Other)?;
+            api_key_scopes::validate(&scopes).map_err(ServiceError::InvalidInput)?;
             key.scopes = scopes;
         }
         if let Some(expires_at) = req.expires_at {
@@ -200,12 +264,16 @@ impl UserManager {
     pub async fn rotate_api_key_secret(&self, id: &str) -> Result<CreateApiKeyResponse> {
         let mut key = self.get_api_key(id).await?;
         if key.revoked_at.is_some() {
-            return Err(ServiceError::Other("api key already revoked".into()));
+            return Err(ServiceError::Conflict("api key already revoked".into()));
         }
 
         let secret = Self::generate_raw_api_key(&key.id);
         key.key_hash = Self::hash_api_key_raw(&secret);
-        key.encrypted_secret = Some(self.encrypt_api_key_secret(&secret)?);
+        key.encrypted_secret = if key.owner_id.is_some() {
+            None
+        } else {
+            Some(self.encrypt_api_key_secret(&secret)?)
+        };
         key.key_prefix = format!(
             "{}{}",
             API_KEY_RAW_PREFIX,
@@ -264,6 +332,35 @@ impl UserManager {
         }
 
         let now = Utc::now().timestamp();
+        // API Key 本身无 JWT exp；claims.exp 填远期占位
+        let exp = key
+            .expires_at
+            .map(|t| t.timestamp())
+            .unwrap_or(now + 10 * 365 * 24 * 3600);
+
+        // 个人密钥：按所属用户的当前记录合成 User claims，权限随用户变更即时生效
+        if let Some(owner_id) = key.owner_id.as_deref() {
+            let user = self.get_user(owner_id).await.map_err(|_| {
+                ServiceError::Unauthorized("api key owner not found".into())
+            })?;
+            let claims = TokenClaims {
+                sub: user.id.clone(),
+                username: user.username.clone(),
+                iss: Some(self.jwt_issuer.clone()),
+                aud: Some(self.jwt_audience.clone()),
+                token_type: TokenType::User,
+                service_ids: user.service_ids,
+                is_admin: user.is_admin,
+                token_version: user.token_version,
+                refresh_nonce: None,
+                service_id: None,
+                session_id: None,
+                exp,
+                iat: now,
+            };
+            return Ok((claims, key.scopes));
+        }
+
         let claims = TokenClaims {
             sub: format!("apikey:{}", key.id),
             username: key.name.clone(),
@@ -276,11 +373,8 @@ impl UserManager {
             token_version: 0,
             refresh_nonce: None,
             service_id: None,
-            // API Key 本身无 JWT exp；claims.exp 填远期占位
-            exp: key
-                .expires_at
-                .map(|t| t.timestamp())
-                .unwrap_or(now + 10 * 365 * 24 * 3600),
+            session_id: None,
+            exp,
             iat: now,
         };
 
@@ -299,3 +393,57 @@ fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
     }
     diff == 0
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn user_api_key_authenticates_as_owner() {
+        let dir = TempDir::new().unwrap();
+        let users = UserManager::new(dir.path(), "test-secret".into());
+        let user = users
+            .create_user(CreateUserRequest {
+                username: "ci".into(),
+                password: "Str0ng-password!".into(),
+                service_ids: vec![ServicePermission::new("svc", ServiceAccessLevel::Read)],
+            })
+            .await
+            .unwrap();
+
+        let created = users
+            .create_user_api_key(
+                &user.id,
+                CreateUserApiKeyRequest {
+                    name: "deploy".into(),
+                    scopes: vec![],
+                    expires_at: None,
+                },
+                &user.id,
+            )
+            .await
+            .unwrap();
+        assert!(created.secret.starts_with(API_KEY_RAW_PREFIX));
+        assert_eq!(created.key.scopes.len(), api_key_scopes::ALL.len());
+
+        let (claims, _) = users.verify_api_key(&created.secret).await.unwrap();
+        assert_eq!(claims.token_type, TokenType::User);
+        assert_eq!(claims.sub, user.id);
+        assert_eq!(claims.service_ids[0].level, ServiceAccessLevel::Read);
+
+        // 明文不落盘，也不能再次查看
+        assert!(users.reveal_api_key_secret(&created.key.id).await.is_err());
+        assert_eq!(users.list_user_api_keys(&user.id).await.unwrap().len(), 1);
+
+        assert!(users
+            .revoke_user_api_key("someone-else", &created.key.id)
+            .await
+            .is_err());
+        users
+            .revoke_user_api_key(&user.id, &created.key.id)
+            .await
+            .unwrap();
+        assert!(users.verify_api_key(&created.secret).await.is_err());
+    }
+}
@@ -2,12 +2,21 @@
 
 use super::crypto::verify_password;
 use super::models::*;
+use super::session::REFRESH_REUSE_GRACE_SECS;
 use super::UserManager;
 use crate::error::{Result, ServiceError};
 use chrono::{Duration, Utc};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use tracing::{info, instrument, warn};
 
+/// `nonce` 是否为该会话刚在宽限期内轮换掉的上一个 nonce
+fn within_reuse_grace(session: &UserSession, nonce: &str) -> bool {
+    session.previous_nonce.as_deref() == Some(nonce)
+        && session
+            .rotated_at
+            .is_some_and(|at| Utc::now() - at < Duration::seconds(REFRESH_REUSE_GRACE_SECS))
+}
+
 /// 虚拟 bcrypt 哈希（cost=12），用户不存在时仍执行同等耗时校验，降低时序枚举面。
 /// 对应口令：timing-equalization-dummy
 const DUMMY_PASSWORD_HASH: &str =
@@ -15,8 +24,14 @@ const DUMMY_PASSWORD_HASH: &str =
 
 impl UserManager {
     /// 用户登录
-    #[instrument(skip(self, password, totp_code))]
-    pub async fn login(&self, username: &str, password: &str, totp_code: Option<&str>) -> Result<AuthToken> {
+    #[instrument(skip(self, password, totp_code, meta))]
+    pub async fn login(
+        &self,
+        username: &str,
+        password: &str,
+        totp_code: Option<&str>,
+        meta: &SessionMeta,
+    ) -> Result<AuthToken> {
         let user = self.find_by_username(username).await?;
 
         // 无论用户是否存在都走 bcrypt，避免通过响应耗时枚举用户名
@@ -26,7 +41,7 @@ impl UserManager {
             .unwrap_or(DUMMY_PASSWORD_HASH);
         let valid = verify_password(password, password_hash).await?;
 
-        let Some(user) = user else {
+        let Some(mut user) = user else {
             warn!(username = %username, "登录失败：用户不存在");
             return Err(ServiceError::Unauthorized("用户名或密码错误".into()));
         };
@@ -37,45 +52,116 @@ impl UserManager {
         }
 
         // 检查是否启用 2FA
-        if let Some(totp_cfg) = &user.totp_config {
-            if totp_cfg.enabled {
-                let code = totp_code.ok_or_else(|| {
-                    ServiceError::TwoFactorRequired("需要双因素认证代码".into())
-                })?;
-
-                if !self.verify_totp(&user, code).await? {
-                    warn!(username = %username, "登录失败：双因素认证代码无效");
-                    return Err(ServiceError::Unauthorized("双因素认证代码无效".into()));
-                }
+        if user.totp_config.as_ref().is_some_and(|cfg| cfg.enabled) {
+            let code = totp_code
+                .ok_or_else(|| ServiceError::TwoFactorRequired("需要双因素认证代码".into()))?;
+
+            if !self.verify_totp(&mut user, code).await? {
+                warn!(username = %username, "登录失败：双因素认证代码无效");
+                return Err(ServiceError::Unauthorized("双因素认证代码无效".into()));
             }
         }
 
         info!(user_id = %user.id, username = %username, "user logged in");
-        self.issue_tokens(user, true)
+        let session = self.open_session(&mut user, meta);
+        self.issue_tokens(user, session)
     }
 
-    /// 刷新 token
-    #[instrument(skip(self, refresh_token))]
-    pub async fn refresh(&self, refresh_token: &str) -> Result<AuthToken> {
-        // 验证 refresh token
-        let claims = self.verify_token(refresh_token).await?;
+    /// 刷新 token（轮换所属会话的 refresh nonce）
+    #[instrument(skip(self, refresh_token, meta))]
+    pub async fn refresh(&self, refresh_token: &str, meta: &SessionMeta) -> Result<AuthToken> {
+        // 验证 refresh token；失败时先判断是否为已轮换 token 的重放
+        let claims = match self.verify_token(refresh_token).await {
+            Ok(claims) => claims,
+            Err(e) => {
+                self.detect_refresh_reuse(refresh_token).await?;
+                return Err(e);
+            }
+        };
 
         if claims.token_type != TokenType::Refresh {
             return Err(ServiceError::Unauthorized("token 类型错误".into()));
         }
 
         // 获取最新用户信息（若密码/权限已变更会触发 token_version 不匹配）
-        let user = self.get_user(&claims.sub).await?;
+        let mut user = self.get_user(&claims.sub).await?;
+        let session = match claims.session_id.as_deref() {
+            Some(session_id) => self.renew_session(&mut user, session_id, meta)?,
+            // 旧版未绑定会话的 refresh token：作废旧 nonce 并迁移为新会话
+            None => {
+                user.refresh_nonce = uuid::Uuid::new_v4().to_string();
+                self.open_session(&mut user, meta)
+            }
+        };
 
-        info!(user_id = %user.id, "token refreshed");
-        self.issue_tokens(user, true)
+        info!(user_id = %user.id, session_id = %session.id, "token refreshed");
+        self.issue_tokens(user, session)
     }
 
-    /// 签发 DevToken JWT（用于 DevToken 登录后获取 JWT）
+    /// 重放检测：签名与 token_version 仍有效、所属会话也还在，但 nonce 已被轮换掉的
+    /// refresh token 说明它被使用过不止一次（可能已泄露）。此时递增 token_version 并清空
+    /// 全部会话，强制所有设备重新登录，返回 [`ServiceError::RefreshTokenReused`]。
+    ///
+    /// 其它失败（过期、已撤销、会话已注销等）返回 Ok，由调用方沿用原错误；
+    /// 刚被轮换掉的 nonce 在 [`REFRESH_REUSE_GRACE_SECS`] 内出示也返回 Ok，
+    /// 避免多个标签页并发刷新被当成重放。
+    async fn detect_refresh_reuse(&self, refresh_token: &str) -> Result<()> {
+        let Ok(claims) = self.decode_claims(refresh_token) else {
+            return Ok(());
+        };
+        let Some(nonce) = claims.refresh_nonce.as_deref() else {
+            return Ok(());
+        };
+        if claims.token_type != TokenType::Refresh {
+            return Ok(());
+        }
+        let Ok(user) = self.get_user(&claims.sub).await else {
+            return Ok(());
+        };
+        if claims.token_version != user.token_version {
+            return Ok(());
+        }
+        let current_nonce = match claims.session_id.as_deref() {
+            Some(session_id) => match user.sessions.iter().find(|s| s.id == session_id) {
+                Some(session) if within_reuse_grace(session, nonce) => return Ok(()),
+                Some(session) => &session.refresh_nonce,
+                // 会话已登出或被撤销，不算重放
+                None => return Ok(()),
+            },
+            None => &user.refresh_nonce,
+        };
+        if nonce == current_nonce {
+            return Ok(());
+        }
+
+        let user = self.revoke_all_tokens(&user.id).await?;
+        warn!(
+            user_id = %user.id,
+            session_id = claims.session_id.as_deref().unwrap_or("-"),
+            "检测到 refresh token 重放，已撤销该用户的全部会话"
+        );
+        Err(ServiceError::RefreshTokenReused(user.id))
+    }
+
+    /// 撤销用户的全部 token（递增 token_version 并轮换 refresh nonce）
     #[instrument(skip(self))]
-    pub async fn issue_dev_token(&self) -> Result<AuthToken> {
+    pub async fn revoke_all_tokens(&self, user_id: &str) -> Result<User> {
+        let mut user = self.get_user(user_id).await?;
+        user.token_version = user.token_version.saturating_add(1);
+        Self::rotate_refresh_nonce(&mut user);
+        user.updated_at = Some(Utc::now());
+
+        self.persist_user(&user)?;
+
+        info!(user_id = %user.id, "all tokens revoked");
+        Ok(user)
+    }
+
+    /// 签发 DevToken JWT（用于 DevToken 登录后获取 JWT）
+    #[instrument(skip(self, meta))]
+    pub async fn issue_dev_token(&self, meta: &SessionMeta) -> Result<AuthToken> {
         // 确保 __devtoken__ 虚拟用户存在
-        let dev_user = match self.get_user("__devtoken__").await {
+        let mut dev_user = match self.get_user("__devtoken__").await {
             Ok(user) => user,
             Err(ServiceError::NotFound(_)) => {
                 // 自动创建虚拟用户
@@ -84,19 +170,17 @@ impl UserManager {
             Err(e) => return Err(e),
         };
         // 复用 issue_tokens 逻辑
-        self.issue_tokens(dev_user, true)
+        let session = self.open_session(&mut dev_user, meta);
+        self.issue_tokens(dev_user, session)
     }
 
-    /// 生成 access token 和 refresh token
-    pub(super) fn issue_tokens(&self, mut user: User, rotate_refresh: bool) -> Result<AuthToken> {
+    /// 为会话生成 access token 和 refresh token（持久化会话变更）
+    pub(super) fn issue_tokens(&self, mut user: User, session: UserSession) -> Result<AuthToken> {
         let now = Utc::now();
         let access_exp = now + Duration::seconds(self.access_token_ttl);
         let refresh_exp = now + Duration::seconds(self.refresh_token_ttl);
-        if rotate_refresh || user.refresh_nonce.is_empty() {
-            Self::rotate_refresh_nonce(&mut user);
-            user.updated_at = Some(now);
-            self.persist_user(&user)?;
-        }
+        user.updated_at = Some(now);
+        self.persist_user(&user)?;
 
         let is_admin = user.is_admin || user.id == "__devtoken__";
         // Access token claims
@@ -111,6 +195,7 @@ impl UserManager {
             token_version: user.token_version,
             refresh_nonce: None,
             service_id: None,
+            session_id: Some(session.id.clone()),
             exp: access_exp.timestamp(),
             iat: now.timestamp(),
         };
@@ -125,8 +210,9 @@ impl UserManager {
             service_ids: vec![],
             is_admin,
             token_version: user.token_version,
-            refresh_nonce: Some(user.refresh_nonce.clone()),
+            refresh_nonce: Some(session.refresh_nonce.clone()),
             service_id: None,
+            session_id: Some(session.id),
             exp: refresh_exp.timestamp(),
             iat: now.timestamp(),
         };
@@ -173,6 +259,7 @@ impl UserManager {
             token_version: claims.token_version,
             refresh_nonce: None,
             service_id: Some(service_id.to_string()),
+            session_id: claims.session_id.clone(),
             exp: exp.timestamp(),
             iat: now.timestamp(),
         };
@@ -185,19 +272,23 @@ impl UserManager {
         .map_err(|e| ServiceError::Other(e.to_string()))
     }
 
-    /// 验证 JWT token
-    pub async fn verify_token(&self, token: &str) -> Result<TokenClaims> {
+    /// 校验签名、有效期、iss 与 aud 并解出 claims（不查询用户状态）
+    fn decode_claims(&self, token: &str) -> Result<TokenClaims> {
         let mut validation = Validation::default();
-        validation.set_audience(&[self.jwt_audience.clone()]);
-        validation.set_issuer(&[self.jwt_issuer.clone()]);
+        validation.set_audience(std::slice::from_ref(&self.jwt_audience));
+        validation.set_issuer(std::slice::from_ref(&self.jwt_issuer));
         let token_data = decode::<TokenClaims>(
             token,
             &DecodingKey::from_secret(self.jwt_secret.as_bytes()),
             &validation,
         )
         .map_err(|e| ServiceError::Unauthorized(format!("token 无效: {}", e)))?;
+        Ok(token_data.claims)
+    }
 
-        let mut claims = token_data.claims;
+    /// 验证 JWT token
+    pub async fn verify_token(&self, token: &str) -> Result<TokenClaims> {
+        let mut claims = self.decode_claims(token)?;
         let refresh_nonce = claims.refresh_nonce.clone();
 
         // 校验 token version 以支持撤销
@@ -206,7 +297,19 @@ impl UserManager {
             return Err(ServiceError::Unauthorized("token 已被撤销".into()));
         }
 
-        if claims.token_type == TokenType::Refresh {
+        if let Some(session_id) = claims.session_id.as_deref() {
+            // 会话被注销后，其签发的全部 token 立即失效
+            let session = user
+                .sessions
+                .iter()
+                .find(|s| s.id == session_id)
+                .ok_or_else(|| ServiceError::Unauthorized("会话已注销".into()))?;
+            if claims.token_type == TokenType::Refresh
+                && refresh_nonce.as_deref() != Some(session.refresh_nonce.as_str())
+            {
+                return Err(ServiceError::Unauthorized("refresh token 已被撤销".into()));
+            }
+        } else if claims.token_type == TokenType::Refresh {
             let nonce = refresh_nonce
                 .as_deref()
                 .ok_or_else(|| ServiceError::Unauthorized("refresh token 缺少 nonce".into()))?;
@@ -224,3 +327,89 @@ impl UserManager {
         Ok(claims)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn revoke_all_tokens_invalidates_outstanding_tokens() {
+        let dir = TempDir::new().unwrap();
+        let users = UserManager::new(dir.path(), "test-secret".into());
+        users
+            .create_user(CreateUserRequest {
+                username: "dave".into(),
+                password: "Str0ng-password!".into(),
+                service_ids: vec![],
+            })
+            .await
+            .unwrap();
+        let token = users
+            .login("dave", "Str0ng-password!", None, &SessionMeta::default())
+            .await
+            .unwrap();
+        let claims = users.verify_token(&token.access_token).await.unwrap();
+
+        users.revoke_all_tokens(&claims.sub).await.unwrap();
+
+        assert!(users.verify_token(&token.access_token).await.is_err());
+        assert!(users
+            .refresh(&token.refresh_token, &SessionMeta::default())
+            .await
+            .is_err());
+        users
+            .login("dave", "Str0ng-password!", None, &SessionMeta::default())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn reused_refresh_token_revokes_all_sessions() {
+        let dir = TempDir::new().unwrap();
+        let users = UserManager::new(dir.path(), "test-secret".into());
+        users
+            .create_user(CreateUserRequest {
+                username: "erin".into(),
+                password: "Str0ng-password!".into(),
+                service_ids: vec![],
+            })
+            .await
+            .unwrap();
+        let first = users
+            .login("erin", "Str0ng-password!", None, &SessionMeta::default())
+            .await
+            .unwrap();
+        let rotated = users
+            .refresh(&first.refresh_token, &SessionMeta::default())
+            .await
+            .unwrap();
+
+        // 宽限期内（如另一个标签页同时刷新）旧 token 只是失效，不触发全量撤销
+        let err = users
+            .refresh(&first.refresh_token, &SessionMeta::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::Unauthorized(_)));
+        assert!(users.verify_token(&rotated.access_token).await.is_ok());
+
+        let claims = users.verify_token(&rotated.access_token).await.unwrap();
+        let mut user = users.get_user(&claims.sub).await.unwrap();
+        user.sessions[0].rotated_at =
+            Some(Utc::now() - Duration::seconds(REFRESH_REUSE_GRACE_SECS + 1));
+        users.persist_user(&user).unwrap();
+
+        let err = users
+            .refresh(&first.refresh_token, &SessionMeta::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::RefreshTokenReused(_)));
+
+        assert!(users.verify_token(&rotated.access_token).await.is_err());
+        let err = users
+            .refresh(&rotated.refresh_token, &SessionMeta::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::Unauthorized(_)));
+    }
+}
@@ -1,6 +1,7 @@
-//! 对称加密工具（TOTP secret / API Key 明文）
+//! 对称加密工具（TOTP secret / API Key 明文 / 服务密钥）
 //!
-//! 使用 AES-256-GCM，密钥从 JWT secret 经 HKDF 派生
+//! 使用 AES-256-GCM，密钥从 JWT secret 经 HKDF 派生；
+//! 整条用户记录的加密（HC_MASTER_KEY）使用同样的算法，密钥从主密钥派生
 //!
 //! @author sky
 
@@ -14,56 +15,92 @@ use sha2::Sha256;
 use crate::error::{Result, ServiceError};
 use super::UserManager;
 
+/// 用户记录加密的 HKDF info
+const USER_RECORD_INFO: &[u8] = b"user-record-encryption";
+
+/// 用户名索引键的 HKDF info
+const USERNAME_INDEX_INFO: &[u8] = b"username-index-key";
+
+/// 从密钥材料派生加密密钥（使用 HKDF-SHA256）
+fn derive_key(secret: &[u8], info: &[u8]) -> [u8; 32] {
+    let hk = Hkdf::<Sha256>::new(None, secret);
+    let mut okm = [0u8; 32];
+    hk.expand(info, &mut okm).expect("HKDF expand failed");
+    okm
+}
+
+/// AES-256-GCM 加密，返回 base64(nonce || ciphertext)
+fn seal(key_bytes: [u8; 32], plaintext: &[u8]) -> Result<String> {
+    let cipher = Aes256Gcm::new(&key_bytes.into());
+
+    let mut nonce_bytes = [0u8; 12];
+    rand::thread_rng().fill_bytes(&mut nonce_bytes);
+    let nonce = Nonce::from_slice(&nonce_bytes);
+
+    let ciphertext = cipher
+        .encrypt(nonce, plaintext)
+        .map_err(|e| ServiceError::Other(format!("encryption failed: {}", e)))?;
+
+    let mut result = nonce_bytes.to_vec();
+    result.extend_from_slice(&ciphertext);
+    Ok(general_purpose::STANDARD.encode(result))
+}
+
+/// 解密 base64(nonce || ciphertext)
+fn open(key_bytes: [u8; 32], encrypted: &str) -> Result<Vec<u8>> {
+    let data = general_purpose::STANDARD
+        .decode(encrypted)
+        .map_err(|e| ServiceError::Other(format!("invalid base64: {}", e)))?;
+
+    if data.len() < 12 {
+        return Err(ServiceError::Other("invalid encrypted data".into()));
+    }
+
+    let (nonce, ciphertext) = data.split_at(12);
+    let cipher = Aes256Gcm::new(&key_bytes.into());
+
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|e| ServiceError::Other(format!("decryption failed: {}", e)))
+}
+
+/// 用主密钥加密整条用户记录（序列化后的 JSON）
+pub(super) fn seal_user_record(master_key: &str, record: &[u8]) -> Result<String> {
+    seal(derive_key(master_key.as_bytes(), USER_RECORD_INFO), record)
+}
+
+/// 用主密钥解密用户记录；密钥错误或数据被篡改时失败
+pub(super) fn open_user_record(master_key: &str, encrypted: &str) -> Result<Vec<u8>> {
+    let key = derive_key(master_key.as_bytes(), USER_RECORD_INFO);
+    open(key, encrypted)
+        .map_err(|_| ServiceError::Other("无法解密用户记录：HC_MASTER_KEY 错误或记录已损坏".into()))
+}
+
 impl UserManager {
     /// 从 JWT secret 派生加密密钥（使用 HKDF-SHA256）
     fn derive_encryption_key(&self, info: &[u8]) -> [u8; 32] {
-        let hk = Hkdf::<Sha256>::new(None, self.jwt_secret.as_bytes());
-        let mut okm = [0u8; 32];
-        hk.expand(info, &mut okm)
-            .expect("HKDF expand failed");
-        okm
+        derive_key(self.jwt_secret.as_bytes(), info)
     }
 
     /// AES-256-GCM 加密，返回 base64(nonce || ciphertext)
     fn encrypt_blob(&self, info: &[u8], plaintext: &str) -> Result<String> {
-        let key_bytes = self.derive_encryption_key(info);
-        let cipher = Aes256Gcm::new(&key_bytes.into());
-
-        let mut nonce_bytes = [0u8; 12];
-        rand::thread_rng().fill_bytes(&mut nonce_bytes);
-        let nonce = Nonce::from_slice(&nonce_bytes);
-
-        let ciphertext = cipher
-            .encrypt(nonce, plaintext.as_bytes())
-            .map_err(|e| ServiceError::Other(format!("encryption failed: {}", e)))?;
-
-        let mut result = nonce_bytes.to_vec();
-        result.extend_from_slice(&ciphertext);
-        Ok(general_purpose::STANDARD.encode(result))
+        seal(self.derive_encryption_key(info), plaintext.as_bytes())
     }
 
     /// 解密 base64(nonce || ciphertext)
     fn decrypt_blob(&self, info: &[u8], encrypted: &str) -> Result<String> {
-        let data = general_purpose::STANDARD
-            .decode(encrypted)
-            .map_err(|e| ServiceError::Other(format!("invalid base64: {}", e)))?;
-
-        if data.len() < 12 {
-            return Err(ServiceError::Other("invalid encrypted data".into()));
-        }
-
-        let (nonce, ciphertext) = data.split_at(12);
-        let key_bytes = self.derive_encryption_key(info);
-        let cipher = Aes256Gcm::new(&key_bytes.into());
-
-        let plaintext = cipher
-            .decrypt(Nonce::from_slice(nonce), ciphertext)
-            .map_err(|e| ServiceError::Other(format!("decryption failed: {}", e)))?;
-
+        let plaintext = open(self.derive_encryption_key(info), encrypted)?;
         String::from_utf8(plaintext)
             .map_err(|e| ServiceError::Other(format!("invalid utf8: {}", e)))
     }
 
+    /// 用户名索引键：HMAC-SHA256（HKDF-Extract）的十六进制，index.json 中不出现明文用户名
+    pub(super) fn username_index_key(&self, username: &str) -> String {
+        let key = self.derive_encryption_key(USERNAME_INDEX_INFO);
+        let (prk, _) = Hkdf::<Sha256>::extract(Some(&key), username.as_bytes());
+        prk.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
     /// 加密 TOTP secret
     pub fn encrypt_totp_secret(&self, secret: &str) -> Result<String> {
         self.encrypt_blob(b"totp-secret-encryption", secret)
@@ -83,4 +120,84 @@ impl UserManager {
     pub fn decrypt_api_key_secret(&self, encrypted: &str) -> Result<String> {
         self.decrypt_blob(b"api-key-secret-encryption", encrypted)
     }
+
+    /// 加密服务密钥（`${SECRET:key}` 引用的值）
+    pub fn encrypt_service_secret(&self, secret: &str) -> Result<String> {
+        self.encrypt_blob(b"service-secret-encryption", secret)
+    }
+
+    /// 解密服务密钥
+    pub fn decrypt_service_secret(&self, encrypted: &str) -> Result<String> {
+        self.decrypt_blob(b"service-secret-encryption", encrypted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user::CreateUserRequest;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn master_key_encrypts_user_records_and_rotates() {
+        let dir = TempDir::new().unwrap();
+        // 加密前写入的明文记录，启动检查时迁移为密文
+        let plain = UserManager::new(dir.path(), "test-secret".into());
+        let user = plain
+            .create_user(CreateUserRequest {
+                username: "frank".into(),
+                password: "Str0ng-password!".into(),
+                service_ids: vec![],
+            })
+            .await
+            .unwrap();
+        let path = dir.path().join("users").join(format!("{}.json", user.id));
+
+        let users = UserManager::new(dir.path(), "test-secret".into()).with_master_key("key-one");
+        assert_eq!(users.verify_master_key().unwrap(), 1);
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(!raw.contains("frank") && !raw.contains(&user.password_hash));
+        assert_eq!(users.get_user(&user.id).await.unwrap().username, "frank");
+        assert_eq!(users.verify_master_key().unwrap(), 0);
+
+        // 缺少或用错密钥时拒绝读取
+        assert!(plain.get_user(&user.id).await.is_err());
+        let wrong = UserManager::new(dir.path(), "test-secret".into()).with_master_key("nope");
+        assert!(wrong.verify_master_key().is_err());
+        let rotate = wrong.rotate_master_key(Some("nope"), Some("key-two"));
+        assert!(rotate.is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), raw);
+
+        let rotated = users.rotate_master_key(Some("key-one"), Some("key-two"));
+        assert_eq!(rotated.unwrap(), 1);
+        assert!(users.get_user(&user.id).await.is_err());
+        let rotated = UserManager::new(dir.path(), "test-secret".into()).with_master_key("key-two");
+        assert!(rotated.find_by_username("frank").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn username_index_does_not_store_plaintext_usernames() {
+        let dir = TempDir::new().unwrap();
+        let users = UserManager::new(dir.path(), "test-secret".into());
+        let user = users
+            .create_user(CreateUserRequest {
+                username: "grace".into(),
+                password: "Str0ng-password!".into(),
+                service_ids: vec![],
+            })
+            .await
+            .unwrap();
+        let index_path = dir.path().join("users").join("index.json");
+        let raw = std::fs::read_to_string(&index_path).unwrap();
+        assert!(!raw.contains("grace"));
+        assert!(raw.contains(&users.username_index_key("grace")));
+
+        // 旧版明文索引在读取时迁移
+        let legacy = serde_json::json!({ "grace": user.id });
+        std::fs::write(&index_path, legacy.to_string()).unwrap();
+        let found = users.find_by_username("grace").await.unwrap().unwrap();
+        assert_eq!(found.id, user.id);
+        let raw = std::fs::read_to_string(&index_path).unwrap();
+        assert!(!raw.contains("grace"));
+    }
 }
@@ -1,9 +1,12 @@
 //! 用户管理器：核心结构和用户 CRUD 操作
 
 use super::crypto::hash_password;
+use super::encryption::{open_user_record, seal_user_record};
 use super::models::*;
+use super::password::PasswordPolicy;
 use crate::error::{Result, ServiceError};
 use chrono::Utc;
+use serde::{Deserialize, Serialize};
 use serde_json;
 use std::collections::HashMap;
 use std::env;
@@ -29,6 +32,17 @@ pub struct UserManager {
     pub(super) access_token_ttl: i64,
     /// Refresh token 有效期（秒）
     pub(super) refresh_token_ttl: i64,
+    /// 密码强度策略
+    pub(super) password_policy: PasswordPolicy,
+    /// 用户记录主密钥（HC_MASTER_KEY）；设置后用户文件整体加密落盘
+    pub(super) master_key: Option<String>,
+}
+
+/// 加密落盘的用户记录：`{"encrypted": base64(nonce || ciphertext)}`
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EncryptedUserRecord {
+    encrypted: String,
 }
 
 // ============================================================================
@@ -56,6 +70,8 @@ impl UserManager {
             jwt_audience: DEFAULT_JWT_AUDIENCE.to_string(),
             access_token_ttl,
             refresh_token_ttl,
+            password_policy: PasswordPolicy::default(),
+            master_key: None,
         }
     }
 
@@ -77,6 +93,18 @@ impl UserManager {
         self
     }
 
+    /// 配置用户记录主密钥：之后写入的用户文件整体加密，读取加密记录必须使用同一密钥
+    pub fn with_master_key(mut self, key: impl Into<String>) -> Self {
+        self.master_key = Some(key.into());
+        self
+    }
+
+    /// 配置密码强度策略
+    pub fn with_password_policy(mut self, policy: PasswordPolicy) -> Self {
+        self.password_policy = policy;
+        self
+    }
+
     /// Access token 有效期（秒）
     pub fn access_token_ttl(&self) -> i64 {
         self.access_token_ttl
@@ -100,32 +128,103 @@ impl UserManager {
         }
     }
 
-    /// 轮换 refresh_nonce（用于撤销旧 token）
+    /// 轮换 refresh_nonce 并清空会话（用于撤销旧 token）
     pub(super) fn rotate_refresh_nonce(user: &mut User) {
         user.refresh_nonce = uuid::Uuid::new_v4().to_string();
+        user.sessions.clear();
     }
 
     /// 持久化用户数据
     pub(super) fn persist_user(&self, user: &User) -> Result<()> {
-        let data = serde_json::to_vec_pretty(user)?;
+        let data = Self::encode_user(user, self.master_key.as_deref())?;
         std::fs::write(self.user_path(&user.id), data)?;
         Ok(())
     }
 
+    /// 读取用户文件；加密记录缺少或用错主密钥时返回错误
+    pub(super) fn read_user_file(&self, path: &Path) -> Result<User> {
+        let data = std::fs::read(path)?;
+        Self::decode_user(&data, self.master_key.as_deref())
+    }
+
+    /// 序列化用户记录，给定主密钥时整体加密
+    fn encode_user(user: &User, master_key: Option<&str>) -> Result<Vec<u8>> {
+        let data = serde_json::to_vec_pretty(user)?;
+        match master_key {
+            Some(key) => Ok(serde_json::to_vec_pretty(&EncryptedUserRecord {
+                encrypted: seal_user_record(key, &data)?,
+            })?),
+            None => Ok(data),
+        }
+    }
+
+    /// 解析用户文件内容：明文记录直接解析（兼容加密前的旧数据），加密记录必须提供正确的主密钥
+    fn decode_user(data: &[u8], master_key: Option<&str>) -> Result<User> {
+        let Ok(record) = serde_json::from_slice::<EncryptedUserRecord>(data) else {
+            return Ok(serde_json::from_slice(data)?);
+        };
+        let key = master_key
+            .ok_or_else(|| ServiceError::Other("用户记录已加密，但未配置 HC_MASTER_KEY".into()))?;
+        let plaintext = open_user_record(key, &record.encrypted)?;
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+
+    /// 用户文件是否为加密记录
+    fn is_encrypted_record(data: &[u8]) -> bool {
+        serde_json::from_slice::<EncryptedUserRecord>(data).is_ok()
+    }
+
+    /// 全部用户记录文件（不含用户名索引）
+    fn user_files(&self) -> Result<Vec<PathBuf>> {
+        let dir = self.users_dir();
+        if !dir.exists() {
+            return Ok(vec![]);
+        }
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().map(|e| e == "json").unwrap_or(false)
+                && path.file_stem().map(|s| s != "index").unwrap_or(true)
+            {
+                files.push(path);
+            }
+        }
+        Ok(files)
+    }
+
     /// 用户名索引文件路径
     fn index_path(&self) -> PathBuf {
         self.users_dir().join("index.json")
     }
 
-    /// 加载用户名 -> ID 索引
+    /// 加载用户名键 -> ID 索引（键见 `username_index_key`）
+    ///
+    /// 旧版本以明文用户名为键，读取时换算成哈希键并写回
     fn load_username_index(&self) -> HashMap<String, String> {
         let path = self.index_path();
-        if let Ok(data) = fs::read(&path) {
-            if let Ok(map) = serde_json::from_slice::<HashMap<String, String>>(&data) {
-                return map;
-            }
+        let Ok(map) = fs::read(&path)
+            .map_err(ServiceError::from)
+            .and_then(|data| Ok(serde_json::from_slice::<HashMap<String, String>>(&data)?))
+        else {
+            return HashMap::new();
+        };
+        let is_hashed =
+            |k: &str| k.len() == 64 && k.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'));
+        if map.keys().all(|k| is_hashed(k)) {
+            return map;
         }
-        HashMap::new()
+        let index: HashMap<String, String> = map
+            .into_iter()
+            .map(|(k, id)| {
+                if is_hashed(&k) {
+                    (k, id)
+                } else {
+                    (self.username_index_key(&k), id)
+                }
+            })
+            .collect();
+        let _ = self.save_username_index(&index);
+        index
     }
 
     /// 保存用户名索引
@@ -142,6 +241,56 @@ impl UserManager {
         Ok(())
     }
 
+    /// 就绪检查：用户目录可读，且用户名索引（若存在）能被解析
+    pub fn check_store(&self) -> Result<()> {
+        std::fs::read_dir(self.users_dir())?;
+        match fs::read(self.index_path()) {
+            Ok(data) => {
+                serde_json::from_slice::<HashMap<String, String>>(&data)?;
+                Ok(())
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// 启动检查：逐个解密用户记录，主密钥错误时失败（fail closed）。
+    ///
+    /// 配置了主密钥时，顺带把仍为明文的旧记录加密写回，返回加密的条数；
+    /// 全部记录读取成功后才会写入，失败时不改动任何文件。
+    pub fn verify_master_key(&self) -> Result<usize> {
+        let key = self.master_key.as_deref();
+        let mut plaintext = Vec::new();
+        for path in self.user_files()? {
+            let data = std::fs::read(&path)?;
+            let user = Self::decode_user(&data, key)?;
+            if key.is_some() && !Self::is_encrypted_record(&data) {
+                plaintext.push((path, user));
+            }
+        }
+        for (path, user) in &plaintext {
+            std::fs::write(path, Self::encode_user(user, key)?)?;
+        }
+        Ok(plaintext.len())
+    }
+
+    /// 更换主密钥：用 `old` 解密全部用户记录，再用 `new` 重新加密写回，返回记录条数。
+    ///
+    /// `old` 为 None 表示现有记录是明文；`new` 为 None 则写回明文（撤销加密）。
+    /// 任一记录无法解密即失败且不写入任何文件；完成后需以 `new` 作为 HC_MASTER_KEY 启动。
+    pub fn rotate_master_key(&self, old: Option<&str>, new: Option<&str>) -> Result<usize> {
+        let mut users = Vec::new();
+        for path in self.user_files()? {
+            let data = std::fs::read(&path)?;
+            users.push((path, Self::decode_user(&data, old)?));
+        }
+        for (path, user) in &users {
+            std::fs::write(path, Self::encode_user(user, new)?)?;
+        }
+        info!(count = users.len(), "rotated user store master key");
+        Ok(users.len())
+    }
+
     /// 用户存储目录
     fn users_dir(&self) -> PathBuf {
         self.data_dir.join("users")
@@ -171,7 +320,7 @@ impl UserManager {
             )));
         }
 
-        Self::validate_password_strength(&req.password)?;
+        self.validate_password_strength(&req.password)?;
         let password_hash = hash_password(&req.password).await?;
 
         let now = Utc::now();
@@ -183,6 +332,7 @@ impl UserManager {
             is_admin: false,
             token_version: 0,
             refresh_nonce: String::new(),
+            sessions: vec![],
             totp_config: None,
             created_at: Some(now),
             updated_at: Some(now),
@@ -192,7 +342,7 @@ impl UserManager {
         // 保存
         self.persist_user(&user)?;
         let mut index = self.load_username_index();
-        index.insert(user.username.clone(), user.id.clone());
+        index.insert(self.username_index_key(&user.username), user.id.clone());
         self.save_username_index(&index)?;
 
         info!(user_id = %user.id, username = %user.username, "created user");
@@ -216,6 +366,7 @@ impl UserManager {
             is_admin: true,
             token_version: 0,
             refresh_nonce: String::new(),
+            sessions: vec![],
             totp_config: None,
             created_at: Some(now),
             updated_at: Some(now),
@@ -225,7 +376,7 @@ impl UserManager {
         // 保存
         self.persist_user(&user)?;
         let mut index = self.load_username_index();
-        index.insert(user.username.clone(), user.id.clone());
+        index.insert(self.username_index_key(&user.username), user.id.clone());
         self.save_username_index(&index)?;
 
         info!("created __devtoken__ virtual user");
@@ -239,8 +390,7 @@ impl UserManager {
         if !path.exists() {
             return Err(ServiceError::NotFound(format!("user: {}", id)));
         }
-        let data = std::fs::read(&path)?;
-        let mut user: User = serde_json::from_slice(&data)?;
+        let mut user = self.read_user_file(&path)?;
         let mut updated = false;
         if user.refresh_nonce.is_empty() {
             Self::ensure_refresh_nonce(&mut user);
@@ -257,15 +407,16 @@ impl UserManager {
     pub async fn find_by_username(&self, username: &str) -> Result<Option<User>> {
         self.ensure_dirs()?;
         let index = self.load_username_index();
+        let key = self.username_index_key(username);
 
         // 优先从索引查找
-        if let Some(id) = index.get(username) {
+        if let Some(id) = index.get(&key) {
             match self.get_user(id).await {
                 Ok(user) => return Ok(Some(user)),
                 Err(ServiceError::NotFound(_)) => {
                     // 索引指向的用户不存在，需要清理索引
                     let mut index = index;
-                    index.remove(username);
+                    index.remove(&key);
                     let _ = self.save_username_index(&index);
                 }
                 Err(e) => return Err(e),
@@ -286,15 +437,13 @@ impl UserManager {
             if path.extension().map(|e| e == "json").unwrap_or(false)
                 && path.file_stem().map(|s| s != "index").unwrap_or(true)
             {
-                if let Ok(data) = std::fs::read(&path) {
-                    if let Ok(user) = serde_json::from_slice::<User>(&data) {
-                        if user.username == username {
-                            // 更新索引
-                            let mut index = self.load_username_index();
-                            index.insert(username.to_string(), user.id.clone());
-                            let _ = self.save_username_index(&index);
-                            return Ok(Some(user));
-                        }
+                if let Ok(user) = self.read_user_file(&path) {
+                    if user.username == username {
+                        // 更新索引
+                        let mut index = self.load_username_index();
+                        index.insert(key, user.id.clone());
+                        let _ = self.save_username_index(&index);
+                        return Ok(Some(user));
                     }
                 }
             }
@@ -319,18 +468,16 @@ impl UserManager {
                     continue;
                 }
                 if path.extension().map(|e| e == "json").unwrap_or(false) {
-                    if let Ok(data) = std::fs::read(&path) {
-                        if let Ok(mut user) = serde_json::from_slice::<User>(&data) {
-                            // 过滤掉内部虚拟用户
-                            if user.id == "__devtoken__" {
-                                continue;
-                            }
-                            if user.refresh_nonce.is_empty() {
-                                Self::ensure_refresh_nonce(&mut user);
-                                let _ = self.persist_user(&user);
-                            }
-                            users.push(user);
+                    if let Ok(mut user) = self.read_user_file(&path) {
+                        // 过滤掉内部虚拟用户
+                        if user.id == "__devtoken__" {
+                            continue;
+                        }
+                        if user.refresh_nonce.is_empty() {
+                            Self::ensure_refresh_nonce(&mut user);
+                            let _ = self.persist_user(&user);
                         }
+                        users.push(user);
                     }
                 }
             }
@@ -354,7 +501,7 @@ impl UserManager {
         let mut bumped = false;
         // 更新密码
         if let Some(password) = req.password {
-            Self::validate_password_strength(&password)?;
+            self.validate_password_strength(&password)?;
             user.password_hash = hash_password(&password).await?;
             bumped = true;
         }
@@ -8,13 +8,18 @@ mod manager;
 mod models;
 mod password;
 mod permissions;
+mod secrets;
+mod session;
 mod totp;
 
 pub use manager::UserManager;
 pub use models::{
-    api_key_scopes, ApiKey, ApiKeySecretResponse, ApiKeySummary, AuthToken, CreateApiKeyRequest,
-    CreateApiKeyResponse, CreateUserRequest, DevTokenLoginRequest, Disable2FARequest,
-    Enable2FARequest, LoginRequest, RefreshRequest, Setup2FARequest, Setup2FAResponse, TokenClaims,
-    TokenType, TwoFactorVerification, UpdateApiKeyRequest, UpdateUserRequest, User, UserSummary,
-    API_KEY_RAW_PREFIX,
+    api_key_scopes, service_access_level, ApiKey, ApiKeySecretResponse, ApiKeySummary, AuthToken,
+    CreateApiKeyRequest, CreateApiKeyResponse, CreateUserApiKeyRequest, CreateUserRequest,
+    DevTokenLoginRequest, Disable2FARequest, Enable2FARequest, LoginRequest, RecoveryCodesResponse,
+    RefreshRequest, RegenerateRecoveryCodesRequest, ServiceAccessLevel, ServicePermission,
+    SessionMeta, SessionSummary, Setup2FARequest, Setup2FAResponse, TokenClaims, TokenType,
+    TwoFactorStatus, TwoFactorVerification, UpdateApiKeyRequest, UpdateUserRequest, User,
+    UserSession, UserSummary, API_KEY_RAW_PREFIX,
 };
+pub use password::PasswordPolicy;
@@ -14,18 +14,22 @@ pub struct User {
     pub username: String,
     /// bcrypt 哈希后的密码
     pub password_hash: String,
-    /// 默认服务列表可见的服务 ID（系统管理员控制权另由 is_admin 覆盖）
+    /// 默认服务列表可见的服务及权限级别（系统管理员控制权另由 is_admin 覆盖）
+    /// 兼容旧版纯字符串列表，读取为 Control
     #[serde(default)]
-    pub service_ids: Vec<String>,
+    pub service_ids: Vec<ServicePermission>,
     /// 是否为系统管理员（可控制全部服务；默认列表仍按 service_ids）
     #[serde(default)]
     pub is_admin: bool,
     /// Token 版本号（用于撤销旧 token）
     #[serde(default)]
     pub token_version: u64,
-    /// Refresh token 随机因子（用于单次刷新）
+    /// Refresh token 随机因子（用于单次刷新；仅兼容未绑定会话的旧 token）
     #[serde(default)]
     pub refresh_nonce: String,
+    /// 登录会话（每个会话持有独立的 refresh nonce）
+    #[serde(default)]
+    pub sessions: Vec<UserSession>,
     /// 2FA 配置（可选）
     pub totp_config: Option<TotpConfig>,
     /// 创建时间
@@ -34,6 +38,66 @@ pub struct User {
     pub updated_at: Option<DateTime<Utc>>,
 }
 
+/// 单个服务的权限级别
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ServiceAccessLevel {
+    /// 查看详情、状态与日志
+    Read,
+    /// 在 Read 基础上启停、修改与交互 attach
+    #[default]
+    Control,
+}
+
+/// 用户对某个服务的权限
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(from = "ServicePermissionRepr")]
+pub struct ServicePermission {
+    pub id: String,
+    pub level: ServiceAccessLevel,
+}
+
+impl ServicePermission {
+    pub fn new(id: impl Into<String>, level: ServiceAccessLevel) -> Self {
+        Self {
+            id: id.into(),
+            level,
+        }
+    }
+}
+
+/// 接受纯字符串（旧格式，视为 Control）或 `{id, level}` 对象
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ServicePermissionRepr {
+    Id(String),
+    Full {
+        id: String,
+        #[serde(default)]
+        level: ServiceAccessLevel,
+    },
+}
+
+impl From<ServicePermissionRepr> for ServicePermission {
+    fn from(repr: ServicePermissionRepr) -> Self {
+        match repr {
+            ServicePermissionRepr::Id(id) => Self::new(id, ServiceAccessLevel::Control),
+            ServicePermissionRepr::Full { id, level } => Self::new(id, level),
+        }
+    }
+}
+
+/// 在权限列表中查找服务的级别
+pub fn service_access_level(
+    permissions: &[ServicePermission],
+    service_id: &str,
+) -> Option<ServiceAccessLevel> {
+    permissions
+        .iter()
+        .find(|p| p.id == service_id)
+        .map(|p| p.level)
+}
+
 /// TOTP 2FA 配置
 #[skip_serializing_none]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,13 +113,75 @@ pub struct TotpConfig {
     pub enabled_at: Option<DateTime<Utc>>,
 }
 
+/// 登录会话
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserSession {
+    /// 会话 ID（由首个 refresh nonce 派生，轮换后保持不变）
+    pub id: String,
+    /// 当前有效的 refresh nonce（单次使用）
+    pub refresh_nonce: String,
+    /// 登录时间
+    pub issued_at: DateTime<Utc>,
+    /// 最近一次登录或刷新时间
+    pub last_used_at: DateTime<Utc>,
+    /// 会话过期时间（与 refresh token 一致）
+    pub expires_at: DateTime<Utc>,
+    /// 客户端 IP
+    #[serde(default)]
+    pub ip: Option<String>,
+    /// 客户端 User-Agent
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    /// 上一次刷新时轮换掉的 nonce，宽限期内再次出示不视为重放
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_nonce: Option<String>,
+    /// 上一次轮换 nonce 的时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotated_at: Option<DateTime<Utc>>,
+}
+
+/// 登录/刷新时记录的客户端信息
+#[derive(Debug, Clone, Default)]
+pub struct SessionMeta {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+/// 会话对外摘要（无 nonce）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub id: String,
+    pub issued_at: DateTime<Utc>,
+    pub last_used_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    /// 是否为发起请求的会话
+    #[serde(default)]
+    pub current: bool,
+}
+
+impl From<UserSession> for SessionSummary {
+    fn from(session: UserSession) -> Self {
+        Self {
+            id: session.id,
+            issued_at: session.issued_at,
+            last_used_at: session.last_used_at,
+            expires_at: session.expires_at,
+            ip: session.ip,
+            user_agent: session.user_agent,
+            current: false,
+        }
+    }
+}
+
 /// 创建用户请求
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateUserRequest {
     pub username: String,
     pub password: String,
     #[serde(default)]
-    pub service_ids: Vec<String>,
+    pub service_ids: Vec<ServicePermission>,
 }
 
 /// 更新用户请求
@@ -64,8 +190,8 @@ pub struct CreateUserRequest {
 pub struct UpdateUserRequest {
     /// 新密码（可选）
     pub password: Option<String>,
-    /// 新的服务 ID 列表（可选）
-    pub service_ids: Option<Vec<String>>,
+    /// 新的服务权限列表（可选）
+    pub service_ids: Option<Vec<ServicePermission>>,
     /// 是否设为系统管理员（可选）
     pub is_admin: Option<bool>,
 }
@@ -139,6 +265,9 @@ pub struct ApiKey {
     pub scopes: Vec<String>,
     /// 创建者用户 ID
     pub created_by: String,
+    /// 个人密钥的所属用户；设置后以该用户身份与权限鉴权，明文仅保存哈希
+    #[serde(default)]
+    pub owner_id: Option<String>,
     /// 创建时间
     pub created_at: DateTime<Utc>,
     /// 最近使用时间
@@ -167,6 +296,16 @@ pub struct CreateApiKeyRequest {
     pub expires_at: Option<DateTime<Utc>>,
 }
 
+/// 创建个人 API Key 请求（scopes 为空时授予全部 scope，实际权限不超过所属用户）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateUserApiKeyRequest {
+    pub name: String,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
 /// 更新 API Key 请求（不能改明文；改权限立即对后续请求生效）
 #[skip_serializing_none]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -184,6 +323,8 @@ pub struct ApiKeySummary {
     pub key_prefix: String,
     pub scopes: Vec<String>,
     pub created_by: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_id: Option<String>,
     pub created_at: DateTime<Utc>,
     pub last_used_at: Option<DateTime<Utc>>,
     pub revoked_at: Option<DateTime<Utc>>,
@@ -198,6 +339,7 @@ impl From<ApiKey> for ApiKeySummary {
             key_prefix: key.key_prefix,
             scopes: key.scopes,
             created_by: key.created_by,
+            owner_id: key.owner_id,
             created_at: key.created_at,
             last_used_at: key.last_used_at,
             revoked_at: key.revoked_at,
@@ -229,9 +371,9 @@ pub struct TokenClaims {
     pub aud: Option<String>,
     /// Token 类型
     pub token_type: TokenType,
-    /// 用户可访问的服务 ID 列表（仅 User token）
+    /// 用户可访问的服务及权限级别（仅 User token）
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub service_ids: Vec<String>,
+    pub service_ids: Vec<ServicePermission>,
     /// 是否为系统管理员（管理用户，不旁路服务访问）
     #[serde(default)]
     pub is_admin: bool,
@@ -244,6 +386,9 @@ pub struct TokenClaims {
     /// Web 代理会话绑定的单个服务 ID
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub service_id: Option<String>,
+    /// 登录会话 ID（会话被删除后对应 token 立即失效）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
     /// 过期时间戳 (Unix timestamp)
     pub exp: i64,
     /// 签发时间戳 (Unix timestamp)
@@ -294,11 +439,16 @@ pub struct RefreshRequest {
 pub struct UserSummary {
     pub id: String,
     pub username: String,
+    /// 有权限的服务 ID（不区分级别，兼容旧客户端）
     pub service_ids: Vec<String>,
+    /// 各服务的权限级别
+    pub service_permissions: Vec<ServicePermission>,
     /// 是否为系统管理员
     pub is_admin: bool,
     /// 是否启用了双因素认证
     pub totp_enabled: bool,
+    /// 剩余可用的恢复码数量（未启用 2FA 时为 0）
+    pub recovery_codes_remaining: usize,
     pub created_at: Option<DateTime<Utc>>,
 }
 
@@ -307,13 +457,20 @@ impl From<User> for UserSummary {
         Self {
             id: user.id,
             username: user.username,
-            service_ids: user.service_ids,
+            service_ids: user.service_ids.iter().map(|p| p.id.clone()).collect(),
+            service_permissions: user.service_ids,
             is_admin: user.is_admin,
             totp_enabled: user
                 .totp_config
                 .as_ref()
                 .map(|cfg| cfg.enabled)
                 .unwrap_or(false),
+            recovery_codes_remaining: user
+                .totp_config
+                .as_ref()
+                .filter(|cfg| cfg.enabled)
+                .map(|cfg| cfg.recovery_codes.len())
+                .unwrap_or(0),
             created_at: user.created_at,
         }
     }
@@ -359,3 +516,56 @@ pub enum TwoFactorVerification {
 /// 2FA 设置请求（无需参数，从 JWT 获取用户信息）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Setup2FARequest {}
+
+/// 重新生成恢复码请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegenerateRecoveryCodesRequest {
+    /// 验证方式
+    pub verification: TwoFactorVerification,
+}
+
+/// 重新生成恢复码响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryCodesResponse {
+    /// 新的恢复码（明文，仅此次返回）
+    pub recovery_codes: Vec<String>,
+}
+
+/// 2FA 状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwoFactorStatus {
+    /// 是否已启用
+    pub enabled: bool,
+    /// 启用时间
+    pub enabled_at: Option<DateTime<Utc>>,
+    /// 剩余可用的恢复码数量
+    pub recovery_codes_remaining: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn service_permissions_accept_legacy_strings() {
+        let perms: Vec<ServicePermission> =
+            serde_json::from_str(r#"["a", {"id": "b", "level": "read"}, {"id": "c"}]"#).unwrap();
+        assert_eq!(
+            perms,
+            vec![
+                ServicePermission::new("a", ServiceAccessLevel::Control),
+                ServicePermission::new("b", ServiceAccessLevel::Read),
+                ServicePermission::new("c", ServiceAccessLevel::Control),
+            ]
+        );
+        assert_eq!(
+            serde_json::to_string(&perms[1]).unwrap(),
+            r#"{"id":"b","level":"read"}"#
+        );
+        assert_eq!(
+            service_access_level(&perms, "b"),
+            Some(ServiceAccessLevel::Read)
+        );
+        assert_eq!(service_access_level(&perms, "z"), None);
+    }
+}
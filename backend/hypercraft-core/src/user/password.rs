@@ -5,14 +5,65 @@ use super::models::*;
 use super::UserManager;
 use crate::error::{Result, ServiceError};
 use chrono::Utc;
+use std::collections::HashSet;
 use tracing::instrument;
 
-impl UserManager {
-    /// 验证密码强度
-    pub(super) fn validate_password_strength(password: &str) -> Result<()> {
-        if password.len() < 8 {
+/// 密码强度策略
+///
+/// 默认：至少 8 位，包含大写、小写字母以及数字或符号
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    /// 最小长度（按字符计）
+    pub min_length: usize,
+    /// 必须包含大写字母
+    pub require_upper: bool,
+    /// 必须包含小写字母
+    pub require_lower: bool,
+    /// 必须包含数字
+    pub require_digit: bool,
+    /// 必须包含符号
+    pub require_symbol: bool,
+    /// 必须包含数字或符号之一
+    pub require_digit_or_symbol: bool,
+    /// 禁用的常见密码（小写存储，比较时忽略大小写）
+    pub denylist: HashSet<String>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            require_upper: true,
+            require_lower: true,
+            require_digit: false,
+            require_symbol: false,
+            require_digit_or_symbol: true,
+            denylist: HashSet::new(),
+        }
+    }
+}
+
+impl PasswordPolicy {
+    /// 追加禁用密码
+    pub fn with_denylist<I, S>(mut self, passwords: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.denylist.extend(
+            passwords
+                .into_iter()
+                .map(|p| p.as_ref().trim().to_lowercase())
+                .filter(|p| !p.is_empty()),
+        );
+        self
+    }
+
+    /// 校验密码，失败时返回具体不满足的规则
+    pub fn validate(&self, password: &str) -> Result<()> {
+        if password.chars().count() < self.min_length {
             return Err(ServiceError::PolicyViolation(
-                "密码长度至少为 8 个字符".into(),
+                format!("密码长度至少为 {} 个字符", self.min_length).into(),
             ));
         }
         let mut has_upper = false;
@@ -30,13 +81,31 @@ impl UserManager {
                 has_symbol = true;
             }
         }
-        if !(has_upper && has_lower && (has_digit || has_symbol)) {
-            return Err(ServiceError::PolicyViolation(
-                "密码必须包含大写字母、小写字母以及数字或符号".into(),
-            ));
+        let rules = [
+            (self.require_upper && !has_upper, "密码必须包含大写字母"),
+            (self.require_lower && !has_lower, "密码必须包含小写字母"),
+            (self.require_digit && !has_digit, "密码必须包含数字"),
+            (self.require_symbol && !has_symbol, "密码必须包含符号"),
+            (
+                self.require_digit_or_symbol && !(has_digit || has_symbol),
+                "密码必须包含数字或符号",
+            ),
+        ];
+        if let Some((_, msg)) = rules.iter().find(|(failed, _)| *failed) {
+            return Err(ServiceError::PolicyViolation((*msg).into()));
+        }
+        if self.denylist.contains(&password.to_lowercase()) {
+            return Err(ServiceError::PolicyViolation("密码过于常见，请更换".into()));
         }
         Ok(())
     }
+}
+
+impl UserManager {
+    /// 按当前策略验证密码强度
+    pub(super) fn validate_password_strength(&self, password: &str) -> Result<()> {
+        self.password_policy.validate(password)
+    }
 
     /// 修改用户密码，可选择强制修改（管理员/dev token）。
     #[instrument(skip(self, current_password, new_password))]
@@ -49,7 +118,7 @@ impl UserManager {
     ) -> Result<User> {
         let mut user = self.get_user(id).await?;
 
-        Self::validate_password_strength(new_password)?;
+        self.validate_password_strength(new_password)?;
         // 校验旧密码（非强制模式）
         if !force {
             let current = current_password
@@ -73,3 +142,42 @@ impl UserManager {
         Ok(user)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn violation(policy: &PasswordPolicy, password: &str) -> String {
+        match policy.validate(password) {
+            Err(ServiceError::PolicyViolation(msg)) => msg.to_string(),
+            other => panic!("expected policy violation, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn password_policy_names_failing_rule() {
+        let policy = PasswordPolicy::default();
+        assert!(policy.validate("Passw0rd").is_ok());
+        assert_eq!(violation(&policy, "Pw0"), "密码长度至少为 8 个字符");
+        assert_eq!(violation(&policy, "password1"), "密码必须包含大写字母");
+        assert_eq!(violation(&policy, "Password"), "密码必须包含数字或符号");
+
+        let strict = PasswordPolicy {
+            min_length: 12,
+            require_symbol: true,
+            ..PasswordPolicy::default()
+        }
+        .with_denylist(["Correct-Horse-1"]);
+        assert_eq!(violation(&strict, "Passw0rd"), "密码长度至少为 12 个字符");
+        assert_eq!(violation(&strict, "Passw0rdPassw0rd"), "密码必须包含符号");
+        assert_eq!(
+            violation(&strict, "correct-horse-1"),
+            "密码必须包含大写字母"
+        );
+        assert_eq!(
+            violation(&strict, "CORRECT-horse-1"),
+            "密码过于常见，请更换"
+        );
+        assert!(strict.validate("Correct-Horse-2").is_ok());
+    }
+}
@@ -7,15 +7,24 @@ use chrono::Utc;
 use tracing::instrument;
 
 impl UserManager {
-    /// 添加服务权限
+    /// 添加服务权限；已存在时更新为指定级别
     #[instrument(skip(self))]
-    pub async fn add_service_permission(&self, user_id: &str, service_id: &str) -> Result<User> {
+    pub async fn add_service_permission(
+        &self,
+        user_id: &str,
+        service_id: &str,
+        level: ServiceAccessLevel,
+    ) -> Result<User> {
         let mut user = self.get_user(user_id).await?;
-        if !user.service_ids.contains(&service_id.to_string()) {
-            user.service_ids.push(service_id.to_string());
-            user.updated_at = Some(Utc::now());
-            self.persist_user(&user)?;
+        match user.service_ids.iter_mut().find(|p| p.id == service_id) {
+            Some(existing) if existing.level == level => return Ok(user),
+            Some(existing) => existing.level = level,
+            None => user
+                .service_ids
+                .push(ServicePermission::new(service_id, level)),
         }
+        user.updated_at = Some(Utc::now());
+        self.persist_user(&user)?;
         Ok(user)
     }
 
@@ -23,15 +32,20 @@ impl UserManager {
     #[instrument(skip(self))]
     pub async fn remove_service_permission(&self, user_id: &str, service_id: &str) -> Result<User> {
         let mut user = self.get_user(user_id).await?;
-        user.service_ids.retain(|id| id != service_id);
+        user.service_ids.retain(|p| p.id != service_id);
         user.updated_at = Some(Utc::now());
         self.persist_user(&user)?;
         Ok(user)
     }
 
-    /// 检查用户是否有权限控制服务
+    /// 检查用户对服务是否至少拥有 `level` 级别的权限
     /// `__devtoken__`、系统管理员与 API Key 全量；普通用户按 service_ids。
-    pub fn has_service_permission(&self, claims: &TokenClaims, service_id: &str) -> bool {
+    pub fn has_service_permission(
+        &self,
+        claims: &TokenClaims,
+        service_id: &str,
+        level: ServiceAccessLevel,
+    ) -> bool {
         if claims.sub == "__devtoken__" || claims.is_admin {
             return true;
         }
@@ -39,7 +53,8 @@ impl UserManager {
             TokenType::Dev => false,
             // API Key 不再按 service_ids 白名单，能力仅由 scopes 约束
             TokenType::ApiKey => true,
-            TokenType::User => claims.service_ids.contains(&service_id.to_string()),
+            TokenType::User => service_access_level(&claims.service_ids, service_id)
+                .is_some_and(|granted| granted >= level),
             TokenType::Web => claims.service_id.as_deref() == Some(service_id),
             TokenType::Refresh => false, // refresh token 不能用于访问服务
         }
@@ -0,0 +1,109 @@
+//! 服务密钥库：加密后落盘 secrets.json，供 manifest env 中的 `${SECRET:key}` 引用
+
+use super::UserManager;
+use crate::error::{Result, ServiceError};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tracing::instrument;
+
+impl UserManager {
+    /// 密钥库文件路径
+    fn secrets_path(&self) -> PathBuf {
+        self.data_dir.join("secrets.json")
+    }
+
+    /// 读取密钥库（值为密文）
+    fn load_secrets(&self) -> Result<BTreeMap<String, String>> {
+        match std::fs::read(self.secrets_path()) {
+            Ok(data) => Ok(serde_json::from_slice(&data)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// 持久化密钥库
+    fn persist_secrets(&self, secrets: &BTreeMap<String, String>) -> Result<()> {
+        std::fs::create_dir_all(&self.data_dir)?;
+        let data = serde_json::to_vec_pretty(secrets)?;
+        std::fs::write(self.secrets_path(), data)?;
+        Ok(())
+    }
+
+    /// 校验密钥名：`[A-Za-z0-9_.-]`，长度 1..=128
+    fn validate_secret_key(key: &str) -> Result<()> {
+        let valid = !key.is_empty()
+            && key.len() <= 128
+            && key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+        if !valid {
+            return Err(ServiceError::PolicyViolation(
+                format!("invalid secret key: {}", key).into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// 列出所有密钥名（不返回值）
+    pub fn list_secret_keys(&self) -> Result<Vec<String>> {
+        Ok(self.load_secrets()?.into_keys().collect())
+    }
+
+    /// 密钥是否存在
+    pub fn secret_exists(&self, key: &str) -> Result<bool> {
+        Ok(self.load_secrets()?.contains_key(key))
+    }
+
+    /// 读取并解密密钥
+    pub fn get_secret(&self, key: &str) -> Result<Option<String>> {
+        match self.load_secrets()?.get(key) {
+            Some(encrypted) => self.decrypt_service_secret(encrypted).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// 写入（或覆盖）密钥
+    #[instrument(skip(self, value))]
+    pub fn set_secret(&self, key: &str, value: &str) -> Result<()> {
+        Self::validate_secret_key(key)?;
+        let mut secrets = self.load_secrets()?;
+        secrets.insert(key.to_string(), self.encrypt_service_secret(value)?);
+        self.persist_secrets(&secrets)
+    }
+
+    /// 删除密钥
+    #[instrument(skip(self))]
+    pub fn delete_secret(&self, key: &str) -> Result<()> {
+        let mut secrets = self.load_secrets()?;
+        if secrets.remove(key).is_none() {
+            return Err(ServiceError::NotFound(format!("secret:{}", key)));
+        }
+        self.persist_secrets(&secrets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn secrets_are_encrypted_at_rest() {
+        let dir = TempDir::new().unwrap();
+        let users = UserManager::new(dir.path(), "test-secret".into());
+
+        users.set_secret("db.password", "hunter2").unwrap();
+        assert_eq!(users.list_secret_keys().unwrap(), vec!["db.password"]);
+        assert_eq!(
+            users.get_secret("db.password").unwrap().as_deref(),
+            Some("hunter2")
+        );
+
+        let raw = std::fs::read_to_string(dir.path().join("secrets.json")).unwrap();
+        assert!(!raw.contains("hunter2"));
+
+        assert!(users.set_secret("../bad", "x").is_err());
+        users.delete_secret("db.password").unwrap();
+        assert!(!users.secret_exists("db.password").unwrap());
+    }
+}
@@ -0,0 +1,206 @@
+//! 登录会话：每次登录独立的 refresh nonce，支持查看与单独注销
+
+use super::models::*;
+use super::UserManager;
+use crate::error::{Result, ServiceError};
+use chrono::{Duration, Utc};
+use sha2::{Digest, Sha256};
+use tracing::{info, instrument};
+
+/// 单个用户最多保留的会话数，超出时淘汰最久未使用的
+const MAX_SESSIONS_PER_USER: usize = 20;
+
+/// User-Agent 最大保留长度
+const MAX_USER_AGENT_LEN: usize = 256;
+
+/// 刚被轮换的 refresh nonce 在此时长内再次出示不算重放（多个标签页同时刷新）
+pub(super) const REFRESH_REUSE_GRACE_SECS: i64 = 30;
+
+/// 由 refresh nonce 派生会话 ID（不可逆，便于对外展示）
+fn derive_session_id(nonce: &str) -> String {
+    let digest = Sha256::digest(nonce.as_bytes());
+    digest
+        .iter()
+        .take(8)
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// 截断过长的 User-Agent
+fn clamp_user_agent(user_agent: Option<&str>) -> Option<String> {
+    user_agent.map(|ua| ua.chars().take(MAX_USER_AGENT_LEN).collect())
+}
+
+impl UserManager {
+    /// 新建会话；顺带清理已过期会话并限制会话数量
+    pub(super) fn open_session(&self, user: &mut User, meta: &SessionMeta) -> UserSession {
+        let now = Utc::now();
+        user.sessions.retain(|s| s.expires_at > now);
+        if user.sessions.len() >= MAX_SESSIONS_PER_USER {
+            user.sessions.sort_by_key(|s| s.last_used_at);
+            let excess = user.sessions.len() + 1 - MAX_SESSIONS_PER_USER;
+            user.sessions.drain(..excess);
+        }
+
+        let nonce = uuid::Uuid::new_v4().to_string();
+        let session = UserSession {
+            id: derive_session_id(&nonce),
+            refresh_nonce: nonce,
+            issued_at: now,
+            last_used_at: now,
+            expires_at: now + Duration::seconds(self.refresh_token_ttl),
+            ip: meta.ip.clone(),
+            user_agent: clamp_user_agent(meta.user_agent.as_deref()),
+            previous_nonce: None,
+            rotated_at: None,
+        };
+        user.sessions.push(session.clone());
+        session
+    }
+
+    /// 刷新会话：轮换 refresh nonce 并续期，会话 ID 保持不变
+    pub(super) fn renew_session(
+        &self,
+        user: &mut User,
+        session_id: &str,
+        meta: &SessionMeta,
+    ) -> Result<UserSession> {
+        let now = Utc::now();
+        let session = user
+            .sessions
+            .iter_mut()
+            .find(|s| s.id == session_id)
+            .ok_or_else(|| ServiceError::Unauthorized("会话已失效".into()))?;
+        let previous =
+            std::mem::replace(&mut session.refresh_nonce, uuid::Uuid::new_v4().to_string());
+        session.previous_nonce = Some(previous);
+        session.rotated_at = Some(now);
+        session.last_used_at = now;
+        session.expires_at = now + Duration::seconds(self.refresh_token_ttl);
+        if meta.ip.is_some() {
+            session.ip = meta.ip.clone();
+        }
+        if meta.user_agent.is_some() {
+            session.user_agent = clamp_user_agent(meta.user_agent.as_deref());
+        }
+        Ok(session.clone())
+    }
+
+    /// 列出用户未过期的会话（按最近使用倒序）
+    #[instrument(skip(self))]
+    pub async fn list_sessions(&self, user_id: &str) -> Result<Vec<UserSession>> {
+        let user = self.get_user(user_id).await?;
+        let now = Utc::now();
+        let mut sessions: Vec<UserSession> = user
+            .sessions
+            .into_iter()
+            .filter(|s| s.expires_at > now)
+            .collect();
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.last_used_at));
+        Ok(sessions)
+    }
+
+    /// 注销单个会话，其 access/refresh token 立即失效
+    #[instrument(skip(self))]
+    pub async fn revoke_session(&self, user_id: &str, session_id: &str) -> Result<()> {
+        let mut user = self.get_user(user_id).await?;
+        let before = user.sessions.len();
+        user.sessions.retain(|s| s.id != session_id);
+        if user.sessions.len() == before {
+            return Err(ServiceError::NotFound(format!("session: {}", session_id)));
+        }
+        user.updated_at = Some(Utc::now());
+
+        self.persist_user(&user)?;
+
+        info!(user_id = %user.id, session_id = %session_id, "session revoked");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn sessions_are_independent() {
+        let dir = TempDir::new().unwrap();
+        let users = UserManager::new(dir.path(), "test-secret".into());
+        let user = users
+            .create_user(CreateUserRequest {
+                username: "erin".into(),
+                password: "Str0ng-password!".into(),
+                service_ids: vec![],
+            })
+            .await
+            .unwrap();
+        let laptop = SessionMeta {
+            ip: Some("10.0.0.1".into()),
+            user_agent: Some("laptop".into()),
+        };
+        let phone = SessionMeta {
+            ip: Some("10.0.0.2".into()),
+            user_agent: Some("phone".into()),
+        };
+
+        let first = users
+            .login("erin", "Str0ng-password!", None, &laptop)
+            .await
+            .unwrap();
+        let second = users
+            .login("erin", "Str0ng-password!", None, &phone)
+            .await
+            .unwrap();
+        let sessions = users.list_sessions(&user.id).await.unwrap();
+        assert_eq!(sessions.len(), 2);
+
+        // 第二次登录不影响第一个会话；刷新后会话 ID 不变
+        let refreshed = users
+            .refresh(&first.refresh_token, &SessionMeta::default())
+            .await
+            .unwrap();
+        let first_sid = users
+            .verify_token(&refreshed.access_token)
+            .await
+            .unwrap()
+            .session_id
+            .unwrap();
+        let sessions = users.list_sessions(&user.id).await.unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].id, first_sid);
+        assert_eq!(sessions[0].user_agent.as_deref(), Some("laptop"));
+
+        // 注销第二个会话，其 token 立即失效
+        let second_sid = users
+            .verify_token(&second.access_token)
+            .await
+            .unwrap()
+            .session_id
+            .unwrap();
+        users.revoke_session(&user.id, &second_sid).await.unwrap();
+        assert!(users.verify_token(&second.access_token).await.is_err());
+        assert!(users
+            .refresh(&second.refresh_token, &SessionMeta::default())
+            .await
+            .is_err());
+        users.verify_token(&refreshed.access_token).await.unwrap();
+        assert!(matches!(
+            users.revoke_session(&user.id, &second_sid).await,
+            Err(ServiceError::NotFound(_))
+        ));
+
+        // 旧 refresh token 单次使用：宽限期过后重放会撤销剩余的全部会话
+        let mut stored = users.get_user(&user.id).await.unwrap();
+        stored.sessions[0].rotated_at =
+            Some(Utc::now() - Duration::seconds(REFRESH_REUSE_GRACE_SECS + 1));
+        users.persist_user(&stored).unwrap();
+        assert!(matches!(
+            users
+                .refresh(&first.refresh_token, &SessionMeta::default())
+                .await,
+            Err(ServiceError::RefreshTokenReused(_))
+        ));
+        assert!(users.verify_token(&refreshed.access_token).await.is_err());
+    }
+}
@@ -10,10 +10,15 @@ use totp_rs::{Algorithm, Secret, TOTP};
 use tracing::{info, instrument, warn};
 
 use super::crypto::{hash_password, verify_password};
-use super::models::{Setup2FAResponse, TotpConfig, TwoFactorVerification, User};
+use super::models::{
+    Setup2FAResponse, TotpConfig, TwoFactorStatus, TwoFactorVerification, User,
+};
 use super::UserManager;
 use crate::error::{Result, ServiceError};
 
+/// 每次生成的恢复码数量
+const RECOVERY_CODE_COUNT: usize = 8;
+
 /// 生成恢复码（格式: ABCD-1234）
 fn generate_recovery_code() -> String {
     // 排除易混淆字符 (0, O, I, L, 1)
@@ -30,14 +35,25 @@ fn generate_recovery_code() -> String {
     format!("{}-{}", part1, part2)
 }
 
+/// 哈希恢复码（落盘用）
+async fn hash_recovery_codes(codes: &[String]) -> Result<Vec<String>> {
+    let mut hashes = Vec::with_capacity(codes.len());
+    for code in codes {
+        hashes.push(hash_password(code).await?);
+    }
+    Ok(hashes)
+}
+
 impl UserManager {
     /// 验证 TOTP code 或恢复码
+    ///
+    /// 恢复码一次性使用：匹配后会从 `user` 中移除并持久化
     #[instrument(skip(self, user, code))]
-    pub async fn verify_totp(&self, user: &User, code: &str) -> Result<bool> {
+    pub async fn verify_totp(&self, user: &mut User, code: &str) -> Result<bool> {
         let totp_cfg = user
             .totp_config
             .as_ref()
-            .ok_or_else(|| ServiceError::Other("双因素认证未配置".into()))?;
+            .ok_or_else(|| ServiceError::Conflict("双因素认证未配置".into()))?;
 
         if !totp_cfg.enabled {
             return Ok(false);
@@ -60,18 +76,30 @@ impl UserManager {
         }
 
         // 2. 尝试验证恢复码
-        for recovery_hash in &totp_cfg.recovery_codes {
+        let mut matched = None;
+        for (idx, recovery_hash) in totp_cfg.recovery_codes.iter().enumerate() {
             if verify_password(code, recovery_hash).await? {
-                warn!(
-                    user_id = %user.id,
-                    "recovery code used for 2FA verification"
-                );
-                // TODO: 恢复码一次性使用，需要从列表删除并持久化
-                return Ok(true);
+                matched = Some(idx);
+                break;
             }
         }
+        let Some(idx) = matched else {
+            return Ok(false);
+        };
+
+        // 恢复码一次性使用，从列表删除并持久化
+        if let Some(cfg) = user.totp_config.as_mut() {
+            cfg.recovery_codes.remove(idx);
+            warn!(
+                user_id = %user.id,
+                remaining = cfg.recovery_codes.len(),
+                "recovery code used for 2FA verification"
+            );
+        }
+        user.updated_at = Some(Utc::now());
+        self.persist_user(user)?;
 
-        Ok(false)
+        Ok(true)
     }
 
     /// 生成 TOTP secret 和恢复码（第一步：setup）
@@ -108,8 +136,10 @@ impl UserManager {
             secret_base32
         );
 
-        // 生成 8 个恢复码
-        let recovery_codes: Vec<String> = (0..8).map(|_| generate_recovery_code()).collect();
+        // 生成恢复码
+        let recovery_codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+            .map(|_| generate_recovery_code())
+            .collect();
 
         info!(user_id = %user_id, "2FA setup initiated");
 
@@ -158,10 +188,7 @@ impl UserManager {
         let encrypted_secret = self.encrypt_totp_secret(secret)?;
 
         // 哈希恢复码
-        let mut recovery_hashes = Vec::new();
-        for code in recovery_codes {
-            recovery_hashes.push(hash_password(code).await?);
-        }
+        let recovery_hashes = hash_recovery_codes(recovery_codes).await?;
 
         // 启用 2FA
         user.totp_config = Some(TotpConfig {
@@ -196,8 +223,8 @@ impl UserManager {
 
         // 验证 TOTP 或恢复码
         let verified = match verification {
-            TwoFactorVerification::Totp { code } => self.verify_totp(&user, code).await?,
-            TwoFactorVerification::Recovery { code } => self.verify_totp(&user, code).await?,
+            TwoFactorVerification::Totp { code } => self.verify_totp(&mut user, code).await?,
+            TwoFactorVerification::Recovery { code } => self.verify_totp(&mut user, code).await?,
         };
 
         if !verified {
@@ -214,4 +241,207 @@ impl UserManager {
         info!(user_id = %user.id, "2FA disabled successfully");
         Ok(())
     }
+
+    /// 重新生成恢复码（旧恢复码全部作废，新明文仅返回一次）
+    #[instrument(skip(self, verification))]
+    pub async fn regenerate_recovery_codes(
+        &self,
+        user_id: &str,
+        verification: &TwoFactorVerification,
+    ) -> Result<Vec<String>> {
+        // 如果是 DevToken 用户（sub="dev"），使用虚拟用户 __devtoken__
+        let actual_user_id = if user_id == "dev" {
+            "__devtoken__"
+        } else {
+            user_id
+        };
+
+        let mut user = self.get_user(actual_user_id).await?;
+
+        let code = match verification {
+            TwoFactorVerification::Totp { code } | TwoFactorVerification::Recovery { code } => {
+                code
+            }
+        };
+        if !self.verify_totp(&mut user, code).await? {
+            warn!(user_id = %user_id, "recovery code regeneration failed: invalid verification");
+            return Err(ServiceError::Unauthorized("验证代码无效".into()));
+        }
+
+        let recovery_codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+            .map(|_| generate_recovery_code())
+            .collect();
+        let recovery_hashes = hash_recovery_codes(&recovery_codes).await?;
+
+        if let Some(cfg) = user.totp_config.as_mut() {
+            cfg.recovery_codes = recovery_hashes;
+        }
+        user.updated_at = Some(Utc::now());
+
+        self.persist_user(&user)?;
+
+        info!(user_id = %user.id, "2FA recovery codes regenerated");
+        Ok(recovery_codes)
+    }
+
+    /// 查询 2FA 状态
+    pub async fn two_factor_status(&self, user_id: &str) -> Result<TwoFactorStatus> {
+        // 如果是 DevToken 用户（sub="dev"），使用虚拟用户 __devtoken__
+        let actual_user_id = if user_id == "dev" {
+            "__devtoken__"
+        } else {
+            user_id
+        };
+
+        let totp_cfg = match self.get_user(actual_user_id).await {
+            Ok(user) => user.totp_config.filter(|cfg| cfg.enabled),
+            // DevToken 虚拟用户尚未创建时视为未启用
+            Err(ServiceError::NotFound(_)) if actual_user_id == "__devtoken__" => None,
+            Err(e) => return Err(e),
+        };
+
+        Ok(match totp_cfg {
+            Some(cfg) => TwoFactorStatus {
+                enabled: true,
+                enabled_at: cfg.enabled_at,
+                recovery_codes_remaining: cfg.recovery_codes.len(),
+            },
+            None => TwoFactorStatus {
+                enabled: false,
+                enabled_at: None,
+                recovery_codes_remaining: 0,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user::models::{CreateUserRequest, SessionMeta};
+    use tempfile::TempDir;
+
+    /// 创建用户并启用 2FA，返回用户 ID 与 setup 结果
+    async fn user_with_2fa(users: &UserManager, username: &str) -> (String, Setup2FAResponse) {
+        let user = users
+            .create_user(CreateUserRequest {
+                username: username.into(),
+                password: "Str0ng-password!".into(),
+                service_ids: vec![],
+            })
+            .await
+            .unwrap();
+
+        let setup = users.setup_2fa(&user.id).await.unwrap();
+        let secret_bytes = Secret::Encoded(setup.secret.clone()).to_bytes().unwrap();
+        let code = TOTP::new(Algorithm::SHA1, 6, 1, 30, secret_bytes)
+            .unwrap()
+            .generate_current()
+            .unwrap();
+        users
+            .enable_2fa(&user.id, &code, &setup.secret, &setup.recovery_codes)
+            .await
+            .unwrap();
+        (user.id, setup)
+    }
+
+    #[tokio::test]
+    async fn verify_without_2fa_is_a_conflict() {
+        let dir = TempDir::new().unwrap();
+        let users = UserManager::new(dir.path(), "test-secret".into());
+        let mut user = users
+            .create_user(CreateUserRequest {
+                username: "bob".into(),
+                password: "Str0ng-password!".into(),
+                service_ids: vec![],
+            })
+            .await
+            .unwrap();
+        assert!(matches!(
+            users.verify_totp(&mut user, "123456").await,
+            Err(ServiceError::Conflict(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn recovery_codes_are_single_use() {
+        let dir = TempDir::new().unwrap();
+        let users = UserManager::new(dir.path(), "test-secret".into());
+        let (user_id, setup) = user_with_2fa(&users, "alice").await;
+
+        let recovery = &setup.recovery_codes[0];
+        let mut user = users.get_user(&user_id).await.unwrap();
+        assert!(users.verify_totp(&mut user, recovery).await.unwrap());
+
+        let mut user = users.get_user(&user_id).await.unwrap();
+        let remaining = user.totp_config.as_ref().unwrap().recovery_codes.len();
+        assert_eq!(remaining, setup.recovery_codes.len() - 1);
+        assert!(!users.verify_totp(&mut user, recovery).await.unwrap());
+        assert!(users
+            .verify_totp(&mut user, &setup.recovery_codes[1])
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn login_requires_code_when_2fa_enabled() {
+        let dir = TempDir::new().unwrap();
+        let users = UserManager::new(dir.path(), "test-secret".into());
+        let (_, setup) = user_with_2fa(&users, "carol").await;
+
+        let err = users
+            .login("carol", "Str0ng-password!", None, &SessionMeta::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::TwoFactorRequired(_)));
+
+        let err = users
+            .login(
+                "carol",
+                "Str0ng-password!",
+                Some("000000-bad"),
+                &SessionMeta::default(),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::Unauthorized(_)));
+
+        users
+            .login(
+                "carol",
+                "Str0ng-password!",
+                Some(&setup.recovery_codes[0]),
+                &SessionMeta::default(),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn regenerate_recovery_codes_replaces_old_set() {
+        let dir = TempDir::new().unwrap();
+        let users = UserManager::new(dir.path(), "test-secret".into());
+        let (user_id, setup) = user_with_2fa(&users, "bob").await;
+
+        let verification = TwoFactorVerification::Recovery {
+            code: setup.recovery_codes[0].clone(),
+        };
+        let fresh = users
+            .regenerate_recovery_codes(&user_id, &verification)
+            .await
+            .unwrap();
+        assert_eq!(fresh.len(), RECOVERY_CODE_COUNT);
+
+        let status = users.two_factor_status(&user_id).await.unwrap();
+        assert!(status.enabled);
+        assert_eq!(status.recovery_codes_remaining, RECOVERY_CODE_COUNT);
+
+        // 旧恢复码全部作废
+        let mut user = users.get_user(&user_id).await.unwrap();
+        assert!(!users
+            .verify_totp(&mut user, &setup.recovery_codes[1])
+            .await
+            .unwrap());
+        assert!(users.verify_totp(&mut user, &fresh[0]).await.unwrap());
+    }
 }
@@ -1,4 +1,5 @@
 use crate::{Result, ServiceError};
+use std::net::{IpAddr, Ipv4Addr};
 
 /// 解析并校验内嵌 Web 的本机上游地址。
 pub fn validate_web_upstream_url(upstream: &str) -> Result<url::Url> {
@@ -25,15 +26,159 @@ pub fn validate_web_upstream_url(upstream: &str) -> Result<url::Url> {
         .host_str()
         .ok_or_else(|| ServiceError::PolicyViolation("web upstream must include host".into()))?;
     if !is_local_web_upstream_host(host) {
-        return Err(ServiceError::PolicyViolation(format!(
-            "web upstream host not allowed: {}",
-            host
-        )));
+        return Err(ServiceError::PolicyViolation(
+            format!("web upstream host not allowed: {}", host).into(),
+        ));
+    }
+
+    Ok(url)
+}
+
+/// 解析并校验健康检查地址：与 Web 上游一致，仅允许本机 http/https。
+pub fn validate_health_check_url(raw: &str) -> Result<url::Url> {
+    let url = url::Url::parse(raw.trim())
+        .map_err(|_| ServiceError::PolicyViolation("health check url is not a valid URL".into()))?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(ServiceError::PolicyViolation(
+            "health check url only supports http/https".into(),
+        ));
+    }
+    if !url.username().is_empty() || url.password().is_some() {
+        return Err(ServiceError::PolicyViolation(
+            "health check url must not include credentials".into(),
+        ));
+    }
+    let host = url.host_str().unwrap_or_default();
+    if !is_local_web_upstream_host(host) {
+        return Err(ServiceError::PolicyViolation(
+            format!("health check host not allowed: {}", host).into(),
+        ));
+    }
+    Ok(url)
+}
+
+/// 解析并校验 Webhook 接收地址：允许外部主机，但只接受 http/https。
+pub fn validate_webhook_url(raw: &str) -> Result<url::Url> {
+    let url = url::Url::parse(raw.trim())
+        .map_err(|_| ServiceError::PolicyViolation("webhook url is not a valid URL".into()))?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(ServiceError::PolicyViolation(
+            "webhook url only supports http/https".into(),
+        ));
     }
+    if url.host_str().is_none() {
+        return Err(ServiceError::PolicyViolation(
+            "webhook url must include host".into(),
+        ));
+    }
+    Ok(url)
+}
 
+/// 服务级 Webhook 地址：在 [`validate_webhook_url`] 之外，拒绝 localhost 与指向
+/// 回环、内网、链路本地（含云元数据 169.254.169.254）等非公网地址的 IP 字面量。
+///
+/// 域名在投递时解析后再用 [`is_public_ip`] 检查；全局 `HC_WEBHOOK_URL` 由运维配置，不受此限制。
+pub fn validate_service_webhook_url(raw: &str) -> Result<url::Url> {
+    let url = validate_webhook_url(raw)?;
+    let blocked = match url.host() {
+        Some(url::Host::Ipv4(ip)) => !is_public_ip(IpAddr::V4(ip)),
+        Some(url::Host::Ipv6(ip)) => !is_public_ip(IpAddr::V6(ip)),
+        Some(url::Host::Domain(host)) => {
+            let host = host.trim_end_matches('.').to_ascii_lowercase();
+            host == "localhost" || host.ends_with(".localhost")
+        }
+        None => true,
+    };
+    if blocked {
+        return Err(ServiceError::PolicyViolation(
+            format!(
+                "webhook host not allowed: {}",
+                url.host_str().unwrap_or_default()
+            )
+            .into(),
+        ));
+    }
     Ok(url)
 }
 
+/// 是否为可从公网访问的地址（服务级 Webhook 只允许投递到这类地址）
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => {
+            // IPv4 映射（::ffff:a.b.c.d）与 NAT64（64:ff9b::/96）按内嵌的 IPv4 判断
+            if let Some(v4) = ip.to_ipv4_mapped() {
+                return is_public_ipv4(v4);
+            }
+            let seg = ip.segments();
+            if seg[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+                let [a, b] = seg[6].to_be_bytes();
+                let [c, d] = seg[7].to_be_bytes();
+                return is_public_ipv4(Ipv4Addr::new(a, b, c, d));
+            }
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_multicast()
+                // fc00::/7 唯一本地地址、fe80::/10 链路本地、2001:db8::/32 文档
+                || (seg[0] & 0xfe00) == 0xfc00
+                || (seg[0] & 0xffc0) == 0xfe80
+                || (seg[0] == 0x2001 && seg[1] == 0x0db8))
+        }
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        // 0.0.0.0/8、100.64.0.0/10（运营商 NAT）、192.0.0.0/24、198.18.0.0/15（基准测试）、240.0.0.0/4
+        || a == 0
+        || (a == 100 && (b & 0xc0) == 64)
+        || (a == 192 && b == 0 && c == 0)
+        || (a == 198 && (b & 0xfe) == 18)
+        || a >= 240)
+}
+
 fn is_local_web_upstream_host(host: &str) -> bool {
     host.eq_ignore_ascii_case("localhost") || matches!(host, "127.0.0.1" | "::1")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn service_webhooks_reject_internal_hosts() {
+        for url in [
+            "http://127.0.0.1/hook",
+            "http://localhost:8080/hook",
+            "http://api.localhost/hook",
+            "http://10.0.0.5/hook",
+            "http://192.168.1.1/hook",
+            "http://172.16.0.1/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://100.64.0.1/hook",
+            "http://0.0.0.0/hook",
+            "http://[::1]/hook",
+            "http://[fd00::1]/hook",
+            "http://[fe80::1]/hook",
+            "http://[::ffff:127.0.0.1]/hook",
+            "http://[64:ff9b::a9fe:a9fe]/hook",
+        ] {
+            assert!(validate_service_webhook_url(url).is_err(), "{url}");
+            assert!(validate_webhook_url(url).is_ok(), "{url}");
+        }
+        for url in [
+            "https://hooks.slack.com/services/x",
+            "http://93.184.216.34/hook",
+            "http://[2606:4700::1111]/hook",
+        ] {
+            assert!(validate_service_webhook_url(url).is_ok(), "{url}");
+        }
+    }
+}
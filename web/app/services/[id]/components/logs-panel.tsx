@@ -96,7 +96,7 @@ export function LogsPanel({ serviceId, serviceState, logPath }: LogsPanelProps)
     const startStream = async () => {
       try {
         const response = await fetch(
-          `${api.getBaseUrl()}/services/${serviceId}/logs?follow=true`,
+          `${api.getBaseUrl()}/services/${serviceId}/logs?follow=true&on_lag=resync`,
           {
             headers: {
               Accept: "text/event-stream",
@@ -125,8 +125,8 @@ export function useServiceForm({ mode, manifest, onReset }: UseServiceFormOption
         envVars: m.env
           ? Object.entries(m.env).map(([key, value]) => ({ key, value }))
           : [],
-        scheduleAction: m.schedule?.enabled ? m.schedule.action : "none",
-        scheduleCron: m.schedule?.cron || "",
+        scheduleAction: m.schedules?.[0]?.enabled ? m.schedules[0].action : "none",
+        scheduleCron: m.schedules?.[0]?.cron || "",
       });
     } else {
       setData({
@@ -164,9 +164,12 @@ export function useServiceForm({ mode, manifest, onReset }: UseServiceFormOption
       }
     }
 
+    // 表单只编辑第一条调度，其余条目原样保留
+    const otherSchedules = isEditMode ? (originalManifest?.schedules ?? []).slice(1) : [];
     const scheduleConfig: Schedule | undefined =
       data.scheduleAction !== "none"
         ? {
+            id: isEditMode ? originalManifest?.schedules?.[0]?.id : undefined,
             enabled: true,
             cron: data.scheduleCron.trim(),
             action: data.scheduleAction,
@@ -188,7 +191,7 @@ export function useServiceForm({ mode, manifest, onReset }: UseServiceFormOption
       log_path: data.logPath.trim() || undefined,
       pty_rows: data.ptyRows,
       terminal_tui: data.terminalTui,
-      schedule: scheduleConfig,
+      schedules: scheduleConfig ? [scheduleConfig, ...otherSchedules] : otherSchedules,
       web: data.webEnabled
         ? {
             enabled: true,
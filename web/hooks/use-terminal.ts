@@ -10,6 +10,8 @@ export interface UseTerminalOptions {
   autoConnect?: boolean;
   autoReconnect?: boolean;
   reconnectInterval?: number;
+  /** 服务尚未运行时，服务端等待其启动的秒数（最多 60），用于“先启动再 attach” */
+  waitSecs?: number;
   onData?: (data: string) => void;
   onStatusChange?: (status: TerminalStatus) => void;
 }
@@ -36,6 +38,7 @@ export function useTerminal({
   autoConnect = false,
   autoReconnect = true,
   reconnectInterval = 2000,
+  waitSecs,
   onData,
   onStatusChange,
 }: UseTerminalOptions): UseTerminalReturn {
@@ -59,8 +62,9 @@ export function useTerminal({
   const buildWsUrl = useCallback(() => {
     const baseUrl = api.getBaseUrl();
     const wsBase = baseUrl.replace("https://", "wss://").replace("http://", "ws://");
-    return `${wsBase}/services/${serviceId}/attach`;
-  }, [serviceId]);
+    const query = waitSecs ? `?wait_secs=${waitSecs}` : "";
+    return `${wsBase}/services/${serviceId}/attach${query}`;
+  }, [serviceId, waitSecs]);
 
   // 连接 WebSocket
   const connect = useCallback(() => {
@@ -105,6 +109,9 @@ export function useTerminal({
             } else if (msg.type === "error") {
               console.error("[Terminal Error]", msg.message);
               setError(msg.message);
+            } else if (msg.type === "stderr") {
+              // 单独捕获的 stderr（split_stderr），以红色显示
+              onData?.(`\x1b[31m${msg.data}\x1b[0m`);
             }
           } catch {
             // 非 JSON 文本，直接输出
@@ -120,7 +127,22 @@ export function useTerminal({
 
       ws.onclose = (event) => {
         wsRef.current = null;
-        if (event.wasClean) {
+        if (event.code >= 4000 && event.code < 5000) {
+          // 服务端拒绝 attach：关闭码为 4000 + HTTP 状态码，原因见 reason / 之前的 error 消息
+          setError(event.reason || `连接被拒绝 (code: ${event.code})`);
+          updateStatus("error");
+          // 权限不足或服务不存在时重连也无济于事
+          if (event.code === 4401 || event.code === 4403 || event.code === 4404) {
+            shouldReconnectRef.current = false;
+          }
+          if (autoReconnect && shouldReconnectRef.current) {
+            reconnectTimeoutRef.current = setTimeout(() => {
+              if (shouldReconnectRef.current && connectRef.current) {
+                connectRef.current();
+              }
+            }, reconnectInterval);
+          }
+        } else if (event.wasClean) {
           updateStatus("disconnected");
         } else {
           // 根据错误码给出更有用的信息
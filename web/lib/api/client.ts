@@ -13,10 +13,15 @@ import type {
   CreateApiKeyResponse,
   UpdateApiKeyRequest,
   ServiceSummary,
+  ServiceListQuery,
   ServiceManifest,
   ServiceDetail,
   ServiceStatus,
+  LifecycleStats,
   ServiceGroup,
+  ChildGroupAction,
+  GroupAction,
+  GroupActionResponse,
   CreateGroupRequest,
   UpdateGroupRequest,
   ReorderServicesRequest,
@@ -30,6 +35,14 @@ import type {
   Setup2FAResponse,
   Enable2FARequest,
   Disable2FARequest,
+  RegenerateRecoveryCodesRequest,
+  RecoveryCodesResponse,
+  TwoFactorStatus,
+  SessionSummary,
+  AuditEntry,
+  ConfigBundle,
+  ImportReport,
+  ManifestValidation,
   WebSessionResponse,
 } from "./types";
 
@@ -204,12 +217,15 @@ class ApiClient {
         error: data.error || data.code || "Unknown error",
         message: data.message || data.error || response.statusText,
         status: response.status,
+        details: data.details,
+        request_id: data.request_id ?? response.headers.get("x-request-id") ?? undefined,
       };
     } catch {
       return {
         error: "Request failed",
         message: response.statusText,
         status: response.status,
+        request_id: response.headers.get("x-request-id") ?? undefined,
       };
     }
   }
@@ -345,6 +361,19 @@ class ApiClient {
     });
   }
 
+  async regenerateRecoveryCodes(
+    req: RegenerateRecoveryCodesRequest
+  ): Promise<RecoveryCodesResponse> {
+    return this.request<RecoveryCodesResponse>("/auth/2fa/recovery-codes", {
+      method: "POST",
+      body: JSON.stringify(req),
+    });
+  }
+
+  async get2FAStatus(userId: string): Promise<TwoFactorStatus> {
+    return this.request<TwoFactorStatus>(`/users/${userId}/2fa/status`);
+  }
+
   // ==================== 用户 API ====================
 
   async listUsers(): Promise<UserSummary[]> {
@@ -375,6 +404,44 @@ class ApiClient {
     });
   }
 
+  async revokeUserTokens(id: string): Promise<void> {
+    return this.request<void>(`/users/${id}/revoke-tokens`, {
+      method: "POST",
+    });
+  }
+
+  /** 读取审计日志：since 之后最近 limit 条，按时间正序（仅管理员） */
+  async listAuditLog(query?: { limit?: number; since?: string }): Promise<AuditEntry[]> {
+    const params = new URLSearchParams();
+    if (query?.limit !== undefined) params.set("limit", String(query.limit));
+    if (query?.since) params.set("since", query.since);
+    const qs = params.toString();
+    return this.request<AuditEntry[]>(qs ? `/audit?${qs}` : "/audit");
+  }
+
+  /** 导出服务、调度与分组配置（仅管理员） */
+  async exportConfig(): Promise<ConfigBundle> {
+    return this.request<ConfigBundle>("/export");
+  }
+
+  /** 导入配置包，overwrite 为 true 时覆盖已存在的条目 */
+  async importConfig(bundle: ConfigBundle, overwrite = false): Promise<ImportReport> {
+    return this.request<ImportReport>(`/import?overwrite=${overwrite}`, {
+      method: "POST",
+      body: JSON.stringify(bundle),
+    });
+  }
+
+  async listUserSessions(id: string): Promise<SessionSummary[]> {
+    return this.request<SessionSummary[]>(`/users/${id}/sessions`);
+  }
+
+  async revokeUserSession(id: string, sessionId: string): Promise<void> {
+    return this.request<void>(`/users/${id}/sessions/${sessionId}`, {
+      method: "DELETE",
+    });
+  }
+
   async setUserServices(id: string, serviceIds: string[]): Promise<UserSummary> {
     return this.request<UserSummary>(`/users/${id}/services`, {
       method: "PUT",
@@ -444,8 +511,13 @@ class ApiClient {
   // ==================== 服务 API ====================
 
   /** 默认服务页列表（按当前用户 service_ids 过滤） */
-  async listServices(): Promise<ServiceSummary[]> {
-    return this.request<ServiceSummary[]>("/services");
+  async listServices(query?: ServiceListQuery): Promise<ServiceSummary[]> {
+    const params = new URLSearchParams();
+    for (const [key, value] of Object.entries(query ?? {})) {
+      if (value !== undefined && value !== null) params.set(key, String(value));
+    }
+    const qs = params.toString();
+    return this.request<ServiceSummary[]>(qs ? `/services?${qs}` : "/services");
   }
 
   /** 用户授权用全量服务候选（仅管理员） */
@@ -464,8 +536,17 @@ class ApiClient {
     });
   }
 
-  async updateService(id: string, manifest: ServiceManifest): Promise<void> {
-    return this.request<void>(`/services/${id}`, {
+  /** 干跑校验：与创建相同的检查，不写入任何内容 */
+  async validateService(manifest: ServiceManifest): Promise<ManifestValidation> {
+    return this.request<ManifestValidation>("/services/validate", {
+      method: "POST",
+      body: JSON.stringify(manifest),
+    });
+  }
+
+  // 不存在时创建（201），存在时更新（200），均返回保存后的 manifest
+  async updateService(id: string, manifest: ServiceManifest): Promise<ServiceManifest> {
+    return this.request<ServiceManifest>(`/services/${id}`, {
       method: "PUT",
       body: JSON.stringify(manifest),
     });
@@ -507,10 +588,28 @@ class ApiClient {
     });
   }
 
+  async sendServiceInput(id: string, data: string): Promise<void> {
+    return this.request<void>(`/services/${id}/input`, {
+      method: "POST",
+      body: JSON.stringify({ data }),
+    });
+  }
+
+  async signalService(id: string, signal: "INT" | "TERM" | "HUP" | "USR1"): Promise<void> {
+    return this.request<void>(`/services/${id}/signal`, {
+      method: "POST",
+      body: JSON.stringify({ signal }),
+    });
+  }
+
   async getServiceStatus(id: string): Promise<ServiceStatus> {
     return this.request<ServiceStatus>(`/services/${id}/status`);
   }
 
+  async getServiceStats(id: string): Promise<LifecycleStats> {
+    return this.request<LifecycleStats>(`/services/${id}/stats`);
+  }
+
   async createWebSession(id: string): Promise<WebSessionResponse> {
     return this.request<WebSessionResponse>(`/services/${id}/web/session`, {
       method: "POST",
@@ -539,15 +638,23 @@ class ApiClient {
 
   // ==================== 服务 Tags 和分组 ====================
 
-  async updateServiceTags(id: string, tags: string[]): Promise<void> {
-    return this.request<void>(`/services/${id}/tags`, {
+  async updateServiceTags(id: string, tags: string[]): Promise<ServiceManifest> {
+    return this.request<ServiceManifest>(`/services/${id}/tags`, {
       method: "PATCH",
       body: JSON.stringify({ tags }),
     });
   }
 
-  async updateServiceGroup(id: string, group: string | null): Promise<void> {
-    return this.request<void>(`/services/${id}/group`, {
+  // 批量增删多个服务的 tag（先删后加），返回更新后的服务摘要
+  async bulkTag(ids: string[], add: string[] = [], remove: string[] = []): Promise<ServiceSummary[]> {
+    return this.request<ServiceSummary[]>("/tags/bulk", {
+      method: "POST",
+      body: JSON.stringify({ ids, add, remove }),
+    });
+  }
+
+  async updateServiceGroup(id: string, group: string | null): Promise<ServiceManifest> {
+    return this.request<ServiceManifest>(`/services/${id}/group`, {
       method: "PATCH",
       body: JSON.stringify({ group }),
     });
@@ -580,16 +687,24 @@ class ApiClient {
     });
   }
 
-  async deleteGroup(id: string): Promise<void> {
-    return this.request<void>(`/groups/${id}`, {
+  async deleteGroup(id: string, children?: ChildGroupAction): Promise<void> {
+    const query = children ? `?children=${children}` : "";
+    return this.request<void>(`/groups/${id}${query}`, {
       method: "DELETE",
     });
   }
 
-  async reorderGroups(groupIds: string[]): Promise<ServiceGroup[]> {
+  async reorderGroups(groupIds: string[], parent?: string): Promise<ServiceGroup[]> {
     return this.request<ServiceGroup[]>("/groups/reorder", {
       method: "POST",
-      body: JSON.stringify({ group_ids: groupIds }),
+      body: JSON.stringify({ group_ids: groupIds, parent }),
+    });
+  }
+
+  // 按服务 order 依次启动/停止/重启分组（含子分组）内的服务，部分失败时返回 207
+  async groupAction(id: string, action: GroupAction): Promise<GroupActionResponse> {
+    return this.request<GroupActionResponse>(`/groups/${id}/${action}`, {
+      method: "POST",
     });
   }
 
@@ -600,6 +715,40 @@ class ApiClient {
     return this.request<{ id: string; data: string }>(`/services/${id}/logs?tail=${bytes}`);
   }
 
+  // 流式获取日志末尾的原始字节，每收到一块回调一次，便于边收边渲染大 tail
+  async streamServiceLogsRaw(
+    id: string,
+    bytes: number,
+    onChunk: (chunk: Uint8Array) => void
+  ): Promise<void> {
+    const url = `${getApiBaseUrl()}/services/${id}/logs?stream=true&tail=${bytes}`;
+    let response = await fetch(url, {
+      credentials: "include",
+    });
+
+    if (response.status === 401) {
+      await this.refreshSession(true);
+      response = await fetch(url, {
+        credentials: "include",
+      });
+    }
+
+    if (!response.ok) {
+      throw await this.parseError(response);
+    }
+    if (!response.body) {
+      onChunk(new Uint8Array(await response.arrayBuffer()));
+      return;
+    }
+
+    const reader = response.body.getReader();
+    for (;;) {
+      const { done, value } = await reader.read();
+      if (done) break;
+      if (value) onChunk(value);
+    }
+  }
+
   // 下载服务配置的日志文件
   async downloadServiceLogFile(id: string): Promise<void> {
     const url = `${getApiBaseUrl()}/services/${id}/log-file`;
@@ -12,6 +12,15 @@ export interface SystemStats {
   disk_usage: number;
 }
 
+export interface ProcessStats {
+  pid: number;
+  cpu_usage: number;
+  memory: number;
+  threads: number;
+  processes: number;
+  uptime_secs: number;
+}
+
 // ==================== 服务相关 ====================
 
 export type ServiceState = "running" | "stopped" | "unknown";
@@ -23,7 +32,51 @@ export interface ServiceSummary {
   tags: string[];
   group: string | null;
   order: number;
-}
+  /** RFC 3339；旧服务可能没有 */
+  created_at?: string | null;
+  /** 运行中时为本次启动时间，可直接算出运行时长 */
+  started_at?: string | null;
+}
+
+export type ServiceSort = "order" | "name" | "state";
+
+/** GET /services 的过滤、排序与分页参数，过滤后总数见 X-Total-Count 响应头 */
+export interface ServiceListQuery {
+  group?: string;
+  state?: ServiceState;
+  tag?: string;
+  q?: string;
+  sort?: ServiceSort;
+  offset?: number;
+  limit?: number;
+}
+
+export type StateChangeReason = "started" | "auto_restart" | "stopped" | "exited" | "crashed";
+
+/** GET /events 推送的生命周期事件 */
+export type ServiceEvent =
+  | {
+      type: "state_changed";
+      id: string;
+      state: ServiceState;
+      reason: StateChangeReason;
+      pid?: number;
+      exit_code?: number;
+      at: string;
+    }
+  | {
+      type: "health_changed";
+      id: string;
+      healthy: boolean;
+      at: string;
+    }
+  | {
+      type: "schedule_fired";
+      id: string;
+      schedule_id: string;
+      action: ScheduleAction;
+      at: string;
+    };
 
 export interface ServiceStatus {
   state: ServiceState;
@@ -31,24 +84,40 @@ export interface ServiceStatus {
   uptime_ms?: number;
 }
 
+/** 持久化的生命周期计数，跨进程与 daemon 重启保留 */
+export interface LifecycleStats {
+  total_starts: number;
+  /** 非主动停止且非零退出的次数 */
+  total_crashes: number;
+  auto_restarts: number;
+  last_start_at?: string;
+  last_crash_at?: string;
+  cumulative_uptime_ms: number;
+}
+
 // ==================== 定时调度相关 ====================
 
 export type ScheduleAction = "start" | "restart" | "stop";
 
 export interface Schedule {
+  id?: string;
   enabled: boolean;
   cron: string;
   action: ScheduleAction;
   timezone?: string;
+  catch_up?: boolean;
 }
 
-export interface ScheduleResponse {
-  schedule: Schedule | null;
+export interface ScheduleEntry extends Schedule {
   next_run: string | null;
 }
 
+export interface ScheduleResponse {
+  schedules: ScheduleEntry[];
+}
+
 export interface UpdateScheduleRequest {
-  schedule: Schedule | null;
+  schedules: Schedule[];
 }
 
 export interface ValidateCronRequest {
@@ -59,6 +128,8 @@ export interface ValidateCronResponse {
   valid: boolean;
   next_runs: string[];
   error?: string;
+  /** 无效时为 "InvalidSchedule"，与错误响应的 code 一致 */
+  code?: string;
 }
 
 export interface WebConfig {
@@ -69,25 +140,43 @@ export interface WebConfig {
 }
 
 export interface ServiceManifest {
+  /** 存储格式版本，由后端维护 */
+  schema_version?: number;
   id: string;
   name: string;
   command: string;
   args?: string[];
   env?: Record<string, string>;
+  env_file?: string | null;
   cwd?: string;
   auto_start?: boolean;
   auto_restart?: boolean;
   clear_log_on_start?: boolean;
   shutdown_command?: string;
+  /** kill 时先发送的信号（TERM / INT / HUP / USR1），超时后再 SIGKILL */
+  kill_signal?: string | null;
+  kill_timeout_secs?: number | null;
   run_as?: string;
+  memory_limit_mb?: number | null;
+  cpu_quota_percent?: number | null;
   created_at?: string;
   tags?: string[];
   group?: string | null;
   order?: number;
   log_path?: string;
+  /** 日志编码（如 utf-8、gb18030、shift_jis、big5、latin1），未设置时按 UTF-8 → GB18030 猜测 */
+  log_encoding?: string | null;
+  /** 启动宽限期（毫秒），期间退出视为启动失败，默认 300 */
+  start_grace_ms?: number;
   pty_rows?: number;
   terminal_tui?: boolean;
-  schedule?: Schedule;
+  /** 是否在 PTY 中运行，默认 true；false 时用管道收集输出，attach 只读 */
+  tty?: boolean;
+  /** 管道模式下单独捕获 stderr 到 stderr.log，默认 false（与 stdout 合并） */
+  split_stderr?: boolean;
+  /** 分离模式（仅 Unix）：API 重启后服务继续运行 */
+  detached?: boolean;
+  schedules?: Schedule[];
   web?: WebConfig;
 }
 
@@ -103,17 +192,36 @@ export interface ServiceGroup {
   name: string;
   order: number;
   color?: string | null;
+  parent?: string | null;
 }
 
+export type ChildGroupAction = "reparent" | "orphan";
+
 export interface CreateGroupRequest {
   id: string;
   name: string;
   color?: string;
+  parent?: string;
 }
 
 export interface UpdateGroupRequest {
   name?: string;
   color?: string | null;
+  /** null 移到顶层 */
+  parent?: string | null;
+}
+
+export type GroupAction = "start" | "stop" | "restart";
+
+export interface GroupActionResponse {
+  group: string;
+  action: GroupAction;
+  results: Array<{
+    id: string;
+    ok: boolean;
+    status?: ServiceStatus;
+    error?: string;
+  }>;
 }
 
 export interface ReorderServicesRequest {
@@ -126,15 +234,44 @@ export interface ReorderServicesRequest {
 
 // ==================== 用户相关 ====================
 
+export type ServiceAccessLevel = "read" | "control";
+
+export interface ServicePermission {
+  id: string;
+  level: ServiceAccessLevel;
+}
+
 export interface UserSummary {
   id: string;
   username: string;
   service_ids: string[];
+  service_permissions?: ServicePermission[];
   is_admin: boolean;
   totp_enabled: boolean;
+  recovery_codes_remaining?: number;
   created_at?: string;
 }
 
+export interface SessionSummary {
+  id: string;
+  issued_at: string;
+  last_used_at: string;
+  expires_at: string;
+  ip?: string | null;
+  user_agent?: string | null;
+  current: boolean;
+}
+
+/** 管理操作审计记录；result 成功为 "ok"，失败为错误信息 */
+export interface AuditEntry {
+  ts: string;
+  actor_sub: string;
+  action: string;
+  target: string;
+  ip: string;
+  result: string;
+}
+
 export interface CreateUserRequest {
   username: string;
   password: string;
@@ -162,6 +299,7 @@ export interface ApiKeySummary {
   key_prefix: string;
   scopes: string[];
   created_by: string;
+  owner_id?: string | null;
   created_at: string;
   last_used_at?: string | null;
   revoked_at?: string | null;
@@ -253,6 +391,20 @@ export type TwoFactorVerification =
   | { type: "totp"; code: string }
   | { type: "recovery"; code: string };
 
+export interface RegenerateRecoveryCodesRequest {
+  verification: TwoFactorVerification;
+}
+
+export interface RecoveryCodesResponse {
+  recovery_codes: string[];
+}
+
+export interface TwoFactorStatus {
+  enabled: boolean;
+  enabled_at?: string;
+  recovery_codes_remaining: number;
+}
+
 // ==================== 日志相关 ====================
 
 export interface LogsResponse {
@@ -266,9 +418,54 @@ export interface WebSessionResponse {
 
 // ==================== API 响应 ====================
 
+export type PolicyErrorDetails =
+  | { kind: "command"; command: string; allowed: string[] }
+  | { kind: "cwd" | "env_file"; path: string; allowed_roots: string[] }
+  | { kind: "cwd" | "command"; path: string; problem: string };
+
 export interface ApiError {
   code?: string;
   error: string;
   message: string;
   status?: number;
+  /** 策略拒绝（CommandNotAllowed / PathNotAllowed / InvalidPath）时附带允许项或具体问题 */
+  details?: PolicyErrorDetails;
+  /** 服务端分配的请求 ID，与 API 日志中的 request_id 对应 */
+  request_id?: string;
+}
+
+// ==================== 配置导入导出 ====================
+
+/** GET /export 返回、POST /import 接收的配置包（不含用户与密钥） */
+export interface ConfigBundle {
+  version: number;
+  exported_at?: string;
+  groups: ServiceGroup[];
+  services: ServiceManifest[];
+}
+
+export type ImportOutcome = "created" | "updated" | "skipped" | "failed";
+
+export interface ImportItemResult {
+  id: string;
+  outcome: ImportOutcome;
+  error?: string;
+}
+
+export interface ImportReport {
+  groups: ImportItemResult[];
+  services: ImportItemResult[];
+}
+
+// ==================== Manifest 干跑校验 ====================
+
+export interface ValidationProblem {
+  /** 出问题的字段，如 id、command、schedules[0] */
+  field: string;
+  message: string;
+}
+
+export interface ManifestValidation {
+  valid: boolean;
+  problems?: ValidationProblem[];
 }
@@ -80,10 +80,10 @@ export function AuthProvider({ children }: { children: ReactNode }) {
   const router = useRouter();
   const pathname = usePathname();
 
-  // 检查后端连接
+  // 检查后端连接（存活探针：子系统降级时后端仍可访问）
   const checkConnection = useCallback(async (): Promise<boolean> => {
     try {
-      const response = await fetch(`${api.getBaseUrl()}/health`, {
+      const response = await fetch(`${api.getBaseUrl()}/health/live`, {
         method: "GET",
         signal: AbortSignal.timeout(5000),
         credentials: "include",